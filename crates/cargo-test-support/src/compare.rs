@@ -206,6 +206,7 @@ fn substitute_macros(input: &str) -> String {
         ("[DIRTY]", "       Dirty"),
         ("[LOCKING]", "     Locking"),
         ("[UPDATING]", "    Updating"),
+        ("[UPGRADING]", "   Upgrading"),
         ("[ADDING]", "      Adding"),
         ("[REMOVING]", "    Removing"),
         ("[REMOVED]", "     Removed"),
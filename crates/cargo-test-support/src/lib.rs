@@ -2,11 +2,61 @@
 //!
 //! See <https://rust-lang.github.io/cargo/contrib/> for a guide on writing tests.
 //!
-//! WARNING: You might not want to use this outside of Cargo.
+//! This is the framework of Cargo's own testsuite, which external subcommands
+//! can also use to test themselves against fake workspaces and registries:
 //!
-//! * This is designed for testing Cargo itself. Use at your own risk.
-//! * No guarantee on any stability across versions.
-//! * No feature request would be accepted unless proved useful for testing Cargo.
+//! ```rust,no_run
+//! use cargo_test_support::registry::Package;
+//! use cargo_test_support::{basic_manifest, project};
+//!
+//! #[cargo_test_support::cargo_test]
+//! fn lists_dependencies() {
+//!     Package::new("bar", "1.0.0").publish();
+//!     let p = project()
+//!         .file(
+//!             "Cargo.toml",
+//!             r#"
+//!                 [package]
+//!                 name = "foo"
+//!                 version = "0.1.0"
+//!                 edition = "2021"
+//!
+//!                 [dependencies]
+//!                 bar = "1.0"
+//!             "#,
+//!         )
+//!         .file("src/lib.rs", "")
+//!         .build();
+//!
+//!     p.cargo("my-subcommand --list")
+//!         .add_bins_to_path()
+//!         .with_stdout("bar 1.0.0\n")
+//!         .run();
+//! }
+//! ```
+//!
+//! Each test runs in its own directory, with its own `CARGO_HOME`, see
+//! [`paths::root`]. Packages published with [`registry::Package`] are
+//! available from a fake crates.io. [`Project::cargo`] runs the `cargo`
+//! binary built along with the tests when testing Cargo itself, and otherwise
+//! the one running the tests, see [`cargo_exe`]. `tests/external_subcommand.rs`
+//! runs this example.
+//!
+//! ## Stability
+//!
+//! Only the items used by the example above follow semver:
+//!
+//! - the [`cargo_test`] attribute and [`cargo_exe`],
+//! - [`project`], [`ProjectBuilder::file`] and [`ProjectBuilder::build`],
+//! - [`Project::cargo`] and [`Project::root`],
+//! - [`Execs::add_bins_to_path`], [`Execs::with_status`],
+//!   [`Execs::with_stdout`], [`Execs::with_stderr`] and [`Execs::run`],
+//! - [`registry::Package::new`] and [`registry::Package::publish`],
+//! - [`paths::root`].
+//!
+//! Everything else, including the other items of those modules and types, is
+//! designed for testing Cargo itself and may change in any release, and
+//! feature requests are only accepted for testing Cargo.
 
 #![allow(clippy::disallowed_methods)]
 #![allow(clippy::print_stderr)]
@@ -507,8 +557,36 @@ pub fn main_file(println: &str, deps: &[&str]) -> String {
     buf
 }
 
+/// The `cargo` binary run by the tests.
+///
+/// This is the one built along with the tests when testing Cargo itself.
+/// Otherwise, like when testing an external subcommand, it is the `cargo`
+/// running the tests, from the `CARGO` environment variable.
 pub fn cargo_exe() -> PathBuf {
-    snapbox::cmd::cargo_bin("cargo")
+    built_or_running_cargo(snapbox::cmd::cargo_bin("cargo"), env::var_os("CARGO"))
+}
+
+fn built_or_running_cargo(built: PathBuf, running: Option<std::ffi::OsString>) -> PathBuf {
+    if built.exists() {
+        return built;
+    }
+    running.map_or(built, PathBuf::from)
+}
+
+#[test]
+fn cargo_exe_falls_back_to_running_cargo() {
+    let missing = PathBuf::from("/nonexistent/target/debug/cargo");
+    let running = PathBuf::from("/toolchain/bin/cargo");
+    assert_eq!(
+        built_or_running_cargo(missing.clone(), Some(running.clone().into())),
+        running
+    );
+    assert_eq!(built_or_running_cargo(missing.clone(), None), missing);
+    let built = env::current_exe().unwrap();
+    assert_eq!(
+        built_or_running_cargo(built.clone(), Some(running.into())),
+        built
+    );
 }
 
 /// This is the raw output from the process.
@@ -807,6 +885,19 @@ impl Execs {
         self
     }
 
+    /// Adds the directory of the binaries built along with the tests to the
+    /// `PATH`, so `cargo` runs an external subcommand being tested.
+    pub fn add_bins_to_path(&mut self) -> &mut Self {
+        if let Some(ref mut p) = self.process_builder {
+            let bins = snapbox::cmd::cargo_bin("cargo");
+            let bins = bins.parent().unwrap().to_path_buf();
+            let path = p.get_env("PATH").unwrap_or_default();
+            let path = env::join_paths(std::iter::once(bins).chain(env::split_paths(&path)));
+            p.env("PATH", t!(path));
+        }
+        self
+    }
+
     pub fn exec_with_output(&mut self) -> Result<Output> {
         self.ran = true;
         // TODO avoid unwrap
@@ -1477,6 +1568,19 @@ fn retry_fails() {
     retry(2, || None::<()>);
 }
 
+#[test]
+fn add_bins_to_path_prepends_bins_dir() {
+    let mut p = ProcessBuilder::new("cargo");
+    p.env("PATH", "/usr/bin");
+    let mut execs = execs().with_process_builder(p);
+    execs.add_bins_to_path();
+    execs.ran = true;
+    let path = execs.process_builder.as_ref().unwrap().get_env("PATH");
+    let path: Vec<_> = env::split_paths(&path.unwrap()).collect();
+    let bins = snapbox::cmd::cargo_bin("cargo");
+    assert_eq!(path, [bins.parent().unwrap(), Path::new("/usr/bin")]);
+}
+
 /// Helper that waits for a thread to finish, up to `n` tenths of a second.
 pub fn thread_wait_timeout<T>(n: u32, thread: JoinHandle<T>) -> T {
     retry(n, || thread.is_finished().then_some(()));
@@ -169,6 +169,8 @@ pub(crate) fn create_index_line(
     yanked: bool,
     links: Option<String>,
     rust_version: Option<&str>,
+    deprecated: Option<&str>,
+    pubtime: Option<&str>,
     v: Option<u32>,
 ) -> String {
     // This emulates what crates.io does to retain backwards compatibility.
@@ -192,6 +194,12 @@ pub(crate) fn create_index_line(
     if let Some(rust_version) = rust_version {
         json["rust_version"] = serde_json::json!(rust_version);
     }
+    if let Some(deprecated) = deprecated {
+        json["deprecated"] = serde_json::json!(deprecated);
+    }
+    if let Some(pubtime) = pubtime {
+        json["pubtime"] = serde_json::json!(pubtime);
+    }
 
     json.to_string()
 }
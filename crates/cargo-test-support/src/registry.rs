@@ -538,6 +538,8 @@ pub struct Package {
     proc_macro: bool,
     links: Option<String>,
     rust_version: Option<String>,
+    deprecated: Option<String>,
+    pubtime: Option<String>,
     cargo_features: Vec<String>,
     v: Option<u32>,
 }
@@ -1172,11 +1174,19 @@ fn save_new_crate(
                 "artifact": dep.artifact,
                 "bindep_target": dep.bindep_target,
                 "lib": dep.lib,
+                "features_profile": dep.features_profile,
             })
         })
         .collect::<Vec<_>>();
+    // This emulates what crates.io does for `features2`: entries using feature
+    // profiles get a new schema version, so older versions of cargo skip them.
+    let uses_feature_profiles = !new_crate.feature_profiles.is_empty()
+        || new_crate
+            .deps
+            .iter()
+            .any(|dep| dep.features_profile.is_some());
 
-    let line = create_index_line(
+    let mut line = create_index_line(
         serde_json::json!(new_crate.name),
         &new_crate.vers,
         deps,
@@ -1186,7 +1196,14 @@ fn save_new_crate(
         new_crate.links,
         new_crate.rust_version.as_deref(),
         None,
+        None,
+        uses_feature_profiles.then_some(4),
     );
+    if !new_crate.feature_profiles.is_empty() {
+        let mut json: serde_json::Value = t!(serde_json::from_str(&line));
+        json["feature_profiles"] = serde_json::json!(new_crate.feature_profiles);
+        line = json.to_string();
+    }
 
     write_to_index(registry_path, &new_crate.name, line, false);
 }
@@ -1212,6 +1229,8 @@ impl Package {
             proc_macro: false,
             links: None,
             rust_version: None,
+            deprecated: None,
+            pubtime: None,
             cargo_features: Vec::new(),
             v: None,
         }
@@ -1361,6 +1380,19 @@ impl Package {
         self
     }
 
+    /// Marks the package as deprecated in the index with the given reason.
+    pub fn deprecated(&mut self, reason: &str) -> &mut Package {
+        self.deprecated = Some(reason.into());
+        self
+    }
+
+    /// Records in the index when the package was published, as an RFC 3339
+    /// timestamp.
+    pub fn pubtime(&mut self, pubtime: &str) -> &mut Package {
+        self.pubtime = Some(pubtime.into());
+        self
+    }
+
     /// Causes the JSON line emitted in the index to be invalid, presumably
     /// causing Cargo to skip over this version.
     pub fn invalid_json(&mut self, invalid: bool) -> &mut Package {
@@ -1451,6 +1483,8 @@ impl Package {
             self.yanked,
             self.links.clone(),
             self.rust_version.as_deref(),
+            self.deprecated.as_deref(),
+            self.pubtime.as_deref(),
             self.v,
         );
 
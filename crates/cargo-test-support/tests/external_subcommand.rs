@@ -0,0 +1,37 @@
+//! Runs the example of the crate documentation, with an alias standing in for
+//! the external subcommand.
+
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+#[cargo_test_support::cargo_test]
+fn lists_dependencies() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2021"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [alias]
+                my-subcommand = "tree --prefix none --depth 1"
+            "#,
+        )
+        .build();
+
+    p.cargo("my-subcommand")
+        .add_bins_to_path()
+        .with_stdout("foo v0.1.0 ([..])\nbar v1.0.0\n")
+        .run();
+}
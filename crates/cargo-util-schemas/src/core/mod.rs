@@ -4,6 +4,7 @@ mod source_kind;
 
 pub use package_id_spec::PackageIdSpec;
 pub use package_id_spec::PackageIdSpecError;
+pub use package_id_spec::PackageIdSpecFields;
 pub use partial_version::PartialVersion;
 pub use partial_version::PartialVersionError;
 pub use source_kind::GitReference;
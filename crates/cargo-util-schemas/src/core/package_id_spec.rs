@@ -9,6 +9,7 @@ use crate::core::PartialVersion;
 use crate::core::PartialVersionError;
 use crate::core::SourceKind;
 use crate::manifest::PackageName;
+use crate::restricted_names::is_glob_pattern;
 use crate::restricted_names::NameValidationError;
 
 type Result<T> = std::result::Result<T, PackageIdSpecError>;
@@ -109,6 +110,37 @@ impl PackageIdSpec {
         })
     }
 
+    /// Parses a spec string like [`PackageIdSpec::parse`], additionally
+    /// allowing the name to be a glob pattern, e.g. `tokio-*` or `my-org-*@2`.
+    ///
+    /// The pattern itself is not validated; see [`PackageIdSpec::is_glob`].
+    ///
+    /// ```
+    /// use cargo_util_schemas::core::PackageIdSpec;
+    ///
+    /// let spec = PackageIdSpec::parse_glob("my-org-*@2").unwrap();
+    /// assert!(spec.is_glob());
+    /// assert_eq!(spec.name(), "my-org-*");
+    /// assert!(!PackageIdSpec::parse_glob("foo@1").unwrap().is_glob());
+    /// ```
+    pub fn parse_glob(spec: &str) -> Result<PackageIdSpec> {
+        let mut parts = spec.splitn(2, [':', '@']);
+        let name = parts.next().unwrap();
+        if spec.contains("://") || !is_glob_pattern(name) {
+            return PackageIdSpec::parse(spec);
+        }
+        let version = match parts.next() {
+            Some(version) => Some(version.parse::<PartialVersion>()?),
+            None => None,
+        };
+        Ok(PackageIdSpec {
+            name: String::from(name),
+            version,
+            url: None,
+            kind: None,
+        })
+    }
+
     /// Tries to convert a valid `Url` to a `PackageIdSpec`.
     fn from_url(mut url: Url) -> Result<PackageIdSpec> {
         let mut kind = None;
@@ -191,6 +223,11 @@ impl PackageIdSpec {
         self.name.as_str()
     }
 
+    /// Whether the name is a glob pattern, as accepted by [`PackageIdSpec::parse_glob`]
+    pub fn is_glob(&self) -> bool {
+        is_glob_pattern(&self.name)
+    }
+
     /// Full `semver::Version`, if present
     pub fn version(&self) -> Option<Version> {
         self.version.as_ref().and_then(|v| v.to_version())
@@ -215,6 +252,12 @@ impl PackageIdSpec {
     pub fn set_kind(&mut self, kind: SourceKind) {
         self.kind = Some(kind);
     }
+
+    /// Returns a view of this spec that serializes as its individual fields,
+    /// rather than the single string form used by [`ser::Serialize`].
+    pub fn fields(&self) -> PackageIdSpecFields<'_> {
+        PackageIdSpecFields(self)
+    }
 }
 
 fn strip_url_protocol(url: &Url) -> Url {
@@ -263,6 +306,59 @@ impl ser::Serialize for PackageIdSpec {
     }
 }
 
+/// Structured serialization of a [`PackageIdSpec`], see [`PackageIdSpec::fields`].
+///
+/// ```
+/// use cargo_util_schemas::core::PackageIdSpec;
+///
+/// let spec = PackageIdSpec::parse("git+https://github.com/rust-lang/cargo?tag=0.52.0#0.52.0")
+///     .unwrap();
+/// let fields = spec.fields();
+/// assert_eq!(fields.source_kind(), Some("git"));
+/// assert_eq!(fields.git_ref().as_deref(), Some("tag=0.52.0"));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PackageIdSpecFields<'a>(&'a PackageIdSpec);
+
+impl<'a> PackageIdSpecFields<'a> {
+    /// The kind of source, e.g. `registry`, `sparse` or `git`.
+    pub fn source_kind(&self) -> Option<&'static str> {
+        self.0.kind.as_ref().map(|kind| match kind {
+            SourceKind::Git(_) => "git",
+            SourceKind::Path => "path",
+            SourceKind::Registry => "registry",
+            SourceKind::SparseRegistry => "sparse",
+            SourceKind::LocalRegistry => "local-registry",
+            SourceKind::Directory => "directory",
+        })
+    }
+
+    /// The git reference in its query string form, e.g. `branch=main`.
+    pub fn git_ref(&self) -> Option<String> {
+        match self.0.kind.as_ref() {
+            Some(SourceKind::Git(git_ref)) => git_ref.pretty_ref(false).map(|r| r.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ser::Serialize for PackageIdSpecFields<'a> {
+    fn serialize<S>(&self, s: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        use ser::SerializeStruct as _;
+
+        let mut state = s.serialize_struct("PackageIdSpec", 5)?;
+        state.serialize_field("name", &self.0.name)?;
+        state.serialize_field("version", &self.0.version.as_ref().map(|v| v.to_string()))?;
+        state.serialize_field("url", &self.0.url.as_ref().map(|u| u.as_str()))?;
+        state.serialize_field("source_kind", &self.source_kind())?;
+        state.serialize_field("git_ref", &self.git_ref())?;
+        state.end()
+    }
+}
+
 impl<'de> de::Deserialize<'de> for PackageIdSpec {
     fn deserialize<D>(d: D) -> std::result::Result<PackageIdSpec, D::Error>
     where
@@ -569,6 +665,26 @@ mod tests {
             },
             "git+ssh://git@github.com/rust-lang/regex.git?branch=dev#regex@1.4.3",
         );
+        ok(
+            "git+ssh://git@github.com/rust-lang/regex.git?tag=v1.4.3#regex@1.4.3",
+            PackageIdSpec {
+                name: String::from("regex"),
+                version: Some("1.4.3".parse().unwrap()),
+                url: Some(Url::parse("ssh://git@github.com/rust-lang/regex.git").unwrap()),
+                kind: Some(SourceKind::Git(GitReference::Tag("v1.4.3".to_owned()))),
+            },
+            "git+ssh://git@github.com/rust-lang/regex.git?tag=v1.4.3#regex@1.4.3",
+        );
+        ok(
+            "git+ssh://git@github.com/rust-lang/regex.git?rev=a1b2c3d4#regex@1.4.3",
+            PackageIdSpec {
+                name: String::from("regex"),
+                version: Some("1.4.3".parse().unwrap()),
+                url: Some(Url::parse("ssh://git@github.com/rust-lang/regex.git").unwrap()),
+                kind: Some(SourceKind::Git(GitReference::Rev("a1b2c3d4".to_owned()))),
+            },
+            "git+ssh://git@github.com/rust-lang/regex.git?rev=a1b2c3d4#regex@1.4.3",
+        );
         ok(
             "file:///path/to/my/project/foo",
             PackageIdSpec {
@@ -652,4 +768,23 @@ mod tests {
         err!("registry+https://github.com", ErrorKind::NameValidation(_));
         err!("https://crates.io/1foo#1.2.3", ErrorKind::NameValidation(_));
     }
+
+    #[test]
+    fn glob_parsing() {
+        let spec = PackageIdSpec::parse_glob("tokio-*").unwrap();
+        assert!(spec.is_glob());
+        assert_eq!(spec.name(), "tokio-*");
+        assert_eq!(spec.partial_version(), None);
+
+        let spec = PackageIdSpec::parse_glob("my-org-*@2").unwrap();
+        assert!(spec.is_glob());
+        assert_eq!(spec.name(), "my-org-*");
+        assert_eq!(spec.partial_version(), Some(&"2".parse().unwrap()));
+        assert_eq!(spec.to_string(), "my-org-*@2");
+
+        let spec = PackageIdSpec::parse_glob("foo@1.2").unwrap();
+        assert!(!spec.is_glob());
+        assert!(PackageIdSpec::parse("foo-*").is_err());
+        assert!(PackageIdSpec::parse_glob("foo-*@^1").is_err());
+    }
 }
@@ -152,6 +152,26 @@ impl GitReference {
         reference
     }
 
+    /// Parses a full reference name like `refs/heads/main` or
+    /// `refs/pull/493/head`.
+    ///
+    /// Branches and tags are recognized by their prefix, any other reference
+    /// is fetched as is. Returns `None` if `name` is neither `HEAD` nor under
+    /// `refs/`.
+    pub fn from_ref_name(name: &str) -> Option<Self> {
+        if name == "HEAD" {
+            Some(GitReference::DefaultBranch)
+        } else if let Some(branch) = name.strip_prefix("refs/heads/") {
+            Some(GitReference::Branch(branch.to_owned()))
+        } else if let Some(tag) = name.strip_prefix("refs/tags/") {
+            Some(GitReference::Tag(tag.to_owned()))
+        } else if name.starts_with("refs/") {
+            Some(GitReference::Rev(name.to_owned()))
+        } else {
+            None
+        }
+    }
+
     /// Returns a `Display`able view of this git reference, or None if using
     /// the head of the default branch
     pub fn pretty_ref(&self, url_encoded: bool) -> Option<PrettyRef<'_>> {
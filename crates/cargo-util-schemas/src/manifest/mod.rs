@@ -39,6 +39,7 @@ pub struct TomlManifest {
     pub example: Option<Vec<TomlExampleTarget>>,
     pub test: Option<Vec<TomlTestTarget>>,
     pub bench: Option<Vec<TomlTestTarget>>,
+    pub fuzz: Option<Vec<TomlFuzzTarget>>,
     pub dependencies: Option<BTreeMap<PackageName, InheritableDependency>>,
     pub dev_dependencies: Option<BTreeMap<PackageName, InheritableDependency>>,
     #[serde(rename = "dev_dependencies")]
@@ -46,13 +47,16 @@ pub struct TomlManifest {
     pub build_dependencies: Option<BTreeMap<PackageName, InheritableDependency>>,
     #[serde(rename = "build_dependencies")]
     pub build_dependencies2: Option<BTreeMap<PackageName, InheritableDependency>>,
-    pub features: Option<BTreeMap<FeatureName, Vec<String>>>,
+    pub features: Option<TomlFeatures>,
     pub target: Option<BTreeMap<String, TomlPlatform>>,
     pub replace: Option<BTreeMap<String, TomlDependency>>,
     pub patch: Option<BTreeMap<String, BTreeMap<PackageName, TomlDependency>>>,
     pub workspace: Option<TomlWorkspace>,
     pub badges: Option<BTreeMap<String, BTreeMap<String, String>>>,
     pub lints: Option<InheritableLints>,
+    pub run: Option<TomlRun>,
+    pub build_directives: Option<TomlBuildDirectives>,
+    pub system_dependencies: Option<BTreeMap<String, TomlSystemDependency>>,
 
     /// Report unused keys (see also nested `_unused_keys`)
     /// Note: this is populated by the caller, rather than automatically
@@ -68,6 +72,7 @@ impl TomlManifest {
             self.example.as_ref().map(|_| "example"),
             self.test.as_ref().map(|_| "test"),
             self.bench.as_ref().map(|_| "bench"),
+            self.fuzz.as_ref().map(|_| "fuzz"),
             self.dependencies.as_ref().map(|_| "dependencies"),
             self.dev_dependencies().as_ref().map(|_| "dev-dependencies"),
             self.build_dependencies()
@@ -77,6 +82,11 @@ impl TomlManifest {
             self.target.as_ref().map(|_| "target"),
             self.badges.as_ref().map(|_| "badges"),
             self.lints.as_ref().map(|_| "lints"),
+            self.run.as_ref().map(|_| "run"),
+            self.build_directives.as_ref().map(|_| "build-directives"),
+            self.system_dependencies
+                .as_ref()
+                .map(|_| "system-dependencies"),
         ]
         .into_iter()
         .flatten()
@@ -103,7 +113,15 @@ impl TomlManifest {
     }
 
     pub fn features(&self) -> Option<&BTreeMap<FeatureName, Vec<String>>> {
-        self.features.as_ref()
+        self.features.as_ref().map(|f| &f.features)
+    }
+
+    pub fn feature_conflicts(&self) -> Option<&BTreeMap<FeatureName, Vec<String>>> {
+        self.features.as_ref().and_then(|f| f.conflicts.as_ref())
+    }
+
+    pub fn feature_profiles(&self) -> Option<&BTreeMap<FeatureName, Vec<String>>> {
+        self.features.as_ref().and_then(|f| f.profiles.as_ref())
     }
 
     pub fn resolved_lints(&self) -> Result<Option<&TomlLints>, UnresolvedError> {
@@ -119,6 +137,9 @@ pub struct TomlWorkspace {
     pub default_members: Option<Vec<String>>,
     pub resolver: Option<String>,
     pub metadata: Option<toml::Value>,
+    pub build: Option<TomlWorkspaceBuild>,
+    pub dependency_pins: Option<BTreeMap<PackageName, String>>,
+    pub bans: Option<BTreeMap<PackageName, String>>,
 
     // Properties that can be inherited by members.
     pub package: Option<InheritablePackage>,
@@ -126,6 +147,15 @@ pub struct TomlWorkspace {
     pub lints: Option<TomlLints>,
 }
 
+/// Build settings applying to all members of the workspace
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlWorkspaceBuild {
+    /// The targets members are built for when none is requested, unless they
+    /// set `package.default-target` or `package.forced-target`
+    pub targets: Option<Vec<String>>,
+}
+
 /// A group of fields that are inheritable by members of the workspace
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
@@ -142,6 +172,7 @@ pub struct InheritablePackage {
     pub license_file: Option<String>,
     pub repository: Option<String>,
     pub publish: Option<VecStringOrBool>,
+    pub publish_lockfile: Option<bool>,
     pub edition: Option<String>,
     pub badges: Option<BTreeMap<String, BTreeMap<String, String>>>,
     pub exclude: Option<Vec<String>>,
@@ -168,9 +199,12 @@ pub struct TomlPackage {
     pub default_target: Option<String>,
     pub forced_target: Option<String>,
     pub links: Option<String>,
+    pub links_metadata: Option<BTreeMap<String, TomlLinksMetadataType>>,
+    pub links_consume: Option<BTreeMap<String, Vec<String>>>,
     pub exclude: Option<InheritableVecString>,
     pub include: Option<InheritableVecString>,
     pub publish: Option<InheritableVecStringOrBool>,
+    pub publish_lockfile: Option<InheritableBool>,
     pub workspace: Option<String>,
     pub im_a_teapot: Option<bool>,
     pub autobins: Option<bool>,
@@ -236,6 +270,13 @@ impl TomlPackage {
         self.publish.as_ref().map(|v| v.resolved()).transpose()
     }
 
+    pub fn resolved_publish_lockfile(&self) -> Result<Option<&bool>, UnresolvedError> {
+        self.publish_lockfile
+            .as_ref()
+            .map(|v| v.resolved())
+            .transpose()
+    }
+
     pub fn resolved_description(&self) -> Result<Option<&String>, UnresolvedError> {
         self.description.as_ref().map(|v| v.resolved()).transpose()
     }
@@ -518,6 +559,41 @@ impl<'de> de::Deserialize<'de> for InheritableVecStringOrBool {
     }
 }
 
+pub type InheritableBool = InheritableField<bool>;
+impl<'de> de::Deserialize<'de> for InheritableBool {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = InheritableBool;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+                f.write_str("a boolean or workspace")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(InheritableField::Value(v))
+            }
+
+            fn visit_map<V>(self, map: V) -> Result<Self::Value, V::Error>
+            where
+                V: de::MapAccess<'de>,
+            {
+                let mvd = de::value::MapAccessDeserializer::new(map);
+                TomlInheritedField::deserialize(mvd).map(InheritableField::Inherit)
+            }
+        }
+
+        d.deserialize_any(Visitor)
+    }
+}
+
 pub type InheritableBtreeMap = InheritableField<BTreeMap<String, BTreeMap<String, String>>>;
 
 impl<'de> de::Deserialize<'de> for InheritableBtreeMap {
@@ -738,6 +814,9 @@ pub struct TomlDetailedDependency<P: Clone = String> {
     pub default_features: Option<bool>,
     #[serde(rename = "default_features")]
     pub default_features2: Option<bool>,
+    /// A set of features from the `[features.profiles]` of the dependency,
+    /// used instead of its default features.
+    pub features_profile: Option<String>,
     pub package: Option<PackageName>,
     pub public: Option<bool>,
 
@@ -776,6 +855,7 @@ impl<P: Clone> Default for TomlDetailedDependency<P> {
             optional: Default::default(),
             default_features: Default::default(),
             default_features2: Default::default(),
+            features_profile: Default::default(),
             package: Default::default(),
             public: Default::default(),
             artifact: Default::default(),
@@ -814,8 +894,13 @@ pub struct TomlProfile {
     pub overflow_checks: Option<bool>,
     pub incremental: Option<bool>,
     pub dir_name: Option<String>,
-    pub inherits: Option<String>,
+    /// A list of profiles is the unstable feature `profile-stack`.
+    pub inherits: Option<TomlProfileInherits>,
     pub strip: Option<StringOrBool>,
+    /// Unstable feature `profile-binary-size`.
+    pub strip_keep_symbols: Option<Vec<String>>,
+    /// Unstable feature `profile-binary-size`.
+    pub compress_debuginfo: Option<String>,
     // Note that `rustflags` is used for the cargo-feature `profile_rustflags`
     pub rustflags: Option<Vec<String>>,
     // These two fields must be last because they are sub-tables, and TOML
@@ -912,12 +997,53 @@ impl TomlProfile {
             self.strip = Some(v.clone());
         }
 
+        if let Some(v) = &profile.strip_keep_symbols {
+            self.strip_keep_symbols = Some(v.clone());
+        }
+
+        if let Some(v) = &profile.compress_debuginfo {
+            self.compress_debuginfo = Some(v.clone());
+        }
+
         if let Some(v) = &profile.trim_paths {
             self.trim_paths = Some(v.clone())
         }
     }
 }
 
+/// The profiles a profile inherits from.
+///
+/// The first one is the parent of the profile, and the others are fragments
+/// layered on top of it, in order.
+#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum TomlProfileInherits {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl TomlProfileInherits {
+    pub fn names(&self) -> &[String] {
+        match self {
+            TomlProfileInherits::One(name) => std::slice::from_ref(name),
+            TomlProfileInherits::Many(names) => names,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TomlProfileInherits {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        UntaggedEnumVisitor::new()
+            .expecting("a profile name or a list of profile names")
+            .string(|name| Ok(TomlProfileInherits::One(name.to_owned())))
+            .seq(|names| names.deserialize().map(TomlProfileInherits::Many))
+            .deserialize(deserializer)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub enum ProfilePackageSpec {
     Spec(PackageIdSpec),
@@ -1204,6 +1330,7 @@ pub type TomlBinTarget = TomlTarget;
 pub type TomlExampleTarget = TomlTarget;
 pub type TomlTestTarget = TomlTarget;
 pub type TomlBenchTarget = TomlTarget;
+pub type TomlFuzzTarget = TomlTarget;
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
@@ -1367,6 +1494,115 @@ impl<T: AsRef<str>> FeatureName<T> {
     }
 }
 
+/// The `[features]` table.
+///
+/// Besides the feature definitions, this holds the `[features.conflicts]`
+/// table of mutually exclusive features and the `[features.profiles]` table
+/// of alternative default feature sets. A `conflicts` or `profiles` key whose
+/// value is an array is still an ordinary feature.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct TomlFeatures {
+    pub features: BTreeMap<FeatureName, Vec<String>>,
+    pub conflicts: Option<BTreeMap<FeatureName, Vec<String>>>,
+    pub profiles: Option<BTreeMap<FeatureName, Vec<String>>>,
+}
+
+impl TomlFeatures {
+    const CONFLICTS: &'static str = "conflicts";
+    const PROFILES: &'static str = "profiles";
+}
+
+impl ser::Serialize for TomlFeatures {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        use ser::SerializeMap as _;
+
+        let len = self.features.len()
+            + usize::from(self.conflicts.is_some())
+            + usize::from(self.profiles.is_some());
+        let mut map = serializer.serialize_map(Some(len))?;
+        for (name, values) in &self.features {
+            map.serialize_entry(name, values)?;
+        }
+        if let Some(conflicts) = &self.conflicts {
+            map.serialize_entry(Self::CONFLICTS, conflicts)?;
+        }
+        if let Some(profiles) = &self.profiles {
+            map.serialize_entry(Self::PROFILES, profiles)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> de::Deserialize<'de> for TomlFeatures {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct FeaturesVisitor;
+
+        impl<'de> de::Visitor<'de> for FeaturesVisitor {
+            type Value = TomlFeatures;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a table of features")
+            }
+
+            fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let mut features = TomlFeatures::default();
+                while let Some(name) = access.next_key::<FeatureName>()? {
+                    if name.as_str() != TomlFeatures::CONFLICTS
+                        && name.as_str() != TomlFeatures::PROFILES
+                    {
+                        features.features.insert(name, access.next_value()?);
+                        continue;
+                    }
+                    match access.next_value::<FeatureOrTable>()? {
+                        FeatureOrTable::Feature(values) => {
+                            features.features.insert(name, values);
+                        }
+                        FeatureOrTable::Table(table) => {
+                            if name.as_str() == TomlFeatures::CONFLICTS {
+                                features.conflicts = Some(table);
+                            } else {
+                                features.profiles = Some(table);
+                            }
+                        }
+                    }
+                }
+                Ok(features)
+            }
+        }
+
+        /// The value of a `conflicts` or `profiles` key, which both map
+        /// names to arrays of features.
+        enum FeatureOrTable {
+            Feature(Vec<String>),
+            Table(BTreeMap<FeatureName, Vec<String>>),
+        }
+
+        impl<'de> de::Deserialize<'de> for FeatureOrTable {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                UntaggedEnumVisitor::new()
+                    .expecting("an array of features or a table of feature arrays")
+                    .seq(|value| value.deserialize().map(FeatureOrTable::Feature))
+                    .map(|value| value.deserialize().map(FeatureOrTable::Table))
+                    .deserialize(deserializer)
+            }
+        }
+
+        deserializer.deserialize_map(FeaturesVisitor)
+    }
+}
+
 /// Corresponds to a `target` entry, but `TomlTarget` is already used.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
@@ -1394,6 +1630,119 @@ impl TomlPlatform {
     }
 }
 
+/// The type of a `cargo::metadata` key declared in `package.links-metadata`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TomlLinksMetadataType {
+    String,
+    Path,
+    Bool,
+    Integer,
+}
+
+impl TomlLinksMetadataType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Path => "path",
+            Self::Bool => "bool",
+            Self::Integer => "integer",
+        }
+    }
+}
+
+/// The `[run]` table, configuring `cargo run`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlRun {
+    /// Named argument presets, selected with `cargo run --preset <name>`.
+    pub args: Option<BTreeMap<String, Vec<String>>>,
+}
+
+/// The `[build-directives]` table, declaring the instructions a trivial build
+/// script would print, without compiling or running one.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlBuildDirectives {
+    /// Like `cargo::rustc-cfg`.
+    pub rustc_cfg: Option<Vec<String>>,
+    /// Like `cargo::rustc-check-cfg`.
+    pub rustc_check_cfg: Option<Vec<String>>,
+    /// Like `cargo::rustc-env`.
+    pub rustc_env: Option<BTreeMap<String, String>>,
+    /// Like `cargo::rustc-link-lib`.
+    pub rustc_link_lib: Option<Vec<String>>,
+    /// Like `cargo::rustc-link-search`, relative to the package root.
+    pub rustc_link_search: Option<Vec<String>>,
+    /// Globs of files, relative to the package root, rebuilding the package
+    /// when they change.
+    pub rerun_if_changed: Option<Vec<String>>,
+    /// Like `cargo::rerun-if-env-changed`.
+    pub rerun_if_env_changed: Option<Vec<String>>,
+}
+
+/// An entry of the `[system-dependencies]` table, a system library probed by
+/// Cargo before building the package.
+#[derive(Clone, Debug, Serialize, Hash)]
+#[serde(untagged)]
+pub enum TomlSystemDependency {
+    /// In the simple format, only a version requirement is specified, eg.
+    /// `zlib = "1.2"`
+    Simple(String),
+    /// The detailed format, eg.
+    /// `openssl = { version = "1.1", pkg-config = "openssl", vcpkg = "openssl" }`
+    Detailed(TomlDetailedSystemDependency),
+}
+
+impl TomlSystemDependency {
+    pub fn version(&self) -> Option<&str> {
+        match self {
+            TomlSystemDependency::Simple(version) => Some(version),
+            TomlSystemDependency::Detailed(d) => d.version.as_deref(),
+        }
+    }
+
+    /// The name of the library for `pkg-config`, if not the name of the entry.
+    pub fn pkg_config(&self) -> Option<&str> {
+        match self {
+            TomlSystemDependency::Simple(_) => None,
+            TomlSystemDependency::Detailed(d) => d.pkg_config.as_deref(),
+        }
+    }
+
+    /// The name of the port for `vcpkg`, which is only probed if set.
+    pub fn vcpkg(&self) -> Option<&str> {
+        match self {
+            TomlSystemDependency::Simple(_) => None,
+            TomlSystemDependency::Detailed(d) => d.vcpkg.as_deref(),
+        }
+    }
+}
+
+impl<'de> de::Deserialize<'de> for TomlSystemDependency {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        UntaggedEnumVisitor::new()
+            .expecting(
+                "a version requirement like \"1.2\" or a \
+                     detailed system dependency like { version = \"1.2\" }",
+            )
+            .string(|value| Ok(TomlSystemDependency::Simple(value.to_owned())))
+            .map(|value| value.deserialize().map(TomlSystemDependency::Detailed))
+            .deserialize(deserializer)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlDetailedSystemDependency {
+    pub version: Option<String>,
+    pub pkg_config: Option<String>,
+    pub vcpkg: Option<String>,
+}
+
 #[derive(Serialize, Debug, Clone)]
 pub struct InheritableLints {
     #[serde(skip_serializing_if = "is_false")]
@@ -87,6 +87,11 @@ pub(crate) fn validate_name(name: &str, what: &'static str) -> Result<()> {
     Ok(())
 }
 
+/// Returns `true` if the name contains any glob pattern wildcards.
+pub(crate) fn is_glob_pattern<T: AsRef<str>>(name: T) -> bool {
+    name.as_ref().contains(&['*', '?', '[', ']'][..])
+}
+
 /// Ensure a package name is [valid][validate_package_name]
 pub(crate) fn sanitize_package_name(name: &str, placeholder: char) -> String {
     let mut slug = String::new();
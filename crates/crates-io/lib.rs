@@ -60,6 +60,10 @@ pub struct NewCrate {
     pub badges: BTreeMap<String, BTreeMap<String, String>>,
     pub links: Option<String>,
     pub rust_version: Option<String>,
+    /// The `[features.profiles]` table, named sets of features which
+    /// dependents can use instead of the default features.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub feature_profiles: BTreeMap<String, Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -81,6 +85,8 @@ pub struct NewCrateDependency {
     pub bindep_target: Option<String>,
     #[serde(default, skip_serializing_if = "is_false")]
     pub lib: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub features_profile: Option<String>,
 }
 
 fn is_false(x: &bool) -> bool {
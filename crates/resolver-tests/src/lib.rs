@@ -11,7 +11,9 @@ use std::task::Poll;
 use std::time::Instant;
 
 use cargo::core::dependency::DepKind;
-use cargo::core::resolver::{self, ResolveOpts, VersionOrdering, VersionPreferences};
+use cargo::core::resolver::{
+    self, PlatformFilter, ResolveOpts, VersionOrdering, VersionPreferences,
+};
 use cargo::core::Resolve;
 use cargo::core::ResolveVersion;
 use cargo::core::{Dependency, PackageId, Registry, Summary};
@@ -171,11 +173,15 @@ pub fn resolve_with_global_context_raw(
     if gctx.cli_unstable().minimal_versions {
         version_prefs.version_ordering(VersionOrdering::MinimumVersionsFirst)
     }
+    if gctx.cli_unstable().direct_minimal_versions {
+        version_prefs.direct_version_ordering(VersionOrdering::MinimumVersionsFirst)
+    }
     let resolve = resolver::resolve(
         &[(summary, opts)],
         &[],
         &mut registry,
         &version_prefs,
+        &PlatformFilter::default(),
         ResolveVersion::with_rust_version(None),
         Some(gctx),
     );
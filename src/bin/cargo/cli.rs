@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Context as _};
 use cargo::core::{features, CliUnstable};
 use cargo::util::context::TermConfig;
+use cargo::util::network::simulate::{self, SimulatedNetwork};
 use cargo::{drop_print, drop_println, CargoResult};
 use clap::builder::UnknownArgumentValueParser;
 use itertools::Itertools;
@@ -93,6 +94,15 @@ pub fn main(gctx: &mut GlobalContext) -> CliResult {
         )?;
         super::init_git(gctx);
 
+        if gctx.cli_unstable().build_server && matches!(cmd, "build" | "check") {
+            if let Some(code) = dispatch_to_build_server(gctx, subcommand_args)? {
+                return if code == 0 {
+                    Ok(())
+                } else {
+                    Err(CliError::code(code))
+                };
+            }
+        }
         exec.exec(gctx, subcommand_args)?;
     }
     Ok(())
@@ -385,6 +395,24 @@ For more information, see issue #12207 <https://github.com/rust-lang/cargo/issue
     Ok((args, GlobalArgs::default()))
 }
 
+/// Runs the build with the `cargo server` of the workspace, if there is one.
+fn dispatch_to_build_server(
+    gctx: &GlobalContext,
+    subcommand_args: &ArgMatches,
+) -> CargoResult<Option<i32>> {
+    let Ok(root_manifest) = subcommand_args.root_manifest(gctx) else {
+        return Ok(None);
+    };
+    let Some(args) = std::env::args_os()
+        .skip(1)
+        .map(|arg| arg.into_string().ok())
+        .collect::<Option<Vec<_>>>()
+    else {
+        return Ok(None);
+    };
+    cargo::ops::cargo_server::dispatch(gctx, &root_manifest, args)
+}
+
 #[tracing::instrument(skip_all)]
 fn configure_gctx(
     gctx: &mut GlobalContext,
@@ -418,6 +446,16 @@ fn configure_gctx(
         .get_one::<String>("color")
         .map(String::as_str)
         .or_else(|| global_color.as_deref());
+    let global_ui = global_args.ui;
+    let ui = args
+        .get_one::<String>("ui")
+        .map(String::as_str)
+        .or_else(|| global_ui.as_deref());
+    let global_simulate_network = global_args.simulate_network;
+    let simulate_network = args
+        .get_one::<String>("simulate-network")
+        .map(String::as_str)
+        .or_else(|| global_simulate_network.as_deref());
     let frozen = args.flag("frozen") || global_args.frozen;
     let locked = args.flag("locked") || global_args.locked;
     let offline = args.flag("offline") || global_args.offline;
@@ -429,6 +467,17 @@ fn configure_gctx(
     if let Some(values) = args.get_many::<String>("config") {
         config_args.extend(values.cloned());
     }
+    let mut patch_sets = global_args.patch_sets;
+    if let Some(values) = args.get_many::<String>("enable-patch-set") {
+        patch_sets.extend(values.cloned());
+    }
+    // `--enable-patch-set` is a shorthand for appending to `enable-patch-sets`.
+    for name in &patch_sets {
+        config_args.push(format!(
+            "enable-patch-sets=[{}]",
+            toml::Value::String(name.clone())
+        ));
+    }
     gctx.configure(
         verbose,
         quiet,
@@ -440,6 +489,33 @@ fn configure_gctx(
         &unstable_flags,
         &config_args,
     )?;
+    if !patch_sets.is_empty() {
+        let unstable = gctx.cli_unstable();
+        unstable.fail_if_stable_opt_custom_z(
+            "--enable-patch-set",
+            None,
+            "patch-sets",
+            unstable.patch_sets,
+        )?;
+    }
+    if ui.is_some() {
+        gctx.cli_unstable().fail_if_stable_opt("--ui", None)?;
+        gctx.shell().set_ui(ui)?;
+    }
+    if let Some(spec) = simulate_network {
+        if gctx.get_env(simulate::ENABLE_ENV).is_err() {
+            return Err(anyhow!(
+                "the `--simulate-network` flag is only meant for testing, \
+                 set `{}=1` to enable it",
+                simulate::ENABLE_ENV
+            )
+            .into());
+        }
+        let network = spec
+            .parse::<SimulatedNetwork>()
+            .context("failed to parse `--simulate-network`")?;
+        gctx.set_simulated_network(Some(network));
+    }
     Ok(())
 }
 
@@ -510,11 +586,14 @@ struct GlobalArgs {
     verbose: u32,
     quiet: bool,
     color: Option<String>,
+    ui: Option<String>,
     frozen: bool,
     locked: bool,
     offline: bool,
     unstable_flags: Vec<String>,
     config_args: Vec<String>,
+    patch_sets: Vec<String>,
+    simulate_network: Option<String>,
 }
 
 impl GlobalArgs {
@@ -523,6 +602,7 @@ impl GlobalArgs {
             verbose: args.verbose(),
             quiet: args.flag("quiet"),
             color: args.get_one::<String>("color").cloned(),
+            ui: args.get_one::<String>("ui").cloned(),
             frozen: args.flag("frozen"),
             locked: args.flag("locked"),
             offline: args.flag("offline"),
@@ -536,6 +616,12 @@ impl GlobalArgs {
                 .unwrap_or_default()
                 .cloned()
                 .collect(),
+            patch_sets: args
+                .get_many::<String>("enable-patch-set")
+                .unwrap_or_default()
+                .cloned()
+                .collect(),
+            simulate_network: args.get_one::<String>("simulate-network").cloned(),
         }
     }
 }
@@ -637,6 +723,12 @@ See '<cyan,bold>cargo help</> <cyan><<command>></>' for more information on a sp
                 .value_name("WHEN")
                 .global(true),
         )
+        .arg(
+            opt("ui", "Output style: human, compact (unstable)")
+                .value_name("MODE")
+                .global(true)
+                .hide(true),
+        )
         .arg(
             Arg::new("directory")
                 .help("Change to DIRECTORY before doing anything (nightly-only)")
@@ -669,6 +761,19 @@ See '<cyan,bold>cargo help</> <cyan><<command>></>' for more information on a sp
             .global(true)
             .hide(true))
         .arg(multi_opt("config", "KEY=VALUE", "Override a configuration value").global(true))
+        // Unstable, see `-Zpatch-sets`.
+        .arg(
+            multi_opt("enable-patch-set", "NAME", "Enable a named patch set from config")
+                .global(true)
+                .hide(true),
+        )
+        // Only meant for testing, see `cargo::util::network::simulate`.
+        .arg(
+            opt("simulate-network", "Inject failures into network operations")
+                .value_name("SPEC")
+                .global(true)
+                .hide(true),
+        )
         // Better suggestion for the unsupported lowercase unstable feature flag.
         .arg( Arg::new("unsupported-lowercase-unstable-feature-flag")
             .help("")
@@ -62,17 +62,17 @@ The package name will be exposed as feature of your crate.")
 The package will be removed from your features.")
                 .conflicts_with("dev")
                 .overrides_with("optional"),
-            flag("public", "Mark the dependency as public (unstable)")
+            flag("public", "Mark the dependency as public")
                 .conflicts_with("dev")
                 .conflicts_with("build")
-                .long_help("Mark the dependency as public (unstable)
+                .long_help("Mark the dependency as public
 
 The dependency can be referenced in your library's public API."),
-            flag("no-public", "Mark the dependency as private (unstable)")
+            flag("no-public", "Mark the dependency as private")
                 .conflicts_with("dev")
                 .conflicts_with("build")
                 .overrides_with("public")
-                .long_help("Mark the dependency as private (unstable)
+                .long_help("Mark the dependency as private
 
 While you can use the crate in your implementation, it cannot be referenced in your public API."),
             clap::Arg::new("rename")
@@ -160,7 +160,12 @@ Build-dependencies are the only dependencies available for use by build scripts
                 .action(ArgAction::Set)
                 .value_name("TARGET")
                 .value_parser(clap::builder::NonEmptyStringValueParser::new())
-                .help("Add as dependency to the given target platform")
+                .help("Add as dependency to the given target platform"),
+            flag("workspace-table",
+                "Add to `workspace.dependencies`, inherited with `workspace = true` (unstable)"),
+            flag("no-workspace-table",
+                "Add to the package's own table, not inherited from the workspace (unstable)")
+                .overrides_with("workspace-table"),
         ])
 }
 
@@ -204,6 +209,16 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
 
     let honor_rust_version = args.honor_rust_version();
 
+    let workspace_table = workspace_table(args);
+    if workspace_table.is_some() {
+        let flag = if workspace_table == Some(true) {
+            "--workspace-table"
+        } else {
+            "--no-workspace-table"
+        };
+        gctx.cli_unstable().fail_if_stable_opt(flag, None)?;
+    }
+
     let options = AddOptions {
         gctx,
         spec,
@@ -211,6 +226,7 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         section,
         dry_run,
         honor_rust_version,
+        workspace_table,
     };
     add(&ws, &options)?;
 
@@ -360,6 +376,13 @@ fn public(matches: &ArgMatches) -> Option<bool> {
     resolve_bool_arg(matches.flag("public"), matches.flag("no-public"))
 }
 
+fn workspace_table(matches: &ArgMatches) -> Option<bool> {
+    resolve_bool_arg(
+        matches.flag("workspace-table"),
+        matches.flag("no-workspace-table"),
+    )
+}
+
 fn resolve_bool_arg(yes: bool, no: bool) -> Option<bool> {
     match (yes, no) {
         (true, false) => Some(true),
@@ -68,6 +68,8 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     let ops = TestOptions {
         no_run: args.flag("no-run"),
         no_fail_fast: args.flag("no-fail-fast"),
+        runners: ops::TestRunners::Default,
+        cache_results: Some(false),
         compile_opts,
     };
 
@@ -1,6 +1,8 @@
 use crate::command_prelude::*;
 
-use cargo::ops;
+use cargo::core::Workspace;
+use cargo::ops::{self, CompileOptions};
+use cargo::CargoResult;
 
 pub fn cli() -> Command {
     subcommand("build")
@@ -36,9 +38,22 @@ pub fn cli() -> Command {
         .arg_target_dir()
         .arg_out_dir()
         .arg_build_plan()
+        .arg_emit_ninja()
         .arg_unit_graph()
+        .arg_explain_rebuild()
+        .arg_checkpoint_interval()
+        .arg_show_dependency_warnings()
         .arg_timings()
         .arg_manifest_path()
+        .arg(
+            opt(
+                "manifest-list",
+                "Path to a file listing the Cargo.toml files to build (unstable)",
+            )
+            .value_name("PATH")
+            .help_heading(heading::MANIFEST_OPTIONS)
+            .conflicts_with_all(["manifest-path", "out-dir"]),
+        )
         .arg_ignore_rust_version()
         .after_help(color_print::cstr!(
             "Run `<cyan,bold>cargo help build</>` for more detailed information.\n"
@@ -46,9 +61,30 @@ pub fn cli() -> Command {
 }
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    if let Some(list) = args.value_of_path("manifest-list", gctx) {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--manifest-list", None)?;
+        let manifests = ops::read_manifest_list(&list)?;
+        let compile_opts =
+            args.compile_options(gctx, CompileMode::Build, None, ProfileChecking::Custom)?;
+        ops::compile_manifest_list(gctx, &manifests, &compile_opts)?;
+        return Ok(());
+    }
+
     let ws = args.workspace(gctx)?;
+    let compile_opts = compile_options(gctx, args, &ws)?;
+    ops::compile(&ws, &compile_opts)?;
+    Ok(())
+}
+
+/// The options of `cargo build`, also used by `cargo server`.
+pub fn compile_options(
+    gctx: &GlobalContext,
+    args: &ArgMatches,
+    ws: &Workspace<'_>,
+) -> CargoResult<CompileOptions> {
     let mut compile_opts =
-        args.compile_options(gctx, CompileMode::Build, Some(&ws), ProfileChecking::Custom)?;
+        args.compile_options(gctx, CompileMode::Build, Some(ws), ProfileChecking::Custom)?;
 
     if let Some(out_dir) = args.value_of_path("out-dir", gctx) {
         compile_opts.build_config.export_dir = Some(out_dir);
@@ -59,6 +95,5 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     if compile_opts.build_config.export_dir.is_some() {
         gctx.cli_unstable().fail_if_stable_opt("--out-dir", 6790)?;
     }
-    ops::compile(&ws, &compile_opts)?;
-    Ok(())
+    Ok(compile_opts)
 }
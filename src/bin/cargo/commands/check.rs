@@ -1,6 +1,8 @@
 use crate::command_prelude::*;
 
-use cargo::ops;
+use cargo::core::Workspace;
+use cargo::ops::{self, CompileOptions};
+use cargo::CargoResult;
 
 pub fn cli() -> Command {
     subcommand("check")
@@ -34,6 +36,7 @@ pub fn cli() -> Command {
         .arg_target_triple("Check for the target triple")
         .arg_target_dir()
         .arg_unit_graph()
+        .arg_show_dependency_warnings()
         .arg_timings()
         .arg_manifest_path()
         .arg_ignore_rust_version()
@@ -44,15 +47,23 @@ pub fn cli() -> Command {
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     let ws = args.workspace(gctx)?;
+    let compile_opts = compile_options(gctx, args, &ws)?;
+
+    ops::compile(&ws, &compile_opts)?;
+    Ok(())
+}
+
+/// The options of `cargo check`, also used by `cargo server`.
+pub fn compile_options(
+    gctx: &GlobalContext,
+    args: &ArgMatches,
+    ws: &Workspace<'_>,
+) -> CargoResult<CompileOptions> {
     // This is a legacy behavior that causes `cargo check` to pass `--test`.
     let test = matches!(
         args.get_one::<String>("profile").map(String::as_str),
         Some("test")
     );
     let mode = CompileMode::Check { test };
-    let compile_opts =
-        args.compile_options(gctx, mode, Some(&ws), ProfileChecking::LegacyTestOnly)?;
-
-    ops::compile(&ws, &compile_opts)?;
-    Ok(())
+    args.compile_options(gctx, mode, Some(ws), ProfileChecking::LegacyTestOnly)
 }
@@ -0,0 +1,22 @@
+use crate::command_prelude::*;
+use cargo::ops::cargo_daemon;
+
+pub fn cli() -> Command {
+    subcommand("daemon")
+        .about("Serve JSON-RPC requests about the workspace over stdio")
+        .arg_silent_suggestion()
+        .arg_manifest_path()
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "daemon",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let root_manifest = args.root_manifest(gctx)?;
+    cargo_daemon::daemon(gctx, &root_manifest)?;
+    Ok(())
+}
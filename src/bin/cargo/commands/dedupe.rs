@@ -0,0 +1,26 @@
+use crate::command_prelude::*;
+use cargo::ops::cargo_dedupe;
+
+pub fn cli() -> Command {
+    subcommand("dedupe")
+        .about("Collapse duplicate versions of dependencies in Cargo.lock")
+        .arg_dry_run("Only print the updates instead of writing the lockfile")
+        .arg_silent_suggestion()
+        .arg_manifest_path()
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "dedupe",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let ws = args.workspace(gctx)?;
+    let opts = cargo_dedupe::DedupeOptions {
+        dry_run: args.dry_run(),
+    };
+    cargo_dedupe::dedupe(&ws, &opts)?;
+    Ok(())
+}
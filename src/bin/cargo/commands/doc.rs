@@ -16,6 +16,10 @@ pub fn cli() -> Command {
             "Don't build documentation for dependencies",
         ))
         .arg(flag("document-private-items", "Document private items"))
+        .arg(flag(
+            "features-table",
+            "Generate a page documenting the features (unstable)",
+        ))
         .arg_message_format()
         .arg_silent_suggestion()
         .arg_package_spec(
@@ -54,9 +58,16 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     let mut compile_opts = args.compile_options(gctx, mode, Some(&ws), ProfileChecking::Custom)?;
     compile_opts.rustdoc_document_private_items = args.flag("document-private-items");
 
+    let features_table = args.flag("features-table");
+    if features_table {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--features-table", None)?;
+    }
+
     let doc_opts = DocOptions {
         open_result: args.flag("open"),
         output_format: ops::OutputFormat::Html,
+        features_table,
         compile_opts,
     };
     ops::doc(&ws, &doc_opts)?;
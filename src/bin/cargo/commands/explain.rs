@@ -0,0 +1,38 @@
+use crate::command_prelude::*;
+use cargo::ops::cargo_explain;
+
+pub fn cli() -> Command {
+    subcommand("explain")
+        .about("Explain why the resolver selected a version of a package")
+        .arg(
+            Arg::new("spec")
+                .value_name("SPEC")
+                .action(ArgAction::Set)
+                .required(true)
+                .help("Package to explain, e.g. `regex@1.10`"),
+        )
+        .arg(
+            opt("format", "Output format")
+                .value_parser(clap::value_parser!(cargo_explain::ExplainFormat))
+                .default_value("human"),
+        )
+        .arg_silent_suggestion()
+        .arg_manifest_path()
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "explain",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let ws = args.workspace(gctx)?;
+    let opts = cargo_explain::ExplainOptions {
+        spec: args.get_one::<String>("spec").unwrap(),
+        format: *args.get_one("format").unwrap(),
+    };
+    cargo_explain::explain(&ws, &opts)?;
+    Ok(())
+}
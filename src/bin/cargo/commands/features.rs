@@ -0,0 +1,149 @@
+use crate::command_prelude::*;
+use anyhow::Context as _;
+use cargo::ops::cargo_features::{self, FeaturesDiffOptions, FeaturesInvocation};
+use cargo::CargoResult;
+
+pub fn cli() -> Command {
+    subcommand("features")
+        .about("Inspect the features enabled by the feature resolver (unstable)")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            subcommand("diff")
+                .about("Compare the features enabled by two sets of flags")
+                .arg(
+                    opt(
+                        "before",
+                        "Package and feature flags of the first invocation",
+                    )
+                    .value_name("FLAGS")
+                    .allow_hyphen_values(true)
+                    .required(true),
+                )
+                .arg(
+                    opt(
+                        "after",
+                        "Package and feature flags of the second invocation",
+                    )
+                    .value_name("FLAGS")
+                    .allow_hyphen_values(true)
+                    .required(true),
+                )
+                .arg(
+                    opt("format", "Output format")
+                        .value_parser(clap::value_parser!(cargo_features::DiffFormat))
+                        .default_value("human"),
+                )
+                .arg_silent_suggestion()
+                .arg_manifest_path(),
+        )
+}
+
+/// The flags accepted within `--before` and `--after`.
+fn invocation_cli() -> Command {
+    Command::new("flags")
+        .no_binary_name(true)
+        .arg_package_spec_no_all(
+            "Package to select",
+            "Select all packages in the workspace",
+            "Exclude packages from the selection",
+        )
+        .arg_features()
+        .arg_target_triple("Resolve features for the target triple")
+        .arg(flag("all-targets", "Include dev-dependencies"))
+}
+
+fn parse_invocation(args: &ArgMatches, name: &str) -> CargoResult<FeaturesInvocation> {
+    let flags = args.get_one::<String>(name).unwrap();
+    let matches = split_shell_words(flags)
+        .and_then(|words| Ok(invocation_cli().try_get_matches_from(words)?))
+        .with_context(|| format!("invalid flags `{flags}` for `--{name}`"))?;
+    Ok(FeaturesInvocation {
+        packages: matches.packages_from_flags()?,
+        cli_features: matches.cli_features()?,
+        targets: matches.targets()?,
+        dev_deps: matches.flag("all-targets"),
+    })
+}
+
+/// Splits `flags` into words like a POSIX shell, so that quoted values such as
+/// `--features "a b"` stay a single argument.
+///
+/// Single quotes keep their contents as is, while backslashes escape the next
+/// character outside of quotes and `"` or `\` within double quotes.
+fn split_shell_words(flags: &str) -> CargoResult<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = None::<String>;
+    let mut chars = flags.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => anyhow::bail!("unterminated single quote"),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => anyhow::bail!("unterminated double quote"),
+                        },
+                        Some(c) => word.push(c),
+                        None => anyhow::bail!("unterminated double quote"),
+                    }
+                }
+            }
+            '\\' => {
+                let word = word.get_or_insert_with(String::new);
+                match chars.next() {
+                    Some(c) => word.push(c),
+                    None => anyhow::bail!("trailing backslash"),
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    match args.subcommand() {
+        Some(("diff", args)) => {
+            gctx.cli_unstable().fail_if_stable_command(
+                gctx,
+                "features diff",
+                None,
+                "unstable-options",
+                gctx.cli_unstable().unstable_options,
+            )?;
+            let ws = args.workspace(gctx)?;
+            let opts = FeaturesDiffOptions {
+                before: parse_invocation(args, "before")?,
+                after: parse_invocation(args, "after")?,
+                format: *args.get_one("format").unwrap(),
+            };
+            cargo_features::features_diff(&ws, &opts)?;
+            Ok(())
+        }
+        Some((cmd, _)) => {
+            unreachable!("unexpected command {}", cmd)
+        }
+        None => {
+            unreachable!("unexpected command")
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::command_prelude::*;
+
+use cargo::core::compiler::{CompileKind, CompileTarget};
+use cargo::ops;
+
+pub fn cli() -> Command {
+    subcommand("fuzz-build")
+        .about("Compile the fuzz targets of a local package, instrumented for libFuzzer")
+        .arg_message_format()
+        .arg_silent_suggestion()
+        .arg_package_spec(
+            "Package to build (see `cargo help pkgid`)",
+            "Build all packages in the workspace",
+            "Exclude packages from the build",
+        )
+        .arg(
+            multi_opt("fuzz", "NAME", "Build only the specified fuzz target")
+                .help_heading(heading::TARGET_SELECTION),
+        )
+        .arg_features()
+        .arg_profile("Build artifacts with the specified profile")
+        .arg_parallel()
+        .arg_target_triple("Build for the target triple")
+        .arg_target_dir()
+        .arg_timings()
+        .arg_manifest_path()
+        .arg_ignore_rust_version()
+        .after_help(color_print::cstr!(
+            "Run `<cyan,bold>cargo help build</>` for more detailed information.\n"
+        ))
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "fuzz-build",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let ws = args.workspace(gctx)?;
+    let mut compile_opts =
+        args.compile_options(gctx, CompileMode::Build, Some(&ws), ProfileChecking::Custom)?;
+    compile_opts.build_config.requested_profile =
+        args.get_profile_name(gctx, "fuzz", ProfileChecking::Custom)?;
+
+    // Like `cargo fuzz`, always pass `--target` so build scripts and
+    // proc-macros are built for the host without instrumentation.
+    let requested_kinds = &mut compile_opts.build_config.requested_kinds;
+    if requested_kinds.iter().any(CompileKind::is_host) {
+        let host = gctx.load_global_rustc(Some(&ws))?.host;
+        let host = CompileKind::Target(CompileTarget::new(&host)?);
+        for kind in requested_kinds.iter_mut().filter(|kind| kind.is_host()) {
+            *kind = host;
+        }
+    }
+
+    let names: Vec<String> = args
+        .get_many::<String>("fuzz")
+        .unwrap_or_default()
+        .cloned()
+        .collect();
+    compile_opts.filter = ops::CompileFilter::no_targets();
+    compile_opts.fuzz = ops::FilterRule::new(names.clone(), names.is_empty());
+    ops::compile(&ws, &compile_opts)?;
+    Ok(())
+}
@@ -5,6 +5,12 @@ use cargo::ops;
 pub fn cli() -> Command {
     subcommand("generate-lockfile")
         .about("Generate the lockfile for a package")
+        .arg_before()
+        .arg(multi_opt(
+            "filter-platform",
+            "TRIPLE",
+            "Only lock dependencies used on the given target triple (unstable)",
+        ))
         .arg_silent_suggestion()
         .arg_manifest_path()
         .arg_ignore_rust_version_with_help(
@@ -24,7 +30,18 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
             gctx.cli_unstable().msrv_policy,
         )?;
     }
-    let ws = args.workspace(gctx)?;
+    let before = args.before()?;
+    if before.is_some() {
+        gctx.cli_unstable().fail_if_stable_opt("--before", None)?;
+    }
+    let filter_platforms = args._values_of("filter-platform");
+    if !filter_platforms.is_empty() {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--filter-platform", None)?;
+    }
+    let mut ws = args.workspace(gctx)?;
+    ws.set_max_publish_time(before);
+    ws.set_filter_platforms(filter_platforms);
     ops::generate_lockfile(&ws)?;
     Ok(())
 }
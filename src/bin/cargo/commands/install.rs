@@ -62,6 +62,15 @@ pub fn cli() -> Command {
                 .value_name("SHA")
                 .requires("git"),
         )
+        .arg(
+            opt(
+                "ref",
+                "Git reference to use when installing from git (unstable)",
+            )
+            .value_name("REF")
+            .requires("git")
+            .conflicts_with_all(&["branch", "tag", "rev"]),
+        )
         .arg(
             opt("path", "Filesystem path to local crate to install from")
                 .value_name("PATH")
@@ -153,6 +162,14 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
             GitReference::Tag(tag.clone())
         } else if let Some(rev) = args.get_one::<String>("rev") {
             GitReference::Rev(rev.clone())
+        } else if let Some(name) = args.get_one::<String>("ref") {
+            gctx.cli_unstable().fail_if_stable_opt("--ref", None)?;
+            GitReference::from_ref_name(name).ok_or_else(|| {
+                anyhow!(
+                    "invalid git reference `{name}`, expected a full reference name like `refs/pull/123/head`
+    Use `--branch`, `--tag` or `--rev` to install from a branch, tag or commit."
+                )
+            })?
         } else {
             GitReference::DefaultBranch
         };
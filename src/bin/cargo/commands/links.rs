@@ -0,0 +1,22 @@
+use crate::command_prelude::*;
+use cargo::ops::cargo_links;
+
+pub fn cli() -> Command {
+    subcommand("links")
+        .about("List the `links` values of the dependencies and the packages owning them")
+        .arg_silent_suggestion()
+        .arg_manifest_path()
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "links",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let ws = args.workspace(gctx)?;
+    cargo_links::links(&ws)?;
+    Ok(())
+}
@@ -13,6 +13,12 @@ pub fn cli() -> Command {
             "TRIPLE",
             "Only include resolve dependencies matching the given target-triple",
         ))
+        .arg(multi_opt(
+            "filter-spec",
+            "SPEC",
+            "Only output the given packages and their dependencies (unstable)",
+        ))
+        .arg_package_filter()
         .arg(flag(
             "no-deps",
             "Output information only about the workspace members \
@@ -23,6 +29,18 @@ pub fn cli() -> Command {
                 .value_name("VERSION")
                 .value_parser(["1"]),
         )
+        .arg(
+            opt("format", "Output format (unstable)")
+                .value_name("FORMAT")
+                .value_parser(["json", "ndjson"]),
+        )
+        .arg(
+            opt(
+                "profile",
+                "Report the settings of the given profile (unstable)",
+            )
+            .value_name("PROFILE-NAME"),
+        )
         .arg_silent_suggestion()
         .arg_features()
         .arg_manifest_path()
@@ -45,14 +63,38 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         Some(version) => version.parse().unwrap(),
     };
 
+    let filter_specs = args._values_of("filter-spec");
+    if !filter_specs.is_empty() {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--filter-spec", None)?;
+    }
+
+    let package_filter = args.package_filter(gctx)?;
+
+    if args.contains_id("profile") {
+        gctx.cli_unstable().fail_if_stable_opt("--profile", None)?;
+    }
+    let profile = args.get_profile_name(gctx, "dev", ProfileChecking::Custom)?;
+
     let options = OutputMetadataOptions {
         cli_features: args.cli_features()?,
         no_deps: args.flag("no-deps"),
         filter_platforms: args._values_of("filter-platform"),
+        filter_specs,
+        package_filter,
         version,
+        profile,
     };
 
-    let result = ops::output_metadata(&ws, &options)?;
-    gctx.shell().print_json(&result)?;
+    match args.get_one::<String>("format").map(String::as_str) {
+        Some("ndjson") => {
+            gctx.cli_unstable().fail_if_stable_opt("--format", None)?;
+            ops::output_metadata_ndjson(&ws, &options)?;
+        }
+        _ => {
+            let result = ops::output_metadata(&ws, &options)?;
+            gctx.shell().print_json(&result)?;
+        }
+    }
     Ok(())
 }
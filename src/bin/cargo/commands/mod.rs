@@ -8,23 +8,31 @@ pub fn builtin() -> Vec<Command> {
         check::cli(),
         clean::cli(),
         config::cli(),
+        daemon::cli(),
+        dedupe::cli(),
         doc::cli(),
+        explain::cli(),
+        features::cli(),
         fetch::cli(),
         fix::cli(),
+        fuzz_build::cli(),
         generate_lockfile::cli(),
         git_checkout::cli(),
         help::cli(),
         init::cli(),
         install::cli(),
+        links::cli(),
         locate_project::cli(),
         login::cli(),
         logout::cli(),
         metadata::cli(),
         new::cli(),
         owner::cli(),
+        owner_of::cli(),
         package::cli(),
         pkgid::cli(),
         publish::cli(),
+        query::cli(),
         read_manifest::cli(),
         remove::cli(),
         report::cli(),
@@ -32,11 +40,13 @@ pub fn builtin() -> Vec<Command> {
         rustc::cli(),
         rustdoc::cli(),
         search::cli(),
+        server::cli(),
         test::cli(),
         tree::cli(),
         uninstall::cli(),
         update::cli(),
         vendor::cli(),
+        verify_lockfile::cli(),
         verify_project::cli(),
         version::cli(),
         yank::cli(),
@@ -53,23 +63,31 @@ pub fn builtin_exec(cmd: &str) -> Option<Exec> {
         "check" => check::exec,
         "clean" => clean::exec,
         "config" => config::exec,
+        "daemon" => daemon::exec,
+        "dedupe" => dedupe::exec,
         "doc" => doc::exec,
+        "explain" => explain::exec,
+        "features" => features::exec,
         "fetch" => fetch::exec,
         "fix" => fix::exec,
+        "fuzz-build" => fuzz_build::exec,
         "generate-lockfile" => generate_lockfile::exec,
         "git-checkout" => git_checkout::exec,
         "help" => help::exec,
         "init" => init::exec,
         "install" => install::exec,
+        "links" => links::exec,
         "locate-project" => locate_project::exec,
         "login" => login::exec,
         "logout" => logout::exec,
         "metadata" => metadata::exec,
         "new" => new::exec,
         "owner" => owner::exec,
+        "owner-of" => owner_of::exec,
         "package" => package::exec,
         "pkgid" => pkgid::exec,
         "publish" => publish::exec,
+        "query" => query::exec,
         "read-manifest" => read_manifest::exec,
         "remove" => remove::exec,
         "report" => report::exec,
@@ -77,11 +95,13 @@ pub fn builtin_exec(cmd: &str) -> Option<Exec> {
         "rustc" => rustc::exec,
         "rustdoc" => rustdoc::exec,
         "search" => search::exec,
+        "server" => server::exec,
         "test" => test::exec,
         "tree" => tree::exec,
         "uninstall" => uninstall::exec,
         "update" => update::exec,
         "vendor" => vendor::exec,
+        "verify-lockfile" => verify_lockfile::exec,
         "verify-project" => verify_project::exec,
         "version" => version::exec,
         "yank" => yank::exec,
@@ -96,23 +116,31 @@ pub mod build;
 pub mod check;
 pub mod clean;
 pub mod config;
+pub mod daemon;
+pub mod dedupe;
 pub mod doc;
+pub mod explain;
+pub mod features;
 pub mod fetch;
 pub mod fix;
+pub mod fuzz_build;
 pub mod generate_lockfile;
 pub mod git_checkout;
 pub mod help;
 pub mod init;
 pub mod install;
+pub mod links;
 pub mod locate_project;
 pub mod login;
 pub mod logout;
 pub mod metadata;
 pub mod new;
 pub mod owner;
+pub mod owner_of;
 pub mod package;
 pub mod pkgid;
 pub mod publish;
+pub mod query;
 pub mod read_manifest;
 pub mod remove;
 pub mod report;
@@ -120,11 +148,13 @@ pub mod run;
 pub mod rustc;
 pub mod rustdoc;
 pub mod search;
+pub mod server;
 pub mod test;
 pub mod tree;
 pub mod uninstall;
 pub mod update;
 pub mod vendor;
+pub mod verify_lockfile;
 pub mod verify_project;
 pub mod version;
 pub mod yank;
@@ -0,0 +1,32 @@
+use crate::command_prelude::*;
+use cargo::ops::cargo_owner_of;
+use std::path::PathBuf;
+
+pub fn cli() -> Command {
+    subcommand("owner-of")
+        .about("Print the workspace member and targets which own a file")
+        .arg(
+            Arg::new("path")
+                .value_name("PATH")
+                .action(ArgAction::Set)
+                .value_parser(clap::value_parser!(PathBuf))
+                .required(true)
+                .help("Path of the file"),
+        )
+        .arg_silent_suggestion()
+        .arg_manifest_path()
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "owner-of",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let ws = args.workspace(gctx)?;
+    let path = args.get_one::<PathBuf>("path").unwrap();
+    cargo_owner_of::owner_of(&ws, path)?;
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use crate::command_prelude::*;
 
 use cargo::ops::{self, PackageOpts};
+use cargo_util::Sha256;
 
 pub fn cli() -> Command {
     subcommand("package")
@@ -24,6 +25,14 @@ pub fn cli() -> Command {
             "allow-dirty",
             "Allow dirty working directories to be packaged",
         ))
+        .arg(flag(
+            "exclude-lockfile",
+            "Don't include Cargo.lock in the package (unstable)",
+        ))
+        .arg(flag(
+            "output-hash",
+            "Print the SHA-256 digest of each packaged tarball (unstable)",
+        ))
         .arg_silent_suggestion()
         .arg_package_spec_no_all(
             "Package(s) to assemble",
@@ -50,8 +59,18 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         .into());
     }
     let specs = args.packages_from_flags()?;
+    let exclude_lockfile = args.flag("exclude-lockfile");
+    if exclude_lockfile {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--exclude-lockfile", None)?;
+    }
+    let output_hash = args.flag("output-hash");
+    if output_hash {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--output-hash", None)?;
+    }
 
-    ops::package(
+    let tarballs = ops::package(
         &ws,
         &PackageOpts {
             gctx,
@@ -59,6 +78,7 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
             list: args.flag("list"),
             check_metadata: !args.flag("no-metadata"),
             allow_dirty: args.flag("allow-dirty"),
+            exclude_lockfile,
             to_package: specs,
             targets: args.targets()?,
             jobs: args.jobs()?,
@@ -67,5 +87,13 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         },
     )?;
 
+    if output_hash {
+        for tarball in tarballs.into_iter().flatten() {
+            let path = tarball.path();
+            let hash = Sha256::new().update_path(path)?.finish_hex();
+            cargo::drop_println!(gctx, "{}  {}", hash, path.display());
+        }
+    }
+
     Ok(())
 }
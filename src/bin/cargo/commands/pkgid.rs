@@ -7,6 +7,10 @@ pub fn cli() -> Command {
     subcommand("pkgid")
         .about("Print a fully qualified package specification")
         .arg(Arg::new("spec").value_name("SPEC").action(ArgAction::Set))
+        .arg(flag(
+            "json",
+            "Output the package ID specification as JSON (unstable)",
+        ))
         .arg_silent_suggestion()
         .arg_package("Argument to get the package ID specifier for")
         .arg_manifest_path()
@@ -16,6 +20,10 @@ pub fn cli() -> Command {
 }
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    let json = args.flag("json");
+    if json {
+        gctx.cli_unstable().fail_if_stable_opt("--json", None)?;
+    }
     let ws = args.workspace(gctx)?;
     if ws.root_maybe().is_embedded() {
         return Err(anyhow::format_err!(
@@ -32,6 +40,10 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         .or_else(|| args.get_one::<String>("package"))
         .map(String::as_str);
     let spec = ops::pkgid(&ws, spec)?;
-    cargo::drop_println!(gctx, "{}", spec);
+    if json {
+        gctx.shell().print_json(&spec.fields())?;
+    } else {
+        cargo::drop_println!(gctx, "{}", spec);
+    }
     Ok(())
 }
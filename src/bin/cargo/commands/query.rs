@@ -0,0 +1,38 @@
+use crate::command_prelude::*;
+use cargo::ops::cargo_query;
+
+pub fn cli() -> Command {
+    subcommand("query")
+        .about("Query the resolved dependency graph of the workspace")
+        .arg(
+            Arg::new("expr")
+                .value_name("EXPR")
+                .action(ArgAction::Set)
+                .required(true)
+                .help("The query expression to evaluate, e.g. `deps(foo) - workspace()`"),
+        )
+        .arg(
+            opt("format", "Output format")
+                .value_parser(cargo_query::QueryFormat::POSSIBLE_VALUES)
+                .default_value("list"),
+        )
+        .arg_silent_suggestion()
+        .arg_manifest_path()
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "query",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let ws = args.workspace(gctx)?;
+    let opts = cargo_query::QueryOptions {
+        expr: args.get_one::<String>("expr").unwrap(),
+        format: args.get_one::<String>("format").unwrap().parse()?,
+    };
+    cargo_query::query(&ws, &opts)?;
+    Ok(())
+}
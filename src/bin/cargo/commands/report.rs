@@ -1,6 +1,5 @@
 use crate::command_prelude::*;
-use cargo::core::compiler::future_incompat::{OnDiskReports, REPORT_PREAMBLE};
-use cargo::drop_println;
+use cargo::ops::cargo_report::{self, ReportOptions};
 
 pub fn cli() -> Command {
     subcommand("report")
@@ -21,13 +20,58 @@ pub fn cli() -> Command {
                     )
                     .value_name("id"),
                 )
-                .arg_package("Package to display a report for"),
+                .arg_package("Package to display a report for")
+                .arg(flag("json", "Output the report as JSON (unstable)")),
+        )
+        .subcommand(
+            subcommand("builds")
+                .about("Reports summaries of previous builds (unstable)")
+                .arg(opt("id", "identifier of the build to display").value_name("id"))
+                .arg(flag("json", "Output the summaries as JSON")),
+        )
+        .subcommand(
+            subcommand("timings")
+                .about("Lists the timing reports of previous builds (unstable)")
+                .arg(flag("json", "Output the list as JSON")),
+        )
+        .subcommand(
+            subcommand("prune")
+                .about("Removes all but the most recent reports (unstable)")
+                .arg(
+                    opt("keep", "Number of reports of each kind to keep")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("1"),
+                ),
         )
 }
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     match args.subcommand() {
         Some(("future-incompatibilities", args)) => report_future_incompatibilities(gctx, args),
+        Some((cmd @ ("builds" | "timings" | "prune"), args)) => {
+            gctx.cli_unstable().fail_if_stable_command(
+                gctx,
+                &format!("report {cmd}"),
+                None,
+                "unstable-options",
+                gctx.cli_unstable().unstable_options,
+            )?;
+            let ws = args.workspace(gctx)?;
+            match cmd {
+                "builds" => cargo_report::report_builds(
+                    &ws,
+                    &ReportOptions {
+                        id: args.value_of_u32("id")?,
+                        package: None,
+                        json: args.flag("json"),
+                    },
+                )?,
+                "timings" => cargo_report::report_timings(&ws, args.flag("json"))?,
+                _ => cargo_report::prune_reports(&ws, *args.get_one::<usize>("keep").unwrap())?,
+            }
+            Ok(())
+        }
         Some((cmd, _)) => {
             unreachable!("unexpected command {}", cmd)
         }
@@ -38,14 +82,16 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
 }
 
 fn report_future_incompatibilities(gctx: &GlobalContext, args: &ArgMatches) -> CliResult {
+    let json = args.flag("json");
+    if json {
+        gctx.cli_unstable().fail_if_stable_opt("--json", None)?;
+    }
     let ws = args.workspace(gctx)?;
-    let reports = OnDiskReports::load(&ws)?;
-    let id = args
-        .value_of_u32("id")?
-        .unwrap_or_else(|| reports.last_id());
-    let krate = args.get_one::<String>("package").map(String::as_str);
-    let report = reports.get_report(id, krate)?;
-    drop_println!(gctx, "{}", REPORT_PREAMBLE);
-    drop(gctx.shell().print_ansi_stdout(report.as_bytes()));
+    let opts = ReportOptions {
+        id: args.value_of_u32("id")?,
+        package: args.get_one::<String>("package").map(String::as_str),
+        json,
+    };
+    cargo_report::report_future_incompat(&ws, &opts)?;
     Ok(())
 }
@@ -24,6 +24,17 @@ pub fn cli() -> Command {
                 .num_args(0..)
                 .trailing_var_arg(true),
         )
+        .arg(
+            opt(
+                "preset",
+                "Prepend the arguments of the named `[run.args]` preset (unstable)",
+            )
+            .value_name("NAME"),
+        )
+        .arg(flag(
+            "no-build",
+            "Run the previously built artifact, failing if it is stale (unstable)",
+        ))
         .arg_message_format()
         .arg_silent_suggestion()
         .arg_package("Package with the target to run")
@@ -83,7 +94,18 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         }
     };
 
-    ops::run(&ws, &compile_opts, &values_os(args, "args")).map_err(|err| to_run_error(gctx, err))
+    if args.flag("no-build") {
+        gctx.cli_unstable().fail_if_stable_opt("--no-build", None)?;
+        compile_opts.build_config.no_build = true;
+    }
+
+    let preset = args.get_one::<String>("preset").map(String::as_str);
+    if preset.is_some() {
+        gctx.cli_unstable().fail_if_stable_opt("--preset", None)?;
+    }
+
+    ops::run(&ws, &compile_opts, preset, &values_os(args, "args"))
+        .map_err(|err| to_run_error(gctx, err))
 }
 
 /// See also `util/toml/mod.rs`s `is_embedded`
@@ -188,7 +210,7 @@ pub fn exec_manifest_command(gctx: &mut GlobalContext, cmd: &str, args: &[OsStri
         cargo::ops::CompileOptions::new(gctx, cargo::core::compiler::CompileMode::Build)?;
     compile_opts.spec = cargo::ops::Packages::Default;
 
-    cargo::ops::run(&ws, &compile_opts, args).map_err(|err| to_run_error(gctx, err))
+    cargo::ops::run(&ws, &compile_opts, None, args).map_err(|err| to_run_error(gctx, err))
 }
 
 fn suggested_script(cmd: &str) -> Option<String> {
@@ -81,6 +81,7 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     let doc_opts = DocOptions {
         open_result: args.flag("open"),
         output_format,
+        features_table: false,
         compile_opts,
     };
     ops::doc(&ws, &doc_opts)?;
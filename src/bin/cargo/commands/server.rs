@@ -0,0 +1,113 @@
+use crate::cli;
+use crate::command_prelude::*;
+
+use cargo::core::Workspace;
+use cargo::ops::cargo_server::{self, Dispatch};
+use cargo::CargoResult;
+use clap::parser::ValueSource;
+
+use super::{build, check};
+
+pub fn cli() -> Command {
+    subcommand("server")
+        .about("Keep the workspace in memory to run `cargo build` and `cargo check` faster")
+        .arg(flag("stop", "Stop the build server of the workspace"))
+        .arg_silent_suggestion()
+        .arg_manifest_path()
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "server",
+        None,
+        "build-server",
+        gctx.cli_unstable().build_server,
+    )?;
+    let root_manifest = args.root_manifest(gctx)?;
+    if args.flag("stop") {
+        cargo_server::stop(gctx, &root_manifest)?;
+        return Ok(());
+    }
+    let options = global_options(args);
+    cargo_server::serve(gctx, &root_manifest, &mut |ws, client_args| {
+        dispatch(ws, client_args, &options)
+    })?;
+    Ok(())
+}
+
+/// Parses the arguments of a client like the `cargo` it runs would, to build
+/// the same way.
+fn dispatch(
+    ws: &mut Workspace<'_>,
+    client_args: &[String],
+    server_options: &[String],
+) -> CargoResult<Dispatch> {
+    let gctx = ws.gctx();
+    let args = std::iter::once("cargo").chain(client_args.iter().map(String::as_str));
+    let matches = match cli::cli(gctx).try_get_matches_from(args) {
+        Ok(matches) => matches,
+        Err(e) => return Ok(Dispatch::Refuse(e.kind().to_string())),
+    };
+    let Some((cmd, args)) = matches.subcommand() else {
+        return Ok(Dispatch::Refuse("no command was given".to_string()));
+    };
+    if !matches!(cmd, "build" | "check") {
+        return Ok(Dispatch::Refuse(format!("`cargo {cmd}` isn't supported")));
+    }
+    if global_options(args) != server_options {
+        return Ok(Dispatch::Refuse(
+            "the global options differ from the server's".to_string(),
+        ));
+    }
+    // The server only forwards stderr, and can't change the target directory
+    // or the workspace of the client.
+    for id in [
+        "target-dir",
+        "message-format",
+        "unit-graph",
+        "build-plan",
+        "manifest-list",
+    ] {
+        if args.ids().any(|given| given.as_str() == id)
+            && args.value_source(id) == Some(ValueSource::CommandLine)
+        {
+            return Ok(Dispatch::Refuse(format!("`--{id}` isn't supported")));
+        }
+    }
+
+    ws.set_resolve_honors_rust_version(args.honor_rust_version());
+    if gctx.cli_unstable().avoid_dev_deps {
+        ws.set_require_optional_deps(false);
+    }
+    let compile_opts = match cmd {
+        "build" => build::compile_options(gctx, args, ws)?,
+        _ => check::compile_options(gctx, args, ws)?,
+    };
+    Ok(Dispatch::Compile(compile_opts))
+}
+
+/// The options which configure the whole `cargo` process, which the server
+/// can't change for each build.
+fn global_options(args: &ArgMatches) -> Vec<String> {
+    let mut options = vec![format!("verbose={}", args.verbose())];
+    for flag in ["quiet", "frozen", "locked", "offline"] {
+        if args.flag(flag) {
+            options.push(flag.to_string());
+        }
+    }
+    for opt in [
+        "color",
+        "ui",
+        "config",
+        "enable-patch-set",
+        "unstable-features",
+    ] {
+        options.extend(
+            args._values_of(opt)
+                .into_iter()
+                .map(|value| format!("{opt}={value}")),
+        );
+    }
+    options
+}
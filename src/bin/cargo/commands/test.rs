@@ -20,6 +20,29 @@ pub fn cli() -> Command {
         )
         .arg(flag("no-run", "Compile, but don't run tests"))
         .arg(flag("no-fail-fast", "Run all tests regardless of failure"))
+        .arg(
+            opt(
+                "runner",
+                "Run tests with the given labeled runner (unstable)",
+            )
+            .value_name("LABEL")
+            .conflicts_with("all-runners"),
+        )
+        .arg(flag(
+            "all-runners",
+            "Run tests once with each labeled runner (unstable)",
+        ))
+        .arg(
+            flag(
+                "cache-results",
+                "Skip test binaries which passed with the same inputs (unstable)",
+            )
+            .conflicts_with("no-cache"),
+        )
+        .arg(flag(
+            "no-cache",
+            "Run all test binaries, ignoring `test.cache-results` (unstable)",
+        ))
         .arg_future_incompat_report()
         .arg_message_format()
         .arg(
@@ -50,6 +73,14 @@ pub fn cli() -> Command {
             flag("doc", "Test only this library's documentation")
                 .help_heading(heading::TARGET_SELECTION),
         )
+        .arg(
+            flag(
+                "fuzz-smoke",
+                "Also build the fuzz targets and run each of them once (unstable)",
+            )
+            .conflicts_with("doc")
+            .help_heading(heading::TARGET_SELECTION),
+        )
         .arg_features()
         .arg_jobs()
         .arg_unsupported_keep_going()
@@ -104,9 +135,39 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         compile_opts.filter = ops::CompileFilter::all_test_targets();
     }
 
+    if args.flag("fuzz-smoke") {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--fuzz-smoke", None)?;
+        compile_opts.fuzz = ops::FilterRule::All;
+    }
+
+    let runners = if let Some(label) = args.get_one::<String>("runner") {
+        gctx.cli_unstable().fail_if_stable_opt("--runner", None)?;
+        ops::TestRunners::Label(label.clone())
+    } else if args.flag("all-runners") {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--all-runners", None)?;
+        ops::TestRunners::All
+    } else {
+        ops::TestRunners::Default
+    };
+
+    let cache_results = if args.flag("cache-results") {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--cache-results", None)?;
+        Some(true)
+    } else if args.flag("no-cache") {
+        gctx.cli_unstable().fail_if_stable_opt("--no-cache", None)?;
+        Some(false)
+    } else {
+        None
+    };
+
     let ops = ops::TestOptions {
         no_run,
         no_fail_fast: args.flag("no-fail-fast"),
+        runners,
+        cache_results,
         compile_opts,
     };
 
@@ -42,6 +42,7 @@ pub fn cli() -> Command {
             "SPEC",
             "Prune the given package from the display of the dependency tree",
         ))
+        .arg_package_filter()
         .arg(opt("depth", "Maximum display depth of the dependency tree").value_name("DEPTH"))
         .arg(flag("no-indent", "Deprecated, use --prefix=none instead").hide(true))
         .arg(flag("prefix-depth", "Deprecated, use --prefix=depth instead").hide(true))
@@ -77,6 +78,36 @@ pub fn cli() -> Command {
                 .short('f')
                 .default_value("{p}"),
         )
+        .arg(
+            opt(
+                "emit",
+                "Print an inventory of the given kind instead of the tree (unstable)",
+            )
+            .value_name("KIND")
+            .value_parser(["licenses"])
+            .conflicts_with_all(["invert", "duplicates"]),
+        )
+        .arg(
+            opt("emit-format", "Output format of --emit (unstable)")
+                .value_name("FMT")
+                .value_parser(clap::value_parser!(tree::LicensesFormat))
+                .requires("emit"),
+        )
+        .arg(
+            opt(
+                "why-feature",
+                "Show what enables the given PACKAGE/FEATURE (unstable)",
+            )
+            .value_name("SPEC")
+            .conflicts_with_all(["invert", "duplicates", "emit"]),
+        )
+        .arg(
+            flag(
+                "sizes",
+                "Show the download and unpacked size of each package (unstable)",
+            )
+            .conflicts_with("emit"),
+        )
         .arg(
             // Backwards compatibility with old cargo-tree.
             flag("version", "Print version info and exit")
@@ -156,10 +187,25 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     };
     let target = tree::Target::from_cli(targets);
 
-    let (edge_kinds, no_proc_macro) = parse_edge_kinds(gctx, args)?;
+    let why_feature = args.get_one::<String>("why-feature").cloned();
+    if why_feature.is_some() {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--why-feature", None)?;
+    }
+
+    let sizes = args.flag("sizes");
+    if sizes {
+        gctx.cli_unstable().fail_if_stable_opt("--sizes", None)?;
+    }
+
+    let (mut edge_kinds, no_proc_macro) = parse_edge_kinds(gctx, args)?;
+    if why_feature.is_some() {
+        edge_kinds.insert(EdgeKind::Feature);
+    }
     let graph_features = edge_kinds.contains(&EdgeKind::Feature);
 
     let pkgs_to_prune = args._values_of("prune");
+    let package_filter = args.package_filter(gctx)?;
 
     let packages = args.packages_from_flags()?;
     let mut invert = args
@@ -192,6 +238,26 @@ subtree of the package given to -p.\n\
         }
     }
 
+    let emit_licenses = if args.contains_id("emit") {
+        gctx.cli_unstable().fail_if_stable_opt("--emit", None)?;
+        let format = args.get_one("emit-format").copied();
+        Some(format.unwrap_or(tree::LicensesFormat::Human))
+    } else {
+        None
+    };
+
+    let format = args.get_one::<String>("format").cloned().unwrap();
+    // Without `-Zunstable-options`, these stay format strings as they always
+    // were on stable.
+    let graph_format = if gctx.cli_unstable().unstable_options {
+        <tree::GraphFormat as clap::ValueEnum>::from_str(&format, false).ok()
+    } else {
+        None
+    };
+    if graph_format.is_some() && emit_licenses.is_some() {
+        return Err(format_err!("`--format {format}` cannot be used with `--emit`").into());
+    }
+
     let ws = args.workspace(gctx)?;
 
     if args.is_present_with_zero_values("package") {
@@ -216,13 +282,18 @@ subtree of the package given to -p.\n\
         edge_kinds,
         invert,
         pkgs_to_prune,
+        package_filter,
         prefix,
         no_dedupe,
         duplicates: args.flag("duplicates"),
-        format: args.get_one::<String>("format").cloned().unwrap(),
+        format,
         graph_features,
         max_display_depth: args.value_of_u32("depth")?.unwrap_or(u32::MAX),
         no_proc_macro,
+        emit_licenses,
+        graph_format,
+        why_feature,
+        sizes,
     };
 
     if opts.graph_features && opts.duplicates {
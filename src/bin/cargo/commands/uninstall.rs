@@ -7,6 +7,17 @@ pub fn cli() -> Command {
         .about("Remove a Rust binary")
         .arg(Arg::new("spec").value_name("SPEC").num_args(0..))
         .arg(opt("root", "Directory to uninstall packages from").value_name("DIR"))
+        .arg(flag(
+            "purge",
+            "Also remove the package's downloaded sources (unstable)",
+        ))
+        .arg(
+            flag(
+                "all",
+                "Uninstall all packages, after confirmation (unstable)",
+            )
+            .conflicts_with_all(["spec", "package", "bin"]),
+        )
         .arg_silent_suggestion()
         .arg_package_spec_simple("Package to uninstall")
         .arg(
@@ -20,6 +31,15 @@ pub fn cli() -> Command {
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     let root = args.get_one::<String>("root").map(String::as_str);
+    let purge = args.flag("purge");
+    if purge {
+        gctx.cli_unstable().fail_if_stable_opt("--purge", None)?;
+    }
+    if args.flag("all") {
+        gctx.cli_unstable().fail_if_stable_opt("--all", None)?;
+        ops::uninstall_all(root, purge, gctx)?;
+        return Ok(());
+    }
 
     if args.is_present_with_zero_values("package") {
         return Err(anyhow::anyhow!(
@@ -34,6 +54,6 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         .unwrap_or_else(|| args.get_many::<String>("package").unwrap_or_default())
         .map(String::as_str)
         .collect();
-    ops::uninstall(root, specs, &values(args, "bin"), gctx)?;
+    ops::uninstall(root, specs, &values(args, "bin"), purge, gctx)?;
     Ok(())
 }
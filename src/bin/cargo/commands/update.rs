@@ -1,8 +1,9 @@
 use crate::command_prelude::*;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context as _};
 use cargo::ops::{self, UpdateOptions};
 use cargo::util::print_available_packages;
+use cargo_util_schemas::core::PartialVersion;
 
 pub fn cli() -> Command {
     subcommand("update")
@@ -35,6 +36,40 @@ pub fn cli() -> Command {
                 .value_name("PRECISE")
                 .requires("package-group"),
         )
+        .arg(
+            flag(
+                "breaking",
+                "Update [SPEC] to latest SemVer-breaking version (unstable)",
+            )
+            .conflicts_with_all(["precise", "recursive", "workspace"]),
+        )
+        .arg_before()
+        .arg(
+            opt(
+                "rust-version",
+                "Prefer the newest versions compatible with this Rust version (unstable)",
+            )
+            .value_name("VER")
+            .conflicts_with_all(["precise", "breaking"]),
+        )
+        .arg(
+            opt(
+                "resolution",
+                "Resolve dependencies to `maximum`, `minimal` or `direct-minimal` versions",
+            )
+            .value_name("MODE")
+            .value_parser(["maximum", "minimal", "direct-minimal"])
+            .hide_possible_values(true),
+        )
+        .arg(
+            optional_opt(
+                "changelog",
+                "Print links to the changes of updated packages (unstable)",
+            )
+            .value_name("FMT")
+            .value_parser(clap::value_parser!(ops::ChangelogFormat))
+            .default_missing_value("human"),
+        )
         .arg_silent_suggestion()
         .arg(
             flag("workspace", "Only update the workspace packages")
@@ -59,7 +94,27 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
             gctx.cli_unstable().msrv_policy,
         )?;
     }
-    let ws = args.workspace(gctx)?;
+    let before = args.before()?;
+    if before.is_some() {
+        gctx.cli_unstable().fail_if_stable_opt("--before", None)?;
+    }
+    let rust_version = args
+        .get_one::<String>("rust-version")
+        .map(|v| {
+            v.parse::<PartialVersion>()
+                .with_context(|| format!("invalid value `{v}` for `--rust-version`"))
+        })
+        .transpose()?;
+    if rust_version.is_some() {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--rust-version", None)?;
+    }
+    let mut ws = args.workspace(gctx)?;
+    ws.set_max_publish_time(before);
+    ws.set_max_rust_version(rust_version);
+    if let Some(resolution) = args.get_one::<String>("resolution") {
+        ws.set_resolution_mode(Some(resolution.parse()?));
+    }
 
     if args.is_present_with_zero_values("package") {
         print_available_packages(&ws)?;
@@ -81,14 +136,34 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         }
     }
 
+    let changelog: Option<ops::ChangelogFormat> = args.get_one("changelog").copied();
+    if changelog.is_some() {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--changelog", None)?;
+    }
+
     let update_opts = UpdateOptions {
         recursive: args.flag("recursive"),
         precise: args.get_one::<String>("precise").map(String::as_str),
         to_update,
         dry_run: args.dry_run(),
         workspace: args.flag("workspace"),
+        changelog,
         gctx,
     };
-    ops::update_lockfile(&ws, &update_opts)?;
+    if args.flag("breaking") {
+        gctx.cli_unstable().fail_if_stable_opt("--breaking", None)?;
+        let upgrades = ops::upgrade_manifests(&mut ws, &update_opts.to_update)?;
+        ops::resolve_ws_with_dry_run(&ws, update_opts.dry_run)?;
+        ops::write_manifest_upgrades(&ws, &upgrades, update_opts.dry_run)?;
+        if update_opts.dry_run {
+            update_opts
+                .gctx
+                .shell()
+                .warn("aborting update due to dry run")?;
+        }
+    } else {
+        ops::update_lockfile(&ws, &update_opts)?;
+    }
     Ok(())
 }
@@ -32,10 +32,20 @@ pub fn cli() -> Command {
             "versioned-dirs",
             "Always include version in subdir name",
         ))
+        .arg(flag(
+            "only-used",
+            "Only vendor crates needed by the selected targets (unstable)",
+        ))
+        .arg(flag(
+            "all-targets",
+            "Also vendor crates used by tests, benches and examples",
+        ))
         .arg(unsupported("no-merge-sources"))
         .arg(unsupported("relative-path"))
         .arg(unsupported("only-git-deps"))
         .arg(unsupported("disallow-duplicates"))
+        .arg_features()
+        .arg_target_triple("Vendor crates used for the target triple")
         .arg_manifest_path()
         .after_help(color_print::cstr!(
             "Run `<cyan,bold>cargo help vendor</>` for more detailed information.\n"
@@ -64,6 +74,28 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     }
 
     let ws = args.workspace(gctx)?;
+    let only_used = if args.flag("only-used") {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--only-used", None)?;
+        let mut compile_opts =
+            args.compile_options(gctx, CompileMode::Build, Some(&ws), ProfileChecking::Custom)?;
+        if args.flag("all-targets") {
+            compile_opts.filter = ops::CompileFilter::new_all_targets();
+        }
+        Some(compile_opts)
+    } else {
+        for flag in ["all-targets", "all-features", "no-default-features"] {
+            if args.flag(flag) {
+                return Err(anyhow::format_err!("`--{flag}` requires `--only-used`").into());
+            }
+        }
+        for opt in ["features", "target"] {
+            if args.contains_id(opt) {
+                return Err(anyhow::format_err!("`--{opt}` requires `--only-used`").into());
+            }
+        }
+        None
+    };
     let path = args
         .get_one::<PathBuf>("path")
         .cloned()
@@ -74,6 +106,7 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
             no_delete: args.flag("no-delete"),
             destination: &path,
             versioned_dirs: args.flag("versioned-dirs"),
+            only_used,
             extra: args
                 .get_many::<PathBuf>("tomls")
                 .unwrap_or_default()
@@ -0,0 +1,30 @@
+use crate::command_prelude::*;
+use cargo::ops::cargo_verify_lockfile;
+
+pub fn cli() -> Command {
+    subcommand("verify-lockfile")
+        .about("Check that Cargo.lock is consistent, up to date, and free of yanked versions")
+        .arg(
+            opt("format", "Output format")
+                .value_parser(clap::value_parser!(cargo_verify_lockfile::VerifyLockfileFormat))
+                .default_value("human"),
+        )
+        .arg_silent_suggestion()
+        .arg_manifest_path()
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "verify-lockfile",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let ws = args.workspace(gctx)?;
+    let opts = cargo_verify_lockfile::VerifyLockfileOptions {
+        format: *args.get_one("format").unwrap(),
+    };
+    cargo_verify_lockfile::verify_lockfile(&ws, &opts)?;
+    Ok(())
+}
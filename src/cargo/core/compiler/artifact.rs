@@ -1,12 +1,14 @@
 //! Generate artifact information from unit dependencies for configuring the compiler environment.
 
 use crate::core::compiler::unit_graph::UnitDep;
-use crate::core::compiler::{BuildRunner, CrateType, FileFlavor, Unit};
-use crate::core::dependency::ArtifactKind;
-use crate::core::{Dependency, Target, TargetKind};
+use crate::core::compiler::{BuildRunner, CompileMode, CrateType, FileFlavor, Unit};
+use crate::core::dependency::{ArtifactKind, ArtifactTarget, DepKind};
+use crate::core::{Dependency, FeatureValue, Target, TargetKind};
 use crate::CargoResult;
+use crate::GlobalContext;
 use std::collections::{HashMap, HashSet};
 use std::ffi::OsString;
+use std::path::Path;
 
 /// Return all environment variables for the given unit-dependencies
 /// if artifacts are present.
@@ -21,57 +23,158 @@ pub fn get_env(
             .iter()
             .filter_map(|f| (f.flavor == FileFlavor::Normal).then(|| &f.path))
         {
-            let artifact_type_upper = unit_artifact_type_name_upper(&unit_dep.unit);
             let dep_name = unit_dep.dep_name.unwrap_or(unit_dep.unit.pkg.name());
-            let dep_name_upper = dep_name.to_uppercase().replace("-", "_");
-
-            let var = format!("CARGO_{}_DIR_{}", artifact_type_upper, dep_name_upper);
-            let path = artifact_path.parent().expect("parent dir for artifacts");
-            env.insert(var, path.to_owned().into());
-
-            let var_file = format!(
-                "CARGO_{}_FILE_{}_{}",
-                artifact_type_upper,
-                dep_name_upper,
-                unit_dep.unit.target.name()
+            insert_env(
+                &mut env,
+                &unit_dep.unit.target,
+                &dep_name,
+                &unit_dep.unit.pkg.name(),
+                artifact_path,
             );
+        }
+    }
+    Ok(env)
+}
 
-            // In older releases, lib-targets defaulted to the name of the package. Newer releases
-            // use the same name as default, but with dashes replaced. Hence, if the name of the
-            // target was inferred by Cargo, we also set the env-var with the unconverted name for
-            // backwards compatibility.
-            let need_compat = unit_dep.unit.target.is_lib() && unit_dep.unit.target.name_inferred();
-            if need_compat {
-                let var_compat = format!(
-                    "CARGO_{}_FILE_{}_{}",
-                    artifact_type_upper,
-                    dep_name_upper,
-                    unit_dep.unit.pkg.name(),
+/// Whether the binaries and C libraries of the artifact dependency `dep` are
+/// skipped for `unit`, with `-Zcheck-skip-artifacts`.
+///
+/// `cargo check` doesn't link anything, so building them, along with the
+/// build scripts and proc-macros they need, is only useful to build scripts
+/// which may run them, or to code including them, like with
+/// `include_bytes!`. Artifacts for another target are still built.
+pub(crate) fn is_unbuilt_artifact(gctx: &GlobalContext, unit: &Unit, dep: &Dependency) -> bool {
+    gctx.cli_unstable().check_skip_artifacts
+        && unit.mode.is_check()
+        && !unit.target.is_custom_build()
+        && dep
+            .artifact()
+            .is_some_and(|artifact| !matches!(artifact.target(), Some(ArtifactTarget::Force(_))))
+}
+
+/// Return the environment variables of the artifact dependencies of `unit`
+/// which aren't built, see [`is_unbuilt_artifact`].
+///
+/// Like `CARGO_BIN_EXE_*` when checking tests, they point to where the
+/// artifacts would be uplifted, so `env!` keeps working even though the files
+/// may not exist.
+pub fn get_unbuilt_env(
+    build_runner: &BuildRunner<'_, '_>,
+    unit: &Unit,
+) -> CargoResult<HashMap<String, OsString>> {
+    let mut env = HashMap::new();
+    let bcx = build_runner.bcx;
+    let dest = build_runner.files().layout(unit.kind).dest();
+    let info = bcx.target_data.info(unit.kind);
+    let deps = bcx.artifact_deps.get(&unit.pkg.package_id());
+    for (dep, dep_id) in deps.into_iter().flatten() {
+        if dep.kind() == DepKind::Build
+            || !is_unbuilt_artifact(bcx.gctx, unit, dep)
+            || !bcx.target_data.dep_platform_activated(dep, unit.kind)
+            || (dep.is_optional() && !is_activated(unit, dep))
+        {
+            continue;
+        }
+        let dep_pkg = bcx.packages.get_one(*dep_id)?;
+        let targets =
+            match_artifacts_kind_with_targets(dep, dep_pkg.targets(), unit.pkg.name().as_str())?;
+        for (artifact_kind, target) in targets {
+            let target_kind = match artifact_kind {
+                ArtifactKind::Cdylib => TargetKind::Lib(vec![CrateType::Cdylib]),
+                ArtifactKind::Staticlib => TargetKind::Lib(vec![CrateType::Staticlib]),
+                _ => TargetKind::Bin,
+            };
+            let mut target = target.clone();
+            target.set_kind(target_kind.clone());
+            let (file_types, _) = info.rustc_outputs(
+                CompileMode::Build,
+                &target_kind,
+                bcx.target_data.short_name(&unit.kind),
+            )?;
+            for file_type in file_types.iter().filter(|f| f.flavor == FileFlavor::Normal) {
+                let path = dest.join(file_type.uplift_filename(&target));
+                insert_env(
+                    &mut env,
+                    &target,
+                    &dep.name_in_toml(),
+                    &dep_pkg.name(),
+                    &path,
                 );
-                if var_compat != var_file {
-                    env.insert(var_compat, artifact_path.to_owned().into());
-                }
             }
+        }
+    }
+    Ok(env)
+}
 
-            env.insert(var_file, artifact_path.to_owned().into());
-
-            // If the name of the target matches the name of the dependency, we strip the
-            // repetition and provide the simpler env-var as well.
-            // For backwards-compatibility of inferred names, we compare against the name of the
-            // package as well, since that used to be the default for library targets.
-            if unit_dep.unit.target.name() == dep_name.as_str()
-                || (need_compat && unit_dep.unit.pkg.name() == dep_name.as_str())
-            {
-                let var = format!("CARGO_{}_FILE_{}", artifact_type_upper, dep_name_upper,);
-                env.insert(var, artifact_path.to_owned().into());
+/// Whether the optional dependency `dep` is activated by the features of
+/// `unit`.
+fn is_activated(unit: &Unit, dep: &Dependency) -> bool {
+    let feature_map = unit.pkg.summary().features();
+    unit.features
+        .iter()
+        .filter_map(|feature| feature_map.get(feature))
+        .flatten()
+        .any(|value| match value {
+            FeatureValue::Dep { dep_name } => *dep_name == dep.name_in_toml(),
+            FeatureValue::DepFeature { dep_name, weak, .. } => {
+                !weak && *dep_name == dep.name_in_toml()
             }
+            FeatureValue::Feature(_) => false,
+        })
+}
+
+/// Inserts the environment variables of the `target` artifact at
+/// `artifact_path` of the dependency named `dep_name`.
+fn insert_env(
+    env: &mut HashMap<String, OsString>,
+    target: &Target,
+    dep_name: &str,
+    pkg_name: &str,
+    artifact_path: &Path,
+) {
+    let artifact_type_upper = artifact_type_name_upper(target);
+    let dep_name_upper = dep_name.to_uppercase().replace("-", "_");
+
+    let var = format!("CARGO_{}_DIR_{}", artifact_type_upper, dep_name_upper);
+    let path = artifact_path.parent().expect("parent dir for artifacts");
+    env.insert(var, path.to_owned().into());
+
+    let var_file = format!(
+        "CARGO_{}_FILE_{}_{}",
+        artifact_type_upper,
+        dep_name_upper,
+        target.name()
+    );
+
+    // In older releases, lib-targets defaulted to the name of the package. Newer releases
+    // use the same name as default, but with dashes replaced. Hence, if the name of the
+    // target was inferred by Cargo, we also set the env-var with the unconverted name for
+    // backwards compatibility.
+    let need_compat = target.is_lib() && target.name_inferred();
+    if need_compat {
+        let var_compat = format!(
+            "CARGO_{}_FILE_{}_{}",
+            artifact_type_upper, dep_name_upper, pkg_name,
+        );
+        if var_compat != var_file {
+            env.insert(var_compat, artifact_path.to_owned().into());
         }
     }
-    Ok(env)
+
+    env.insert(var_file, artifact_path.to_owned().into());
+
+    // If the name of the target matches the name of the dependency, we strip the
+    // repetition and provide the simpler env-var as well.
+    // For backwards-compatibility of inferred names, we compare against the name of the
+    // package as well, since that used to be the default for library targets.
+    if target.name() == dep_name || (need_compat && pkg_name == dep_name) {
+        let var = format!("CARGO_{}_FILE_{}", artifact_type_upper, dep_name_upper,);
+        env.insert(var, artifact_path.to_owned().into());
+    }
 }
 
-fn unit_artifact_type_name_upper(unit: &Unit) -> &'static str {
-    match unit.target.kind() {
+fn artifact_type_name_upper(target: &Target) -> &'static str {
+    match target.kind() {
         TargetKind::Lib(kinds) => match kinds.as_slice() {
             &[CrateType::Cdylib] => "CDYLIB",
             &[CrateType::Staticlib] => "STATICLIB",
@@ -0,0 +1,885 @@
+//! A cache of the outputs of registry dependencies, shared by all workspaces.
+//!
+//! With `build.artifact-cache`, the `.rlib` and `.rmeta` files of the library
+//! units of registry packages are stored in `$CARGO_HOME/artifact-cache`, in
+//! an entry named by a hash of everything which went into compiling them:
+//!
+//! * The package, along with the checksum of its `.crate` file.
+//! * The verbose version of `rustc`.
+//! * The `rustc` invocation, with the paths into the target directory and the
+//!   workspace made relative, so that it's the same in every workspace. This
+//!   covers the flags, the features, the profile, and the output of the build
+//!   scripts.
+//! * The keys of the dependencies of the unit, which each unit records in its
+//!   fingerprint directory for its dependents.
+//! * The output of the build scripts the unit depends on, and the files of
+//!   their `OUT_DIR`, as generated code doesn't show in the invocation.
+//!
+//! A unit whose dependencies don't all have a key isn't cached.
+//!
+//! The key is computed right before running `rustc`, once the build scripts
+//! the unit depends on have run. On a hit, the files of the entry are
+//! hard-linked into the target directory instead of running `rustc`. On a
+//! miss, the outputs are copied to a new entry after `rustc` succeeds.
+//!
+//! Entries are created in a temporary directory which is then renamed, so
+//! concurrent builds never see a partial entry. Restoring an entry updates
+//! its modification time, and once a day, the entries which weren't used for
+//! `build.cache.max-age` are removed, see [`auto_gc`].
+//!
+//! With `build.cache.remote`, the entries are also shared through an HTTP
+//! server, see [`Remote`].
+//!
+//! The runs of the build scripts of registry packages are cached too, in
+//! `$CARGO_HOME/artifact-cache/build-scripts`, see [`CacheableBuildScript`].
+//! Its entries are removed by [`auto_gc`] the same way.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context as _};
+use cargo_util::{paths, ProcessBuilder, Sha256};
+use curl::easy::{Easy, List};
+use filetime::FileTime;
+use flate2::read::GzDecoder;
+use flate2::{Compression, GzBuilder};
+use tar::{Archive, Builder, EntryType};
+use tracing::debug;
+
+use crate::core::compiler::build_runner::OutputFile;
+use crate::core::compiler::{
+    fingerprint, BuildContext, BuildOutput, BuildRunner, CompileMode, FileFlavor,
+    RemoteArtifactCache, Unit,
+};
+use crate::util::errors::CargoResult;
+use crate::util::network::http::http_handle;
+
+/// Bumped whenever the layout of entries or what goes into the keys changes.
+const ARTIFACT_CACHE_VERSION: u32 = 3;
+
+/// The name of the file in the fingerprint directory of a unit recording its
+/// key, for its dependents.
+const KEY_FILE: &str = "artifact-cache-key";
+
+/// The name of the file in the cache directory whose modification time is
+/// when [`auto_gc`] last ran.
+const LAST_GC_FILE: &str = ".last-gc";
+
+/// How often [`auto_gc`] looks for unused entries.
+const GC_FREQUENCY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The name of Cargo's dep-info file of the unit in an entry.
+const DEP_INFO_FILE: &str = "dep-info";
+
+/// Arguments which only change how diagnostics are shown, and are left out of
+/// the keys.
+const DIAGNOSTIC_ARGS: [&str; 3] = ["--error-format=", "--json=", "--diagnostic-width="];
+
+/// Environment variables of build scripts which are left out of the keys, as
+/// they don't change what the build script outputs.
+const VOLATILE_SCRIPT_ENV: [&str; 1] = ["NUM_JOBS"];
+
+/// What is needed to look up and store the outputs of a unit which can be
+/// cached.
+pub struct CacheableUnit {
+    /// The directory of the entries, like `~/.cargo/artifact-cache`.
+    cache_dir: PathBuf,
+    /// What identifies the unit besides its `rustc` invocation.
+    seed: String,
+    target_dir: PathBuf,
+    ws_root: PathBuf,
+    pkg_root: PathBuf,
+    /// Where the key of the unit is recorded.
+    key_file: PathBuf,
+    /// Where the keys of the dependencies of the unit are recorded.
+    dep_key_files: Vec<PathBuf>,
+    /// The directories of the runs of the build scripts the unit depends on.
+    script_run_dirs: Vec<PathBuf>,
+    /// Cargo's dep-info file of the unit.
+    dep_info: PathBuf,
+    /// Where entries missing from the local cache are downloaded from.
+    remote: Option<Remote>,
+}
+
+/// What is needed to look up and store the results of a build script run
+/// which can be cached.
+///
+/// The key of a run is a hash of the package, the verbose version of `rustc`,
+/// and the command running the build script, with the paths into the target
+/// directory and the workspace made relative. Its environment covers the
+/// profile, the features, the target, and the metadata of the build scripts
+/// of the `links` dependencies. The program itself is named after the
+/// metadata hash of the compiled build script, which covers its own
+/// dependencies.
+///
+/// An entry holds the stdout and stderr of the run, the `OUT_DIR` it ran with,
+/// which the paths of its output refer to, a copy of the files of `OUT_DIR`,
+/// the values of its `rerun-if-env-changed` variables, and the hashes of its
+/// `rerun-if-changed` paths outside of the package, like system headers. A run
+/// is only restored if these variables have the same values and these paths
+/// the same hashes, the files of the package being covered by its checksum. A
+/// build script printing `cargo::nondeterministic=true` isn't cached, nor is
+/// one whose `OUT_DIR` holds anything but regular files and directories.
+///
+/// The entries are only stored in the local cache.
+pub struct CacheableBuildScript {
+    /// The directory of the entries, like `~/.cargo/artifact-cache/build-scripts`.
+    cache_dir: PathBuf,
+    /// What identifies the run besides the command running the build script.
+    seed: String,
+    target_dir: PathBuf,
+    ws_root: PathBuf,
+    pkg_root: PathBuf,
+}
+
+/// A build script run restored from the cache by [`CacheableBuildScript::restore`].
+pub struct RestoredBuildScript {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    /// The `OUT_DIR` the build script ran with.
+    pub out_dir: PathBuf,
+}
+
+/// The remote backend of the cache, an HTTP server which stores each entry as
+/// two files:
+///
+/// * `<key>.tar.gz`, an archive of the files of the entry.
+/// * `<key>.sha256`, the SHA-256 checksum of the archive, uploaded last.
+///
+/// A downloaded archive is only unpacked if it matches its checksum, and if
+/// it holds nothing but regular files at its root. Any plain server which
+/// answers `GET` and `PUT`, like a front-end to an object storage bucket,
+/// can be used.
+///
+/// Once the server can't be reached, it's no longer used for the rest of the
+/// build, and the failure is only reported once.
+pub struct Remote {
+    config: RemoteArtifactCache,
+    handle: Easy,
+    /// Shared by the units of the build, see
+    /// [`BuildRunner::remote_artifact_cache_unreachable`].
+    unreachable: Arc<AtomicBool>,
+}
+
+/// Returns where the key of `unit` is recorded, if it's a unit which can be
+/// cached.
+///
+/// Its outputs may then be hard links to the files of an entry, so they must
+/// be removed before `rustc` writes them.
+pub fn key_file(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> Option<PathBuf> {
+    let cacheable = unit.pkg.package_id().source_id().is_registry()
+        && unit.pkg.summary().checksum().is_some()
+        && unit.target.is_lib()
+        && matches!(
+            unit.mode,
+            CompileMode::Build | CompileMode::Check { test: false }
+        )
+        && !unit.is_std;
+    cacheable.then(|| build_runner.files().fingerprint_dir(unit).join(KEY_FILE))
+}
+
+/// Prepares the lookup of `unit` in the cache, if the cache is enabled and the
+/// unit can be cached.
+pub fn prepare(
+    build_runner: &mut BuildRunner<'_, '_>,
+    unit: &Unit,
+) -> CargoResult<Option<CacheableUnit>> {
+    let bcx = build_runner.bcx;
+    if !bcx.build_config.artifact_cache || bcx.build_config.build_plan {
+        return Ok(None);
+    }
+    let Some(key_file) = key_file(build_runner, unit) else {
+        return Ok(None);
+    };
+    let seed = format!(
+        "{ARTIFACT_CACHE_VERSION}\n{}\n{}\n{}",
+        unit.pkg.package_id(),
+        unit.pkg.summary().checksum().unwrap_or_default(),
+        bcx.rustc().verbose_version,
+    );
+    // Without network access, the local cache is still used.
+    let unreachable = &build_runner.remote_artifact_cache_unreachable;
+    let remote = match &bcx.build_config.remote_artifact_cache {
+        Some(config) if !bcx.gctx.offline() && !unreachable.load(Ordering::Relaxed) => {
+            Some(Remote {
+                config: config.clone(),
+                handle: http_handle(bcx.gctx)?,
+                unreachable: Arc::clone(unreachable),
+            })
+        }
+        _ => None,
+    };
+    let (script_deps, deps): (Vec<_>, Vec<_>) = build_runner
+        .unit_deps(unit)
+        .iter()
+        .partition(|dep| dep.unit.mode.is_run_custom_build());
+    let dep_key_files = deps
+        .iter()
+        .map(|dep| {
+            build_runner
+                .files()
+                .fingerprint_dir(&dep.unit)
+                .join(KEY_FILE)
+        })
+        .collect();
+    let script_run_dirs = script_deps
+        .iter()
+        .map(|dep| build_runner.files().build_script_run_dir(&dep.unit))
+        .collect();
+    Ok(Some(CacheableUnit {
+        cache_dir: bcx.gctx.home().join("artifact-cache").into_path_unlocked(),
+        seed,
+        target_dir: bcx.ws.target_dir().into_path_unlocked(),
+        ws_root: bcx.ws.root().to_path_buf(),
+        pkg_root: unit.pkg.root().to_path_buf(),
+        key_file,
+        dep_key_files,
+        script_run_dirs,
+        dep_info: fingerprint::dep_info_loc(build_runner, unit),
+        remote,
+    }))
+}
+
+/// Prepares the lookup of the build script run by `unit` in the cache, if the
+/// cache is enabled and the build script can be cached.
+pub fn prepare_build_script(
+    build_runner: &BuildRunner<'_, '_>,
+    unit: &Unit,
+) -> Option<CacheableBuildScript> {
+    let bcx = build_runner.bcx;
+    let cacheable = bcx.build_config.artifact_cache
+        && !bcx.build_config.build_plan
+        && unit.pkg.package_id().source_id().is_registry()
+        && unit.pkg.summary().checksum().is_some();
+    cacheable.then(|| CacheableBuildScript {
+        cache_dir: bcx
+            .gctx
+            .home()
+            .join("artifact-cache")
+            .join("build-scripts")
+            .into_path_unlocked(),
+        seed: format!(
+            "{ARTIFACT_CACHE_VERSION}\n{}\n{}\n{}",
+            unit.pkg.package_id(),
+            unit.pkg.summary().checksum().unwrap_or_default(),
+            bcx.rustc().verbose_version,
+        ),
+        target_dir: bcx.ws.target_dir().into_path_unlocked(),
+        ws_root: bcx.ws.root().to_path_buf(),
+        pkg_root: unit.pkg.root().to_path_buf(),
+    })
+}
+
+/// Removes the outputs of a unit which may be hard links to the files of an
+/// entry, along with its key.
+pub fn unlink_outputs(key_file: &Path, outputs: &[OutputFile]) -> CargoResult<()> {
+    if !key_file.exists() {
+        return Ok(());
+    }
+    for output in outputs {
+        if output.path.exists() {
+            paths::remove_file(&output.path)?;
+        }
+    }
+    paths::remove_file(key_file)?;
+    Ok(())
+}
+
+impl CacheableUnit {
+    /// Computes the key of the unit from the `rustc` invocation which would
+    /// compile it.
+    ///
+    /// Returns `None` if a dependency has no key.
+    pub fn key(&self, rustc: &ProcessBuilder) -> CargoResult<Option<String>> {
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed.as_bytes());
+        hash_command(
+            &mut hasher,
+            rustc,
+            (&self.target_dir, &self.ws_root),
+            &DIAGNOSTIC_ARGS,
+            &[],
+        );
+        for dep_key_file in &self.dep_key_files {
+            let Ok(dep_key) = paths::read(dep_key_file) else {
+                debug!("no artifact cache key at {}", dep_key_file.display());
+                return Ok(None);
+            };
+            hasher.update(b"\0").update(dep_key.as_bytes());
+        }
+        for run_dir in &self.script_run_dirs {
+            let output = paths::read(&run_dir.join("output"))?;
+            hasher
+                .update(b"\0")
+                .update(relative(&self.target_dir, &self.ws_root, output.as_ref()).as_bytes());
+            hash_dir(&mut hasher, &run_dir.join("out"))?;
+        }
+        Ok(Some(hasher.finish_hex()))
+    }
+
+    /// Downloads the entry of `key` from the remote backend, if there is one
+    /// and the entry isn't in the local cache.
+    pub fn fetch(&mut self, key: &str) -> CargoResult<()> {
+        let Some(remote) = &mut self.remote else {
+            return Ok(());
+        };
+        let entry = self.cache_dir.join(key);
+        if entry.exists() {
+            return Ok(());
+        }
+        let Some(checksum) = remote.get(&format!("{key}.sha256"))? else {
+            debug!("artifact cache entry {key} isn't in the remote cache");
+            return Ok(());
+        };
+        let Some(archive) = remote.get(&format!("{key}.tar.gz"))? else {
+            return Ok(());
+        };
+        let expected = String::from_utf8_lossy(&checksum).trim().to_string();
+        let actual = Sha256::new().update(&archive).finish_hex();
+        if actual != expected {
+            bail!(
+                "the checksum of `{}/{key}.tar.gz` is `{actual}`, \
+                 but `{key}.sha256` says it should be `{expected}`",
+                remote.config.url
+            );
+        }
+
+        let tmp = self
+            .cache_dir
+            .join(format!("{key}.{}.tmp", std::process::id()));
+        paths::create_dir_all(&tmp)?;
+        let result = unpack(&archive, &tmp);
+        if result.is_err() || fs::rename(&tmp, &entry).is_err() {
+            paths::remove_dir_all(&tmp)?;
+        }
+        result.with_context(|| format!("failed to unpack `{}/{key}.tar.gz`", remote.config.url))
+    }
+
+    /// Hard-links the files of the entry of `key` into the target directory.
+    ///
+    /// Returns `false` if there is no entry, or if it was compiled with other
+    /// values of the environment variables the crate reads.
+    pub fn restore(
+        &self,
+        key: &str,
+        rustc: &ProcessBuilder,
+        outputs: &[OutputFile],
+    ) -> CargoResult<bool> {
+        let entry = self.cache_dir.join(key);
+        let dep_info = entry.join(DEP_INFO_FILE);
+        let Some(info) = fingerprint::parse_dep_info(&self.pkg_root, &self.target_dir, &dep_info)?
+        else {
+            return Ok(false);
+        };
+        for (var, value) in &info.env {
+            let current = rustc.get_env(var).and_then(|v| v.into_string().ok());
+            if current != *value {
+                debug!("artifact cache entry {key} was compiled with another `{var}`");
+                return Ok(false);
+            }
+        }
+        let files = outputs
+            .iter()
+            .filter(|output| output.flavor != FileFlavor::DebugInfo)
+            .map(|output| (entry.join(output.path.file_name().unwrap()), &output.path))
+            .collect::<Vec<_>>();
+        if files.iter().any(|(src, _)| !src.is_file()) {
+            return Ok(false);
+        }
+
+        // The outputs must be newer than the dep-info file for the unit to
+        // be fresh, and newer than the outputs of the dependencies for the
+        // dependents to be.
+        let now = FileTime::now();
+        // The entry is in use, so it's kept by `auto_gc`.
+        paths::set_file_time_no_err(&entry, now);
+        paths::copy(&dep_info, &self.dep_info)?;
+        paths::set_file_time_no_err(&self.dep_info, now);
+        for (src, dst) in files {
+            paths::link_or_copy(&src, dst)?;
+            paths::set_file_time_no_err(dst, now);
+        }
+        self.record(key)?;
+        debug!(
+            "restored {} from artifact cache entry {key}",
+            self.pkg_root.display()
+        );
+        Ok(true)
+    }
+
+    /// Copies the outputs of the unit to the entry of `key`, once `rustc` has
+    /// compiled it, and uploads the entry to the remote backend unless it's
+    /// read-only.
+    pub fn store(&mut self, key: &str, outputs: &[OutputFile]) -> CargoResult<()> {
+        self.record(key)?;
+        let entry = self.cache_dir.join(key);
+        if entry.exists() {
+            return Ok(());
+        }
+        let mut files = Vec::new();
+        for output in outputs {
+            if output.flavor == FileFlavor::DebugInfo {
+                if output.path.exists() {
+                    // Split debuginfo may be a directory, and refers to the
+                    // paths of the objects it was split from.
+                    debug!("not caching {}, it has split debuginfo", key);
+                    return Ok(());
+                }
+                continue;
+            }
+            files.push(&output.path);
+        }
+
+        let tmp = self
+            .cache_dir
+            .join(format!("{key}.{}.tmp", std::process::id()));
+        paths::create_dir_all(&tmp)?;
+        for path in files {
+            paths::copy(path, tmp.join(path.file_name().unwrap()))?;
+        }
+        paths::copy(&self.dep_info, tmp.join(DEP_INFO_FILE))?;
+        if let Err(e) = fs::rename(&tmp, &entry) {
+            // Another build stored the same entry first.
+            debug!("failed to rename {}: {e}", tmp.display());
+            paths::remove_dir_all(&tmp)?;
+            return Ok(());
+        }
+
+        match &mut self.remote {
+            Some(remote) if !remote.config.read_only => {
+                let archive = pack(&entry)?;
+                let checksum = Sha256::new().update(&archive).finish_hex();
+                remote.put(&format!("{key}.tar.gz"), &archive)?;
+                remote.put(&format!("{key}.sha256"), checksum.as_bytes())?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Records the key of the unit for its dependents.
+    fn record(&self, key: &str) -> CargoResult<()> {
+        paths::write(&self.key_file, key)
+    }
+}
+
+impl CacheableBuildScript {
+    /// Computes the key of the run from the command running the build script,
+    /// once the metadata of the build scripts it depends on is in its
+    /// environment.
+    pub fn key(&self, cmd: &ProcessBuilder) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed.as_bytes());
+        hash_command(
+            &mut hasher,
+            cmd,
+            (&self.target_dir, &self.ws_root),
+            &[],
+            &VOLATILE_SCRIPT_ENV,
+        );
+        hasher.finish_hex()
+    }
+
+    /// Copies the files of the entry of `key` to `out_dir`, replacing its
+    /// contents, and returns the output of the run.
+    ///
+    /// Returns `None` if there is no entry, if the build script ran with other
+    /// values of its `rerun-if-env-changed` variables, or if its
+    /// `rerun-if-changed` paths outside of the package changed since.
+    pub fn restore(
+        &self,
+        key: &str,
+        cmd: &ProcessBuilder,
+        out_dir: &Path,
+    ) -> CargoResult<Option<RestoredBuildScript>> {
+        let entry = self.cache_dir.join(key);
+        if !self.is_up_to_date(key, cmd)? {
+            return Ok(None);
+        }
+        if out_dir.exists() {
+            paths::remove_dir_all(out_dir)?;
+        }
+        copy_dir(&entry.join("out"), out_dir)?;
+        // The entry is in use, so it's kept by `auto_gc`.
+        paths::set_file_time_no_err(&entry, FileTime::now());
+        debug!("restored build script run from cache entry {key}");
+        Ok(Some(RestoredBuildScript {
+            stdout: paths::read_bytes(&entry.join("output"))?,
+            stderr: paths::read_bytes(&entry.join("stderr"))?,
+            out_dir: paths::bytes2path(&paths::read_bytes(&entry.join("root-output"))?)?,
+        }))
+    }
+
+    /// Whether there is an entry for `key` which ran with the same values of
+    /// its `rerun-if-env-changed` variables as `cmd`, and with the same
+    /// contents of its `rerun-if-changed` paths outside of the package.
+    fn is_up_to_date(&self, key: &str, cmd: &ProcessBuilder) -> CargoResult<bool> {
+        let entry = self.cache_dir.join(key);
+        let Ok(env) = paths::read(&entry.join("env.json")) else {
+            return Ok(false);
+        };
+        let env: Vec<(String, Option<String>)> = serde_json::from_str(&env)?;
+        for (var, value) in &env {
+            let current = cmd.get_env(var).and_then(|v| v.into_string().ok());
+            if current != *value {
+                debug!("build script cache entry {key} ran with another `{var}`");
+                return Ok(false);
+            }
+        }
+        let Ok(inputs) = paths::read(&entry.join("inputs.json")) else {
+            return Ok(false);
+        };
+        let inputs: Vec<(PathBuf, Option<String>)> = serde_json::from_str(&inputs)?;
+        for (path, hash) in &inputs {
+            if hash_input(path)? != *hash {
+                debug!(
+                    "build script cache entry {key} ran with another `{}`",
+                    path.display()
+                );
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Copies the output of a run and the files of its `OUT_DIR` to the entry
+    /// of `key`, once the build script succeeded, replacing an entry which
+    /// ran with other inputs.
+    pub fn store(
+        &self,
+        key: &str,
+        cmd: &ProcessBuilder,
+        output: &BuildOutput,
+        stdout: &[u8],
+        stderr: &[u8],
+        out_dir: &Path,
+    ) -> CargoResult<()> {
+        let entry = self.cache_dir.join(key);
+        if output.nondeterministic || self.is_up_to_date(key, cmd)? {
+            return Ok(());
+        }
+        let env: Vec<(&String, Option<String>)> = output
+            .rerun_if_env_changed
+            .iter()
+            .map(|var| (var, cmd.get_env(var).and_then(|v| v.into_string().ok())))
+            .collect();
+        let mut inputs = Vec::new();
+        for path in &output.rerun_if_changed {
+            let path = paths::normalize_path(&self.pkg_root.join(path));
+            // The files of the package are covered by its checksum, and the
+            // files generated in `OUT_DIR` are restored with the entry.
+            if path.starts_with(&self.pkg_root) || path.starts_with(out_dir) {
+                continue;
+            }
+            let hash = hash_input(&path)?;
+            inputs.push((path, hash));
+        }
+
+        let tmp = self
+            .cache_dir
+            .join(format!("{key}.{}.tmp", std::process::id()));
+        paths::create_dir_all(&tmp)?;
+        if !copy_dir(out_dir, &tmp.join("out"))? {
+            debug!("not caching {key}, its `OUT_DIR` has special files");
+            paths::remove_dir_all(&tmp)?;
+            return Ok(());
+        }
+        paths::write(&tmp.join("output"), stdout)?;
+        paths::write(&tmp.join("stderr"), stderr)?;
+        paths::write(&tmp.join("root-output"), paths::path2bytes(out_dir)?)?;
+        paths::write(&tmp.join("env.json"), serde_json::to_string(&env)?)?;
+        paths::write(&tmp.join("inputs.json"), serde_json::to_string(&inputs)?)?;
+        if entry.exists() {
+            // The entry ran with other inputs. It's moved aside before being
+            // removed, so that it's never seen partially removed.
+            let stale = self
+                .cache_dir
+                .join(format!("{key}.{}.stale.tmp", std::process::id()));
+            if fs::rename(&entry, &stale).is_ok() {
+                paths::remove_dir_all(&stale)?;
+            }
+        }
+        if let Err(e) = fs::rename(&tmp, &entry) {
+            // Another build stored the same entry first.
+            debug!("failed to rename {}: {e}", tmp.display());
+            paths::remove_dir_all(&tmp)?;
+        }
+        Ok(())
+    }
+}
+
+/// Hashes the program, arguments and environment variables of `cmd`, with
+/// the paths into the target directory and the workspace made relative.
+///
+/// The arguments starting with one of `skip_args` and the variables in
+/// `skip_envs` are left out.
+fn hash_command(
+    hasher: &mut Sha256,
+    cmd: &ProcessBuilder,
+    (target_dir, ws_root): (&Path, &Path),
+    skip_args: &[&str],
+    skip_envs: &[&str],
+) {
+    let relative = |value: &OsStr| relative(target_dir, ws_root, value);
+    hasher.update(relative(cmd.get_program()).as_bytes());
+    for arg in cmd.get_args() {
+        let arg = relative(arg);
+        if skip_args.iter().any(|prefix| arg.starts_with(prefix)) {
+            continue;
+        }
+        hasher.update(b"\0").update(arg.as_bytes());
+    }
+    for (key, value) in cmd.get_envs() {
+        if skip_envs.contains(&key.as_str()) {
+            continue;
+        }
+        let value = value.as_deref().map(relative);
+        hasher
+            .update(b"\0")
+            .update(key.as_bytes())
+            .update(b"=")
+            .update(value.unwrap_or_default().as_bytes());
+    }
+}
+
+/// Makes the paths into the target directory and the workspace in `value`
+/// relative.
+fn relative(target_dir: &Path, ws_root: &Path, value: &OsStr) -> String {
+    value
+        .to_string_lossy()
+        .replace(target_dir.to_string_lossy().as_ref(), "[TARGET]")
+        .replace(ws_root.to_string_lossy().as_ref(), "[ROOT]")
+}
+
+/// Hashes the paths and contents of the files under `dir`, in order.
+fn hash_dir(hasher: &mut Sha256, dir: &Path) -> CargoResult<()> {
+    for entry in walkdir::WalkDir::new(dir).sort_by_file_name() {
+        let entry = entry?;
+        let path = entry.path().strip_prefix(dir)?;
+        hasher
+            .update(b"\0")
+            .update(path.to_string_lossy().as_bytes());
+        let file_type = entry.file_type();
+        if file_type.is_file() {
+            hasher.update(b"=").update_path(entry.path())?;
+        } else if file_type.is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            hasher
+                .update(b"->")
+                .update(target.to_string_lossy().as_bytes());
+        }
+    }
+    Ok(())
+}
+
+/// Hashes the contents of the file at `path`, or the paths and contents of the
+/// files under the directory at `path`, or returns `None` if there is nothing.
+fn hash_input(path: &Path) -> CargoResult<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut hasher = Sha256::new();
+    if path.is_dir() {
+        hash_dir(&mut hasher, path)?;
+    } else {
+        hasher.update_path(path)?;
+    }
+    Ok(Some(hasher.finish_hex()))
+}
+
+/// Removes the entries of the artifact cache which weren't used for
+/// `build.cache.max-age`, at most once a day.
+///
+/// This is best effort: failing to remove an entry is only logged, as is
+/// failing to look for entries.
+pub fn auto_gc(bcx: &BuildContext<'_, '_>) {
+    if !bcx.build_config.artifact_cache || bcx.build_config.build_plan {
+        return;
+    }
+    let cache_dir = bcx.gctx.home().join("artifact-cache").into_path_unlocked();
+    for dir in [cache_dir.join("build-scripts"), cache_dir] {
+        if let Err(e) = gc_dir(&dir, bcx.build_config.artifact_cache_max_age) {
+            debug!("failed to clean the artifact cache: {e:?}");
+        }
+    }
+}
+
+/// Removes the entries of `cache_dir` last modified over `max_age` ago, and
+/// the temporary directories of entries left by interrupted builds, unless
+/// this already ran in the last day.
+fn gc_dir(cache_dir: &Path, max_age: Duration) -> CargoResult<()> {
+    let now = SystemTime::now();
+    let age = |path: &Path| -> CargoResult<Duration> {
+        let modified = fs::metadata(path)?.modified()?;
+        Ok(now.duration_since(modified).unwrap_or_default())
+    };
+    let last_gc = cache_dir.join(LAST_GC_FILE);
+    if !cache_dir.exists() || age(&last_gc).is_ok_and(|age| age < GC_FREQUENCY) {
+        return Ok(());
+    }
+    paths::write(&last_gc, "")?;
+    for entry in fs::read_dir(cache_dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap().to_string_lossy();
+        let is_entry = name.len() == 64 && name.bytes().all(|b| b.is_ascii_hexdigit());
+        let max_age = if is_entry {
+            max_age
+        } else if name.ends_with(".tmp") {
+            GC_FREQUENCY
+        } else {
+            continue;
+        };
+        if age(&path)? > max_age {
+            debug!("removing unused artifact cache entry {}", path.display());
+            if let Err(e) = paths::remove_dir_all(&path) {
+                debug!("failed to remove {}: {e:?}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copies the regular files and the directories under `src` to `dst`.
+///
+/// Returns `false`, leaving a partial copy, if there is anything else under
+/// `src`, like a symlink.
+fn copy_dir(src: &Path, dst: &Path) -> CargoResult<bool> {
+    for entry in walkdir::WalkDir::new(src) {
+        let entry = entry?;
+        let path = dst.join(entry.path().strip_prefix(src)?);
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            paths::create_dir_all(&path)?;
+        } else if file_type.is_file() {
+            paths::copy(entry.path(), &path)?;
+        } else {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+impl Remote {
+    /// Downloads `name`, or returns `None` if the server doesn't have it or
+    /// can't be reached anymore.
+    fn get(&mut self, name: &str) -> CargoResult<Option<Vec<u8>>> {
+        if self.unreachable.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let url = format!("{}/{name}", self.config.url);
+        self.handle.get(true)?;
+        self.handle.url(&url)?;
+        self.handle.http_headers(self.headers()?)?;
+        let mut body = Vec::new();
+        let result = {
+            let mut transfer = self.handle.transfer();
+            transfer.write_function(|data| {
+                body.extend_from_slice(data);
+                Ok(data.len())
+            })?;
+            transfer.perform()
+        };
+        if !self.check_reachable(result, || format!("failed to download `{url}`"))? {
+            return Ok(None);
+        }
+        match self.handle.response_code()? {
+            200 => Ok(Some(body)),
+            404 | 410 => Ok(None),
+            code => bail!("failed to download `{url}`, got {code}"),
+        }
+    }
+
+    /// Uploads `body` as `name`, unless the server can't be reached anymore.
+    fn put(&mut self, name: &str, mut body: &[u8]) -> CargoResult<()> {
+        if self.unreachable.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let url = format!("{}/{name}", self.config.url);
+        self.handle.upload(true)?;
+        self.handle.url(&url)?;
+        self.handle.in_filesize(body.len() as u64)?;
+        let mut headers = self.headers()?;
+        // Don't wait for the server to accept the body before sending it.
+        headers.append("Expect:")?;
+        self.handle.http_headers(headers)?;
+        let result = {
+            let mut transfer = self.handle.transfer();
+            transfer.read_function(|buf| Ok(body.read(buf).unwrap_or(0)))?;
+            transfer.perform()
+        };
+        if !self.check_reachable(result, || format!("failed to upload `{url}`"))? {
+            return Ok(());
+        }
+        match self.handle.response_code()? {
+            200..=299 => Ok(()),
+            code => bail!("failed to upload `{url}`, got {code}"),
+        }
+    }
+
+    fn headers(&self) -> CargoResult<List> {
+        let mut headers = List::new();
+        if let Some(token) = &self.config.token {
+            headers.append(&format!("Authorization: {}", token.as_deref().expose()))?;
+        }
+        Ok(headers)
+    }
+
+    /// Returns whether a transfer went through, or marks the server as
+    /// unreachable if it failed. The error is only reported for the first
+    /// transfer to fail.
+    fn check_reachable(
+        &self,
+        result: Result<(), curl::Error>,
+        context: impl FnOnce() -> String,
+    ) -> CargoResult<bool> {
+        let Err(e) = result else {
+            return Ok(true);
+        };
+        if self.unreachable.swap(true, Ordering::Relaxed) {
+            return Ok(false);
+        }
+        Err(anyhow::Error::new(e).context(format!(
+            "{}, the remote artifact cache is no longer used for this build",
+            context()
+        )))
+    }
+}
+
+/// Archives the files of an entry.
+fn pack(entry: &Path) -> CargoResult<Vec<u8>> {
+    let mut names = fs::read_dir(entry)?
+        .map(|e| Ok(e?.file_name()))
+        .collect::<CargoResult<Vec<_>>>()?;
+    names.sort();
+    let encoder = GzBuilder::new().write(Vec::new(), Compression::default());
+    let mut builder = Builder::new(encoder);
+    for name in names {
+        builder.append_path_with_name(entry.join(&name), &name)?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+/// Unpacks the archive of an entry in `dir`, rejecting anything but regular
+/// files at the root of the archive.
+fn unpack(archive: &[u8], dir: &Path) -> CargoResult<()> {
+    let mut archive = Archive::new(GzDecoder::new(archive));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let mut components = path.components();
+        let name = match (components.next(), components.next()) {
+            (Some(std::path::Component::Normal(name)), None) => name.to_owned(),
+            _ => bail!("invalid path `{}` in archive", path.display()),
+        };
+        if entry.header().entry_type() != EntryType::Regular {
+            bail!("`{}` in archive isn't a regular file", path.display());
+        }
+        entry.unpack(dir.join(name))?;
+    }
+    Ok(())
+}
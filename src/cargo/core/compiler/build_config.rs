@@ -1,14 +1,22 @@
 use crate::core::compiler::CompileKind;
-use crate::util::context::JobsConfig;
+use crate::core::gc::{parse_human_size, parse_time_span};
+use crate::util::context::{BuildSandboxConfig, JobsConfig};
 use crate::util::interning::InternedString;
+use crate::util::memory;
 use crate::util::{CargoResult, GlobalContext, RustfixDiagnosticServer};
 use anyhow::{bail, Context as _};
+use cargo_credential::Secret;
 use cargo_util::ProcessBuilder;
-use serde::ser;
+use serde::{ser, Deserialize};
 use std::cell::RefCell;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::thread::available_parallelism;
+use std::time::Duration;
+
+/// How long the entries of the artifact cache are kept without being used,
+/// unless `build.cache.max-age` says otherwise.
+const DEFAULT_ARTIFACT_CACHE_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
 
 /// Configuration information for a rustc build.
 #[derive(Debug, Clone)]
@@ -17,6 +25,9 @@ pub struct BuildConfig {
     pub requested_kinds: Vec<CompileKind>,
     /// Number of rustc jobs to run in parallel.
     pub jobs: u32,
+    /// Number of jobs ending with a link step to run in parallel, within
+    /// `jobs`.
+    pub link_jobs: Option<u32>,
     /// Do not abort the build as soon as there is an error.
     pub keep_going: bool,
     /// Build profile
@@ -29,8 +40,21 @@ pub struct BuildConfig {
     pub force_rebuild: bool,
     /// Output a build plan to stdout instead of actually compiling.
     pub build_plan: bool,
+    /// Write the build plan as a Ninja build file to this path, instead of
+    /// printing it. Only used along with `build_plan`.
+    pub emit_ninja: Option<PathBuf>,
     /// Output the unit graph to stdout instead of actually compiling.
     pub unit_graph: bool,
+    /// Explain which fingerprint component changed for each dirty unit.
+    pub explain_rebuild: bool,
+    /// Only use the artifacts of previous builds, failing instead of
+    /// building a unit which isn't fresh.
+    pub no_build: bool,
+    /// Package ID specs of the dependencies whose warnings are shown in a
+    /// summary at the end of the build, with `--show-dependency-warnings`.
+    pub show_dependency_warnings: Vec<String>,
+    /// How often to save the state of the build while it runs.
+    pub checkpoint_interval: Option<Duration>,
     /// An optional override of the rustc process for primary units
     pub primary_unit_rustc: Option<ProcessBuilder>,
     /// A thread used by `cargo fix` to receive messages on a socket regarding
@@ -46,6 +70,62 @@ pub struct BuildConfig {
     pub future_incompat_report: bool,
     /// Which kinds of build timings to output (empty if none).
     pub timing_outputs: Vec<TimingOutput>,
+    /// Whether to replay the cached diagnostics of fresh units.
+    pub replay_warnings: bool,
+    /// How to detect changes to the source files of local units.
+    pub fingerprint: FingerprintMode,
+    /// The memory the jobs of the build may use together, in bytes.
+    pub max_memory: Option<u64>,
+    /// Whether to share the outputs of registry dependencies between
+    /// workspaces through `$CARGO_HOME/artifact-cache`.
+    pub artifact_cache: bool,
+    /// The remote backend of the artifact cache, from `build.cache`.
+    pub remote_artifact_cache: Option<RemoteArtifactCache>,
+    /// How long the entries of the artifact cache are kept without being
+    /// used, from `build.cache.max-age`.
+    pub artifact_cache_max_age: Duration,
+    /// The program, and its arguments, running the `rustc` invocations
+    /// instead of Cargo, from `build.executor`.
+    pub executor: Option<(PathBuf, Vec<String>)>,
+    /// Which packages run their build scripts, and expand their procedural
+    /// macros, in an OS sandbox, from `build.sandbox`.
+    pub sandbox: SandboxMode,
+}
+
+/// Where the entries of the artifact cache are shared with other machines.
+#[derive(Debug, Clone)]
+pub struct RemoteArtifactCache {
+    /// The URL the entries are downloaded from and uploaded to, without a
+    /// trailing slash.
+    pub url: String,
+    /// Sent in the `Authorization` header of the requests.
+    pub token: Option<Secret<String>>,
+    /// Whether to only download entries, never upload them.
+    pub read_only: bool,
+}
+
+/// Which packages are sandboxed by `build.sandbox`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SandboxMode {
+    /// Nothing is sandboxed.
+    #[default]
+    Off,
+    /// Every package is sandboxed.
+    All,
+    /// Only the packages with these names are sandboxed.
+    Packages(Vec<String>),
+}
+
+impl SandboxMode {
+    /// Whether the build script, or the procedural macros, of the package
+    /// named `name` run in the sandbox.
+    pub fn applies_to(&self, name: &str) -> bool {
+        match self {
+            SandboxMode::Off => false,
+            SandboxMode::All => true,
+            SandboxMode::Packages(names) => names.iter().any(|n| n == name),
+        }
+    }
 }
 
 fn default_parallelism() -> CargoResult<u32> {
@@ -58,7 +138,12 @@ impl BuildConfig {
     /// Parses all config files to learn about build configuration. Currently
     /// configured options are:
     ///
+    /// * `build.artifact-cache`
+    /// * `build.fingerprint`
     /// * `build.jobs`
+    /// * `build.link-jobs`
+    /// * `build.max-memory`
+    /// * `build.replay-warnings`
     /// * `build.target`
     /// * `target.$target.ar`
     /// * `target.$target.linker`
@@ -97,6 +182,116 @@ impl BuildConfig {
             },
         };
 
+        let link_jobs = match cfg.link_jobs {
+            Some(_) if !gctx.cli_unstable().unstable_options => {
+                gctx.shell()
+                    .warn("`build.link-jobs` requires `-Zunstable-options`, ignoring it")?;
+                None
+            }
+            Some(0) => anyhow::bail!("link-jobs may not be 0"),
+            link_jobs => link_jobs,
+        };
+
+        let replay_warnings = match cfg.replay_warnings {
+            Some(replay) if gctx.cli_unstable().unstable_options => replay,
+            Some(_) => {
+                gctx.shell()
+                    .warn("`build.replay-warnings` requires `-Zunstable-options`, ignoring it")?;
+                true
+            }
+            None => true,
+        };
+
+        let fingerprint = match cfg.fingerprint {
+            Some(mode) if gctx.cli_unstable().unstable_options => mode,
+            Some(_) => {
+                gctx.shell()
+                    .warn("`build.fingerprint` requires `-Zunstable-options`, ignoring it")?;
+                FingerprintMode::Mtime
+            }
+            None => FingerprintMode::Mtime,
+        };
+
+        let max_memory = match cfg.max_memory.as_deref() {
+            Some(_) if !gctx.cli_unstable().unstable_options => {
+                gctx.shell()
+                    .warn("`build.max-memory` requires `-Zunstable-options`, ignoring it")?;
+                None
+            }
+            Some("auto") => {
+                let available = memory::available();
+                if available.is_none() {
+                    gctx.shell().warn(
+                        "could not determine the available memory for \
+                         `build.max-memory = \"auto\"`, ignoring it",
+                    )?;
+                }
+                available
+            }
+            Some(size) => Some(
+                parse_human_size(size)
+                    .with_context(|| "failed to parse config `build.max-memory`")?,
+            ),
+            None => None,
+        };
+
+        let artifact_cache = match cfg.artifact_cache {
+            Some(enabled) if gctx.cli_unstable().unstable_options => enabled,
+            Some(_) => {
+                gctx.shell()
+                    .warn("`build.artifact-cache` requires `-Zunstable-options`, ignoring it")?;
+                false
+            }
+            None => false,
+        };
+
+        let remote_artifact_cache = match &cfg.cache {
+            Some(cache) if !gctx.cli_unstable().unstable_options && cache.remote.is_some() => {
+                gctx.shell()
+                    .warn("`build.cache.remote` requires `-Zunstable-options`, ignoring it")?;
+                None
+            }
+            Some(cache) => cache.remote.as_ref().map(|url| RemoteArtifactCache {
+                url: url.trim_end_matches('/').to_string(),
+                token: cache.token.clone(),
+                read_only: cache.read_only.unwrap_or(false),
+            }),
+            None => None,
+        };
+        // The remote entries are downloaded into the local cache.
+        let artifact_cache = artifact_cache || remote_artifact_cache.is_some();
+        let artifact_cache_max_age = match cfg.cache.as_ref().and_then(|c| c.max_age.as_ref()) {
+            Some(max_age) => parse_time_span(max_age)
+                .with_context(|| "failed to parse config `build.cache.max-age`")?,
+            None => DEFAULT_ARTIFACT_CACHE_MAX_AGE,
+        };
+
+        let executor = match &cfg.executor {
+            Some(executor) if gctx.cli_unstable().unstable_options => {
+                Some((executor.path.resolve_program(gctx), executor.args.clone()))
+            }
+            Some(_) => {
+                gctx.shell()
+                    .warn("`build.executor` requires `-Zunstable-options`, ignoring it")?;
+                None
+            }
+            None => None,
+        };
+
+        let sandbox = match &cfg.sandbox {
+            Some(_) if !gctx.cli_unstable().unstable_options => {
+                gctx.shell()
+                    .warn("`build.sandbox` requires `-Zunstable-options`, ignoring it")?;
+                SandboxMode::Off
+            }
+            Some(BuildSandboxConfig::Enabled(true)) => SandboxMode::All,
+            Some(BuildSandboxConfig::Enabled(false)) | None => SandboxMode::Off,
+            Some(BuildSandboxConfig::Packages(names)) => SandboxMode::Packages(names.clone()),
+        };
+        if sandbox != SandboxMode::Off && !cfg!(any(target_os = "linux", target_os = "macos")) {
+            bail!("`build.sandbox` is only supported on Linux and macOS");
+        }
+
         if gctx.cli_unstable().build_std.is_some() && requested_kinds[0].is_host() {
             // TODO: This should eventually be fixed.
             anyhow::bail!("-Zbuild-std requires --target");
@@ -105,18 +300,32 @@ impl BuildConfig {
         Ok(BuildConfig {
             requested_kinds,
             jobs,
+            link_jobs,
             keep_going,
             requested_profile: InternedString::new("dev"),
             mode,
             message_format: MessageFormat::Human,
             force_rebuild: false,
             build_plan: false,
+            emit_ninja: None,
             unit_graph: false,
+            explain_rebuild: false,
+            no_build: false,
+            show_dependency_warnings: Vec::new(),
+            checkpoint_interval: None,
             primary_unit_rustc: None,
             rustfix_diagnostic_server: Rc::new(RefCell::new(None)),
             export_dir: None,
             future_incompat_report: false,
             timing_outputs: Vec::new(),
+            replay_warnings,
+            fingerprint,
+            max_memory,
+            artifact_cache,
+            remote_artifact_cache,
+            artifact_cache_max_age,
+            executor,
+            sandbox,
         })
     }
 
@@ -153,6 +362,20 @@ pub enum MessageFormat {
         ansi: bool,
     },
     Short,
+    /// Diagnostics are rendered like with `Human`, and also printed as a
+    /// SARIF log once the build is finished.
+    Sarif,
+}
+
+/// How changes to source files are detected, set by `build.fingerprint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FingerprintMode {
+    /// A file has changed if it's newer than the last build.
+    Mtime,
+    /// A file has changed if its contents differ from the last build. The
+    /// contents are only hashed if its mtime changed but not its size.
+    Hash,
 }
 
 /// The general "mode" for what to do.
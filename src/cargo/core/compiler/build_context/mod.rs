@@ -3,12 +3,14 @@
 use crate::core::compiler::unit_graph::UnitGraph;
 use crate::core::compiler::{BuildConfig, CompileKind, Unit};
 use crate::core::profiles::Profiles;
-use crate::core::PackageSet;
 use crate::core::Workspace;
-use crate::util::context::GlobalContext;
+use crate::core::{Dependency, PackageId, PackageSet};
+use crate::core::{PackageIdSpec, PackageIdSpecQuery};
+use crate::util::context::{CargoOriginLintsConfig, GlobalContext, OriginLintPolicy};
 use crate::util::errors::CargoResult;
 use crate::util::interning::InternedString;
 use crate::util::Rustc;
+use anyhow::Context as _;
 use std::collections::{HashMap, HashSet};
 
 mod target_info;
@@ -78,6 +80,19 @@ pub struct BuildContext<'a, 'gctx> {
 
     /// The list of all kinds that are involved in this build
     pub all_kinds: HashSet<CompileKind>,
+
+    /// The artifact dependencies of each package, along with the package
+    /// they resolved to, when `-Zcheck-skip-artifacts` is used.
+    pub artifact_deps: HashMap<PackageId, Vec<(Dependency, PackageId)>>,
+
+    /// The `[lints.cargo-origin]` config.
+    origin_lints: CargoOriginLintsConfig,
+
+    /// The `[lints.cargo-origin.package]` policies, with their parsed specs.
+    package_lints: Vec<(PackageIdSpec, OriginLintPolicy)>,
+
+    /// The parsed specs of `--show-dependency-warnings`.
+    dependency_warning_specs: Vec<PackageIdSpec>,
 }
 
 impl<'a, 'gctx> BuildContext<'a, 'gctx> {
@@ -91,6 +106,7 @@ impl<'a, 'gctx> BuildContext<'a, 'gctx> {
         roots: Vec<Unit>,
         unit_graph: UnitGraph,
         scrape_units: Vec<Unit>,
+        artifact_deps: HashMap<PackageId, Vec<(Dependency, PackageId)>>,
     ) -> CargoResult<BuildContext<'a, 'gctx>> {
         let all_kinds = unit_graph
             .keys()
@@ -98,6 +114,38 @@ impl<'a, 'gctx> BuildContext<'a, 'gctx> {
             .chain(build_config.requested_kinds.iter().copied())
             .chain(std::iter::once(CompileKind::Host))
             .collect();
+        let origin_lints = ws.gctx().origin_lints_config()?.clone();
+        let package_lints = origin_lints
+            .package
+            .iter()
+            .flatten()
+            .map(|(spec, policy)| {
+                let spec = PackageIdSpec::parse(spec).with_context(|| {
+                    format!("invalid package ID spec `{spec}` in `lints.cargo-origin.package`")
+                })?;
+                Ok((spec, *policy))
+            })
+            .collect::<CargoResult<_>>()?;
+        let dependency_warning_specs: Vec<PackageIdSpec> = build_config
+            .show_dependency_warnings
+            .iter()
+            .map(|spec| {
+                PackageIdSpec::parse(spec).with_context(|| {
+                    format!("invalid package ID spec `{spec}` in `--show-dependency-warnings`")
+                })
+            })
+            .collect::<CargoResult<_>>()?;
+        if !dependency_warning_specs.is_empty() {
+            let built: HashSet<PackageId> = unit_graph.keys().map(|u| u.pkg.package_id()).collect();
+            for spec in &dependency_warning_specs {
+                if !built.iter().any(|&id| spec.matches(id)) {
+                    // `query` fails here, with suggestions of similar package names.
+                    spec.query(built.iter().copied()).context(
+                        "failed to select the dependencies in `--show-dependency-warnings`",
+                    )?;
+                }
+            }
+        }
 
         Ok(BuildContext {
             ws,
@@ -111,6 +159,10 @@ impl<'a, 'gctx> BuildContext<'a, 'gctx> {
             unit_graph,
             scrape_units,
             all_kinds,
+            artifact_deps,
+            origin_lints,
+            package_lints,
+            dependency_warning_specs,
         })
     }
 
@@ -129,6 +181,59 @@ impl<'a, 'gctx> BuildContext<'a, 'gctx> {
         self.target_data.rustc.host
     }
 
+    /// The `[lints.cargo-origin]` policy for the package of `unit`, if any,
+    /// or else for its origin.
+    ///
+    /// The standard library of `-Zbuild-std` has no origin.
+    pub fn origin_lint_policy(&self, unit: &Unit) -> Option<OriginLintPolicy> {
+        if unit.is_std {
+            return None;
+        }
+        let pkg_id = unit.pkg.package_id();
+        if let Some((_, policy)) = self
+            .package_lints
+            .iter()
+            .find(|(spec, _)| spec.matches(pkg_id))
+        {
+            return Some(*policy);
+        }
+        let source_id = pkg_id.source_id();
+        if self.ws.is_member(&unit.pkg) {
+            self.origin_lints.workspace
+        } else if source_id.is_path() {
+            self.origin_lints.path_deps
+        } else if source_id.is_git() {
+            self.origin_lints.git
+        } else if source_id.is_registry() {
+            self.origin_lints.registry
+        } else {
+            None
+        }
+    }
+
+    /// Whether warnings of `unit` should be shown, taking
+    /// `[lints.cargo-origin]` into account.
+    pub fn show_warnings(&self, unit: &Unit) -> bool {
+        match self.origin_lint_policy(unit) {
+            Some(OriginLintPolicy::Allow) => false,
+            Some(OriginLintPolicy::DenyWarnings | OriginLintPolicy::Warn) => true,
+            None => unit.show_warnings(self.gctx),
+        }
+    }
+
+    /// Whether `unit` is a dependency selected with
+    /// `--show-dependency-warnings`, whose lints are capped to warnings
+    /// instead of being silenced, and whose warnings are shown together at
+    /// the end of the build.
+    pub fn summarize_warnings(&self, unit: &Unit) -> bool {
+        !unit.is_local()
+            && !unit.is_std
+            && self
+                .dependency_warning_specs
+                .iter()
+                .any(|spec| spec.matches(unit.pkg.package_id()))
+    }
+
     /// Gets the number of jobs specified for this build.
     pub fn jobs(&self) -> u32 {
         self.build_config.jobs
@@ -60,6 +60,9 @@ pub struct TargetInfo {
     /// Can be removed once the minimum supported rustc version of Cargo is
     /// at minimum 1.80.0.
     pub support_check_cfg: bool,
+    /// Whether or not rustc accepts `--extern priv:` to mark private
+    /// dependencies, which still requires `-Zunstable-options`.
+    pub support_extern_priv: bool,
 }
 
 /// Kind of each file generated by a Unit, part of `FileType`.
@@ -212,6 +215,10 @@ impl TargetInfo {
                 )
                 .is_ok();
 
+            let support_extern_priv = rustc
+                .cached_output(process.clone().arg("-Zunstable-options"), extra_fingerprint)
+                .is_ok();
+
             process.arg("--print=sysroot");
             process.arg("--print=split-debuginfo");
             process.arg("--print=crate-name"); // `___` as a delimiter.
@@ -324,6 +331,7 @@ impl TargetInfo {
                 cfg,
                 support_split_debuginfo,
                 support_check_cfg,
+                support_extern_priv,
             });
         }
     }
@@ -732,7 +740,8 @@ fn extra_args(
             // --target. Or, phrased differently, no `--target` behaves the same as `--target
             // <host>`, and host artifacts are always "special" (they don't pick up `RUSTFLAGS` for
             // example).
-            return Ok(rustflags_from_host(gctx, flags, host_triple)?.unwrap_or_else(Vec::new));
+            let rustflags = rustflags_from_host(gctx, flags, host_triple)?.unwrap_or_else(Vec::new);
+            return expand_placeholders(gctx, rustflags);
         }
     }
 
@@ -745,14 +754,22 @@ fn extra_args(
     } else if let Some(rustflags) =
         rustflags_from_target(gctx, host_triple, target_cfg, kind, flags)?
     {
-        Ok(rustflags)
+        expand_placeholders(gctx, rustflags)
     } else if let Some(rustflags) = rustflags_from_build(gctx, flags)? {
-        Ok(rustflags)
+        expand_placeholders(gctx, rustflags)
     } else {
         Ok(Vec::new())
     }
 }
 
+/// Expands the config placeholders in flags coming from the config.
+fn expand_placeholders(gctx: &GlobalContext, flags: Vec<String>) -> CargoResult<Vec<String>> {
+    flags
+        .into_iter()
+        .map(|flag| Ok(gctx.expand_placeholders(&flag)?.into_owned()))
+        .collect()
+}
+
 /// Gets compiler flags from environment variables.
 /// See [`extra_args`] for more.
 fn rustflags_from_env(gctx: &GlobalContext, flags: Flags) -> Option<Vec<String>> {
@@ -934,6 +951,7 @@ impl<'gctx> RustcTargetData<'gctx> {
         let all_kinds = requested_kinds
             .iter()
             .copied()
+            .chain(ws.default_kinds().iter().copied())
             .chain(ws.members().flat_map(|p| {
                 p.manifest()
                     .default_kind()
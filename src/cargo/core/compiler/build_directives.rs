@@ -0,0 +1,125 @@
+//! Applies the `[build-directives]` of packages, which declare what a trivial
+//! build script would print, without compiling or running one.
+//!
+//! The directives of a package apply to its own units like the output of a
+//! build script: `rustc-cfg`, `rustc-check-cfg` and `rustc-env` to all of its
+//! targets, including their documentation, and `rustc-link-lib` to its
+//! library, or to its other targets without one. The `rustc-link-search`
+//! paths also apply to the units linking it, directly or not.
+//!
+//! The directives are part of the fingerprint of the units of the package,
+//! along with the files matching `rerun-if-changed` and the variables of
+//! `rerun-if-env-changed`, see `fingerprint::calculate_normal`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use cargo_util::ProcessBuilder;
+use cargo_util_schemas::manifest::TomlBuildDirectives;
+
+use crate::core::compiler::{BuildRunner, Unit};
+use crate::util::CargoResult;
+
+/// The `[build-directives]` of the package of `unit`, if any.
+pub fn directives(unit: &Unit) -> Option<&TomlBuildDirectives> {
+    unit.pkg
+        .manifest()
+        .resolved_toml()
+        .build_directives
+        .as_ref()
+}
+
+/// Adds the `--cfg` and `--check-cfg` flags, and the environment variables,
+/// of the directives of the package of `unit`.
+pub fn add_custom_flags(cmd: &mut ProcessBuilder, unit: &Unit) {
+    let Some(directives) = directives(unit) else {
+        return;
+    };
+    for cfg in directives.rustc_cfg.iter().flatten() {
+        cmd.arg("--cfg").arg(cfg);
+    }
+    for check_cfg in directives.rustc_check_cfg.iter().flatten() {
+        cmd.arg("--check-cfg").arg(check_cfg);
+    }
+    for (name, value) in directives.rustc_env.iter().flatten() {
+        cmd.env(name, value);
+    }
+}
+
+/// Adds the `-l` flags of the directives of the package of `unit` if
+/// `pass_l_flag`, and the `-L` flags of the directives of the packages it
+/// links, including its own.
+pub fn add_native_deps(
+    build_runner: &BuildRunner<'_, '_>,
+    unit: &Unit,
+    cmd: &mut ProcessBuilder,
+    pass_l_flag: bool,
+) {
+    if let Some(libs) = directives(unit).and_then(|d| d.rustc_link_lib.as_ref()) {
+        if pass_l_flag {
+            for lib in libs {
+                cmd.arg("-l").arg(lib);
+            }
+        }
+    }
+
+    // Like the outputs of build scripts, the search paths are propagated
+    // through the linkable dependencies which aren't for the host.
+    let mut seen = HashSet::new();
+    let mut stack = vec![unit];
+    while let Some(unit) = stack.pop() {
+        if !seen.insert(unit) {
+            continue;
+        }
+        if let Some(directives) = directives(unit) {
+            for path in directives.rustc_link_search.iter().flatten() {
+                cmd.arg("-L").arg(link_search_path(unit.pkg.root(), path));
+            }
+        }
+        stack.extend(
+            build_runner
+                .unit_deps(unit)
+                .iter()
+                .filter(|dep| !dep.unit.target.for_host() && dep.unit.target.is_linkable())
+                .map(|dep| &dep.unit),
+        );
+    }
+}
+
+/// Resolves a `rustc-link-search` path, like `native=libs`, relative to the
+/// package root.
+fn link_search_path(pkg_root: &Path, path: &str) -> String {
+    const KINDS: &[&str] = &["native", "crate", "dependency", "framework", "all"];
+    let (kind, dir) = match path.split_once('=') {
+        Some((kind, dir)) if KINDS.contains(&kind) => (Some(kind), dir),
+        _ => (None, path),
+    };
+    let dir = pkg_root.join(dir);
+    match kind {
+        Some(kind) => format!("{kind}={}", dir.display()),
+        None => dir.display().to_string(),
+    }
+}
+
+/// The files of the package at `pkg_root` matching the `rerun-if-changed`
+/// globs of its directives, relative to it.
+pub fn rerun_if_changed(
+    directives: &TomlBuildDirectives,
+    pkg_root: &Path,
+) -> CargoResult<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for pattern in directives.rerun_if_changed.iter().flatten() {
+        let root = glob::Pattern::escape(&pkg_root.to_string_lossy());
+        let full = Path::new(&root).join(pattern);
+        let matches = glob::glob(&full.to_string_lossy()).with_context(|| {
+            format!("invalid glob `{pattern}` in `build-directives.rerun-if-changed`")
+        })?;
+        for path in matches.filter_map(Result::ok) {
+            paths.push(path.strip_prefix(pkg_root).unwrap_or(&path).to_path_buf());
+        }
+    }
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
@@ -0,0 +1,217 @@
+//! Support for detecting changes of the build inputs between builds.
+//!
+//! With `-Zbuild-meta`, when a build comes to an end Cargo saves the inputs
+//! which affect the whole build in [`BUILD_META_FILE`] in the target
+//! directory: the version of `rustc`, and a hash of the relevant environment
+//! variables and config values. The next build compares its own inputs with
+//! them, and if units had to be rebuilt, warns about the inputs which changed
+//! since they are a likely cause. This complements the per-unit explanations
+//! of the fingerprints with a view of the whole build.
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use anyhow::{bail, Context as _};
+use serde::{Deserialize, Serialize};
+
+use crate::core::compiler::BuildContext;
+use crate::core::Workspace;
+use crate::util::context::{ConfigKey, ConfigValue};
+use crate::util::hex::short_hash;
+use crate::util::CargoResult;
+
+/// The file in the top-level `target` directory where the build inputs are
+/// stored.
+const BUILD_META_FILE: &str = ".build-meta";
+/// Current version of the on-disk format.
+const ON_DISK_VERSION: u32 = 0;
+
+/// Environment variables which affect how every unit is built.
+const ENV_VARS: &[&str] = &[
+    "AR",
+    "CC",
+    "CFLAGS",
+    "CXX",
+    "CXXFLAGS",
+    "RUSTC",
+    "RUSTC_WORKSPACE_WRAPPER",
+    "RUSTC_WRAPPER",
+    "RUSTDOC",
+    "RUSTDOCFLAGS",
+    "RUSTFLAGS",
+];
+/// Prefixes of the environment variables which affect how every unit is
+/// built, mostly config set through the environment.
+const ENV_PREFIXES: &[&str] = &[
+    "CARGO_BUILD_",
+    "CARGO_ENCODED_",
+    "CARGO_PROFILE_",
+    "CARGO_TARGET_",
+];
+/// Config values which affect how every unit is built.
+const CONFIG_KEYS: &[&str] = &[
+    "build.rustc",
+    "build.rustc-wrapper",
+    "build.rustc-workspace-wrapper",
+    "build.rustdoc",
+    "build.rustdocflags",
+    "build.rustflags",
+    "build.target",
+    "env",
+    "profile",
+    "target",
+];
+
+/// The inputs of a build, saved to disk.
+///
+/// Values are hashed, to avoid saving secrets which may be in the
+/// environment.
+#[derive(Serialize, Deserialize)]
+pub struct BuildInputs {
+    /// A schema version number, to handle older cargo's from trying to read
+    /// something that they don't understand.
+    version: u32,
+    /// First line of `rustc -vV`.
+    rustc: String,
+    /// Hashes of the values of the relevant environment variables which are
+    /// set.
+    env: BTreeMap<String, String>,
+    /// Hashes of the relevant config values which are set.
+    config: BTreeMap<String, String>,
+}
+
+impl BuildInputs {
+    /// Collects the inputs of the current build.
+    pub fn new(bcx: &BuildContext<'_, '_>) -> CargoResult<BuildInputs> {
+        let gctx = bcx.gctx;
+        let rustc = bcx
+            .rustc()
+            .verbose_version
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let env = gctx
+            .env()
+            .filter(|(key, _)| {
+                ENV_VARS.contains(key) || ENV_PREFIXES.iter().any(|p| key.starts_with(p))
+            })
+            // The target directory is where the inputs are saved, so it
+            // can't change between builds comparing them.
+            .filter(|(key, _)| *key != "CARGO_TARGET_DIR")
+            .map(|(key, value)| (key.to_string(), short_hash(&value)))
+            .collect();
+        let mut config = BTreeMap::new();
+        for key in CONFIG_KEYS {
+            if let Some(cv) = gctx.get_cv_with_env(&ConfigKey::from_str(key))? {
+                config.insert(key.to_string(), short_hash(&canonical(&cv)));
+            }
+        }
+        Ok(BuildInputs {
+            version: ON_DISK_VERSION,
+            rustc,
+            env,
+            config,
+        })
+    }
+
+    /// Describes the inputs which changed since `previous`.
+    pub fn changes(&self, previous: &BuildInputs) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.rustc != previous.rustc {
+            changes.push(format!(
+                "rustc changed from `{}` to `{}`",
+                previous.rustc, self.rustc
+            ));
+        }
+        changes.extend(diff("environment variable", &previous.env, &self.env));
+        changes.extend(diff("config", &previous.config, &self.config));
+        changes
+    }
+
+    /// Loads the inputs saved by the previous build, if any.
+    pub fn load(ws: &Workspace<'_>) -> CargoResult<Option<BuildInputs>> {
+        let path = ws.target_dir();
+        if !path.as_path_unlocked().join(BUILD_META_FILE).exists() {
+            return Ok(None);
+        }
+        let file = path.open_ro_shared(BUILD_META_FILE, ws.gctx(), "build inputs")?;
+        let mut contents = String::new();
+        file.file()
+            .read_to_string(&mut contents)
+            .with_context(|| "failed to read build inputs")?;
+        let inputs: BuildInputs =
+            serde_json::from_str(&contents).with_context(|| "failed to load build inputs")?;
+        if inputs.version != ON_DISK_VERSION {
+            bail!("build inputs were saved from a future version of Cargo");
+        }
+        Ok(Some(inputs))
+    }
+
+    /// Saves the inputs for the next build.
+    pub fn save(&self, ws: &Workspace<'_>) {
+        if let Err(e) = self.write(ws) {
+            crate::display_warning_with_error(
+                "failed to write build inputs",
+                &e,
+                &mut ws.gctx().shell(),
+            );
+        }
+    }
+
+    fn write(&self, ws: &Workspace<'_>) -> CargoResult<()> {
+        let contents = serde_json::to_vec(self).unwrap();
+        let file =
+            ws.target_dir()
+                .open_rw_exclusive_create(BUILD_META_FILE, ws.gctx(), "build inputs")?;
+        let mut file = file.file();
+        file.set_len(0)?;
+        file.write_all(&contents)?;
+        Ok(())
+    }
+}
+
+/// Describes the entries of `what` which were added, removed, or changed.
+fn diff(
+    what: &str,
+    previous: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (key, hash) in current {
+        match previous.get(key) {
+            None => changes.push(format!("{what} `{key}` was set")),
+            Some(prev) if prev != hash => changes.push(format!("{what} `{key}` changed")),
+            Some(_) => {}
+        }
+    }
+    for key in previous.keys() {
+        if !current.contains_key(key) {
+            changes.push(format!("{what} `{key}` was unset"));
+        }
+    }
+    changes
+}
+
+/// Renders a config value independently of where it was defined, with
+/// tables sorted by key.
+fn canonical(cv: &ConfigValue) -> String {
+    match cv {
+        ConfigValue::Integer(i, _) => i.to_string(),
+        ConfigValue::String(s, _) => format!("{s:?}"),
+        ConfigValue::Boolean(b, _) => b.to_string(),
+        ConfigValue::List(list, _) => {
+            let items: Vec<String> = list.iter().map(|(s, _)| format!("{s:?}")).collect();
+            format!("[{}]", items.join(","))
+        }
+        ConfigValue::Table(table, _) => {
+            let sorted: BTreeMap<&String, String> =
+                table.iter().map(|(k, v)| (k, canonical(v))).collect();
+            let items: Vec<String> = sorted
+                .into_iter()
+                .map(|(k, v)| format!("{k:?}={v}"))
+                .collect();
+            format!("{{{}}}", items.join(","))
+        }
+    }
+}
@@ -5,17 +5,25 @@
 //! shared with an external build system. Each Invocation in the BuildPlan comprises a single
 //! subprocess and defines the build environment, the outputs produced by the subprocess, and the
 //! dependencies on other Invocations.
+//!
+//! The output of `cargo build --build-plan` is stable. Any incompatible change
+//! to it must bump [`VERSION`], while new fields can be added at any time.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
 use std::path::{Path, PathBuf};
 
 use serde::Serialize;
 
 use super::build_runner::OutputFile;
-use super::{BuildRunner, CompileKind, CompileMode, Unit};
+use super::{fingerprint, BuildRunner, CompileKind, CompileMode, Unit};
 use crate::core::TargetKind;
 use crate::util::{internal, CargoResult, GlobalContext};
-use cargo_util::ProcessBuilder;
+use cargo_util::{paths, ProcessBuilder};
+
+/// The version of the build plan format, which external build systems should
+/// check before reading the rest of it.
+pub const VERSION: u32 = 1;
 
 #[derive(Debug, Serialize)]
 struct Invocation {
@@ -25,6 +33,14 @@ struct Invocation {
     kind: CompileKind,
     compile_mode: CompileMode,
     deps: Vec<usize>,
+    /// The files the invocation reads, see [`inputs`]. The manifests are
+    /// listed in [`SerializedBuildPlan::inputs`].
+    inputs: Vec<PathBuf>,
+    /// The files the previous build of the invocation read, see
+    /// [`previous_build_inputs`]. This is best-effort, and omitted when
+    /// nothing is known.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    previous_build_inputs: Vec<PathBuf>,
     outputs: Vec<PathBuf>,
     links: BTreeMap<PathBuf, PathBuf>,
     program: String,
@@ -41,12 +57,18 @@ pub struct BuildPlan {
 
 #[derive(Debug, Serialize)]
 struct SerializedBuildPlan {
+    version: u32,
     invocations: Vec<Invocation>,
     inputs: Vec<PathBuf>,
 }
 
 impl Invocation {
-    pub fn new(unit: &Unit, deps: Vec<usize>) -> Invocation {
+    pub fn new(
+        unit: &Unit,
+        deps: Vec<usize>,
+        inputs: Vec<PathBuf>,
+        previous_build_inputs: Vec<PathBuf>,
+    ) -> Invocation {
         let id = unit.pkg.package_id();
         Invocation {
             package_name: id.name().to_string(),
@@ -55,6 +77,8 @@ impl Invocation {
             target_kind: unit.target.kind().clone(),
             compile_mode: unit.mode,
             deps,
+            inputs,
+            previous_build_inputs,
             outputs: Vec::new(),
             links: BTreeMap::new(),
             program: String::new(),
@@ -115,7 +139,12 @@ impl BuildPlan {
             .iter()
             .map(|dep| self.invocation_map[&dep.unit.buildkey()])
             .collect();
-        let invocation = Invocation::new(unit, deps);
+        let invocation = Invocation::new(
+            unit,
+            deps,
+            inputs(unit),
+            previous_build_inputs(build_runner, unit)?,
+        );
         self.plan.invocations.push(invocation);
         Ok(())
     }
@@ -148,11 +177,311 @@ impl BuildPlan {
         let encoded = serde_json::to_string(&self.plan).unwrap();
         crate::drop_println!(gctx, "{}", encoded);
     }
+
+    /// Writes the plan as a [Ninja](https://ninja-build.org) build file, for
+    /// `cargo build --emit-ninja`.
+    ///
+    /// Each invocation becomes a build statement, with its [`inputs`] as
+    /// inputs, the outputs of its dependencies as implicit inputs and
+    /// the dep-info file of rustc as depfile. Build scripts write their output
+    /// to the `output` file next to their `OUT_DIR`, like in a regular build,
+    /// and the rustc commands read the directives from the outputs which apply
+    /// to them, see [`build_script_args`]. The commands are run by a POSIX
+    /// shell, from the directory rustc would run in.
+    pub fn output_ninja(self, path: &Path) -> CargoResult<()> {
+        let invocations = &self.plan.invocations;
+        let outputs: Vec<_> = invocations.iter().map(ninja_outputs).collect();
+
+        let mut ninja = String::new();
+        writeln!(
+            ninja,
+            "# Generated by `cargo build --emit-ninja`, which must be run again"
+        )?;
+        writeln!(ninja, "# when one of these manifests changes:")?;
+        for input in &self.plan.inputs {
+            writeln!(ninja, "#   {}", input.display())?;
+        }
+        writeln!(ninja)?;
+        writeln!(ninja, "ninja_required_version = 1.10")?;
+        writeln!(ninja)?;
+        writeln!(ninja, "rule cargo")?;
+        writeln!(ninja, "  command = $command")?;
+        writeln!(ninja, "  description = $description")?;
+
+        for (i, (invocation, outputs)) in invocations.iter().zip(&outputs).enumerate() {
+            if outputs.is_empty() {
+                continue;
+            }
+            let build_scripts = build_script_outputs(invocations, &outputs, i);
+            let deps: Vec<_> = invocation
+                .deps
+                .iter()
+                .flat_map(|&dep| &outputs[dep])
+                .collect();
+            writeln!(ninja)?;
+            write!(ninja, "build {}: cargo", ninja_paths(outputs))?;
+            if !invocation.inputs.is_empty() {
+                write!(ninja, " {}", ninja_paths(&invocation.inputs))?;
+            }
+            if !deps.is_empty() {
+                write!(ninja, " | {}", ninja_paths(deps))?;
+            }
+            writeln!(ninja)?;
+            writeln!(
+                ninja,
+                "  command = {}",
+                ninja_escape(&shell_command(invocation, &outputs[0], &build_scripts))
+            )?;
+            let description = if invocation.compile_mode.is_run_custom_build() {
+                "Running build script of"
+            } else {
+                "Compiling"
+            };
+            writeln!(
+                ninja,
+                "  description = {description} {} v{}",
+                invocation.package_name, invocation.package_version
+            )?;
+            if let Some(depfile) = rustc_dep_info(invocation) {
+                writeln!(
+                    ninja,
+                    "  depfile = {}",
+                    ninja_escape(&depfile.to_string_lossy())
+                )?;
+                writeln!(ninja, "  deps = gcc")?;
+            }
+        }
+
+        paths::write(path, ninja)
+    }
+}
+
+/// The files read by the invocation of `unit` which are known without
+/// building it: the root source file of the target, or the directory of the
+/// package for a build script, which runs again when any of its files changes.
+fn inputs(unit: &Unit) -> Vec<PathBuf> {
+    if unit.mode.is_run_custom_build() {
+        return vec![unit.pkg.root().to_path_buf()];
+    }
+    unit.target
+        .src_path()
+        .path()
+        .map(Path::to_path_buf)
+        .into_iter()
+        .collect()
+}
+
+/// The files read by the previous build of `unit`, if there was one: the
+/// source files listed in the dep-info file of the target, or the
+/// `rerun-if-changed` paths of the previous run of a build script.
+///
+/// The plan is computed without running anything, so these may be out of
+/// date, or missing when nothing was built yet.
+fn previous_build_inputs(
+    build_runner: &BuildRunner<'_, '_>,
+    unit: &Unit,
+) -> CargoResult<Vec<PathBuf>> {
+    let pkg_root = unit.pkg.root();
+    if unit.mode.is_run_custom_build() {
+        let rerun_if_changed = build_runner
+            .build_explicit_deps
+            .get(unit)
+            .map(|deps| deps.rerun_if_changed.as_slice())
+            .unwrap_or_default();
+        return Ok(rerun_if_changed
+            .iter()
+            .map(|path| pkg_root.join(path))
+            .collect());
+    }
+
+    let target_root = build_runner.bcx.ws.target_dir().into_path_unlocked();
+    let dep_info = fingerprint::dep_info_loc(build_runner, unit);
+    let inputs = fingerprint::parse_dep_info(pkg_root, &target_root, &dep_info)?
+        .map(|info| info.files)
+        .unwrap_or_default();
+    Ok(inputs)
+}
+
+/// The files written by an invocation, its first being the one the command
+/// produces.
+fn ninja_outputs(invocation: &Invocation) -> Vec<PathBuf> {
+    if invocation.compile_mode.is_run_custom_build() {
+        let out_dir = invocation.env.get("OUT_DIR").map(Path::new);
+        return out_dir
+            .and_then(Path::parent)
+            .map(|dir| dir.join("output"))
+            .into_iter()
+            .collect();
+    }
+    invocation
+        .outputs
+        .iter()
+        .chain(invocation.links.keys())
+        .cloned()
+        .collect()
+}
+
+/// The `output` files of the build scripts whose directives apply to the
+/// invocation at `index`, along with whether the build script is the one of
+/// its own package. The build scripts of the other packages it depends on only
+/// contribute their link search paths, like in a regular build.
+fn build_script_outputs<'a>(
+    invocations: &[Invocation],
+    outputs: &'a [Vec<PathBuf>],
+    index: usize,
+) -> Vec<(&'a Path, bool)> {
+    let invocation = &invocations[index];
+    if invocation.compile_mode.is_run_custom_build() {
+        return Vec::new();
+    }
+    let mut build_scripts = Vec::new();
+    let mut visited = BTreeSet::new();
+    let mut stack = invocation.deps.clone();
+    while let Some(dep) = stack.pop() {
+        if !visited.insert(dep) {
+            continue;
+        }
+        let dep_invocation = &invocations[dep];
+        if dep_invocation.compile_mode.is_run_custom_build() {
+            let own = dep_invocation.package_name == invocation.package_name
+                && dep_invocation.package_version == invocation.package_version;
+            if let Some(output) = outputs[dep].first() {
+                build_scripts.push((output.as_path(), own));
+            }
+        } else if dep_invocation.target_kind != TargetKind::CustomBuild {
+            // The dependencies of build scripts only apply to the build script.
+            stack.extend(&dep_invocation.deps);
+        }
+    }
+    build_scripts.sort();
+    build_scripts
+}
+
+/// Shell commands adding the directives of the `output` files of build scripts
+/// to the positional parameters, which are passed to rustc, and exporting the
+/// environment variables they set.
+fn build_script_args(build_scripts: &[(&Path, bool)]) -> String {
+    let mut command = String::from("set -- && ");
+    for (output, own) in build_scripts {
+        let directives = if *own {
+            concat!(
+                r#"cargo:rustc-cfg=*|cargo::rustc-cfg=*) set -- "$@" --cfg "${line#*=}";; "#,
+                r#"cargo:rustc-check-cfg=*|cargo::rustc-check-cfg=*) set -- "$@" --check-cfg "${line#*=}";; "#,
+                r#"cargo:rustc-env=*|cargo::rustc-env=*) export "${line#*=}";; "#,
+                r#"cargo:rustc-link-lib=*|cargo::rustc-link-lib=*) set -- "$@" -l "${line#*=}";; "#,
+                r#"cargo:rustc-link-search=*|cargo::rustc-link-search=*) set -- "$@" -L "${line#*=}";; "#,
+                r#"cargo:rustc-link-arg=*|cargo::rustc-link-arg=*) set -- "$@" -C "link-arg=${line#*=}";; "#,
+                r#"cargo:rustc-flags=*|cargo::rustc-flags=*) set -- "$@" ${line#*=};; "#,
+            )
+        } else {
+            r#"cargo:rustc-link-search=*|cargo::rustc-link-search=*) set -- "$@" -L "${line#*=}";; "#
+        };
+        write!(
+            command,
+            r#"while IFS= read -r line; do case "$line" in {directives}esac; done < {} && "#,
+            shell_quote(&output.to_string_lossy())
+        )
+        .unwrap();
+    }
+    command
+}
+
+/// The shell command running `invocation`, then creating its hard links.
+fn shell_command(
+    invocation: &Invocation,
+    output: &Path,
+    build_scripts: &[(&Path, bool)],
+) -> String {
+    let mut command = String::new();
+    let is_run_custom_build = invocation.compile_mode.is_run_custom_build();
+    if is_run_custom_build {
+        if let Some(out_dir) = invocation.env.get("OUT_DIR") {
+            write!(command, "mkdir -p {} && ", shell_quote(out_dir)).unwrap();
+        }
+    }
+    if let Some(cwd) = &invocation.cwd {
+        write!(command, "cd {} && ", shell_quote(&cwd.to_string_lossy())).unwrap();
+    }
+    if !build_scripts.is_empty() {
+        command.push_str(&build_script_args(build_scripts));
+    }
+    command.push_str("env");
+    for (key, value) in &invocation.env {
+        write!(command, " {}", shell_quote(&format!("{key}={value}"))).unwrap();
+    }
+    write!(command, " {}", shell_quote(&invocation.program)).unwrap();
+    for arg in &invocation.args {
+        write!(command, " {}", shell_quote(arg)).unwrap();
+    }
+    if !build_scripts.is_empty() {
+        command.push_str(r#" "$@""#);
+    }
+    if is_run_custom_build {
+        write!(command, " > {}", shell_quote(&output.to_string_lossy())).unwrap();
+    }
+    for (link, src) in &invocation.links {
+        write!(
+            command,
+            " && ln -f {} {}",
+            shell_quote(&src.to_string_lossy()),
+            shell_quote(&link.to_string_lossy())
+        )
+        .unwrap();
+    }
+    command
+}
+
+/// The dep-info file rustc writes next to the outputs of `invocation`.
+fn rustc_dep_info(invocation: &Invocation) -> Option<PathBuf> {
+    if invocation.compile_mode.is_run_custom_build() {
+        return None;
+    }
+    let arg = |name: &str| {
+        let i = invocation.args.iter().position(|arg| arg == name)?;
+        invocation.args.get(i + 1)
+    };
+    let crate_name = arg("--crate-name")?;
+    let out_dir = arg("--out-dir")?;
+    let extra_filename = invocation
+        .args
+        .iter()
+        .find_map(|arg| arg.strip_prefix("extra-filename="))
+        .unwrap_or_default();
+    Some(Path::new(out_dir).join(format!("{crate_name}{extra_filename}.d")))
+}
+
+/// Quotes `s` for a POSIX shell.
+///
+/// Ninja can't represent newlines in a variable, so they are written by
+/// `printf` instead.
+fn shell_quote(s: &str) -> String {
+    if s.contains('\n') {
+        let escaped = s.replace('\\', "\\\\").replace('\n', "\\n");
+        return format!("\"$(printf '%b' {})\"", shell_quote(&escaped));
+    }
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn ninja_escape(s: &str) -> String {
+    s.replace('$', "$$")
+}
+
+fn ninja_paths<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) -> String {
+    paths
+        .into_iter()
+        .map(|path| {
+            ninja_escape(&path.to_string_lossy())
+                .replace(' ', "$ ")
+                .replace(':', "$:")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 impl SerializedBuildPlan {
     pub fn new() -> SerializedBuildPlan {
         SerializedBuildPlan {
+            version: VERSION,
             invocations: Vec::new(),
             inputs: Vec::new(),
         }
@@ -0,0 +1,160 @@
+//! Support for recording a summary of each build on disk.
+//!
+//! With `-Zbuild-reports`, when a build comes to an end Cargo appends a
+//! [`BuildSummary`] to [`BUILDS_FILE`] in the [`REPORTS_DIR`] directory of the
+//! target directory. Users can browse the recorded builds with
+//! `cargo report builds`, see [`crate::ops::cargo_report`].
+
+use crate::core::Workspace;
+use crate::util::CargoResult;
+use anyhow::{bail, format_err, Context};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// The directory in the top-level `target` directory where build reports are
+/// stored.
+pub const REPORTS_DIR: &str = "reports";
+/// The file in [`REPORTS_DIR`] where build summaries are stored.
+const BUILDS_FILE: &str = "builds.json";
+/// Current version of the on-disk format.
+const ON_DISK_VERSION: u32 = 0;
+/// Max number of build summaries to save on disk.
+const MAX_BUILDS: usize = 20;
+
+/// The structure saved to disk containing the build summaries.
+#[derive(Serialize, Deserialize)]
+pub struct OnDiskBuilds {
+    /// A schema version number, to handle older cargo's from trying to read
+    /// something that they don't understand.
+    version: u32,
+    /// The ID to use for the next build summary to save.
+    next_id: u32,
+    /// Available build summaries, oldest first.
+    builds: Vec<BuildSummary>,
+}
+
+/// A summary of a single build.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BuildSummary {
+    /// Unique reference to the build for the `--id` CLI flag.
+    pub id: u32,
+    /// When the build finished, in RFC 3339 format.
+    pub finished: String,
+    /// The requested profile.
+    pub profile: String,
+    /// Whether the build succeeded.
+    pub success: bool,
+    /// Wall-clock duration of the Cargo invocation, in seconds.
+    pub duration: f64,
+    /// Number of units which were up to date.
+    pub fresh: u32,
+    /// Number of units which had to be rebuilt.
+    pub dirty: u32,
+    /// Number of warnings emitted by the compiler.
+    pub warnings: usize,
+    /// Number of errors encountered.
+    pub errors: usize,
+}
+
+impl Default for OnDiskBuilds {
+    fn default() -> OnDiskBuilds {
+        OnDiskBuilds {
+            version: ON_DISK_VERSION,
+            next_id: 1,
+            builds: Vec::new(),
+        }
+    }
+}
+
+impl OnDiskBuilds {
+    /// Loads the on-disk build summaries, or an empty set if none were saved
+    /// yet.
+    pub fn load(ws: &Workspace<'_>) -> CargoResult<OnDiskBuilds> {
+        let path = ws.target_dir().join(REPORTS_DIR);
+        if !path.as_path_unlocked().join(BUILDS_FILE).exists() {
+            return Ok(OnDiskBuilds::default());
+        }
+        let file = path.open_ro_shared(BUILDS_FILE, ws.gctx(), "build reports")?;
+        let mut contents = String::new();
+        file.file()
+            .read_to_string(&mut contents)
+            .with_context(|| "failed to read build reports")?;
+        let on_disk: OnDiskBuilds =
+            serde_json::from_str(&contents).with_context(|| "failed to load build reports")?;
+        if on_disk.version != ON_DISK_VERSION {
+            bail!(
+                "unable to read build reports; reports were saved from a future version of Cargo"
+            );
+        }
+        Ok(on_disk)
+    }
+
+    /// Saves a new build summary, assigning it the next ID.
+    pub fn save_build(mut self, ws: &Workspace<'_>, mut summary: BuildSummary) -> CargoResult<u32> {
+        summary.id = self.next_id;
+        self.next_id += 1;
+        self.builds.push(summary);
+        if self.builds.len() > MAX_BUILDS {
+            self.builds.remove(0);
+        }
+        self.write(ws)?;
+        Ok(self.next_id - 1)
+    }
+
+    /// Removes all but the `keep` most recent on-disk build summaries,
+    /// returning how many were removed.
+    pub fn prune(ws: &Workspace<'_>, keep: usize) -> CargoResult<usize> {
+        let mut on_disk = OnDiskBuilds::load(ws)?;
+        let removed = on_disk.builds.len().saturating_sub(keep);
+        if removed > 0 {
+            on_disk.builds.drain(..removed);
+            on_disk.write(ws)?;
+        }
+        Ok(removed)
+    }
+
+    fn write(&self, ws: &Workspace<'_>) -> CargoResult<()> {
+        let on_disk = serde_json::to_vec(self).unwrap();
+        let file = ws.target_dir().join(REPORTS_DIR).open_rw_exclusive_create(
+            BUILDS_FILE,
+            ws.gctx(),
+            "build reports",
+        )?;
+        let mut file = file.file();
+        file.set_len(0)?;
+        file.write_all(&on_disk)?;
+        Ok(())
+    }
+
+    /// Returns all build summaries, oldest first.
+    pub fn builds(&self) -> &[BuildSummary] {
+        &self.builds
+    }
+
+    /// Returns the build summary with the given ID.
+    pub fn get_build(&self, id: u32) -> CargoResult<&BuildSummary> {
+        self.builds.iter().find(|b| b.id == id).ok_or_else(|| {
+            let available = itertools::join(self.builds.iter().map(|b| b.id), ", ");
+            format_err!(
+                "could not find build with ID {}\n\
+                 Available IDs are: {}",
+                id,
+                available
+            )
+        })
+    }
+}
+
+/// Records the summary of a build which just finished.
+///
+/// Failures are only reported as a warning, since they should not fail the
+/// build itself.
+pub fn save_build_summary(ws: &Workspace<'_>, summary: BuildSummary) {
+    if let Err(e) = OnDiskBuilds::load(ws).and_then(|builds| builds.save_build(ws, summary)) {
+        crate::display_warning_with_error(
+            "failed to write on-disk build report",
+            &e,
+            &mut ws.gctx().shell(),
+        );
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex};
 
 use crate::core::compiler::compilation::{self, UnitOutput};
@@ -16,10 +17,11 @@ use jobserver::Client;
 
 use super::build_plan::BuildPlan;
 use super::custom_build::{self, BuildDeps, BuildScriptOutputs, BuildScripts};
-use super::fingerprint::Fingerprint;
+use super::fingerprint::{Fingerprint, FingerprintIndex};
 use super::job_queue::JobQueue;
 use super::layout::Layout;
 use super::lto::Lto;
+use super::system_deps::{self, SystemDep};
 use super::unit_graph::UnitDep;
 use super::{
     BuildContext, Compilation, CompileKind, CompileMode, Executor, FileFlavor, RustDocFingerprint,
@@ -48,8 +50,12 @@ pub struct BuildRunner<'a, 'gctx> {
     pub build_explicit_deps: HashMap<Unit, BuildDeps>,
     /// Fingerprints used to detect if a unit is out-of-date.
     pub fingerprints: HashMap<Unit, Arc<Fingerprint>>,
+    /// Fingerprint index of each layout, or `None` if the index is disabled.
+    pub fingerprint_indexes: HashMap<CompileKind, Option<Arc<FingerprintIndex>>>,
     /// Cache of file mtimes to reduce filesystem hits.
     pub mtime_cache: HashMap<PathBuf, FileTime>,
+    /// Cache of the content hashes of files, with `build.fingerprint = "hash"`.
+    pub hash_cache: HashMap<PathBuf, u64>,
     /// A set used to track which units have been compiled.
     /// A unit may appear in the job graph multiple times as a dependency of
     /// multiple packages, but it only needs to run once.
@@ -85,6 +91,14 @@ pub struct BuildRunner<'a, 'gctx> {
     /// because the target has a type error. This is in an Arc<Mutex<..>>
     /// because it is continuously updated as the job progresses.
     pub failed_scrape_units: Arc<Mutex<HashSet<Metadata>>>,
+
+    /// The `[system-dependencies]` found for each package and platform it is
+    /// built for, given to its build script.
+    pub system_deps: HashMap<(PackageId, CompileKind), Vec<SystemDep>>,
+
+    /// Set once the remote backend of the artifact cache couldn't be
+    /// reached, so that it isn't tried again for every unit of the build.
+    pub remote_artifact_cache_unreachable: Arc<AtomicBool>,
 }
 
 impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
@@ -112,7 +126,9 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
             compilation: Compilation::new(bcx)?,
             build_script_outputs: Arc::new(Mutex::new(BuildScriptOutputs::default())),
             fingerprints: HashMap::new(),
+            fingerprint_indexes: HashMap::new(),
             mtime_cache: HashMap::new(),
+            hash_cache: HashMap::new(),
             compiled: HashSet::new(),
             build_scripts: HashMap::new(),
             build_explicit_deps: HashMap::new(),
@@ -123,6 +139,8 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
             lto: HashMap::new(),
             metadata_for_doc_units: HashMap::new(),
             failed_scrape_units: Arc::new(Mutex::new(HashSet::new())),
+            system_deps: HashMap::new(),
+            remote_artifact_cache_unreachable: Arc::new(AtomicBool::new(false)),
         })
     }
 
@@ -147,6 +165,7 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
         self.lto = super::lto::generate(self.bcx)?;
         self.prepare_units()?;
         self.prepare()?;
+        self.system_deps = system_deps::probe(self.bcx)?;
         custom_build::build_map(&mut self)?;
         self.check_collisions()?;
         self.compute_metadata_for_doc_units();
@@ -183,7 +202,10 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
 
         if build_plan {
             plan.set_inputs(self.build_plan_inputs()?);
-            plan.output_plan(self.bcx.gctx);
+            match &self.bcx.build_config.emit_ninja {
+                Some(path) => plan.output_ninja(path)?,
+                None => plan.output_plan(self.bcx.gctx),
+            }
         }
 
         // Add `OUT_DIR` to env vars if unit has a build script.
@@ -227,7 +249,12 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
                     self.compilation
                         .tests
                         .push(self.unit_output(unit, &output.path));
-                } else if unit.target.is_executable() {
+                    if let Some(fingerprint) = self.fingerprints.get(unit) {
+                        self.compilation
+                            .test_fingerprints
+                            .insert(unit.clone(), fingerprint.hash_u64());
+                    }
+                } else if unit.target.is_executable() || unit.target.is_fuzz() {
                     self.compilation
                         .binaries
                         .push(self.unit_output(unit, bindst));
@@ -274,7 +301,7 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
                 use super::MessageFormat;
                 let format = match self.bcx.build_config.message_format {
                     MessageFormat::Short => "short",
-                    MessageFormat::Human => "human",
+                    MessageFormat::Human | MessageFormat::Sarif => "human",
                     MessageFormat::Json { .. } => "json",
                 };
                 args.push("--error-format".into());
@@ -1,6 +1,6 @@
 //! Type definitions for the result of a compilation.
 
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ffi::{OsStr, OsString};
 use std::path::PathBuf;
 
@@ -69,6 +69,11 @@ pub struct Compilation<'gctx> {
     /// An array of all tests created during this compilation.
     pub tests: Vec<UnitOutput>,
 
+    /// The fingerprint hashes of the units of [`Compilation::tests`], used to
+    /// skip the test binaries which already passed with
+    /// `cargo test --cache-results`.
+    pub test_fingerprints: HashMap<Unit, u64>,
+
     /// An array of all binaries created.
     pub binaries: Vec<UnitOutput>,
 
@@ -120,6 +125,10 @@ pub struct Compilation<'gctx> {
     primary_rustc_process: Option<ProcessBuilder>,
 
     target_runners: HashMap<CompileKind, Option<(PathBuf, Vec<String>)>>,
+    /// Labeled runners from `target.<triple>.runners` for each requested target.
+    labeled_target_runners: HashMap<CompileKind, BTreeMap<String, (PathBuf, Vec<String>)>>,
+    /// The label of the runner selected with [`Compilation::select_target_runner`].
+    target_runner_label: Option<String>,
     /// The linker to use for each host or target.
     target_linkers: HashMap<CompileKind, Option<PathBuf>>,
 }
@@ -145,6 +154,7 @@ impl<'gctx> Compilation<'gctx> {
             deps_output: HashMap::new(),
             sysroot_target_libdir: get_sysroot_target_libdir(bcx)?,
             tests: Vec::new(),
+            test_fingerprints: HashMap::new(),
             binaries: Vec::new(),
             cdylibs: Vec::new(),
             root_crate_names: Vec::new(),
@@ -162,6 +172,13 @@ impl<'gctx> Compilation<'gctx> {
                 .chain(Some(&CompileKind::Host))
                 .map(|kind| Ok((*kind, target_runner(bcx, *kind)?)))
                 .collect::<CargoResult<HashMap<_, _>>>()?,
+            labeled_target_runners: bcx
+                .build_config
+                .requested_kinds
+                .iter()
+                .map(|kind| Ok((*kind, labeled_target_runners(bcx, *kind)?)))
+                .collect::<CargoResult<HashMap<_, _>>>()?,
+            target_runner_label: None,
             target_linkers: bcx
                 .build_config
                 .requested_kinds
@@ -237,9 +254,40 @@ impl<'gctx> Compilation<'gctx> {
     }
 
     pub fn target_runner(&self, kind: CompileKind) -> Option<&(PathBuf, Vec<String>)> {
+        if let Some(label) = self.target_runner_label(kind) {
+            return Some(&self.labeled_target_runners[&kind][label]);
+        }
         self.target_runners.get(&kind).and_then(|x| x.as_ref())
     }
 
+    /// Gets the label of the selected runner, if `kind` has a runner of that
+    /// label in `target.<triple>.runners`.
+    pub fn target_runner_label(&self, kind: CompileKind) -> Option<&str> {
+        let label = self.target_runner_label.as_deref()?;
+        self.labeled_target_runners
+            .get(&kind)?
+            .contains_key(label)
+            .then_some(label)
+    }
+
+    /// Gets the labels of all `target.<triple>.runners` configured for the
+    /// requested targets.
+    pub fn target_runner_labels(&self) -> BTreeSet<&str> {
+        self.labeled_target_runners
+            .values()
+            .flat_map(|runners| runners.keys().map(String::as_str))
+            .collect()
+    }
+
+    /// Switches to the runner with the given label for every requested
+    /// target that has it in `target.<triple>.runners`.
+    ///
+    /// Targets without a runner of that label use their default runner from
+    /// `target.<triple>.runner`, as does every target with `None`.
+    pub fn select_target_runner(&mut self, label: Option<&str>) {
+        self.target_runner_label = label.map(str::to_string);
+    }
+
     /// Gets the user-specified linker for a particular host or target.
     pub fn target_linker(&self, kind: CompileKind) -> Option<PathBuf> {
         self.target_linkers.get(&kind).and_then(|x| x.clone())
@@ -453,8 +501,7 @@ fn target_runner(
     let key = format!("target.{}.runner", target);
 
     if let Some(v) = bcx.gctx.get::<Option<context::PathAndArgs>>(&key)? {
-        let path = v.path.resolve_program(bcx.gctx);
-        return Ok(Some((path, v.args)));
+        return Ok(Some(v.resolve(bcx.gctx)?));
     }
 
     // try target.'cfg(...)'.runner
@@ -477,12 +524,26 @@ fn target_runner(
             runner.definition
         );
     }
-    Ok(matching_runner.map(|(_k, runner)| {
-        (
-            runner.val.path.clone().resolve_program(bcx.gctx),
-            runner.val.args.clone(),
-        )
-    }))
+    matching_runner
+        .map(|(_k, runner)| runner.val.resolve(bcx.gctx))
+        .transpose()
+}
+
+/// Gets the labeled runners from `target.{}.runners` for a particular target.
+fn labeled_target_runners(
+    bcx: &BuildContext<'_, '_>,
+    kind: CompileKind,
+) -> CargoResult<BTreeMap<String, (PathBuf, Vec<String>)>> {
+    let target = bcx.target_data.short_name(&kind);
+    let key = format!("target.{}.runners", target);
+    let runners = bcx
+        .gctx
+        .get::<Option<BTreeMap<String, context::PathAndArgs>>>(&key)?
+        .unwrap_or_default();
+    runners
+        .into_iter()
+        .map(|(label, v)| Ok((label, v.resolve(bcx.gctx)?)))
+        .collect()
 }
 
 /// Gets the user-specified linker for a particular host or target from the configuration.
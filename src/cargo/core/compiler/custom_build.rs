@@ -31,6 +31,7 @@
 //! [`CompileMode::RunCustomBuild`]: super::CompileMode
 //! [instructions]: https://doc.rust-lang.org/cargo/reference/build-scripts.html#outputs-of-the-build-script
 
+use super::unit_graph::UnitDep;
 use super::{fingerprint, BuildRunner, Job, Unit, Work};
 use crate::core::compiler::artifact;
 use crate::core::compiler::build_runner::Metadata;
@@ -43,10 +44,12 @@ use crate::util::machine_message::{self, Message};
 use anyhow::{bail, Context as _};
 use cargo_platform::Cfg;
 use cargo_util::paths;
-use cargo_util_schemas::manifest::RustVersion;
+use cargo_util_schemas::manifest::{RustVersion, TomlLinksMetadataType};
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::{Entry, HashMap};
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Output};
 use std::str::{self, FromStr};
 use std::sync::{Arc, Mutex};
 
@@ -60,6 +63,9 @@ const OLD_CARGO_WARNING_SYNTAX: &str = "cargo:warning=";
 ///
 /// [the doc]: https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#cargo-warning
 const NEW_CARGO_WARNING_SYNTAX: &str = "cargo::warning=";
+/// A build script instruction that tells Cargo to render a structured
+/// diagnostic, given as JSON, see [`BuildScriptDiagnostic`].
+const CARGO_DIAGNOSTIC_SYNTAX: &str = "cargo::diagnostic=";
 /// Contains the parsed output of a custom build script.
 #[derive(Clone, Debug, Hash, Default)]
 pub struct BuildOutput {
@@ -75,6 +81,12 @@ pub struct BuildOutput {
     pub check_cfgs: Vec<String>,
     /// Additional environment variables to run the compiler with.
     pub env: Vec<(String, String)>,
+    /// Various `--cfg` flags to pass to the compiler for some kinds of
+    /// targets only, from the scoped tables of a build script override.
+    pub target_cfgs: Vec<(LinkArgTarget, String)>,
+    /// Additional environment variables to run the compiler with for some
+    /// kinds of targets only, like [`BuildOutput::target_cfgs`].
+    pub target_env: Vec<(LinkArgTarget, (String, String))>,
     /// Metadata to pass to the immediate dependencies.
     pub metadata: Vec<(String, String)>,
     /// Paths to trigger a rerun of this build script.
@@ -87,6 +99,76 @@ pub struct BuildOutput {
     /// These are only displayed if this is a "local" package, `-vv` is used,
     /// or there is a build error for any target in this package.
     pub warnings: Vec<String>,
+    /// Diagnostics printed with `cargo::diagnostic`, displayed like warnings.
+    pub diagnostics: Vec<BuildScriptDiagnostic>,
+    /// Whether the build script declared with `cargo::nondeterministic` that
+    /// its outputs may differ between runs with the same inputs, so that its
+    /// runs aren't cached by `build.artifact-cache`.
+    pub nondeterministic: bool,
+}
+
+/// A diagnostic printed by a build script with `cargo::diagnostic`, like
+/// `cargo::diagnostic={"level":"error","message":"libfoo not found"}`.
+#[derive(Clone, Debug, Hash, Serialize, Deserialize)]
+pub struct BuildScriptDiagnostic {
+    pub level: DiagnosticLevel,
+    pub message: String,
+    /// The file the diagnostic is about, relative to the package root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help: Option<String>,
+}
+
+/// The level of a [`BuildScriptDiagnostic`], rendered like the diagnostics
+/// of Cargo itself.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticLevel {
+    Error,
+    Warning,
+    Note,
+}
+
+impl BuildScriptDiagnostic {
+    /// Renders the diagnostic for the shell, after its level.
+    pub fn render(&self, package_id: PackageId) -> String {
+        let mut rendered = format!(
+            "{}@{}: {}",
+            package_id.name(),
+            package_id.version(),
+            self.message
+        );
+        if let Some(file) = &self.file {
+            rendered.push_str(&format!("\n  --> {}", file.display()));
+            if let Some(line) = self.line {
+                rendered.push_str(&format!(":{line}"));
+                if let Some(column) = self.column {
+                    rendered.push_str(&format!(":{column}"));
+                }
+            }
+        }
+        if let Some(help) = &self.help {
+            rendered.push_str(&format!("\n   = help: {help}"));
+        }
+        rendered
+    }
+}
+
+/// The error of a build script run which reported `errors` errors with
+/// `cargo::diagnostic`, which are rendered along with its warnings.
+fn reported_errors(pkg_descr: &str, errors: usize) -> anyhow::Error {
+    anyhow::format_err!(
+        "the build script reported {errors} error{}",
+        if errors == 1 { "" } else { "s" }
+    )
+    .context(format!(
+        "failed to run custom build command for `{pkg_descr}`"
+    ))
 }
 
 /// Map of packages to build script output.
@@ -237,6 +319,31 @@ fn emit_build_output(
     Ok(())
 }
 
+/// Emits the warnings, diagnostics and stderr of a build script as a
+/// [`machine_message::BuildScriptOutput`] JSON string to standard output, if
+/// there are any.
+fn emit_build_script_output(
+    state: &JobState<'_, '_>,
+    output: &BuildOutput,
+    stderr: &[u8],
+    package_id: PackageId,
+    fresh: bool,
+) -> CargoResult<()> {
+    if output.warnings.is_empty() && output.diagnostics.is_empty() && stderr.is_empty() {
+        return Ok(());
+    }
+    let msg = machine_message::BuildScriptOutput {
+        package_id: package_id.to_spec(),
+        fresh,
+        warnings: &output.warnings,
+        diagnostics: &output.diagnostics,
+        stderr: &String::from_utf8_lossy(stderr),
+    }
+    .to_json_string();
+    state.stdout(msg)?;
+    Ok(())
+}
+
 /// Constructs the unit of work of running a build script.
 ///
 /// The construction includes:
@@ -258,6 +365,16 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
     let script_out_dir = build_runner.files().build_script_out_dir(unit);
     let script_run_dir = build_runner.files().build_script_run_dir(unit);
     let build_plan = bcx.build_config.build_plan;
+    // In the sandbox, the build script can only write to `OUT_DIR`.
+    let sandbox_access = bcx
+        .build_config
+        .sandbox
+        .applies_to(&unit.pkg.name())
+        .then(|| {
+            let mut access = super::sandbox::Access::new(build_runner, unit);
+            access.writable.push(script_out_dir.clone());
+            access
+        });
     let invocation_name = unit.buildkey();
 
     if let Some(deps) = unit.pkg.manifest().metabuild() {
@@ -312,6 +429,18 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
         cmd.env("CARGO_TRIM_PATHS", trim_paths.to_string());
     }
 
+    for dep in build_runner
+        .system_deps
+        .get(&(unit.pkg.package_id(), unit.kind))
+        .iter()
+        .copied()
+        .flatten()
+    {
+        for (var, value) in dep.env() {
+            cmd.env(&var, value);
+        }
+    }
+
     // Be sure to pass along all enabled features for this package, this is the
     // last piece of statically known information that we have.
     for feat in &unit.features {
@@ -382,6 +511,13 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
             }
         })
         .collect::<Vec<_>>();
+    let consumed_metadata = consumed_links_metadata(build_runner, unit, dependencies)?;
+    let links_metadata_schema = unit
+        .pkg
+        .manifest()
+        .resolved_toml()
+        .package()
+        .and_then(|p| p.links_metadata.clone());
     let library_name = unit.pkg.library().map(|t| t.crate_name());
     let pkg_descr = unit.pkg.to_string();
     let build_script_outputs = Arc::clone(&build_runner.build_script_outputs);
@@ -400,6 +536,11 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
     );
     let build_scripts = build_runner.build_scripts.get(unit).cloned();
     let json_messages = bcx.build_config.emit_json();
+    // The `build-script-output` messages are unstable, and like warnings are
+    // only emitted for local packages.
+    let json_script_output =
+        json_messages && bcx.gctx.cli_unstable().unstable_options && bcx.show_warnings(unit);
+    let replay_warnings = bcx.build_config.replay_warnings;
     let extra_verbose = bcx.gctx.extra_verbose();
     let (prev_output, prev_script_out_dir) = prev_build_output(build_runner, unit);
     let metadata_hash = build_runner.get_run_build_script_metadata(unit);
@@ -418,6 +559,7 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
     // Need a separate copy for the fresh closure.
     let targets_fresh = targets.clone();
     let msrv_fresh = msrv.clone();
+    let err_file_fresh = err_file.clone();
 
     let env_profile_name = unit.profile.name.to_uppercase();
     let built_with_debuginfo = build_runner
@@ -428,6 +570,8 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
         .map(|dep| dep.unit.profile.debuginfo.is_turned_on())
         .unwrap_or(false);
 
+    let script_cache = super::artifact_cache::prepare_build_script(build_runner, unit);
+
     // Prepare the unit of "dirty work" which will actually run the custom build
     // command.
     //
@@ -462,6 +606,11 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
                     );
                 }
             }
+            if !consumed_metadata.is_empty() {
+                let json =
+                    links_metadata_json(&consumed_metadata, &build_script_outputs, &pkg_descr)?;
+                cmd.env("CARGO_LINKS_METADATA", json.to_string());
+            }
             if let Some(build_scripts) = build_scripts {
                 super::add_plugin_deps(
                     &mut cmd,
@@ -477,65 +626,110 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
             return Ok(());
         }
 
+        // The same run of the build script may already be cached, by a
+        // build in another target directory, so that it doesn't run again.
+        let cache_key = script_cache.as_ref().map(|cache| cache.key(&cmd));
+        let restored = match (&script_cache, &cache_key) {
+            (Some(cache), Some(key)) => cache.restore(key, &cmd, &script_out_dir)?,
+            _ => None,
+        };
+        // The paths in the output refer to the `OUT_DIR` it ran with.
+        let generated_out_dir = match &restored {
+            Some(restored) => restored.out_dir.clone(),
+            None => script_out_dir.clone(),
+        };
+
         // And now finally, run the build command itself!
-        state.running(&cmd);
         let timestamp = paths::set_invocation_time(&script_run_dir)?;
         let prefix = format!("[{} {}] ", id.name(), id.version());
         let mut warnings_in_case_of_panic = Vec::new();
-        let output = cmd
-            .exec_with_streaming(
-                &mut |stdout| {
-                    if let Some(warning) = stdout
-                        .strip_prefix(OLD_CARGO_WARNING_SYNTAX)
-                        .or(stdout.strip_prefix(NEW_CARGO_WARNING_SYNTAX))
-                    {
-                        warnings_in_case_of_panic.push(warning.to_owned());
-                    }
-                    if extra_verbose {
-                        state.stdout(format!("{}{}", prefix, stdout))?;
-                    }
-                    Ok(())
-                },
-                &mut |stderr| {
-                    if extra_verbose {
-                        state.stderr(format!("{}{}", prefix, stderr))?;
-                    }
-                    Ok(())
-                },
-                true,
-            )
-            .with_context(|| {
-                let mut build_error_context =
-                    format!("failed to run custom build command for `{}`", pkg_descr);
-
-                // If we're opting into backtraces, mention that build dependencies' backtraces can
-                // be improved by requesting debuginfo to be built, if we're not building with
-                // debuginfo already.
-                //
-                // ALLOWED: Other tools like `rustc` might read it directly
-                // through `std::env`. We should make their behavior consistent.
-                #[allow(clippy::disallowed_methods)]
-                if let Ok(show_backtraces) = std::env::var("RUST_BACKTRACE") {
-                    if !built_with_debuginfo && show_backtraces != "0" {
-                        build_error_context.push_str(&format!(
-                            "\n\
-                            note: To improve backtraces for build dependencies, set the \
-                            CARGO_PROFILE_{env_profile_name}_BUILD_OVERRIDE_DEBUG=true environment \
-                            variable to enable debug information generation.",
-                        ));
-                    }
-                }
+        let mut diagnostics_in_case_of_panic: Vec<BuildScriptDiagnostic> = Vec::new();
+        let sandboxed_cmd = sandbox_access
+            .as_ref()
+            .map(|access| super::sandbox::command(&cmd, access));
+        let output = match restored {
+            Some(restored) => Ok(Output {
+                status: ExitStatus::default(),
+                stdout: restored.stdout,
+                stderr: restored.stderr,
+            }),
+            None => {
+                state.running(&cmd);
+                sandboxed_cmd
+                    .as_ref()
+                    .unwrap_or(&cmd)
+                    .exec_with_streaming(
+                        &mut |stdout| {
+                            if let Some(warning) = stdout
+                                .strip_prefix(OLD_CARGO_WARNING_SYNTAX)
+                                .or(stdout.strip_prefix(NEW_CARGO_WARNING_SYNTAX))
+                            {
+                                warnings_in_case_of_panic.push(warning.to_owned());
+                            }
+                            if let Some(diagnostic) = stdout
+                                .strip_prefix(CARGO_DIAGNOSTIC_SYNTAX)
+                                .filter(|_| nightly_features_allowed)
+                                .and_then(|json| serde_json::from_str(json.trim()).ok())
+                            {
+                                diagnostics_in_case_of_panic.push(diagnostic);
+                            }
+                            if extra_verbose {
+                                state.stdout(format!("{}{}", prefix, stdout))?;
+                            }
+                            Ok(())
+                        },
+                        &mut |stderr| {
+                            if extra_verbose {
+                                state.stderr(format!("{}{}", prefix, stderr))?;
+                            }
+                            Ok(())
+                        },
+                        true,
+                    )
+                    .with_context(|| {
+                        let mut build_error_context =
+                            format!("failed to run custom build command for `{}`", pkg_descr);
+
+                        // If we're opting into backtraces, mention that build dependencies' backtraces can
+                        // be improved by requesting debuginfo to be built, if we're not building with
+                        // debuginfo already.
+                        //
+                        // ALLOWED: Other tools like `rustc` might read it directly
+                        // through `std::env`. We should make their behavior consistent.
+                        #[allow(clippy::disallowed_methods)]
+                        if let Ok(show_backtraces) = std::env::var("RUST_BACKTRACE") {
+                            if !built_with_debuginfo && show_backtraces != "0" {
+                                build_error_context.push_str(&format!(
+                                    "\n\
+                                    note: To improve backtraces for build dependencies, set the \
+                                    CARGO_PROFILE_{env_profile_name}_BUILD_OVERRIDE_DEBUG=true environment \
+                                    variable to enable debug information generation.",
+                                ));
+                            }
+                        }
 
-                build_error_context
-            });
+                        build_error_context
+                    })
+            }
+        };
 
         if let Err(error) = output {
+            // The errors the build script reported replace its raw output,
+            // which is still streamed with `-vv`.
+            let errors = diagnostics_in_case_of_panic
+                .iter()
+                .filter(|d| d.level == DiagnosticLevel::Error)
+                .count();
             insert_warnings_in_build_outputs(
                 build_script_outputs,
                 id,
                 metadata_hash,
                 warnings_in_case_of_panic,
+                diagnostics_in_case_of_panic,
             );
+            if errors > 0 {
+                return Err(reported_errors(&pkg_descr, errors));
+            }
             return Err(error);
         }
 
@@ -553,26 +747,54 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
         // modified in the middle of the build.
         paths::set_file_time_no_err(output_file, timestamp);
         paths::write(&err_file, &output.stderr)?;
-        paths::write(&root_output_file, paths::path2bytes(&script_out_dir)?)?;
+        paths::write(&root_output_file, paths::path2bytes(&generated_out_dir)?)?;
         let parsed_output = BuildOutput::parse(
             &output.stdout,
             library_name,
             &pkg_descr,
-            &script_out_dir,
+            &generated_out_dir,
             &script_out_dir,
             extra_check_cfg,
             nightly_features_allowed,
             &targets,
             &msrv,
         )?;
+        if let Some(schema) = &links_metadata_schema {
+            validate_links_metadata(schema, &parsed_output.metadata, &pkg_descr)?;
+        }
+        if let (Some(cache), Some(key)) = (&script_cache, &cache_key) {
+            if let Err(e) = cache.store(
+                key,
+                &cmd,
+                &parsed_output,
+                &output.stdout,
+                &output.stderr,
+                &script_out_dir,
+            ) {
+                state.warning(format!(
+                    "failed to store the build script run of `{pkg_descr}` in the artifact cache: {e:#}"
+                ))?;
+            }
+        }
 
         if json_messages {
             emit_build_output(state, &parsed_output, script_out_dir.as_path(), id)?;
         }
+        if json_script_output {
+            emit_build_script_output(state, &parsed_output, &output.stderr, id, false)?;
+        }
+        let errors = parsed_output
+            .diagnostics
+            .iter()
+            .filter(|d| d.level == DiagnosticLevel::Error)
+            .count();
         build_script_outputs
             .lock()
             .unwrap()
             .insert(id, metadata_hash, parsed_output);
+        if errors > 0 {
+            return Err(reported_errors(&pkg_descr, errors));
+        }
         Ok(())
     });
 
@@ -581,7 +803,7 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
     // above.
     let fresh = Work::new(move |state| {
         let (id, library_name, pkg_descr, build_script_outputs, output_file, script_out_dir) = all;
-        let output = match prev_output {
+        let mut output = match prev_output {
             Some(output) => output,
             None => BuildOutput::parse_file(
                 &output_file,
@@ -600,6 +822,25 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
             emit_build_output(state, &output, script_out_dir.as_path(), id)?;
         }
 
+        // Replay what the build script printed when it last ran, like the
+        // diagnostics of fresh rustc units. Its warnings are printed once the
+        // unit finishes, from `build_script_outputs`.
+        if replay_warnings {
+            let stderr = paths::read_bytes(&err_file_fresh).unwrap_or_default();
+            if extra_verbose {
+                let prefix = format!("[{} {}] ", id.name(), id.version());
+                for line in String::from_utf8_lossy(&stderr).lines() {
+                    state.stderr(format!("{}{}", prefix, line))?;
+                }
+            }
+            if json_script_output {
+                emit_build_script_output(state, &output, &stderr, id, true)?;
+            }
+        } else {
+            output.warnings.clear();
+            output.diagnostics.clear();
+        }
+
         build_script_outputs
             .lock()
             .unwrap()
@@ -620,6 +861,161 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
     Ok(job)
 }
 
+/// The `links` metadata a build script consumes from one of its dependencies,
+/// as declared in `package.links-consume`.
+struct ConsumedLinksMetadata {
+    /// The `links` name of the dependency.
+    links: String,
+    /// The dependency package.
+    pkg_id: PackageId,
+    /// The metadata hash of the dependency's build script run.
+    metadata: Metadata,
+    /// The consumed keys, with their types from `package.links-metadata`.
+    keys: Vec<(String, TomlLinksMetadataType)>,
+}
+
+/// Resolves `package.links-consume` of the build script run `unit` against
+/// the build scripts of its `links` dependencies.
+///
+/// Every consumed key must be declared in the `package.links-metadata` schema
+/// of the dependency.
+fn consumed_links_metadata(
+    build_runner: &BuildRunner<'_, '_>,
+    unit: &Unit,
+    dependencies: &[UnitDep],
+) -> CargoResult<Vec<ConsumedLinksMetadata>> {
+    let Some(consume) = unit
+        .pkg
+        .manifest()
+        .resolved_toml()
+        .package()
+        .and_then(|p| p.links_consume.as_ref())
+    else {
+        return Ok(Vec::new());
+    };
+    let mut consumed = Vec::new();
+    for (links, keys) in consume {
+        let Some(dep) = dependencies.iter().find(|dep| {
+            dep.unit.mode.is_run_custom_build()
+                && dep.unit.pkg.manifest().links() == Some(links.as_str())
+        }) else {
+            bail!(
+                "package `{}` consumes the `links` metadata of `{links}`, \
+                 but none of its dependencies has `links = \"{links}\"`",
+                unit.pkg
+            );
+        };
+        let schema = dep
+            .unit
+            .pkg
+            .manifest()
+            .resolved_toml()
+            .package()
+            .and_then(|p| p.links_metadata.as_ref());
+        let mut typed_keys = Vec::new();
+        for key in keys {
+            let Some(ty) = schema.and_then(|schema| schema.get(key)) else {
+                bail!(
+                    "package `{}` consumes the `links` metadata key `{key}` of `{links}`, \
+                     but `{}` does not declare it in `package.links-metadata`",
+                    unit.pkg,
+                    dep.unit.pkg
+                );
+            };
+            typed_keys.push((key.clone(), *ty));
+        }
+        consumed.push(ConsumedLinksMetadata {
+            links: links.clone(),
+            pkg_id: dep.unit.pkg.package_id(),
+            metadata: build_runner.get_run_build_script_metadata(&dep.unit),
+            keys: typed_keys,
+        });
+    }
+    Ok(consumed)
+}
+
+/// Builds the value of `CARGO_LINKS_METADATA` from the outputs of the build
+/// scripts in `consumed`.
+///
+/// The result maps each `links` name to an object of the consumed keys and
+/// their typed values.
+fn links_metadata_json(
+    consumed: &[ConsumedLinksMetadata],
+    build_script_outputs: &BuildScriptOutputs,
+    pkg_descr: &str,
+) -> CargoResult<serde_json::Value> {
+    let mut json = serde_json::Map::new();
+    for consumed in consumed {
+        let script_output = build_script_outputs.get(consumed.metadata).ok_or_else(|| {
+            internal(format!(
+                "failed to locate build state for links metadata: {}/{}",
+                consumed.pkg_id, consumed.metadata
+            ))
+        })?;
+        let mut values = serde_json::Map::new();
+        for (key, ty) in &consumed.keys {
+            // Like `DEP_<LINKS>_<KEY>`, the last emitted value wins.
+            let Some((_, value)) = script_output.metadata.iter().rev().find(|(k, _)| k == key)
+            else {
+                bail!(
+                    "the build script of `{pkg_descr}` consumes the `links` metadata key `{key}` \
+                     of `{}`, but the build script of `{}` did not emit it",
+                    consumed.links,
+                    consumed.pkg_id
+                );
+            };
+            // Outputs of fresh build scripts may predate the schema.
+            let Some(value) = links_metadata_value(*ty, value) else {
+                bail!(
+                    "the build script of `{}` emitted an invalid value `{value}` \
+                     for the `cargo::metadata` key `{key}`, expected {}",
+                    consumed.pkg_id,
+                    ty.as_str()
+                );
+            };
+            values.insert(key.clone(), value);
+        }
+        json.insert(consumed.links.clone(), values.into());
+    }
+    Ok(json.into())
+}
+
+/// Checks the `cargo::metadata` emitted by a build script against the
+/// `package.links-metadata` schema of its package.
+fn validate_links_metadata(
+    schema: &BTreeMap<String, TomlLinksMetadataType>,
+    metadata: &[(String, String)],
+    pkg_descr: &str,
+) -> CargoResult<()> {
+    for (key, value) in metadata {
+        let Some(ty) = schema.get(key) else {
+            bail!(
+                "the build script of `{pkg_descr}` emitted the `cargo::metadata` key `{key}`, \
+                 which is not declared in `package.links-metadata`"
+            );
+        };
+        if links_metadata_value(*ty, value).is_none() {
+            bail!(
+                "the build script of `{pkg_descr}` emitted an invalid value `{value}` \
+                 for the `cargo::metadata` key `{key}`, expected {}",
+                ty.as_str()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Converts a `cargo::metadata` value to JSON according to its declared type.
+///
+/// Returns `None` if the value doesn't match the type.
+fn links_metadata_value(ty: TomlLinksMetadataType, value: &str) -> Option<serde_json::Value> {
+    match ty {
+        TomlLinksMetadataType::String | TomlLinksMetadataType::Path => Some(value.into()),
+        TomlLinksMetadataType::Bool => value.parse::<bool>().ok().map(Into::into),
+        TomlLinksMetadataType::Integer => value.parse::<i64>().ok().map(Into::into),
+    }
+}
+
 /// When a build script run fails, store only warnings and nuke other outputs,
 /// as they are likely broken.
 fn insert_warnings_in_build_outputs(
@@ -627,9 +1023,11 @@ fn insert_warnings_in_build_outputs(
     id: PackageId,
     metadata_hash: Metadata,
     warnings: Vec<String>,
+    diagnostics: Vec<BuildScriptDiagnostic>,
 ) {
     let build_output_with_only_warnings = BuildOutput {
         warnings,
+        diagnostics,
         ..BuildOutput::default()
     };
     build_script_outputs
@@ -692,6 +1090,8 @@ impl BuildOutput {
         let mut rerun_if_changed = Vec::new();
         let mut rerun_if_env_changed = Vec::new();
         let mut warnings = Vec::new();
+        let mut diagnostics = Vec::new();
+        let mut nondeterministic = false;
         let whence = format!("build script of `{}`", pkg_descr);
         // Old syntax:
         //    cargo:rustc-flags=VALUE
@@ -984,6 +1384,42 @@ impl BuildOutput {
                     }
                 }
                 "warning" => warnings.push(value.to_string()),
+                "diagnostic" if !old_syntax => {
+                    if !nightly_features_allowed {
+                        bail!(
+                            "invalid output in {whence}: `{line}`\n\
+                            The `cargo::diagnostic` instruction is unstable and requires \
+                            the nightly channel of Cargo.\n\
+                            {DOCS_LINK_SUGGESTION}",
+                        );
+                    }
+                    let diagnostic: BuildScriptDiagnostic = serde_json::from_str(&value)
+                        .with_context(|| {
+                            format!(
+                                "invalid output in {whence}: `{line}`\n\
+                            Expected a JSON object with a `level` and a `message`."
+                            )
+                        })?;
+                    diagnostics.push(diagnostic);
+                }
+                "nondeterministic" if !old_syntax => {
+                    if !nightly_features_allowed {
+                        bail!(
+                            "invalid output in {whence}: `{line}`\n\
+                            The `cargo::nondeterministic` instruction is unstable and requires \
+                            the nightly channel of Cargo.\n\
+                            {DOCS_LINK_SUGGESTION}",
+                        );
+                    }
+                    nondeterministic = match value.as_str() {
+                        "true" => true,
+                        "false" => false,
+                        _ => bail!(
+                            "invalid output in {whence}: `{line}`\n\
+                            Expected `true` or `false` for `cargo::nondeterministic`.",
+                        ),
+                    };
+                }
                 "rerun-if-changed" => rerun_if_changed.push(PathBuf::from(value)),
                 "rerun-if-env-changed" => rerun_if_env_changed.push(value.to_string()),
                 "metadata" => {
@@ -1005,10 +1441,14 @@ impl BuildOutput {
             cfgs,
             check_cfgs,
             env,
+            target_cfgs: Vec::new(),
+            target_env: Vec::new(),
             metadata,
             rerun_if_changed,
             rerun_if_env_changed,
             warnings,
+            diagnostics,
+            nondeterministic,
         })
     }
 
@@ -112,6 +112,52 @@ impl fmt::Display for FileTimeDiff {
     }
 }
 
+/// The fingerprint component that made a unit dirty, and how it changed, as
+/// reported by `--explain-rebuild`.
+#[derive(Serialize)]
+pub struct RebuildDetails {
+    /// The kind of component that changed, such as `rustflags` or `env`.
+    pub component: &'static str,
+    /// The file, environment variable or dependency that changed, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The previous value of the component. `None` if there is no value to
+    /// compare, `Some(None)` if it was unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Option<String>>,
+    /// The current value of the component, like `old`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Option<String>>,
+}
+
+impl RebuildDetails {
+    fn new(component: &'static str) -> RebuildDetails {
+        RebuildDetails {
+            component,
+            name: None,
+            old: None,
+            new: None,
+        }
+    }
+
+    fn name(mut self, name: impl fmt::Display) -> RebuildDetails {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    fn change(mut self, old: impl fmt::Display, new: impl fmt::Display) -> RebuildDetails {
+        self.old = Some(Some(old.to_string()));
+        self.new = Some(Some(new.to_string()));
+        self
+    }
+
+    fn change_opt(mut self, old: Option<&String>, new: Option<&String>) -> RebuildDetails {
+        self.old = Some(old.cloned());
+        self.new = Some(new.cloned());
+        self
+    }
+}
+
 #[derive(Copy, Clone)]
 struct After {
     old_time: FileTime,
@@ -146,6 +192,115 @@ impl DirtyReason {
         }
     }
 
+    /// Describes which fingerprint component changed, for `--explain-rebuild`.
+    pub fn details(&self, root: &Path) -> RebuildDetails {
+        let relative = |path: &Path| {
+            path.strip_prefix(root)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+        match self {
+            DirtyReason::RustcChanged => RebuildDetails::new("toolchain"),
+            DirtyReason::FeaturesChanged { old, new }
+            | DirtyReason::DeclaredFeaturesChanged { old, new } => {
+                RebuildDetails::new("features").change(old, new)
+            }
+            DirtyReason::TargetConfigurationChanged | DirtyReason::CompileKindChanged => {
+                RebuildDetails::new("target")
+            }
+            DirtyReason::PathToSourceChanged => RebuildDetails::new("path"),
+            DirtyReason::ProfileConfigurationChanged => RebuildDetails::new("profile"),
+            DirtyReason::RustflagsChanged { old, new } => {
+                RebuildDetails::new("rustflags").change(old.join(" "), new.join(" "))
+            }
+            DirtyReason::MetadataChanged => RebuildDetails::new("metadata"),
+            DirtyReason::ConfigSettingsChanged => RebuildDetails::new("config"),
+            DirtyReason::LocalLengthsChanged => RebuildDetails::new("local"),
+            DirtyReason::PrecalculatedComponentsChanged { old, new } => {
+                RebuildDetails::new("local").change(old, new)
+            }
+            DirtyReason::LocalFingerprintTypeChanged { old, new } => {
+                RebuildDetails::new("local").change(old, new)
+            }
+            DirtyReason::DepInfoOutputChanged { old, new } => {
+                RebuildDetails::new("dep-info").change(relative(old), relative(new))
+            }
+            DirtyReason::RerunIfChangedOutputFileChanged { old, new } => {
+                RebuildDetails::new("rerun-if-changed").change(relative(old), relative(new))
+            }
+            DirtyReason::RerunIfChangedOutputPathsChanged { old, new } => {
+                let paths = |paths: &[PathBuf]| {
+                    paths
+                        .iter()
+                        .map(|p| relative(p))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                RebuildDetails::new("rerun-if-changed").change(paths(old), paths(new))
+            }
+            DirtyReason::EnvVarsChanged { old, new } => RebuildDetails::new("env").change(old, new),
+            DirtyReason::EnvVarChanged {
+                name,
+                old_value,
+                new_value,
+            } => RebuildDetails::new("env")
+                .name(name)
+                .change_opt(old_value.as_ref(), new_value.as_ref()),
+            DirtyReason::NumberOfDependenciesChanged { old, new } => {
+                RebuildDetails::new("dependency").change(old, new)
+            }
+            DirtyReason::UnitDependencyNameChanged { old, new } => {
+                RebuildDetails::new("dependency").change(old, new)
+            }
+            DirtyReason::UnitDependencyInfoChanged {
+                old_name,
+                old_fingerprint,
+                new_name,
+                new_fingerprint,
+            } => RebuildDetails::new("dependency").name(new_name).change(
+                format_args!("{old_name} ({old_fingerprint:016x})"),
+                format_args!("{new_name} ({new_fingerprint:016x})"),
+            ),
+            DirtyReason::FsStatusOutdated(status) => match status {
+                FsStatus::StaleItem(StaleItem::MissingFile(file))
+                | FsStatus::StaleItem(StaleItem::ChangedFile { stale: file, .. })
+                | FsStatus::StaleItem(StaleItem::ChangedFileContents { stale: file }) => {
+                    RebuildDetails::new("file").name(relative(file))
+                }
+                FsStatus::StaleItem(StaleItem::ChangedEnv {
+                    var,
+                    previous,
+                    current,
+                }) => RebuildDetails::new("env")
+                    .name(var)
+                    .change_opt(previous.as_ref(), current.as_ref()),
+                FsStatus::StaleDependency { name, .. } | FsStatus::StaleDepFingerprint { name } => {
+                    RebuildDetails::new("dependency").name(name)
+                }
+                FsStatus::Stale | FsStatus::UpToDate { .. } => RebuildDetails::new("unknown"),
+            },
+            DirtyReason::NothingObvious => RebuildDetails::new("unknown"),
+            DirtyReason::Forced => RebuildDetails::new("forced"),
+            DirtyReason::FreshBuild => RebuildDetails::new("fresh-build"),
+        }
+    }
+
+    /// Like [`DirtyReason::present_to`], but also notes the previous and
+    /// current value of the component that changed, for `--explain-rebuild`.
+    pub fn explain_to(&self, s: &mut Shell, unit: &Unit, root: &Path) -> CargoResult<()> {
+        self.present_to(s, unit, root)?;
+        let RebuildDetails { old, new, .. } = self.details(root);
+        if let (Some(old), Some(new)) = (old, new) {
+            let value = |v: Option<String>| match v {
+                Some(v) => format!("`{v}`"),
+                None => "unset".to_string(),
+            };
+            s.note(format_args!("was {}, now {}", value(old), value(new)))?;
+        }
+        Ok(())
+    }
+
     pub fn present_to(&self, s: &mut Shell, unit: &Unit, root: &Path) -> CargoResult<()> {
         match self {
             DirtyReason::RustcChanged => s.dirty_because(unit, "the toolchain changed"),
@@ -235,6 +390,16 @@ impl DirtyReason {
                             format_args!("the file `{}` has changed ({after})", file.display()),
                         )
                     }
+                    StaleItem::ChangedFileContents { stale } => {
+                        let file = stale.strip_prefix(root).unwrap_or(&stale);
+                        s.dirty_because(
+                            unit,
+                            format_args!(
+                                "the file `{}` has changed (its contents differ from last build)",
+                                file.display()
+                            ),
+                        )
+                    }
                     StaleItem::ChangedEnv { var, .. } => s.dirty_because(
                         unit,
                         format_args!("the environment variable {var} changed"),
@@ -0,0 +1,162 @@
+//! A persistent index of fingerprint hashes, enabled by `-Zfingerprint-index`.
+//!
+//! Checking whether a unit is fresh normally opens and reads the small hash
+//! file of every unit in the `.fingerprint` directory. On large workspaces,
+//! and especially on network filesystems, those opens dominate the time of a
+//! no-op build. The index keeps the same hashes in a single append-only file
+//! per [`Layout`], which is read once per build.
+//!
+//! Each line has the form `<path> <hash>`, where `<path>` is the location of
+//! the hash file relative to the `.fingerprint` directory and `<hash>` is the
+//! hex hash written to it. An empty hash records that the hash file was
+//! cleared before a rebuild. When a path appears more than once, the last
+//! line wins. A trailing line without a newline was cut short by an
+//! interrupted build and is ignored.
+//!
+//! The individual hash files are still written, so the index is only a cache:
+//! paths missing from it fall back to reading the hash file, and the index
+//! may be deleted at any time.
+//!
+//! [`Layout`]: crate::core::compiler::layout::Layout
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Context as _;
+use cargo_util::paths;
+use tracing::debug;
+
+use crate::util::errors::CargoResult;
+
+/// The index is rewritten without superseded lines once it holds more than
+/// this many lines per live entry.
+const COMPACT_RATIO: usize = 4;
+
+/// An append-only map from fingerprint hash files to their contents.
+///
+/// Shared between the job queue threads that write fingerprints.
+pub struct FingerprintIndex {
+    /// The `.fingerprint` directory that keys are relative to.
+    root: PathBuf,
+    /// Location of the index file.
+    path: PathBuf,
+    /// Last recorded hash of each hash file.
+    entries: Mutex<HashMap<String, String>>,
+    /// Handle used to append to the index, opened on first write.
+    file: Mutex<Option<File>>,
+}
+
+impl FingerprintIndex {
+    /// Loads the index at `path` for the fingerprint directory `root`.
+    ///
+    /// A missing or unreadable index is treated as empty.
+    pub fn load(root: &Path, path: PathBuf) -> FingerprintIndex {
+        let mut entries = HashMap::new();
+        let mut lines = 0;
+        match paths::read(&path) {
+            Ok(contents) => {
+                for line in contents.split_inclusive('\n') {
+                    let Some(line) = line.strip_suffix('\n') else {
+                        break;
+                    };
+                    let Some((key, hash)) = line.rsplit_once(' ') else {
+                        continue;
+                    };
+                    lines += 1;
+                    entries.insert(key.to_string(), hash.to_string());
+                }
+            }
+            Err(e) => debug!("fingerprint index {} not loaded: {e:?}", path.display()),
+        }
+
+        let index = FingerprintIndex {
+            root: root.to_path_buf(),
+            path,
+            entries: Mutex::new(entries),
+            file: Mutex::new(None),
+        };
+        if lines > COMPACT_RATIO * index.entries.lock().unwrap().len() {
+            if let Err(e) = index.compact() {
+                debug!("failed to compact fingerprint index: {e:?}");
+            }
+        }
+        index
+    }
+
+    /// Returns the recorded contents of the hash file at `loc`, if any.
+    pub fn get(&self, loc: &Path) -> Option<String> {
+        let key = self.key(loc)?;
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    /// Records that the hash file at `loc` now contains `hash`.
+    ///
+    /// This must happen *before* a hash file is cleared, and *after* a new
+    /// hash is written, so that an interrupted build never leaves an index
+    /// entry claiming a unit is fresh when its hash file says otherwise.
+    pub fn record(&self, loc: &Path, hash: &str) -> CargoResult<()> {
+        let Some(key) = self.key(loc) else {
+            return Ok(());
+        };
+        let mut file = self.file.lock().unwrap();
+        if file.is_none() {
+            let f = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .with_context(|| {
+                    format!("failed to open fingerprint index `{}`", self.path.display())
+                })?;
+            *file = Some(f);
+        }
+        // A single write so concurrent appends never interleave within a line.
+        file.as_mut()
+            .unwrap()
+            .write_all(format!("{key} {hash}\n").as_bytes())
+            .with_context(|| {
+                format!(
+                    "failed to write fingerprint index `{}`",
+                    self.path.display()
+                )
+            })?;
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), hash.to_string());
+        Ok(())
+    }
+
+    /// Flushes the appended entries to disk, for checkpoints of long builds.
+    pub fn sync(&self) -> CargoResult<()> {
+        if let Some(file) = self.file.lock().unwrap().as_ref() {
+            file.sync_data().with_context(|| {
+                format!("failed to sync fingerprint index `{}`", self.path.display())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Rewrites the index with only the live entries.
+    fn compact(&self) -> CargoResult<()> {
+        let entries = self.entries.lock().unwrap();
+        let mut contents = String::new();
+        for (key, hash) in entries.iter() {
+            contents.push_str(key);
+            contents.push(' ');
+            contents.push_str(hash);
+            contents.push('\n');
+        }
+        let tmp = self.path.with_extension("tmp");
+        paths::write(&tmp, contents)?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("failed to replace `{}`", self.path.display()))?;
+        Ok(())
+    }
+
+    fn key<'a>(&self, loc: &'a Path) -> Option<&'a str> {
+        loc.strip_prefix(&self.root).ok()?.to_str()
+    }
+}
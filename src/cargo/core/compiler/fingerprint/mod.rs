@@ -89,7 +89,7 @@
 //!        release channel (bootstrap/stable/beta/nightly) in libstd.
 //!
 //! [^5]: Config settings that are not otherwise captured anywhere else.
-//!       Currently, this is only `doc.extern-map`.
+//!       Currently, this is `doc.extern-map` and `lints.cargo-origin`.
 //!
 //! [^6]: Via [`Manifest::lint_rustflags`][crate::core::Manifest::lint_rustflags]
 //!
@@ -126,6 +126,10 @@
 //! applicable). Build script `invoked.timestamp` files are in the build
 //! output directory.
 //!
+//! With `-Zfingerprint-index`, the hashes are additionally recorded in a single
+//! `index` file in the `.fingerprint` directory, so a no-op build reads one
+//! file instead of one per Unit. See the [`index`] module for details.
+//!
 //! ## Fingerprint calculation
 //!
 //! After the list of Units has been calculated, the Units are added to the
@@ -194,6 +198,14 @@
 //! We want Cargo to recompile whenever the standard library rlib/dylibs
 //! change, and this is a generic mechanism to make that work.
 //!
+//! #### -Zproc-macro-inputs
+//!
+//! Proc-macros reading files or environment variables with the standard
+//! library aren't tracked by `rustc`. With `-Zproc-macro-inputs`, they can
+//! report those inputs in the file at `CARGO_PROC_MACRO_INPUTS`, which Cargo
+//! merges into the `rustc` dep-info before translating it. See
+//! [`add_proc_macro_inputs`].
+//!
 //! ### Mtime comparison
 //!
 //! The use of modification timestamps is the most common way a unit will be
@@ -235,6 +247,18 @@
 //! build, so it takes a conservative approach of assuming the file was *not*
 //! included, and it should be rebuilt during the next build.
 //!
+//! #### Content hashes
+//!
+//! With `build.fingerprint = "hash"`, the fingerprint dep-info file also
+//! records a [`FileStamp`] of each input file: its size, mtime, and a hash of
+//! its contents. The mtime of a stamped file isn't compared to the dep-info
+//! file. Instead, the file is unchanged if its size and mtime still match,
+//! and otherwise its contents are hashed again and compared. This avoids
+//! rebuilds when only the mtimes changed, like when a CI cache is restored
+//! over a fresh checkout, and catches edits which didn't make the file newer
+//! than the last build. Files modified after the build started aren't
+//! stamped, and still use the mtime comparison.
+//!
 //! #### Rustdoc mtime handling
 //!
 //! Rustdoc does not emit a dep-info file, so Cargo currently has a relatively
@@ -353,10 +377,12 @@
 //! [`A-rebuild-detection`]: https://github.com/rust-lang/cargo/issues?q=is%3Aissue+is%3Aopen+label%3AA-rebuild-detection
 
 mod dirty_reason;
+mod index;
 
 use std::collections::hash_map::{Entry, HashMap};
 
 use std::env;
+use std::fs;
 use std::hash::{self, Hash, Hasher};
 use std::io;
 use std::path::{Path, PathBuf};
@@ -366,6 +392,7 @@ use std::time::SystemTime;
 
 use anyhow::{bail, format_err, Context as _};
 use cargo_util::{paths, ProcessBuilder};
+use cargo_util_schemas::manifest::TomlBuildDirectives;
 use filetime::FileTime;
 use serde::de;
 use serde::ser;
@@ -380,10 +407,12 @@ use crate::util::{self, try_canonicalize};
 use crate::util::{internal, path_args, StableHasher};
 use crate::{GlobalContext, CARGO_ENV};
 
+use super::build_directives;
 use super::custom_build::BuildDeps;
-use super::{BuildContext, BuildRunner, FileFlavor, Job, Unit, Work};
+use super::{BuildContext, BuildRunner, FileFlavor, FingerprintMode, Job, Unit, Work};
 
-pub use dirty_reason::DirtyReason;
+pub use dirty_reason::{DirtyReason, RebuildDetails};
+pub use index::FingerprintIndex;
 
 /// Determines if a [`Unit`] is up-to-date, and if not prepares necessary work to
 /// update the persisted fingerprint.
@@ -418,7 +447,15 @@ pub fn prepare_target(
     // information about failed comparisons to aid in debugging.
     let fingerprint = calculate(build_runner, unit)?;
     let mtime_on_use = build_runner.bcx.gctx.cli_unstable().mtime_on_use;
-    let dirty_reason = compare_old_fingerprint(unit, &loc, &*fingerprint, mtime_on_use, force);
+    let index = fingerprint_index(build_runner, unit);
+    let dirty_reason = compare_old_fingerprint(
+        unit,
+        &loc,
+        index.as_deref(),
+        &*fingerprint,
+        mtime_on_use,
+        force,
+    );
 
     let Some(dirty_reason) = dirty_reason else {
         return Ok(Job::new_fresh());
@@ -471,6 +508,11 @@ pub fn prepare_target(
         // still log the reason for the fingerprint failure instead of just
         // reporting "failed to read fingerprint" during the next build if
         // this build fails.
+        //
+        // The index has to forget the hash first, see `FingerprintIndex::record`.
+        if let Some(index) = &index {
+            index.record(&loc, "")?;
+        }
         paths::write(&loc, b"")?;
     }
 
@@ -505,10 +547,10 @@ pub fn prepare_target(
                 *fingerprint.local.lock().unwrap() = new_local;
             }
 
-            write_fingerprint(&loc, &fingerprint)
+            write_fingerprint(&loc, &fingerprint, index.as_deref())
         })
     } else {
-        Work::new(move |_| write_fingerprint(&loc, &fingerprint))
+        Work::new(move |_| write_fingerprint(&loc, &fingerprint, index.as_deref()))
     };
 
     Ok(Job::new_dirty(write_fingerprint, dirty_reason))
@@ -757,6 +799,9 @@ pub enum StaleItem {
         stale: PathBuf,
         stale_mtime: FileTime,
     },
+    ChangedFileContents {
+        stale: PathBuf,
+    },
     ChangedEnv {
         var: String,
         previous: Option<String>,
@@ -792,6 +837,7 @@ impl LocalFingerprint {
     fn find_stale_item(
         &self,
         mtime_cache: &mut HashMap<PathBuf, FileTime>,
+        hash_cache: Option<&mut HashMap<PathBuf, u64>>,
         pkg_root: &Path,
         target_root: &Path,
         cargo_exe: &Path,
@@ -834,13 +880,20 @@ impl LocalFingerprint {
                         current,
                     }));
                 }
-                Ok(find_stale_file(mtime_cache, &dep_info, info.files.iter()))
+                let stamps = hash_cache.map(|hash_cache| (&info.stamps, hash_cache));
+                Ok(find_stale_file(
+                    mtime_cache,
+                    stamps,
+                    &dep_info,
+                    info.files.iter(),
+                ))
             }
 
             // We need to verify that no paths listed in `paths` are newer than
             // the `output` path itself, or the last time the build script ran.
             LocalFingerprint::RerunIfChanged { output, paths } => Ok(find_stale_file(
                 mtime_cache,
+                None,
                 &target_root.join(output),
                 paths.iter().map(|p| pkg_root.join(p)),
             )),
@@ -894,7 +947,7 @@ impl Fingerprint {
         *self.memoized_hash.lock().unwrap() = None;
     }
 
-    fn hash_u64(&self) -> u64 {
+    pub(crate) fn hash_u64(&self) -> u64 {
         if let Some(s) = *self.memoized_hash.lock().unwrap() {
             return s;
         }
@@ -1076,6 +1129,7 @@ impl Fingerprint {
     fn check_filesystem(
         &mut self,
         mtime_cache: &mut HashMap<PathBuf, FileTime>,
+        mut hash_cache: Option<&mut HashMap<PathBuf, u64>>,
         pkg_root: &Path,
         target_root: &Path,
         cargo_exe: &Path,
@@ -1180,9 +1234,14 @@ impl Fingerprint {
         // files for this package itself. If we do find something log a helpful
         // message and bail out so we stay stale.
         for local in self.local.get_mut().unwrap().iter() {
-            if let Some(item) =
-                local.find_stale_item(mtime_cache, pkg_root, target_root, cargo_exe, gctx)?
-            {
+            if let Some(item) = local.find_stale_item(
+                mtime_cache,
+                hash_cache.as_deref_mut(),
+                pkg_root,
+                target_root,
+                cargo_exe,
+                gctx,
+            )? {
                 item.log();
                 self.fs_status = FsStatus::StaleItem(item);
                 return Ok(());
@@ -1302,6 +1361,9 @@ impl StaleItem {
                 info!("          (vs) {:?}", reference);
                 info!("               {:?} < {:?}", reference_mtime, stale_mtime);
             }
+            StaleItem::ChangedFileContents { stale } => {
+                info!("stale: changed contents {:?}", stale);
+            }
             StaleItem::ChangedEnv {
                 var,
                 previous,
@@ -1344,8 +1406,11 @@ fn calculate(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResult
     // `fs_status` field of it.
     let target_root = target_root(build_runner);
     let cargo_exe = build_runner.bcx.gctx.cargo_exe()?;
+    let hash_cache = (build_runner.bcx.build_config.fingerprint == FingerprintMode::Hash)
+        .then_some(&mut build_runner.hash_cache);
     fingerprint.check_filesystem(
         &mut build_runner.mtime_cache,
+        hash_cache,
         unit.pkg.root(),
         &target_root,
         cargo_exe,
@@ -1398,7 +1463,17 @@ fn calculate_normal(
     } else {
         let dep_info = dep_info_loc(build_runner, unit);
         let dep_info = dep_info.strip_prefix(&target_root).unwrap().to_path_buf();
-        vec![LocalFingerprint::CheckDepInfo { dep_info }]
+        let mut local = vec![LocalFingerprint::CheckDepInfo {
+            dep_info: dep_info.clone(),
+        }];
+        if let Some(directives) = build_directives::directives(unit) {
+            local.extend(local_fingerprints_build_directives(
+                directives,
+                dep_info,
+                unit.pkg.root(),
+            )?);
+        }
+        local
     };
 
     // Figure out what the outputs of our unit is, and we'll be storing them
@@ -1426,6 +1501,8 @@ fn calculate_normal(
         build_runner.bcx.extra_args_for(unit),
         build_runner.lto[unit],
         unit.pkg.manifest().lint_rustflags(),
+        build_runner.bcx.origin_lint_policy(unit),
+        build_runner.bcx.summarize_warnings(unit),
     ));
     // Include metadata since it is exposed as environment variables.
     let m = unit.pkg.manifest().metadata();
@@ -1442,6 +1519,9 @@ fn calculate_normal(
     if let Some(allow_features) = &build_runner.bcx.gctx.cli_unstable().allow_features {
         allow_features.hash(&mut config);
     }
+    if let Some(directives) = build_directives::directives(unit) {
+        directives.hash(&mut config);
+    }
     let compile_kind = unit.kind.fingerprint_hash();
     let mut declared_features = unit.pkg.summary().features().keys().collect::<Vec<_>>();
     declared_features.sort(); // to avoid useless rebuild if the user orders it's features
@@ -1521,12 +1601,22 @@ See https://doc.rust-lang.org/cargo/reference/build-scripts.html#rerun-if-change
 
     let rustflags = build_runner.bcx.rustflags_args(unit).to_vec();
 
+    // The build script reruns when its system dependencies change.
+    let config = match build_runner
+        .system_deps
+        .get(&(unit.pkg.package_id(), unit.kind))
+    {
+        Some(system_deps) => util::hash_u64(system_deps),
+        None => 0,
+    };
+
     Ok(Fingerprint {
         local: Mutex::new(local),
         rustc: util::hash_u64(&build_runner.bcx.rustc().verbose_version),
         deps,
         outputs: if overridden { Vec::new() } else { vec![output] },
         rustflags,
+        config,
 
         // Most of the other info is blank here as we don't really include it
         // in the execution of the build script, but... this may be a latent
@@ -1702,16 +1792,55 @@ fn local_fingerprints_deps(
     local
 }
 
+/// Compute the [`LocalFingerprint`] values for the `[build-directives]` of
+/// the package of a unit whose dep-info file is at `dep_info`, relative to the
+/// target root.
+///
+/// The files matching the `rerun-if-changed` globs are stale if they are
+/// newer than the dep-info file, which is written once the unit is built.
+fn local_fingerprints_build_directives(
+    directives: &TomlBuildDirectives,
+    dep_info: PathBuf,
+    pkg_root: &Path,
+) -> CargoResult<Vec<LocalFingerprint>> {
+    let mut local = Vec::new();
+    if directives.rerun_if_changed.is_some() {
+        let paths = build_directives::rerun_if_changed(directives, pkg_root)?;
+        local.push(LocalFingerprint::RerunIfChanged {
+            output: dep_info,
+            paths,
+        });
+    }
+    local.extend(
+        directives
+            .rerun_if_env_changed
+            .iter()
+            .flatten()
+            .map(LocalFingerprint::from_env),
+    );
+    Ok(local)
+}
+
 /// Writes the short fingerprint hash value to `<loc>`
 /// and logs detailed JSON information to `<loc>.json`.
-fn write_fingerprint(loc: &Path, fingerprint: &Fingerprint) -> CargoResult<()> {
+///
+/// The hash is also recorded in the fingerprint `index`, if any.
+fn write_fingerprint(
+    loc: &Path,
+    fingerprint: &Fingerprint,
+    index: Option<&FingerprintIndex>,
+) -> CargoResult<()> {
     debug_assert_ne!(fingerprint.rustc, 0);
     // fingerprint::new().rustc == 0, make sure it doesn't make it to the file system.
     // This is mostly so outside tools can reliably find out what rust version this file is for,
     // as we can use the full hash.
     let hash = fingerprint.hash_u64();
     debug!("write fingerprint ({:x}) : {}", hash, loc.display());
-    paths::write(loc, util::to_hex(hash).as_bytes())?;
+    let hex = util::to_hex(hash);
+    paths::write(loc, hex.as_bytes())?;
+    if let Some(index) = index {
+        index.record(loc, &hex)?;
+    }
 
     let json = serde_json::to_string(fingerprint).unwrap();
     if cfg!(debug_assertions) {
@@ -1722,6 +1851,37 @@ fn write_fingerprint(loc: &Path, fingerprint: &Fingerprint) -> CargoResult<()> {
     Ok(())
 }
 
+/// Returns the fingerprint index of the layout `unit` is built in, loading it
+/// on first use.
+///
+/// Without `-Zfingerprint-index` this returns `None` and deletes any index
+/// left behind by an earlier build, as it would no longer track the hash
+/// files written from now on.
+fn fingerprint_index(
+    build_runner: &mut BuildRunner<'_, '_>,
+    unit: &Unit,
+) -> Option<Arc<FingerprintIndex>> {
+    if let Some(index) = build_runner.fingerprint_indexes.get(&unit.kind) {
+        return index.clone();
+    }
+    let layout = build_runner.files().layout(unit.kind);
+    let path = layout.fingerprint_index();
+    let index = if build_runner.bcx.gctx.cli_unstable().fingerprint_index {
+        Some(Arc::new(FingerprintIndex::load(layout.fingerprint(), path)))
+    } else {
+        if path.exists() {
+            if let Err(e) = paths::remove_file(&path) {
+                debug!("failed to remove stale fingerprint index: {e:?}");
+            }
+        }
+        None
+    };
+    build_runner
+        .fingerprint_indexes
+        .insert(unit.kind, index.clone());
+    index
+}
+
 /// Prepare for work when a package starts to build
 pub fn prepare_init(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<()> {
     let new1 = build_runner.files().fingerprint_dir(unit);
@@ -1736,7 +1896,7 @@ pub fn prepare_init(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> Carg
 
 /// Returns the location that the dep-info file will show up at
 /// for the [`Unit`] specified.
-pub fn dep_info_loc(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> PathBuf {
+pub fn dep_info_loc(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> PathBuf {
     build_runner.files().fingerprint_file_path(unit, "dep-")
 }
 
@@ -1748,11 +1908,15 @@ fn target_root(build_runner: &BuildRunner<'_, '_>) -> PathBuf {
 
 /// Reads the value from the old fingerprint hash file and compare.
 ///
+/// The hash is taken from the fingerprint `index` when it has an entry for the
+/// file, avoiding a read of the file itself.
+///
 /// If dirty, it then restores the detailed information
 /// from the fingerprint JSON file, and provides an rich dirty reason.
 fn compare_old_fingerprint(
     unit: &Unit,
     old_hash_path: &Path,
+    index: Option<&FingerprintIndex>,
     new_fingerprint: &Fingerprint,
     mtime_on_use: bool,
     forced: bool,
@@ -1764,7 +1928,7 @@ fn compare_old_fingerprint(
         paths::set_file_time_no_err(old_hash_path, t);
     }
 
-    let compare = _compare_old_fingerprint(old_hash_path, new_fingerprint);
+    let compare = _compare_old_fingerprint(old_hash_path, index, new_fingerprint);
 
     match compare.as_ref() {
         Ok(None) => {}
@@ -1793,9 +1957,13 @@ fn compare_old_fingerprint(
 
 fn _compare_old_fingerprint(
     old_hash_path: &Path,
+    index: Option<&FingerprintIndex>,
     new_fingerprint: &Fingerprint,
 ) -> CargoResult<Option<DirtyReason>> {
-    let old_fingerprint_short = paths::read(old_hash_path)?;
+    let old_fingerprint_short = match index.and_then(|index| index.get(old_hash_path)) {
+        Some(hash) => hash,
+        None => paths::read(old_hash_path)?,
+    };
 
     let new_hash = new_fingerprint.hash_u64();
 
@@ -1849,6 +2017,11 @@ pub fn parse_dep_info(
             DepInfoPathType::TargetRootRelative => target_root.join(path),
         }
     }));
+    for (i, stamp) in info.stamps {
+        if let Some(path) = ret.files.get(i) {
+            ret.stamps.insert(path.clone(), stamp);
+        }
+    }
     Ok(Some(ret))
 }
 
@@ -1864,8 +2037,13 @@ fn pkg_fingerprint(bcx: &BuildContext<'_, '_>, pkg: &Package) -> CargoResult<Str
 }
 
 /// The `reference` file is considered as "stale" if any file from `paths` has a newer mtime.
+///
+/// With `build.fingerprint = "hash"`, `stamps` holds the [`FileStamp`]s of the
+/// last build, and the cache of the content hashes of files. A file with a
+/// stamp is instead stale if its contents changed.
 fn find_stale_file<I>(
     mtime_cache: &mut HashMap<PathBuf, FileTime>,
+    mut stamps: Option<(&HashMap<PathBuf, FileStamp>, &mut HashMap<PathBuf, u64>)>,
     reference: &Path,
     paths: I,
 ) -> Option<StaleItem>
@@ -1909,6 +2087,17 @@ where
             }
         };
 
+        if let Some((stamps, hash_cache)) = &mut stamps {
+            if let Some(stamp) = stamps.get(path) {
+                if stamp.is_current(path, path_mtime, hash_cache) {
+                    continue;
+                }
+                return Some(StaleItem::ChangedFileContents {
+                    stale: path.to_path_buf(),
+                });
+            }
+        }
+
         // TODO: fix #5918.
         // Note that equal mtimes should be considered "stale". For filesystems with
         // not much timestamp precision like 1s this is would be a conservative approximation
@@ -1979,6 +2168,12 @@ enum DepInfoPathType {
 ///
 /// The serialized Cargo format will contain a list of files, all of which are
 /// relative if they're under `root`. or absolute if they're elsewhere.
+///
+/// If `stamp_before` is set, which is the time the build started, a
+/// [`FileStamp`] is also recorded for each file which wasn't modified since.
+///
+/// The `proc_macro_inputs` file, if any, has the inputs reported by the
+/// proc-macros expanded by rustc, see [`add_proc_macro_inputs`].
 pub fn translate_dep_info(
     rustc_dep_info: &Path,
     cargo_dep_info: &Path,
@@ -1987,8 +2182,13 @@ pub fn translate_dep_info(
     target_root: &Path,
     rustc_cmd: &ProcessBuilder,
     allow_package: bool,
+    stamp_before: Option<FileTime>,
+    proc_macro_inputs: Option<&Path>,
 ) -> CargoResult<()> {
-    let depinfo = parse_rustc_dep_info(rustc_dep_info)?;
+    let mut depinfo = parse_rustc_dep_info(rustc_dep_info)?;
+    if let Some(proc_macro_inputs) = proc_macro_inputs {
+        add_proc_macro_inputs(&mut depinfo, proc_macro_inputs, rustc_cmd)?;
+    }
 
     let target_root = try_canonicalize(target_root)?;
     let pkg_root = try_canonicalize(pkg_root)?;
@@ -2049,6 +2249,11 @@ pub fn translate_dep_info(
             // effect.
             (DepInfoPathType::TargetRootRelative, &*abs_file)
         };
+        if let Some(stamp_before) = stamp_before {
+            if let Some(stamp) = FileStamp::new(&canon_file, stamp_before) {
+                on_disk_info.stamps.push((on_disk_info.files.len(), stamp));
+            }
+        }
         on_disk_info.files.push((ty, path.to_owned()));
     }
     paths::write(cargo_dep_info, on_disk_info.serialize()?)?;
@@ -2068,6 +2273,77 @@ pub struct RustcDepInfo {
     /// means that the env var wasn't actually set and the compilation depends
     /// on it not being set.
     pub env: Vec<(String, Option<String>)>,
+    /// The stamps of the files, when recorded by Cargo in its own dep-info
+    /// file with `build.fingerprint = "hash"`.
+    pub stamps: HashMap<PathBuf, FileStamp>,
+}
+
+/// The state of an input file when its unit was last built, used to detect
+/// whether its contents changed since, independently of its mtime.
+#[derive(Clone, Copy, Debug)]
+pub struct FileStamp {
+    size: u64,
+    mtime: FileTime,
+    hash: u64,
+}
+
+impl FileStamp {
+    /// Stamps the file at `path`, or returns `None` if it can't be read or
+    /// was modified after `stamp_before`.
+    fn new(path: &Path, stamp_before: FileTime) -> Option<FileStamp> {
+        let meta = fs::metadata(path).ok()?;
+        let mtime = FileTime::from_last_modification_time(&meta);
+        // The file could have been modified while rustc was reading it.
+        if !meta.is_file() || mtime >= stamp_before {
+            return None;
+        }
+        Some(FileStamp {
+            size: meta.len(),
+            mtime,
+            hash: hash_file(path).ok()?,
+        })
+    }
+
+    /// Whether the file at `path`, modified at `mtime`, still has the stamped
+    /// contents. The contents are only hashed if the size is the same but
+    /// the mtime differs.
+    fn is_current(
+        &self,
+        path: &Path,
+        mtime: FileTime,
+        hash_cache: &mut HashMap<PathBuf, u64>,
+    ) -> bool {
+        let Ok(meta) = fs::metadata(path) else {
+            return false;
+        };
+        if meta.len() != self.size {
+            return false;
+        }
+        if mtime == self.mtime {
+            return true;
+        }
+        let hash = match hash_cache.entry(path.to_path_buf()) {
+            Entry::Occupied(o) => *o.get(),
+            Entry::Vacant(v) => {
+                let Ok(hash) = hash_file(path) else {
+                    return false;
+                };
+                *v.insert(hash)
+            }
+        };
+        debug!(
+            "hashed {:?} after its mtime changed, same contents: {}",
+            path,
+            hash == self.hash
+        );
+        hash == self.hash
+    }
+}
+
+fn hash_file(path: &Path) -> CargoResult<u64> {
+    let mut hasher = StableHasher::new();
+    hasher.write(&paths::read_bytes(path)?);
+    Ok(hasher.finish())
 }
 
 /// Same as [`RustcDepInfo`] except avoids absolute paths as much as possible to
@@ -2079,6 +2355,10 @@ pub struct RustcDepInfo {
 struct EncodedDepInfo {
     files: Vec<(DepInfoPathType, PathBuf)>,
     env: Vec<(String, Option<String>)>,
+    /// The stamps of some of the `files`, by index. They are serialized
+    /// last, and only if there are any, so that the format stays compatible
+    /// with older versions of Cargo.
+    stamps: Vec<(usize, FileStamp)>,
 }
 
 impl EncodedDepInfo {
@@ -2107,7 +2387,21 @@ impl EncodedDepInfo {
             };
             env.push((key, val));
         }
-        return Some(EncodedDepInfo { files, env });
+
+        let mut stamps = Vec::new();
+        if !bytes.is_empty() {
+            let nstamps = read_usize(bytes)?;
+            for _ in 0..nstamps {
+                let i = read_usize(bytes)?;
+                let size = read_u64(bytes)?;
+                let seconds = read_u64(bytes)? as i64;
+                let nanos = read_usize(bytes)? as u32;
+                let hash = read_u64(bytes)?;
+                let mtime = FileTime::from_unix_time(seconds, nanos);
+                stamps.push((i, FileStamp { size, mtime, hash }));
+            }
+        }
+        return Some(EncodedDepInfo { files, env, stamps });
 
         fn read_usize(bytes: &mut &[u8]) -> Option<usize> {
             let ret = bytes.get(..4)?;
@@ -2115,6 +2409,12 @@ impl EncodedDepInfo {
             Some(u32::from_le_bytes(ret.try_into().unwrap()) as usize)
         }
 
+        fn read_u64(bytes: &mut &[u8]) -> Option<u64> {
+            let ret = bytes.get(..8)?;
+            *bytes = &bytes[8..];
+            Some(u64::from_le_bytes(ret.try_into().unwrap()))
+        }
+
         fn read_u8(bytes: &mut &[u8]) -> Option<u8> {
             let ret = *bytes.get(0)?;
             *bytes = &bytes[1..];
@@ -2152,6 +2452,17 @@ impl EncodedDepInfo {
                 }
             }
         }
+
+        if !self.stamps.is_empty() {
+            write_usize(dst, self.stamps.len());
+            for (i, stamp) in self.stamps.iter() {
+                write_usize(dst, *i);
+                write_u64(dst, stamp.size);
+                write_u64(dst, stamp.mtime.unix_seconds() as u64);
+                write_usize(dst, stamp.mtime.nanoseconds() as usize);
+                write_u64(dst, stamp.hash);
+            }
+        }
         return Ok(ret);
 
         fn write_bytes(dst: &mut Vec<u8>, val: impl AsRef<[u8]>) {
@@ -2163,7 +2474,42 @@ impl EncodedDepInfo {
         fn write_usize(dst: &mut Vec<u8>, val: usize) {
             dst.extend(&u32::to_le_bytes(val as u32));
         }
+
+        fn write_u64(dst: &mut Vec<u8>, val: u64) {
+            dst.extend(&u64::to_le_bytes(val));
+        }
+    }
+}
+
+/// Adds the inputs reported by proc-macros in the file at `inputs` to the
+/// dep-info of rustc, with `-Zproc-macro-inputs`.
+///
+/// Like the instructions of build scripts, each line of the file is either
+/// `cargo::rerun-if-changed=PATH`, the path being relative to the cwd of
+/// rustc, or `cargo::rerun-if-env-changed=NAME`, the variable being recorded
+/// with the value rustc had. Other lines are ignored, leaving room for more
+/// kinds of inputs.
+fn add_proc_macro_inputs(
+    depinfo: &mut RustcDepInfo,
+    inputs: &Path,
+    rustc_cmd: &ProcessBuilder,
+) -> CargoResult<()> {
+    if !inputs.exists() {
+        // No proc-macro reported anything.
+        return Ok(());
+    }
+    let contents = paths::read(inputs)?;
+    for line in contents.lines() {
+        if let Some(path) = line.strip_prefix("cargo::rerun-if-changed=") {
+            depinfo.files.push(path.into());
+        } else if let Some(name) = line.strip_prefix("cargo::rerun-if-env-changed=") {
+            let value = rustc_cmd
+                .get_env(name)
+                .and_then(|value| value.into_string().ok());
+            depinfo.env.push((name.to_string(), value));
+        }
     }
+    Ok(())
 }
 
 /// Parse the `.d` dep-info file generated by rustc.
@@ -164,21 +164,7 @@ impl OnDiskReports {
         if self.reports.len() > MAX_REPORTS {
             self.reports.remove(0);
         }
-        let on_disk = serde_json::to_vec(&self).unwrap();
-        if let Err(e) = ws
-            .target_dir()
-            .open_rw_exclusive_create(
-                FUTURE_INCOMPAT_FILE,
-                ws.gctx(),
-                "Future incompatibility report",
-            )
-            .and_then(|file| {
-                let mut file = file.file();
-                file.set_len(0)?;
-                file.write_all(&on_disk)?;
-                Ok(())
-            })
-        {
+        if let Err(e) = self.write(ws) {
             crate::display_warning_with_error(
                 "failed to write on-disk future incompatible report",
                 &e,
@@ -189,6 +175,35 @@ impl OnDiskReports {
         saved_id
     }
 
+    fn write(&self, ws: &Workspace<'_>) -> CargoResult<()> {
+        let on_disk = serde_json::to_vec(self).unwrap();
+        let file = ws.target_dir().open_rw_exclusive_create(
+            FUTURE_INCOMPAT_FILE,
+            ws.gctx(),
+            "Future incompatibility report",
+        )?;
+        let mut file = file.file();
+        file.set_len(0)?;
+        file.write_all(&on_disk)?;
+        Ok(())
+    }
+
+    /// Removes all but the `keep` most recent on-disk reports, returning how
+    /// many were removed.
+    pub fn prune(ws: &Workspace<'_>, keep: usize) -> CargoResult<usize> {
+        let path = ws.target_dir().join(FUTURE_INCOMPAT_FILE);
+        if !path.as_path_unlocked().exists() {
+            return Ok(0);
+        }
+        let mut on_disk_reports = OnDiskReports::load(ws)?;
+        let removed = on_disk_reports.reports.len().saturating_sub(keep);
+        if removed > 0 {
+            on_disk_reports.reports.drain(..removed);
+            on_disk_reports.write(ws)?;
+        }
+        Ok(removed)
+    }
+
     /// Loads the on-disk reports.
     pub fn load(ws: &Workspace<'_>) -> CargoResult<OnDiskReports> {
         let report_file = match ws.target_dir().open_ro_shared(
@@ -220,14 +235,20 @@ impl OnDiskReports {
         Ok(on_disk_reports)
     }
 
-    /// Returns the most recent report ID.
-    pub fn last_id(&self) -> u32 {
-        self.reports.last().map(|r| r.id).unwrap()
+    /// Returns the most recent report ID, or an error if all the reports
+    /// were pruned.
+    pub fn last_id(&self) -> CargoResult<u32> {
+        match self.reports.last() {
+            Some(report) => Ok(report.id),
+            None => bail!("no reports are currently available"),
+        }
     }
 
-    /// Returns an ANSI-styled report
-    pub fn get_report(&self, id: u32, package: Option<&str>) -> CargoResult<String> {
-        let report = self.reports.iter().find(|r| r.id == id).ok_or_else(|| {
+    fn find_report(&self, id: u32) -> CargoResult<&OnDiskReport> {
+        if self.reports.is_empty() {
+            bail!("no reports are currently available");
+        }
+        self.reports.iter().find(|r| r.id == id).ok_or_else(|| {
             let available = itertools::join(self.reports.iter().map(|r| r.id), ", ");
             format_err!(
                 "could not find report with ID {}\n\
@@ -235,37 +256,69 @@ impl OnDiskReports {
                 id,
                 available
             )
-        })?;
-
-        let mut to_display = report.suggestion_message.clone();
-        to_display += "\n";
+        })
+    }
 
-        let package_report = if let Some(package) = package {
-            report
-                .per_package
-                .get(package)
-                .ok_or_else(|| {
-                    format_err!(
-                        "could not find package with ID `{}`\n
+    /// Returns the per-package reports of the given report, restricted to
+    /// `package` if given.
+    fn per_package<'a>(
+        report: &'a OnDiskReport,
+        package: Option<&str>,
+    ) -> CargoResult<Vec<(&'a str, &'a str)>> {
+        if let Some(package) = package {
+            let (spec, rendered) = report.per_package.get_key_value(package).ok_or_else(|| {
+                format_err!(
+                    "could not find package with ID `{}`\n
                 Available packages are: {}\n
                 Omit the `--package` flag to display a report for all packages",
-                        package,
-                        itertools::join(report.per_package.keys(), ", ")
-                    )
-                })?
-                .to_string()
+                    package,
+                    itertools::join(report.per_package.keys(), ", ")
+                )
+            })?;
+            Ok(vec![(spec.as_str(), rendered.as_str())])
         } else {
-            report
+            Ok(report
                 .per_package
-                .values()
-                .cloned()
-                .collect::<Vec<_>>()
-                .join("\n")
-        };
+                .iter()
+                .map(|(spec, rendered)| (spec.as_str(), rendered.as_str()))
+                .collect())
+        }
+    }
+
+    /// Returns an ANSI-styled report
+    pub fn get_report(&self, id: u32, package: Option<&str>) -> CargoResult<String> {
+        let report = self.find_report(id)?;
+
+        let mut to_display = report.suggestion_message.clone();
+        to_display += "\n";
+
+        let package_report = Self::per_package(report, package)?
+            .into_iter()
+            .map(|(_, rendered)| rendered)
+            .collect::<Vec<_>>()
+            .join("\n");
         to_display += &package_report;
 
         Ok(to_display)
     }
+
+    /// Returns a report as a JSON value, with ANSI styling removed.
+    pub fn get_report_json(
+        &self,
+        id: u32,
+        package: Option<&str>,
+    ) -> CargoResult<serde_json::Value> {
+        let report = self.find_report(id)?;
+        let packages: BTreeMap<&str, String> = Self::per_package(report, package)?
+            .into_iter()
+            .map(|(spec, rendered)| (spec, anstream::adapter::strip_str(rendered).to_string()))
+            .collect();
+        Ok(serde_json::json!({
+            "id": report.id,
+            "suggestion_message": anstream::adapter::strip_str(&report.suggestion_message).to_string(),
+            "packages": packages,
+        }))
+    }
 }
 
 fn render_report(per_package_reports: &[FutureIncompatReportPackage]) -> BTreeMap<String, String> {
@@ -0,0 +1,187 @@
+//! Estimates the remaining time of a build, for `--ui compact`.
+//!
+//! When the build finishes, the duration of each unit which had to be
+//! rebuilt is saved in [`DURATIONS_FILE`] in the target directory. The next
+//! build adds up the saved durations of the units it still has to run, and
+//! uses them as the cost of each unit when scheduling, see [`load_history`].
+//!
+//! With `build.max-memory`, the peak memory of each rebuilt unit is saved in
+//! the same file, and used to estimate how much memory it will need the next
+//! time it runs.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context as _};
+use serde::{Deserialize, Serialize};
+
+use super::JobId;
+use crate::core::compiler::{CompileKind, Unit};
+use crate::core::Workspace;
+use crate::util::CargoResult;
+
+/// The file in the top-level `target` directory where unit durations are
+/// stored.
+const DURATIONS_FILE: &str = ".unit-durations.json";
+/// Current version of the on-disk format.
+const ON_DISK_VERSION: u32 = 0;
+
+/// The structure saved to disk containing the unit durations.
+#[derive(Default, Serialize, Deserialize)]
+struct OnDiskDurations {
+    /// A schema version number, to handle older cargo's from trying to read
+    /// something that they don't understand.
+    version: u32,
+    /// Duration of the last build of each unit, in seconds.
+    units: BTreeMap<String, f64>,
+    /// Peak memory of the last build of each unit, in bytes.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    memory: BTreeMap<String, u64>,
+}
+
+/// Tracks the expected remaining duration of a build.
+pub struct Eta {
+    /// Durations recorded by previous builds, in seconds.
+    history: BTreeMap<String, f64>,
+    /// Peak memory recorded by previous builds, in bytes.
+    memory: BTreeMap<String, u64>,
+    /// Sum of the recorded durations of the units which haven't finished yet.
+    remaining: f64,
+    /// Units being rebuilt, with the time they were started.
+    running: HashMap<JobId, (String, Instant)>,
+}
+
+/// Loads the durations, in seconds, and the peak memory, in bytes, recorded
+/// by previous builds, keyed by [`unit_key`].
+///
+/// Failures only disable the estimate, since they should not fail the build
+/// itself.
+pub fn load_history(ws: &Workspace<'_>) -> (BTreeMap<String, f64>, BTreeMap<String, u64>) {
+    match load(ws) {
+        Ok(on_disk) => (on_disk.units, on_disk.memory),
+        Err(e) => {
+            tracing::debug!("failed to load unit durations: {e:?}");
+            (BTreeMap::new(), BTreeMap::new())
+        }
+    }
+}
+
+impl Eta {
+    /// Starts tracking a build of `units`, from the durations and peak memory
+    /// recorded by previous builds.
+    pub fn new<'a>(
+        history: BTreeMap<String, f64>,
+        memory: BTreeMap<String, u64>,
+        units: impl Iterator<Item = &'a Unit>,
+    ) -> Eta {
+        let remaining = units.filter_map(|unit| history.get(&unit_key(unit))).sum();
+        Eta {
+            history,
+            memory,
+            remaining,
+            running: HashMap::new(),
+        }
+    }
+
+    /// Marks a unit which has to be rebuilt as started.
+    pub fn unit_started(&mut self, id: JobId, unit: &Unit) {
+        self.running.insert(id, (unit_key(unit), Instant::now()));
+    }
+
+    /// Marks a unit as finished, recording its duration if it was rebuilt.
+    pub fn unit_finished(&mut self, id: JobId, unit: &Unit) {
+        let key = unit_key(unit);
+        if let Some(expected) = self.history.get(&key) {
+            self.remaining = (self.remaining - expected).max(0.0);
+        }
+        if let Some((key, start)) = self.running.remove(&id) {
+            self.history.insert(key, start.elapsed().as_secs_f64());
+        }
+    }
+
+    /// Records the peak memory of a unit which was rebuilt.
+    pub fn record_memory(&mut self, unit: &Unit, bytes: u64) {
+        self.memory.insert(unit_key(unit), bytes);
+    }
+
+    /// Returns the expected remaining duration, or `None` if no previous
+    /// build recorded anything.
+    pub fn estimate(&self) -> Option<Duration> {
+        if self.remaining == 0.0 {
+            return None;
+        }
+        // Time already spent on running units isn't left to wait for.
+        let spent: f64 = self
+            .running
+            .values()
+            .filter_map(|(key, start)| {
+                let expected = self.history.get(key)?;
+                Some(start.elapsed().as_secs_f64().min(*expected))
+            })
+            .sum();
+        let parallelism = self.running.len().max(1) as f64;
+        let secs = (self.remaining - spent).max(0.0) / parallelism;
+        Some(Duration::from_secs_f64(secs))
+    }
+
+    /// Saves the recorded durations for the next build.
+    pub fn save(&self, ws: &Workspace<'_>) {
+        let on_disk = OnDiskDurations {
+            version: ON_DISK_VERSION,
+            units: self.history.clone(),
+            memory: self.memory.clone(),
+        };
+        if let Err(e) = write(ws, &on_disk) {
+            crate::display_warning_with_error(
+                "failed to write unit durations",
+                &e,
+                &mut ws.gctx().shell(),
+            );
+        }
+    }
+}
+
+/// Identifies a unit across builds.
+pub fn unit_key(unit: &Unit) -> String {
+    let kind = match unit.kind {
+        CompileKind::Host => "host",
+        CompileKind::Target(target) => target.short_name(),
+    };
+    format!(
+        "{} {:?} {} {}",
+        unit.pkg.package_id(),
+        unit.mode,
+        unit.target.description_named(),
+        kind
+    )
+}
+
+fn load(ws: &Workspace<'_>) -> CargoResult<OnDiskDurations> {
+    let path = ws.target_dir();
+    if !path.as_path_unlocked().join(DURATIONS_FILE).exists() {
+        return Ok(OnDiskDurations::default());
+    }
+    let file = path.open_ro_shared(DURATIONS_FILE, ws.gctx(), "unit durations")?;
+    let mut contents = String::new();
+    file.file()
+        .read_to_string(&mut contents)
+        .with_context(|| "failed to read unit durations")?;
+    let on_disk: OnDiskDurations =
+        serde_json::from_str(&contents).with_context(|| "failed to load unit durations")?;
+    if on_disk.version != ON_DISK_VERSION {
+        bail!("unit durations were saved from a future version of Cargo");
+    }
+    Ok(on_disk)
+}
+
+fn write(ws: &Workspace<'_>, on_disk: &OnDiskDurations) -> CargoResult<()> {
+    let contents = serde_json::to_vec(on_disk).unwrap();
+    let file =
+        ws.target_dir()
+            .open_rw_exclusive_create(DURATIONS_FILE, ws.gctx(), "unit durations")?;
+    let mut file = file.file();
+    file.set_len(0)?;
+    file.write_all(&contents)?;
+    Ok(())
+}
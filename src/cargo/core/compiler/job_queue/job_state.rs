@@ -47,6 +47,10 @@ pub struct JobState<'a, 'gctx> {
     /// sending a double message later on.
     rmeta_required: Cell<bool>,
 
+    /// Whether the warnings are sent to the summary of
+    /// `--show-dependency-warnings` instead of being displayed.
+    summarize_warnings: bool,
+
     // Historical versions of Cargo made use of the `'a` argument here, so to
     // leave the door open to future refactorings keep it here.
     _marker: marker::PhantomData<&'a ()>,
@@ -58,12 +62,14 @@ impl<'a, 'gctx> JobState<'a, 'gctx> {
         messages: Arc<Queue<Message>>,
         output: Option<&'a DiagDedupe<'gctx>>,
         rmeta_required: bool,
+        summarize_warnings: bool,
     ) -> Self {
         Self {
             id,
             messages,
             output,
             rmeta_required: Cell::new(rmeta_required),
+            summarize_warnings,
             _marker: marker::PhantomData,
         }
     }
@@ -102,9 +108,14 @@ impl<'a, 'gctx> JobState<'a, 'gctx> {
         Ok(())
     }
 
-    /// See [`Message::Diagnostic`] and [`Message::WarningCount`].
+    /// See [`Message::Diagnostic`], [`Message::WarningCount`] and
+    /// [`Message::DependencyWarning`].
     pub fn emit_diag(&self, level: String, diag: String, fixable: bool) -> CargoResult<()> {
-        if let Some(dedupe) = self.output {
+        if self.summarize_warnings && level == "warning" {
+            // Not bounded, like the other messages of fresh jobs.
+            self.messages
+                .push(Message::DependencyWarning { id: self.id, diag });
+        } else if let Some(dedupe) = self.output {
             let emitted = dedupe.emit_diag(&diag)?;
             if level == "warning" {
                 self.messages.push(Message::WarningCount {
@@ -124,6 +135,11 @@ impl<'a, 'gctx> JobState<'a, 'gctx> {
         Ok(())
     }
 
+    /// See [`Message::SarifDiagnostic`].
+    pub fn sarif_diagnostic(&self, diag: String) {
+        self.messages.push_bounded(Message::SarifDiagnostic(diag));
+    }
+
     /// See [`Message::Warning`].
     pub fn warning(&self, warning: String) -> CargoResult<()> {
         self.messages.push_bounded(Message::Warning {
@@ -133,6 +149,12 @@ impl<'a, 'gctx> JobState<'a, 'gctx> {
         Ok(())
     }
 
+    /// See [`Message::MissingSymbols`].
+    pub fn missing_symbols(&self, symbols: Vec<String>) {
+        self.messages
+            .push(Message::MissingSymbols(self.id, symbols));
+    }
+
     /// A method used to signal to the coordinator thread that the rmeta file
     /// for an rlib has been produced. This is only called for some rmeta
     /// builds when required, and can be called at any time before a job ends.
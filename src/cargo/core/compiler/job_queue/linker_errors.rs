@@ -0,0 +1,376 @@
+//! Explains the symbols the linker couldn't find when linking a unit fails.
+//!
+//! The error of `rustc` includes the whole output of the linker, starting
+//! with the linker command, which can be thousands of lines long. Outside of
+//! `--verbose`, [`truncate`] shortens it to the lines reporting the missing
+//! symbols. The symbols reported as missing are
+//! parsed from it with [`missing_symbols`], and once the unit failed, the job
+//! queue follows the error with a note attributing each of them to the crate
+//! or the native library which should have provided it, see [`report`].
+//!
+//! The formats of the GNU linkers, LLD, the macOS linker, and the MSVC linker
+//! are recognized. A mangled Rust symbol is attributed to its crate. Any other
+//! symbol is attributed to a native library linked by a package with a
+//! `links` key if the library, or the `links` value, is a prefix of the
+//! symbol, like `ssl` for `SSL_new`.
+
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+
+use crate::core::compiler::{descriptive_pkg_name, BuildRunner, Unit};
+use crate::util::CargoResult;
+
+/// The number of lines of a linker error shown outside of `--verbose`.
+const MAX_LINES: usize = 40;
+
+/// The number of lines at the start of a linker error always shown, with the
+/// linker which failed and its command.
+const HEAD_LINES: usize = 3;
+
+/// The number of missing symbols explained.
+const MAX_SYMBOLS: usize = 10;
+
+/// The patterns of the lines reporting a missing symbol, capturing it.
+const MISSING_SYMBOL_PATTERNS: [&str; 4] = [
+    // GNU ld and gold.
+    r"undefined reference to `([^']+)'",
+    // LLD.
+    r"undefined symbol: (\S+)",
+    // The macOS linker.
+    r#"(?m)^\s*"([^"]+)", referenced from:"#,
+    // The MSVC linker.
+    r"unresolved external symbol (\S+)",
+];
+
+/// Whether the error of `rustc` with `message` is a linker failure.
+pub fn is_linker_error(message: &str) -> bool {
+    message.starts_with("linking with `")
+}
+
+/// Parses the symbols the linker reported as missing from its output,
+/// without duplicates.
+pub fn missing_symbols(output: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut symbols = Vec::new();
+    for pattern in MISSING_SYMBOL_PATTERNS {
+        let re = Regex::new(pattern).unwrap();
+        for captures in re.captures_iter(output) {
+            let symbol = captures[1].to_string();
+            if seen.insert(symbol.clone()) {
+                symbols.push(symbol);
+            }
+        }
+    }
+    symbols
+}
+
+/// Keeps the first lines of the rendered linker error of `rustc`, the lines
+/// reporting missing symbols along with the line before each of them, which
+/// usually says where the symbol is referenced, and the last line, which
+/// holds the hints of `rustc`.
+pub fn truncate(rendered: String) -> String {
+    let lines = rendered.lines().collect::<Vec<_>>();
+    if lines.len() <= MAX_LINES {
+        return rendered;
+    }
+    let patterns = MISSING_SYMBOL_PATTERNS.map(|pattern| Regex::new(pattern).unwrap());
+    let mut keep = vec![false; lines.len()];
+    keep[..HEAD_LINES].fill(true);
+    keep[lines.len() - 1] = true;
+    let mut kept = HEAD_LINES + 1;
+    for (i, line) in lines.iter().enumerate() {
+        if kept >= MAX_LINES {
+            break;
+        }
+        if patterns.iter().any(|re| re.is_match(line)) {
+            for i in i.saturating_sub(1)..=i {
+                if !keep[i] {
+                    keep[i] = true;
+                    kept += 1;
+                }
+            }
+        }
+    }
+
+    let mut truncated = Vec::new();
+    let mut omitted = 0;
+    for (line, keep) in lines.iter().zip(keep) {
+        if !keep {
+            omitted += 1;
+            continue;
+        }
+        if omitted > 0 {
+            truncated.push(format!(
+                "          ... {omitted} lines omitted, run with `--verbose` to see them"
+            ));
+            omitted = 0;
+        }
+        truncated.push(line.to_string());
+    }
+    truncated.join("\n")
+}
+
+/// Prints which crate or native library should have provided each of the
+/// `symbols` the linker couldn't find when linking `unit`.
+pub fn report(
+    build_runner: &BuildRunner<'_, '_>,
+    unit: &Unit,
+    symbols: &[String],
+) -> CargoResult<()> {
+    let units = reachable_units(build_runner, unit);
+    let crates = units
+        .iter()
+        .filter(|u| u.target.is_lib())
+        .map(|u| (u.target.crate_name(), *u))
+        .collect::<HashMap<_, _>>();
+    let native_libs = native_libs(build_runner, &units);
+
+    let name = descriptive_pkg_name(&unit.pkg.name(), &unit.target, &unit.mode);
+    let mut msg = match symbols.len() {
+        1 => format!("the linker couldn't find a symbol needed by {name}:"),
+        n => format!("the linker couldn't find {n} symbols needed by {name}:"),
+    };
+    for symbol in symbols.iter().take(MAX_SYMBOLS) {
+        msg.push_str("\n  ");
+        if let Some((krate, path)) = rust_symbol(symbol) {
+            match path {
+                Some(path) => msg.push_str(&format!("`{path}` (`{symbol}`)")),
+                None => msg.push_str(&format!("`{symbol}`")),
+            }
+            match crates.get(&krate) {
+                Some(dep) => msg.push_str(&format!(
+                    ", from the crate `{krate}` of `{}`",
+                    dep.pkg.package_id()
+                )),
+                None => msg.push_str(&format!(
+                    ", from the crate `{krate}`, which isn't a dependency of `{}`",
+                    unit.pkg.name()
+                )),
+            }
+            continue;
+        }
+        msg.push_str(&format!("`{symbol}`"));
+        match native_libs
+            .iter()
+            .find(|lib| symbol_has_prefix(symbol, &lib.name))
+        {
+            Some(lib) if lib.name == lib.links => msg.push_str(&format!(
+                ", which may be provided by `{}` (`links = \"{}\"`)",
+                lib.unit.pkg.package_id(),
+                lib.links
+            )),
+            Some(lib) => msg.push_str(&format!(
+                ", which may be provided by the native library `{}` linked by `{}`",
+                lib.name,
+                lib.unit.pkg.package_id()
+            )),
+            None => msg.push_str(", which isn't provided by any package with a `links` key"),
+        }
+    }
+    if symbols.len() > MAX_SYMBOLS {
+        msg.push_str(&format!("\n  and {} more", symbols.len() - MAX_SYMBOLS));
+    }
+    build_runner.bcx.gctx.shell().note(msg)
+}
+
+/// A native library, or the `links` value of a package, which may provide
+/// missing symbols.
+struct NativeLib<'a> {
+    name: String,
+    links: String,
+    unit: &'a Unit,
+}
+
+/// The native libraries linked by the packages of `units` with a `links`
+/// key, along with their `links` values.
+fn native_libs<'a>(build_runner: &BuildRunner<'_, '_>, units: &[&'a Unit]) -> Vec<NativeLib<'a>> {
+    let outputs = build_runner.build_script_outputs.lock().unwrap();
+    let mut libs = Vec::new();
+    for unit in units {
+        let Some(links) = unit.pkg.manifest().links() else {
+            continue;
+        };
+        let output = build_runner
+            .find_build_script_metadata(unit)
+            .and_then(|metadata| outputs.get(metadata));
+        for link in output.iter().flat_map(|output| &output.library_links) {
+            // Like `static=foo:renamed`.
+            let name = link
+                .rsplit_once('=')
+                .map_or(link.as_str(), |(_, name)| name);
+            let name = name.split_once(':').map_or(name, |(name, _)| name);
+            libs.push(NativeLib {
+                name: name.to_string(),
+                links: links.to_string(),
+                unit,
+            });
+        }
+        libs.push(NativeLib {
+            name: links.to_string(),
+            links: links.to_string(),
+            unit,
+        });
+    }
+    libs
+}
+
+/// The units `unit` depends on, directly or not, along with itself.
+fn reachable_units<'a>(build_runner: &'a BuildRunner<'_, '_>, unit: &'a Unit) -> Vec<&'a Unit> {
+    let mut seen = HashSet::new();
+    let mut stack = vec![unit];
+    let mut units = Vec::new();
+    while let Some(unit) = stack.pop() {
+        if !seen.insert(unit) {
+            continue;
+        }
+        units.push(unit);
+        stack.extend(build_runner.unit_deps(unit).iter().map(|dep| &dep.unit));
+    }
+    units
+}
+
+/// Whether `symbol` starts with the name of a native library, or the name
+/// followed by `lib`, ignoring case and leading underscores, like `SSL_new`
+/// with `ssl` or `zlibVersion` with `z`.
+///
+/// The prefix must be followed by an underscore or, in camel case, by an
+/// uppercase letter.
+fn symbol_has_prefix(symbol: &str, name: &str) -> bool {
+    let symbol = symbol.trim_start_matches('_');
+    let name = name.strip_prefix("lib").unwrap_or(name);
+    if name.is_empty() {
+        return false;
+    }
+    [name.to_string(), format!("{name}lib")]
+        .iter()
+        .any(|prefix| {
+            symbol
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+                && symbol[prefix.len()..]
+                    .chars()
+                    .next()
+                    .map_or(true, |c| c == '_' || c.is_ascii_uppercase())
+        })
+}
+
+/// The crate of a mangled Rust symbol, along with its demangled path for the
+/// legacy mangling.
+fn rust_symbol(symbol: &str) -> Option<(String, Option<String>)> {
+    let symbol = symbol.trim_start_matches('_');
+    if let Some(mangled) = symbol.strip_prefix("ZN") {
+        let mut path = Vec::new();
+        let mut rest = mangled;
+        while let Some(len_end) = rest.find(|c: char| !c.is_ascii_digit()).filter(|&i| i > 0) {
+            let len: usize = rest[..len_end].parse().ok()?;
+            let ident = rest.get(len_end..len_end + len)?;
+            path.push(ident);
+            rest = &rest[len_end + len..];
+        }
+        if !rest.starts_with('E') || path.is_empty() {
+            return None;
+        }
+        // Drop the hash, like `h0123456789abcdef`.
+        if path.len() > 1 && path.last().is_some_and(|last| is_hash(last)) {
+            path.pop();
+        }
+        return Some((path[0].to_string(), Some(path.join("::"))));
+    }
+    if symbol.starts_with('R') {
+        let re = Regex::new(r"^R[0-9]*[A-Za-z]*?C(?:s[0-9A-Za-z]*_)?([0-9]+)_?").unwrap();
+        let captures = re.captures(symbol)?;
+        let len: usize = captures[1].parse().ok()?;
+        let start = captures.get(0)?.end();
+        let krate = symbol.get(start..start + len)?;
+        return Some((krate.to_string(), None));
+    }
+    None
+}
+
+fn is_hash(ident: &str) -> bool {
+    ident.len() == 17 && ident.starts_with('h') && ident[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_missing_symbols() {
+        let output = "\
+/usr/bin/ld: foo.o: in function `main':
+foo.c:(.text+0x5): undefined reference to `SSL_new'
+foo.c:(.text+0xa): undefined reference to `SSL_new'
+rust-lld: error: undefined symbol: _ZN3bar3baz17h0123456789abcdefE
+  \"_git_libgit2_init\", referenced from:
+error LNK2019: unresolved external symbol zlibVersion referenced in function main
+";
+        assert_eq!(
+            missing_symbols(output),
+            [
+                "SSL_new",
+                "_ZN3bar3baz17h0123456789abcdefE",
+                "_git_libgit2_init",
+                "zlibVersion",
+            ]
+        );
+    }
+
+    #[test]
+    fn demangles_rust_symbols() {
+        assert_eq!(
+            rust_symbol("_ZN3bar3baz17h0123456789abcdefE"),
+            Some(("bar".to_string(), Some("bar::baz".to_string())))
+        );
+        assert_eq!(
+            rust_symbol("__ZN8my_crate5inner3fooE"),
+            Some((
+                "my_crate".to_string(),
+                Some("my_crate::inner::foo".to_string())
+            ))
+        );
+        assert_eq!(
+            rust_symbol("_RNvCs1234_7mycrate3foo"),
+            Some(("mycrate".to_string(), None))
+        );
+        assert_eq!(rust_symbol("SSL_new"), None);
+    }
+
+    #[test]
+    fn matches_native_library_prefixes() {
+        assert!(symbol_has_prefix("SSL_new", "ssl"));
+        assert!(symbol_has_prefix("_git_libgit2_init", "git"));
+        assert!(symbol_has_prefix("zlibVersion", "libz"));
+        assert!(!symbol_has_prefix("sslnew", "ssl"));
+        assert!(!symbol_has_prefix("SSL_new", "crypto"));
+        assert!(!symbol_has_prefix("zstd_compress", "z"));
+    }
+
+    #[test]
+    fn truncates_around_missing_symbols() {
+        let mut lines = vec![
+            "error: linking with `cc` failed: exit status: 1".to_string(),
+            "  |".to_string(),
+            "  = note: LC_ALL=\"C\" \"cc\" \"foo.o\"".to_string(),
+        ];
+        lines.extend((0..100).map(|i| format!("  = note: /usr/bin/ld: warning {i}")));
+        lines.push("          /usr/bin/ld: foo.o: in function `main':".to_string());
+        lines.push("          foo.c:(.text+0x5): undefined reference to `SSL_new'".to_string());
+        lines.extend((0..100).map(|i| format!("  = note: /usr/bin/ld: warning {i}")));
+        lines.push("  = note: some hint".to_string());
+        let truncated = truncate(lines.join("\n"));
+        assert_eq!(
+            truncated,
+            "\
+error: linking with `cc` failed: exit status: 1
+  |
+  = note: LC_ALL=\"C\" \"cc\" \"foo.o\"
+          ... 100 lines omitted, run with `--verbose` to see them
+          /usr/bin/ld: foo.o: in function `main':
+          foo.c:(.text+0x5): undefined reference to `SSL_new'
+          ... 100 lines omitted, run with `--verbose` to see them
+  = note: some hint"
+        );
+    }
+}
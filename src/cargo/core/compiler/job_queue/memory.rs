@@ -0,0 +1,144 @@
+//! Limits the number of jobs running at once by their memory use, with
+//! `build.max-memory`.
+//!
+//! A unit which has to be rebuilt is only started if the memory expected to
+//! be used by the running units, plus the memory it is expected to use
+//! itself, fits in the limit. The memory a unit is expected to use is its
+//! peak memory the last time it was rebuilt, see [`eta`](super::eta). Units
+//! which were never measured use the average of the others, or
+//! [`PLACEHOLDER_ESTIMATE`] when none was, and a running unit which uses more
+//! than expected counts with its current use.
+//!
+//! While a unit runs, the memory of the processes spawned by its job, and of
+//! their descendants, is sampled periodically. See [`crate::util::memory`]
+//! for the platforms where this is supported. Elsewhere, units are never
+//! measured, so the limit only bounds the number of running units by the
+//! placeholder.
+//!
+//! A unit is always started when no other one is running, so that the build
+//! makes progress even if a single unit exceeds the limit.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::eta::unit_key;
+use super::JobId;
+use crate::core::compiler::Unit;
+use crate::util::memory::Thread;
+
+/// How often the memory of the running units is sampled.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Expected memory of units when no unit was measured yet, in bytes. This is
+/// in the range of what `rustc` uses for a mid-sized crate.
+const PLACEHOLDER_ESTIMATE: u64 = 512 * 1024 * 1024;
+
+pub struct MemoryLimit {
+    /// The memory all running units may use together, in bytes.
+    limit: u64,
+    /// Peak memory recorded by previous builds, in bytes.
+    history: BTreeMap<String, u64>,
+    /// Expected memory of units without a recorded peak.
+    default_estimate: u64,
+    /// Units being rebuilt.
+    running: HashMap<JobId, Running>,
+    /// The threads running each job, registered by the jobs themselves.
+    threads: JobThreads,
+    last_sample: Instant,
+}
+
+struct Running {
+    /// Expected peak memory of the unit.
+    estimate: u64,
+    /// Memory used by the unit at the last sample.
+    current: u64,
+    /// Highest memory use sampled so far, if any.
+    peak: Option<u64>,
+}
+
+/// Where each job registers the thread running it, so that the processes it
+/// spawns can be measured from the main thread.
+#[derive(Clone, Default)]
+pub struct JobThreads(Arc<Mutex<HashMap<JobId, Thread>>>);
+
+impl JobThreads {
+    /// Registers the calling thread as the one running job `id`.
+    pub fn register(&self, id: JobId) {
+        if let Some(thread) = Thread::current() {
+            self.0.lock().unwrap().insert(id, thread);
+        }
+    }
+}
+
+impl MemoryLimit {
+    pub fn new(limit: u64, history: BTreeMap<String, u64>) -> MemoryLimit {
+        let default_estimate = if history.is_empty() {
+            PLACEHOLDER_ESTIMATE
+        } else {
+            history.values().sum::<u64>() / history.len() as u64
+        };
+        MemoryLimit {
+            limit,
+            history,
+            default_estimate,
+            running: HashMap::new(),
+            threads: JobThreads::default(),
+            last_sample: Instant::now(),
+        }
+    }
+
+    /// Whether `unit` can be started without exceeding the limit.
+    pub fn fits(&self, unit: &Unit) -> bool {
+        if self.running.is_empty() {
+            return true;
+        }
+        let in_use: u64 = self
+            .running
+            .values()
+            .map(|running| running.estimate.max(running.current))
+            .sum();
+        in_use.saturating_add(self.estimate(unit)) <= self.limit
+    }
+
+    /// Marks a unit which has to be rebuilt as started. The returned handle
+    /// must be used to register the thread running its job.
+    pub fn unit_started(&mut self, id: JobId, unit: &Unit) -> JobThreads {
+        let running = Running {
+            estimate: self.estimate(unit),
+            current: 0,
+            peak: None,
+        };
+        self.running.insert(id, running);
+        self.threads.clone()
+    }
+
+    /// Marks a unit as finished, returning its peak memory if it was measured.
+    pub fn unit_finished(&mut self, id: JobId) -> Option<u64> {
+        self.threads.0.lock().unwrap().remove(&id);
+        self.running.remove(&id)?.peak
+    }
+
+    /// Measures the memory of the running units, if it wasn't done recently.
+    pub fn sample(&mut self) {
+        if self.running.is_empty() || self.last_sample.elapsed() < SAMPLE_INTERVAL {
+            return;
+        }
+        self.last_sample = Instant::now();
+        let threads = self.threads.0.lock().unwrap();
+        for (id, running) in &mut self.running {
+            let Some(memory) = threads.get(id).and_then(|thread| thread.spawned_memory()) else {
+                continue;
+            };
+            running.current = memory;
+            running.peak = Some(running.peak.unwrap_or(0).max(memory));
+        }
+    }
+
+    fn estimate(&self, unit: &Unit) -> u64 {
+        self.history
+            .get(&unit_key(unit))
+            .copied()
+            .unwrap_or(self.default_estimate)
+    }
+}
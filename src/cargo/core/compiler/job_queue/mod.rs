@@ -57,18 +57,24 @@
 //!
 //! ## Scheduling
 //!
-//! The current scheduling algorithm is not really polished. It is simply based
-//! on a dependency graph [`DependencyQueue`]. We continue adding nodes onto
-//! the graph until we finalize it. When the graph gets finalized, it finds the
-//! sum of the cost of each dependencies of each node, including transitively.
-//! The sum of dependency cost turns out to be the cost of each given node.
+//! The scheduling algorithm is based on a dependency graph [`DependencyQueue`].
+//! We continue adding nodes onto the graph until we finalize it. When the
+//! graph gets finalized, it finds the length of the critical path starting at
+//! each node: its own cost, plus the longest chain of costs of the nodes
+//! depending on it, transitively. Nodes on longer paths are started first, so
+//! that the final long chain of a build (e.g. the leaf binary and its biggest
+//! dependency) isn't left for last.
 //!
-//! At the time being, the cost is just passed as a fixed placeholder in
-//! [`JobQueue::enqueue`]. In the future, we could explore more possibilities
-//! around it. For instance, we start persisting timing information for each
-//! build somewhere. For a subsequent build, we can look into the historical
-//! data and perform a PGO-like optimization to prioritize jobs, making a build
-//! fully pipelined.
+//! The cost of a unit is the time it took the last time it was rebuilt, as
+//! saved in `target/.unit-durations.json`. Units which were never built use
+//! the average duration of the other units, or a fixed placeholder on the
+//! first build. See [`JobQueue::enqueue`].
+//!
+//! With `build.max-memory`, a unit is also held back while the memory it is
+//! expected to use doesn't fit next to the running units, see [`memory`].
+//! With `build.link-jobs`, units which end with a link step, like binaries
+//! and tests, are held back while that many of them are already running. See
+//! [`is_link_job`].
 //!
 //! ## Message queue
 //!
@@ -110,43 +116,83 @@
 //! [`push`]: Queue::push
 //! [`push_bounded`]: Queue::push_bounded
 
+mod eta;
 mod job;
 mod job_state;
+pub(super) mod linker_errors;
+mod memory;
 
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Write as _;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread::{self, Scope};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{format_err, Context as _};
-use cargo_util::ProcessBuilder;
+use cargo_util::{paths, ProcessBuilder};
 use jobserver::{Acquired, HelperThread};
 use semver::Version;
 use tracing::{debug, trace};
 
+use self::eta::Eta;
 pub use self::job::Freshness::{self, Dirty, Fresh};
 pub use self::job::{Job, Work};
 pub use self::job_state::JobState;
+use self::memory::MemoryLimit;
+use super::build_meta::BuildInputs;
 use super::build_runner::OutputFile;
+use super::sarif::SarifLog;
 use super::timings::Timings;
-use super::{BuildContext, BuildPlan, BuildRunner, CompileMode, Unit};
+use super::{
+    BuildContext, BuildPlan, BuildRunner, CompileMode, DiagnosticLevel, MessageFormat, Unit,
+};
+use crate::core::compiler::build_reports::{self, BuildSummary};
 use crate::core::compiler::descriptive_pkg_name;
 use crate::core::compiler::future_incompat::{
     self, FutureBreakageItem, FutureIncompatReportPackage,
 };
 use crate::core::resolver::ResolveBehavior;
-use crate::core::{PackageId, Shell, TargetKind};
+use crate::core::{PackageId, Shell, TargetKind, UiMode};
+use crate::util::cancel::Cancelled;
 use crate::util::diagnostic_server::{self, DiagnosticPrinter};
 use crate::util::errors::AlreadyPrintedError;
+use crate::util::interrupt::{self, InterruptGuard};
 use crate::util::machine_message::{self, Message as _};
 use crate::util::CargoResult;
 use crate::util::{self, internal};
 use crate::util::{DependencyQueue, GlobalContext, Progress, ProgressStyle, Queue};
 
+/// The cost of each unit when there is no recorded duration at all, such as
+/// on the first build. Any value works, since all units then cost the same.
+const DEFAULT_UNIT_COST: usize = 1000;
+
+/// Converts a recorded duration, in seconds, to the cost of a unit in the
+/// [`DependencyQueue`], in milliseconds.
+fn unit_cost(secs: f64) -> usize {
+    ((secs * 1000.0) as usize).max(1)
+}
+
+/// Whether a unit ends with a link step, which is where building large
+/// binaries uses the most memory and I/O. This is the case of executables,
+/// tests, and dynamic or static libraries, but not of build scripts and
+/// proc-macros which are usually small.
+fn is_link_job(unit: &Unit) -> bool {
+    if !unit.mode.generates_executable() || unit.target.is_custom_build() {
+        return false;
+    }
+    unit.mode.is_rustc_test()
+        || unit.target.is_executable()
+        || unit.target.is_test()
+        || unit.target.is_bench()
+        || unit.target.is_fuzz()
+        || unit.target.is_dylib()
+        || unit.target.is_cdylib()
+        || unit.target.is_staticlib()
+}
+
 /// This structure is backed by the `DependencyQueue` type and manages the
 /// queueing of compilation steps for each package. Packages enqueue units of
 /// work and then later on the entire graph is converted to DrainState and
@@ -155,6 +201,12 @@ pub struct JobQueue<'gctx> {
     queue: DependencyQueue<Unit, Artifact, Job>,
     counts: HashMap<PackageId, usize>,
     timings: Timings<'gctx>,
+    /// Durations of the units recorded by previous builds, in seconds.
+    durations: BTreeMap<String, f64>,
+    /// The cost of units without a recorded duration.
+    default_cost: usize,
+    /// Peak memory of the units recorded by previous builds, in bytes.
+    memory: BTreeMap<String, u64>,
 }
 
 /// This structure is backed by the `DependencyQueue` type and manages the
@@ -173,12 +225,28 @@ struct DrainState<'gctx> {
     diag_dedupe: DiagDedupe<'gctx>,
     /// Count of warnings, used to print a summary after the job succeeds
     warning_count: HashMap<JobId, WarningCount>,
+    /// Total number of warnings emitted during the build
+    total_warnings: usize,
     active: HashMap<JobId, Unit>,
     compiled: HashSet<PackageId>,
     documented: HashSet<PackageId>,
     scraped: HashSet<PackageId>,
     counts: HashMap<PackageId, usize>,
     progress: Progress<'gctx>,
+    /// Durations of the units being rebuilt, and estimate of the remaining
+    /// build time. Not tracked with `--build-plan`.
+    eta: Option<Eta>,
+    /// Whether the estimate is shown on the progress bar, with `--ui compact`.
+    show_eta: bool,
+    /// Memory use of the running units, with `build.max-memory`.
+    memory_limit: Option<MemoryLimit>,
+    /// Dirty units which end with a link step and are running.
+    link_jobs: HashSet<JobId>,
+    /// Symbols the linker couldn't find, for the units which failed to link.
+    missing_symbols: HashMap<JobId, Vec<String>>,
+    /// Inputs of this build and how they changed since the previous one, only
+    /// tracked with `-Zbuild-meta`.
+    build_inputs: Option<(BuildInputs, Vec<String>)>,
     next_id: u32,
     timings: Timings<'gctx>,
 
@@ -200,6 +268,31 @@ struct DrainState<'gctx> {
     /// How many jobs we've finished
     finished: usize,
     per_package_future_incompat_reports: Vec<FutureIncompatReportPackage>,
+    /// Diagnostics to print at the end of the build, with
+    /// `--message-format sarif`.
+    sarif: Option<SarifLog>,
+    /// Warnings to print at the end of the build, with
+    /// `--show-dependency-warnings`.
+    dependency_warnings: DependencyWarnings,
+    /// Whether the build was interrupted, after which no new job is started.
+    interrupted: bool,
+    /// How often the state of the build is saved, with
+    /// `--checkpoint-interval`.
+    checkpoint_interval: Option<Duration>,
+    last_checkpoint: Instant,
+}
+
+/// The warnings of the dependencies selected with
+/// `--show-dependency-warnings`, deduplicated across all of them.
+#[derive(Default)]
+struct DependencyWarnings {
+    /// The warnings, in the order they were emitted.
+    diags: Vec<String>,
+    /// The hashes of `diags`.
+    seen: HashSet<u64>,
+    /// The number of warnings of each package, and how many of them were
+    /// duplicates.
+    counts: BTreeMap<PackageId, WarningCount>,
 }
 
 /// Count of warnings, used to print a summary after the job succeeds
@@ -214,6 +307,8 @@ pub struct WarningCount {
     /// if any errors have been seen ofr the current
     /// target
     pub fixable: FixableWarnings,
+    /// the warnings were replayed from the cache of a fresh unit
+    pub cached: bool,
 }
 
 impl WarningCount {
@@ -367,19 +462,44 @@ enum Message {
         id: JobId,
         warning: String,
     },
+    // A diagnostic from rustc, as JSON, to include in the SARIF log
+    SarifDiagnostic(String),
+    // A warning of a dependency selected with `--show-dependency-warnings`,
+    // displayed at the end of the build
+    DependencyWarning {
+        id: JobId,
+        diag: String,
+    },
 
     FixDiagnostic(diagnostic_server::Message),
     Token(io::Result<Acquired>),
     Finish(JobId, Artifact, CargoResult<()>),
     FutureIncompatReport(JobId, Vec<FutureBreakageItem>),
+    // The symbols the linker couldn't find when a unit failed to link,
+    // explained once the unit finished
+    MissingSymbols(JobId, Vec<String>),
 }
 
 impl<'gctx> JobQueue<'gctx> {
     pub fn new(bcx: &BuildContext<'_, 'gctx>) -> JobQueue<'gctx> {
+        let (durations, memory) = if bcx.build_config.build_plan {
+            (BTreeMap::new(), BTreeMap::new())
+        } else {
+            eta::load_history(bcx.ws)
+        };
+        let default_cost = if durations.is_empty() {
+            DEFAULT_UNIT_COST
+        } else {
+            let total: f64 = durations.values().sum();
+            unit_cost(total / durations.len() as f64)
+        };
         JobQueue {
             queue: DependencyQueue::new(),
             counts: HashMap::new(),
             timings: Timings::new(bcx, &bcx.roots),
+            durations,
+            default_cost,
+            memory,
         }
     }
 
@@ -453,11 +573,14 @@ impl<'gctx> JobQueue<'gctx> {
             }
         }
 
-        // For now we use a fixed placeholder value for the cost of each unit, but
-        // in the future this could be used to allow users to provide hints about
-        // relative expected costs of units, or this could be automatically set in
-        // a smarter way using timing data from a previous compilation.
-        self.queue.queue(unit.clone(), job, queue_deps, 100);
+        // The cost is how long the unit took the last time it was rebuilt, so
+        // that units on the critical path of the build are started first.
+        let cost = self
+            .durations
+            .get(&eta::unit_key(unit))
+            .map(|secs| unit_cost(*secs))
+            .unwrap_or(self.default_cost);
+        self.queue.queue(unit.clone(), job, queue_deps, cost);
         *self.counts.entry(unit.pkg.package_id()).or_insert(0) += 1;
         Ok(())
     }
@@ -477,6 +600,34 @@ impl<'gctx> JobQueue<'gctx> {
 
         let progress =
             Progress::with_style("Building", ProgressStyle::Ratio, build_runner.bcx.gctx);
+        // Durations are always recorded, since they are the cost of each unit
+        // in the next build, but the estimate is only shown with `--ui compact`.
+        let build_plan = build_runner.bcx.build_config.build_plan;
+        let memory_limit = build_runner
+            .bcx
+            .build_config
+            .max_memory
+            .filter(|_| !build_plan)
+            .map(|limit| MemoryLimit::new(limit, self.memory.clone()));
+        let eta = (!build_plan).then(|| Eta::new(self.durations, self.memory, self.queue.nodes()));
+        let show_eta = build_runner.bcx.gctx.shell().ui() == UiMode::Compact;
+        let build_inputs = if build_runner.bcx.gctx.cli_unstable().build_meta
+            && !build_runner.bcx.build_config.build_plan
+        {
+            let current = BuildInputs::new(build_runner.bcx)?;
+            let changes = match BuildInputs::load(build_runner.bcx.ws) {
+                Ok(Some(previous)) => current.changes(&previous),
+                Ok(None) => Vec::new(),
+                Err(e) => {
+                    debug!("failed to load build inputs: {e:?}");
+                    Vec::new()
+                }
+            };
+            self.timings.set_input_changes(changes.clone());
+            Some((current, changes))
+        } else {
+            None
+        };
         let state = DrainState {
             total_units: self.queue.len(),
             queue: self.queue,
@@ -487,12 +638,19 @@ impl<'gctx> JobQueue<'gctx> {
             messages: Arc::new(Queue::new(100)),
             diag_dedupe: DiagDedupe::new(build_runner.bcx.gctx),
             warning_count: HashMap::new(),
+            total_warnings: 0,
             active: HashMap::new(),
             compiled: HashSet::new(),
             documented: HashSet::new(),
             scraped: HashSet::new(),
             counts: self.counts,
             progress,
+            eta,
+            show_eta,
+            memory_limit,
+            link_jobs: HashSet::new(),
+            missing_symbols: HashMap::new(),
+            build_inputs,
             next_id: 0,
             timings: self.timings,
             tokens: Vec::new(),
@@ -503,6 +661,12 @@ impl<'gctx> JobQueue<'gctx> {
             ),
             finished: 0,
             per_package_future_incompat_reports: Vec::new(),
+            sarif: (build_runner.bcx.build_config.message_format == MessageFormat::Sarif)
+                .then(|| SarifLog::new(build_runner.bcx.ws.root())),
+            dependency_warnings: DependencyWarnings::default(),
+            interrupted: false,
+            checkpoint_interval: build_runner.bcx.build_config.checkpoint_interval,
+            last_checkpoint: Instant::now(),
         };
 
         // Create a helper thread for acquiring jobserver tokens
@@ -529,6 +693,11 @@ impl<'gctx> JobQueue<'gctx> {
             .take()
             .map(move |srv| srv.start(move |msg| messages.push(Message::FixDiagnostic(msg))));
 
+        // Ctrl-C is handled by the queue until the running jobs are done.
+        let _interrupt_guard = (build_runner.bcx.gctx.cli_unstable().graceful_interrupt
+            && !build_runner.bcx.build_config.build_plan)
+            .then(InterruptGuard::install);
+
         thread::scope(move |scope| {
             match state.drain_the_queue(build_runner, plan, scope, &helper) {
                 Some(err) => Err(err),
@@ -571,7 +740,12 @@ impl<'gctx> DrainState<'gctx> {
         // remove items from its end to schedule the highest priority items
         // sooner.
         while self.has_extra_tokens() && !self.pending_queue.is_empty() {
-            let (unit, job, _) = self.pending_queue.pop().unwrap();
+            // Jobs held back don't keep their tokens, they start with the one
+            // of the job whose end lets them start.
+            let Some(idx) = self.next_pending_job(build_runner.bcx.build_config.link_jobs) else {
+                break;
+            };
+            let (unit, job, _) = self.pending_queue.remove(idx);
             *self.counts.get_mut(&unit.pkg.package_id()).unwrap() -= 1;
             if !build_runner.bcx.build_config.build_plan {
                 // Print out some nice progress information.
@@ -583,7 +757,21 @@ impl<'gctx> DrainState<'gctx> {
                     build_runner.bcx.ws.root(),
                     &unit,
                     job.freshness(),
+                    build_runner.bcx.build_config.explain_rebuild,
                 )?;
+                if build_runner.bcx.build_config.explain_rebuild {
+                    self.explain_rebuild(build_runner, &unit, job.freshness())?;
+                }
+                if build_runner.bcx.gctx.shell().json_progress() {
+                    let msg = machine_message::UnitStarted {
+                        package_id: unit.pkg.package_id().to_spec(),
+                        target: &unit.target,
+                        mode: unit.mode,
+                        fresh: job.freshness().is_fresh(),
+                    }
+                    .to_json_string();
+                    build_runner.bcx.gctx.shell().print_json_progress(&msg)?;
+                }
             }
             self.run(&unit, job, build_runner, scope);
         }
@@ -595,6 +783,23 @@ impl<'gctx> DrainState<'gctx> {
         self.active.len() < self.tokens.len() + 1
     }
 
+    /// Returns the index in the `pending_queue` of the job with the highest
+    /// priority which fits in the memory limit and `build.link-jobs`, if any.
+    fn next_pending_job(&self, link_jobs: Option<u32>) -> Option<usize> {
+        let links_full = link_jobs.is_some_and(|max| self.link_jobs.len() >= max as usize);
+        self.pending_queue.iter().rposition(|(unit, job, _)| {
+            if job.freshness().is_fresh() {
+                return true;
+            }
+            if links_full && is_link_job(unit) {
+                return false;
+            }
+            self.memory_limit
+                .as_ref()
+                .map_or(true, |limit| limit.fits(unit))
+        })
+    }
+
     fn handle_event(
         &mut self,
         build_runner: &mut BuildRunner<'_, '_>,
@@ -637,6 +842,25 @@ impl<'gctx> DrainState<'gctx> {
                     cnts.disallow_fixable();
                 }
             }
+            Message::SarifDiagnostic(diag) => {
+                if let Some(sarif) = &mut self.sarif {
+                    sarif.add(&diag);
+                }
+            }
+            Message::DependencyWarning { id, diag } => {
+                let warnings = &mut self.dependency_warnings;
+                let count = warnings
+                    .counts
+                    .entry(self.active[&id].pkg.package_id())
+                    .or_default();
+                count.total += 1;
+                if warnings.seen.insert(util::hash_u64(&diag)) {
+                    warnings.diags.push(diag);
+                    self.total_warnings += 1;
+                } else {
+                    count.duplicates += 1;
+                }
+            }
             Message::Warning { id, warning } => {
                 build_runner.bcx.gctx.shell().warn(warning)?;
                 self.bump_warning_count(id, true, false);
@@ -663,7 +887,19 @@ impl<'gctx> DrainState<'gctx> {
                             id,
                             &build_runner.bcx.rustc().workspace_wrapper,
                         );
-                        self.active.remove(&id).unwrap()
+                        let unit = self.active.remove(&id).unwrap();
+                        self.link_jobs.remove(&id);
+                        let peak_memory = self
+                            .memory_limit
+                            .as_mut()
+                            .and_then(|limit| limit.unit_finished(id));
+                        if let Some(eta) = &mut self.eta {
+                            eta.unit_finished(id, &unit);
+                            if let Some(peak) = peak_memory {
+                                eta.record_memory(&unit, peak);
+                            }
+                        }
+                        unit
                     }
                     // ... otherwise if it hasn't finished we leave it
                     // in there as we'll get another `Finish` later on.
@@ -673,6 +909,9 @@ impl<'gctx> DrainState<'gctx> {
                     }
                 };
                 debug!("end ({:?}): {:?}", unit, result);
+                if artifact == Artifact::All && build_runner.bcx.gctx.shell().json_progress() {
+                    self.emit_unit_finished(build_runner.bcx.gctx, &unit, result.is_ok())?;
+                }
                 match result {
                     Ok(()) => self.finish(id, &unit, artifact, build_runner)?,
                     Err(_) if build_runner.bcx.unit_can_fail_for_docscraping(&unit) => {
@@ -683,7 +922,16 @@ impl<'gctx> DrainState<'gctx> {
                             .insert(build_runner.files().metadata(&unit));
                         self.queue.finish(&unit, &artifact);
                     }
+                    Err(error) if self.interrupted => {
+                        // The unit was most likely killed by the interrupt,
+                        // so its outputs may be incomplete.
+                        debug!("discarding outputs of {:?}: {:?}", unit, error);
+                        self.discard_outputs(build_runner, &unit);
+                    }
                     Err(error) => {
+                        if let Some(symbols) = self.missing_symbols.remove(&id) {
+                            linker_errors::report(build_runner, &unit, &symbols)?;
+                        }
                         let msg = "The following warnings were emitted during compilation:";
                         self.emit_warnings(Some(msg), &unit, build_runner)?;
                         self.back_compat_notice(build_runner, &unit)?;
@@ -694,6 +942,9 @@ impl<'gctx> DrainState<'gctx> {
                     }
                 }
             }
+            Message::MissingSymbols(id, symbols) => {
+                self.missing_symbols.entry(id).or_default().extend(symbols);
+            }
             Message::FutureIncompatReport(id, items) => {
                 let package_id = self.active[&id].pkg.package_id();
                 self.per_package_future_incompat_reports
@@ -717,15 +968,24 @@ impl<'gctx> DrainState<'gctx> {
         // listen for a message with a timeout, and on timeout we run the
         // previous parts of the loop again.
         let mut events = self.messages.try_pop_all();
+        if let Some(limit) = &mut self.memory_limit {
+            limit.sample();
+        }
         if events.is_empty() {
             loop {
                 self.tick_progress();
+                if let Some(limit) = &mut self.memory_limit {
+                    limit.sample();
+                }
                 self.tokens.truncate(self.active.len() - 1);
                 match self.messages.pop(Duration::from_millis(500)) {
                     Some(message) => {
                         events.push(message);
                         break;
                     }
+                    // Returns without events so that long running units
+                    // don't hold back the checkpoints.
+                    None if self.checkpoint_due() => break,
                     None => continue,
                 }
             }
@@ -763,7 +1023,11 @@ impl<'gctx> DrainState<'gctx> {
         // must be handled in such a way that the loop is still allowed to
         // drain event messages.
         loop {
-            if errors.count == 0 || build_runner.bcx.build_config.keep_going {
+            if let Err(e) = self.check_interrupted(build_runner) {
+                self.handle_error(&mut build_runner.bcx.gctx.shell(), &mut errors, e);
+            }
+            if !self.interrupted && (errors.count == 0 || build_runner.bcx.build_config.keep_going)
+            {
                 if let Err(e) = self.spawn_work_if_possible(build_runner, jobserver_helper, scope) {
                     self.handle_error(&mut build_runner.bcx.gctx.shell(), &mut errors, e);
                 }
@@ -780,13 +1044,23 @@ impl<'gctx> DrainState<'gctx> {
             // jobserver interface is architected we may acquire a token that we
             // don't actually use, and if this happens just relinquish it back
             // to the jobserver itself.
-            for event in self.wait_for_events() {
+            let events = self.wait_for_events();
+            // Jobs killed by Ctrl-C report errors, which must not be
+            // mistaken for compilation errors.
+            if let Err(e) = self.check_interrupted(build_runner) {
+                self.handle_error(&mut build_runner.bcx.gctx.shell(), &mut errors, e);
+            }
+            for event in events {
                 if let Err(event_err) = self.handle_event(build_runner, plan, event) {
                     self.handle_error(&mut build_runner.bcx.gctx.shell(), &mut errors, event_err);
                 }
             }
+            if self.checkpoint_due() {
+                self.checkpoint(build_runner);
+            }
         }
         self.progress.clear();
+        self.checkpoint(build_runner);
 
         let profile_name = build_runner.bcx.build_config.requested_profile;
         // NOTE: this may be a bit inaccurate, since this may not display the
@@ -810,6 +1084,56 @@ impl<'gctx> DrainState<'gctx> {
         if let Err(e) = self.timings.finished(build_runner, &errors.to_error()) {
             self.handle_error(&mut build_runner.bcx.gctx.shell(), &mut errors, e);
         }
+        if let Some((inputs, changes)) = self.build_inputs.take() {
+            let (fresh, dirty) = self.timings.unit_counts();
+            if dirty > 0 && !changes.is_empty() {
+                let mut msg = format!(
+                    "build inputs changed since the last build, \
+                     which may explain why {dirty} of {} units were rebuilt:",
+                    fresh + dirty
+                );
+                for change in changes {
+                    msg.push_str("\n  ");
+                    msg.push_str(&change);
+                }
+                let mut shell = build_runner.bcx.gctx.shell();
+                if let Err(e) = shell.warn(msg) {
+                    self.handle_error(&mut shell, &mut errors, e);
+                }
+            }
+            inputs.save(build_runner.bcx.ws);
+        }
+        if let Err(e) = self.report_dependency_warnings(build_runner.bcx.gctx) {
+            self.handle_error(&mut build_runner.bcx.gctx.shell(), &mut errors, e);
+        }
+        if build_runner.bcx.gctx.cli_unstable().build_reports
+            && !build_runner.bcx.build_config.build_plan
+        {
+            let (fresh, dirty) = self.timings.unit_counts();
+            let summary = BuildSummary {
+                id: 0,
+                finished: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+                profile: profile_name.to_string(),
+                success: errors.count == 0,
+                duration: build_runner
+                    .bcx
+                    .gctx
+                    .creation_time()
+                    .elapsed()
+                    .as_secs_f64(),
+                fresh,
+                dirty,
+                warnings: self.total_warnings,
+                errors: errors.count,
+            };
+            build_reports::save_build_summary(build_runner.bcx.ws, summary);
+        }
+        if let Some(sarif) = &self.sarif {
+            let mut shell = build_runner.bcx.gctx.shell();
+            if let Err(e) = writeln!(shell.out(), "{}", sarif.to_json()) {
+                self.handle_error(&mut shell, &mut errors, e);
+            }
+        }
         if build_runner.bcx.build_config.emit_json() {
             let mut shell = build_runner.bcx.gctx.shell();
             let msg = machine_message::BuildFinished {
@@ -825,6 +1149,8 @@ impl<'gctx> DrainState<'gctx> {
             // Any errors up to this point have already been printed via the
             // `display_error` inside `handle_error`.
             Some(anyhow::Error::new(AlreadyPrintedError::new(error)))
+        } else if self.interrupted {
+            Some(anyhow::Error::new(Cancelled).context("build was interrupted"))
         } else if self.queue.is_empty() && self.pending_queue.is_empty() {
             let profile_link = build_runner.bcx.gctx.shell().err_hyperlink(
                 "https://doc.rust-lang.org/cargo/reference/profiles.html#default-profiles",
@@ -848,6 +1174,67 @@ impl<'gctx> DrainState<'gctx> {
         }
     }
 
+    /// Stops starting new jobs once Ctrl-C was pressed with
+    /// `-Zgraceful-interrupt`, or the build was otherwise cancelled.
+    fn check_interrupted(&mut self, build_runner: &BuildRunner<'_, '_>) -> CargoResult<()> {
+        let gctx = build_runner.bcx.gctx;
+        if self.interrupted {
+            return Ok(());
+        }
+        // Ctrl-C is sent to the whole process group, so the running compilers
+        // are stopped as well, while a cancelled build lets them finish.
+        let note = if interrupt::is_interrupted() {
+            "build interrupted, not starting any new job".to_string()
+        } else if gctx.cancellation_token().is_cancelled() {
+            format!(
+                "build cancelled, waiting for {} running job(s) to finish",
+                self.active.len()
+            )
+        } else {
+            return Ok(());
+        };
+        self.interrupted = true;
+        self.progress.clear();
+        gctx.shell().note(note)
+    }
+
+    /// Removes the outputs of a unit which didn't finish, so that no later
+    /// build mistakes a partially written file for a complete one.
+    fn discard_outputs(&self, build_runner: &BuildRunner<'_, '_>, unit: &Unit) {
+        let Ok(outputs) = build_runner.outputs(unit) else {
+            return;
+        };
+        for output in outputs.iter() {
+            if output.path.exists() {
+                if let Err(e) = paths::remove_file(&output.path) {
+                    debug!("failed to remove {}: {e:?}", output.path.display());
+                }
+            }
+        }
+    }
+
+    /// Whether `--checkpoint-interval` elapsed since the last checkpoint.
+    fn checkpoint_due(&self) -> bool {
+        self.checkpoint_interval
+            .is_some_and(|interval| self.last_checkpoint.elapsed() >= interval)
+    }
+
+    /// Saves the state of the build so far: the durations of the finished
+    /// units and the fingerprint indexes of `-Zfingerprint-index`.
+    ///
+    /// Fingerprints themselves are written as soon as each unit finishes.
+    fn checkpoint(&mut self, build_runner: &BuildRunner<'_, '_>) {
+        self.last_checkpoint = Instant::now();
+        if let Some(eta) = &self.eta {
+            eta.save(build_runner.bcx.ws);
+        }
+        for index in build_runner.fingerprint_indexes.values().flatten() {
+            if let Err(e) = index.sync() {
+                debug!("failed to sync fingerprint index: {e:?}");
+            }
+        }
+    }
+
     fn handle_error(
         &self,
         shell: &mut Shell,
@@ -880,16 +1267,60 @@ impl<'gctx> DrainState<'gctx> {
         );
         self.timings.record_cpu();
 
-        let active_names = self
-            .active
-            .values()
-            .map(|u| self.name_for_progress(u))
-            .collect::<Vec<_>>();
-        let _ = self.progress.tick_now(
-            self.finished,
-            self.total_units,
-            &format!(": {}", active_names.join(", ")),
-        );
+        let msg = match self.eta.as_ref().filter(|_| self.show_eta) {
+            // Only show the unit which has been running the longest, so the
+            // line doesn't jump around.
+            Some(eta) => {
+                let current = self
+                    .active
+                    .iter()
+                    .min_by_key(|(id, _)| **id)
+                    .map(|(_, u)| self.name_for_progress(u))
+                    .unwrap_or_default();
+                match eta.estimate() {
+                    Some(remaining) => format!(": {current} (ETA {})", util::elapsed(remaining)),
+                    None => format!(": {current}"),
+                }
+            }
+            None => {
+                let active_names = self
+                    .active
+                    .values()
+                    .map(|u| self.name_for_progress(u))
+                    .collect::<Vec<_>>();
+                format!(": {}", active_names.join(", "))
+            }
+        };
+        let _ = self
+            .progress
+            .tick_now(self.finished, self.total_units, &msg);
+    }
+
+    /// Reports a finished unit, and the overall progress, when progress is
+    /// reported as JSON events.
+    fn emit_unit_finished(
+        &self,
+        gctx: &GlobalContext,
+        unit: &Unit,
+        success: bool,
+    ) -> CargoResult<()> {
+        let mut shell = gctx.shell();
+        let msg = machine_message::UnitFinished {
+            package_id: unit.pkg.package_id().to_spec(),
+            target: &unit.target,
+            mode: unit.mode,
+            success,
+        }
+        .to_json_string();
+        shell.print_json_progress(&msg)?;
+        let msg = machine_message::BuildProgress {
+            finished: self.finished,
+            total: self.total_units,
+            percentage: self.finished * 100 / self.total_units.max(1),
+        }
+        .to_json_string();
+        shell.print_json_progress(&msg)?;
+        Ok(())
     }
 
     fn name_for_progress(&self, unit: &Unit) -> String {
@@ -907,6 +1338,7 @@ impl<'gctx> DrainState<'gctx> {
                 TargetKind::ExampleBin | TargetKind::ExampleLib(_) => {
                     format!("{}(example test)", target_name)
                 }
+                TargetKind::Fuzz => format!("{}(fuzz)", target_name),
             },
             _ => match unit.target.kind() {
                 TargetKind::Lib(_) => pkg_name.to_string(),
@@ -917,6 +1349,7 @@ impl<'gctx> DrainState<'gctx> {
                 TargetKind::ExampleBin | TargetKind::ExampleLib(_) => {
                     format!("{}(example)", target_name)
                 }
+                TargetKind::Fuzz => format!("{}(fuzz)", target_name),
             },
         }
     }
@@ -942,22 +1375,48 @@ impl<'gctx> DrainState<'gctx> {
         let messages = self.messages.clone();
         let is_fresh = job.freshness().is_fresh();
         let rmeta_required = build_runner.rmeta_required(unit);
+        let summarize_warnings = build_runner.bcx.summarize_warnings(unit);
 
         let doit = move |diag_dedupe| {
-            let state = JobState::new(id, messages, diag_dedupe, rmeta_required);
+            let state = JobState::new(
+                id,
+                messages,
+                diag_dedupe,
+                rmeta_required,
+                summarize_warnings,
+            );
             state.run_to_finish(job);
         };
 
         match is_fresh {
             true => {
                 self.timings.add_fresh();
+                // The `(cached)` marker of replayed warnings is unstable.
+                if build_runner.bcx.gctx.cli_unstable().unstable_options {
+                    self.warning_count.entry(id).or_default().cached = true;
+                }
                 // Running a fresh job on the same thread is often much faster than spawning a new
                 // thread to run the job.
                 doit(Some(&self.diag_dedupe));
             }
             false => {
                 self.timings.add_dirty();
-                scope.spawn(move || doit(None));
+                if let Some(eta) = &mut self.eta {
+                    eta.unit_started(id, unit);
+                }
+                if is_link_job(unit) {
+                    self.link_jobs.insert(id);
+                }
+                let threads = self
+                    .memory_limit
+                    .as_mut()
+                    .map(|limit| limit.unit_started(id, unit));
+                scope.spawn(move || {
+                    if let Some(threads) = threads {
+                        threads.register(id);
+                    }
+                    doit(None)
+                });
             }
         }
     }
@@ -974,7 +1433,7 @@ impl<'gctx> DrainState<'gctx> {
         };
         let bcx = &mut build_runner.bcx;
         if let Some(output) = outputs.get(metadata) {
-            if !output.warnings.is_empty() {
+            if !output.warnings.is_empty() || !output.diagnostics.is_empty() {
                 if let Some(msg) = msg {
                     writeln!(bcx.gctx.shell().err(), "{}\n", msg)?;
                 }
@@ -986,6 +1445,16 @@ impl<'gctx> DrainState<'gctx> {
                     bcx.gctx.shell().warn(warning_with_package)?;
                 }
 
+                for diagnostic in output.diagnostics.iter() {
+                    let rendered = diagnostic.render(unit.pkg.package_id());
+                    let mut shell = bcx.gctx.shell();
+                    match diagnostic.level {
+                        DiagnosticLevel::Error => shell.error(rendered)?,
+                        DiagnosticLevel::Warning => shell.warn(rendered)?,
+                        DiagnosticLevel::Note => shell.note(rendered)?,
+                    }
+                }
+
                 if msg.is_some() {
                     // Output an empty line.
                     writeln!(bcx.gctx.shell().err())?;
@@ -997,6 +1466,9 @@ impl<'gctx> DrainState<'gctx> {
     }
 
     fn bump_warning_count(&mut self, id: JobId, emitted: bool, fixable: bool) {
+        if emitted {
+            self.total_warnings += 1;
+        }
         let cnts = self.warning_count.entry(id).or_default();
         cnts.total += 1;
         if !emitted {
@@ -1016,6 +1488,36 @@ impl<'gctx> DrainState<'gctx> {
         }
     }
 
+    /// Displays the warnings of the dependencies selected with
+    /// `--show-dependency-warnings`, separated from the rest of the output.
+    fn report_dependency_warnings(&self, gctx: &GlobalContext) -> CargoResult<()> {
+        let warnings = &self.dependency_warnings;
+        if warnings.counts.is_empty() {
+            return Ok(());
+        }
+        let mut shell = gctx.shell();
+        shell.note("warnings of the dependencies selected with `--show-dependency-warnings`:")?;
+        for diag in &warnings.diags {
+            shell.print_ansi_stderr(diag.as_bytes())?;
+            shell.err().write_all(b"\n")?;
+        }
+        for (pkg_id, count) in &warnings.counts {
+            let mut message = format!("`{pkg_id}` generated {} warning", count.total);
+            if count.total > 1 {
+                message.push('s');
+            }
+            match count.duplicates {
+                0 => {}
+                1 => message.push_str(" (1 duplicate)"),
+                n => {
+                    let _ = write!(message, " ({n} duplicates)");
+                }
+            }
+            shell.warn(message)?;
+        }
+        Ok(())
+    }
+
     /// Displays a final report of the warnings emitted by a particular job.
     fn report_warning_count(
         &mut self,
@@ -1046,6 +1548,9 @@ impl<'gctx> DrainState<'gctx> {
                 let _ = write!(message, " ({} duplicates)", n);
             }
         }
+        if count.cached {
+            message.push_str(" (cached)");
+        }
         // Only show the `cargo fix` message if its a local `Unit`
         if unit.is_local() {
             // Do not show this if there are any errors or no fixable warnings
@@ -1098,7 +1603,7 @@ impl<'gctx> DrainState<'gctx> {
         artifact: Artifact,
         build_runner: &mut BuildRunner<'_, '_>,
     ) -> CargoResult<()> {
-        if unit.mode.is_run_custom_build() && unit.show_warnings(build_runner.bcx.gctx) {
+        if unit.mode.is_run_custom_build() && build_runner.bcx.show_warnings(unit) {
             self.emit_warnings(None, unit, build_runner)?;
         }
         let unlocked = self.queue.finish(unit, &artifact);
@@ -1109,6 +1614,40 @@ impl<'gctx> DrainState<'gctx> {
         Ok(())
     }
 
+    /// Prints why a dirty unit is rebuilt for `--explain-rebuild`, as a
+    /// [`machine_message::RebuildReason`] with `--message-format json`.
+    ///
+    /// Unlike the `Dirty` status printed in verbose mode, this is printed for
+    /// every unit rather than the first one of each package.
+    fn explain_rebuild(
+        &self,
+        build_runner: &BuildRunner<'_, '_>,
+        unit: &Unit,
+        fresh: &Freshness,
+    ) -> CargoResult<()> {
+        let Dirty(dirty_reason) = fresh else {
+            return Ok(());
+        };
+        if dirty_reason.is_fresh_build() {
+            return Ok(());
+        }
+        let ws_root = build_runner.bcx.ws.root();
+        let mut shell = build_runner.bcx.gctx.shell();
+        if build_runner.bcx.build_config.emit_json() {
+            let msg = machine_message::RebuildReason {
+                package_id: unit.pkg.package_id().to_spec(),
+                target: &unit.target,
+                mode: unit.mode,
+                details: dirty_reason.details(ws_root),
+            }
+            .to_json_string();
+            writeln!(shell.out(), "{}", msg)?;
+        } else {
+            dirty_reason.explain_to(&mut shell, unit, ws_root)?;
+        }
+        Ok(())
+    }
+
     // This isn't super trivial because we don't want to print loads and
     // loads of information to the console, but we also want to produce a
     // faithful representation of what's happening. This is somewhat nuanced
@@ -1124,6 +1663,7 @@ impl<'gctx> DrainState<'gctx> {
         ws_root: &Path,
         unit: &Unit,
         fresh: &Freshness,
+        explain_rebuild: bool,
     ) -> CargoResult<()> {
         if (self.compiled.contains(&unit.pkg.package_id())
             && !unit.mode.is_doc()
@@ -1138,7 +1678,9 @@ impl<'gctx> DrainState<'gctx> {
             // Any dirty stage which runs at least one command gets printed as
             // being a compiled package.
             Dirty(dirty_reason) => {
-                if !dirty_reason.is_fresh_build() {
+                // With `--explain-rebuild`, the reason is printed for each unit
+                // by `explain_rebuild` instead.
+                if !dirty_reason.is_fresh_build() && !explain_rebuild {
                     gctx.shell()
                         .verbose(|shell| dirty_reason.present_to(shell, unit, ws_root))?;
                 }
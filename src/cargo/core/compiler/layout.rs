@@ -226,6 +226,10 @@ impl Layout {
     pub fn fingerprint(&self) -> &Path {
         &self.fingerprint
     }
+    /// Fetch the path of the fingerprint index (`-Zfingerprint-index`).
+    pub fn fingerprint_index(&self) -> PathBuf {
+        self.fingerprint.join("index")
+    }
     /// Fetch the build script path.
     pub fn build(&self) -> &Path {
         &self.build
@@ -26,15 +26,19 @@
 //! * [`Unit`] contains sufficient information to build something, usually
 //!   turning into a compiler invocation in a later phase.
 //!
-//! [^1]: Maybe [`-Zbuild-plan`](https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#build-plan)
-//!   was designed to serve that purpose but still [in flux](https://github.com/rust-lang/cargo/issues/7614).
+//! [^1]: [`--build-plan`](https://doc.rust-lang.org/nightly/cargo/commands/cargo-build.html#option-cargo-build---build-plan)
+//!   exposes the commands which would be run, but not the phases producing them.
 //!
 //! [`ops::cargo_compile::compile`]: crate::ops::compile
 
 pub mod artifact;
+mod artifact_cache;
 mod build_config;
 pub(crate) mod build_context;
+mod build_directives;
+mod build_meta;
 mod build_plan;
+pub mod build_reports;
 pub(crate) mod build_runner;
 mod compilation;
 mod compile_kind;
@@ -47,8 +51,12 @@ pub(crate) mod layout;
 mod links;
 mod lto;
 mod output_depinfo;
+mod remote_exec;
 pub mod rustdoc;
+mod sandbox;
+mod sarif;
 pub mod standard_lib;
+mod system_deps;
 mod timings;
 mod unit;
 pub mod unit_dependencies;
@@ -67,20 +75,27 @@ use anyhow::{bail, Context as _, Error};
 use lazycell::LazyCell;
 use tracing::{debug, trace};
 
-pub use self::build_config::{BuildConfig, CompileMode, MessageFormat, TimingOutput};
+pub use self::artifact_cache::auto_gc as artifact_cache_gc;
+pub use self::build_config::{
+    BuildConfig, CompileMode, FingerprintMode, MessageFormat, RemoteArtifactCache, SandboxMode,
+    TimingOutput,
+};
 pub use self::build_context::{
     BuildContext, FileFlavor, FileType, RustDocFingerprint, RustcTargetData, TargetInfo,
 };
 use self::build_plan::BuildPlan;
+use self::build_runner::OutputFile;
 pub use self::build_runner::{BuildRunner, Metadata};
 pub use self::compilation::{Compilation, Doctest, UnitOutput};
 pub use self::compile_kind::{CompileKind, CompileTarget};
 pub use self::crate_type::CrateType;
 pub use self::custom_build::LinkArgTarget;
-pub use self::custom_build::{BuildOutput, BuildScriptOutputs, BuildScripts};
+pub use self::custom_build::{
+    BuildOutput, BuildScriptDiagnostic, BuildScriptOutputs, BuildScripts, DiagnosticLevel,
+};
 pub(crate) use self::fingerprint::DirtyReason;
 pub use self::job_queue::Freshness;
-use self::job_queue::{Job, JobQueue, JobState, Work};
+use self::job_queue::{linker_errors, Job, JobQueue, JobState, Work};
 pub(crate) use self::layout::Layout;
 pub use self::lto::Lto;
 use self::output_depinfo::output_depinfo;
@@ -90,6 +105,7 @@ pub use crate::core::compiler::unit::{Unit, UnitInterner};
 use crate::core::manifest::TargetSourcePath;
 use crate::core::profiles::{PanicStrategy, Profile, StripInner};
 use crate::core::{Feature, PackageId, Target, Verbosity};
+use crate::util::context::OriginLintPolicy;
 use crate::util::errors::{CargoResult, VerboseError};
 use crate::util::interning::InternedString;
 use crate::util::machine_message::{self, Message};
@@ -198,15 +214,17 @@ fn compile<'gctx>(
             };
             work.then(link_targets(build_runner, unit, false)?)
         } else {
-            // We always replay the output cache,
-            // since it might contain future-incompat-report messages
+            // We always replay the output cache, since it might contain
+            // future-incompat-report messages, even if the diagnostics are
+            // hidden with `build.replay-warnings = false`.
             let work = replay_output_cache(
                 unit.pkg.package_id(),
                 PathBuf::from(unit.pkg.manifest_path()),
                 &unit.target,
                 build_runner.files().message_cache_path(unit),
                 build_runner.bcx.build_config.message_format,
-                unit.show_warnings(bcx.gctx),
+                (bcx.show_warnings(unit) || bcx.summarize_warnings(unit))
+                    && bcx.build_config.replay_warnings,
             );
             // Need to link targets on both the dirty and fresh.
             work.then(link_targets(build_runner, unit, true)?)
@@ -214,6 +232,17 @@ fn compile<'gctx>(
 
         job
     };
+    if bcx.build_config.no_build {
+        if let Freshness::Dirty(dirty_reason) = job.freshness() {
+            let mut shell = bcx.gctx.shell();
+            dirty_reason.present_to(&mut shell, unit, bcx.ws.root())?;
+            bail!(
+                "the {} of `{}` is not up to date, and `--no-build` doesn't build it",
+                unit.target.description_named(),
+                unit.pkg
+            );
+        }
+    }
     jobs.enqueue(build_runner, unit, job)?;
 
     // Be sure to compile all dependencies of this target as well.
@@ -273,6 +302,8 @@ fn rustc(
     // If we are a binary and the package also contains a library, then we
     // don't pass the `-l` flags.
     let pass_l_flag = unit.target.is_lib() || !unit.pkg.targets().iter().any(|t| t.is_lib());
+    build_directives::add_custom_flags(&mut rustc, unit);
+    build_directives::add_native_deps(build_runner, unit, &mut rustc, pass_l_flag);
 
     let dep_info_name = if build_runner.files().use_extra_filename(unit) {
         format!(
@@ -285,6 +316,16 @@ fn rustc(
     };
     let rustc_dep_info_loc = root.join(dep_info_name);
     let dep_info_loc = fingerprint::dep_info_loc(build_runner, unit);
+    // The proc-macros expanded in the unit report their inputs next to the
+    // dep-info of rustc, in the output directory it can write to.
+    let proc_macro_inputs_loc = (build_runner.bcx.gctx.cli_unstable().proc_macro_inputs
+        && build_runner
+            .unit_deps(unit)
+            .iter()
+            .any(|dep| dep.unit.target.proc_macro()))
+    .then(|| rustc_dep_info_loc.with_extension("inputs"));
+
+    let strip_commands = strip_commands(build_runner, unit, &outputs);
 
     let mut output_options = OutputOptions::new(build_runner, unit);
     let package_id = unit.pkg.package_id();
@@ -304,7 +345,21 @@ fn rustc(
     let fingerprint_dir = build_runner.files().fingerprint_dir(unit);
     let script_metadata = build_runner.find_build_script_metadata(unit);
     let is_local = unit.is_local();
+    let stamp_sources = build_runner.bcx.build_config.fingerprint == FingerprintMode::Hash;
     let artifact = unit.artifact;
+    let artifact_cache_key_file = artifact_cache::key_file(build_runner, unit);
+    let mut artifact_cache = artifact_cache::prepare(build_runner, unit)?;
+    let executor = build_runner.bcx.build_config.executor.clone();
+    // Procedural macros are expanded by `rustc`, which then runs in the
+    // sandbox, only able to write to its own directories.
+    let mut sandbox_access = sandbox_dirs(build_runner, unit).map(|(out_dir, incremental_dir)| {
+        let mut access = sandbox::Access::new(build_runner, unit);
+        access.writable.push(out_dir.clone());
+        if unit.profile.incremental {
+            access.writable.push(incremental_dir);
+        }
+        (out_dir, access)
+    });
 
     let hide_diagnostics_for_scrape_unit = build_runner.bcx.unit_can_fail_for_docscraping(unit)
         && !matches!(
@@ -328,6 +383,9 @@ fn rustc(
     if hide_diagnostics_for_scrape_unit {
         output_options.show_diagnostics = false;
     }
+    if sandbox_access.is_some() {
+        output_options.sandbox_out_dir = Some(root.clone());
+    }
 
     return Ok(Work::new(move |state| {
         // Artifacts are in a different location than typical units,
@@ -356,8 +414,35 @@ fn rustc(
                     current_id,
                 )?;
                 add_plugin_deps(&mut rustc, &script_outputs, &build_scripts, &root_output)?;
+                if let Some((_, access)) = &mut sandbox_access {
+                    // The native libraries found by the build scripts are
+                    // declared inputs of the sandboxed `rustc`.
+                    for key in build_scripts.to_link.iter() {
+                        if let Some(output) = script_outputs.get(key.1) {
+                            access.readable.extend(output.library_paths.iter().cloned());
+                        }
+                    }
+                }
             }
-            add_custom_flags(&mut rustc, &script_outputs, script_metadata)?;
+            add_custom_flags(&mut rustc, &script_outputs, script_metadata, &target)?;
+        }
+
+        let artifact_cache_key = match &artifact_cache {
+            Some(cache) => cache.key(&rustc)?,
+            None => None,
+        };
+        if let (Some(cache), Some(key)) = (&mut artifact_cache, &artifact_cache_key) {
+            if let Err(e) = cache.fetch(key) {
+                state.warning(format!(
+                    "failed to download `{name}` from the remote artifact cache: {e:#}"
+                ))?;
+            }
+            if cache.restore(key, &rustc, &outputs)? {
+                return Ok(());
+            }
+        }
+        if let Some(key_file) = &artifact_cache_key_file {
+            artifact_cache::unlink_outputs(key_file, &outputs)?;
         }
 
         for output in outputs.iter() {
@@ -385,14 +470,60 @@ fn rustc(
             }
         }
 
+        if let Some(proc_macro_inputs_loc) = &proc_macro_inputs_loc {
+            // Drop the inputs reported by the previous build.
+            if proc_macro_inputs_loc.exists() {
+                paths::remove_file(proc_macro_inputs_loc)?;
+            }
+            // A sandboxed `rustc` writes it to its scratch directory too.
+            let proc_macro_inputs_loc = match (&sandbox_access, proc_macro_inputs_loc.file_name()) {
+                (Some((out_dir, _)), Some(file_name)) => out_dir.join(file_name),
+                _ => proc_macro_inputs_loc.clone(),
+            };
+            rustc.env("CARGO_PROC_MACRO_INPUTS", proc_macro_inputs_loc);
+        }
+
+        if let (Some((out_dir, access)), false) = (&sandbox_access, build_plan) {
+            // Drop the outputs left by a failed build.
+            if out_dir.exists() {
+                paths::remove_dir_all(out_dir)?;
+            }
+            for dir in &access.writable {
+                paths::create_dir_all(dir)?;
+            }
+        }
+
         state.running(&rustc);
         let timestamp = paths::set_invocation_time(&fingerprint_dir)?;
         if build_plan {
             state.build_plan(buildkey, rustc.clone(), outputs.clone());
         } else {
+            let executor_outputs: Vec<_> = outputs
+                .iter()
+                .map(|output| output.path.clone())
+                .chain([rustc_dep_info_loc.clone()])
+                .collect();
+            let executor_cmd = match &executor {
+                Some(executor) => Some(remote_exec::command(
+                    executor,
+                    &rustc,
+                    &fingerprint_dir,
+                    package_id,
+                    &target,
+                    &pkg_root,
+                    &executor_outputs,
+                )?),
+                None => None,
+            };
+            let sandboxed_cmd = sandbox_access
+                .as_ref()
+                .map(|(_, access)| sandbox::command(&rustc, access));
             let result = exec
                 .exec(
-                    &rustc,
+                    executor_cmd
+                        .as_ref()
+                        .or(sandboxed_cmd.as_ref())
+                        .unwrap_or(&rustc),
                     package_id,
                     &target,
                     mode,
@@ -435,6 +566,24 @@ fn rustc(
 
             // Exec should never return with success *and* generate an error.
             debug_assert_eq!(output_options.errors_seen, 0);
+
+            if let Some(executor) = &executor {
+                remote_exec::check_outputs(executor, &executor_outputs).with_context(|| {
+                    let name = descriptive_pkg_name(&name, &target, &mode);
+                    format!("could not compile {name}")
+                })?;
+            }
+
+            if let Some((out_dir, _)) = &sandbox_access {
+                sandbox::move_outputs(out_dir, &root)?;
+            }
+
+            for (strip, output) in &strip_commands {
+                state.running(strip);
+                strip.exec_with_output().with_context(|| {
+                    format!("failed to strip the symbols of `{}`", output.display())
+                })?;
+            }
         }
 
         if rustc_dep_info_loc.exists() {
@@ -447,6 +596,8 @@ fn rustc(
                 &rustc,
                 // Do not track source files in the fingerprint for registry dependencies.
                 is_local,
+                stamp_sources.then_some(timestamp),
+                proc_macro_inputs_loc.as_deref(),
             )
             .with_context(|| {
                 internal(format!(
@@ -459,6 +610,14 @@ fn rustc(
             paths::set_file_time_no_err(dep_info_loc, timestamp);
         }
 
+        if let (Some(cache), Some(key)) = (&mut artifact_cache, &artifact_cache_key) {
+            if let Err(e) = cache.store(key, &outputs) {
+                state.warning(format!(
+                    "failed to store `{name}` in the artifact cache: {e:#}"
+                ))?;
+            }
+        }
+
         Ok(())
     }));
 
@@ -506,6 +665,39 @@ fn rustc(
     }
 }
 
+/// Creates the commands stripping the symbols of the outputs of `unit` after
+/// linking, except the `strip-keep-symbols` of its profile.
+///
+/// The `STRIP` environment variable selects the program, which must accept
+/// the options of GNU `strip`, like `llvm-strip` does.
+fn strip_commands(
+    build_runner: &BuildRunner<'_, '_>,
+    unit: &Unit,
+    outputs: &[OutputFile],
+) -> Vec<(ProcessBuilder, PathBuf)> {
+    if !unit.profile.strips_after_linking(&unit.target) {
+        return Vec::new();
+    }
+    let program = build_runner
+        .bcx
+        .gctx
+        .get_env_os("STRIP")
+        .unwrap_or_else(|| OsString::from("strip"));
+    outputs
+        .iter()
+        .filter(|output| output.flavor == FileFlavor::Normal)
+        .map(|output| {
+            let mut cmd = ProcessBuilder::new(&program);
+            cmd.arg("--strip-all");
+            for symbol in &unit.profile.strip_keep_symbols {
+                cmd.arg(format!("--keep-symbol={symbol}"));
+            }
+            cmd.arg(&output.path);
+            (cmd, output.path.clone())
+        })
+        .collect()
+}
+
 fn verbose_if_simple_exit_code(err: Error) -> Error {
     // If a signal on unix (`code == None`) or an abnormal termination
     // on Windows (codes like `0xC0000409`), don't hide the error details.
@@ -792,6 +984,7 @@ fn prepare_rustdoc(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> CargoResu
 /// Creates a unit of work invoking `rustdoc` for documenting the `unit`.
 fn rustdoc(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<Work> {
     let mut rustdoc = prepare_rustdoc(build_runner, unit)?;
+    build_directives::add_custom_flags(&mut rustdoc, unit);
 
     let crate_name = unit.target.crate_name();
     let doc_dir = build_runner.files().out_dir(unit);
@@ -848,6 +1041,7 @@ fn rustdoc(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<W
             &mut rustdoc,
             &build_script_outputs.lock().unwrap(),
             script_metadata,
+            &target,
         )?;
 
         // Add the output of scraped examples to the rustdoc command.
@@ -921,6 +1115,29 @@ fn append_crate_version_flag(unit: &Unit, rustdoc: &mut ProcessBuilder) {
 ///
 /// [`--cap-lints`]: https://doc.rust-lang.org/nightly/rustc/lints/levels.html#capping-lints
 fn add_cap_lints(bcx: &BuildContext<'_, '_>, unit: &Unit, cmd: &mut ProcessBuilder) {
+    // `--show-dependency-warnings` takes precedence over everything else.
+    if bcx.summarize_warnings(unit) {
+        cmd.arg("--cap-lints").arg("warn");
+        return;
+    }
+
+    // `[lints.cargo-origin]` takes precedence over the defaults below.
+    match bcx.origin_lint_policy(unit) {
+        Some(OriginLintPolicy::DenyWarnings) => {
+            cmd.arg("-D").arg("warnings");
+            return;
+        }
+        Some(OriginLintPolicy::Warn) => {
+            cmd.arg("--cap-lints").arg("warn");
+            return;
+        }
+        Some(OriginLintPolicy::Allow) => {
+            cmd.arg("--cap-lints").arg("allow");
+            return;
+        }
+        None => {}
+    }
+
     // If this is an upstream dep we don't want warnings from, turn off all
     // lints.
     if !unit.show_warnings(bcx.gctx) {
@@ -996,6 +1213,7 @@ fn build_base_args(
         ref panic,
         incremental,
         strip,
+        compress_debuginfo,
         rustflags: profile_rustflags,
         trim_paths,
         ..
@@ -1080,6 +1298,7 @@ fn build_base_args(
         trim_paths_args(cmd, build_runner, unit, &trim_paths)?;
     }
 
+    cmd.args(&fuzz_args(build_runner, unit));
     cmd.args(unit.pkg.manifest().lint_rustflags());
     cmd.args(&profile_rustflags);
     if let Some(args) = build_runner.bcx.extra_args_for(unit) {
@@ -1137,8 +1356,13 @@ fn build_base_args(
         cmd.arg("-C").arg("rpath");
     }
 
-    cmd.arg("--out-dir")
-        .arg(&build_runner.files().out_dir(unit));
+    let sandbox_dirs = sandbox_dirs(build_runner, unit);
+    match &sandbox_dirs {
+        Some((out_dir, _)) => cmd.arg("--out-dir").arg(out_dir),
+        None => cmd
+            .arg("--out-dir")
+            .arg(&build_runner.files().out_dir(unit)),
+    };
 
     fn opt(cmd: &mut ProcessBuilder, key: &str, prefix: &str, val: Option<&OsStr>) {
         if let Some(val) = val {
@@ -1163,19 +1387,34 @@ fn build_base_args(
             .map(|s| s.as_ref()),
     );
     if incremental {
-        let dir = build_runner
-            .files()
-            .layout(unit.kind)
-            .incremental()
-            .as_os_str();
+        let dir = match &sandbox_dirs {
+            Some((_, incremental_dir)) => incremental_dir.as_os_str(),
+            None => build_runner
+                .files()
+                .layout(unit.kind)
+                .incremental()
+                .as_os_str(),
+        };
         opt(cmd, "-C", "incremental=", Some(dir));
     }
 
-    let strip = strip.into_inner();
+    let strip = if unit.profile.strips_after_linking(&unit.target) {
+        // The symbols are stripped by `strip_commands`, which keeps some of
+        // them.
+        StripInner::Named(InternedString::new("debuginfo"))
+    } else {
+        strip.into_inner()
+    };
     if strip != StripInner::None {
         cmd.arg("-C").arg(format!("strip={}", strip));
     }
 
+    if let Some(compress_debuginfo) = compress_debuginfo {
+        if compress_debuginfo != "none" {
+            cmd.arg(format!("-Zdebuginfo-compression={compress_debuginfo}"));
+        }
+    }
+
     if unit.is_std {
         // -Zforce-unstable-if-unmarked prevents the accidental use of
         // unstable crates within the sysroot (such as "extern crate libc" or
@@ -1410,6 +1649,31 @@ fn lto_args(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> Vec<OsString> {
     result
 }
 
+/// Adds the flags instrumenting code for coverage-guided fuzzing with
+/// libFuzzer, the same as `cargo fuzz`, when building with the `fuzz` profile.
+///
+/// Only units built for an explicit `--target` are instrumented, so build
+/// scripts and proc-macros, which run on the host, are left alone.
+fn fuzz_args(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> Vec<&'static str> {
+    let enabled = build_runner
+        .bcx
+        .ws
+        .unstable_features()
+        .is_enabled(Feature::fuzz_targets());
+    if !enabled || unit.profile.name != "fuzz" || unit.kind.is_host() || unit.is_std {
+        return Vec::new();
+    }
+    vec![
+        "-Cpasses=sancov-module",
+        "-Cllvm-args=-sanitizer-coverage-level=4",
+        "-Cllvm-args=-sanitizer-coverage-inline-8bit-counters",
+        "-Cllvm-args=-sanitizer-coverage-pc-table",
+        "-Cllvm-args=-sanitizer-coverage-trace-compares",
+        "--cfg",
+        "fuzzing",
+    ]
+}
+
 /// Adds dependency-relevant rustc flags and environment variables
 /// to the command to execute, such as [`-L`] and [`--extern`].
 ///
@@ -1479,6 +1743,11 @@ fn build_deps_args(
     for (var, env) in artifact::get_env(build_runner, deps)? {
         cmd.env(&var, env);
     }
+    if unit.mode.is_check() {
+        for (var, env) in artifact::get_unbuilt_env(build_runner, unit)? {
+            cmd.env(&var, env);
+        }
+    }
 
     // This will only be set if we're already using a feature
     // requiring nightly rust
@@ -1491,11 +1760,12 @@ fn build_deps_args(
 
 /// Adds extra rustc flags and environment variables collected from the output
 /// of a build-script to the command to execute, include custom environment
-/// variables and `cfg`.
+/// variables and `cfg`, including those only applying to `target`.
 fn add_custom_flags(
     cmd: &mut ProcessBuilder,
     build_script_outputs: &BuildScriptOutputs,
     metadata: Option<Metadata>,
+    target: &Target,
 ) -> CargoResult<()> {
     if let Some(metadata) = metadata {
         if let Some(output) = build_script_outputs.get(metadata) {
@@ -1508,6 +1778,16 @@ fn add_custom_flags(
             for (name, value) in output.env.iter() {
                 cmd.env(name, value);
             }
+            for (lt, cfg) in &output.target_cfgs {
+                if lt.applies_to(target) {
+                    cmd.arg("--cfg").arg(cfg);
+                }
+            }
+            for (lt, (name, value)) in &output.target_env {
+                if lt.applies_to(target) {
+                    cmd.env(name, value);
+                }
+            }
         }
     }
 
@@ -1523,19 +1803,22 @@ pub fn extern_args(
     let mut result = Vec::new();
     let deps = build_runner.unit_deps(unit);
 
+    // Libraries opt into having their other dependencies marked as private by
+    // declaring at least one public dependency. Marking them requires a
+    // nightly rustc, so this is skipped with rustc versions not accepting it.
+    let marks_private_deps = unit.pkg.dependencies().iter().any(|d| d.is_public())
+        && build_runner
+            .bcx
+            .target_data
+            .info(unit.kind)
+            .support_extern_priv;
+
     // Closure to add one dependency to `result`.
     let mut link_to =
         |dep: &UnitDep, extern_crate_name: InternedString, noprelude: bool| -> CargoResult<()> {
             let mut value = OsString::new();
             let mut opts = Vec::new();
-            let is_public_dependency_enabled = unit
-                .pkg
-                .manifest()
-                .unstable_features()
-                .require(Feature::public_dependency())
-                .is_ok()
-                || build_runner.bcx.gctx.cli_unstable().public_dependency;
-            if !dep.public && unit.target.is_lib() && is_public_dependency_enabled {
+            if !dep.public && unit.target.is_lib() && marks_private_deps {
                 opts.push("priv");
                 *unstable_opts = true;
             }
@@ -1598,6 +1881,38 @@ fn envify(s: &str) -> String {
         .collect()
 }
 
+/// Returns the directories `rustc` writes to when building `unit` expands
+/// the procedural macros of sandboxed packages, which runs it in the sandbox:
+/// a scratch directory for its outputs, which Cargo moves to the output
+/// directory shared with other units, and an incremental directory of its
+/// own.
+fn sandbox_dirs(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> Option<(PathBuf, PathBuf)> {
+    let build_config = &build_runner.bcx.build_config;
+    // An executor running `rustc` elsewhere is trusted to sandbox it.
+    if build_config.executor.is_some() || build_config.build_plan {
+        return None;
+    }
+    let sandboxed = build_runner.unit_deps(unit).iter().any(|dep| {
+        dep.unit.target.proc_macro() && build_config.sandbox.applies_to(&dep.unit.pkg.name())
+    });
+    sandboxed.then(|| {
+        let out_dir = build_runner
+            .files()
+            .fingerprint_dir(unit)
+            .join("sandbox-out");
+        let incremental_dir = build_runner
+            .files()
+            .layout(unit.kind)
+            .incremental()
+            .join(format!(
+                "{}-{}",
+                unit.target.crate_name(),
+                build_runner.files().metadata(unit)
+            ));
+        (out_dir, incremental_dir)
+    })
+}
+
 /// Configuration of the display of messages emitted by the compiler,
 /// e.g. diagnostics, warnings, errors, and message caching.
 struct OutputOptions {
@@ -1620,6 +1935,13 @@ struct OutputOptions {
     warnings_seen: usize,
     /// Tracks the number of errors we've seen so far.
     errors_seen: usize,
+    /// If `true`, shorten the output of the linker in linker errors, which
+    /// is shown in full with `--verbose`.
+    truncate_linker_output: bool,
+    /// The output directory where the metadata a sandboxed `rustc` writes to
+    /// its scratch directory is moved as soon as it's produced, for
+    /// pipelining.
+    sandbox_out_dir: Option<PathBuf>,
 }
 
 impl OutputOptions {
@@ -1634,6 +1956,8 @@ impl OutputOptions {
             show_diagnostics: true,
             warnings_seen: 0,
             errors_seen: 0,
+            truncate_linker_output: build_runner.bcx.gctx.shell().verbosity() != Verbosity::Verbose,
+            sandbox_out_dir: None,
         }
     }
 }
@@ -1726,6 +2050,7 @@ fn on_stderr_line_inner(
         // it ourselves.
         MessageFormat::Human
         | MessageFormat::Short
+        | MessageFormat::Sarif
         | MessageFormat::Json {
             render_diagnostics: true,
             ..
@@ -1765,11 +2090,25 @@ fn on_stderr_line_inner(
                     // Skip this line; we'll print our own summary at the end.
                     return Ok(true);
                 }
+                if options.format == MessageFormat::Sarif && options.show_diagnostics {
+                    state.sarif_diagnostic(compiler_message.get().to_string());
+                }
                 // state.stderr will add a newline
                 if msg.rendered.ends_with('\n') {
                     msg.rendered.pop();
                 }
-                let rendered = msg.rendered;
+                let mut rendered = msg.rendered;
+                if msg.level == "error" && linker_errors::is_linker_error(&msg.message) {
+                    let symbols = linker_errors::missing_symbols(
+                        &anstream::adapter::strip_str(&rendered).to_string(),
+                    );
+                    if !symbols.is_empty() {
+                        state.missing_symbols(symbols);
+                    }
+                    if options.truncate_linker_output {
+                        rendered = linker_errors::truncate(rendered);
+                    }
+                }
                 if options.show_diagnostics {
                     let machine_applicable: bool = msg
                         .children
@@ -1827,6 +2166,9 @@ fn on_stderr_line_inner(
         trace!("found directive from rustc: `{}`", artifact.artifact);
         if artifact.artifact.ends_with(".rmeta") {
             debug!("looks like metadata finished early!");
+            if let Some(out_dir) = &options.sandbox_out_dir {
+                sandbox::move_output(Path::new(&artifact.artifact), out_dir)?;
+            }
             state.rmeta_produced();
         }
         return Ok(false);
@@ -1881,6 +2223,8 @@ fn replay_output_cache(
         show_diagnostics,
         warnings_seen: 0,
         errors_seen: 0,
+        truncate_linker_output: false,
+        sandbox_out_dir: None,
     };
     Work::new(move |state| {
         if !path.exists() {
@@ -1941,7 +2285,7 @@ pub(crate) fn apply_env_config(
         }
 
         if value.is_force() || gctx.get_env_os(key).is_none() {
-            cmd.env(key, value.resolve(gctx));
+            cmd.env(key, value.resolve(gctx)?);
         }
     }
     Ok(())
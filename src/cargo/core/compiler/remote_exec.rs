@@ -0,0 +1,133 @@
+//! Runs the `rustc` invocations of a build with the program of
+//! `build.executor`, like a remote execution service, instead of Cargo.
+//!
+//! Cargo still decides which units are dirty, and fingerprints them once
+//! they're built. For each `rustc` invocation, it writes a [`Request`] as JSON
+//! to `executor-request.json` in the fingerprint directory of the unit, and
+//! runs `<executor> <args>... <path of the request>` in place of `rustc`,
+//! with the environment `rustc` would have.
+//!
+//! The executor must run the invocation of the request, write what `rustc`
+//! writes to its stdout and stderr to its own, which includes the JSON
+//! messages Cargo reads diagnostics and artifact notifications from, and exit
+//! with the exit code of `rustc`. Once it exits successfully, every output
+//! declared by the request must exist at its path, which is where the
+//! executor transfers the artifacts built elsewhere. This is checked by
+//! [`check_outputs`].
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::bail;
+use cargo_util::{paths, ProcessBuilder};
+use serde::Serialize;
+
+use crate::core::{PackageId, Target};
+use crate::util::errors::CargoResult;
+
+/// Bumped whenever a field of [`Request`] changes in an incompatible way.
+const PROTOCOL_VERSION: u32 = 1;
+
+/// The name of the file of the request in the fingerprint directory.
+const REQUEST_FILE: &str = "executor-request.json";
+
+/// A `rustc` invocation for the executor to run.
+#[derive(Serialize)]
+struct Request<'a> {
+    version: u32,
+    package_id: PackageId,
+    target: &'a str,
+    /// The `rustc` program, which may be a wrapper.
+    program: String,
+    args: Vec<String>,
+    /// The environment variables Cargo sets or removes, the latter with
+    /// `null`, on top of its own environment.
+    env: BTreeMap<&'a str, Option<String>>,
+    cwd: Option<&'a Path>,
+    /// The files and directories the invocation reads: the root of the
+    /// package, the `--extern` crates, the `-L` directories and `OUT_DIR`.
+    inputs: Vec<PathBuf>,
+    /// The files the invocation must produce, including the dep-info file.
+    outputs: &'a [PathBuf],
+}
+
+/// Writes the request for the `rustc` invocation and returns the command
+/// running `executor` with it.
+pub fn command(
+    executor: &(PathBuf, Vec<String>),
+    rustc: &ProcessBuilder,
+    fingerprint_dir: &Path,
+    package_id: PackageId,
+    target: &Target,
+    pkg_root: &Path,
+    outputs: &[PathBuf],
+) -> CargoResult<ProcessBuilder> {
+    let args = rustc
+        .get_args()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let mut inputs = vec![pkg_root.to_path_buf()];
+    for pair in args.windows(2) {
+        let path = match pair[0].as_str() {
+            // Like `--extern foo=path/to/libfoo.rlib`.
+            "--extern" => pair[1].split_once('=').map(|(_, path)| path),
+            // Like `-L dependency=path/to/deps`.
+            "-L" => Some(pair[1].split_once('=').map_or(&*pair[1], |(_, dir)| dir)),
+            _ => None,
+        };
+        if let Some(path) = path {
+            inputs.push(PathBuf::from(path));
+        }
+    }
+    if let Some(out_dir) = rustc.get_env("OUT_DIR") {
+        inputs.push(PathBuf::from(out_dir));
+    }
+    inputs.sort();
+    inputs.dedup();
+
+    let request = Request {
+        version: PROTOCOL_VERSION,
+        package_id,
+        target: target.name(),
+        program: rustc.get_program().to_string_lossy().into_owned(),
+        args,
+        env: rustc
+            .get_envs()
+            .iter()
+            .map(|(key, value)| {
+                let value = value.as_ref().map(|v| v.to_string_lossy().into_owned());
+                (key.as_str(), value)
+            })
+            .collect(),
+        cwd: rustc.get_cwd(),
+        inputs,
+        outputs,
+    };
+    let request_path = fingerprint_dir.join(REQUEST_FILE);
+    paths::write(&request_path, serde_json::to_string_pretty(&request)?)?;
+
+    let (program, executor_args) = executor;
+    let mut cmd = rustc.clone();
+    cmd.program(program)
+        .args_replace(executor_args)
+        .arg(&request_path);
+    Ok(cmd)
+}
+
+/// Checks that `executor` produced every output of the request once it
+/// exited successfully.
+pub fn check_outputs(executor: &(PathBuf, Vec<String>), outputs: &[PathBuf]) -> CargoResult<()> {
+    let missing: Vec<_> = outputs
+        .iter()
+        .filter(|output| !output.exists())
+        .map(|output| format!("  {}", output.display()))
+        .collect();
+    if !missing.is_empty() {
+        bail!(
+            "the executor `{}` exited successfully but did not produce:\n{}",
+            executor.0.display(),
+            missing.join("\n")
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,215 @@
+//! Runs build scripts, and the `rustc` invocations expanding procedural
+//! macros, in an OS sandbox, see `build.sandbox`.
+//!
+//! Both run arbitrary code of dependencies with the permissions of the user
+//! building them, which makes them the easiest way for a malicious package to
+//! attack a machine. In the sandbox, they can't access the network, and they
+//! can only see the system directories, like the headers and libraries they
+//! may need, and the paths of their [`Access`]: the toolchain, the sources of
+//! the package, and the outputs of its dependencies. Everything else, like
+//! `~/.ssh` or the credentials of Cargo, is hidden. They can only write to the
+//! writable paths of their [`Access`], like `OUT_DIR`.
+//!
+//! On Linux, the command runs under [bubblewrap], which must be installed,
+//! in a new mount namespace only containing those paths, with a private
+//! `/tmp`, and in a new network namespace. On macOS, it runs under
+//! `sandbox-exec` with a profile denying network access, and reads and writes
+//! outside of those paths.
+//!
+//! [bubblewrap]: https://github.com/containers/bubblewrap
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use cargo_util::ProcessBuilder;
+
+use crate::core::compiler::{BuildRunner, CompileKind, Unit};
+use crate::util::CargoResult;
+
+/// The system directories a sandboxed command can read.
+#[cfg(target_os = "linux")]
+const SYSTEM_PATHS: &[&str] = &[
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/etc",
+    "/nix/store",
+];
+
+/// The system directories a sandboxed command can read.
+#[cfg(target_os = "macos")]
+const SYSTEM_PATHS: &[&str] = &[
+    "/System",
+    "/Library",
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/private/etc",
+    "/private/var/db",
+    "/Applications/Xcode.app",
+    "/opt/homebrew",
+    "/dev",
+];
+
+/// The paths a sandboxed command can access, besides the system directories.
+#[derive(Clone, Debug, Default)]
+pub struct Access {
+    /// Paths it can only read.
+    pub readable: Vec<PathBuf>,
+    /// Paths it can read and write.
+    pub writable: Vec<PathBuf>,
+}
+
+impl Access {
+    /// The paths a command building `unit` can read: the toolchain, the
+    /// sources of the package, and the outputs of its dependencies.
+    pub fn new(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> Access {
+        let bcx = build_runner.bcx;
+        let mut readable = Vec::new();
+        if let Some(dir) = bcx.rustc().path.parent() {
+            readable.push(dir.to_path_buf());
+        }
+        for kind in [CompileKind::Host, unit.kind] {
+            readable.push(bcx.target_data.info(kind).sysroot.clone());
+            readable.push(build_runner.files().layout(kind).dest().to_path_buf());
+        }
+        if let Some(linker) = build_runner.compilation.target_linker(unit.kind) {
+            if let Some(dir) = linker.parent() {
+                readable.push(dir.to_path_buf());
+            }
+        }
+        readable.push(unit.pkg.root().to_path_buf());
+        // Workspace members may refer to shared files of the workspace.
+        if unit.is_local() {
+            readable.push(bcx.ws.root().to_path_buf());
+        }
+        readable.retain(|path| path.is_absolute());
+        Access {
+            readable,
+            writable: Vec::new(),
+        }
+    }
+}
+
+/// Returns `cmd` running in the sandbox, only able to access `access`.
+pub fn command(cmd: &ProcessBuilder, access: &Access) -> ProcessBuilder {
+    let (program, mut args) = sandbox_args(access);
+    args.push(cmd.get_program().clone());
+    args.extend(cmd.get_args().cloned());
+    let mut sandboxed = cmd.clone();
+    sandboxed
+        .program(program)
+        .args_replace(&args)
+        // The arguments of the sandbox itself can't be moved to an argfile.
+        .retry_with_argfile(false);
+    sandboxed
+}
+
+/// Moves the file at `path`, written by a sandboxed command to its scratch
+/// directory, to `out_dir`.
+pub fn move_output(path: &Path, out_dir: &Path) -> CargoResult<()> {
+    let Some(file_name) = path.file_name() else {
+        return Ok(());
+    };
+    let dst = out_dir.join(file_name);
+    fs::rename(path, &dst)
+        .with_context(|| format!("failed to move `{}` to `{}`", path.display(), dst.display()))
+}
+
+/// Moves every file a sandboxed command wrote to its scratch directory
+/// `scratch` to `out_dir`.
+pub fn move_outputs(scratch: &Path, out_dir: &Path) -> CargoResult<()> {
+    let entries = fs::read_dir(scratch)
+        .with_context(|| format!("failed to read directory `{}`", scratch.display()))?;
+    for entry in entries {
+        let entry =
+            entry.with_context(|| format!("failed to read directory `{}`", scratch.display()))?;
+        move_output(&entry.path(), out_dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn sandbox_args(access: &Access) -> (&'static str, Vec<OsString>) {
+    let mut args = Vec::new();
+    for path in SYSTEM_PATHS {
+        args.extend(["--ro-bind-try", path, path].map(OsString::from));
+    }
+    // Writable paths are mounted last, in case they are in a readable one.
+    for path in &access.readable {
+        args.push("--ro-bind-try".into());
+        args.push(path.into());
+        args.push(path.into());
+    }
+    for path in &access.writable {
+        args.push("--bind".into());
+        args.push(path.into());
+        args.push(path.into());
+    }
+    args.extend(
+        [
+            "--tmpfs",
+            "/tmp",
+            "--setenv",
+            "TMPDIR",
+            "/tmp",
+            "--dev",
+            "/dev",
+            "--proc",
+            "/proc",
+            "--unshare-net",
+            "--unshare-pid",
+            "--die-with-parent",
+            "--",
+        ]
+        .map(OsString::from),
+    );
+    ("bwrap", args)
+}
+
+#[cfg(target_os = "macos")]
+fn sandbox_args(access: &Access) -> (&'static str, Vec<OsString>) {
+    let mut params = Vec::new();
+    let mut subpaths = |name: &str, paths: &[PathBuf]| {
+        let mut filters = String::new();
+        for (i, path) in paths.iter().enumerate() {
+            // The profile matches paths with their symlinks resolved, like
+            // `/private/var` for `/var`.
+            let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            filters.push_str(&format!(" (subpath (param \"{name}_{i}\"))"));
+            let mut param = OsString::from(format!("{name}_{i}="));
+            param.push(path);
+            params.push(OsString::from("-D"));
+            params.push(param);
+        }
+        filters
+    };
+    let readable = subpaths("READABLE", &access.readable);
+    let writable = subpaths("WRITABLE", &access.writable);
+    let system: String = SYSTEM_PATHS
+        .iter()
+        .map(|path| format!(" (subpath \"{path}\")"))
+        .collect();
+    let profile = format!(
+        "(version 1)\n\
+         (allow default)\n\
+         (deny network*)\n\
+         (deny file-read* file-write*)\n\
+         (allow file-read-metadata)\n\
+         (allow file-read*{system}{readable}{writable})\n\
+         (allow file-write* (literal \"/dev/null\"){writable})"
+    );
+    let mut args = vec![OsString::from("-p"), OsString::from(profile)];
+    args.extend(params);
+    ("sandbox-exec", args)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn sandbox_args(_access: &Access) -> (&'static str, Vec<OsString>) {
+    unreachable!("`build.sandbox` is rejected on this platform by `BuildConfig::new`")
+}
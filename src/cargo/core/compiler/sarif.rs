@@ -0,0 +1,192 @@
+//! Conversion of the diagnostics of a build into a [SARIF] log.
+//!
+//! With `--message-format sarif`, diagnostics are rendered on stderr like
+//! with the `human` format, and are also collected as rustc emits them. Once
+//! the build is finished, they are printed on stdout as a single SARIF 2.1.0
+//! document, which code scanning services can ingest.
+//!
+//! Artifact URIs are relative to the workspace root, which is recorded as the
+//! `%SRCROOT%` base URI, so the log doesn't depend on where the repository
+//! was checked out.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use url::Url;
+
+/// The base URI id of the workspace root.
+const SRCROOT: &str = "%SRCROOT%";
+
+/// A partial rustc diagnostic, with the fields which are reported.
+#[derive(Deserialize)]
+struct Diagnostic {
+    message: String,
+    code: Option<DiagnosticCode>,
+    level: String,
+    spans: Vec<DiagnosticSpan>,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticCode {
+    code: String,
+    explanation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticSpan {
+    file_name: String,
+    line_start: usize,
+    line_end: usize,
+    column_start: usize,
+    column_end: usize,
+    is_primary: bool,
+}
+
+/// The diagnostics collected during a build.
+pub struct SarifLog {
+    ws_root: PathBuf,
+    /// Rules by id, for the error codes and lints which were reported.
+    rules: BTreeMap<String, Value>,
+    results: Vec<Value>,
+    /// Serialized results, to skip diagnostics reported several times, such
+    /// as for a library and its unit tests.
+    seen: HashSet<String>,
+}
+
+impl SarifLog {
+    pub fn new(ws_root: &Path) -> SarifLog {
+        SarifLog {
+            ws_root: ws_root.to_path_buf(),
+            rules: BTreeMap::new(),
+            results: Vec::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Records a diagnostic, given as the JSON emitted by rustc.
+    pub fn add(&mut self, diagnostic: &str) {
+        let Ok(diagnostic) = serde_json::from_str::<Diagnostic>(diagnostic) else {
+            return;
+        };
+        let level = match diagnostic.level.as_str() {
+            "error" | "error: internal compiler error" => "error",
+            "warning" => "warning",
+            "note" | "help" => "note",
+            _ => return,
+        };
+        let locations: Vec<_> = diagnostic
+            .spans
+            .iter()
+            .filter(|span| span.is_primary)
+            .map(|span| self.location(span))
+            .collect();
+        let mut result = json!({
+            "level": level,
+            "message": { "text": diagnostic.message },
+            "locations": locations,
+        });
+        if let Some(code) = &diagnostic.code {
+            result["ruleId"] = json!(code.code);
+            self.rules
+                .entry(code.code.clone())
+                .or_insert_with(|| rule(code));
+        }
+        if self.seen.insert(result.to_string()) {
+            self.results.push(result);
+        }
+    }
+
+    /// Returns the SARIF document.
+    pub fn to_json(&self) -> String {
+        let mut run = json!({
+            "tool": {
+                "driver": {
+                    "name": "cargo",
+                    "informationUri": "https://doc.rust-lang.org/cargo/",
+                    "rules": self.rules.values().collect::<Vec<_>>(),
+                }
+            },
+            "results": self.results,
+        });
+        if let Ok(root) = Url::from_directory_path(&self.ws_root) {
+            run["originalUriBaseIds"] = json!({ SRCROOT: { "uri": root.as_str() } });
+        }
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [run],
+        })
+        .to_string()
+    }
+
+    fn location(&self, span: &DiagnosticSpan) -> Value {
+        // rustc is run from the workspace root, so relative paths already are
+        // relative to it.
+        let path = Path::new(&span.file_name);
+        let relative = if path.is_relative() {
+            Some(path)
+        } else {
+            path.strip_prefix(&self.ws_root).ok()
+        };
+        let artifact_location = match relative {
+            Some(relative) => json!({
+                "uri": relative_uri(relative),
+                "uriBaseId": SRCROOT,
+            }),
+            None => match Url::from_file_path(path) {
+                Ok(url) => json!({ "uri": url.as_str() }),
+                Err(()) => json!({ "uri": span.file_name }),
+            },
+        };
+        json!({
+            "physicalLocation": {
+                "artifactLocation": artifact_location,
+                "region": {
+                    "startLine": span.line_start,
+                    "startColumn": span.column_start,
+                    "endLine": span.line_end,
+                    "endColumn": span.column_end,
+                }
+            }
+        })
+    }
+}
+
+/// Describes the error code or lint `code`.
+fn rule(code: &DiagnosticCode) -> Value {
+    let mut rule = json!({ "id": code.code });
+    if let Some(summary) = code
+        .explanation
+        .as_deref()
+        .and_then(|explanation| explanation.lines().next())
+    {
+        rule["shortDescription"] = json!({ "text": summary });
+    }
+    if let Some(lint) = code.code.strip_prefix("clippy::") {
+        rule["helpUri"] = json!(format!(
+            "https://rust-lang.github.io/rust-clippy/master/index.html#{lint}"
+        ));
+    } else if is_error_code(&code.code) {
+        rule["helpUri"] = json!(format!(
+            "https://doc.rust-lang.org/error_codes/{}.html",
+            code.code
+        ));
+    }
+    rule
+}
+
+/// Whether `code` is a rustc error code, like `E0308`.
+fn is_error_code(code: &str) -> bool {
+    code.len() == 5 && code.starts_with('E') && code[1..].bytes().all(|b| b.is_ascii_digit())
+}
+
+fn relative_uri(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -102,6 +102,7 @@ pub fn resolve_std<'gctx>(
         /*exclude*/ &None,
         /*inheritable*/ &None,
         /*custom_metadata*/ &None,
+        /*build*/ &None,
     ));
     let virtual_manifest = crate::core::VirtualManifest::new(
         Rc::default(),
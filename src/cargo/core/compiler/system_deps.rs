@@ -0,0 +1,317 @@
+//! Probes the `[system-dependencies]` of the packages to build, see [`probe`].
+//!
+//! Each system dependency is first looked up with `pkg-config`, or the
+//! program in the `PKG_CONFIG` environment variable, under its `pkg-config`
+//! name, which defaults to the name of the entry. If it isn't found there and
+//! it has a `vcpkg` name, it is then looked up with `vcpkg list`, using the
+//! `vcpkg` executable of `VCPKG_ROOT` if set.
+//!
+//! The dependencies are probed for each platform the package is built for,
+//! reading the `pkg-config` variables of that platform, see [`pkg_config_env`].
+//! A library is only probed once per platform, even if several packages need
+//! it.
+//!
+//! The build script of the package gets the results in environment variables,
+//! see [`SystemDep::env`], and reruns when they change, as they are part of
+//! its fingerprint.
+
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+
+use cargo_util::ProcessBuilder;
+use cargo_util_schemas::manifest::TomlSystemDependency;
+
+use crate::core::compiler::{BuildContext, CompileKind};
+use crate::core::PackageId;
+use crate::util::CargoResult;
+
+/// The `pkg-config` variables read for each platform, see [`pkg_config_env`].
+const PKG_CONFIG_VARS: [&str; 3] = [
+    "PKG_CONFIG_PATH",
+    "PKG_CONFIG_LIBDIR",
+    "PKG_CONFIG_SYSROOT_DIR",
+];
+
+/// A system dependency found by [`probe`].
+#[derive(Debug, Hash)]
+pub struct SystemDep {
+    /// The name of the entry in `[system-dependencies]`.
+    pub name: String,
+    /// The version found, as reported by the probe.
+    pub version: String,
+    /// The flags to link the library.
+    pub libs: Option<String>,
+    /// The flags to compile C code using the library.
+    pub cflags: Option<String>,
+}
+
+impl SystemDep {
+    /// The environment variables describing the dependency to the build
+    /// script, `CARGO_SYSTEM_DEP_<NAME>_VERSION`, and `_LIBS` and `_CFLAGS`
+    /// when known.
+    pub fn env(&self) -> Vec<(String, &str)> {
+        let prefix = format!("CARGO_SYSTEM_DEP_{}", super::envify(&self.name));
+        let mut env = vec![(format!("{prefix}_VERSION"), self.version.as_str())];
+        if let Some(libs) = &self.libs {
+            env.push((format!("{prefix}_LIBS"), libs));
+        }
+        if let Some(cflags) = &self.cflags {
+            env.push((format!("{prefix}_CFLAGS"), cflags));
+        }
+        env
+    }
+}
+
+/// The results of the lookups of the libraries, shared by the packages
+/// needing them.
+#[derive(Default)]
+struct Lookups {
+    /// The results of [`pkg_config`], by library and platform.
+    pkg_config: HashMap<(String, CompileKind), Result<(String, String, String), String>>,
+    /// The results of [`vcpkg`], by port.
+    vcpkg: HashMap<String, Result<(String, Option<PathBuf>), String>>,
+}
+
+/// Probes the system dependencies of all the packages to build, for each
+/// platform they are built for.
+///
+/// Instead of failing on the first one, this reports all the missing
+/// dependencies at once, before anything is built.
+pub fn probe(
+    bcx: &BuildContext<'_, '_>,
+) -> CargoResult<HashMap<(PackageId, CompileKind), Vec<SystemDep>>> {
+    // The compilation of a build script is for the host, but it runs for the
+    // platform of its package.
+    let mut packages = bcx
+        .unit_graph
+        .keys()
+        .filter(|unit| !unit.target.is_custom_build() || unit.mode.is_run_custom_build())
+        .map(|unit| (&unit.pkg, unit.kind))
+        .collect::<Vec<_>>();
+    packages.sort_by_key(|(pkg, kind)| (pkg.package_id(), *kind));
+    packages.dedup_by_key(|(pkg, kind)| (pkg.package_id(), *kind));
+
+    let mut lookups = Lookups::default();
+    let mut found = HashMap::new();
+    let mut missing = Vec::new();
+    for (pkg, kind) in packages {
+        let Some(system_deps) = &pkg.manifest().resolved_toml().system_dependencies else {
+            continue;
+        };
+        let mut deps = Vec::new();
+        for (name, dep) in system_deps {
+            match probe_one(bcx, &mut lookups, kind, name, dep) {
+                Ok(dep) => deps.push(dep),
+                Err(reason) => {
+                    let req = dep
+                        .version()
+                        .map(|version| format!(" {version}"))
+                        .unwrap_or_default();
+                    let platform = match kind {
+                        CompileKind::Host => String::new(),
+                        CompileKind::Target(target) => format!(" for `{}`", target.short_name()),
+                    };
+                    missing.push(format!(
+                        "  `{name}{req}`, required by `{}`{platform}: {reason}",
+                        pkg.package_id()
+                    ));
+                }
+            }
+        }
+        found.insert((pkg.package_id(), kind), deps);
+    }
+
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "failed to find the system dependencies of the packages to build:\n{}",
+            missing.join("\n")
+        );
+    }
+    Ok(found)
+}
+
+/// Probes a system dependency for the platform of `kind`, returning why it
+/// wasn't found on failure.
+fn probe_one(
+    bcx: &BuildContext<'_, '_>,
+    lookups: &mut Lookups,
+    kind: CompileKind,
+    name: &str,
+    dep: &TomlSystemDependency,
+) -> Result<SystemDep, String> {
+    let req = match dep.version() {
+        // Already validated when parsing the manifest.
+        Some(version) => semver::VersionReq::parse(version).map_err(|e| e.to_string())?,
+        None => semver::VersionReq::STAR,
+    };
+    let matches = |version: &str| parse_version(version).is_some_and(|v| req.matches(&v));
+
+    let pkg_config_name = dep.pkg_config().unwrap_or(name);
+    let found = lookups
+        .pkg_config
+        .entry((pkg_config_name.to_string(), kind))
+        .or_insert_with(|| pkg_config(bcx, kind, pkg_config_name))
+        .clone();
+    let mut reason = match found {
+        Ok((version, libs, cflags)) if matches(&version) => {
+            return Ok(SystemDep {
+                name: name.to_string(),
+                version,
+                libs: Some(libs),
+                cflags: Some(cflags),
+            });
+        }
+        Ok((version, ..)) => format!("found version `{version}` with `pkg-config`"),
+        Err(reason) => reason,
+    };
+
+    if let Some(vcpkg_name) = dep.vcpkg() {
+        let found = lookups
+            .vcpkg
+            .entry(vcpkg_name.to_string())
+            .or_insert_with(|| vcpkg(bcx, vcpkg_name))
+            .clone();
+        match found {
+            Ok((version, installed)) if matches(&version) => {
+                let flags = |flag: &str, dir: &str| {
+                    installed
+                        .as_ref()
+                        .map(|installed| format!("{flag}{}", installed.join(dir).display()))
+                };
+                return Ok(SystemDep {
+                    name: name.to_string(),
+                    version,
+                    libs: flags("-L", "lib"),
+                    cflags: flags("-I", "include"),
+                });
+            }
+            Ok((version, _)) => {
+                reason.push_str(&format!(", found version `{version}` with `vcpkg`"));
+            }
+            Err(vcpkg_reason) => {
+                reason.push_str(", ");
+                reason.push_str(&vcpkg_reason);
+            }
+        }
+    }
+    Err(reason)
+}
+
+/// Looks up `name` with the `pkg-config` of the platform of `kind`,
+/// returning its version, libs and cflags.
+fn pkg_config(
+    bcx: &BuildContext<'_, '_>,
+    kind: CompileKind,
+    name: &str,
+) -> Result<(String, String, String), String> {
+    let program = pkg_config_env(bcx, kind, "PKG_CONFIG");
+    let vars = PKG_CONFIG_VARS
+        .iter()
+        .filter_map(|var| Some((*var, pkg_config_env(bcx, kind, var)?)))
+        .collect::<Vec<_>>();
+    let triple = bcx.target_data.short_name(&kind);
+    // Like the `pkg-config` crate, the libraries of the host aren't used for
+    // another platform, unless asked to.
+    if triple != bcx.host_triple().as_str()
+        && program.is_none()
+        && vars.is_empty()
+        && bcx.gctx.get_env_os("PKG_CONFIG_ALLOW_CROSS").as_deref() != Some(OsStr::new("1"))
+    {
+        return Err(format!(
+            "`pkg-config` isn't configured for cross-compiling to `{triple}`, \
+             set `PKG_CONFIG_SYSROOT_DIR_{triple}` or `PKG_CONFIG_ALLOW_CROSS=1`"
+        ));
+    }
+    let program = program.unwrap_or_else(|| "pkg-config".into());
+    let query = |flag: &str| {
+        let mut cmd = ProcessBuilder::new(&program);
+        for (var, value) in &vars {
+            cmd.env(var, value);
+        }
+        let output = cmd
+            .arg(flag)
+            .arg(name)
+            .output()
+            .map_err(|e| format!("failed to run `pkg-config`: {e}"))?;
+        if !output.status.success() {
+            return Err(format!("not found by `pkg-config` as `{name}`"));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    Ok((query("--modversion")?, query("--libs")?, query("--cflags")?))
+}
+
+/// Reads the `pkg-config` variable `var` for the platform of `kind`, which
+/// like with the `pkg-config` crate, is the first one set among
+/// `<var>_<target>`, `<var>_<target with underscores>`, `TARGET_<var>`, or
+/// `HOST_<var>` for the host, and `<var>`.
+fn pkg_config_env(bcx: &BuildContext<'_, '_>, kind: CompileKind, var: &str) -> Option<OsString> {
+    let triple = bcx.target_data.short_name(&kind);
+    let prefix = if triple == bcx.host_triple().as_str() {
+        "HOST"
+    } else {
+        "TARGET"
+    };
+    [
+        format!("{var}_{triple}"),
+        format!("{var}_{}", triple.replace('-', "_")),
+        format!("{prefix}_{var}"),
+        var.to_string(),
+    ]
+    .iter()
+    .find_map(|var| bcx.gctx.get_env_os(var))
+}
+
+/// Looks up the port `name` with `vcpkg list`, returning its version and the
+/// directory it is installed in, when `VCPKG_ROOT` is set.
+fn vcpkg(bcx: &BuildContext<'_, '_>, name: &str) -> Result<(String, Option<PathBuf>), String> {
+    let root = bcx.gctx.get_env_os("VCPKG_ROOT").map(PathBuf::from);
+    let program = match &root {
+        Some(root) => root.join("vcpkg"),
+        None => PathBuf::from("vcpkg"),
+    };
+    let output = ProcessBuilder::new(&program)
+        .arg("list")
+        .arg(name)
+        .output()
+        .map_err(|e| format!("failed to run `vcpkg`: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The lines look like `zlib:x64-windows  1.3.1  A compression library`.
+    let found = stdout.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        let (port, triplet) = words.next()?.split_once(':')?;
+        let version = words.next()?;
+        (port == name).then(|| (triplet.to_string(), version.to_string()))
+    });
+    let Some((triplet, version)) = found else {
+        return Err(format!("not found by `vcpkg` as `{name}`"));
+    };
+    // Drop the port version, like the `#1` of `1.3.1#1`.
+    let version = version.split('#').next().unwrap_or_default().to_string();
+    let installed = root.map(|root| root.join("installed").join(triplet));
+    Ok((version, installed))
+}
+
+/// Parses the version of a library, which may be missing components or have
+/// a suffix, like `1.2` or `3.0.2-dev`, into a SemVer version.
+fn parse_version(version: &str) -> Option<semver::Version> {
+    let mut parts = version
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next()?
+        .split('.')
+        .map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some(semver::Version::new(major, minor, patch))
+}
+
+#[test]
+fn parses_library_versions() {
+    let parse = |v| parse_version(v).map(|v| v.to_string());
+    assert_eq!(parse("1.2").as_deref(), Some("1.2.0"));
+    assert_eq!(parse("3.0.2-dev").as_deref(), Some("3.0.2"));
+    assert_eq!(parse("1.2.11.1").as_deref(), Some("1.2.11"));
+    assert_eq!(parse("unknown"), None);
+}
@@ -46,6 +46,9 @@ pub struct Timings<'gctx> {
     total_fresh: u32,
     /// Total number of dirty units.
     total_dirty: u32,
+    /// Build inputs which changed since the previous build, with
+    /// `-Zbuild-meta`.
+    input_changes: Vec<String>,
     /// Time tracking for each individual unit.
     unit_times: Vec<UnitTime>,
     /// Units that are in the process of being built.
@@ -142,6 +145,7 @@ impl<'gctx> Timings<'gctx> {
             profile,
             total_fresh: 0,
             total_dirty: 0,
+            input_changes: Vec::new(),
             unit_times: Vec::new(),
             active: HashMap::new(),
             concurrency: Vec::new(),
@@ -258,6 +262,17 @@ impl<'gctx> Timings<'gctx> {
         self.total_dirty += 1;
     }
 
+    /// Records the build inputs which changed since the previous build, to
+    /// show them in the report.
+    pub fn set_input_changes(&mut self, changes: Vec<String>) {
+        self.input_changes = changes;
+    }
+
+    /// Returns the number of fresh and dirty units encountered so far.
+    pub fn unit_counts(&self) -> (u32, u32) {
+        (self.total_fresh, self.total_dirty)
+    }
+
     /// Take a sample of CPU usage
     pub fn record_cpu(&mut self) {
         if !self.enabled {
@@ -385,6 +400,14 @@ impl<'gctx> Timings<'gctx> {
             Some(e) => format!(r#"<tr><td class="error-text">Error:</td><td>{e}</td></tr>"#),
             None => "".to_string(),
         };
+        let input_changes = if self.input_changes.is_empty() {
+            "".to_string()
+        } else {
+            format!(
+                "  <tr>\n    <td>Changed inputs:</td><td>{}</td>\n  </tr>",
+                self.input_changes.join("<br>")
+            )
+        };
         write!(
             f,
             r#"
@@ -417,6 +440,7 @@ impl<'gctx> Timings<'gctx> {
     <td>rustc:</td><td>{}</td>
   </tr>
 {}
+{}
 </table>
 "#,
             targets,
@@ -430,6 +454,7 @@ impl<'gctx> Timings<'gctx> {
             self.start_str,
             total_time,
             rustc_info,
+            input_changes,
             error_msg,
         )?;
         Ok(())
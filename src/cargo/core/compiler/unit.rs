@@ -110,9 +110,9 @@ impl UnitInner {
 }
 
 impl Unit {
-    /// Gets the unique key for [`-Zbuild-plan`].
+    /// Gets the unique key for [`--build-plan`].
     ///
-    /// [`-Zbuild-plan`]: https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#build-plan
+    /// [`--build-plan`]: https://doc.rust-lang.org/nightly/cargo/commands/cargo-build.html#option-cargo-build---build-plan
     pub fn buildkey(&self) -> String {
         format!("{}-{}", self.pkg.name(), short_hash(self))
     }
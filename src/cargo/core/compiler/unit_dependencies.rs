@@ -19,7 +19,7 @@ use std::collections::{HashMap, HashSet};
 
 use tracing::trace;
 
-use crate::core::compiler::artifact::match_artifacts_kind_with_targets;
+use crate::core::compiler::artifact::{is_unbuilt_artifact, match_artifacts_kind_with_targets};
 use crate::core::compiler::unit_graph::{UnitDep, UnitGraph};
 use crate::core::compiler::{
     CompileKind, CompileMode, CrateType, RustcTargetData, Unit, UnitInterner,
@@ -418,8 +418,9 @@ fn calc_artifact_deps<'a>(
         };
         has_artifact_lib |= artifact.is_lib();
         // Custom build scripts (build/compile) never get artifact dependencies,
-        // but the run-build-script step does (where it is handled).
-        if !unit.target.is_custom_build() {
+        // but the run-build-script step does (where it is handled). Checked
+        // units don't need them either, see `is_unbuilt_artifact`.
+        if !unit.target.is_custom_build() && !is_unbuilt_artifact(state.gctx, unit, dep) {
             debug_assert!(
                 !unit.mode.is_run_custom_build(),
                 "BUG: This should be handled in a separate branch"
@@ -1051,10 +1052,11 @@ impl<'a, 'gctx> State<'a, 'gctx> {
                         }
 
                         // If this dependency is **not** a transitive dependency, then it
-                        // only applies to test/example targets.
+                        // only applies to test/example/fuzz targets.
                         if !dep.is_transitive()
                             && !unit.target.is_test()
                             && !unit.target.is_example()
+                            && !unit.target.is_fuzz()
                             && !unit.mode.is_any_test()
                         {
                             return false;
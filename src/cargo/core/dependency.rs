@@ -43,6 +43,9 @@ struct Inner {
     public: bool,
     default_features: bool,
     features: Vec<InternedString>,
+    /// A set of features from `[features.profiles]` of the dependency, used
+    /// instead of its default features.
+    features_profile: Option<InternedString>,
     // The presence of this information turns a dependency into an artifact dependency.
     artifact: Option<Artifact>,
 
@@ -63,6 +66,8 @@ struct SerializedDependency<'a> {
     uses_default_features: bool,
     features: &'a [InternedString],
     #[serde(skip_serializing_if = "Option::is_none")]
+    features_profile: Option<InternedString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     artifact: Option<&'a Artifact>,
     target: Option<&'a Platform>,
     /// The registry URL this dependency is from.
@@ -88,6 +93,7 @@ impl ser::Serialize for Dependency {
             optional: self.is_optional(),
             uses_default_features: self.uses_default_features(),
             features: self.features(),
+            features_profile: self.features_profile(),
             target: self.platform(),
             rename: self.explicit_name_in_toml().map(|s| s.as_str()),
             registry: registry_id.as_ref().map(|sid| sid.url().as_str()),
@@ -173,6 +179,7 @@ impl Dependency {
                 optional: false,
                 public: false,
                 features: Vec::new(),
+                features_profile: None,
                 default_features: true,
                 specified_req: false,
                 platform: None,
@@ -305,6 +312,16 @@ impl Dependency {
         self
     }
 
+    /// Sets the feature profile of the dependency to use instead of its
+    /// default features.
+    pub fn set_features_profile(
+        &mut self,
+        features_profile: Option<impl Into<InternedString>>,
+    ) -> &mut Dependency {
+        Rc::make_mut(&mut self.inner).features_profile = features_profile.map(Into::into);
+        self
+    }
+
     /// Sets whether the dependency is optional.
     pub fn set_optional(&mut self, optional: bool) -> &mut Dependency {
         Rc::make_mut(&mut self.inner).optional = optional;
@@ -396,6 +413,11 @@ impl Dependency {
     pub fn features(&self) -> &[InternedString] {
         &self.inner.features
     }
+    /// Returns the name of the `[features.profiles]` entry of the dependency
+    /// to use instead of its default features.
+    pub fn features_profile(&self) -> Option<InternedString> {
+        self.inner.features_profile
+    }
 
     /// Returns `true` if the package (`sum`) can fulfill this dependency request.
     pub fn matches(&self, sum: &Summary) -> bool {
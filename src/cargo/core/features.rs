@@ -473,7 +473,7 @@ features! {
     (unstable, metabuild, "", "reference/unstable.html#metabuild"),
 
     /// Specifying the 'public' attribute on dependencies.
-    (unstable, public_dependency, "", "reference/unstable.html#public-dependency"),
+    (stable, public_dependency, "1.80", "reference/specifying-dependencies.html#public-dependencies"),
 
     /// Allow to specify profiles other than 'dev', 'release', 'test', etc.
     (stable, named_profiles, "1.57", "reference/profiles.html#custom-profiles"),
@@ -513,6 +513,45 @@ features! {
 
     /// Allow multiple packages to participate in the same API namespace
     (unstable, open_namespaces, "", "reference/unstable.html#open-namespaces"),
+
+    /// Allow `[run.args]` presets for `cargo run --preset`.
+    (unstable, run_args, "", "reference/unstable.html#run-args"),
+
+    /// Allow declaring and consuming typed `links` metadata of build scripts.
+    (unstable, links_metadata, "", "reference/unstable.html#links-metadata"),
+
+    /// Allow declaring mutually exclusive features in `[features.conflicts]`.
+    (unstable, feature_conflicts, "", "reference/unstable.html#feature-conflicts"),
+
+    /// Allow constraining transitive dependencies in `[workspace.dependency-pins]`.
+    (unstable, dependency_pins, "", "reference/unstable.html#dependency-pins"),
+
+    /// Allow forbidding packages from the dependency graph in `[workspace.bans]`.
+    (unstable, bans, "", "reference/unstable.html#bans"),
+
+    /// Allow declaring `[[fuzz]]` targets built with `cargo fuzz-build`.
+    (unstable, fuzz_targets, "", "reference/unstable.html#fuzz-targets"),
+
+    /// Allow named sets of default features in `[features.profiles]`.
+    (unstable, feature_profiles, "", "reference/unstable.html#feature-profiles"),
+
+    /// Allow `compress-debuginfo` and `strip-keep-symbols` in profiles.
+    (unstable, profile_binary_size, "", "reference/unstable.html#profile-binary-size"),
+
+    /// Allow a list of profiles in `inherits`, to stack profile fragments.
+    (unstable, profile_stack, "", "reference/unstable.html#profile-stack"),
+
+    /// Allow `package.publish-lockfile` to choose whether `Cargo.lock` is packaged.
+    (unstable, lockfile_policy, "", "reference/unstable.html#lockfile-policy"),
+
+    /// Allow workspace members checked out from pinned `git+` URLs.
+    (unstable, git_members, "", "reference/unstable.html#git-members"),
+
+    /// Allow `[build-directives]` declaring the outputs of trivial build scripts.
+    (unstable, build_directives, "", "reference/unstable.html#build-directives"),
+
+    /// Allow `[system-dependencies]` probed by Cargo before building.
+    (unstable, system_dependencies, "", "reference/unstable.html#system-dependencies"),
 }
 
 /// Status and metadata for a single unstable feature.
@@ -754,19 +793,28 @@ unstable_cli_options!(
     avoid_dev_deps: bool = ("Avoid installing dev-dependencies if possible"),
     binary_dep_depinfo: bool = ("Track changes to dependency artifacts"),
     bindeps: bool = ("Allow Cargo packages to depend on bin, cdylib, and staticlib crates, and use the artifacts built by those crates"),
+    build_meta: bool = ("Record the build inputs in `target/.build-meta` and warn about their changes"),
+    build_reports: bool = ("Record a summary of each build in `target/reports`"),
+    build_server: bool = ("Run `cargo build` and `cargo check` with the `cargo server` of the workspace"),
     #[serde(deserialize_with = "deserialize_build_std")]
     build_std: Option<Vec<String>>  = ("Enable Cargo to compile the standard library itself as part of a crate graph compilation"),
     build_std_features: Option<Vec<String>>  = ("Configure features enabled for the standard library itself when building the standard library"),
     cargo_lints: bool = ("Enable the `[lints.cargo]` table"),
+    cargo_origin_lints: bool = ("Enable the `[lints.cargo-origin]` table in .cargo/config.toml file"),
+    check_skip_artifacts: bool = ("Don't build the bin, cdylib, and staticlib artifact dependencies of checked targets"),
     codegen_backend: bool = ("Enable the `codegen-backend` option in profiles in .cargo/config.toml file"),
     config_include: bool = ("Enable the `include` key in config files"),
+    config_placeholders: bool = ("Expand `{workspace-root}` and `{target-dir}` in config values"),
     direct_minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum (direct dependencies only)"),
     doctest_xcompile: bool = ("Compile and run doctests for non-host target using runner config"),
     dual_proc_macros: bool = ("Build proc-macros for both the host and the target"),
     features: Option<Vec<String>>,
+    fingerprint_index: bool = ("Keep fingerprint hashes in a single index file per profile to speed up no-op builds"),
     gc: bool = ("Track cache usage and \"garbage collect\" unused files"),
     git: Option<GitFeatures> = ("Enable support for shallow git fetch operations"),
+    git_credentials: bool = ("Enable `net.git-credentials` to fetch git repositories with credential providers"),
     gitoxide: Option<GitoxideFeatures> = ("Use gitoxide for the given git interactions, or all of them if no argument is given"),
+    graceful_interrupt: bool = ("Save the build state and discard partial outputs on Ctrl-C"),
     host_config: bool = ("Enable the `[host]` section in the .cargo/config.toml file"),
     minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum"),
     msrv_policy: bool = ("Enable rust-version aware policy within cargo"),
@@ -774,17 +822,24 @@ unstable_cli_options!(
     next_lockfile_bump: bool,
     no_index_update: bool = ("Do not update the registry index even if the cache is outdated"),
     panic_abort_tests: bool = ("Enable support to run tests with -Cpanic=abort"),
+    patch_sets: bool = ("Enable named `[patch-sets]` in .cargo/config.toml files"),
+    proc_macro_inputs: bool = ("Let proc-macros report the files and environment variables they read"),
+    profile_binary_size: bool = ("Enable the `compress-debuginfo` and `strip-keep-symbols` options in profiles"),
     profile_rustflags: bool = ("Enable the `rustflags` option in profiles in .cargo/config.toml file"),
-    public_dependency: bool = ("Respect a dependency's `public` field in Cargo.toml to control public/private dependencies"),
+    profile_stack: bool = ("Enable a list of profiles in `inherits` in .cargo/config.toml file"),
     publish_timeout: bool = ("Enable the `publish.timeout` key in .cargo/config.toml file"),
+    resolve_cache: bool = ("Reuse the resolve of the workspace when its inputs haven't changed"),
     rustdoc_map: bool = ("Allow passing external documentation mappings to rustdoc"),
     rustdoc_scrape_examples: bool = ("Allows Rustdoc to scrape code examples from reverse-dependencies"),
     script: bool = ("Enable support for single-file, `.rs` packages"),
     separate_nightlies: bool,
     skip_rustdoc_fingerprint: bool,
     target_applies_to_host: bool = ("Enable the `target-applies-to-host` key in the .cargo/config.toml file"),
+    test_timeout: bool = ("Enable the `test.timeout` key in .cargo/config.toml file"),
     trim_paths: bool = ("Enable the `trim-paths` option in profiles"),
     unstable_options: bool = ("Allow the usage of unstable options"),
+    vet: bool = ("Enable the `vet` config to require audits of new dependency versions"),
+    yanked_policy: bool = ("Enable the `resolver.yanked` config to treat locked yanked versions"),
 );
 
 const STABILIZED_COMPILE_PROGRESS: &str = "The progress bar is now always \
@@ -862,6 +917,9 @@ const STABILIZED_LINTS: &str = "The `[lints]` table is now always available.";
 const STABILIZED_CHECK_CFG: &str =
     "Compile-time checking of conditional (a.k.a. `-Zcheck-cfg`) is now always enabled.";
 
+const STABILIZED_PUBLIC_DEPENDENCY: &str =
+    "The `public` field of dependencies is now always respected.";
+
 fn deserialize_build_std<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -881,6 +939,8 @@ pub struct GitFeatures {
     pub shallow_index: bool,
     /// When cloning git dependencies, perform a shallow clone and maintain shallowness on subsequent fetches.
     pub shallow_deps: bool,
+    /// When checking out git dependencies, only materialize the packages in use.
+    pub sparse_checkout: bool,
 }
 
 impl GitFeatures {
@@ -888,6 +948,7 @@ impl GitFeatures {
         GitFeatures {
             shallow_index: true,
             shallow_deps: true,
+            sparse_checkout: true,
         }
     }
 }
@@ -897,15 +958,18 @@ fn parse_git(it: impl Iterator<Item = impl AsRef<str>>) -> CargoResult<Option<Gi
     let GitFeatures {
         shallow_index,
         shallow_deps,
+        sparse_checkout,
     } = &mut out;
 
     for e in it {
         match e.as_ref() {
             "shallow-index" => *shallow_index = true,
             "shallow-deps" => *shallow_deps = true,
+            "sparse-checkout" => *sparse_checkout = true,
             _ => {
                 bail!(
-                    "unstable 'git' only takes 'shallow-index' and 'shallow-deps' as valid inputs"
+                    "unstable 'git' only takes 'shallow-index', 'shallow-deps' and \
+                     'sparse-checkout' as valid inputs"
                 )
             }
         }
@@ -1117,6 +1181,7 @@ impl CliUnstable {
             "lints" => stabilized_warn(k, "1.74", STABILIZED_LINTS),
             "registry-auth" => stabilized_warn(k, "1.74", STABILIZED_REGISTRY_AUTH),
             "check-cfg" => stabilized_warn(k, "1.80", STABILIZED_CHECK_CFG),
+            "public-dependency" => stabilized_warn(k, "1.80", STABILIZED_PUBLIC_DEPENDENCY),
 
             // Unstable features
             // Sorted alphabetically:
@@ -1125,16 +1190,23 @@ impl CliUnstable {
             "avoid-dev-deps" => self.avoid_dev_deps = parse_empty(k, v)?,
             "binary-dep-depinfo" => self.binary_dep_depinfo = parse_empty(k, v)?,
             "bindeps" => self.bindeps = parse_empty(k, v)?,
+            "build-meta" => self.build_meta = parse_empty(k, v)?,
+            "build-reports" => self.build_reports = parse_empty(k, v)?,
+            "build-server" => self.build_server = parse_empty(k, v)?,
             "build-std" => {
                 self.build_std = Some(crate::core::compiler::standard_lib::parse_unstable_flag(v))
             }
             "build-std-features" => self.build_std_features = Some(parse_features(v)),
             "cargo-lints" => self.cargo_lints = parse_empty(k, v)?,
+            "cargo-origin-lints" => self.cargo_origin_lints = parse_empty(k, v)?,
+            "check-skip-artifacts" => self.check_skip_artifacts = parse_empty(k, v)?,
             "codegen-backend" => self.codegen_backend = parse_empty(k, v)?,
             "config-include" => self.config_include = parse_empty(k, v)?,
+            "config-placeholders" => self.config_placeholders = parse_empty(k, v)?,
             "direct-minimal-versions" => self.direct_minimal_versions = parse_empty(k, v)?,
             "doctest-xcompile" => self.doctest_xcompile = parse_empty(k, v)?,
             "dual-proc-macros" => self.dual_proc_macros = parse_empty(k, v)?,
+            "fingerprint-index" => self.fingerprint_index = parse_empty(k, v)?,
             "gc" => self.gc = parse_empty(k, v)?,
             "git" => {
                 self.git = v.map_or_else(
@@ -1142,12 +1214,14 @@ impl CliUnstable {
                     |v| parse_git(v.split(',')),
                 )?
             }
+            "git-credentials" => self.git_credentials = parse_empty(k, v)?,
             "gitoxide" => {
                 self.gitoxide = v.map_or_else(
                     || Ok(Some(GitoxideFeatures::all())),
                     |v| parse_gitoxide(v.split(',')),
                 )?
             }
+            "graceful-interrupt" => self.graceful_interrupt = parse_empty(k, v)?,
             "host-config" => self.host_config = parse_empty(k, v)?,
             "next-lockfile-bump" => self.next_lockfile_bump = parse_empty(k, v)?,
             "minimal-versions" => self.minimal_versions = parse_empty(k, v)?,
@@ -1156,17 +1230,24 @@ impl CliUnstable {
             "mtime-on-use" => self.mtime_on_use = parse_empty(k, v)?,
             "no-index-update" => self.no_index_update = parse_empty(k, v)?,
             "panic-abort-tests" => self.panic_abort_tests = parse_empty(k, v)?,
-            "public-dependency" => self.public_dependency = parse_empty(k, v)?,
+            "patch-sets" => self.patch_sets = parse_empty(k, v)?,
+            "proc-macro-inputs" => self.proc_macro_inputs = parse_empty(k, v)?,
+            "profile-binary-size" => self.profile_binary_size = parse_empty(k, v)?,
             "profile-rustflags" => self.profile_rustflags = parse_empty(k, v)?,
+            "profile-stack" => self.profile_stack = parse_empty(k, v)?,
             "trim-paths" => self.trim_paths = parse_empty(k, v)?,
             "publish-timeout" => self.publish_timeout = parse_empty(k, v)?,
+            "resolve-cache" => self.resolve_cache = parse_empty(k, v)?,
             "rustdoc-map" => self.rustdoc_map = parse_empty(k, v)?,
             "rustdoc-scrape-examples" => self.rustdoc_scrape_examples = parse_empty(k, v)?,
             "separate-nightlies" => self.separate_nightlies = parse_empty(k, v)?,
             "skip-rustdoc-fingerprint" => self.skip_rustdoc_fingerprint = parse_empty(k, v)?,
             "script" => self.script = parse_empty(k, v)?,
             "target-applies-to-host" => self.target_applies_to_host = parse_empty(k, v)?,
+            "test-timeout" => self.test_timeout = parse_empty(k, v)?,
             "unstable-options" => self.unstable_options = parse_empty(k, v)?,
+            "vet" => self.vet = parse_empty(k, v)?,
+            "yanked-policy" => self.yanked_policy = parse_empty(k, v)?,
             _ => bail!("\
             unknown `-Z` flag specified: {k}\n\n\
             For available unstable features, see https://doc.rust-lang.org/nightly/cargo/reference/unstable.html\n\
@@ -1178,35 +1259,34 @@ impl CliUnstable {
 
     /// Generates an error if `-Z unstable-options` was not used for a new,
     /// unstable command-line flag.
-    pub fn fail_if_stable_opt(&self, flag: &str, issue: u32) -> CargoResult<()> {
+    pub fn fail_if_stable_opt(&self, flag: &str, issue: impl Into<Option<u32>>) -> CargoResult<()> {
         self.fail_if_stable_opt_custom_z(flag, issue, "unstable-options", self.unstable_options)
     }
 
     pub fn fail_if_stable_opt_custom_z(
         &self,
         flag: &str,
-        issue: u32,
+        issue: impl Into<Option<u32>>,
         z_name: &str,
         enabled: bool,
     ) -> CargoResult<()> {
         if !enabled {
-            let see = format!(
-                "See https://github.com/rust-lang/cargo/issues/{issue} for more \
-                 information about the `{flag}` flag."
-            );
+            let see = match issue.into() {
+                Some(issue) => format!(
+                    "\nSee https://github.com/rust-lang/cargo/issues/{issue} for more \
+                     information about the `{flag}` flag."
+                ),
+                None => String::new(),
+            };
             // NOTE: a `config` isn't available here, check the channel directly
             let channel = channel();
             if channel == "nightly" || channel == "dev" {
-                bail!(
-                    "the `{flag}` flag is unstable, pass `-Z {z_name}` to enable it\n\
-                     {see}"
-                );
+                bail!("the `{flag}` flag is unstable, pass `-Z {z_name}` to enable it{see}");
             } else {
                 bail!(
                     "the `{flag}` flag is unstable, and only available on the nightly channel \
                      of Cargo, but this is the `{channel}` channel\n\
-                     {SEE_CHANNELS}\n\
-                     {see}"
+                     {SEE_CHANNELS}{see}"
                 );
             }
         }
@@ -1219,30 +1299,31 @@ impl CliUnstable {
         &self,
         gctx: &GlobalContext,
         command: &str,
-        issue: u32,
+        issue: impl Into<Option<u32>>,
         z_name: &str,
         enabled: bool,
     ) -> CargoResult<()> {
         if enabled {
             return Ok(());
         }
-        let see = format!(
-            "See https://github.com/rust-lang/cargo/issues/{} for more \
-            information about the `cargo {}` command.",
-            issue, command
-        );
+        let see = match issue.into() {
+            Some(issue) => format!(
+                "\nSee https://github.com/rust-lang/cargo/issues/{} for more \
+                information about the `cargo {}` command.",
+                issue, command
+            ),
+            None => String::new(),
+        };
         if gctx.nightly_features_allowed {
             bail!(
                 "the `cargo {command}` command is unstable, pass `-Z {z_name}` \
-                 to enable it\n\
-                 {see}",
+                 to enable it{see}",
             );
         } else {
             bail!(
                 "the `cargo {}` command is unstable, and only available on the \
                  nightly channel of Cargo, but this is the `{}` channel\n\
-                 {}\n\
-                 {}",
+                 {}{}",
                 command,
                 channel(),
                 SEE_CHANNELS,
@@ -546,6 +546,54 @@ impl GlobalCacheTracker {
             .with_context(|| "failed to clean entries from the global cache")
     }
 
+    /// Deletes the `.crate` file and the extracted source of a registry
+    /// package from every registry, returning the deleted paths.
+    ///
+    /// `package_dir` is the name of the package's source directory, like
+    /// `foo-1.2.3`. Entries last used after `since` are kept, since another
+    /// project may depend on them, and so are entries missing from the
+    /// database, whose last use is unknown.
+    pub fn clean_registry_package(
+        &mut self,
+        clean_ctx: &mut CleanContext<'_>,
+        package_dir: &str,
+        since: &SystemTime,
+    ) -> CargoResult<Vec<PathBuf>> {
+        let gctx = clean_ctx.gctx;
+        let since = to_timestamp(since);
+        let tx = self.conn.transaction()?;
+        let mut delete_paths = Vec::new();
+        for (table_name, name, base_path) in [
+            (
+                REGISTRY_CRATE_TABLE,
+                format!("{package_dir}.crate"),
+                gctx.registry_cache_path(),
+            ),
+            (
+                REGISTRY_SRC_TABLE,
+                package_dir.to_string(),
+                gctx.registry_source_path(),
+            ),
+        ] {
+            debug!(target: "gc", "cleaning {name} from {table_name} unused since {since:?}");
+            let mut stmt = tx.prepare_cached(&format!(
+                "DELETE FROM {table_name} WHERE name = ?1 AND timestamp <= ?2
+                    RETURNING registry_id"
+            ))?;
+            let ids = stmt
+                .query_map(params![name, since], |row| row.get(0))?
+                .collect::<Result<Vec<i64>, _>>()?;
+            let id_map = Self::get_id_map(&tx, REGISTRY_INDEX_TABLE, &ids)?;
+            let base_path = base_path.into_path_unlocked();
+            for id in ids {
+                delete_paths.push(base_path.join(&id_map[&id]).join(&name));
+            }
+        }
+        clean_ctx.remove_paths(&delete_paths)?;
+        tx.commit()?;
+        Ok(delete_paths)
+    }
+
     #[tracing::instrument(skip_all)]
     fn clean_inner(
         &mut self,
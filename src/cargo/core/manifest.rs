@@ -155,6 +155,7 @@ pub enum TargetKind {
     ExampleLib(Vec<CrateType>),
     ExampleBin,
     CustomBuild,
+    Fuzz,
 }
 
 impl ser::Serialize for TargetKind {
@@ -170,6 +171,7 @@ impl ser::Serialize for TargetKind {
             Test => ["test"].serialize(s),
             CustomBuild => ["custom-build"].serialize(s),
             Bench => ["bench"].serialize(s),
+            Fuzz => ["fuzz"].serialize(s),
         }
     }
 }
@@ -184,6 +186,7 @@ impl fmt::Debug for TargetKind {
             Test => "test".fmt(f),
             CustomBuild => "custom-build".fmt(f),
             Bench => "bench".fmt(f),
+            Fuzz => "fuzz".fmt(f),
         }
     }
 }
@@ -197,6 +200,7 @@ impl TargetKind {
             TargetKind::ExampleBin | TargetKind::ExampleLib(..) => "example",
             TargetKind::Bench => "bench",
             TargetKind::CustomBuild => "build-script",
+            TargetKind::Fuzz => "fuzz",
         }
     }
 
@@ -222,6 +226,7 @@ impl TargetKind {
             | TargetKind::Bench
             | TargetKind::Test
             | TargetKind::ExampleBin
+            | TargetKind::Fuzz
             | TargetKind::Bin => vec![CrateType::Bin],
         }
     }
@@ -492,6 +497,43 @@ impl Manifest {
     pub fn document(&self) -> &toml_edit::ImDocument<String> {
         &self.document
     }
+    /// The documentation of the features, from the `##` comments right
+    /// before them in the `[features]` table
+    pub fn feature_docs(&self) -> BTreeMap<String, String> {
+        let Some(features) = self
+            .document
+            .as_item()
+            .as_table_like()
+            .and_then(|t| t.get("features"))
+            .and_then(|f| f.as_table_like())
+        else {
+            return BTreeMap::new();
+        };
+        let mut docs = BTreeMap::new();
+        for (name, _) in features.iter() {
+            let Some((key, _)) = features.get_key_value(name) else {
+                continue;
+            };
+            let prefix = key
+                .leaf_decor()
+                .prefix()
+                .and_then(|p| p.span())
+                .map_or("", |span| &self.contents[span]);
+            let mut lines = prefix
+                .trim_end()
+                .lines()
+                .rev()
+                .map(str::trim)
+                .map_while(|line| line.strip_prefix("##"))
+                .map(|line| line.strip_prefix(' ').unwrap_or(line))
+                .collect::<Vec<_>>();
+            if !lines.is_empty() {
+                lines.reverse();
+                docs.insert(name.to_string(), lines.join("\n"));
+            }
+        }
+        docs
+    }
     /// The [`TomlManifest`] as parsed from [`Manifest::document`]
     pub fn original_toml(&self) -> &TomlManifest {
         &self.original_toml
@@ -875,6 +917,26 @@ impl Target {
         target
     }
 
+    pub fn fuzz_target(
+        name: &str,
+        src_path: PathBuf,
+        required_features: Option<Vec<String>>,
+        edition: Edition,
+    ) -> Target {
+        let mut target = Target::with_path(src_path, edition);
+        target
+            .set_kind(TargetKind::Fuzz)
+            .set_name(name)
+            .set_required_features(required_features)
+            .set_tested(false)
+            .set_benched(false)
+            .set_doc(false)
+            .set_doctest(false)
+            .set_harness(false)
+            .set_doc_scrape_examples(RustdocScrapeExamples::Disabled);
+        target
+    }
+
     pub fn name(&self) -> &str {
         &self.inner.name
     }
@@ -1002,6 +1064,9 @@ impl Target {
     pub fn is_custom_build(&self) -> bool {
         *self.kind() == TargetKind::CustomBuild
     }
+    pub fn is_fuzz(&self) -> bool {
+        *self.kind() == TargetKind::Fuzz
+    }
 
     /// Returns the arguments suitable for `--crate-type` to pass to rustc.
     pub fn rustc_crate_types(&self) -> Vec<CrateType> {
@@ -1080,6 +1145,7 @@ impl Target {
                 format!("example \"{}\"", self.name())
             }
             TargetKind::CustomBuild => "build script".to_string(),
+            TargetKind::Fuzz => format!("fuzz \"{}\"", self.name()),
         }
     }
 }
@@ -1095,6 +1161,7 @@ impl fmt::Display for Target {
                 write!(f, "Target(example: {})", self.name())
             }
             TargetKind::CustomBuild => write!(f, "Target(script)"),
+            TargetKind::Fuzz => write!(f, "Target(fuzz: {})", self.name()),
         }
     }
 }
@@ -7,9 +7,9 @@ pub use self::package_id::PackageId;
 pub use self::package_id_spec::PackageIdSpecQuery;
 pub use self::registry::Registry;
 pub use self::resolver::{Resolve, ResolveVersion};
-pub use self::shell::{Shell, Verbosity};
+pub use self::shell::{JsonProgress, Shell, UiMode, Verbosity};
 pub use self::source_id::SourceId;
-pub use self::summary::{FeatureMap, FeatureValue, Summary};
+pub use self::summary::{FeatureMap, FeatureProfiles, FeatureValue, Summary};
 pub use self::workspace::{
     find_workspace_root, resolve_relative_path, MaybePackage, Workspace, WorkspaceConfig,
     WorkspaceRootConfig,
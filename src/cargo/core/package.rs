@@ -28,6 +28,8 @@ use crate::sources::source::{MaybePackage, SourceMap};
 use crate::util::cache_lock::{CacheLock, CacheLockMode};
 use crate::util::errors::{CargoResult, HttpNotSuccessful};
 use crate::util::interning::InternedString;
+use crate::util::machine_message::{self, Message as _};
+use crate::util::network::http::configure_registry_http_handle;
 use crate::util::network::http::http_handle_and_timeout;
 use crate::util::network::http::HttpTimeout;
 use crate::util::network::retry::{Retry, RetryResult};
@@ -184,8 +186,21 @@ impl Package {
     }
 
     /// Returns if package should include `Cargo.lock`.
+    ///
+    /// This is `package.publish-lockfile` if it's set, otherwise whether the
+    /// package has a binary or an example.
     pub fn include_lockfile(&self) -> bool {
-        self.targets().iter().any(|t| t.is_example() || t.is_bin())
+        self.publish_lockfile()
+            .unwrap_or_else(|| self.targets().iter().any(|t| t.is_example() || t.is_bin()))
+    }
+
+    /// Gets the `package.publish-lockfile` field of the manifest.
+    pub fn publish_lockfile(&self) -> Option<bool> {
+        self.manifest()
+            .resolved_toml()
+            .package()
+            .and_then(|p| p.resolved_publish_lockfile().ok().flatten())
+            .copied()
     }
 
     pub fn serialized(&self) -> SerializedPackage {
@@ -705,6 +720,7 @@ impl<'a, 'gctx> Downloads<'a, 'gctx> {
         assert!(self.pending_ids.insert(id));
 
         let (mut handle, _timeout) = http_handle_and_timeout(self.set.gctx)?;
+        configure_registry_http_handle(self.set.gctx, id.source_id(), &mut handle)?;
         handle.get(true)?;
         handle.url(&url)?;
         handle.follow_location(true)?; // follow redirects
@@ -762,6 +778,14 @@ impl<'a, 'gctx> Downloads<'a, 'gctx> {
             self.set.gctx.shell().status("Downloading", "crates ...")?;
         }
 
+        if self.set.gctx.shell().json_progress() {
+            let msg = machine_message::DownloadStarted {
+                package_id: id.to_spec(),
+            }
+            .to_json_string();
+            self.set.gctx.shell().print_json_progress(&msg)?;
+        }
+
         let dl = Download {
             token,
             data: RefCell::new(Vec::new()),
@@ -871,6 +895,14 @@ impl<'a, 'gctx> Downloads<'a, 'gctx> {
         // progress bar is enabled this provides a good log of what's happening.
         self.progress.borrow_mut().as_mut().unwrap().clear();
         self.set.gctx.shell().status("Downloaded", &dl.descriptor)?;
+        if self.set.gctx.shell().json_progress() {
+            let msg = machine_message::DownloadFinished {
+                package_id: dl.id.to_spec(),
+                bytes: data.len(),
+            }
+            .to_json_string();
+            self.set.gctx.shell().print_json_progress(&msg)?;
+        }
 
         self.downloads_finished += 1;
         self.downloaded_bytes += dl.total.get();
@@ -946,6 +978,7 @@ impl<'a, 'gctx> Downloads<'a, 'gctx> {
         // actually block waiting for I/O to happen, which we achieve with the
         // `wait` method on `multi`.
         loop {
+            self.set.gctx.cancellation_token().check()?;
             self.add_sleepers()?;
             let n = tls::set(self, || {
                 self.set
@@ -2,8 +2,12 @@ use std::collections::HashMap;
 
 use anyhow::{bail, Context as _};
 
+use crate::core::GitReference;
 use crate::core::PackageId;
 use crate::core::PackageIdSpec;
+use crate::core::SourceId;
+use crate::core::SourceKind;
+use crate::sources::git::looks_like_commit_hash;
 use crate::util::edit_distance;
 use crate::util::errors::CargoResult;
 
@@ -37,7 +41,14 @@ impl PackageIdSpecQuery for PackageIdSpec {
     }
 
     fn matches(&self, package_id: PackageId) -> bool {
-        if self.name() != package_id.name().as_str() {
+        if self.is_glob() {
+            let Ok(pattern) = glob::Pattern::new(self.name()) else {
+                return false;
+            };
+            if !pattern.matches(package_id.name().as_str()) {
+                return false;
+            }
+        } else if self.name() != package_id.name().as_str() {
             return false;
         }
 
@@ -54,7 +65,7 @@ impl PackageIdSpecQuery for PackageIdSpec {
         }
 
         if let Some(k) = &self.kind() {
-            if *k != package_id.source_id().kind() {
+            if !kind_matches(k, package_id.source_id()) {
                 return false;
             }
         }
@@ -143,11 +154,31 @@ impl PackageIdSpecQuery for PackageIdSpec {
     }
 }
 
+/// Checks whether the source kind of a spec matches `source_id`.
+///
+/// Besides an exact match, a `?rev=` qualifier holding a commit hash also
+/// matches a git source locked to that commit, so two lockfile entries of the
+/// same git package can be told apart however their dependencies name them.
+fn kind_matches(kind: &SourceKind, source_id: SourceId) -> bool {
+    if *kind == source_id.kind() {
+        return true;
+    }
+    match (kind, source_id.kind(), source_id.precise_git_fragment()) {
+        (SourceKind::Git(GitReference::Rev(rev)), SourceKind::Git(_), Some(locked)) => {
+            looks_like_commit_hash(rev)
+                && locked
+                    .get(..rev.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(rev))
+        }
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::PackageIdSpec;
     use super::PackageIdSpecQuery;
-    use crate::core::{PackageId, SourceId};
+    use crate::core::{GitReference, PackageId, SourceId};
     use url::Url;
 
     #[test]
@@ -212,4 +243,68 @@ mod tests {
             .unwrap()
             .matches(pre));
     }
+
+    #[test]
+    fn matching_git() {
+        let url = Url::parse("https://example.com/foo.git").unwrap();
+        let locked = "f0a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3";
+
+        let sid = SourceId::for_git(&url, GitReference::Branch("dev".to_owned()))
+            .unwrap()
+            .with_git_precise(Some(locked.to_owned()));
+        let foo = PackageId::try_new("foo", "1.2.3", sid).unwrap();
+        assert!(
+            PackageIdSpec::parse("git+https://example.com/foo.git?branch=dev#foo@1.2.3")
+                .unwrap()
+                .matches(foo)
+        );
+        assert!(
+            !PackageIdSpec::parse("git+https://example.com/foo.git?branch=main#foo")
+                .unwrap()
+                .matches(foo)
+        );
+        assert!(
+            !PackageIdSpec::parse("git+https://example.com/foo.git?tag=dev#foo")
+                .unwrap()
+                .matches(foo)
+        );
+        assert!(
+            PackageIdSpec::parse(&format!("git+https://example.com/foo.git?rev={locked}#foo"))
+                .unwrap()
+                .matches(foo)
+        );
+        assert!(
+            PackageIdSpec::parse("git+https://example.com/foo.git?rev=F0A1B2C3D#foo")
+                .unwrap()
+                .matches(foo)
+        );
+        assert!(
+            !PackageIdSpec::parse("git+https://example.com/foo.git?rev=f0a1b2#foo")
+                .unwrap()
+                .matches(foo)
+        );
+        assert!(
+            !PackageIdSpec::parse("git+https://example.com/foo.git?rev=0a1b2c3d#foo")
+                .unwrap()
+                .matches(foo)
+        );
+
+        let sid = SourceId::for_git(&url, GitReference::Tag("v1.2.3".to_owned())).unwrap();
+        let bar = PackageId::try_new("bar", "1.2.3", sid).unwrap();
+        assert!(
+            PackageIdSpec::parse("git+https://example.com/foo.git?tag=v1.2.3#bar")
+                .unwrap()
+                .matches(bar)
+        );
+        assert!(
+            !PackageIdSpec::parse("git+https://example.com/foo.git?tag=v1.2.4#bar")
+                .unwrap()
+                .matches(bar)
+        );
+        assert!(
+            !PackageIdSpec::parse("git+https://example.com/foo.git?rev=f0a1b2c3d#bar")
+                .unwrap()
+                .matches(bar)
+        );
+    }
 }
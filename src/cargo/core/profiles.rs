@@ -21,7 +21,7 @@
 //! The precedence is explained in [`ProfileMaker`].
 //! The algorithm happens within [`ProfileMaker::get_profile`].
 
-use crate::core::compiler::{CompileKind, CompileTarget, Unit};
+use crate::core::compiler::{CompileKind, CompileTarget, CrateType, Unit};
 use crate::core::dependency::Artifact;
 use crate::core::resolver::features::FeaturesFor;
 use crate::core::Feature;
@@ -35,7 +35,7 @@ use anyhow::{bail, Context as _};
 use cargo_util_schemas::manifest::TomlTrimPaths;
 use cargo_util_schemas::manifest::TomlTrimPathsValue;
 use cargo_util_schemas::manifest::{
-    ProfilePackageSpec, StringOrBool, TomlDebugInfo, TomlProfile, TomlProfiles,
+    ProfilePackageSpec, StringOrBool, TomlDebugInfo, TomlProfile, TomlProfileInherits, TomlProfiles,
 };
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::Hash;
@@ -90,9 +90,13 @@ impl Profiles {
             || gctx.cli_unstable().trim_paths;
         Self::add_root_profiles(&mut profile_makers, &profiles, trim_paths_enabled);
 
+        let fuzz_targets_enabled = ws.unstable_features().is_enabled(Feature::fuzz_targets());
+
         // Merge with predefined profiles.
         use std::collections::btree_map::Entry;
-        for (predef_name, mut predef_prof) in Self::predefined_profiles().into_iter() {
+        for (predef_name, mut predef_prof) in
+            Self::predefined_profiles(fuzz_targets_enabled).into_iter()
+        {
             match profiles.entry(InternedString::new(predef_name)) {
                 Entry::Vacant(vac) => {
                     vac.insert(predef_prof);
@@ -106,7 +110,20 @@ impl Profiles {
             }
         }
 
+        // Profiles stacked after the first one in `inherits` are fragments,
+        // which don't need to inherit from anything themselves.
+        let fragments: HashSet<_> = profiles
+            .values()
+            .filter_map(|profile| profile.inherits.as_ref())
+            .flat_map(|inherits| inherits.names().iter().skip(1))
+            .map(|name| InternedString::new(name))
+            .collect();
         for (name, profile) in &profiles {
+            if profile.inherits.is_none() && fragments.contains(name) && *name != requested_profile
+            {
+                // Fragments can't be used on their own.
+                continue;
+            }
             profile_makers.add_maker(*name, profile, &profiles)?;
         }
         // Verify that the requested profile is defined *somewhere*.
@@ -149,30 +166,45 @@ impl Profiles {
 
     /// Returns the built-in profiles (not including dev/release, which are
     /// "root" profiles).
-    fn predefined_profiles() -> Vec<(&'static str, TomlProfile)> {
-        vec![
+    ///
+    /// The `fuzz` profile is only built-in with the `fuzz-targets` feature, so
+    /// it doesn't change existing custom profiles of the same name.
+    fn predefined_profiles(fuzz_targets_enabled: bool) -> Vec<(&'static str, TomlProfile)> {
+        let mut profiles = vec![
             (
                 "bench",
                 TomlProfile {
-                    inherits: Some(String::from("release")),
+                    inherits: Some(TomlProfileInherits::One(String::from("release"))),
                     ..TomlProfile::default()
                 },
             ),
             (
                 "test",
                 TomlProfile {
-                    inherits: Some(String::from("dev")),
+                    inherits: Some(TomlProfileInherits::One(String::from("dev"))),
                     ..TomlProfile::default()
                 },
             ),
             (
                 "doc",
                 TomlProfile {
-                    inherits: Some(String::from("dev")),
+                    inherits: Some(TomlProfileInherits::One(String::from("dev"))),
                     ..TomlProfile::default()
                 },
             ),
-        ]
+        ];
+        if fuzz_targets_enabled {
+            profiles.push((
+                "fuzz",
+                TomlProfile {
+                    inherits: Some(TomlProfileInherits::One(String::from("release"))),
+                    debug_assertions: Some(true),
+                    overflow_checks: Some(true),
+                    ..TomlProfile::default()
+                },
+            ));
+        }
+        profiles
     }
 
     /// Creates a `ProfileMaker`, and inserts it into `self.by_name`.
@@ -211,6 +243,9 @@ impl Profiles {
 
     /// Build a `ProfileMaker` by recursively following the `inherits` setting.
     ///
+    /// The first profile in `inherits` is the parent, and the following ones
+    /// are fragments merged on top of it, in order, before `profile` itself.
+    ///
     /// * `name`: The name of the profile being processed.
     /// * `profile`: The TOML profile being processed.
     /// * `set`: Set of profiles that have been visited, used to detect cycles.
@@ -224,44 +259,71 @@ impl Profiles {
         set: &mut HashSet<InternedString>,
         profiles: &BTreeMap<InternedString, TomlProfile>,
     ) -> CargoResult<ProfileMaker> {
-        let mut maker = match &profile.inherits {
-            Some(inherits_name) if inherits_name == "dev" || inherits_name == "release" => {
-                // These are the root profiles added in `add_root_profiles`.
-                self.get_profile_maker(&inherits_name).unwrap().clone()
+        let Some((inherits_name, fragment_names)) = profile
+            .inherits
+            .as_ref()
+            .and_then(|inherits| inherits.names().split_first())
+        else {
+            bail!(
+                "profile `{}` is missing an `inherits` directive \
+                 (`inherits` is required for all profiles except `dev` or `release`)",
+                name
+            );
+        };
+        let mut maker = if inherits_name == "dev" || inherits_name == "release" {
+            // These are the root profiles added in `add_root_profiles`.
+            self.get_profile_maker(&inherits_name).unwrap().clone()
+        } else {
+            let inherits_name = InternedString::new(&inherits_name);
+            if !set.insert(inherits_name) {
+                bail!(
+                    "profile inheritance loop detected with profile `{}` inheriting `{}`",
+                    name,
+                    inherits_name
+                );
             }
-            Some(inherits_name) => {
-                let inherits_name = InternedString::new(&inherits_name);
-                if !set.insert(inherits_name) {
+
+            match profiles.get(&inherits_name) {
+                None => {
                     bail!(
-                        "profile inheritance loop detected with profile `{}` inheriting `{}`",
+                        "profile `{}` inherits from `{}`, but that profile is not defined",
                         name,
                         inherits_name
                     );
                 }
-
-                match profiles.get(&inherits_name) {
-                    None => {
-                        bail!(
-                            "profile `{}` inherits from `{}`, but that profile is not defined",
-                            name,
-                            inherits_name
-                        );
-                    }
-                    Some(parent) => self.process_chain(inherits_name, parent, set, profiles)?,
-                }
+                Some(parent) => self.process_chain(inherits_name, parent, set, profiles)?,
             }
-            None => {
-                bail!(
-                    "profile `{}` is missing an `inherits` directive \
-                     (`inherits` is required for all profiles except `dev` or `release`)",
-                    name
-                );
-            }
-        };
-        match &mut maker.toml {
-            Some(toml) => toml.merge(profile),
-            None => maker.toml = Some(profile.clone()),
         };
+        for fragment_name in fragment_names {
+            let fragment = match profiles.get(fragment_name.as_str()) {
+                None => {
+                    bail!(
+                        "profile `{}` inherits from `{}`, but that profile is not defined",
+                        name,
+                        fragment_name
+                    );
+                }
+                Some(_) if fragment_name == "dev" || fragment_name == "release" => {
+                    bail!(
+                        "profile `{}` stacks `{}`, but `dev` and `release` can only be \
+                         the first profile in `inherits`",
+                        name,
+                        fragment_name
+                    );
+                }
+                Some(fragment) if fragment.inherits.is_some() => {
+                    bail!(
+                        "profile `{}` stacks `{}`, but that profile has an `inherits` directive \
+                         (only the first profile in `inherits` may inherit from other profiles)",
+                        name,
+                        fragment_name
+                    );
+                }
+                Some(fragment) => fragment,
+            };
+            maker.merge_toml(fragment);
+        }
+        maker.merge_toml(profile);
         Ok(maker)
     }
 
@@ -424,6 +486,14 @@ impl ProfileMaker {
         ProfileMaker { default, toml }
     }
 
+    /// Merges the settings of `toml` on top of the current ones.
+    fn merge_toml(&mut self, toml: &TomlProfile) {
+        match &mut self.toml {
+            Some(self_toml) => self_toml.merge(toml),
+            None => self.toml = Some(toml.clone()),
+        }
+    }
+
     /// Generates a new `Profile`.
     fn get_profile(
         &self,
@@ -572,6 +642,12 @@ fn merge_profile(profile: &mut Profile, toml: &TomlProfile) {
     if let Some(trim_paths) = &toml.trim_paths {
         profile.trim_paths = Some(trim_paths.clone());
     }
+    if let Some(symbols) = &toml.strip_keep_symbols {
+        profile.strip_keep_symbols = symbols.iter().map(InternedString::from).collect();
+    }
+    if let Some(compress_debuginfo) = &toml.compress_debuginfo {
+        profile.compress_debuginfo = Some(InternedString::new(compress_debuginfo));
+    }
     profile.strip = match toml.strip {
         Some(StringOrBool::Bool(true)) => {
             Strip::Resolved(StripInner::Named(InternedString::new("symbols")))
@@ -619,6 +695,15 @@ pub struct Profile {
     pub incremental: bool,
     pub panic: PanicStrategy,
     pub strip: Strip,
+    // remove when `profile-binary-size` is stablized
+    /// Symbols kept when stripping symbols, which Cargo then does itself
+    /// after linking, see [`Profile::strips_after_linking`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub strip_keep_symbols: Vec<InternedString>,
+    // `None` means use rustc default.
+    // remove when `profile-binary-size` is stablized
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compress_debuginfo: Option<InternedString>,
     #[serde(skip_serializing_if = "Vec::is_empty")] // remove when `rustflags` is stablized
     // Note that `rustflags` is used for the cargo-feature `profile_rustflags`
     pub rustflags: Vec<InternedString>,
@@ -644,6 +729,8 @@ impl Default for Profile {
             incremental: false,
             panic: PanicStrategy::Unwind,
             strip: Strip::Deferred(StripInner::None),
+            strip_keep_symbols: vec![],
+            compress_debuginfo: None,
             rustflags: vec![],
             trim_paths: None,
         }
@@ -673,6 +760,8 @@ compact_debug! {
                 incremental
                 panic
                 strip
+                strip_keep_symbols
+                compress_debuginfo
                 rustflags
                 trim_paths
             )]
@@ -727,6 +816,21 @@ impl Profile {
         }
     }
 
+    /// Whether the symbols of the binaries and cdylibs of `target` are stripped
+    /// by Cargo after linking, instead of by rustc, to keep the
+    /// `strip-keep-symbols` of this profile.
+    ///
+    /// This only applies to targets whose crate types are all linked into a
+    /// binary or a cdylib, other libraries are stripped by rustc as usual.
+    pub fn strips_after_linking(&self, target: &Target) -> bool {
+        !self.strip_keep_symbols.is_empty()
+            && self.strip.into_inner() == StripInner::Named(InternedString::new("symbols"))
+            && target
+                .rustc_crate_types()
+                .iter()
+                .all(|crate_type| matches!(crate_type, CrateType::Bin | CrateType::Cdylib))
+    }
+
     /// Compares all fields except `name`, which doesn't affect compilation.
     /// This is necessary for `Unit` deduplication for things like "test" and
     /// "dev" which are essentially the same.
@@ -742,6 +846,7 @@ impl Profile {
             self.overflow_checks,
             self.rpath,
             (self.incremental, self.panic, self.strip),
+            (&self.strip_keep_symbols, self.compress_debuginfo),
             &self.rustflags,
             &self.trim_paths,
         )
@@ -1269,7 +1374,12 @@ fn merge_config_profiles(
             profile.merge(&config_profile);
         }
         if let Some(inherits) = &profile.inherits {
-            check_to_add.insert(InternedString::new(inherits));
+            check_to_add.extend(
+                inherits
+                    .names()
+                    .iter()
+                    .map(|name| InternedString::new(name)),
+            );
         }
     }
     // Add the built-in profiles. This is important for things like `cargo
@@ -1286,7 +1396,12 @@ fn merge_config_profiles(
             if !profiles.contains_key(name.as_str()) {
                 if let Some(config_profile) = get_config_profile(ws, &name)? {
                     if let Some(inherits) = &config_profile.inherits {
-                        check_to_add.insert(InternedString::new(inherits));
+                        check_to_add.extend(
+                            inherits
+                                .names()
+                                .iter()
+                                .map(|name| InternedString::new(name)),
+                        );
                     }
                     profiles.insert(name, config_profile);
                 }
@@ -63,6 +63,12 @@ pub trait Registry {
 
     /// Block until all outstanding [`Poll::Pending`] requests are [`Poll::Ready`].
     fn block_until_ready(&mut self) -> CargoResult<()>;
+
+    /// Makes progress on the outstanding [`Poll::Pending`] requests without
+    /// blocking. See [`Source::poll_ready`].
+    fn poll_ready(&mut self) -> Poll<CargoResult<()>> {
+        Poll::Ready(self.block_until_ready())
+    }
 }
 
 /// This structure represents a registry of known packages. It internally
@@ -795,6 +801,24 @@ impl<'gctx> Registry for PackageRegistry<'gctx> {
         }
         Ok(())
     }
+
+    fn poll_ready(&mut self) -> Poll<CargoResult<()>> {
+        let mut ready = true;
+        for (source_id, source) in self.sources.sources_mut() {
+            match source.poll_ready() {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => {
+                    return Poll::Ready(Err(e.context(format!("Unable to update {}", source_id))))
+                }
+                Poll::Pending => ready = false,
+            }
+        }
+        if ready {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
 }
 
 /// See [`PackageRegistry::lock`].
@@ -162,16 +162,30 @@ impl ResolverContext {
             RequestedFeatures::DepFeatures {
                 features,
                 uses_default_features,
+                features_profile,
             } => {
                 let has_default_feature = summary.features().contains_key("default");
+                let profile_features = match features_profile {
+                    Some(profile) => match summary.feature_profiles().get(profile) {
+                        Some(profile_features) => profile_features.as_slice(),
+                        // Let the activation report the missing profile.
+                        None => return Ok(false),
+                    },
+                    None => &[],
+                };
                 Ok(match self.resolve_features.get(&id) {
                     Some(prev) => {
                         features.is_subset(prev)
+                            && profile_features.iter().all(|f| prev.contains(f))
                             && (!uses_default_features
                                 || prev.contains("default")
                                 || !has_default_feature)
                     }
-                    None => features.is_empty() && (!uses_default_features || !has_default_feature),
+                    None => {
+                        features.is_empty()
+                            && profile_features.is_empty()
+                            && (!uses_default_features || !has_default_feature)
+                    }
                 })
             }
         }
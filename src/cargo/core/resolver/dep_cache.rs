@@ -11,7 +11,7 @@
 
 use crate::core::resolver::context::ResolverContext;
 use crate::core::resolver::errors::describe_path_in_context;
-use crate::core::resolver::types::{ConflictReason, DepInfo, FeaturesSet};
+use crate::core::resolver::types::{ConflictReason, DepInfo, FeaturesSet, PlatformFilter};
 use crate::core::resolver::{
     ActivateError, ActivateResult, CliFeatures, RequestedFeatures, ResolveOpts, VersionOrdering,
     VersionPreferences,
@@ -33,6 +33,7 @@ pub struct RegistryQueryer<'a> {
     pub registry: &'a mut (dyn Registry + 'a),
     replacements: &'a [(PackageIdSpec, Dependency)],
     version_prefs: &'a VersionPreferences,
+    platform_filter: &'a PlatformFilter,
     /// a cache of `Candidate`s that fulfil a `Dependency` (and whether `first_version`)
     registry_cache: HashMap<(Dependency, Option<VersionOrdering>), Poll<Rc<Vec<Summary>>>>,
     /// a cache of `Dependency`s that are required for a `Summary`
@@ -53,17 +54,23 @@ impl<'a> RegistryQueryer<'a> {
         registry: &'a mut dyn Registry,
         replacements: &'a [(PackageIdSpec, Dependency)],
         version_prefs: &'a VersionPreferences,
+        platform_filter: &'a PlatformFilter,
     ) -> Self {
         RegistryQueryer {
             registry,
             replacements,
             version_prefs,
+            platform_filter,
             registry_cache: HashMap::new(),
             summary_cache: HashMap::new(),
             used_replacements: HashMap::new(),
         }
     }
 
+    pub fn version_prefs(&self) -> &'a VersionPreferences {
+        self.version_prefs
+    }
+
     pub fn reset_pending(&mut self) -> bool {
         let mut all_ready = true;
         self.registry_cache.retain(|_, r| {
@@ -240,7 +247,8 @@ impl<'a> RegistryQueryer<'a> {
         // First, figure out our set of dependencies based on the requested set
         // of features. This also calculates what features we're going to enable
         // for our own dependencies.
-        let (used_features, deps) = resolve_features(parent, candidate, opts)?;
+        let (used_features, deps) =
+            resolve_features(parent, candidate, opts, self.platform_filter)?;
 
         // Next, transform all dependencies into a list of possible candidates
         // which can satisfy that dependency.
@@ -290,6 +298,7 @@ pub fn resolve_features<'b>(
     parent: Option<PackageId>,
     s: &'b Summary,
     opts: &'b ResolveOpts,
+    platform_filter: &PlatformFilter,
 ) -> ActivateResult<(HashSet<InternedString>, Vec<(Dependency, FeaturesSet)>)> {
     // First, filter by dev-dependencies.
     let deps = s.dependencies();
@@ -308,6 +317,9 @@ pub fn resolve_features<'b>(
             continue;
         }
         valid_dep_names.insert(dep.name_in_toml());
+        if !platform_filter.matches(dep) {
+            continue;
+        }
         // So we want this dependency. Move the features we want from
         // `feature_deps` to `ret` and register ourselves as using this
         // name.
@@ -379,12 +391,33 @@ fn build_requirements<'a, 'b: 'a>(
         RequestedFeatures::DepFeatures {
             features,
             uses_default_features,
+            features_profile,
         } => {
             for feature in features.iter() {
                 if let Err(e) = reqs.require_feature(*feature) {
                     return Err(e.into_activate_error(parent, s));
                 }
             }
+            if let Some(profile) = features_profile {
+                let Some(profile_features) = s.feature_profiles().get(profile) else {
+                    return Err(match parent {
+                        None => ActivateError::Fatal(anyhow::format_err!(
+                            "Package `{}` does not have the feature profile `{}`",
+                            s.package_id(),
+                            profile
+                        )),
+                        Some(p) => ActivateError::Conflict(
+                            p,
+                            ConflictReason::MissingFeatureProfile(*profile),
+                        ),
+                    });
+                };
+                for feature in profile_features {
+                    if let Err(e) = reqs.require_feature(*feature) {
+                        return Err(e.into_activate_error(parent, s));
+                    }
+                }
+            }
             handle_default(*uses_default_features, &mut reqs)?;
         }
     }
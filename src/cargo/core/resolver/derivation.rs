@@ -0,0 +1,242 @@
+//! Tracking of why the candidates of a dependency were rejected, used to
+//! explain resolution failures.
+//!
+//! As the resolver goes, [`Derivations`] records the reason each candidate it
+//! tries is rejected for, including the candidates abandoned while
+//! backtracking because one of their own dependencies couldn't be resolved.
+//! When the resolver runs out of candidates for a dependency, those records
+//! are rendered as a tree, e.g.
+//!
+//! ```text
+//! `foo v0.1.0` requires `bar = "^2"`
+//! └── `bar v2.0.0` requires `baz = "^3"`, which conflicts with `baz v1.1.0`
+//! ```
+//!
+//! Conflicts with previously selected packages are already described by the
+//! error message, so they are only part of the tree in verbose mode, which
+//! shows the full derivation, recursing into the rejected dependencies.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::rc::Rc;
+
+use crate::core::{Dependency, PackageId, Summary};
+
+use super::context::ResolverContext;
+use super::types::{ConflictMap, ConflictReason};
+
+/// Why a candidate version of a dependency was rejected.
+#[derive(Clone)]
+pub(super) enum Rejection {
+    /// The candidate conflicts with a selected package.
+    Conflict(PackageId, ConflictReason),
+    /// The candidate was selected, but none of the `candidates` of its
+    /// dependency `dep` could be while the `conflicts` were selected.
+    Dependency {
+        dep: Dependency,
+        candidates: Rc<Vec<Summary>>,
+        conflicts: ConflictMap,
+    },
+}
+
+/// The rejections recorded during a resolution.
+///
+/// This lives outside of the [`ResolverContext`], so the reasons found in a
+/// branch of the search are kept when backtracking out of it.
+#[derive(Default)]
+pub(super) struct Derivations {
+    /// The last reason each candidate was rejected for.
+    rejections: HashMap<PackageId, Rejection>,
+}
+
+impl Derivations {
+    /// Records why `candidate` was rejected, replacing any previous reason.
+    pub(super) fn reject(&mut self, candidate: PackageId, rejection: Rejection) {
+        self.rejections.insert(candidate, rejection);
+    }
+
+    /// Forgets why `candidate` was rejected, when it is skipped without
+    /// knowing why in the current branch of the search.
+    pub(super) fn forget(&mut self, candidate: PackageId) {
+        self.rejections.remove(&candidate);
+    }
+
+    /// Explains why none of the `candidates` for `dep` of `parent` could be
+    /// selected.
+    ///
+    /// Without `verbose`, only the candidates rejected because of their own
+    /// dependencies are shown, and `None` is returned if there are none.
+    pub(super) fn explain(
+        &self,
+        cx: &ResolverContext,
+        parent: &Summary,
+        dep: &Dependency,
+        candidates: &[Summary],
+        verbose: bool,
+    ) -> Option<String> {
+        let candidates: Vec<_> = candidates
+            .iter()
+            .map(|c| c.package_id())
+            .filter(|c| {
+                verbose || matches!(self.rejections.get(c), Some(Rejection::Dependency { .. }))
+            })
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        let mut out = format!(
+            "`{}` requires `{}`",
+            parent.package_id(),
+            describe_requirement(dep)
+        );
+        let mut visited = HashSet::new();
+        let render = Render {
+            derivations: self,
+            cx,
+            verbose,
+        };
+        render.candidates(&mut out, &candidates, "", &mut visited);
+        Some(out)
+    }
+}
+
+/// The state needed to render the tree of rejections.
+struct Render<'a> {
+    derivations: &'a Derivations,
+    cx: &'a ResolverContext,
+    verbose: bool,
+}
+
+impl Render<'_> {
+    /// Renders each of the `candidates` and why it was rejected.
+    fn candidates(
+        &self,
+        out: &mut String,
+        candidates: &[PackageId],
+        prefix: &str,
+        visited: &mut HashSet<PackageId>,
+    ) {
+        for (i, &candidate) in candidates.iter().enumerate() {
+            let (branch, indent) = branch(i + 1 == candidates.len());
+            write!(out, "\n{prefix}{branch}`{candidate}` ").unwrap();
+            let prefix = format!("{prefix}{indent}");
+            match self.derivations.rejections.get(&candidate) {
+                Some(Rejection::Conflict(other, reason)) => {
+                    out.push_str(&describe_conflict(*other, reason));
+                    if !is_feature_conflict(reason) {
+                        self.requirers(out, *other, &prefix, &mut HashSet::new());
+                    }
+                }
+                Some(Rejection::Dependency {
+                    dep,
+                    candidates,
+                    conflicts,
+                }) => {
+                    write!(out, "requires `{}`", describe_requirement(dep)).unwrap();
+                    if self.verbose && visited.insert(candidate) {
+                        out.push_str(", but none of its versions could be selected");
+                        let candidates: Vec<_> =
+                            candidates.iter().map(|c| c.package_id()).collect();
+                        self.candidates(out, &candidates, &prefix, visited);
+                    } else {
+                        let conflicts: Vec<_> =
+                            conflicts.keys().map(|id| format!("`{id}`")).collect();
+                        if conflicts.is_empty() {
+                            out.push_str(", which could not be resolved");
+                        } else {
+                            write!(out, ", which conflicts with {}", conflicts.join(", ")).unwrap();
+                        }
+                    }
+                }
+                None => out.push_str("could not be selected"),
+            }
+        }
+    }
+
+    /// Renders the packages requiring `pkg`, recursively in verbose mode.
+    fn requirers(
+        &self,
+        out: &mut String,
+        pkg: PackageId,
+        prefix: &str,
+        visited: &mut HashSet<PackageId>,
+    ) {
+        if !visited.insert(pkg) {
+            return;
+        }
+        let mut requirers = Vec::new();
+        for (requirer, deps) in self.cx.parents.edges(&pkg) {
+            let mut reqs: Vec<_> = deps.iter().map(describe_requirement).collect();
+            reqs.sort();
+            reqs.dedup();
+            for req in reqs {
+                requirers.push((*requirer, req));
+            }
+        }
+        let count = requirers.len();
+        for (i, (requirer, req)) in requirers.into_iter().enumerate() {
+            let (branch, indent) = branch(i + 1 == count);
+            write!(out, "\n{prefix}{branch}`{requirer}` requires `{req}`").unwrap();
+            if self.verbose {
+                let prefix = format!("{prefix}{indent}");
+                self.requirers(out, requirer, &prefix, visited);
+            }
+        }
+    }
+}
+
+/// The branch and the indentation of its children for an entry of the tree.
+fn branch(last: bool) -> (&'static str, &'static str) {
+    if last {
+        ("└── ", "    ")
+    } else {
+        ("├── ", "│   ")
+    }
+}
+
+/// Whether `reason` is about the features `other` requires of the candidate,
+/// rather than about `other` being selected.
+fn is_feature_conflict(reason: &ConflictReason) -> bool {
+    matches!(
+        reason,
+        ConflictReason::MissingFeatures(_)
+            | ConflictReason::RequiredDependencyAsFeature(_)
+            | ConflictReason::NonImplicitDependencyAsFeature(_)
+            | ConflictReason::MissingFeatureProfile(_)
+    )
+}
+
+/// Describes why a candidate conflicts with `other`.
+fn describe_conflict(other: PackageId, reason: &ConflictReason) -> String {
+    match reason {
+        ConflictReason::Semver => format!("conflicts with `{other}`, which is already selected"),
+        ConflictReason::Links(link) => {
+            format!("links to `{link}`, but `{other}` already links to it")
+        }
+        ConflictReason::MissingFeatures(features) => {
+            format!("does not have the features `{features}` required by `{other}`")
+        }
+        ConflictReason::RequiredDependencyAsFeature(features) => {
+            format!("has a required dependency for the features `{features}` required by `{other}`")
+        }
+        ConflictReason::NonImplicitDependencyAsFeature(features) => format!(
+            "has no implicit feature for the dependencies `{features}` required by `{other}`"
+        ),
+        ConflictReason::MissingFeatureProfile(profile) => {
+            format!("does not have the features profile `{profile}` required by `{other}`")
+        }
+        ConflictReason::PublicDependency(_) | ConflictReason::PubliclyExports(_) => {
+            format!("conflicts with the public dependencies of `{other}`")
+        }
+    }
+}
+
+/// Describes a dependency the way it was written in the manifest, e.g.
+/// `bar = "^1.0"`, or just `bar` for path and git dependencies.
+fn describe_requirement(dep: &Dependency) -> String {
+    if dep.source_id().is_path() || dep.source_id().is_git() {
+        dep.name_in_toml().to_string()
+    } else {
+        format!("{} = \"{}\"", dep.name_in_toml(), dep.version_req())
+    }
+}
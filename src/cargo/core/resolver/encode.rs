@@ -156,6 +156,7 @@ impl EncodableResolve {
     pub fn into_resolve(self, original: &str, ws: &Workspace<'_>) -> CargoResult<Resolve> {
         let path_deps: HashMap<String, HashMap<semver::Version, SourceId>> = build_path_deps(ws)?;
         let mut checksums = HashMap::new();
+        let mut content_checksums = HashMap::new();
 
         let mut version = match self.version {
             Some(n @ 5) if ws.gctx().nightly_features_allowed => {
@@ -221,7 +222,11 @@ impl EncodableResolve {
                 // didn't ever encode this field.
                 if let Some(cksum) = &pkg.checksum {
                     version = version.max(ResolveVersion::V2);
-                    checksums.insert(id, Some(cksum.clone()));
+                    if version >= ResolveVersion::V5 && is_content_checksummed(id) {
+                        content_checksums.insert(id, cksum.clone());
+                    } else {
+                        checksums.insert(id, Some(cksum.clone()));
+                    }
                 }
 
                 assert!(live_pkgs.insert(enc_id, (id, pkg)).is_none())
@@ -403,7 +408,7 @@ impl EncodableResolve {
             version = ResolveVersion::V2;
         }
 
-        return Ok(Resolve::new(
+        let mut resolve = Resolve::new(
             g,
             replacements,
             HashMap::new(),
@@ -412,7 +417,9 @@ impl EncodableResolve {
             unused_patches,
             version,
             HashMap::new(),
-        ));
+        );
+        resolve.set_content_checksums(content_checksums);
+        return Ok(resolve);
 
         fn get_source_id<'a>(
             path_deps: &'a HashMap<String, HashMap<semver::Version, SourceId>>,
@@ -794,7 +801,9 @@ fn encodable_resolve_node(
         source: encodable_source_id(id.source_id(), resolve.version()),
         dependencies: deps,
         replace,
-        checksum: if resolve.version() >= ResolveVersion::V2 {
+        checksum: if resolve.version() >= ResolveVersion::V5 && is_content_checksummed(id) {
+            resolve.content_checksums().get(&id).cloned()
+        } else if resolve.version() >= ResolveVersion::V2 {
             resolve.checksums().get(&id).and_then(|s| s.clone())
         } else {
             None
@@ -802,6 +811,12 @@ fn encodable_resolve_node(
     }
 }
 
+/// Whether the `checksum` of `id` is a hash of its contents rather than of a
+/// `.crate` file, see [`ResolveVersion::V5`].
+fn is_content_checksummed(id: PackageId) -> bool {
+    id.source_id().is_git() || id.source_id().is_path()
+}
+
 pub fn encodable_package_id(
     id: PackageId,
     state: &EncodeState<'_>,
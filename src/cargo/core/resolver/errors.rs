@@ -1,13 +1,16 @@
 use std::fmt;
 use std::task::Poll;
 
+use crate::core::shell::Verbosity;
 use crate::core::{Dependency, PackageId, Registry, Summary};
 use crate::sources::source::QueryKind;
 use crate::util::edit_distance::edit_distance;
 use crate::util::{GlobalContext, OptVersionReq, VersionExt};
 use anyhow::Error;
+use semver::VersionReq;
 
 use super::context::ResolverContext;
+use super::derivation::Derivations;
 use super::types::{ConflictMap, ConflictReason};
 
 /// Error during resolution providing a path of `PackageId`s.
@@ -76,6 +79,8 @@ pub(super) fn activation_error(
     dep: &Dependency,
     conflicting_activations: &ConflictMap,
     candidates: &[Summary],
+    derivations: &Derivations,
+    pin: Option<&VersionReq>,
     gctx: Option<&GlobalContext>,
 ) -> ResolveError {
     let to_resolve_err = |err| {
@@ -193,6 +198,18 @@ pub(super) fn activation_error(
                     );
                     // p == parent so the full path is redundant.
                 }
+                ConflictReason::MissingFeatureProfile(profile) => {
+                    msg.push_str("\n\nthe package `");
+                    msg.push_str(&*p.name());
+                    msg.push_str("` depends on `");
+                    msg.push_str(&*dep.package_name());
+                    msg.push_str("`, with features profile: `");
+                    msg.push_str(profile);
+                    msg.push_str("` but `");
+                    msg.push_str(&*dep.package_name());
+                    msg.push_str("` does not have this profile in `[features.profiles]`.\n");
+                    // p == parent so the full path is redundant.
+                }
                 ConflictReason::PublicDependency(pkg_id) => {
                     // TODO: This needs to be implemented.
                     unimplemented!("pub dep {:?}", pkg_id);
@@ -219,6 +236,14 @@ pub(super) fn activation_error(
         msg.push_str(&*dep.package_name());
         msg.push_str("` which could resolve this conflict");
 
+        let verbose = gctx.map_or(false, |gctx| gctx.shell().verbosity() == Verbosity::Verbose);
+        if let Some(explanation) =
+            derivations.explain(resolver_ctx, parent, dep, candidates, verbose)
+        {
+            msg.push_str("\n\nexplanation:\n");
+            msg.push_str(&explanation);
+        }
+
         return to_resolve_err(anyhow::format_err!("{}", msg));
     }
 
@@ -307,6 +332,14 @@ pub(super) fn activation_error(
             msg.push_str("\nperhaps a crate was updated and forgotten to be re-vendored?");
         }
 
+        if let Some(pin) = pin {
+            msg.push_str(&format!(
+                "\nonly versions matching `{pin}` are considered, \
+                 as `{}` is pinned in `workspace.dependency-pins`",
+                dep.package_name()
+            ));
+        }
+
         msg
     } else {
         // Maybe the user mistyped the name? Like `dep-thing` when `Dep_Thing`
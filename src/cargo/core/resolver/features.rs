@@ -42,7 +42,7 @@ use crate::core::compiler::{CompileKind, CompileTarget, RustcTargetData};
 use crate::core::dependency::{ArtifactTarget, DepKind, Dependency};
 use crate::core::resolver::types::FeaturesSet;
 use crate::core::resolver::{Resolve, ResolveBehavior};
-use crate::core::{FeatureValue, PackageId, PackageIdSpec, PackageSet, Workspace};
+use crate::core::{FeatureValue, Package, PackageId, PackageIdSpec, PackageSet, Workspace};
 use crate::util::interning::InternedString;
 use crate::util::CargoResult;
 use anyhow::{bail, Context};
@@ -244,6 +244,8 @@ pub enum RequestedFeatures {
         features: FeaturesSet,
         /// The `default-features` dependency field.
         uses_default_features: bool,
+        /// The `features-profile` dependency field.
+        features_profile: Option<InternedString>,
     },
 }
 
@@ -351,6 +353,16 @@ impl ResolvedFeatures {
         self.activated_features_int(pkg_id, features_for).ok()
     }
 
+    /// Returns every activated package along with the features enabled for
+    /// it.
+    pub fn all_activated_features(
+        &self,
+    ) -> impl Iterator<Item = (PackageId, FeaturesFor, &BTreeSet<InternedString>)> + '_ {
+        self.activated_features
+            .iter()
+            .map(|((pkg_id, features_for), features)| (*pkg_id, *features_for, features))
+    }
+
     fn activated_features_int(
         &self,
         pkg_id: PackageId,
@@ -505,7 +517,7 @@ impl<'a, 'gctx> FeatureResolver<'a, 'gctx> {
             };
             self.activate_pkg(member.package_id(), fk, &fvs)?;
         }
-        Ok(())
+        self.check_feature_conflicts(&member_features)
     }
 
     /// Activates [`FeatureValue`]s on the given package.
@@ -747,6 +759,13 @@ impl<'a, 'gctx> FeatureResolver<'a, 'gctx> {
         if dep.uses_default_features() && feature_map.contains_key(&default) {
             result.push(FeatureValue::Feature(default));
         }
+        // The dependency resolver already checked that the profile exists.
+        if let Some(profile_features) = dep
+            .features_profile()
+            .and_then(|profile| summary.feature_profiles().get(&profile))
+        {
+            result.extend(profile_features.iter().map(|f| FeatureValue::Feature(*f)));
+        }
         result
     }
 
@@ -935,6 +954,117 @@ impl<'a, 'gctx> FeatureResolver<'a, 'gctx> {
             .collect()
     }
 
+    /// Checks that no package has two features enabled which it declared as
+    /// mutually exclusive in `[features.conflicts]`.
+    ///
+    /// The error names the packages which enabled each side, since the
+    /// conflicting package itself usually isn't the one at fault.
+    fn check_feature_conflicts(
+        &self,
+        member_features: &[(&Package, CliFeatures)],
+    ) -> CargoResult<()> {
+        let mut keys: Vec<_> = self.activated_features.keys().collect();
+        keys.sort();
+        for &(pkg_id, fk) in keys {
+            let pkg = self.package_set.get_one(pkg_id)?;
+            let Some(conflicts) = pkg.manifest().resolved_toml().feature_conflicts() else {
+                continue;
+            };
+            let activated = &self.activated_features[&(pkg_id, fk)];
+            for (feature, others) in conflicts {
+                if !activated.contains(feature.as_str()) {
+                    continue;
+                }
+                let Some(other) = others.iter().find(|o| activated.contains(o.as_str())) else {
+                    continue;
+                };
+                let mut msg = format!(
+                    "features `{feature}` and `{other}` of package `{pkg_id}` are mutually \
+                     exclusive, but both are enabled"
+                );
+                for name in [feature.as_str(), other.as_str()] {
+                    let enabled_by = self.feature_enabled_by(pkg_id, name, member_features);
+                    msg.push_str(&format!("\n\n`{name}` is enabled by:"));
+                    for by in enabled_by {
+                        msg.push_str(&format!("\n  {by}"));
+                    }
+                }
+                bail!(msg);
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the packages which enable `feature` of `pkg_id`, either directly
+    /// or through other features of `pkg_id` which enable it.
+    fn feature_enabled_by(
+        &self,
+        pkg_id: PackageId,
+        feature: &str,
+        member_features: &[(&Package, CliFeatures)],
+    ) -> BTreeSet<String> {
+        let feature_map = self.resolve.summary(pkg_id).features();
+        // Every feature of `pkg_id` which ends up enabling `feature`.
+        let mut enabling: HashSet<InternedString> = HashSet::new();
+        enabling.insert(InternedString::new(feature));
+        loop {
+            let len = enabling.len();
+            for (name, fvs) in feature_map {
+                if fvs.iter().any(|fv| match fv {
+                    FeatureValue::Feature(f) => enabling.contains(f),
+                    _ => false,
+                }) {
+                    enabling.insert(*name);
+                }
+            }
+            if enabling.len() == len {
+                break;
+            }
+        }
+
+        let mut enabled_by = BTreeSet::new();
+        for (member, cli_features) in member_features {
+            if member.package_id() == pkg_id
+                && self
+                    .fvs_from_requested(pkg_id, cli_features)
+                    .iter()
+                    .any(|fv| matches!(fv, FeatureValue::Feature(f) if enabling.contains(f)))
+            {
+                enabled_by.insert(format!("{pkg_id} (selected on the command line)"));
+            }
+        }
+        for &(parent_id, parent_fk) in self.activated_features.keys() {
+            for (dep_id, deps) in self.resolve.deps(parent_id) {
+                if dep_id != pkg_id {
+                    continue;
+                }
+                for dep in deps {
+                    let by_dependency = self
+                        .fvs_from_dependency(dep_id, dep)
+                        .iter()
+                        .any(|fv| matches!(fv, FeatureValue::Feature(f) if enabling.contains(f)));
+                    let parent_features = self.resolve.summary(parent_id).features();
+                    let by_feature = self.activated_features[&(parent_id, parent_fk)]
+                        .iter()
+                        .filter_map(|f| parent_features.get(f))
+                        .flatten()
+                        .any(|fv| match fv {
+                            FeatureValue::DepFeature {
+                                dep_name,
+                                dep_feature,
+                                ..
+                            } => *dep_name == dep.name_in_toml() && enabling.contains(dep_feature),
+                            _ => false,
+                        });
+                    if by_dependency || by_feature {
+                        enabled_by.insert(parent_id.to_string());
+                    }
+                }
+            }
+        }
+        enabled_by
+    }
+
     /// Compare the activated features to the resolver. Used for testing.
     fn compare(&self) {
         let mut found = false;
@@ -61,6 +61,8 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::mem;
 use std::rc::Rc;
+use std::task::Poll;
+use std::thread;
 use std::time::{Duration, Instant};
 
 use tracing::{debug, trace};
@@ -73,6 +75,7 @@ use crate::util::network::PollExt;
 
 use self::context::ResolverContext;
 use self::dep_cache::RegistryQueryer;
+use self::derivation::{Derivations, Rejection};
 use self::features::RequestedFeatures;
 use self::types::{ConflictMap, ConflictReason, DepsFrame};
 use self::types::{FeaturesSet, RcVecIter, RemainingDeps, ResolverProgress};
@@ -82,12 +85,13 @@ pub use self::encode::{EncodableDependency, EncodablePackageId, EncodableResolve
 pub use self::errors::{ActivateError, ActivateResult, ResolveError};
 pub use self::features::{CliFeatures, ForceAllTargets, HasDevUnits};
 pub use self::resolve::{Resolve, ResolveVersion};
-pub use self::types::{ResolveBehavior, ResolveOpts};
+pub use self::types::{PlatformFilter, ResolveBehavior, ResolveOpts};
 pub use self::version_prefs::{VersionOrdering, VersionPreferences};
 
 mod conflict_cache;
 mod context;
 mod dep_cache;
+mod derivation;
 pub(crate) mod encode;
 pub(crate) mod errors;
 pub mod features;
@@ -117,6 +121,9 @@ mod version_prefs;
 /// * `version_prefs` - this represents a preference for some versions over others,
 ///   based on the lock file or other reasons such as `[patch]`es.
 ///
+/// * `platform_filter` - the platforms whose dependencies are resolved, or all
+///   of them if empty.
+///
 /// * `resolve_version` - this controls how the lockfile will be serialized.
 ///
 /// * `config` - a location to print warnings and such, or `None` if no warnings
@@ -127,16 +134,12 @@ pub fn resolve(
     replacements: &[(PackageIdSpec, Dependency)],
     registry: &mut dyn Registry,
     version_prefs: &VersionPreferences,
+    platform_filter: &PlatformFilter,
     resolve_version: ResolveVersion,
     gctx: Option<&GlobalContext>,
 ) -> CargoResult<Resolve> {
-    let first_version = match gctx {
-        Some(config) if config.cli_unstable().direct_minimal_versions => {
-            Some(VersionOrdering::MinimumVersionsFirst)
-        }
-        _ => None,
-    };
-    let mut registry = RegistryQueryer::new(registry, replacements, version_prefs);
+    let first_version = version_prefs.direct_ordering();
+    let mut registry = RegistryQueryer::new(registry, replacements, version_prefs, platform_filter);
     let resolver_ctx = loop {
         let resolver_ctx = ResolverContext::new();
         let resolver_ctx =
@@ -144,7 +147,7 @@ pub fn resolve(
         if registry.reset_pending() {
             break resolver_ctx;
         } else {
-            registry.registry.block_until_ready()?;
+            wait_until_ready(registry.registry, gctx)?;
         }
     };
 
@@ -183,6 +186,26 @@ pub fn resolve(
     Ok(resolve)
 }
 
+/// How long to wait between two polls of the sources with pending queries.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Waits for the pending queries of `registry`, polling its sources together
+/// rather than blocking on each of them in turn, and stopping if the resolve
+/// is cancelled in the meantime.
+fn wait_until_ready(registry: &mut dyn Registry, gctx: Option<&GlobalContext>) -> CargoResult<()> {
+    loop {
+        match registry.poll_ready() {
+            Poll::Ready(result) => return result,
+            Poll::Pending => {
+                if let Some(gctx) = gctx {
+                    gctx.cancellation_token().check()?;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
 /// Recursively activates the dependencies for `summaries`, in depth-first order,
 /// backtracking across possible candidates for each dependency as necessary.
 ///
@@ -202,6 +225,10 @@ fn activate_deps_loop(
     // backtrack.
     let mut past_conflicting_activations = conflict_cache::ConflictCache::new();
 
+    // `derivations` records why each candidate was rejected, across
+    // backtracking, to explain the error if we fail to resolve.
+    let mut derivations = Derivations::default();
+
     // Activate all the initial summaries to kick off some work.
     for (summary, opts) in summaries {
         debug!("initial activation: {}", summary.package_id());
@@ -277,7 +304,11 @@ fn activate_deps_loop(
         let mut backtracked = false;
 
         loop {
-            let next = remaining_candidates.next(&mut conflicting_activations, &resolver_ctx);
+            let next = remaining_candidates.next(
+                &mut conflicting_activations,
+                &resolver_ctx,
+                &mut derivations,
+            );
 
             let (candidate, has_another) = next.ok_or(()).or_else(|_| {
                 // If we get here then our `remaining_candidates` was just
@@ -307,6 +338,17 @@ fn activate_deps_loop(
                 // As we mentioned above with the `backtracked` variable if this
                 // local is set to `true` then our `conflicting_activations` may
                 // not be right, so we can't push into our global cache.
+                // Whatever we backtrack to, `parent` is abandoned because
+                // of `dep`.
+                derivations.reject(
+                    parent.package_id(),
+                    Rejection::Dependency {
+                        dep: dep.clone(),
+                        candidates: Rc::clone(&candidates),
+                        conflicts: conflicting_activations.clone(),
+                    },
+                );
+
                 let mut generalize_conflicting_activations = None;
                 if !just_here_for_the_error_messages && !backtracked {
                     past_conflicting_activations.insert(&dep, &conflicting_activations);
@@ -330,6 +372,7 @@ fn activate_deps_loop(
                     generalize_conflicting_activations
                         .as_ref()
                         .unwrap_or(&conflicting_activations),
+                    &mut derivations,
                 ) {
                     Some((candidate, has_another, frame)) => {
                         // Reset all of our local variables used with the
@@ -346,6 +389,10 @@ fn activate_deps_loop(
                     }
                     None => {
                         debug!("no candidates found");
+                        let pin = registry
+                            .version_prefs()
+                            .pinned_req(dep.package_name())
+                            .filter(|_| dep.source_id().is_registry());
                         Err(errors::activation_error(
                             &resolver_ctx,
                             registry.registry,
@@ -353,6 +400,8 @@ fn activate_deps_loop(
                             &dep,
                             &conflicting_activations,
                             &candidates,
+                            &derivations,
+                            pin,
                             gctx,
                         ))
                     }
@@ -365,6 +414,7 @@ fn activate_deps_loop(
             // otherwise we'll just backtrack here anyway (helping us to skip
             // some work).
             if just_here_for_the_error_messages && !backtracked && has_another {
+                derivations.forget(candidate.package_id());
                 continue;
             }
 
@@ -396,6 +446,7 @@ fn activate_deps_loop(
                 features: RequestedFeatures::DepFeatures {
                     features: Rc::clone(&features),
                     uses_default_features: dep.uses_default_features(),
+                    features_profile: dep.features_profile(),
                 },
             };
             trace!(
@@ -439,11 +490,13 @@ fn activate_deps_loop(
                     // conflict with us.
                     let mut has_past_conflicting_dep = just_here_for_the_error_messages;
                     if !has_past_conflicting_dep {
-                        if let Some(conflicting) = frame
+                        if let Some((new_dep, new_candidates, conflicting)) = frame
                             .remaining_siblings
                             .clone()
-                            .filter_map(|(ref new_dep, _, _)| {
-                                past_conflicting_activations.conflicting(&resolver_ctx, new_dep)
+                            .filter_map(|(new_dep, new_candidates, _)| {
+                                past_conflicting_activations
+                                    .conflicting(&resolver_ctx, &new_dep)
+                                    .map(|conflicting| (new_dep, new_candidates, conflicting))
                             })
                             .next()
                         {
@@ -460,6 +513,14 @@ fn activate_deps_loop(
                                     .filter(|&(p, _)| p != &pid)
                                     .map(|(&p, r)| (p, r.clone())),
                             );
+                            derivations.reject(
+                                pid,
+                                Rejection::Dependency {
+                                    dep: new_dep,
+                                    candidates: new_candidates,
+                                    conflicts: conflicting.clone(),
+                                },
+                            );
 
                             has_past_conflicting_dep = true;
                         }
@@ -501,6 +562,8 @@ fn activate_deps_loop(
                                         .filter(|&(p, _)| p != &pid)
                                         .map(|(&p, r)| (p, r.clone())),
                                 );
+                                derivations
+                                    .reject(pid, Rejection::Conflict(other_parent, rel.clone()));
                                 conflicting_activations.insert(other_parent, rel);
                                 has_past_conflicting_dep = true;
                             }
@@ -527,6 +590,8 @@ fn activate_deps_loop(
                                 &parent,
                                 backtracked,
                                 &conflicting_activations,
+                                // Only probing, nothing is rejected yet.
+                                &mut Derivations::default(),
                             )
                             .is_none()
                         }
@@ -578,6 +643,7 @@ fn activate_deps_loop(
                 // frame's list of conflicting activations as to why this
                 // candidate failed, and then move on.
                 Err(ActivateError::Conflict(id, reason)) => {
+                    derivations.reject(pid, Rejection::Conflict(id, reason.clone()));
                     conflicting_activations.insert(id, reason);
                     false
                 }
@@ -749,6 +815,7 @@ impl RemainingCandidates {
         &mut self,
         conflicting_prev_active: &mut ConflictMap,
         cx: &ResolverContext,
+        derivations: &mut Derivations,
     ) -> Option<(Summary, bool)> {
         for b in self.remaining.by_ref() {
             let b_id = b.package_id();
@@ -762,6 +829,8 @@ impl RemainingCandidates {
                         conflicting_prev_active
                             .entry(a)
                             .or_insert_with(|| ConflictReason::Links(link));
+                        derivations
+                            .reject(b_id, Rejection::Conflict(a, ConflictReason::Links(link)));
                         continue;
                     }
                 }
@@ -780,6 +849,10 @@ impl RemainingCandidates {
                     conflicting_prev_active
                         .entry(a.package_id())
                         .or_insert(ConflictReason::Semver);
+                    derivations.reject(
+                        b_id,
+                        Rejection::Conflict(a.package_id(), ConflictReason::Semver),
+                    );
                     continue;
                 }
             }
@@ -939,6 +1012,7 @@ fn find_candidate(
     parent: &Summary,
     backtracked: bool,
     conflicting_activations: &ConflictMap,
+    derivations: &mut Derivations,
 ) -> Option<(Summary, bool, BacktrackFrame)> {
     // When we're calling this method we know that `parent` failed to
     // activate. That means that some dependency failed to get resolved for
@@ -959,9 +1033,11 @@ fn find_candidate(
     };
 
     while let Some(mut frame) = backtrack_stack.pop() {
-        let next = frame
-            .remaining_candidates
-            .next(&mut frame.conflicting_activations, &frame.context);
+        let next = frame.remaining_candidates.next(
+            &mut frame.conflicting_activations,
+            &frame.context,
+            derivations,
+        );
         let Some((candidate, has_another)) = next else {
             continue;
         };
@@ -31,6 +31,10 @@ pub struct Resolve {
     /// validate the correct crate file is used. This is `None` for sources
     /// that do not use `.crate` files, like path or git dependencies.
     checksums: HashMap<PackageId, Option<String>>,
+    /// Content hash of git and path packages outside the workspace, recorded
+    /// in V5 lock files so changes to their sources can be detected. See
+    /// `cargo::ops::lockfile` for how they are computed.
+    content_checksums: HashMap<PackageId, String>,
     /// "Unknown" metadata. This is a collection of extra, unrecognized data
     /// found in the `[metadata]` section of `Cargo.lock`, preserved for
     /// forwards compatibility.
@@ -95,6 +99,9 @@ pub enum ResolveVersion {
     /// Unstable. Will collect a certain amount of changes and then go.
     ///
     /// Changes made:
+    ///
+    /// * Packages from git sources and path packages outside the workspace
+    ///   record a `checksum` of their contents.
     V5,
 }
 
@@ -154,6 +161,10 @@ impl ResolveVersion {
     }
 }
 
+/// Key of the `[metadata]` table of `Cargo.lock` listing the platforms
+/// passed to `cargo generate-lockfile --filter-platform`.
+const FILTER_PLATFORMS_KEY: &str = "filter-platforms";
+
 impl Resolve {
     pub fn new(
         graph: Graph<PackageId, HashSet<Dependency>>,
@@ -187,6 +198,7 @@ impl Resolve {
             replacements,
             features,
             checksums,
+            content_checksums: HashMap::new(),
             metadata,
             unused_patches,
             reverse_replacements,
@@ -365,8 +377,26 @@ unable to verify that `{0}` is the same as when the lockfile was generated
         PackageIdSpec::query_str(spec, self.iter())
     }
 
+    /// Finds the packages selected by `specs`.
+    ///
+    /// Glob specs select every matching package, and must match at least one.
     pub fn specs_to_ids(&self, specs: &[PackageIdSpec]) -> CargoResult<Vec<PackageId>> {
-        specs.iter().map(|s| s.query(self.iter())).collect()
+        let mut ids = Vec::new();
+        for spec in specs {
+            if spec.is_glob() {
+                let mut matched: Vec<_> = self.iter().filter(|id| spec.matches(*id)).collect();
+                if matched.is_empty() {
+                    anyhow::bail!("package pattern `{spec}` did not match any packages");
+                }
+                matched.sort();
+                ids.extend(matched);
+            } else {
+                ids.push(spec.query(self.iter())?);
+            }
+        }
+        let mut seen = HashSet::new();
+        ids.retain(|id| seen.insert(*id));
+        Ok(ids)
     }
 
     pub fn unused_patches(&self) -> &[PackageId] {
@@ -377,10 +407,38 @@ unable to verify that `{0}` is the same as when the lockfile was generated
         &self.checksums
     }
 
+    pub fn content_checksums(&self) -> &HashMap<PackageId, String> {
+        &self.content_checksums
+    }
+
+    pub fn set_content_checksums(&mut self, content_checksums: HashMap<PackageId, String>) {
+        self.content_checksums = content_checksums;
+    }
+
     pub fn metadata(&self) -> &Metadata {
         &self.metadata
     }
 
+    /// The platforms this resolve is restricted to, see
+    /// [`Resolve::set_filter_platforms`].
+    pub fn filter_platforms(&self) -> Vec<String> {
+        self.metadata
+            .get(FILTER_PLATFORMS_KEY)
+            .map(|platforms| platforms.split(',').map(str::to_owned).collect())
+            .unwrap_or_default()
+    }
+
+    /// Records the platforms this resolve is restricted to in the `[metadata]`
+    /// table, so later resolves from the lock file stay restricted to them.
+    pub fn set_filter_platforms(&mut self, platforms: &[String]) {
+        if platforms.is_empty() {
+            self.metadata.remove(FILTER_PLATFORMS_KEY);
+        } else {
+            self.metadata
+                .insert(FILTER_PLATFORMS_KEY.to_owned(), platforms.join(","));
+        }
+    }
+
     pub fn extern_crate_name_and_dep_name(
         &self,
         from: PackageId,
@@ -446,6 +504,13 @@ unable to verify that `{0}` is the same as when the lockfile was generated
     pub fn summary(&self, pkg_id: PackageId) -> &Summary {
         &self.summaries[&pkg_id]
     }
+
+    /// Whether this resolve knows the summaries and dependency declarations
+    /// of its packages, which isn't the case when it is loaded from a lock
+    /// file.
+    pub fn has_summaries(&self) -> bool {
+        !self.summaries.is_empty()
+    }
 }
 
 impl PartialEq for Resolve {
@@ -459,7 +524,7 @@ impl PartialEq for Resolve {
         compare! {
             // fields to compare
             graph replacements reverse_replacements features
-            checksums metadata unused_patches public_dependencies summaries
+            checksums content_checksums metadata unused_patches public_dependencies summaries
             |
             // fields to ignore
             version
@@ -3,6 +3,7 @@ use crate::core::{Dependency, PackageId, Summary};
 use crate::util::errors::CargoResult;
 use crate::util::interning::InternedString;
 use crate::util::GlobalContext;
+use cargo_platform::Cfg;
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Range;
@@ -55,6 +56,7 @@ impl ResolverProgress {
         // to amortize the cost of the current time lookup.
         self.ticks += 1;
         if let Some(config) = gctx {
+            config.cancellation_token().check()?;
             if config.shell().is_err_tty()
                 && !self.printed
                 && self.ticks % 1000 == 0
@@ -120,9 +122,10 @@ impl ResolveBehavior {
         match resolver {
             "1" => Ok(ResolveBehavior::V1),
             "2" => Ok(ResolveBehavior::V2),
-            "3" => Ok(ResolveBehavior::V3),
+            // The MSRV-aware resolver, under a name saying what it does.
+            "3" | "msrv" => Ok(ResolveBehavior::V3),
             s => anyhow::bail!(
-                "`resolver` setting `{}` is not valid, valid options are \"1\" or \"2\"",
+                "`resolver` setting `{}` is not valid, valid options are \"1\", \"2\", \"3\" or \"msrv\"",
                 s
             ),
         }
@@ -138,6 +141,41 @@ impl ResolveBehavior {
     }
 }
 
+/// Restricts the resolve to the dependencies used on some platforms.
+///
+/// Dependencies declared under `[target.'cfg(..)'.dependencies]` which don't
+/// apply to any of the platforms are left out, along with everything only they
+/// pull in. With no platforms, every dependency is resolved.
+#[derive(Clone, Debug, Default)]
+pub struct PlatformFilter {
+    /// Target names along with their `cfg` values.
+    platforms: Vec<(String, Vec<Cfg>)>,
+}
+
+impl PlatformFilter {
+    pub fn new(platforms: Vec<(String, Vec<Cfg>)>) -> PlatformFilter {
+        PlatformFilter { platforms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.platforms.is_empty()
+    }
+
+    /// Whether `dep` is used on any of the platforms.
+    pub fn matches(&self, dep: &Dependency) -> bool {
+        if self.platforms.is_empty() {
+            return true;
+        }
+        match dep.platform() {
+            Some(platform) => self
+                .platforms
+                .iter()
+                .any(|(name, cfg)| platform.matches(name, cfg)),
+            None => true,
+        }
+    }
+}
+
 /// Options for how the resolve should work.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct ResolveOpts {
@@ -301,6 +339,10 @@ pub enum ConflictReason {
     /// optional dependency is "hidden" using namespaced `dep:` syntax.
     NonImplicitDependencyAsFeature(InternedString),
 
+    /// A dependency selected a feature profile which the candidate doesn't
+    /// declare in `[features.profiles]`.
+    MissingFeatureProfile(InternedString),
+
     // TODO: needs more info for `activation_error`
     // TODO: needs more info for `find_candidate`
     /// pub dep error
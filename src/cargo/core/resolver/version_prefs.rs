@@ -1,10 +1,13 @@
 //! This module implements support for preferring some versions of a package
 //! over other versions.
 
+use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use cargo_util_schemas::core::PartialVersion;
+use semver::VersionReq;
+use time::OffsetDateTime;
 
 use crate::core::{Dependency, PackageId, Summary};
 use crate::util::interning::InternedString;
@@ -21,7 +24,13 @@ pub struct VersionPreferences {
     try_to_use: HashSet<PackageId>,
     prefer_patch_deps: HashMap<InternedString, HashSet<Dependency>>,
     version_ordering: VersionOrdering,
+    direct_version_ordering: Option<VersionOrdering>,
     max_rust_version: Option<PartialVersion>,
+    max_publish_time: Option<OffsetDateTime>,
+    /// Registry packages none of whose candidates recorded when they were
+    /// published, so `max_publish_time` couldn't filter them.
+    without_publish_time: RefCell<BTreeSet<InternedString>>,
+    pins: HashMap<InternedString, VersionReq>,
 }
 
 #[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
@@ -49,10 +58,46 @@ impl VersionPreferences {
         self.version_ordering = ordering;
     }
 
+    /// Overrides the version ordering for the direct dependencies of the
+    /// packages being resolved.
+    pub fn direct_version_ordering(&mut self, ordering: VersionOrdering) {
+        self.direct_version_ordering = Some(ordering);
+    }
+
+    /// The version ordering for the direct dependencies of the packages being
+    /// resolved, if it differs from [`Self::version_ordering`].
+    pub fn direct_ordering(&self) -> Option<VersionOrdering> {
+        self.direct_version_ordering
+    }
+
     pub fn max_rust_version(&mut self, ver: Option<PartialVersion>) {
         self.max_rust_version = ver;
     }
 
+    /// Only consider versions published before `time`, if the registry
+    /// recorded when they were published.
+    pub fn max_publish_time(&mut self, time: Option<OffsetDateTime>) {
+        self.max_publish_time = time;
+    }
+
+    /// Returns the registry packages [`Self::max_publish_time`] had no effect
+    /// on, because none of their versions recorded when it was published.
+    pub fn packages_without_publish_time(&self) -> BTreeSet<InternedString> {
+        self.without_publish_time.borrow().clone()
+    }
+
+    /// Only consider versions of registry packages named `name` matching
+    /// `req`.
+    pub fn pin(&mut self, name: InternedString, req: VersionReq) {
+        self.pins.insert(name, req);
+    }
+
+    /// The requirement [`VersionPreferences::pin`] applies to the registry
+    /// packages named `name`, if any.
+    pub fn pinned_req(&self, name: InternedString) -> Option<&VersionReq> {
+        self.pins.get(&name)
+    }
+
     /// Sort (and filter) the given vector of summaries in-place
     ///
     /// Note: all summaries presumed to be for the same package.
@@ -63,6 +108,8 @@ impl VersionPreferences {
     /// 3. `first_version`, falling back to [`VersionPreferences::version_ordering`] when `None`
     ///
     /// Filtering:
+    /// - [`VersionPreferences::pin`]
+    /// - [`VersionPreferences::max_publish_time`], except for preferred packages
     /// - `first_version`
     pub fn sort_summaries(
         &self,
@@ -77,6 +124,28 @@ impl VersionPreferences {
                     .map(|deps| deps.iter().any(|d| d.matches_id(*pkg_id)))
                     .unwrap_or(false)
         };
+        if !self.pins.is_empty() {
+            summaries.retain(|s| {
+                !s.source_id().is_registry()
+                    || self
+                        .pins
+                        .get(&s.name())
+                        .map_or(true, |req| req.matches(s.version()))
+            });
+        }
+        if let Some(max_publish_time) = self.max_publish_time {
+            if summaries
+                .iter()
+                .all(|s| s.source_id().is_registry() && s.pubtime().is_none())
+            {
+                if let Some(s) = summaries.first() {
+                    self.without_publish_time.borrow_mut().insert(s.name());
+                }
+            }
+            summaries.retain(|s| {
+                should_prefer(&s.package_id()) || s.pubtime().map_or(true, |t| t < max_publish_time)
+            });
+        }
         summaries.sort_unstable_by(|a, b| {
             let prefer_a = should_prefer(&a.package_id());
             let prefer_b = should_prefer(&b.package_id());
@@ -267,6 +336,39 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_max_publish_time() {
+        let time = |t: &str| {
+            OffsetDateTime::parse(t, &time::format_description::well_known::Rfc3339).unwrap()
+        };
+        let published = |version: &str, pubtime: Option<&str>| {
+            let mut summary = summ("foo", version, None);
+            if let Some(pubtime) = pubtime {
+                summary.set_pubtime(time(pubtime));
+            }
+            summary
+        };
+
+        let mut vp = VersionPreferences::default();
+        vp.max_publish_time(Some(time("2024-06-01T00:00:00Z")));
+        vp.prefer_package_id(pkgid("foo", "1.2.3"));
+
+        let mut summaries = vec![
+            published("1.2.4", Some("2024-06-01T00:00:00Z")),
+            published("1.2.3", Some("2024-07-01T00:00:00Z")),
+            published("1.2.2", Some("2024-05-31T23:59:59Z")),
+            published("1.2.1", None),
+            published("1.2.0", Some("2024-01-01T00:00:00Z")),
+        ];
+
+        vp.version_ordering(VersionOrdering::MaximumVersionsFirst);
+        vp.sort_summaries(&mut summaries, None);
+        assert_eq!(
+            describe(&summaries),
+            "foo/1.2.3, foo/1.2.2, foo/1.2.1, foo/1.2.0".to_string()
+        );
+    }
+
     #[test]
     fn test_empty_summaries() {
         let vp = VersionPreferences::default();
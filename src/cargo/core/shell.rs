@@ -18,6 +18,11 @@ pub struct Shell {
     output: ShellOut,
     /// How verbose messages should be.
     verbosity: Verbosity,
+    /// How build progress should be presented.
+    ui: UiMode,
+    /// Where progress is reported as JSON events instead of a progress bar,
+    /// if it is.
+    json_progress: Option<JsonProgress>,
     /// Flag that indicates the current line needs to be cleared before
     /// printing. Used when a progress bar is currently displayed.
     needs_clear: bool,
@@ -58,6 +63,8 @@ impl Shell {
                 stderr_unicode: supports_unicode(&std::io::stderr()),
             },
             verbosity: Verbosity::Verbose,
+            ui: UiMode::Human,
+            json_progress: None,
             needs_clear: false,
             hostname: None,
         }
@@ -68,6 +75,8 @@ impl Shell {
         Shell {
             output: ShellOut::Write(AutoStream::never(out)), // strip all formatting on write
             verbosity: Verbosity::Verbose,
+            ui: UiMode::Human,
+            json_progress: None,
             needs_clear: false,
             hostname: None,
         }
@@ -84,6 +93,8 @@ impl Shell {
     ) -> CargoResult<()> {
         match self.verbosity {
             Verbosity::Quiet => Ok(()),
+            // Progress statuses are summarized by the progress line instead.
+            _ if justified && self.ui == UiMode::Compact && is_progress(status, color) => Ok(()),
             _ => {
                 if self.needs_clear {
                     self.err_erase_line();
@@ -230,6 +241,38 @@ impl Shell {
         self.verbosity
     }
 
+    /// Updates the UI mode (human or compact) from a string.
+    pub fn set_ui(&mut self, ui: Option<&str>) -> CargoResult<()> {
+        self.ui = ui.map(|u| u.parse()).transpose()?.unwrap_or(UiMode::Human);
+        Ok(())
+    }
+
+    /// Gets the UI mode of the shell.
+    pub fn ui(&self) -> UiMode {
+        self.ui
+    }
+
+    /// Sets where progress is reported as JSON events.
+    pub fn set_json_progress(&mut self, json_progress: JsonProgress) {
+        self.json_progress = Some(json_progress);
+    }
+
+    /// Gets whether progress is reported as JSON events.
+    pub fn json_progress(&self) -> bool {
+        self.json_progress.is_some()
+    }
+
+    /// Prints a JSON progress event, to stdout or stderr depending on where
+    /// progress is reported.
+    pub fn print_json_progress(&mut self, msg: &str) -> CargoResult<()> {
+        match self.json_progress {
+            Some(JsonProgress::Stdout) => writeln!(self.out(), "{}", msg)?,
+            Some(JsonProgress::Stderr) => writeln!(self.err(), "{}", msg)?,
+            None => {}
+        }
+        Ok(())
+    }
+
     /// Updates the color choice (always, never, or auto) from a string..
     pub fn set_color_choice(&mut self, color: Option<&str>) -> CargoResult<()> {
         if let ShellOut::Stream {
@@ -511,6 +554,49 @@ pub enum Verbosity {
     Quiet,
 }
 
+/// How build progress should be presented.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiMode {
+    /// Print a status line for each step, e.g. `Compiling foo`.
+    Human,
+    /// Only keep a single status line updated, with the current crate, the
+    /// number of finished units and an estimate of the remaining time.
+    /// Warnings and errors are still printed.
+    Compact,
+}
+
+impl std::str::FromStr for UiMode {
+    type Err = anyhow::Error;
+    fn from_str(ui: &str) -> Result<Self, Self::Err> {
+        let ui = match ui {
+            "human" => UiMode::Human,
+            "compact" => UiMode::Compact,
+            arg => anyhow::bail!(
+                "argument for --ui must be human or compact, but found `{}`",
+                arg
+            ),
+        };
+        Ok(ui)
+    }
+}
+
+/// Whether a status only reports the progress of Cargo, like `Compiling foo`,
+/// and isn't printed with `--ui compact`. Warnings, errors and the reasons why
+/// units are rebuilt are still printed.
+fn is_progress(status: &dyn fmt::Display, color: &Style) -> bool {
+    *color == HEADER && status.to_string() != "Dirty"
+}
+
+/// Where progress is reported as JSON events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonProgress {
+    /// With the other JSON messages, from `--message-format json-progress`.
+    Stdout,
+    /// From `term.progress = "json"`, which applies to every command, so
+    /// it doesn't get mixed with their own output.
+    Stderr,
+}
+
 /// Whether messages should use color output
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ColorChoice {
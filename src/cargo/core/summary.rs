@@ -10,6 +10,7 @@ use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::mem;
 use std::rc::Rc;
+use time::OffsetDateTime;
 
 /// Subset of a `Manifest`. Contains only the most important information about
 /// a package.
@@ -25,9 +26,12 @@ struct Inner {
     package_id: PackageId,
     dependencies: Vec<Dependency>,
     features: Rc<FeatureMap>,
+    feature_profiles: Rc<FeatureProfiles>,
     checksum: Option<String>,
     links: Option<InternedString>,
     rust_version: Option<RustVersion>,
+    deprecated: Option<String>,
+    pubtime: Option<OffsetDateTime>,
 }
 
 impl Summary {
@@ -57,9 +61,12 @@ impl Summary {
                 package_id: pkg_id,
                 dependencies,
                 features: Rc::new(feature_map),
+                feature_profiles: Default::default(),
                 checksum: None,
                 links: links.map(|l| l.into()),
                 rust_version,
+                deprecated: None,
+                pubtime: None,
             }),
         })
     }
@@ -83,6 +90,12 @@ impl Summary {
         &self.inner.features
     }
 
+    /// The named feature sets from `[features.profiles]`, which dependents
+    /// can select instead of the default features.
+    pub fn feature_profiles(&self) -> &FeatureProfiles {
+        &self.inner.feature_profiles
+    }
+
     pub fn checksum(&self) -> Option<&str> {
         self.inner.checksum.as_deref()
     }
@@ -94,6 +107,18 @@ impl Summary {
         self.inner.rust_version.as_ref()
     }
 
+    /// The reason the registry gave for deprecating this package, if any.
+    ///
+    /// An empty string means the package is deprecated without a reason.
+    pub fn deprecated(&self) -> Option<&str> {
+        self.inner.deprecated.as_deref()
+    }
+
+    /// When this version was published, if the registry recorded it.
+    pub fn pubtime(&self) -> Option<OffsetDateTime> {
+        self.inner.pubtime
+    }
+
     pub fn override_id(mut self, id: PackageId) -> Summary {
         Rc::make_mut(&mut self.inner).package_id = id;
         self
@@ -103,6 +128,18 @@ impl Summary {
         Rc::make_mut(&mut self.inner).checksum = Some(cksum);
     }
 
+    pub fn set_feature_profiles(&mut self, feature_profiles: FeatureProfiles) {
+        Rc::make_mut(&mut self.inner).feature_profiles = Rc::new(feature_profiles);
+    }
+
+    pub fn set_deprecated(&mut self, reason: String) {
+        Rc::make_mut(&mut self.inner).deprecated = Some(reason);
+    }
+
+    pub fn set_pubtime(&mut self, pubtime: OffsetDateTime) {
+        Rc::make_mut(&mut self.inner).pubtime = Some(pubtime);
+    }
+
     pub fn map_dependencies<F>(mut self, f: F) -> Summary
     where
         F: FnMut(Dependency) -> Dependency,
@@ -114,6 +151,20 @@ impl Summary {
         self
     }
 
+    pub fn try_map_dependencies<F>(mut self, f: F) -> CargoResult<Summary>
+    where
+        F: FnMut(Dependency) -> CargoResult<Dependency>,
+    {
+        {
+            let slot = &mut Rc::make_mut(&mut self.inner).dependencies;
+            *slot = mem::take(slot)
+                .into_iter()
+                .map(f)
+                .collect::<CargoResult<_>>()?;
+        }
+        Ok(self)
+    }
+
     pub fn map_source(self, to_replace: SourceId, replace_with: SourceId) -> Summary {
         let me = if self.package_id().source_id() == to_replace {
             let new_id = self.package_id().with_source_id(replace_with);
@@ -418,3 +469,5 @@ impl fmt::Display for FeatureValue {
 }
 
 pub type FeatureMap = BTreeMap<InternedString, Vec<FeatureValue>>;
+/// Maps the name of a feature profile to the features it enables.
+pub type FeatureProfiles = BTreeMap<InternedString, Vec<InternedString>>;
@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::hash_map::{Entry, HashMap};
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
@@ -10,34 +10,41 @@ use itertools::Itertools;
 use tracing::debug;
 use url::Url;
 
-use crate::core::compiler::Unit;
+use crate::core::compiler::{CompileKind, CompileTarget, Unit};
 use crate::core::features::Features;
 use crate::core::registry::PackageRegistry;
 use crate::core::resolver::features::CliFeatures;
 use crate::core::resolver::ResolveBehavior;
 use crate::core::{
-    Dependency, Edition, FeatureValue, PackageId, PackageIdSpec, PackageIdSpecQuery,
+    Dependency, Edition, FeatureValue, PackageId, PackageIdSpec, PackageIdSpecQuery, Resolve,
 };
-use crate::core::{EitherManifest, Package, SourceId, VirtualManifest};
+use crate::core::{EitherManifest, GitReference, Package, SourceId, VirtualManifest};
 use crate::ops;
-use crate::sources::{PathSource, CRATES_IO_INDEX, CRATES_IO_REGISTRY};
+use crate::sources::{GitSource, PathSource, CRATES_IO_INDEX, CRATES_IO_REGISTRY};
+use crate::util::cache_lock::CacheLockMode;
 use crate::util::edit_distance;
 use crate::util::errors::{CargoResult, ManifestError};
 use crate::util::interning::InternedString;
 use crate::util::lints::{
-    analyze_cargo_lints_table, check_im_a_teapot, check_implicit_features, unused_dependencies,
+    analyze_cargo_lints_table, check_deprecated_dependencies, check_im_a_teapot,
+    check_implicit_features, check_multiple_versions, check_stale_dependencies,
+    unused_dependencies,
 };
 use crate::util::toml::{read_manifest, InheritableFields};
 use crate::util::{
     context::CargoResolverConfig, context::CargoResolverPrecedence, context::ConfigRelativePath,
-    Filesystem, GlobalContext, IntoUrl,
+    context::IncompatibleRustVersions, context::ResolutionMode, context::YankedPolicy, Filesystem,
+    GlobalContext, IntoUrl,
 };
 use cargo_util::paths;
 use cargo_util::paths::normalize_path;
+use cargo_util_schemas::core::PartialVersion;
 use cargo_util_schemas::manifest;
 use cargo_util_schemas::manifest::RustVersion;
 use cargo_util_schemas::manifest::{TomlDependency, TomlProfiles};
 use pathdiff::diff_paths;
+use semver::VersionReq;
+use time::OffsetDateTime;
 
 /// The core abstraction in Cargo for working with a workspace of crates.
 ///
@@ -73,6 +80,10 @@ pub struct Workspace<'gctx> {
     members: Vec<PathBuf>,
     member_ids: HashSet<PackageId>,
 
+    // The directories of the members checked out from the pinned `git+` URLs
+    // of `workspace.members`, by URL. They can't be packaged or published.
+    git_members: BTreeMap<String, PathBuf>,
+
     // The subset of `members` that are used by the
     // `build`, `check`, `test`, and `bench` subcommands
     // when no package is selected with `--package` / `-p` and `--workspace`
@@ -106,9 +117,38 @@ pub struct Workspace<'gctx> {
     /// The resolver behavior specified with the `resolver` field.
     resolve_behavior: ResolveBehavior,
     resolve_honors_rust_version: bool,
+    /// Which versions of dependencies the resolver prefers.
+    resolution_mode: ResolutionMode,
+    /// How locked versions which have been yanked are treated.
+    yanked_policy: YankedPolicy,
+    /// Only versions published before this time are considered, set with
+    /// `--before`.
+    max_publish_time: Option<OffsetDateTime>,
+    /// The Rust version the resolver prefers dependencies to be compatible
+    /// with instead of the workspace's, set with `cargo update --rust-version`.
+    max_rust_version: Option<PartialVersion>,
+    /// Platforms the lock file is restricted to, set with
+    /// `cargo generate-lockfile --filter-platform`.
+    filter_platforms: Vec<String>,
 
     /// Workspace-level custom metadata
     custom_metadata: Option<toml::Value>,
+
+    /// The kinds members are built for by default, from
+    /// `workspace.build.targets`.
+    default_kinds: Vec<CompileKind>,
+
+    /// Version requirements applied to any package of the dependency graph
+    /// with that name, from `workspace.dependency-pins`.
+    dependency_pins: BTreeMap<InternedString, VersionReq>,
+
+    /// Versions of packages which must not be part of the dependency graph,
+    /// from `workspace.bans`.
+    bans: BTreeMap<InternedString, VersionReq>,
+
+    /// Whether the lints of the resolved dependency graph were emitted, so
+    /// that they are only emitted by the first resolve of the workspace.
+    resolve_lints_emitted: Cell<bool>,
 }
 
 // Separate structure for tracking loaded packages (to avoid loading anything
@@ -186,6 +226,9 @@ pub struct WorkspaceRootConfig {
     exclude: Vec<String>,
     inheritable_fields: InheritableFields,
     custom_metadata: Option<toml::Value>,
+    build: Option<manifest::TomlWorkspaceBuild>,
+    dependency_pins: Option<BTreeMap<manifest::PackageName, String>>,
+    bans: Option<BTreeMap<manifest::PackageName, String>>,
 }
 
 impl<'gctx> Workspace<'gctx> {
@@ -208,9 +251,12 @@ impl<'gctx> Workspace<'gctx> {
             ws.root_manifest = ws.find_root(manifest_path)?;
         }
 
-        ws.custom_metadata = ws
-            .load_workspace_config()?
-            .and_then(|cfg| cfg.custom_metadata);
+        if let Some(cfg) = ws.load_workspace_config()? {
+            ws.default_kinds = cfg.default_kinds()?;
+            ws.dependency_pins = parse_version_reqs(&cfg.dependency_pins, "dependency-pins")?;
+            ws.bans = parse_version_reqs(&cfg.bans, "bans")?;
+            ws.custom_metadata = cfg.custom_metadata;
+        }
         ws.find_members()?;
         ws.set_resolve_behavior()?;
         ws.validate()?;
@@ -229,6 +275,7 @@ impl<'gctx> Workspace<'gctx> {
             target_dir: None,
             members: Vec::new(),
             member_ids: HashSet::new(),
+            git_members: BTreeMap::new(),
             default_members: Vec::new(),
             is_ephemeral: false,
             require_optional_deps: true,
@@ -236,7 +283,16 @@ impl<'gctx> Workspace<'gctx> {
             ignore_lock: false,
             resolve_behavior: ResolveBehavior::V1,
             resolve_honors_rust_version: false,
+            resolution_mode: ResolutionMode::Maximum,
+            yanked_policy: YankedPolicy::Allow,
+            max_publish_time: None,
+            max_rust_version: None,
+            filter_platforms: Vec::new(),
             custom_metadata: None,
+            default_kinds: Vec::new(),
+            dependency_pins: BTreeMap::new(),
+            bans: BTreeMap::new(),
+            resolve_lints_emitted: Cell::new(false),
         }
     }
 
@@ -314,9 +370,40 @@ impl<'gctx> Workspace<'gctx> {
                 }
             }
         }
+        // Unlike the rest of the `resolver` table, this is stable.
+        let resolution = self
+            .gctx()
+            .get::<Option<ResolutionMode>>("resolver.resolution")?;
+        self.resolution_mode = if self.gctx().cli_unstable().minimal_versions {
+            ResolutionMode::Minimal
+        } else if self.gctx().cli_unstable().direct_minimal_versions {
+            ResolutionMode::DirectMinimal
+        } else {
+            resolution.unwrap_or_default()
+        };
+        if self.gctx().cli_unstable().yanked_policy {
+            self.yanked_policy = self
+                .gctx()
+                .get::<Option<YankedPolicy>>("resolver.yanked")?
+                .unwrap_or_default();
+        }
         match self.gctx().get::<CargoResolverConfig>("resolver") {
+            Ok(CargoResolverConfig {
+                incompatible_rust_versions: Some(incompatible),
+                ..
+            }) => {
+                if self.gctx().cli_unstable().msrv_policy {
+                    self.resolve_honors_rust_version =
+                        incompatible == IncompatibleRustVersions::Fallback;
+                } else {
+                    self.gctx()
+                        .shell()
+                        .warn("ignoring `resolver` config table without `-Zmsrv-policy`")?;
+                }
+            }
             Ok(CargoResolverConfig {
                 something_like_precedence: Some(precedence),
+                incompatible_rust_versions: None,
             }) => {
                 if self.gctx().cli_unstable().msrv_policy {
                     self.resolve_honors_rust_version =
@@ -329,6 +416,7 @@ impl<'gctx> Workspace<'gctx> {
             }
             Ok(CargoResolverConfig {
                 something_like_precedence: None,
+                incompatible_rust_versions: None,
             }) => {}
             Err(err) => {
                 if self.gctx().cli_unstable().msrv_policy {
@@ -428,6 +516,16 @@ impl<'gctx> Workspace<'gctx> {
         self.packages.get(self.root_manifest())
     }
 
+    /// Returns the directories in which the globs of `workspace.members`
+    /// look for members, whose modification time changes when a member is
+    /// added or removed.
+    pub fn member_glob_dirs(&self) -> CargoResult<Vec<PathBuf>> {
+        match self.root_maybe().workspace_config() {
+            WorkspaceConfig::Root(root_config) => root_config.member_glob_dirs(),
+            WorkspaceConfig::Member { .. } => Ok(Vec::new()),
+        }
+    }
+
     pub fn target_dir(&self) -> Filesystem {
         self.target_dir
             .clone()
@@ -462,7 +560,40 @@ impl<'gctx> Workspace<'gctx> {
         let config_patch: Option<
             BTreeMap<String, BTreeMap<String, TomlDependency<ConfigRelativePath>>>,
         > = self.gctx.get("patch")?;
+        let mut patch = self.patch_from_config(config_patch, "[patch]")?;
+
+        if self.gctx.cli_unstable().patch_sets {
+            let enabled: Vec<String> = self
+                .gctx
+                .get::<Option<Vec<String>>>("enable-patch-sets")?
+                .unwrap_or_default();
+            // Later patch sets take precedence over earlier ones, and all of
+            // them over `[patch]`.
+            for name in enabled {
+                let patch_set: Option<
+                    BTreeMap<String, BTreeMap<String, TomlDependency<ConfigRelativePath>>>,
+                > = self.gctx.get(&format!("patch-sets.{name}"))?;
+                if patch_set.is_none() {
+                    bail!("patch set `{name}` is not defined in `[patch-sets]`");
+                }
+                let patch_set =
+                    self.patch_from_config(patch_set, &format!("[patch-sets.{name}]"))?;
+                patch = merge_patches(patch_set, &patch);
+            }
+        }
 
+        Ok(patch)
+    }
+
+    /// Converts a `[patch]`-like table from config, where `what` names the
+    /// table for errors and warnings.
+    fn patch_from_config(
+        &self,
+        config_patch: Option<
+            BTreeMap<String, BTreeMap<String, TomlDependency<ConfigRelativePath>>>,
+        >,
+        what: &str,
+    ) -> CargoResult<HashMap<Url, Vec<Dependency>>> {
         let source = SourceId::for_path(self.root())?;
 
         let mut warnings = Vec::new();
@@ -476,7 +607,7 @@ impl<'gctx> Workspace<'gctx> {
                     .get_registry_index(url)
                     .or_else(|_| url.into_url())
                     .with_context(|| {
-                        format!("[patch] entry `{}` should be a URL or registry name", url)
+                        format!("{what} entry `{}` should be a URL or registry name", url)
                     })?,
             };
             patch.insert(
@@ -503,7 +634,7 @@ impl<'gctx> Workspace<'gctx> {
         for message in warnings {
             self.gctx
                 .shell()
-                .warn(format!("[patch] in cargo config: {}", message))?
+                .warn(format!("{what} in cargo config: {}", message))?
         }
 
         Ok(patch)
@@ -526,29 +657,7 @@ impl<'gctx> Workspace<'gctx> {
             return Ok(from_config);
         }
 
-        // We could just chain from_manifest and from_config,
-        // but that's not quite right as it won't deal with overlaps.
-        let mut combined = from_config;
-        for (url, deps_from_manifest) in from_manifest {
-            if let Some(deps_from_config) = combined.get_mut(url) {
-                // We want from_config to take precedence for each patched name.
-                // NOTE: This is inefficient if the number of patches is large!
-                let mut from_manifest_pruned = deps_from_manifest.clone();
-                for dep_from_config in &mut *deps_from_config {
-                    if let Some(i) = from_manifest_pruned.iter().position(|dep_from_manifest| {
-                        // XXX: should this also take into account version numbers?
-                        dep_from_config.name_in_toml() == dep_from_manifest.name_in_toml()
-                    }) {
-                        from_manifest_pruned.swap_remove(i);
-                    }
-                }
-                // Whatever is left does not exist in manifest dependencies.
-                deps_from_config.extend(from_manifest_pruned);
-            } else {
-                combined.insert(url.clone(), deps_from_manifest.clone());
-            }
-        }
-        Ok(combined)
+        Ok(merge_patches(from_config, from_manifest))
     }
 
     /// Returns an iterator over all packages in this workspace
@@ -618,6 +727,15 @@ impl<'gctx> Workspace<'gctx> {
         self.member_ids.contains(&pkg.package_id())
     }
 
+    /// Returns the `git+` URL of `workspace.members` the member `pkg` was
+    /// checked out from, if it was.
+    pub fn git_member_url(&self, pkg: &Package) -> Option<&str> {
+        self.git_members
+            .iter()
+            .find(|(_, dir)| *dir == pkg.root())
+            .map(|(url, _)| url.as_str())
+    }
+
     pub fn is_ephemeral(&self) -> bool {
         self.is_ephemeral
     }
@@ -659,10 +777,83 @@ impl<'gctx> Workspace<'gctx> {
         self.resolve_honors_rust_version
     }
 
+    pub fn set_resolution_mode(&mut self, resolution_mode: Option<ResolutionMode>) {
+        if let Some(resolution_mode) = resolution_mode {
+            self.resolution_mode = resolution_mode;
+        }
+    }
+
+    pub fn resolution_mode(&self) -> ResolutionMode {
+        self.resolution_mode
+    }
+
+    pub fn yanked_policy(&self) -> YankedPolicy {
+        self.yanked_policy
+    }
+
+    pub fn max_publish_time(&self) -> Option<OffsetDateTime> {
+        self.max_publish_time
+    }
+
+    pub fn set_max_publish_time(&mut self, max_publish_time: Option<OffsetDateTime>) {
+        self.max_publish_time = max_publish_time;
+    }
+
+    pub fn filter_platforms(&self) -> &[String] {
+        &self.filter_platforms
+    }
+
+    pub fn set_filter_platforms(&mut self, filter_platforms: Vec<String>) {
+        self.filter_platforms = filter_platforms;
+    }
+
+    pub fn max_rust_version(&self) -> Option<&PartialVersion> {
+        self.max_rust_version.as_ref()
+    }
+
+    /// Makes the resolver prefer dependencies compatible with `rust_version`,
+    /// regardless of `rust-version` of the members and whether the resolver
+    /// honors it.
+    pub fn set_max_rust_version(&mut self, rust_version: Option<PartialVersion>) {
+        self.max_rust_version = rust_version;
+    }
+
     pub fn custom_metadata(&self) -> Option<&toml::Value> {
         self.custom_metadata.as_ref()
     }
 
+    /// The kinds members are built for when no target is requested and they
+    /// don't set `package.default-target`, as set by
+    /// `workspace.build.targets`.
+    ///
+    /// Empty if not set, in which case members are built for the host.
+    pub fn default_kinds(&self) -> &[CompileKind] {
+        &self.default_kinds
+    }
+
+    /// The version requirements of `workspace.dependency-pins`, which the
+    /// resolver applies to every registry package of the dependency graph
+    /// with that name.
+    pub fn dependency_pins(&self) -> &BTreeMap<InternedString, VersionReq> {
+        &self.dependency_pins
+    }
+
+    /// Whether `pkg_id` satisfies the pin of its name, if any. Pins only
+    /// apply to registry packages.
+    pub fn matches_dependency_pins(&self, pkg_id: PackageId) -> bool {
+        !pkg_id.source_id().is_registry()
+            || self
+                .dependency_pins
+                .get(&pkg_id.name())
+                .map_or(true, |req| req.matches(pkg_id.version()))
+    }
+
+    /// The version requirements of `workspace.bans`. No package matching
+    /// them may be part of the dependency graph.
+    pub fn bans(&self) -> &BTreeMap<InternedString, VersionReq> {
+        &self.bans
+    }
+
     pub fn load_workspace_config(&mut self) -> CargoResult<Option<WorkspaceRootConfig>> {
         // If we didn't find a root, it must mean there is no [workspace] section, and thus no
         // metadata.
@@ -735,10 +926,10 @@ impl<'gctx> Workspace<'gctx> {
         let root_manifest_path = self.root_manifest.clone().unwrap();
 
         let members_paths =
-            workspace_config.members_paths(workspace_config.members.as_ref().unwrap_or(&vec![]))?;
+            self.members_paths(&workspace_config, workspace_config.members.as_deref())?;
         let default_members_paths = if root_manifest_path == self.current_manifest {
             if let Some(ref default) = workspace_config.default_members {
-                Some(workspace_config.members_paths(default)?)
+                Some(self.members_paths(&workspace_config, Some(default))?)
             } else {
                 None
             }
@@ -794,6 +985,89 @@ impl<'gctx> Workspace<'gctx> {
         Ok(())
     }
 
+    /// Expands the globs of `workspace.members` or `workspace.default-members`
+    /// to the directories of the members, checking out the ones with a `git+`
+    /// URL.
+    fn members_paths(
+        &mut self,
+        workspace_config: &WorkspaceRootConfig,
+        members: Option<&[String]>,
+    ) -> CargoResult<Vec<PathBuf>> {
+        let (git_members, globs): (Vec<_>, Vec<_>) = members
+            .unwrap_or_default()
+            .iter()
+            .cloned()
+            .partition(|member| member.starts_with("git+"));
+        let mut paths = workspace_config.members_paths(&globs)?;
+        for url in git_members {
+            let path = self.checkout_git_member(&url).with_context(|| {
+                format!(
+                    "failed to check out workspace member `{url}`\n\
+                     referenced by workspace at `{}`",
+                    self.root_manifest().display()
+                )
+            })?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Checks out the repository of a `git+` URL of `workspace.members` at
+    /// its pinned revision, into the `git-members` directory of the target
+    /// directory, and returns the directory of the member.
+    ///
+    /// The checkout belongs to the workspace, unlike the ones of git
+    /// dependencies, shared by all the workspaces and cleaned by `cargo gc`.
+    ///
+    /// The member is the package at the root of the repository, or the one
+    /// selected with a `package` parameter.
+    fn checkout_git_member(&mut self, url: &str) -> CargoResult<PathBuf> {
+        if let Some(path) = self.git_members.get(url) {
+            return Ok(path.clone());
+        }
+        let source_id = SourceId::from_url(url)?;
+        if !matches!(source_id.git_reference(), Some(GitReference::Rev(_))) {
+            bail!("the revision of the member must be pinned with a `rev` parameter");
+        }
+        let package = url
+            .into_url()?
+            .query_pairs()
+            .find(|(key, _)| key == "package")
+            .map(|(_, name)| name.into_owned());
+
+        let packages = {
+            let _lock = self
+                .gctx
+                .acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+            let mut source = GitSource::new(source_id, self.gctx)?;
+            let checkouts = self.target_dir().join("git-members");
+            source.set_checkouts_path(checkouts.into_path_unlocked());
+            source.read_packages()?
+        };
+        let depth = |pkg: &Package| pkg.root().components().count();
+        let candidates = match &package {
+            Some(name) => packages
+                .iter()
+                .filter(|pkg| pkg.name().as_str() == name)
+                .collect::<Vec<_>>(),
+            None => {
+                let min = packages.iter().map(depth).min().unwrap_or_default();
+                packages.iter().filter(|pkg| depth(pkg) == min).collect()
+            }
+        };
+        let pkg = match (candidates.as_slice(), package) {
+            ([pkg], _) => *pkg,
+            ([], Some(name)) => bail!("the repository has no package named `{name}`"),
+            ([], None) => bail!("the repository has no package"),
+            (_, _) => {
+                bail!("the repository has several packages, select one with a `package` parameter")
+            }
+        };
+        let path = pkg.root().to_path_buf();
+        self.git_members.insert(url.to_string(), path.clone());
+        Ok(path)
+    }
+
     fn find_path_deps(
         &mut self,
         manifest_path: &Path,
@@ -952,6 +1226,14 @@ impl<'gctx> Workspace<'gctx> {
 
     fn validate_members(&mut self) -> CargoResult<()> {
         for member in self.members.clone() {
+            // Members checked out from git aren't below the root.
+            if self
+                .git_members
+                .values()
+                .any(|dir| member.parent() == Some(dir))
+            {
+                continue;
+            }
             let root = self.find_root(&member)?;
             if root == self.root_manifest {
                 continue;
@@ -1183,17 +1465,7 @@ impl<'gctx> Workspace<'gctx> {
 
     pub fn emit_lints(&self, pkg: &Package, path: &Path) -> CargoResult<()> {
         let mut error_count = 0;
-        let toml_lints = pkg
-            .manifest()
-            .resolved_toml()
-            .lints
-            .clone()
-            .map(|lints| lints.lints)
-            .unwrap_or(manifest::TomlLints::default());
-        let cargo_lints = toml_lints
-            .get("cargo")
-            .cloned()
-            .unwrap_or(manifest::TomlToolLints::default());
+        let cargo_lints = cargo_lints(pkg);
 
         let ws_contents = match self.root_maybe() {
             MaybePackage::Package(pkg) => pkg.manifest().contents(),
@@ -1227,6 +1499,45 @@ impl<'gctx> Workspace<'gctx> {
         }
     }
 
+    /// Emits the lints of every workspace member that need the resolved
+    /// dependency graph, such as deprecated dependencies.
+    ///
+    /// They are only emitted by the first resolve of the workspace, commands
+    /// resolving it again don't repeat them.
+    ///
+    /// `registry` is used to look up the other versions of the dependencies.
+    pub fn emit_resolve_lints(
+        &self,
+        resolve: &Resolve,
+        registry: &mut PackageRegistry<'_>,
+    ) -> CargoResult<()> {
+        if self.resolve_lints_emitted.replace(true) {
+            return Ok(());
+        }
+        let mut error_count = 0;
+        let members: Vec<_> = self.members().map(|pkg| (pkg, cargo_lints(pkg))).collect();
+        check_deprecated_dependencies(&members, resolve, &mut error_count, self.gctx)?;
+        for (pkg, cargo_lints) in &members {
+            check_stale_dependencies(
+                pkg,
+                cargo_lints,
+                resolve,
+                registry,
+                &mut error_count,
+                self.gctx,
+            )?;
+        }
+        check_multiple_versions(&members, resolve, &mut error_count, self.gctx)?;
+        if error_count > 0 {
+            Err(crate::util::errors::AlreadyPrintedError::new(anyhow!(
+                "encountered {error_count} errors(s) while running lints"
+            ))
+            .into())
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn set_target_dir(&mut self, target_dir: Filesystem) {
         self.target_dir = Some(target_dir);
     }
@@ -1738,6 +2049,9 @@ impl WorkspaceRootConfig {
         exclude: &Option<Vec<String>>,
         inheritable: &Option<InheritableFields>,
         custom_metadata: &Option<toml::Value>,
+        build: &Option<manifest::TomlWorkspaceBuild>,
+        dependency_pins: &Option<BTreeMap<manifest::PackageName, String>>,
+        bans: &Option<BTreeMap<manifest::PackageName, String>>,
     ) -> WorkspaceRootConfig {
         WorkspaceRootConfig {
             root_dir: root_dir.to_path_buf(),
@@ -1746,8 +2060,32 @@ impl WorkspaceRootConfig {
             exclude: exclude.clone().unwrap_or_default(),
             inheritable_fields: inheritable.clone().unwrap_or_default(),
             custom_metadata: custom_metadata.clone(),
+            build: build.clone(),
+            dependency_pins: dependency_pins.clone(),
+            bans: bans.clone(),
         }
     }
+
+    /// Parses `workspace.build.targets`, where `host` stands for the host
+    /// platform.
+    fn default_kinds(&self) -> CargoResult<Vec<CompileKind>> {
+        let targets = self.build.as_ref().and_then(|b| b.targets.as_deref());
+        let mut kinds = Vec::new();
+        for target in targets.unwrap_or_default() {
+            let kind = if target == "host" {
+                CompileKind::Host
+            } else {
+                CompileKind::Target(CompileTarget::new(target).with_context(|| {
+                    format!("invalid target `{target}` in `workspace.build.targets`")
+                })?)
+            };
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+        Ok(kinds)
+    }
+
     /// Checks the path against the `excluded` list.
     ///
     /// This method does **not** consider the `members` list.
@@ -1771,6 +2109,28 @@ impl WorkspaceRootConfig {
         self.members.is_some()
     }
 
+    fn member_glob_dirs(&self) -> CargoResult<Vec<PathBuf>> {
+        let is_glob = |path: &Path| path.to_str().map_or(false, |s| s.contains(['*', '?', '[']));
+        let mut dirs = Vec::new();
+        for member in self.members.iter().flatten() {
+            let pattern = self.root_dir.join(member);
+            if !is_glob(&pattern) {
+                continue;
+            }
+            // Members may be added in any of the directories matched by the
+            // components of the glob, up to its first component without one.
+            for dir in pattern.ancestors().skip(1) {
+                if !is_glob(dir) {
+                    dirs.push(dir.to_path_buf());
+                    break;
+                }
+                let expanded = Self::expand_member_path(dir)?;
+                dirs.extend(expanded.into_iter().filter(|path| path.is_dir()));
+            }
+        }
+        Ok(dirs)
+    }
+
     fn members_paths(&self, globs: &[String]) -> CargoResult<Vec<PathBuf>> {
         let mut expanded_list = Vec::new();
 
@@ -1815,6 +2175,36 @@ impl WorkspaceRootConfig {
     }
 }
 
+/// The `[lints.cargo]` table of `pkg`, or an empty table if it has none.
+fn cargo_lints(pkg: &Package) -> manifest::TomlToolLints {
+    pkg.manifest()
+        .resolved_toml()
+        .lints
+        .as_ref()
+        .and_then(|lints| lints.lints.get("cargo"))
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Parses a `workspace.<key>` table of package names to version
+/// requirements.
+fn parse_version_reqs(
+    table: &Option<BTreeMap<manifest::PackageName, String>>,
+    key: &str,
+) -> CargoResult<BTreeMap<InternedString, VersionReq>> {
+    let mut reqs = BTreeMap::new();
+    for (name, req) in table.iter().flatten() {
+        let parsed = req.parse::<VersionReq>().with_context(|| {
+            format!(
+                "failed to parse the version requirement `{req}` for `{name}` \
+                 in `workspace.{key}`"
+            )
+        })?;
+        reqs.insert(InternedString::new(name), parsed);
+    }
+    Ok(reqs)
+}
+
 pub fn resolve_relative_path(
     label: &str,
     old_root: &Path,
@@ -1857,6 +2247,36 @@ pub fn find_workspace_root(
     })
 }
 
+/// Combines two sets of patches, where `high` takes precedence over `low` for
+/// each patched name.
+fn merge_patches(
+    high: HashMap<Url, Vec<Dependency>>,
+    low: &HashMap<Url, Vec<Dependency>>,
+) -> HashMap<Url, Vec<Dependency>> {
+    // We could just chain `high` and `low`,
+    // but that's not quite right as it won't deal with overlaps.
+    let mut combined = high;
+    for (url, deps_from_low) in low {
+        if let Some(deps_from_high) = combined.get_mut(url) {
+            // NOTE: This is inefficient if the number of patches is large!
+            let mut from_low_pruned = deps_from_low.clone();
+            for dep_from_high in &mut *deps_from_high {
+                if let Some(i) = from_low_pruned.iter().position(|dep_from_low| {
+                    // XXX: should this also take into account version numbers?
+                    dep_from_high.name_in_toml() == dep_from_low.name_in_toml()
+                }) {
+                    from_low_pruned.swap_remove(i);
+                }
+            }
+            // Whatever is left does not exist in `high`.
+            deps_from_high.extend(from_low_pruned);
+        } else {
+            combined.insert(url.clone(), deps_from_low.clone());
+        }
+    }
+    combined
+}
+
 /// Finds the path of the root of the workspace.
 ///
 /// This uses a callback to determine if the given path tells us what the
@@ -7,6 +7,7 @@ use std::collections::BTreeSet;
 use std::collections::VecDeque;
 use std::fmt::Write;
 use std::path::Path;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::Context as _;
@@ -56,6 +57,9 @@ pub struct AddOptions<'a> {
     pub dry_run: bool,
     /// Whether the minimum supported Rust version should be considered during resolution
     pub honor_rust_version: Option<bool>,
+    /// Whether to add the dependencies to `workspace.dependencies` and inherit them, or to the
+    /// package's own table, instead of following what is already in the workspace
+    pub workspace_table: Option<bool>,
 }
 
 /// Add dependencies to a manifest
@@ -96,6 +100,7 @@ pub fn add(workspace: &Workspace<'_>, options: &AddOptions<'_>) -> CargoResult<(
                     &options.spec,
                     &options.section,
                     options.honor_rust_version,
+                    options.workspace_table,
                     options.gctx,
                     &mut registry,
                 )
@@ -114,7 +119,19 @@ pub fn add(workspace: &Workspace<'_>, options: &AddOptions<'_>) -> CargoResult<(
                 }))
             })
         });
-    for dep in deps {
+    // Other manifests of the workspace modified by `--workspace-table`.
+    let mut other_manifests = BTreeMap::new();
+    for mut dep in deps {
+        if options.workspace_table == Some(true) {
+            hoist_to_workspace(
+                workspace,
+                &mut manifest,
+                &mut other_manifests,
+                &mut dep.dep,
+                &options.section,
+                &mut options.gctx.shell(),
+            )?;
+        }
         print_action_msg(&mut options.gctx.shell(), &dep, &dep_table)?;
         if let Some(Source::Path(src)) = dep.source() {
             if src.path == manifest.path.parent().unwrap_or_else(|| Path::new("")) {
@@ -219,6 +236,12 @@ pub fn add(workspace: &Workspace<'_>, options: &AddOptions<'_>) -> CargoResult<(
         manifest.gc_dep(dep.toml_key());
     }
 
+    // Only keep the manifests which were actually changed.
+    let other_manifests: Vec<_> = other_manifests
+        .into_values()
+        .filter(|other| paths::read(&other.path).map_or(true, |raw| raw != other.to_string()))
+        .collect();
+
     if was_sorted {
         if let Some(table) = manifest
             .get_table_mut(&dep_table)
@@ -237,12 +260,21 @@ pub fn add(workspace: &Workspace<'_>, options: &AddOptions<'_>) -> CargoResult<(
                 manifest.path.display()
             );
         }
+        if let Some(other) = other_manifests.first() {
+            anyhow::bail!(
+                "the manifest file {} needs to be updated but --locked was passed to prevent this",
+                other.path.display()
+            );
+        }
     }
 
     if options.dry_run {
         options.gctx.shell().warn("aborting add due to dry run")?;
     } else {
         manifest.write()?;
+        for other in &other_manifests {
+            other.write_any()?;
+        }
     }
 
     Ok(())
@@ -289,6 +321,7 @@ fn resolve_dependency(
     spec: &Package,
     section: &DepTable,
     honor_rust_version: Option<bool>,
+    workspace_table: Option<bool>,
     gctx: &GlobalContext,
     registry: &mut PackageRegistry<'_>,
 ) -> CargoResult<DependencyUI> {
@@ -415,7 +448,11 @@ fn resolve_dependency(
     }
 
     if let Some(Source::Workspace(_)) = dependency.source() {
-        check_invalid_ws_keys(dependency.toml_key(), arg)?;
+        if workspace_table == Some(false) {
+            dependency = inline_workspace_dep(dependency, ws.root_manifest())?;
+        } else {
+            check_invalid_ws_keys(dependency.toml_key(), arg)?;
+        }
     }
 
     let version_required = dependency.source().and_then(|s| s.as_registry()).is_some();
@@ -452,6 +489,177 @@ fn resolve_dependency(
     Ok(dependency)
 }
 
+/// Copies the source of the workspace dependency into `dependency`, for it to
+/// no longer inherit it.
+fn inline_workspace_dep(
+    mut dependency: Dependency,
+    root_manifest: &Path,
+) -> CargoResult<Dependency> {
+    let toml_key = dependency.toml_key().to_owned();
+    let ws_dep = find_workspace_dep(&toml_key, root_manifest)?;
+    let Some(source) = ws_dep.source else {
+        anyhow::bail!("`workspace.dependencies.{toml_key}` has no source");
+    };
+    if let Some(mut features) = ws_dep.features {
+        features.extend(dependency.features.take().into_iter().flatten());
+        dependency.features = Some(features);
+    }
+    if dependency.default_features.is_none() {
+        dependency.default_features = ws_dep.default_features;
+    }
+    dependency.registry = ws_dep.registry;
+    dependency.rename = (ws_dep.name != toml_key).then_some(toml_key);
+    dependency.name = ws_dep.name;
+    Ok(dependency.set_source(source))
+}
+
+/// Moves the source of `dep` to `workspace.dependencies` for `dep` to inherit
+/// it, and makes the same dependency of the other tables of the workspace
+/// inherit it too, when it wouldn't change what they depend on.
+fn hoist_to_workspace(
+    ws: &Workspace<'_>,
+    manifest: &mut LocalManifest,
+    other_manifests: &mut BTreeMap<PathBuf, LocalManifest>,
+    dep: &mut Dependency,
+    section: &DepTable,
+    shell: &mut Shell,
+) -> CargoResult<()> {
+    let toml_key = dep.toml_key().to_owned();
+    let ws_dep = if matches!(dep.source(), Some(Source::Workspace(_))) {
+        find_workspace_dep(&toml_key, ws.root_manifest())?
+    } else {
+        let root = manifest_mut(ws.root_manifest(), manifest, other_manifests)?;
+        if !root.data.contains_key("workspace") {
+            anyhow::bail!(
+                "cannot add `{toml_key}` to `workspace.dependencies`, \
+                 {} is not the manifest of a workspace",
+                root.path.display()
+            );
+        }
+        let mut ws_dep = Dependency::new(&dep.name);
+        ws_dep.source = dep.source.clone();
+        ws_dep.rename = dep.rename.clone();
+        ws_dep.registry = dep.registry.take();
+        ws_dep.default_features = dep.default_features.take();
+        let ws_table = ["workspace".to_owned(), "dependencies".to_owned()];
+        print_action_msg(shell, &DependencyUI::new(ws_dep.clone()), &ws_table)?;
+        root.insert_into_table(&ws_table, &ws_dep)?;
+        dep.source = Some(WorkspaceSource::new().into());
+        ws_dep
+    };
+
+    for member in ws.members() {
+        let path = member.manifest_path();
+        let is_modified_package = path == manifest.path;
+        let member_manifest = manifest_mut(path, manifest, other_manifests)?;
+        for (table, item) in member_manifest.get_sections() {
+            // The table the dependency is added to is written afterwards.
+            let is_added_table = is_modified_package && table == *section;
+            if is_added_table || item.get(&toml_key).is_none() {
+                continue;
+            }
+            let table_path: Vec<_> = table.to_table().into_iter().map(String::from).collect();
+            let mut other = member_manifest.get_dependency(&table_path, &toml_key)?;
+            if other.name != dep.name || matches!(other.source(), Some(Source::Workspace(_))) {
+                continue;
+            }
+            if !can_inherit(&other, &ws_dep) {
+                shell.warn(format!(
+                    "not updating {toml_key} in {} of `{}` to inherit from the workspace, \
+                     it has another source, version requirement, registry or `default-features`",
+                    section_name(&table_path),
+                    member.name()
+                ))?;
+                continue;
+            }
+            other.registry = None;
+            other.default_features = None;
+            let other = other.set_source(WorkspaceSource::new());
+            member_manifest.insert_into_table(&table_path, &other)?;
+            shell.status(
+                "Updating",
+                format!(
+                    "{toml_key} in {} of `{}` to inherit from the workspace",
+                    section_name(&table_path),
+                    member.name()
+                ),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `dep` can inherit `ws_dep` without changing what it depends on: it
+/// must have the same source, with a version requirement which the minimum
+/// version of the requirement of `ws_dep` matches, the same registry, and the
+/// same `default-features`.
+fn can_inherit(dep: &Dependency, ws_dep: &Dependency) -> bool {
+    let (version, ws_version) = match (dep.source(), ws_dep.source()) {
+        (Some(Source::Registry(src)), Some(Source::Registry(ws_src))) => {
+            (Some(&src.version), Some(&ws_src.version))
+        }
+        (Some(Source::Path(src)), Some(Source::Path(ws_src))) => {
+            if paths::normalize_path(&src.path) != paths::normalize_path(&ws_src.path) {
+                return false;
+            }
+            (src.version.as_ref(), ws_src.version.as_ref())
+        }
+        (Some(Source::Git(src)), Some(Source::Git(ws_src))) => {
+            if (&src.git, &src.branch, &src.tag, &src.rev)
+                != (&ws_src.git, &ws_src.branch, &ws_src.tag, &ws_src.rev)
+            {
+                return false;
+            }
+            (src.version.as_ref(), ws_src.version.as_ref())
+        }
+        _ => return false,
+    };
+    let compatible_version = match (version, ws_version) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(req), Some(ws_req)) => req == ws_req || raises_minimum(req, ws_req),
+    };
+    compatible_version
+        && dep.registry == ws_dep.registry
+        && dep.default_features.unwrap_or(true) == ws_dep.default_features.unwrap_or(true)
+}
+
+/// Whether the minimum version of the plain requirement `ws_req`, like `0.8.5`,
+/// matches `req`, like `0.8`, so that replacing `req` with `ws_req` only
+/// raises its minimum.
+fn raises_minimum(req: &str, ws_req: &str) -> bool {
+    let Ok(req) = semver::VersionReq::parse(req) else {
+        return false;
+    };
+    let Ok(ws_version) = PartialVersion::from_str(ws_req.trim_start_matches('^')) else {
+        return false;
+    };
+    let minimum = semver::Version {
+        major: ws_version.major,
+        minor: ws_version.minor.unwrap_or(0),
+        patch: ws_version.patch.unwrap_or(0),
+        pre: ws_version.pre.unwrap_or_default(),
+        build: Default::default(),
+    };
+    req.matches(&minimum)
+}
+
+/// Returns the manifest at `path`, either the one of the package being
+/// modified, or another one of the workspace.
+fn manifest_mut<'m>(
+    path: &Path,
+    manifest: &'m mut LocalManifest,
+    other_manifests: &'m mut BTreeMap<PathBuf, LocalManifest>,
+) -> CargoResult<&'m mut LocalManifest> {
+    if path == manifest.path {
+        return Ok(manifest);
+    }
+    if !other_manifests.contains_key(path) {
+        other_manifests.insert(path.to_owned(), LocalManifest::try_new(path)?);
+    }
+    Ok(other_manifests.get_mut(path).unwrap())
+}
+
 /// When { workspace = true } you cannot define other keys that configure
 /// the source of the dependency such as `version`, `registry`, `registry-index`,
 /// `path`, `git`, `branch`, `tag`, `rev`, or `package`. You can also not define
@@ -672,6 +880,19 @@ ignoring {dependency}@{latest_version} (which requires rustc {latest_rust_versio
                 }
             }
 
+            if let Some(deprecation) = latest.deprecated() {
+                let latest_version = latest.version();
+                if deprecation.is_empty() {
+                    gctx.shell().warn(format_args!(
+                        "{dependency}@{latest_version} is deprecated by its registry"
+                    ))?;
+                } else {
+                    gctx.shell().warn(format_args!(
+                        "{dependency}@{latest_version} is deprecated by its registry: {deprecation}"
+                    ))?;
+                }
+            }
+
             let mut dep = Dependency::from(latest);
             if let Some(reg_name) = dependency.registry.as_deref() {
                 dep = dep.set_registry(reg_name);
@@ -1001,15 +1222,20 @@ fn print_action_msg(shell: &mut Shell, dep: &DependencyUI, section: &[String]) -
     if dep.public().unwrap_or(false) {
         write!(message, " public")?;
     }
-    let section = if section.len() == 1 {
-        section[0].clone()
-    } else {
-        format!("{} for target `{}`", &section[2], &section[1])
-    };
-    write!(message, " {section}")?;
+    write!(message, " {}", section_name(section))?;
     shell.status("Adding", message)
 }
 
+fn section_name(section: &[String]) -> String {
+    match section {
+        [table] => table.clone(),
+        [target_key, target, table] if target_key == "target" => {
+            format!("{table} for target `{target}`")
+        }
+        _ => section.join("."),
+    }
+}
+
 fn print_dep_table_msg(shell: &mut Shell, dep: &DependencyUI) -> CargoResult<()> {
     if matches!(shell.verbosity(), crate::core::shell::Verbosity::Quiet) {
         return Ok(());
@@ -171,6 +171,12 @@ fn clean_specs(
 
     clean_ctx.progress = Box::new(CleaningPackagesBar::new(clean_ctx.gctx, packages.len()));
 
+    // The fingerprint index would otherwise still describe the fingerprints
+    // removed below.
+    for (_, layout) in &layouts_with_host {
+        clean_ctx.rm_rf(&layout.fingerprint_index())?;
+    }
+
     // Try to reduce the amount of times we iterate over the same target directory by storing away
     // the directories we've iterated over (and cleaned for a given package).
     let mut cleaned_packages: HashMap<_, HashSet<_>> = HashMap::default();
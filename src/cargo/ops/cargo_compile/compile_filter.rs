@@ -81,7 +81,7 @@ impl FilterRule {
     /// Check if a filter is specific.
     ///
     /// Only filters without rules are considered as not specific.
-    fn is_specific(&self) -> bool {
+    pub(crate) fn is_specific(&self) -> bool {
         match *self {
             FilterRule::All => true,
             FilterRule::Just(ref targets) => !targets.is_empty(),
@@ -215,6 +215,21 @@ impl CompileFilter {
         }
     }
 
+    /// Constructs a filter that includes no target, for when they are only
+    /// selected with [`CompileOptions::fuzz`].
+    ///
+    /// [`CompileOptions::fuzz`]: super::CompileOptions::fuzz
+    pub fn no_targets() -> Self {
+        Self::Only {
+            all_targets: false,
+            lib: LibRule::False,
+            bins: FilterRule::none(),
+            examples: FilterRule::none(),
+            tests: FilterRule::none(),
+            benches: FilterRule::none(),
+        }
+    }
+
     /// Indicates if Cargo needs to build any dev dependency.
     pub fn need_dev_deps(&self, mode: CompileMode) -> bool {
         match mode {
@@ -264,7 +279,7 @@ impl CompileFilter {
                             LibRule::False => false,
                         };
                     }
-                    TargetKind::CustomBuild => return false,
+                    TargetKind::CustomBuild | TargetKind::Fuzz => return false,
                 };
                 rule.matches(target)
             }
@@ -0,0 +1,150 @@
+//! Compiling several unrelated workspaces in one invocation, see
+//! `cargo build --manifest-list`.
+//!
+//! The workspaces are built sequentially, one after another, with the same
+//! [`GlobalContext`], so they share the package cache and the download of
+//! dependencies they have in common. Each build has its own job queue and
+//! jobserver: the units of a workspace run in parallel up to the `-j` limit,
+//! but a workspace only starts once the previous one is done, so a small
+//! project leaves the other jobs idle. A failing workspace doesn't stop the
+//! others from being built; its errors are reported as they happen and a
+//! summary is printed at the end.
+//!
+//! As the configuration is only loaded once, from the current directory, a
+//! project with its own `.cargo/config.toml` is rejected rather than built
+//! without it. Likewise, `--package` and `--exclude` are rejected, as the
+//! same package selection can't apply to unrelated projects.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context as _};
+
+use crate::core::Workspace;
+use crate::ops::{CompileOptions, Packages};
+use crate::util::context::GlobalContext;
+use crate::util::CargoResult;
+use cargo_util::paths;
+
+/// Reads a manifest list file.
+///
+/// Each non-empty line is a path to a `Cargo.toml`, relative to the directory
+/// containing the list. Lines starting with `#` are comments.
+pub fn read_manifest_list(path: &Path) -> CargoResult<Vec<PathBuf>> {
+    let contents = paths::read(path)
+        .with_context(|| format!("failed to read manifest list `{}`", path.display()))?;
+    let base = path.parent().unwrap_or_else(|| Path::new(""));
+    let manifests: Vec<_> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| paths::normalize_path(&base.join(line)))
+        .collect();
+    if manifests.is_empty() {
+        bail!("manifest list `{}` is empty", path.display());
+    }
+    for manifest in &manifests {
+        if !manifest.ends_with("Cargo.toml") {
+            bail!(
+                "`{}` in manifest list `{}` is not a path to a Cargo.toml file",
+                manifest.display(),
+                path.display()
+            );
+        }
+        if !manifest.is_file() {
+            bail!(
+                "manifest path `{}` in manifest list `{}` does not exist",
+                manifest.display(),
+                path.display()
+            );
+        }
+    }
+    Ok(manifests)
+}
+
+/// Checks that no configuration file applies to `manifest` other than the ones
+/// loaded from the current directory.
+fn check_no_project_config(gctx: &GlobalContext, manifest: &Path) -> CargoResult<()> {
+    let dir = manifest.parent().unwrap();
+    for ancestor in dir.ancestors() {
+        if gctx.cwd().starts_with(ancestor) {
+            // The configuration of this directory and its parents is loaded.
+            break;
+        }
+        for name in ["config", "config.toml"] {
+            let config = ancestor.join(".cargo").join(name);
+            if config.is_file() {
+                bail!(
+                    "the configuration file `{}` applies to `{}`, \
+                     but projects in a manifest list are built with the configuration of `{}`\n\
+                     Build this project separately, or move its configuration.",
+                    config.display(),
+                    manifest.display(),
+                    gctx.cwd().display()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Compiles every workspace of `manifests` with the same `options`, one at a
+/// time.
+///
+/// Returns an error if any of the workspaces failed to load or build.
+pub fn compile_manifest_list(
+    gctx: &GlobalContext,
+    manifests: &[PathBuf],
+    options: &CompileOptions,
+) -> CargoResult<()> {
+    if let Packages::Packages(_) | Packages::OptOut(_) = options.spec {
+        bail!("`--package` and `--exclude` can't be used with `--manifest-list`");
+    }
+    for manifest in manifests {
+        check_no_project_config(gctx, manifest)?;
+    }
+
+    let mut succeeded = 0;
+    let mut failed = Vec::new();
+    for (i, manifest) in manifests.iter().enumerate() {
+        gctx.shell().status(
+            "Building",
+            format!("[{}/{}] {}", i + 1, manifests.len(), manifest.display()),
+        )?;
+        let result = Workspace::new(manifest, gctx).and_then(|mut ws| {
+            ws.set_resolve_honors_rust_version(options.honor_rust_version);
+            if gctx.cli_unstable().avoid_dev_deps {
+                ws.set_require_optional_deps(false);
+            }
+            super::compile(&ws, options).map(drop)
+        });
+        match result {
+            Ok(()) => succeeded += 1,
+            Err(e) => {
+                crate::display_error(&e, &mut gctx.shell());
+                failed.push(manifest);
+            }
+        }
+    }
+
+    let mut summary = format!("{succeeded} of {} projects built", manifests.len());
+    if !failed.is_empty() {
+        summary.push_str(&format!(
+            ", failed to build {} (see error(s) above)",
+            failed
+                .iter()
+                .map(|manifest| format!("`{}`", manifest.display()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    gctx.shell().status("Summary", summary)?;
+
+    if !failed.is_empty() {
+        bail!(
+            "{} of {} projects failed to build",
+            failed.len(),
+            manifests.len()
+        );
+    }
+    Ok(())
+}
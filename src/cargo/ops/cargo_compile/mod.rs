@@ -36,7 +36,7 @@
 //! ["Cargo Target"]: https://doc.rust-lang.org/nightly/cargo/reference/cargo-targets.html
 
 use cargo_platform::Cfg;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
@@ -46,6 +46,7 @@ use crate::core::compiler::{standard_lib, CrateType, TargetInfo};
 use crate::core::compiler::{BuildConfig, BuildContext, BuildRunner, Compilation};
 use crate::core::compiler::{CompileKind, CompileMode, CompileTarget, RustcTargetData, Unit};
 use crate::core::compiler::{DefaultExecutor, Executor, UnitInterner};
+use crate::core::dependency::DepKind;
 use crate::core::profiles::Profiles;
 use crate::core::resolver::features::{self, CliFeatures, FeaturesFor};
 use crate::core::resolver::{HasDevUnits, Resolve};
@@ -67,6 +68,9 @@ mod packages;
 
 pub use packages::Packages;
 
+mod manifest_list;
+pub use manifest_list::{compile_manifest_list, read_manifest_list};
+
 /// Contains information about how a package should be compiled.
 ///
 /// Note on distinction between `CompileOptions` and [`BuildConfig`]:
@@ -99,6 +103,8 @@ pub struct CompileOptions {
     /// Whether the build process should check the minimum Rust version
     /// defined in the cargo metadata for a crate.
     pub honor_rust_version: Option<bool>,
+    /// Fuzz targets to build in addition to the targets selected by `filter`.
+    pub fuzz: FilterRule,
 }
 
 impl CompileOptions {
@@ -117,6 +123,7 @@ impl CompileOptions {
             target_rustc_crate_types: None,
             rustdoc_document_private_items: false,
             honor_rust_version: None,
+            fuzz: FilterRule::none(),
         })
     }
 }
@@ -156,6 +163,7 @@ pub fn compile_ws<'a>(
         return Compilation::new(&bcx);
     }
     crate::core::gc::auto_gc(bcx.gctx);
+    crate::core::compiler::artifact_cache_gc(&bcx);
     let build_runner = BuildRunner::new(&bcx)?;
     build_runner.compile(exec)
 }
@@ -214,6 +222,7 @@ pub fn create_bcx<'a, 'gctx>(
         ref target_rustc_crate_types,
         rustdoc_document_private_items,
         honor_rust_version,
+        ref fuzz,
     } = *options;
     let gctx = ws.gctx();
 
@@ -257,6 +266,7 @@ pub fn create_bcx<'a, 'gctx>(
             });
 
         if filter.need_dev_deps(build_config.mode)
+            || fuzz.is_specific()
             || (build_config.mode.is_doc() && any_pkg_has_scrape_enabled)
         {
             HasDevUnits::Yes
@@ -279,6 +289,7 @@ pub fn create_bcx<'a, 'gctx>(
         targeted_resolve: resolve,
         resolved_features,
     } = resolve;
+    warn_conflicting_public_dependencies(ws, &resolve)?;
 
     let std_resolve_features = if let Some(crates) = &gctx.cli_unstable().build_std {
         let (std_package_set, std_resolve, std_features) =
@@ -360,6 +371,7 @@ pub fn create_bcx<'a, 'gctx>(
         ws,
         packages: &to_builds,
         filter,
+        fuzz,
         requested_kinds: &build_config.requested_kinds,
         explicit_host_kind,
         mode: build_config.mode,
@@ -525,6 +537,25 @@ where `<compatible-ver>` is the latest version supporting rustc {rustc_version}"
         }
     }
 
+    // The packages the artifact dependencies resolved to, for the ones
+    // `-Zcheck-skip-artifacts` leaves out of the unit graph.
+    let mut artifact_deps = HashMap::new();
+    if gctx.cli_unstable().check_skip_artifacts {
+        for pkg_id in resolve.iter() {
+            let deps = resolve
+                .deps(pkg_id)
+                .flat_map(|(dep_id, deps)| {
+                    deps.iter()
+                        .filter(|dep| dep.artifact().is_some())
+                        .map(move |dep| (dep.clone(), dep_id))
+                })
+                .collect::<Vec<_>>();
+            if !deps.is_empty() {
+                artifact_deps.insert(pkg_id, deps);
+            }
+        }
+    }
+
     let bcx = BuildContext::new(
         ws,
         pkg_set,
@@ -535,11 +566,80 @@ where `<compatible-ver>` is the latest version supporting rustc {rustc_version}"
         units,
         unit_graph,
         scrape_units,
+        artifact_deps,
     )?;
 
     Ok(bcx)
 }
 
+/// Warns about each workspace member exposing two different packages with the
+/// same name through its public dependencies.
+///
+/// A public dependency is part of the API of the package depending on it, and
+/// so are its own public dependencies. If two of them are different packages
+/// with the same name, e.g. two semver incompatible versions of a crate,
+/// users of the package see two unrelated sets of types with the same paths.
+///
+/// This is only a warning, as graphs like these resolved fine before public
+/// dependencies were stabilized.
+fn warn_conflicting_public_dependencies(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<()> {
+    let public_deps = |pkg: PackageId| {
+        resolve
+            .deps(pkg)
+            .filter(|(_, deps)| {
+                deps.iter()
+                    .any(|d| d.kind() == DepKind::Normal && d.is_public())
+            })
+            .map(|(dep_id, _)| dep_id)
+            .collect::<BTreeSet<_>>()
+    };
+
+    let mut members: Vec<_> = ws
+        .members()
+        .map(|pkg| pkg.package_id())
+        .filter(|id| resolve.contains(id))
+        .collect();
+    members.sort();
+    for pkg in members {
+        // Walk the public dependencies transitively, remembering through
+        // which package each one was first reached to describe the path.
+        let mut reached_from = BTreeMap::new();
+        let mut queue: VecDeque<_> = public_deps(pkg).into_iter().map(|dep| (dep, pkg)).collect();
+        let mut by_name = HashMap::new();
+        while let Some((dep, parent)) = queue.pop_front() {
+            if dep == pkg || reached_from.contains_key(&dep) {
+                continue;
+            }
+            reached_from.insert(dep, parent);
+            if let Some(&other) = by_name.get(&dep.name()) {
+                let path = |mut id: PackageId| {
+                    let mut path = vec![id];
+                    while let Some(&parent) = reached_from.get(&id) {
+                        path.push(parent);
+                        id = parent;
+                    }
+                    path.iter()
+                        .rev()
+                        .map(|id| format!("`{id}`"))
+                        .collect::<Vec<_>>()
+                        .join(" -> ")
+                };
+                ws.gctx().shell().warn(format!(
+                    "package `{}` publicly depends on two different packages named `{}`:\n  {}\n  {}",
+                    pkg,
+                    dep.name(),
+                    path(other),
+                    path(dep),
+                ))?;
+                break;
+            }
+            by_name.insert(dep.name(), dep);
+            queue.extend(public_deps(dep).into_iter().map(|next| (next, dep)));
+        }
+    }
+    Ok(())
+}
+
 /// This is used to rebuild the unit graph, sharing host dependencies if possible,
 /// and applying other unit adjustments based on the whole graph.
 ///
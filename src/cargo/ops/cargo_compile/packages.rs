@@ -26,6 +26,9 @@ pub enum Packages {
     /// As of the time of this writing, it only works on opting out workspace members.
     OptOut(Vec<String>),
     /// A sequence of hand-picked packages that will be built. Normally done by `-p` flag.
+    ///
+    /// Glob patterns like `tokio-*` or `my-org-*@2` may select both workspace
+    /// members and dependencies.
     Packages(Vec<String>),
 }
 
@@ -42,6 +45,13 @@ impl Packages {
     }
 
     /// Converts selected packages to [`PackageIdSpec`]s.
+    ///
+    /// For [`Packages::Packages`], glob patterns are expanded to the matching
+    /// workspace members and also kept as glob specs, so they can select
+    /// dependencies once resolved. See [`Resolve::specs_to_ids`]. A pattern
+    /// matching neither a member nor a dependency declared by one is an error.
+    ///
+    /// [`Resolve::specs_to_ids`]: crate::core::resolver::Resolve::specs_to_ids
     pub fn to_package_id_specs(&self, ws: &Workspace<'_>) -> CargoResult<Vec<PackageIdSpec>> {
         let specs = match self {
             Packages::All => ws
@@ -69,7 +79,7 @@ impl Packages {
                     .map(|id| id.to_string())
                     .collect::<BTreeSet<_>>();
                 emit_package_not_found(ws, names, true).or_else(warn)?;
-                emit_pattern_not_found(ws, patterns, true).or_else(warn)?;
+                emit_pattern_not_found(ws, &patterns, true).or_else(warn)?;
                 specs
             }
             Packages::Packages(packages) if packages.is_empty() => {
@@ -85,8 +95,23 @@ impl Packages {
                         .map(|id| id.to_spec());
                     specs.extend(matched_pkgs);
                 }
-                emit_pattern_not_found(ws, patterns, false)?;
-                specs.into_iter().collect()
+                // Dependencies are only known once resolved, so a pattern
+                // matching no member must at least match the name of a
+                // dependency declared by one.
+                for (spec, matched) in patterns.iter_mut() {
+                    if !*matched {
+                        let pattern = build_glob(spec.name())?;
+                        *matched = ws.members().any(|pkg| {
+                            pkg.dependencies()
+                                .iter()
+                                .any(|dep| pattern.matches(dep.package_name().as_str()))
+                        });
+                    }
+                }
+                emit_pattern_not_found(ws, &patterns, false)?;
+                let mut specs: Vec<_> = specs.into_iter().collect();
+                specs.extend(patterns.into_iter().map(|(spec, _)| spec));
+                specs
             }
             Packages::Default => ws
                 .default_members()
@@ -107,6 +132,27 @@ impl Packages {
         Ok(specs)
     }
 
+    /// Like [`Packages::to_package_id_specs`], but glob patterns only select
+    /// workspace members and are an error if they match none of them.
+    pub fn to_member_package_id_specs(
+        &self,
+        ws: &Workspace<'_>,
+    ) -> CargoResult<Vec<PackageIdSpec>> {
+        let (patterns, specs): (Vec<_>, Vec<_>) = self
+            .to_package_id_specs(ws)?
+            .into_iter()
+            .partition(PackageIdSpec::is_glob);
+        let patterns: Vec<_> = patterns
+            .into_iter()
+            .map(|spec| {
+                let matched = ws.members().any(|pkg| spec.matches(pkg.package_id()));
+                (spec, matched)
+            })
+            .collect();
+        emit_pattern_not_found(ws, &patterns, false)?;
+        Ok(specs)
+    }
+
     /// Gets a list of selected [`Package`]s.
     pub fn get_packages<'ws>(&self, ws: &'ws Workspace<'_>) -> CargoResult<Vec<&'ws Package>> {
         let packages: Vec<_> = match self {
@@ -129,7 +175,7 @@ impl Packages {
                     .map(|id| id.to_string())
                     .collect::<BTreeSet<_>>();
                 emit_package_not_found(ws, names, true)?;
-                emit_pattern_not_found(ws, patterns, true)?;
+                emit_pattern_not_found(ws, &patterns, true)?;
                 packages
             }
             Packages::Packages(opt_in) => {
@@ -149,7 +195,7 @@ impl Packages {
                     .map(|id| id.to_string())
                     .collect::<BTreeSet<_>>();
                 emit_package_not_found(ws, names, false)?;
-                emit_pattern_not_found(ws, patterns, false)?;
+                emit_pattern_not_found(ws, &patterns, false)?;
                 packages
             }
         };
@@ -188,13 +234,13 @@ fn emit_package_not_found(
 /// Emits "glob pattern not found" error.
 fn emit_pattern_not_found(
     ws: &Workspace<'_>,
-    opt_patterns: Vec<(glob::Pattern, bool)>,
+    opt_patterns: &[(PackageIdSpec, bool)],
     opt_out: bool,
 ) -> CargoResult<()> {
     let not_matched = opt_patterns
         .iter()
         .filter(|(_, matched)| !*matched)
-        .map(|(pat, _)| pat.as_str())
+        .map(|(spec, _)| spec.to_string())
         .collect::<Vec<_>>();
     if !not_matched.is_empty() {
         anyhow::bail!(
@@ -209,9 +255,12 @@ fn emit_pattern_not_found(
 
 /// Given a list opt-in or opt-out package selection strings, generates two
 /// collections that represent glob patterns and package id specs respectively.
+///
+/// Glob patterns are kept as [`PackageIdSpec`]s so they can carry a version,
+/// like `my-org-*@2`.
 fn opt_patterns_and_ids(
     opt: &[String],
-) -> CargoResult<(Vec<(glob::Pattern, bool)>, BTreeSet<PackageIdSpec>)> {
+) -> CargoResult<(Vec<(PackageIdSpec, bool)>, BTreeSet<PackageIdSpec>)> {
     let mut opt_patterns = Vec::new();
     let mut opt_ids = BTreeSet::new();
     for x in opt.iter() {
@@ -219,7 +268,11 @@ fn opt_patterns_and_ids(
             Ok(spec) => {
                 opt_ids.insert(spec);
             }
-            Err(_) if is_glob_pattern(x) => opt_patterns.push((build_glob(x)?, false)),
+            Err(_) if is_glob_pattern(x) => {
+                let spec = PackageIdSpec::parse_glob(x)?;
+                build_glob(spec.name())?;
+                opt_patterns.push((spec, false));
+            }
             Err(e) => return Err(e.into()),
         }
     }
@@ -228,9 +281,9 @@ fn opt_patterns_and_ids(
 
 /// Checks whether a package matches any of a list of glob patterns generated
 /// from `opt_patterns_and_names`.
-fn match_patterns(pkg: &Package, patterns: &mut Vec<(glob::Pattern, bool)>) -> bool {
-    patterns.iter_mut().any(|(m, matched)| {
-        let is_matched = m.matches(pkg.name().as_str());
+fn match_patterns(pkg: &Package, patterns: &mut Vec<(PackageIdSpec, bool)>) -> bool {
+    patterns.iter_mut().any(|(spec, matched)| {
+        let is_matched = spec.matches(pkg.package_id());
         *matched |= is_matched;
         is_matched
     })
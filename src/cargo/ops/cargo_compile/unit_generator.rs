@@ -48,6 +48,7 @@ pub(super) struct UnitGenerator<'a, 'gctx> {
     pub ws: &'a Workspace<'gctx>,
     pub packages: &'a [&'a Package],
     pub filter: &'a CompileFilter,
+    pub fuzz: &'a FilterRule,
     pub requested_kinds: &'a [CompileKind],
     pub explicit_host_kind: CompileKind,
     pub mode: CompileMode,
@@ -109,19 +110,27 @@ impl<'a> UnitGenerator<'a, '_> {
         // graph is built almost like if `--target $HOST` was
         // specified. See `rebuild_unit_graph_shared` for more on
         // why this is done. However, if the package has its own
-        // `package.target` key, then this gets used instead of
+        // `package.target` key, or the workspace sets
+        // `workspace.build.targets`, then this gets used instead of
         // `$HOST`
         let explicit_kinds = if let Some(k) = pkg.manifest().forced_kind() {
             vec![k]
         } else {
             self.requested_kinds
                 .iter()
+                .flat_map(|kind| match kind {
+                    CompileKind::Host => match pkg.manifest().default_kind() {
+                        Some(k) => vec![k],
+                        None if !self.ws.default_kinds().is_empty() => {
+                            self.ws.default_kinds().to_vec()
+                        }
+                        None => vec![CompileKind::Host],
+                    },
+                    CompileKind::Target(t) => vec![CompileKind::Target(*t)],
+                })
                 .map(|kind| match kind {
-                    CompileKind::Host => pkg
-                        .manifest()
-                        .default_kind()
-                        .unwrap_or(self.explicit_host_kind),
-                    CompileKind::Target(t) => CompileKind::Target(*t),
+                    CompileKind::Host => self.explicit_host_kind,
+                    kind => kind,
                 })
                 .collect()
         };
@@ -430,6 +439,21 @@ impl<'a> UnitGenerator<'a, '_> {
             }
         }
 
+        // Fuzz targets are only built when requested, with `cargo fuzz-build`
+        // or `cargo test --fuzz-smoke`, and never as tests.
+        if self.fuzz.is_specific() {
+            let fuzz_mode = match self.mode {
+                CompileMode::Check { .. } => CompileMode::Check { test: false },
+                _ => CompileMode::Build,
+            };
+            proposals.extend(self.list_rule_targets(
+                self.fuzz,
+                "fuzz",
+                Target::is_fuzz,
+                fuzz_mode,
+            )?);
+        }
+
         Ok(proposals)
     }
 
@@ -599,6 +623,15 @@ Rustdoc did not scrape the following examples because they require dev-dependenc
                     dep_feature,
                     weak: false,
                 } => {
+                    // A resolve loaded from the lock file, like the one reused
+                    // by `-Zresolve-cache`, doesn't know the declarations of the
+                    // dependencies. The resolve of the requested packages does,
+                    // for the dependencies it activated.
+                    let (resolve, complete) = if resolve.has_summaries() {
+                        (resolve, true)
+                    } else {
+                        (self.resolve, false)
+                    };
                     match resolve.deps(summary.package_id()).find(|(_dep_id, deps)| {
                         deps.iter().any(|dep| dep.name_in_toml() == *dep_name)
                     }) {
@@ -616,6 +649,12 @@ Rustdoc did not scrape the following examples because they require dev-dependenc
                                 ))?;
                             }
                         }
+                        // An optional dependency which isn't activated.
+                        None if !complete
+                            && summary
+                                .dependencies()
+                                .iter()
+                                .any(|dep| dep.name_in_toml() == *dep_name) => {}
                         None => {
                             shell.warn(format!(
                                 "invalid feature `{}` in required-features of target `{}`: \
@@ -0,0 +1,245 @@
+//! Implementation of `cargo daemon` subcommand.
+//!
+//! The daemon serves [JSON-RPC 2.0] over stdio, with one message per line, so
+//! that IDEs don't need to spawn Cargo, load the manifests and resolve the
+//! dependencies again for every request. The workspace, its resolve and its
+//! metadata are kept in memory until the client sends a `didChange`
+//! notification, and the index and git sources are only updated once for the
+//! whole session, like within any other Cargo command.
+//!
+//! The requests are:
+//!
+//! * `metadata`: the output of `cargo metadata --format-version 1`, or with
+//!   `{"no_deps": true}` of `cargo metadata --no-deps`.
+//! * `resolve`: the resolved packages, with the IDs of their dependencies.
+//! * `check`: runs `cargo check`, for the workspace or for
+//!   `{"package": "<spec>"}`. Diagnostics are printed to stderr.
+//! * `shutdown`: stops the daemon.
+//!
+//! [JSON-RPC 2.0]: https://www.jsonrpc.org/specification
+
+use std::io::BufRead;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::compiler::CompileMode;
+use crate::core::resolver::CliFeatures;
+use crate::core::{PackageIdSpec, Resolve, Workspace};
+use crate::ops::{self, CompileOptions, OutputMetadataOptions, PackageFilter, Packages};
+use crate::util::errors::CargoResult;
+use crate::util::interning::InternedString;
+use crate::GlobalContext;
+
+/// The error code of a request which failed, in the range JSON-RPC reserves
+/// for implementation-defined server errors.
+const REQUEST_FAILED: i64 = -32000;
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Deserialize)]
+struct Request {
+    /// Absent for notifications, which don't get a response.
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize)]
+struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ResponseError>,
+}
+
+#[derive(Serialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct MetadataParams {
+    no_deps: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct CheckParams {
+    package: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ResolvedPackage {
+    id: PackageIdSpec,
+    dependencies: Vec<PackageIdSpec>,
+}
+
+/// The state kept in memory between requests, dropped on `didChange`.
+#[derive(Default)]
+struct Cache<'gctx> {
+    ws: Option<Workspace<'gctx>>,
+    resolve: Option<Resolve>,
+    metadata: Option<Value>,
+    metadata_no_deps: Option<Value>,
+}
+
+/// Serves requests from stdin until `shutdown` or the end of the input.
+pub fn daemon(gctx: &GlobalContext, root_manifest: &Path) -> CargoResult<()> {
+    let mut cache = Cache::default();
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                respond(gctx, Value::Null, Err((PARSE_ERROR, e.to_string())))?;
+                continue;
+            }
+        };
+        if request.method == "didChange" {
+            cache = Cache::default();
+            continue;
+        }
+        let result = match request.method.as_str() {
+            "shutdown" => Ok(Value::Null),
+            "metadata" => params(request.params)
+                .and_then(|params| metadata(gctx, root_manifest, &mut cache, params)),
+            "resolve" => resolve(gctx, root_manifest, &mut cache),
+            "check" => params(request.params)
+                .and_then(|params| check(gctx, root_manifest, &mut cache, params)),
+            method => Err((METHOD_NOT_FOUND, format!("unknown method `{method}`"))),
+        };
+        if let Some(id) = request.id {
+            respond(gctx, id, result)?;
+        }
+        if request.method == "shutdown" {
+            break;
+        }
+    }
+    Ok(())
+}
+
+type RequestResult = Result<Value, (i64, String)>;
+
+fn respond(gctx: &GlobalContext, id: Value, result: RequestResult) -> CargoResult<()> {
+    let (result, error) = match result {
+        Ok(result) => (Some(result), None),
+        Err((code, message)) => (None, Some(ResponseError { code, message })),
+    };
+    gctx.shell().print_json(&Response {
+        jsonrpc: "2.0",
+        id,
+        result,
+        error,
+    })
+}
+
+fn params<T: for<'de> Deserialize<'de> + Default>(params: Value) -> Result<T, (i64, String)> {
+    if params.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, e.to_string()))
+}
+
+fn failed(e: anyhow::Error) -> (i64, String) {
+    (REQUEST_FAILED, format!("{e:?}"))
+}
+
+fn workspace<'a, 'gctx>(
+    gctx: &'gctx GlobalContext,
+    root_manifest: &Path,
+    cache: &'a mut Cache<'gctx>,
+) -> CargoResult<&'a Workspace<'gctx>> {
+    if cache.ws.is_none() {
+        cache.ws = Some(Workspace::new(root_manifest, gctx)?);
+    }
+    Ok(cache.ws.as_ref().unwrap())
+}
+
+fn metadata<'gctx>(
+    gctx: &'gctx GlobalContext,
+    root_manifest: &Path,
+    cache: &mut Cache<'gctx>,
+    params: MetadataParams,
+) -> RequestResult {
+    let cached = if params.no_deps {
+        &cache.metadata_no_deps
+    } else {
+        &cache.metadata
+    };
+    if let Some(metadata) = cached {
+        return Ok(metadata.clone());
+    }
+    let ws = workspace(gctx, root_manifest, cache).map_err(failed)?;
+    let opts = OutputMetadataOptions {
+        cli_features: CliFeatures::new_all(false),
+        no_deps: params.no_deps,
+        version: 1,
+        filter_platforms: Vec::new(),
+        filter_specs: Vec::new(),
+        package_filter: PackageFilter::default(),
+        profile: InternedString::new("dev"),
+    };
+    let metadata = ops::output_metadata(ws, &opts)
+        .and_then(|info| Ok(serde_json::to_value(info)?))
+        .map_err(failed)?;
+    if params.no_deps {
+        cache.metadata_no_deps = Some(metadata.clone());
+    } else {
+        cache.metadata = Some(metadata.clone());
+    }
+    Ok(metadata)
+}
+
+fn resolve<'gctx>(
+    gctx: &'gctx GlobalContext,
+    root_manifest: &Path,
+    cache: &mut Cache<'gctx>,
+) -> RequestResult {
+    if cache.resolve.is_none() {
+        let ws = workspace(gctx, root_manifest, cache).map_err(failed)?;
+        let (_packages, resolve) = ops::resolve_ws(ws).map_err(failed)?;
+        cache.resolve = Some(resolve);
+    }
+    let resolve = cache.resolve.as_ref().unwrap();
+    let mut packages: Vec<_> = resolve.iter().collect();
+    packages.sort();
+    let packages: Vec<_> = packages
+        .into_iter()
+        .map(|id| {
+            let mut dependencies: Vec<_> = resolve.deps(id).map(|(dep, _)| dep).collect();
+            dependencies.sort();
+            ResolvedPackage {
+                id: id.to_spec(),
+                dependencies: dependencies.into_iter().map(|dep| dep.to_spec()).collect(),
+            }
+        })
+        .collect();
+    serde_json::to_value(packages).map_err(|e| failed(e.into()))
+}
+
+fn check<'gctx>(
+    gctx: &'gctx GlobalContext,
+    root_manifest: &Path,
+    cache: &mut Cache<'gctx>,
+    params: CheckParams,
+) -> RequestResult {
+    let ws = workspace(gctx, root_manifest, cache).map_err(failed)?;
+    let mut opts = CompileOptions::new(gctx, CompileMode::Check { test: false }).map_err(failed)?;
+    opts.spec = match params.package {
+        Some(package) => Packages::Packages(vec![package]),
+        None => Packages::Default,
+    };
+    ops::compile(ws, &opts).map_err(failed)?;
+    Ok(Value::Null)
+}
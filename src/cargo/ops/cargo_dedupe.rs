@@ -0,0 +1,114 @@
+//! Implementation of `cargo dedupe` subcommand.
+//!
+//! The resolver never selects two semver-compatible versions of a package,
+//! but it can end up with several incompatible ones while a single version
+//! would satisfy every requirement. For example with `Cargo.lock` holding
+//! `bar 1.5.0` for a dependency on `bar = ">=1, <3"` and `bar 2.1.0` for
+//! another on `bar = "2"`. `cargo dedupe` finds the versions whose dependents
+//! all accept a newer version already in `Cargo.lock`, and runs the
+//! equivalent of `cargo update --precise` to collapse them.
+
+use std::collections::{BTreeMap, HashSet};
+
+use crate::core::{PackageId, Resolve, SourceId, Workspace};
+use crate::drop_println;
+use crate::ops;
+use crate::util::errors::CargoResult;
+use crate::util::interning::InternedString;
+
+pub struct DedupeOptions {
+    /// Only print the updates instead of performing them.
+    pub dry_run: bool,
+}
+
+/// An update collapsing `from` into `to`, another version of the same package
+/// in `Cargo.lock`.
+struct Collapse {
+    from: PackageId,
+    to: PackageId,
+}
+
+pub fn dedupe(ws: &Workspace<'_>, opts: &DedupeOptions) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    // The resolve loaded from `Cargo.lock` doesn't have the version
+    // requirements, so resolve again, which keeps the locked versions.
+    let (_packages, resolve) = ops::resolve_ws_with_dry_run(ws, true)?;
+    let collapses = find_collapses(&resolve);
+    if collapses.is_empty() {
+        gctx.shell()
+            .note("no duplicate versions can be collapsed into one")?;
+        return Ok(());
+    }
+
+    for Collapse { from, to } in &collapses {
+        if opts.dry_run {
+            drop_println!(
+                gctx,
+                "cargo update -p {}@{} --precise {}",
+                from.name(),
+                from.version(),
+                to.version()
+            );
+            continue;
+        }
+        let precise = to.version().to_string();
+        let update_opts = ops::UpdateOptions {
+            gctx,
+            to_update: vec![format!("{}@{}", from.name(), from.version())],
+            precise: Some(&precise),
+            recursive: false,
+            dry_run: false,
+            workspace: false,
+            changelog: None,
+        };
+        ops::update_lockfile(ws, &update_opts)?;
+    }
+    if opts.dry_run {
+        gctx.shell().warn("not updating lockfile due to dry run")?;
+    }
+    Ok(())
+}
+
+/// Finds the minimal set of updates to collapse duplicate versions.
+fn find_collapses(resolve: &Resolve) -> Vec<Collapse> {
+    // Only registry packages can be updated to a precise version.
+    let mut versions: BTreeMap<(InternedString, SourceId), Vec<PackageId>> = BTreeMap::new();
+    for id in resolve.iter().filter(|id| id.source_id().is_registry()) {
+        versions
+            .entry((id.name(), id.source_id()))
+            .or_default()
+            .push(id);
+    }
+
+    let mut collapses = Vec::new();
+    for mut ids in versions.into_values().filter(|ids| ids.len() > 1) {
+        // Newest first, so the versions which are kept are known before
+        // looking for a target for the older ones.
+        ids.sort_by(|a, b| b.version().cmp(a.version()));
+        let mut moved = HashSet::new();
+        for (i, from) in ids.iter().enumerate() {
+            let target = ids[..i]
+                .iter()
+                .filter(|to| !moved.contains(*to))
+                .find(|to| accepted_by_dependents(resolve, *from, **to));
+            if let Some(to) = target {
+                moved.insert(*from);
+                collapses.push(Collapse {
+                    from: *from,
+                    to: *to,
+                });
+            }
+        }
+    }
+    collapses
+}
+
+/// Returns `true` if every package depending on `from` accepts `to` instead.
+fn accepted_by_dependents(resolve: &Resolve, from: PackageId, to: PackageId) -> bool {
+    resolve
+        .iter()
+        .flat_map(|parent| resolve.deps(parent))
+        .filter(|(dep_id, _)| *dep_id == from)
+        .flat_map(|(_, deps)| deps)
+        .all(|dep| dep.version_req().matches(to.version()))
+}
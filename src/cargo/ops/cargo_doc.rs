@@ -1,9 +1,11 @@
 use crate::core::compiler::{Compilation, CompileKind};
-use crate::core::{shell::Verbosity, Shell, Workspace};
+use crate::core::{shell::Verbosity, FeatureValue, Package, Shell, Workspace};
 use crate::ops;
 use crate::util::context::{GlobalContext, PathAndArgs};
 use crate::util::CargoResult;
 use anyhow::{bail, Error};
+use cargo_util::paths;
+use std::fmt::Write as _;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
@@ -47,6 +49,9 @@ pub struct DocOptions {
     pub open_result: bool,
     /// Same as `rustdoc --output-format`
     pub output_format: OutputFormat,
+    /// Whether to generate a `features.html` page for each documented
+    /// package, with `--features-table`
+    pub features_table: bool,
     /// Options to pass through to the compiler
     pub compile_opts: ops::CompileOptions,
 }
@@ -55,6 +60,12 @@ pub struct DocOptions {
 pub fn doc(ws: &Workspace<'_>, options: &DocOptions) -> CargoResult<()> {
     let compilation = ops::compile(ws, &options.compile_opts)?;
 
+    if options.features_table {
+        for pkg in options.compile_opts.spec.get_packages(ws)? {
+            write_features_table(ws, options, &compilation, pkg)?;
+        }
+    }
+
     if options.open_result {
         let name = &compilation
             .root_crate_names
@@ -116,6 +127,117 @@ pub fn doc(ws: &Workspace<'_>, options: &DocOptions) -> CargoResult<()> {
     Ok(())
 }
 
+/// Writes `features.html` next to the `index.html` of the documentation of
+/// `pkg`, listing its features, what each enables, and their documentation
+/// from the `##` comments of `Cargo.toml`.
+fn write_features_table(
+    ws: &Workspace<'_>,
+    options: &DocOptions,
+    compilation: &Compilation<'_>,
+    pkg: &Package,
+) -> CargoResult<()> {
+    let Some(target) = pkg
+        .targets()
+        .iter()
+        .find(|t| t.is_lib())
+        .or_else(|| pkg.targets().iter().find(|t| t.is_bin()))
+    else {
+        return Ok(());
+    };
+    let features = pkg.summary().features();
+    let default = features
+        .get("default")
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let docs = pkg.manifest().feature_docs();
+
+    let name = escape(&pkg.name());
+    let version = pkg.version();
+    let mut html = format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>Features of {name} {version}</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; margin: 2em auto; max-width: 60em; }}\n\
+         table {{ border-collapse: collapse; width: 100%; }}\n\
+         th, td {{ border: 1px solid #ccc; padding: 0.4em; text-align: left; vertical-align: top; }}\n\
+         </style>\n\
+         </head>\n\
+         <body>\n\
+         <h1>Features of <code>{name}</code> {version}</h1>\n\
+         <p><a href=\"index.html\">Back to the documentation</a></p>\n"
+    );
+    if features.is_empty() {
+        html.push_str("<p>This package has no features.</p>\n");
+    } else {
+        html.push_str("<table>\n<tr><th>Feature</th><th>Enables</th><th>Description</th></tr>\n");
+        for (feature, values) in features {
+            let id = escape(feature);
+            let is_default = default.contains(&FeatureValue::Feature(*feature));
+            let _ = write!(html, "<tr id=\"feature-{id}\"><td><code>{id}</code>");
+            if is_default {
+                html.push_str(" (default)");
+            }
+            html.push_str("</td><td>");
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    html.push_str(", ");
+                }
+                let value_html = escape(&value.to_string());
+                match value {
+                    FeatureValue::Feature(_) => {
+                        let _ = write!(
+                            html,
+                            "<a href=\"#feature-{value_html}\"><code>{value_html}</code></a>"
+                        );
+                    }
+                    _ => {
+                        let _ = write!(html, "<code>{value_html}</code>");
+                    }
+                }
+            }
+            html.push_str("</td><td>");
+            for paragraph in docs
+                .get(feature.as_str())
+                .into_iter()
+                .flat_map(|doc| doc.split("\n\n"))
+            {
+                let _ = write!(html, "<p>{}</p>", escape(paragraph));
+            }
+            html.push_str("</td></tr>\n");
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+
+    for kind in &options.compile_opts.build_config.requested_kinds {
+        let dir = compilation.root_output[kind]
+            .with_file_name("doc")
+            .join(target.crate_name());
+        if !dir.exists() {
+            continue;
+        }
+        let path = dir.join("features.html");
+        paths::write(&path, &html)?;
+        if ws.gctx().shell().verbosity() == Verbosity::Verbose {
+            let mut shell = ws.gctx().shell();
+            let link = shell.err_file_hyperlink(&path);
+            shell.status("Generated", format!("{link}{}{link:#}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Escapes the characters of `s` which are special in HTML.
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 fn path_by_output_format(
     compilation: &Compilation<'_>,
     kind: &CompileKind,
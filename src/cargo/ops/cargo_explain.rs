@@ -0,0 +1,400 @@
+//! Implementation of `cargo explain` subcommand.
+//!
+//! `cargo explain <spec>` resolves the workspace and reports why the resolver
+//! ended up with the selected version of a package:
+//!
+//! * whether the version was already recorded in `Cargo.lock`,
+//! * every package depending on it, with the version requirement, dependency
+//!   kind and features it requests, and which requirement is the tightest,
+//! * every newer version in its registry and what ruled it out,
+//! * the features activated on the package and what they enable in turn.
+
+use crate::core::dependency::DepKind;
+use crate::core::registry::PackageRegistry;
+use crate::core::Registry as _;
+use crate::core::{Dependency, PackageId, PackageIdSpec, PackageIdSpecQuery, Resolve, Workspace};
+use crate::drop_println;
+use crate::ops;
+use crate::sources::source::QueryKind;
+use crate::sources::IndexSummary;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::context::ResolutionMode;
+use crate::util::errors::CargoResult;
+use crate::util::OptVersionReq;
+use semver::{Version, VersionReq};
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExplainFormat {
+    Human,
+    Json,
+}
+
+impl clap::ValueEnum for ExplainFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[ExplainFormat::Human, ExplainFormat::Json]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let name = match self {
+            ExplainFormat::Human => "human",
+            ExplainFormat::Json => "json",
+        };
+        Some(clap::builder::PossibleValue::new(name))
+    }
+}
+
+/// Options for `cargo explain`.
+pub struct ExplainOptions<'a> {
+    pub spec: &'a str,
+    pub format: ExplainFormat,
+}
+
+#[derive(Serialize)]
+struct Explanation {
+    id: String,
+    name: String,
+    version: String,
+    /// Whether `Cargo.lock` already had this exact package.
+    locked: bool,
+    dependents: Vec<Dependent>,
+    newer_versions: Vec<NewerVersion>,
+    features: Vec<ActivatedFeature>,
+}
+
+#[derive(Serialize)]
+struct Dependent {
+    id: String,
+    #[serde(skip)]
+    display: String,
+    requirement: String,
+    kind: &'static str,
+    optional: bool,
+    default_features: bool,
+    features: Vec<String>,
+    /// Whether this is the requirement matching the fewest published
+    /// versions.
+    tightest: bool,
+}
+
+#[derive(Serialize)]
+struct NewerVersion {
+    version: String,
+    reason: Reason,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum Reason {
+    /// Rejected by the requirements of these dependents.
+    Requirement {
+        dependents: Vec<String>,
+        #[serde(skip)]
+        display: Vec<String>,
+    },
+    Yanked,
+    /// Requires a newer Rust than the workspace supports.
+    RustVersion {
+        rust_version: String,
+    },
+    /// Allowed, but the version in `Cargo.lock` was kept.
+    Locked,
+    /// Allowed, but lower versions are preferred.
+    MinimalVersions,
+    /// Allowed by every requirement, but not compatible with the rest of the
+    /// graph, e.g. because of another package's requirement on one of its
+    /// dependencies.
+    Conflict,
+}
+
+#[derive(Serialize)]
+struct ActivatedFeature {
+    name: String,
+    enables: Vec<String>,
+}
+
+pub fn explain(ws: &Workspace<'_>, opts: &ExplainOptions<'_>) -> CargoResult<()> {
+    let spec = PackageIdSpec::parse(opts.spec)?;
+    let previous_resolve = ops::load_pkg_lockfile(ws)?;
+    let (_packages, resolve) = ops::resolve_ws(ws)?;
+    let pkg_id = spec.query(resolve.iter())?;
+
+    let locked = previous_resolve
+        .as_ref()
+        .map_or(false, |previous| previous.contains(&pkg_id));
+    let requirements = requirements(&resolve, pkg_id);
+    let available = available_versions(ws, pkg_id)?;
+
+    let tightest = if available.is_empty() {
+        None
+    } else {
+        requirements
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (_, dep))| {
+                let req = original_req(dep);
+                available
+                    .iter()
+                    .filter(|s| {
+                        req.as_ref()
+                            .map_or(true, |req| req.matches(s.as_summary().version()))
+                    })
+                    .count()
+            })
+            .map(|(i, _)| i)
+    };
+    let dependents = requirements
+        .iter()
+        .enumerate()
+        .map(|(i, (parent, dep))| Dependent {
+            id: parent.to_spec().to_string(),
+            display: parent.to_string(),
+            requirement: dep.version_req().to_string(),
+            kind: match dep.kind() {
+                DepKind::Normal => "normal",
+                DepKind::Development => "dev",
+                DepKind::Build => "build",
+            },
+            optional: dep.is_optional(),
+            default_features: dep.uses_default_features(),
+            features: dep.features().iter().map(|f| f.to_string()).collect(),
+            tightest: tightest == Some(i),
+        })
+        .collect();
+
+    let mut newer: Vec<_> = available
+        .iter()
+        .filter(|s| s.as_summary().version() > pkg_id.version())
+        .collect();
+    newer.sort_by(|a, b| b.as_summary().version().cmp(a.as_summary().version()));
+    let newer_versions = newer
+        .into_iter()
+        .map(|s| NewerVersion {
+            version: s.as_summary().version().to_string(),
+            reason: reason(ws, s, &requirements, locked),
+        })
+        .collect();
+
+    let summary = resolve.summary(pkg_id);
+    let features = resolve
+        .features(pkg_id)
+        .iter()
+        .map(|name| ActivatedFeature {
+            name: name.to_string(),
+            enables: summary
+                .features()
+                .get(name)
+                .into_iter()
+                .flatten()
+                .map(|value| value.to_string())
+                .collect(),
+        })
+        .collect();
+
+    let explanation = Explanation {
+        id: pkg_id.to_spec().to_string(),
+        name: pkg_id.name().to_string(),
+        version: pkg_id.version().to_string(),
+        locked,
+        dependents,
+        newer_versions,
+        features,
+    };
+    match opts.format {
+        ExplainFormat::Human => print_human(ws, pkg_id, &explanation),
+        ExplainFormat::Json => ws.gctx().shell().print_json(&explanation),
+    }
+}
+
+/// The dependency declarations on `pkg_id`, along with the package declaring
+/// them.
+fn requirements(resolve: &Resolve, pkg_id: PackageId) -> Vec<(PackageId, Dependency)> {
+    let mut requirements: Vec<_> = resolve
+        .iter()
+        .flat_map(|parent| {
+            resolve
+                .deps(parent)
+                .filter(|(dep_id, _)| *dep_id == pkg_id)
+                .flat_map(move |(_, deps)| deps.iter().map(move |dep| (parent, dep.clone())))
+        })
+        .collect();
+    requirements.sort_by(|(a, a_dep), (b, b_dep)| {
+        a.cmp(b)
+            .then_with(|| a_dep.kind().cmp(&b_dep.kind()))
+            .then_with(|| a_dep.name_in_toml().cmp(&b_dep.name_in_toml()))
+    });
+    requirements
+}
+
+/// The requirement as written in the manifest, as dependencies of a resolve
+/// may be locked to the version in `Cargo.lock`.
+///
+/// Returns `None` if any version matches.
+fn original_req(dep: &Dependency) -> Option<VersionReq> {
+    match dep.version_req() {
+        OptVersionReq::Any => None,
+        OptVersionReq::Req(req)
+        | OptVersionReq::Locked(_, req)
+        | OptVersionReq::Precise(_, req) => Some(req.clone()),
+    }
+}
+
+/// Every version of `pkg_id` published in its registry.
+///
+/// Other sources only have a single version, so this is empty for them.
+fn available_versions(ws: &Workspace<'_>, pkg_id: PackageId) -> CargoResult<Vec<IndexSummary>> {
+    if !pkg_id.source_id().is_registry() {
+        return Ok(Vec::new());
+    }
+    let gctx = ws.gctx();
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+    let mut registry = PackageRegistry::new(gctx)?;
+    let query = Dependency::parse(pkg_id.name(), None, pkg_id.source_id())?;
+    let possibilities = loop {
+        match registry.query_vec(&query, QueryKind::Exact) {
+            std::task::Poll::Ready(res) => {
+                break res?;
+            }
+            std::task::Poll::Pending => registry.block_until_ready()?,
+        }
+    };
+    Ok(possibilities)
+}
+
+/// Why the resolver didn't pick `candidate`, a version newer than the
+/// selected one.
+fn reason(
+    ws: &Workspace<'_>,
+    candidate: &IndexSummary,
+    requirements: &[(PackageId, Dependency)],
+    locked: bool,
+) -> Reason {
+    let summary = candidate.as_summary();
+    let version = summary.version();
+    let mut rejected_by: Vec<_> = requirements
+        .iter()
+        .filter(|(_, dep)| !matches(dep, version))
+        .map(|(parent, _)| *parent)
+        .collect();
+    rejected_by.dedup();
+    if !rejected_by.is_empty() {
+        return Reason::Requirement {
+            dependents: rejected_by
+                .iter()
+                .map(|id| id.to_spec().to_string())
+                .collect(),
+            display: rejected_by.iter().map(|id| id.to_string()).collect(),
+        };
+    }
+    if candidate.is_yanked() {
+        return Reason::Yanked;
+    }
+    let max_rust_version = ws.max_rust_version().or_else(|| {
+        ws.resolve_honors_rust_version()
+            .then(|| ws.rust_version().map(|v| v.as_partial()))
+            .flatten()
+    });
+    if let (Some(max_rust_version), Some(rust_version)) = (max_rust_version, summary.rust_version())
+    {
+        if !rust_version.is_compatible_with(max_rust_version) {
+            return Reason::RustVersion {
+                rust_version: rust_version.to_string(),
+            };
+        }
+    }
+    if locked {
+        return Reason::Locked;
+    }
+    if ws.resolution_mode() != ResolutionMode::Maximum {
+        return Reason::MinimalVersions;
+    }
+    Reason::Conflict
+}
+
+fn matches(dep: &Dependency, version: &Version) -> bool {
+    original_req(dep).map_or(true, |req| req.matches(version))
+}
+
+fn print_human(
+    ws: &Workspace<'_>,
+    pkg_id: PackageId,
+    explanation: &Explanation,
+) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    drop_println!(gctx, "{}", pkg_id);
+    if explanation.locked {
+        drop_println!(gctx, "  locked: this version was already in Cargo.lock");
+    } else {
+        drop_println!(gctx, "  locked: no, this version was newly selected");
+    }
+
+    if explanation.dependents.is_empty() {
+        drop_println!(gctx, "  required by: nothing, it is a workspace member");
+    } else {
+        drop_println!(gctx, "  required by:");
+        for dependent in &explanation.dependents {
+            let mut line = format!(
+                "    {} requires `{}`",
+                dependent.display, dependent.requirement
+            );
+            if dependent.kind != "normal" {
+                line.push_str(&format!(" ({})", dependent.kind));
+            }
+            if dependent.optional {
+                line.push_str(" (optional)");
+            }
+            let mut features = Vec::new();
+            if dependent.default_features {
+                features.push("default");
+            }
+            features.extend(dependent.features.iter().map(String::as_str));
+            if !features.is_empty() {
+                line.push_str(&format!(", features: {}", features.join(", ")));
+            }
+            if dependent.tightest {
+                line.push_str(" [tightest]");
+            }
+            drop_println!(gctx, "{}", line);
+        }
+    }
+
+    if !explanation.newer_versions.is_empty() {
+        drop_println!(gctx, "  newer versions:");
+        for newer in &explanation.newer_versions {
+            let reason = match &newer.reason {
+                Reason::Requirement { display, .. } => {
+                    format!("excluded by the requirement of {}", display.join(", "))
+                }
+                Reason::Yanked => "yanked".to_string(),
+                Reason::RustVersion { rust_version } => {
+                    format!("requires Rust {rust_version}")
+                }
+                Reason::Locked => "allowed, but Cargo.lock is kept as is".to_string(),
+                Reason::MinimalVersions => {
+                    "allowed, but minimal versions are preferred".to_string()
+                }
+                Reason::Conflict => {
+                    "allowed, but conflicts with the rest of the dependency graph".to_string()
+                }
+            };
+            drop_println!(gctx, "    v{}: {}", newer.version, reason);
+        }
+    }
+
+    if !explanation.features.is_empty() {
+        drop_println!(gctx, "  features:");
+        for feature in &explanation.features {
+            if feature.enables.is_empty() {
+                drop_println!(gctx, "    {}", feature.name);
+            } else {
+                drop_println!(
+                    gctx,
+                    "    {} enables {}",
+                    feature.name,
+                    feature.enables.join(", ")
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,325 @@
+//! Implementation of the `cargo features` subcommands.
+//!
+//! `cargo features diff` runs the feature resolver for two sets of command
+//! line flags, e.g. `-p foo` and `--workspace`, and reports which packages
+//! gained or lost features between them. For every changed feature it
+//! explains why the feature is enabled in the invocation that has it:
+//!
+//! * it was requested on the command line for a selected workspace member,
+//! * another enabled feature of the same package enables it,
+//! * a package depending on it enables it in its dependency declaration,
+//! * an enabled feature of a package depending on it enables it with the
+//!   `dep-name/feature-name` syntax.
+
+use crate::core::compiler::{CompileKind, RustcTargetData};
+use crate::core::resolver::features::FeaturesFor;
+use crate::core::resolver::{CliFeatures, ForceAllTargets, HasDevUnits};
+use crate::core::{FeatureValue, PackageId, Resolve, Workspace};
+use crate::drop_println;
+use crate::ops::{self, Packages};
+use crate::util::interning::InternedString;
+use crate::util::CargoResult;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffFormat {
+    Human,
+    Json,
+}
+
+impl clap::ValueEnum for DiffFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[DiffFormat::Human, DiffFormat::Json]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let name = match self {
+            DiffFormat::Human => "human",
+            DiffFormat::Json => "json",
+        };
+        Some(clap::builder::PossibleValue::new(name))
+    }
+}
+
+/// The flags of one of the invocations compared by `cargo features diff`.
+pub struct FeaturesInvocation {
+    pub packages: Packages,
+    pub cli_features: CliFeatures,
+    pub targets: Vec<String>,
+    /// Whether dev-dependencies are taken into account, as with
+    /// `--all-targets`.
+    pub dev_deps: bool,
+}
+
+/// Options for `cargo features diff`.
+pub struct FeaturesDiffOptions {
+    pub before: FeaturesInvocation,
+    pub after: FeaturesInvocation,
+    pub format: DiffFormat,
+}
+
+/// Why a feature is enabled in an invocation.
+#[derive(Serialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum Reason {
+    /// Requested on the command line for a selected workspace member.
+    CommandLine,
+    /// Enabled by another enabled feature of the same package.
+    Feature { feature: InternedString },
+    /// Enabled by the dependency declaration of `package`.
+    Dependency { package: String },
+    /// Enabled by an enabled feature of `package` with the
+    /// `dep-name/feature-name` syntax.
+    DependentFeature {
+        package: String,
+        feature: InternedString,
+    },
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::CommandLine => write!(f, "requested on the command line"),
+            Reason::Feature { feature } => write!(f, "enabled by feature `{feature}`"),
+            Reason::Dependency { package } => {
+                write!(f, "enabled by the dependency declaration of `{package}`")
+            }
+            Reason::DependentFeature { package, feature } => {
+                write!(f, "enabled by feature `{feature}` of `{package}`")
+            }
+        }
+    }
+}
+
+/// The features resolved for one invocation.
+struct Resolved {
+    resolve: Resolve,
+    features: BTreeMap<(PackageId, FeaturesFor), BTreeSet<InternedString>>,
+    /// The selected workspace members.
+    members: HashSet<PackageId>,
+    cli_features: CliFeatures,
+}
+
+impl Resolved {
+    fn new(ws: &Workspace<'_>, invocation: &FeaturesInvocation) -> CargoResult<Resolved> {
+        let requested_kinds = CompileKind::from_requested_targets(ws.gctx(), &invocation.targets)?;
+        let mut target_data = RustcTargetData::new(ws, &requested_kinds)?;
+        let specs = invocation.packages.to_package_id_specs(ws)?;
+        let has_dev_units = if invocation.dev_deps {
+            HasDevUnits::Yes
+        } else {
+            HasDevUnits::No
+        };
+        let ws_resolve = ops::resolve_ws_with_opts(
+            ws,
+            &mut target_data,
+            &requested_kinds,
+            &invocation.cli_features,
+            &specs,
+            has_dev_units,
+            ForceAllTargets::No,
+        )?;
+        let features = ws_resolve
+            .resolved_features
+            .all_activated_features()
+            .map(|(pkg_id, features_for, features)| ((pkg_id, features_for), features.clone()))
+            .collect();
+        let members = invocation
+            .packages
+            .get_packages(ws)?
+            .into_iter()
+            .map(|pkg| pkg.package_id())
+            .collect();
+        Ok(Resolved {
+            resolve: ws_resolve.targeted_resolve,
+            features,
+            members,
+            cli_features: invocation.cli_features.clone(),
+        })
+    }
+
+    /// Features enabled for `pkg_id` for any kind of dependency.
+    fn enabled_features(&self, pkg_id: PackageId) -> BTreeSet<InternedString> {
+        self.features
+            .iter()
+            .filter(|((id, _), _)| *id == pkg_id)
+            .flat_map(|(_, features)| features.iter().copied())
+            .collect()
+    }
+
+    /// Finds why `feature` is enabled for `pkg_id`.
+    fn explain(
+        &self,
+        pkg_id: PackageId,
+        features_for: FeaturesFor,
+        feature: InternedString,
+    ) -> Vec<Reason> {
+        let mut reasons = BTreeSet::new();
+
+        if self.members.contains(&pkg_id) {
+            let cli = &self.cli_features;
+            if cli.all_features
+                || (feature == "default" && cli.uses_default_features)
+                || cli.features.contains(&FeatureValue::Feature(feature))
+            {
+                reasons.insert(Reason::CommandLine);
+            }
+        }
+
+        let summary = self.resolve.summary(pkg_id);
+        for other in &self.features[&(pkg_id, features_for)] {
+            let enables = summary
+                .features()
+                .get(other)
+                .map_or(false, |fvs| fvs.contains(&FeatureValue::Feature(feature)));
+            if enables {
+                reasons.insert(Reason::Feature { feature: *other });
+            }
+        }
+
+        for parent in self.resolve.iter() {
+            if !self.features.keys().any(|(id, _)| *id == parent) {
+                continue;
+            }
+            let parent_features = self.enabled_features(parent);
+            for (dep_id, deps) in self.resolve.deps(parent) {
+                if dep_id != pkg_id {
+                    continue;
+                }
+                for dep in deps {
+                    let in_profile = dep
+                        .features_profile()
+                        .and_then(|profile| summary.feature_profiles().get(&profile))
+                        .map_or(false, |features| features.contains(&feature));
+                    if dep.features().contains(&feature)
+                        || (feature == "default" && dep.uses_default_features())
+                        || in_profile
+                    {
+                        reasons.insert(Reason::Dependency {
+                            package: parent.to_string(),
+                        });
+                    }
+                    for parent_feature in &parent_features {
+                        let Some(fvs) = self.resolve.summary(parent).features().get(parent_feature)
+                        else {
+                            continue;
+                        };
+                        let enables = fvs.iter().any(|fv| {
+                            matches!(
+                                fv,
+                                FeatureValue::DepFeature { dep_name, dep_feature, .. }
+                                    if *dep_name == dep.name_in_toml() && *dep_feature == feature
+                            )
+                        });
+                        if enables {
+                            reasons.insert(Reason::DependentFeature {
+                                package: parent.to_string(),
+                                feature: *parent_feature,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        reasons.into_iter().collect()
+    }
+}
+
+#[derive(Serialize)]
+struct ChangedFeature {
+    feature: InternedString,
+    /// Why the feature is enabled, in the invocation which enables it.
+    reasons: Vec<Reason>,
+}
+
+#[derive(Serialize)]
+struct PackageDiff {
+    id: String,
+    /// Whether the features are for a build dependency or proc-macro.
+    for_host: bool,
+    added: Vec<ChangedFeature>,
+    removed: Vec<ChangedFeature>,
+}
+
+/// Resolves features for both invocations and prints the differences.
+pub fn features_diff(ws: &Workspace<'_>, opts: &FeaturesDiffOptions) -> CargoResult<()> {
+    let before = Resolved::new(ws, &opts.before)?;
+    let after = Resolved::new(ws, &opts.after)?;
+
+    let empty = BTreeSet::new();
+    let keys: BTreeSet<_> = before
+        .features
+        .keys()
+        .chain(after.features.keys())
+        .collect();
+    let mut diffs = Vec::new();
+    for &(pkg_id, features_for) in keys {
+        let key = (pkg_id, features_for);
+        let old = before.features.get(&key).unwrap_or(&empty);
+        let new = after.features.get(&key).unwrap_or(&empty);
+        let added: Vec<_> = new
+            .difference(old)
+            .map(|&feature| ChangedFeature {
+                feature,
+                reasons: after.explain(pkg_id, features_for, feature),
+            })
+            .collect();
+        let removed: Vec<_> = old
+            .difference(new)
+            .map(|&feature| ChangedFeature {
+                feature,
+                reasons: before.explain(pkg_id, features_for, feature),
+            })
+            .collect();
+        if added.is_empty() && removed.is_empty() {
+            continue;
+        }
+        diffs.push((pkg_id, features_for, added, removed));
+    }
+
+    let gctx = ws.gctx();
+    match opts.format {
+        DiffFormat::Human => {
+            if diffs.is_empty() {
+                gctx.shell()
+                    .note("both invocations enable the same features")?;
+            }
+            for (pkg_id, features_for, added, removed) in &diffs {
+                if *features_for == FeaturesFor::NormalOrDev {
+                    drop_println!(gctx, "{pkg_id}");
+                } else {
+                    drop_println!(gctx, "{pkg_id} [{features_for}]");
+                }
+                let changes = added
+                    .iter()
+                    .map(|change| ('+', change))
+                    .chain(removed.iter().map(|change| ('-', change)));
+                for (sign, change) in changes {
+                    drop_println!(gctx, "  {sign} {}", change.feature);
+                    for reason in &change.reasons {
+                        drop_println!(gctx, "      {reason}");
+                    }
+                }
+            }
+        }
+        DiffFormat::Json => {
+            #[derive(Serialize)]
+            struct Diff {
+                packages: Vec<PackageDiff>,
+            }
+            let packages = diffs
+                .into_iter()
+                .map(|(pkg_id, features_for, added, removed)| PackageDiff {
+                    id: pkg_id.to_spec().to_string(),
+                    for_host: features_for == FeaturesFor::HostDep,
+                    added,
+                    removed,
+                })
+                .collect();
+            gctx.shell().print_json(&Diff { packages })?;
+        }
+    }
+    Ok(())
+}
@@ -2,17 +2,27 @@ use crate::core::registry::PackageRegistry;
 use crate::core::resolver::features::{CliFeatures, HasDevUnits};
 use crate::core::shell::Verbosity;
 use crate::core::Registry as _;
-use crate::core::{PackageId, PackageIdSpec, PackageIdSpecQuery};
+use crate::core::{Dependency, PackageId, PackageIdSpec, PackageIdSpecQuery};
 use crate::core::{Resolve, SourceId, Workspace};
 use crate::ops;
 use crate::sources::source::QueryKind;
+use crate::sources::IndexSummary;
 use crate::util::cache_lock::CacheLockMode;
-use crate::util::context::GlobalContext;
+use crate::util::context::{GlobalContext, ResolutionMode};
 use crate::util::style;
-use crate::util::CargoResult;
+use crate::util::toml_mut;
+use crate::util::toml_mut::dependency::MaybeWorkspace;
+use crate::util::toml_mut::manifest::LocalManifest;
+use crate::util::toml_mut::upgrade::upgrade_requirement;
+use crate::util::{CargoResult, OptVersionReq};
+use cargo_util_schemas::core::PartialVersion;
+use cargo_util_schemas::manifest::RustVersion;
+use semver::{Comparator, Op, Version, VersionReq};
+use serde::Serialize;
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, HashSet};
-use tracing::debug;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::{debug, trace};
+use url::Url;
 
 pub struct UpdateOptions<'a> {
     pub gctx: &'a GlobalContext,
@@ -21,6 +31,31 @@ pub struct UpdateOptions<'a> {
     pub recursive: bool,
     pub dry_run: bool,
     pub workspace: bool,
+    /// Reports links to the changes of each updated registry package.
+    pub changelog: Option<ChangelogFormat>,
+}
+
+/// How `cargo update --changelog` reports the changes of updated packages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangelogFormat {
+    /// A `Changelog` status line per updated package.
+    Human,
+    /// A single JSON object on stdout.
+    Json,
+}
+
+impl clap::ValueEnum for ChangelogFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[ChangelogFormat::Human, ChangelogFormat::Json]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let name = match self {
+            ChangelogFormat::Human => "human",
+            ChangelogFormat::Json => "json",
+        };
+        Some(clap::builder::PossibleValue::new(name))
+    }
 }
 
 pub fn generate_lockfile(ws: &Workspace<'_>) -> CargoResult<()> {
@@ -177,6 +212,9 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
         opts.precise.is_some(),
         &mut registry,
     )?;
+    if let Some(format) = opts.changelog {
+        print_changelogs(ws, &previous_resolve, &resolve, registry, format)?;
+    }
     if opts.dry_run {
         opts.gctx
             .shell()
@@ -187,6 +225,347 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
     Ok(())
 }
 
+#[derive(Serialize)]
+struct ChangelogOutput {
+    updates: Vec<PackageChangelog>,
+}
+
+#[derive(Serialize)]
+struct PackageChangelog {
+    name: String,
+    from: Version,
+    to: Version,
+    /// The `package.repository` of the new version.
+    repository: Option<String>,
+    /// The link to the changes between both versions, if the repository is
+    /// on a known forge.
+    changelog: Option<String>,
+}
+
+/// Prints links to the changes of the registry packages updated from
+/// `previous_resolve` to `resolve`.
+///
+/// The index doesn't record the repository of a package, so the new versions
+/// are downloaded to read it from their manifest.
+fn print_changelogs(
+    ws: &Workspace<'_>,
+    previous_resolve: &Resolve,
+    resolve: &Resolve,
+    registry: PackageRegistry<'_>,
+    format: ChangelogFormat,
+) -> CargoResult<()> {
+    let changes: Vec<_> = PackageDiff::diff(previous_resolve, resolve)
+        .iter()
+        .filter_map(|diff| diff.change().map(|(from, to)| (*from, *to)))
+        .filter(|(_, to)| to.source_id().is_registry())
+        .collect();
+    let ids: Vec<_> = changes.iter().map(|(_, to)| *to).collect();
+    let pkg_set = registry.get(&ids)?;
+    let pkgs = pkg_set.get_many(ids)?;
+
+    let mut updates = Vec::new();
+    for (from, to) in changes {
+        let pkg = pkgs
+            .iter()
+            .find(|pkg| pkg.package_id() == to)
+            .expect("downloaded");
+        let repository = pkg.manifest().metadata().repository.clone();
+        let changelog = repository
+            .as_deref()
+            .and_then(|repository| changelog_url(repository, from.version(), to.version()));
+        updates.push(PackageChangelog {
+            name: to.name().to_string(),
+            from: from.version().clone(),
+            to: to.version().clone(),
+            repository,
+            changelog,
+        });
+    }
+
+    match format {
+        ChangelogFormat::Human => {
+            for update in &updates {
+                let Some(link) = update.changelog.as_ref().or(update.repository.as_ref()) else {
+                    continue;
+                };
+                ws.gctx().shell().status_with_color(
+                    "Changelog",
+                    format!("{} v{} -> v{}: {link}", update.name, update.from, update.to),
+                    &style::NOTE,
+                )?;
+            }
+        }
+        ChangelogFormat::Json => ws.gctx().shell().print_json(&ChangelogOutput { updates })?,
+    }
+    Ok(())
+}
+
+/// Guesses the link comparing the tags of two versions in `repository`.
+///
+/// Tags are assumed to be the version prefixed with `v`, like `v1.0.0`. Only
+/// GitHub, GitLab and Codeberg are known, as there is no common URL scheme
+/// for comparing tags.
+fn changelog_url(repository: &str, from: &Version, to: &Version) -> Option<String> {
+    let url = Url::parse(repository).ok()?;
+    let host = url.host_str()?;
+    let segments: Vec<_> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+    let (path, compare) = match host {
+        // Repositories are often given with the path of a package within
+        // them, like `https://github.com/owner/repo/tree/main/crates/foo`.
+        "github.com" | "codeberg.org" => (segments.get(..2)?.join("/"), "compare"),
+        // Projects can be nested in groups, which the `-` separates from the
+        // pages of the project.
+        "gitlab.com" => (
+            segments
+                .iter()
+                .take_while(|s| **s != "-")
+                .copied()
+                .collect::<Vec<_>>()
+                .join("/"),
+            "-/compare",
+        ),
+        _ => return None,
+    };
+    let path = path.trim_end_matches(".git");
+    if path.is_empty() {
+        return None;
+    }
+    Some(format!("https://{host}/{path}/{compare}/v{from}...v{to}"))
+}
+
+/// The latest version each dependency selected by `cargo update --breaking`
+/// was upgraded to, keyed by package name and source.
+pub type UpgradeMap = HashMap<(String, SourceId), Version>;
+
+/// Upgrades the version requirements of the dependencies of the workspace
+/// members to the latest available, semver incompatible, versions.
+///
+/// Only dependencies selected by the package ID specs of `to_update`, or all
+/// if it is empty, are upgraded, and only when they come from a registry, are
+/// not renamed and have a single caret requirement. Specs with a version or a
+/// URL select the dependencies locked to a package they match. The members
+/// are updated in memory so the workspace can be resolved with the new
+/// requirements, see [`write_manifest_upgrades`] to write them back to the
+/// manifests.
+///
+/// Fails if a spec selects no dependency of the workspace members.
+pub fn upgrade_manifests(ws: &mut Workspace<'_>, to_update: &[String]) -> CargoResult<UpgradeMap> {
+    let gctx = ws.gctx();
+    let mut upgrades = HashMap::new();
+    let mut upgrade_messages = HashSet::new();
+    let specs = to_update
+        .iter()
+        .map(|spec| PackageIdSpec::parse(spec))
+        .collect::<Result<Vec<_>, _>>()?;
+    let previous_resolve = ops::load_pkg_lockfile(ws)?;
+    let mut selected_specs = HashSet::new();
+
+    // Updates often require a lot of modifications to the registry, so ensure
+    // that we're synchronized against other Cargos.
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+
+    let mut registry = PackageRegistry::new(gctx)?;
+    registry.lock_patches();
+
+    for member in ws.members_mut() {
+        debug!("upgrading manifest for `{}`", member.name());
+
+        *member.manifest_mut().summary_mut() = member
+            .manifest()
+            .summary()
+            .clone()
+            .try_map_dependencies(|dep| {
+                upgrade_dependency(
+                    gctx,
+                    &specs,
+                    previous_resolve.as_ref(),
+                    &mut selected_specs,
+                    &mut registry,
+                    &mut upgrades,
+                    &mut upgrade_messages,
+                    dep,
+                )
+            })?;
+    }
+
+    if let Some(spec) = specs.iter().find(|spec| !selected_specs.contains(*spec)) {
+        anyhow::bail!(
+            "package ID specification `{spec}` did not match any dependency \
+             of the workspace members"
+        );
+    }
+
+    Ok(upgrades)
+}
+
+/// Whether `spec` selects `dep` for `cargo update --breaking`.
+fn selects_dependency(spec: &PackageIdSpec, dep: &Dependency, previous: Option<&Resolve>) -> bool {
+    if spec.name() != dep.package_name().as_str() {
+        return false;
+    }
+    if spec.partial_version().is_none() && spec.url().is_none() {
+        return true;
+    }
+    previous.map_or(false, |previous| {
+        previous
+            .iter()
+            .any(|id| dep.matches_id(id) && spec.matches(id))
+    })
+}
+
+fn upgrade_dependency(
+    gctx: &GlobalContext,
+    specs: &[PackageIdSpec],
+    previous: Option<&Resolve>,
+    selected_specs: &mut HashSet<PackageIdSpec>,
+    registry: &mut PackageRegistry<'_>,
+    upgrades: &mut UpgradeMap,
+    upgrade_messages: &mut HashSet<String>,
+    dep: Dependency,
+) -> CargoResult<Dependency> {
+    let name = dep.package_name();
+    if !specs.is_empty() {
+        let selected: Vec<_> = specs
+            .iter()
+            .filter(|spec| selects_dependency(spec, &dep, previous))
+            .collect();
+        if selected.is_empty() {
+            trace!("skipping dependency `{name}` not selected for upgrading");
+            return Ok(dep);
+        }
+        selected_specs.extend(selected.into_iter().cloned());
+    }
+    if name != dep.name_in_toml() {
+        trace!(
+            "skipping dependency `{name}` renamed to `{}`",
+            dep.name_in_toml()
+        );
+        return Ok(dep);
+    }
+    if !dep.source_id().is_registry() {
+        trace!("skipping non-registry dependency `{name}`");
+        return Ok(dep);
+    }
+    let OptVersionReq::Req(current) = dep.version_req() else {
+        trace!("skipping dependency `{name}` without a simple version requirement");
+        return Ok(dep);
+    };
+    let [comparator] = &current.comparators[..] else {
+        trace!("skipping dependency `{name}` with multiple version comparators");
+        return Ok(dep);
+    };
+    if comparator.op != Op::Caret {
+        trace!("skipping dependency `{name}` with a non-caret requirement");
+        return Ok(dep);
+    }
+
+    let query = Dependency::parse(name, None, dep.source_id())?;
+    let possibilities = loop {
+        match registry.query_vec(&query, QueryKind::Exact) {
+            std::task::Poll::Ready(res) => {
+                break res?;
+            }
+            std::task::Poll::Pending => registry.block_until_ready()?,
+        }
+    };
+    let Some(latest) = possibilities
+        .iter()
+        .map(|s| s.as_summary().version())
+        .filter(|v| v.pre.is_empty())
+        .max()
+    else {
+        trace!("skipping dependency `{name}` without any published versions");
+        return Ok(dep);
+    };
+    if current.matches(latest) {
+        trace!("skipping dependency `{name}` without a breaking update available");
+        return Ok(dep);
+    }
+    let Some(new_req) = upgrade_requirement(&current.to_string(), latest) else {
+        return Ok(dep);
+    };
+
+    let upgrade_message = format!("{name} {current} -> {new_req}");
+    if upgrade_messages.insert(upgrade_message.clone()) {
+        gctx.shell()
+            .status_with_color("Upgrading", &upgrade_message, &style::GOOD)?;
+    }
+    upgrades.insert((name.to_string(), dep.source_id()), latest.clone());
+
+    let mut dep = dep;
+    dep.set_version_req(OptVersionReq::Req(VersionReq::parse(&new_req)?));
+    Ok(dep)
+}
+
+/// Writes the upgrades found by [`upgrade_manifests`] to the manifest of the
+/// workspace root, including `[workspace.dependencies]`, and the manifests
+/// of the workspace members.
+///
+/// Returns whether any manifest changed, even if nothing was written because
+/// of `dry_run`.
+pub fn write_manifest_upgrades(
+    ws: &Workspace<'_>,
+    upgrades: &UpgradeMap,
+    dry_run: bool,
+) -> CargoResult<bool> {
+    if upgrades.is_empty() {
+        return Ok(false);
+    }
+
+    let mut manifest_paths = vec![ws.root_manifest()];
+    for member in ws.members() {
+        if member.manifest_path() != ws.root_manifest() {
+            manifest_paths.push(member.manifest_path());
+        }
+    }
+
+    let mut any_file_has_changed = false;
+    for manifest_path in manifest_paths {
+        let crate_root = manifest_path.parent().expect("manifest path is absolute");
+        let mut local_manifest = LocalManifest::try_new(manifest_path)?;
+        let mut manifest_has_changed = false;
+
+        for dep_table in local_manifest.get_dependency_tables_mut() {
+            for (mut dep_key, dep_item) in dep_table.iter_mut() {
+                let dependency = toml_mut::dependency::Dependency::from_toml(
+                    crate_root,
+                    dep_key.get(),
+                    dep_item,
+                )?;
+                let Some(toml_mut::dependency::Source::Registry(source)) = dependency.source()
+                else {
+                    continue;
+                };
+                let MaybeWorkspace::Other(source_id) = dependency.source_id(ws.gctx())? else {
+                    continue;
+                };
+                let Some(latest) = upgrades.get(&(dependency.name.clone(), source_id)) else {
+                    continue;
+                };
+                let Some(new_req) = upgrade_requirement(&source.version, latest) else {
+                    continue;
+                };
+
+                trace!("upgrading dependency `{}`", dependency.name);
+                let dependency = dependency
+                    .clone()
+                    .set_source(toml_mut::dependency::RegistrySource::new(new_req));
+                dependency.update_toml(crate_root, &mut dep_key, dep_item);
+                manifest_has_changed = true;
+                any_file_has_changed = true;
+            }
+        }
+
+        if manifest_has_changed && !dry_run {
+            debug!("writing upgraded manifest to {}", manifest_path.display());
+            // The workspace root may be a virtual manifest.
+            local_manifest.write_any()?;
+        }
+    }
+
+    Ok(any_file_has_changed)
+}
+
 /// Prints lockfile change statuses.
 ///
 /// This would acquire the package-cache lock, as it may update the index to
@@ -219,7 +598,9 @@ fn print_lockfile_generation(
         return Ok(());
     }
     status_locking(ws, num_pkgs)?;
+    let added: Vec<_> = diff.iter().flat_map(|d| d.added.iter().copied()).collect();
 
+    let mut capped = Vec::new();
     for diff in diff {
         fn format_latest(version: semver::Version) -> String {
             let warn = style::WARN;
@@ -251,16 +632,22 @@ fn print_lockfile_generation(
                 None
             };
 
-            if let Some(latest) = latest {
+            // With a minimal resolution, report every minimal version chosen.
+            if latest.is_some() || is_minimally_resolved(ws, resolve, *package) {
                 ws.gctx().shell().status_with_color(
                     "Adding",
-                    format!("{package}{latest}"),
+                    format!("{package}{}", latest.unwrap_or_default()),
                     &style::NOTE,
                 )?;
             }
+            capped.extend(rust_version_cap(ws, &possibilities, *package));
         }
     }
 
+    warn_incompatible_rust_versions(ws, resolve, &added)?;
+    warn_unbounded_minimal_versions(ws, resolve, &added)?;
+    note_rust_version_caps(ws, &capped)?;
+
     Ok(())
 }
 
@@ -276,6 +663,7 @@ fn print_lockfile_sync(
         return Ok(());
     }
     status_locking(ws, num_pkgs)?;
+    let added: Vec<_> = diff.iter().flat_map(|d| d.added.iter().copied()).collect();
 
     for diff in diff {
         fn format_latest(version: semver::Version) -> String {
@@ -355,6 +743,9 @@ fn print_lockfile_sync(
         }
     }
 
+    warn_incompatible_rust_versions(ws, resolve, &added)?;
+    warn_unbounded_minimal_versions(ws, resolve, &added)?;
+
     Ok(())
 }
 
@@ -370,8 +761,10 @@ fn print_lockfile_updates(
     if !precise {
         status_locking(ws, num_pkgs)?;
     }
+    let added: Vec<_> = diff.iter().flat_map(|d| d.added.iter().copied()).collect();
 
     let mut unchanged_behind = 0;
+    let mut capped = Vec::new();
     for diff in diff {
         fn format_latest(version: semver::Version) -> String {
             let warn = style::WARN;
@@ -417,6 +810,8 @@ fn print_lockfile_updates(
             // regardless of whether the build metadata has gone up or down.
             // This metadata is often stuff like git commit hashes, which are
             // not meaningfully ordered.
+            capped.extend(rust_version_cap(ws, &possibilities, *added));
+
             if removed.version().cmp_precedence(added.version()) == Ordering::Greater {
                 ws.gctx()
                     .shell()
@@ -453,9 +848,11 @@ fn print_lockfile_updates(
                     format!("{package}{latest}"),
                     &style::NOTE,
                 )?;
+                capped.extend(rust_version_cap(ws, &possibilities, *package));
             }
         }
         for package in &diff.unchanged {
+            capped.extend(rust_version_cap(ws, &possibilities, *package));
             let latest = if !possibilities.is_empty() {
                 possibilities
                     .iter()
@@ -481,6 +878,13 @@ fn print_lockfile_updates(
         }
     }
 
+    // A `--precise` version was asked for, so there was nothing to fall back from.
+    if !precise {
+        warn_incompatible_rust_versions(ws, resolve, &added)?;
+        warn_unbounded_minimal_versions(ws, resolve, &added)?;
+        note_rust_version_caps(ws, &capped)?;
+    }
+
     if ws.gctx().shell().verbosity() == Verbosity::Verbose {
         ws.gctx().shell().note(
             "to see how you depend on a package, run `cargo tree --invert --package <dep>@<ver>`",
@@ -502,23 +906,16 @@ fn status_locking(ws: &Workspace<'_>, num_pkgs: usize) -> CargoResult<()> {
     let plural = if num_pkgs == 1 { "" } else { "s" };
 
     let mut cfg = String::new();
-    // Don't have a good way to describe `direct_minimal_versions` atm
-    if !ws.gctx().cli_unstable().direct_minimal_versions {
+    // Don't have a good way to describe `direct-minimal` atm
+    if ws.resolution_mode() != ResolutionMode::DirectMinimal {
         write!(&mut cfg, " to")?;
-        if ws.gctx().cli_unstable().minimal_versions {
+        if ws.resolution_mode() == ResolutionMode::Minimal {
             write!(&mut cfg, " earliest")?;
         } else {
             write!(&mut cfg, " latest")?;
         }
 
-        if ws.resolve_honors_rust_version() {
-            let rust_version = if let Some(ver) = ws.rust_version() {
-                ver.clone().into_partial()
-            } else {
-                let rustc = ws.gctx().load_global_rustc(Some(ws))?;
-                let rustc_version = rustc.version.clone().into();
-                rustc_version
-            };
+        if let Some(rust_version) = resolve_rust_version(ws)? {
             write!(&mut cfg, " Rust {rust_version}")?;
         }
         write!(&mut cfg, " compatible version{plural}")?;
@@ -530,6 +927,160 @@ fn status_locking(ws: &Workspace<'_>, num_pkgs: usize) -> CargoResult<()> {
     Ok(())
 }
 
+/// The Rust version dependencies are resolved against, if the resolver honors
+/// `rust-version` at all.
+fn resolve_rust_version(ws: &Workspace<'_>) -> CargoResult<Option<PartialVersion>> {
+    if let Some(rust_version) = ws.max_rust_version() {
+        return Ok(Some(rust_version.clone()));
+    }
+    if !ws.resolve_honors_rust_version() {
+        return Ok(None);
+    }
+    let rust_version = if let Some(ver) = ws.rust_version() {
+        ver.clone().into_partial()
+    } else {
+        let rustc = ws.gctx().load_global_rustc(Some(ws))?;
+        rustc.version.clone().into()
+    };
+    Ok(Some(rust_version))
+}
+
+/// Warns about newly locked packages which need a newer Rust than the
+/// workspace's, which the resolver only picks when no compatible version
+/// satisfies the dependency requirements.
+fn warn_incompatible_rust_versions(
+    ws: &Workspace<'_>,
+    resolve: &Resolve,
+    added: &[PackageId],
+) -> CargoResult<()> {
+    let Some(rust_version) = resolve_rust_version(ws)? else {
+        return Ok(());
+    };
+    for pkg_id in added {
+        if ws.members().any(|m| m.package_id() == *pkg_id) {
+            continue;
+        }
+        let Some(pkg_rust_version) = resolve.summary(*pkg_id).rust_version() else {
+            continue;
+        };
+        if !pkg_rust_version.is_compatible_with(&rust_version) {
+            ws.gctx().shell().warn(format!(
+                "no version of `{}` compatible with Rust {rust_version} satisfies the \
+                 requirements, falling back to `{pkg_id}` which requires Rust {pkg_rust_version}",
+                pkg_id.name(),
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Finds the newest SemVer compatible version of `pkg_id` which was skipped
+/// because it requires a newer Rust than `cargo update --rust-version`.
+fn rust_version_cap(
+    ws: &Workspace<'_>,
+    possibilities: &[IndexSummary],
+    pkg_id: PackageId,
+) -> Option<(PackageId, Version, RustVersion)> {
+    let max_rust_version = ws.max_rust_version()?;
+    let current = pkg_id.version();
+    let compatible = VersionReq {
+        comparators: vec![Comparator {
+            op: Op::Caret,
+            major: current.major,
+            minor: Some(current.minor),
+            patch: Some(current.patch),
+            pre: current.pre.clone(),
+        }],
+    };
+    possibilities
+        .iter()
+        .filter(|s| !s.is_yanked())
+        .map(|s| s.as_summary())
+        .filter(|s| is_latest(s.version(), current) && compatible.matches(s.version()))
+        .filter_map(|s| {
+            let rust_version = s.rust_version()?;
+            (!rust_version.is_compatible_with(max_rust_version))
+                .then(|| (s.version().clone(), rust_version.clone()))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(version, rust_version)| (pkg_id, version, rust_version))
+}
+
+/// Reports the packages held back by `cargo update --rust-version`.
+fn note_rust_version_caps(
+    ws: &Workspace<'_>,
+    capped: &[(PackageId, Version, RustVersion)],
+) -> CargoResult<()> {
+    let Some(max_rust_version) = ws.max_rust_version() else {
+        return Ok(());
+    };
+    for (pkg_id, version, rust_version) in capped {
+        ws.gctx().shell().note(format!(
+            "capped `{pkg_id}` for Rust {max_rust_version}, \
+             v{version} requires Rust {rust_version}"
+        ))?;
+    }
+    Ok(())
+}
+
+/// Whether the resolver preferred the lowest compatible version of `pkg_id`.
+fn is_minimally_resolved(ws: &Workspace<'_>, resolve: &Resolve, pkg_id: PackageId) -> bool {
+    if !pkg_id.source_id().is_registry() {
+        return false;
+    }
+    match ws.resolution_mode() {
+        ResolutionMode::Maximum => false,
+        ResolutionMode::Minimal => true,
+        ResolutionMode::DirectMinimal => ws.members().any(|m| {
+            resolve
+                .deps(m.package_id())
+                .any(|(dep_id, _)| dep_id == pkg_id)
+        }),
+    }
+}
+
+/// Warns about pre-1.0 packages locked to their minimal version, when the
+/// requirement selecting them also accepts later `0.x` releases.
+///
+/// Pre-1.0 releases of different minor versions (or patch versions for
+/// `0.0.x`) are not semver compatible, so the minimal version is likely not
+/// what the requirement was meant to allow.
+fn warn_unbounded_minimal_versions(
+    ws: &Workspace<'_>,
+    resolve: &Resolve,
+    added: &[PackageId],
+) -> CargoResult<()> {
+    for pkg_id in added {
+        let version = pkg_id.version();
+        if version.major != 0 || !is_minimally_resolved(ws, resolve, *pkg_id) {
+            continue;
+        }
+        let next_incompatible = if version.minor == 0 {
+            semver::Version::new(0, 0, version.patch + 1)
+        } else {
+            semver::Version::new(0, version.minor + 1, 0)
+        };
+        let unbounded = resolve
+            .iter()
+            .flat_map(|parent| {
+                resolve
+                    .deps(parent)
+                    .filter(|(dep_id, _)| dep_id == pkg_id)
+                    .flat_map(move |(_, deps)| deps.iter().map(move |dep| (parent, dep)))
+            })
+            .find(|(_, dep)| dep.version_req().matches(&next_incompatible));
+        if let Some((parent, dep)) = unbounded {
+            ws.gctx().shell().warn(format!(
+                "`{pkg_id}` is the minimal version of `{}` matching `{}` (required by `{parent}`), \
+                 but the requirement also accepts later, incompatible pre-1.0 versions",
+                pkg_id.name(),
+                dep.version_req(),
+            ))?;
+        }
+    }
+    Ok(())
+}
+
 fn is_latest(candidate: &semver::Version, current: &semver::Version) -> bool {
     current < candidate
                 // Only match pre-release if major.minor.patch are the same
@@ -840,7 +840,7 @@ pub fn install_list(dst: Option<&str>, gctx: &GlobalContext) -> CargoResult<()>
     let root = resolve_root(dst, gctx)?;
     let tracker = InstallTracker::load(gctx, &root)?;
     for (k, v) in tracker.all_installed_bins() {
-        drop_println!(gctx, "{}:", k);
+        drop_println!(gctx, "{}:", display_installed(*k));
         for bin in v {
             drop_println!(gctx, "    {}", bin);
         }
@@ -848,6 +848,22 @@ pub fn install_list(dst: Option<&str>, gctx: &GlobalContext) -> CargoResult<()>
     Ok(())
 }
 
+/// Like the `Display` of [`PackageId`], but with the full commit of packages
+/// installed from git, rather than an abbreviated one.
+fn display_installed(pkg_id: PackageId) -> String {
+    let source_id = pkg_id.source_id();
+    match source_id.precise_git_fragment() {
+        Some(commit) => format!(
+            "{} v{} ({}#{})",
+            pkg_id.name(),
+            pkg_id.version(),
+            source_id.with_git_precise(None),
+            commit
+        ),
+        None => pkg_id.to_string(),
+    }
+}
+
 /// Removes executables that are no longer part of a package that was
 /// previously installed.
 fn remove_orphaned_bins(
@@ -0,0 +1,25 @@
+//! Implementation of `cargo links` subcommand.
+//!
+//! Lists the `links` values of the packages in the dependency graph of the
+//! workspace, with the package owning each of them. These are the names to
+//! use in build script overrides, like `[target.<triple>.<links>]`.
+
+use crate::core::Workspace;
+use crate::drop_println;
+use crate::ops;
+use crate::util::errors::CargoResult;
+
+pub fn links(ws: &Workspace<'_>) -> CargoResult<()> {
+    let (_packages, resolve) = ops::resolve_ws(ws)?;
+    // The resolver already made sure that a `links` value is owned by at
+    // most one package.
+    let mut links: Vec<_> = resolve
+        .iter()
+        .filter_map(|id| Some((resolve.summary(id).links()?, id)))
+        .collect();
+    links.sort();
+    for (name, id) in links {
+        drop_println!(ws.gctx(), "{} {}", name, id.to_spec());
+    }
+    Ok(())
+}
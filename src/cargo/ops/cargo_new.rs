@@ -971,37 +971,12 @@ fn update_manifest_with_new_member(
     workspace_document: &mut toml_edit::DocumentMut,
     display_path: &str,
 ) -> CargoResult<bool> {
-    // If the members element already exist, check if one of the patterns
-    // in the array already includes the new package's relative path.
-    // - Add the relative path if the members don't match the new package's path.
-    // - Create a new members array if there are no members element in the workspace yet.
-    if let Some(workspace) = workspace_document.get_mut("workspace") {
-        if let Some(members) = workspace
-            .get_mut("members")
-            .and_then(|members| members.as_array_mut())
-        {
-            for member in members.iter() {
-                let pat = member
-                    .as_str()
-                    .with_context(|| format!("invalid non-string member `{}`", member))?;
-                let pattern = glob::Pattern::new(pat)
-                    .with_context(|| format!("cannot build glob pattern from `{}`", pat))?;
-
-                if pattern.matches(&display_path) {
-                    return Ok(false);
-                }
-            }
-
-            let was_sorted = is_sorted(members.iter().map(Value::as_str));
-            members.push(display_path);
-            if was_sorted {
-                members.sort_by(|lhs, rhs| lhs.as_str().cmp(&rhs.as_str()));
-            }
-        } else {
-            let mut array = Array::new();
-            array.push(display_path);
-
-            workspace["members"] = toml_edit::value(array);
+    if let Some(workspace) = workspace_document
+        .get_mut("workspace")
+        .and_then(|workspace| workspace.as_table_like_mut())
+    {
+        if !add_workspace_member(workspace, display_path)? {
+            return Ok(false);
         }
     }
 
@@ -1012,6 +987,46 @@ fn update_manifest_with_new_member(
     Ok(true)
 }
 
+/// Adds `display_path` to the `members` of the `workspace` table, unless one
+/// of the patterns in the array already includes it.
+///
+/// Creates the array if there are no members in the workspace yet, and keeps
+/// the members sorted if they were. Returns `false` if the path was already
+/// a member.
+pub(crate) fn add_workspace_member(
+    workspace: &mut dyn toml_edit::TableLike,
+    display_path: &str,
+) -> CargoResult<bool> {
+    let Some(members) = workspace
+        .get_mut("members")
+        .and_then(|members| members.as_array_mut())
+    else {
+        let mut array = Array::new();
+        array.push(display_path);
+        workspace.insert("members", toml_edit::value(array));
+        return Ok(true);
+    };
+
+    for member in members.iter() {
+        let pat = member
+            .as_str()
+            .with_context(|| format!("invalid non-string member `{}`", member))?;
+        let pattern = glob::Pattern::new(pat)
+            .with_context(|| format!("cannot build glob pattern from `{}`", pat))?;
+
+        if pattern.matches(&display_path) {
+            return Ok(false);
+        }
+    }
+
+    let was_sorted = is_sorted(members.iter().map(Value::as_str));
+    members.push(display_path);
+    if was_sorted {
+        members.sort_by(|lhs, rhs| lhs.as_str().cmp(&rhs.as_str()));
+    }
+    Ok(true)
+}
+
 fn get_display_path(root_manifest_path: &Path, package_path: &Path) -> CargoResult<String> {
     // Find the relative path for the package from the workspace root directory.
     let workspace_root = root_manifest_path.parent().with_context(|| {
@@ -2,14 +2,17 @@ use crate::core::compiler::artifact::match_artifacts_kind_with_targets;
 use crate::core::compiler::{CompileKind, RustcTargetData};
 use crate::core::dependency::DepKind;
 use crate::core::package::SerializedPackage;
-use crate::core::resolver::{features::CliFeatures, HasDevUnits, Resolve};
-use crate::core::{Package, PackageId, PackageIdSpec, Workspace};
-use crate::ops::{self, Packages};
+use crate::core::profiles::{Profile, Profiles, UnitFor};
+use crate::core::resolver::features::{CliFeatures, FeaturesFor};
+use crate::core::resolver::{HasDevUnits, Resolve};
+use crate::core::{Package, PackageId, PackageIdSpec, PackageIdSpecQuery, Workspace};
+use crate::ops::{self, PackageFilter, Packages};
 use crate::util::interning::InternedString;
 use crate::util::CargoResult;
 use cargo_platform::Platform;
+use cargo_util_schemas::manifest::TomlLints;
 use serde::Serialize;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 
 const VERSION: u32 = 1;
@@ -19,24 +22,33 @@ pub struct OutputMetadataOptions {
     pub no_deps: bool,
     pub version: u32,
     pub filter_platforms: Vec<String>,
+    /// Only output these packages and their dependencies, or the whole
+    /// workspace if empty.
+    pub filter_specs: Vec<String>,
+    /// Leaves out the dependencies from some sources or with some licenses.
+    pub package_filter: PackageFilter,
+    /// The profile of the settings of each package, only reported with
+    /// `-Zunstable-options`.
+    pub profile: InternedString,
 }
 
 /// Loads the manifest, resolves the dependencies of the package to the concrete
 /// used versions - considering overrides - and writes all dependencies in a JSON
 /// format to stdout.
 pub fn output_metadata(ws: &Workspace<'_>, opt: &OutputMetadataOptions) -> CargoResult<ExportInfo> {
-    if opt.version != VERSION {
-        anyhow::bail!(
-            "metadata version {} not supported, only {} is currently supported",
-            opt.version,
-            VERSION
-        );
-    }
+    check_version(opt)?;
+    let settings = PackageSettings::new(ws, opt)?;
     let (packages, resolve) = if opt.no_deps {
-        let packages = ws.members().map(|pkg| pkg.serialized()).collect();
+        let packages = filtered_members(ws, opt)?
+            .map(|pkg| MetadataPackage::new(ws, pkg, settings.as_ref()))
+            .collect();
         (packages, None)
     } else {
         let (packages, resolve) = build_resolve_graph(ws, opt)?;
+        let packages = packages
+            .iter()
+            .map(|pkg| MetadataPackage::new(ws, pkg, settings.as_ref()))
+            .collect();
         (packages, Some(resolve))
     };
 
@@ -55,12 +67,82 @@ pub fn output_metadata(ws: &Workspace<'_>, opt: &OutputMetadataOptions) -> Cargo
     })
 }
 
+/// Like [`output_metadata`], but writes the metadata to stdout as
+/// newline-delimited JSON, one [`NdjsonMessage`] per line, serialized
+/// separately instead of as a single document.
+pub fn output_metadata_ndjson(ws: &Workspace<'_>, opt: &OutputMetadataOptions) -> CargoResult<()> {
+    check_version(opt)?;
+    let gctx = ws.gctx();
+    let settings = PackageSettings::new(ws, opt)?;
+    let package =
+        |pkg: &Package| NdjsonMessage::Package(MetadataPackage::new(ws, pkg, settings.as_ref()));
+    let resolve = if opt.no_deps {
+        for pkg in filtered_members(ws, opt)? {
+            gctx.shell().print_json(&package(pkg))?;
+        }
+        None
+    } else {
+        let (packages, resolve) = build_resolve_graph(ws, opt)?;
+        for pkg in &packages {
+            gctx.shell().print_json(&package(pkg))?;
+        }
+        let MetadataResolve { nodes, root, units } = resolve;
+        for node in nodes {
+            gctx.shell().print_json(&NdjsonMessage::ResolveNode(node))?;
+        }
+        for unit in units.into_iter().flatten() {
+            gctx.shell().print_json(&NdjsonMessage::Unit(unit))?;
+        }
+        Some(NdjsonResolve { root })
+    };
+
+    gctx.shell()
+        .print_json(&NdjsonMessage::Metadata(NdjsonMetadata {
+            workspace_members: ws.members().map(|pkg| pkg.package_id().to_spec()).collect(),
+            workspace_default_members: ws
+                .default_members()
+                .map(|pkg| pkg.package_id().to_spec())
+                .collect(),
+            resolve,
+            target_directory: ws.target_dir().into_path_unlocked(),
+            version: VERSION,
+            workspace_root: ws.root().to_path_buf(),
+            metadata: ws.custom_metadata().cloned(),
+        }))
+}
+
+/// Gets the workspace members selected by `--filter-spec`, for `--no-deps`.
+fn filtered_members<'a>(
+    ws: &'a Workspace<'_>,
+    opt: &OutputMetadataOptions,
+) -> CargoResult<impl Iterator<Item = &'a Package>> {
+    let ids = opt
+        .filter_specs
+        .iter()
+        .map(|spec| PackageIdSpec::query_str(spec, ws.members().map(Package::package_id)))
+        .collect::<CargoResult<HashSet<_>>>()?;
+    Ok(ws
+        .members()
+        .filter(move |pkg| ids.is_empty() || ids.contains(&pkg.package_id())))
+}
+
+fn check_version(opt: &OutputMetadataOptions) -> CargoResult<()> {
+    if opt.version != VERSION {
+        anyhow::bail!(
+            "metadata version {} not supported, only {} is currently supported",
+            opt.version,
+            VERSION
+        );
+    }
+    Ok(())
+}
+
 /// This is the structure that is serialized and displayed to the user.
 ///
 /// See cargo-metadata.adoc for detailed documentation of the format.
 #[derive(Serialize)]
 pub struct ExportInfo {
-    packages: Vec<SerializedPackage>,
+    packages: Vec<MetadataPackage>,
     workspace_members: Vec<PackageIdSpec>,
     workspace_default_members: Vec<PackageIdSpec>,
     resolve: Option<MetadataResolve>,
@@ -70,10 +152,120 @@ pub struct ExportInfo {
     metadata: Option<toml::Value>,
 }
 
+/// A line of `cargo metadata --format ndjson`.
+///
+/// The packages come first, then the nodes and units of the resolve graph,
+/// and finally a `metadata` message with the remaining fields of
+/// [`ExportInfo`].
+#[derive(Serialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+enum NdjsonMessage {
+    Package(MetadataPackage),
+    ResolveNode(MetadataResolveNode),
+    Unit(MetadataUnit),
+    Metadata(NdjsonMetadata),
+}
+
+/// A package, with the settings Cargo builds it with when
+/// `-Zunstable-options` is passed.
+#[derive(Serialize)]
+struct MetadataPackage {
+    #[serde(flatten)]
+    package: SerializedPackage,
+    /// The `[lints]` table, after inheriting `workspace.lints`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lints: Option<TomlLints>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profiles: Option<MetadataProfiles>,
+}
+
+/// The profile a package is built with, for each kind of dependency. See
+/// [`MetadataUnit::kind`].
+#[derive(Serialize)]
+struct MetadataProfiles {
+    normal: Profile,
+    host: Profile,
+}
+
+/// What is needed to compute the profiles of packages.
+struct PackageSettings {
+    profiles: Profiles,
+    kind: CompileKind,
+}
+
+impl PackageSettings {
+    /// Returns `None` without `-Zunstable-options`, as the settings of the
+    /// packages are only reported then.
+    fn new(ws: &Workspace<'_>, opt: &OutputMetadataOptions) -> CargoResult<Option<Self>> {
+        if !ws.gctx().cli_unstable().unstable_options {
+            return Ok(None);
+        }
+        let kinds = CompileKind::from_requested_targets(ws.gctx(), &opt.filter_platforms)?;
+        Ok(Some(PackageSettings {
+            profiles: Profiles::new(ws, opt.profile)?,
+            kind: kinds[0],
+        }))
+    }
+}
+
+impl MetadataPackage {
+    fn new(ws: &Workspace<'_>, pkg: &Package, settings: Option<&PackageSettings>) -> Self {
+        let Some(settings) = settings else {
+            return MetadataPackage {
+                package: pkg.serialized(),
+                lints: None,
+                profiles: None,
+            };
+        };
+        let lints = pkg
+            .manifest()
+            .resolved_toml()
+            .resolved_lints()
+            .expect("previously resolved")
+            .cloned()
+            .unwrap_or_default();
+        let pkg_id = pkg.package_id();
+        let is_member = ws.is_member(pkg);
+        let is_local = pkg_id.source_id().is_path();
+        let profile = |unit_for| {
+            settings
+                .profiles
+                .get_profile(pkg_id, is_member, is_local, unit_for, settings.kind)
+        };
+        MetadataPackage {
+            package: pkg.serialized(),
+            lints: Some(lints),
+            profiles: Some(MetadataProfiles {
+                normal: profile(UnitFor::new_normal(settings.kind)),
+                host: profile(UnitFor::new_host(false, settings.kind)),
+            }),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct NdjsonMetadata {
+    workspace_members: Vec<PackageIdSpec>,
+    workspace_default_members: Vec<PackageIdSpec>,
+    resolve: Option<NdjsonResolve>,
+    target_directory: PathBuf,
+    version: u32,
+    workspace_root: PathBuf,
+    metadata: Option<toml::Value>,
+}
+
+#[derive(Serialize)]
+struct NdjsonResolve {
+    root: Option<PackageIdSpec>,
+}
+
 #[derive(Serialize)]
 struct MetadataResolve {
     nodes: Vec<MetadataResolveNode>,
     root: Option<PackageIdSpec>,
+    /// The features of each unit, only with `-Zunstable-options`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    units: Option<Vec<MetadataUnit>>,
 }
 
 #[derive(Serialize)]
@@ -84,6 +276,22 @@ struct MetadataResolveNode {
     features: Vec<InternedString>,
 }
 
+/// The features a package is built with for one kind of dependency.
+///
+/// With feature resolver v2, the same package may be built with different
+/// features as a normal dependency, a build dependency or proc-macro, or an
+/// artifact dependency for another target.
+#[derive(Serialize)]
+struct MetadataUnit {
+    pkg: PackageIdSpec,
+    /// `"normal"` for normal and dev dependencies, `"host"` for build
+    /// dependencies and proc-macros, or `"artifact"`.
+    kind: &'static str,
+    /// The target of an artifact dependency.
+    target: Option<InternedString>,
+    features: Vec<InternedString>,
+}
+
 #[derive(Serialize)]
 struct Dep {
     // TODO(bindeps): after -Zbindeps gets stabilized,
@@ -126,7 +334,7 @@ struct DepKindInfo {
 fn build_resolve_graph(
     ws: &Workspace<'_>,
     metadata_opts: &OutputMetadataOptions,
-) -> CargoResult<(Vec<SerializedPackage>, MetadataResolve)> {
+) -> CargoResult<(Vec<Package>, MetadataResolve)> {
     // TODO: Without --filter-platform, features are being resolved for `host` only.
     // How should this work?
     let requested_kinds =
@@ -159,29 +367,65 @@ fn build_resolve_graph(
         .map(|pkg| (pkg.package_id(), Package::clone(pkg)))
         .collect();
 
-    // Start from the workspace roots, and recurse through filling out the
-    // map, filtering targets as necessary.
+    // Start from the workspace roots, or the packages given with
+    // `--filter-spec`, and recurse through filling out the map, filtering
+    // targets as necessary.
+    let roots = if metadata_opts.filter_specs.is_empty() {
+        ws.members().map(Package::package_id).collect()
+    } else {
+        metadata_opts
+            .filter_specs
+            .iter()
+            .map(|spec| PackageIdSpec::query_str(spec, ws_resolve.targeted_resolve.iter()))
+            .collect::<CargoResult<Vec<_>>>()?
+    };
     let mut node_map = BTreeMap::new();
-    for member_pkg in ws.members() {
+    for root in roots {
         build_resolve_graph_r(
             &mut node_map,
-            member_pkg.package_id(),
+            root,
             &ws_resolve.targeted_resolve,
             &package_map,
             &target_data,
             &requested_kinds,
+            &metadata_opts.package_filter,
         )?;
     }
+    let units = ws.gctx().cli_unstable().unstable_options.then(|| {
+        let mut units: Vec<_> = ws_resolve
+            .resolved_features
+            .all_activated_features()
+            .filter(|(pkg_id, ..)| node_map.contains_key(pkg_id))
+            .collect();
+        units.sort_by_key(|(pkg_id, features_for, _)| (*pkg_id, *features_for));
+        units
+            .into_iter()
+            .map(|(pkg_id, features_for, features)| {
+                let pkg_id = *package_map.get_key_value(&pkg_id).unwrap().0;
+                let (kind, target) = match features_for {
+                    FeaturesFor::NormalOrDev => ("normal", None),
+                    FeaturesFor::HostDep => ("host", None),
+                    FeaturesFor::ArtifactDep(target) => ("artifact", Some(target.rustc_target())),
+                };
+                MetadataUnit {
+                    pkg: pkg_id.to_spec(),
+                    kind,
+                    target,
+                    features: features.iter().copied().collect(),
+                }
+            })
+            .collect()
+    });
     // Get a Vec of Packages.
     let actual_packages = package_map
         .into_iter()
         .filter_map(|(pkg_id, pkg)| node_map.get(&pkg_id).map(|_| pkg))
-        .map(|pkg| pkg.serialized())
         .collect();
 
     let mr = MetadataResolve {
         nodes: node_map.into_iter().map(|(_pkg_id, node)| node).collect(),
         root: ws.current_opt().map(|pkg| pkg.package_id().to_spec()),
+        units,
     };
     Ok((actual_packages, mr))
 }
@@ -193,6 +437,7 @@ fn build_resolve_graph_r(
     package_map: &BTreeMap<PackageId, Package>,
     target_data: &RustcTargetData<'_>,
     requested_kinds: &[CompileKind],
+    package_filter: &PackageFilter,
 ) -> CargoResult<()> {
     if node_map.contains_key(&pkg_id) {
         return Ok(());
@@ -216,8 +461,10 @@ fn build_resolve_graph_r(
 
     let deps = {
         let mut dep_metadatas = Vec::new();
-        let iter = resolve.deps(pkg_id).filter(|(_dep_id, deps)| {
-            if requested_kinds == [CompileKind::Host] {
+        let iter = resolve.deps(pkg_id).filter(|(dep_id, deps)| {
+            if package_filter.prunes(&package_map[dep_id]) {
+                false
+            } else if requested_kinds == [CompileKind::Host] {
                 true
             } else {
                 requested_kinds.iter().any(|kind| {
@@ -345,6 +592,7 @@ fn build_resolve_graph_r(
             package_map,
             target_data,
             requested_kinds,
+            package_filter,
         )?;
     }
 
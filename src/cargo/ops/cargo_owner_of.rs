@@ -0,0 +1,82 @@
+//! Implementation of `cargo owner-of` subcommand.
+//!
+//! Maps a file to the workspace member and the targets it belongs to, with
+//! the targets discovered when reading the manifests. A file is owned
+//! by the member with the closest root, and by the targets whose source file
+//! it is or, failing that, by the targets whose source directory is the
+//! closest to the file. For example `src/util/mod.rs` is owned by both the
+//! library and the binary of a package with `src/lib.rs` and `src/main.rs`.
+
+use std::path::Path;
+
+use cargo_util::paths;
+
+use crate::core::{Package, Target, Workspace};
+use crate::drop_println;
+use crate::util::errors::CargoResult;
+
+pub fn owner_of(ws: &Workspace<'_>, path: &Path) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let path = paths::normalize_path(&gctx.cwd().join(path));
+    let Some(pkg) = ws
+        .members()
+        .filter(|pkg| path.starts_with(pkg.root()))
+        .max_by_key(|pkg| pkg.root().components().count())
+    else {
+        anyhow::bail!(
+            "`{}` is not owned by any member of the workspace at `{}`",
+            path.display(),
+            ws.root().display()
+        );
+    };
+
+    let targets = owning_targets(pkg, &path);
+    if targets.is_empty() {
+        drop_println!(gctx, "{}", pkg.package_id().to_spec());
+    }
+    for target in targets {
+        drop_println!(
+            gctx,
+            "{} {} {}",
+            pkg.package_id().to_spec(),
+            target.kind().description(),
+            target.name()
+        );
+    }
+    Ok(())
+}
+
+fn owning_targets<'a>(pkg: &'a Package, path: &Path) -> Vec<&'a Target> {
+    let src_paths = pkg
+        .targets()
+        .iter()
+        .filter_map(|target| Some((target, target.src_path().path()?)));
+    let exact: Vec<_> = src_paths
+        .clone()
+        .filter(|(_, src_path)| *src_path == path)
+        .map(|(target, _)| target)
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    // The module files of a target live next to its source file, so the
+    // targets with the closest source directory are the likely owners. A
+    // source file at the root of the package, like `build.rs`, would own
+    // every file of the package though.
+    let ancestors: Vec<_> = src_paths
+        .filter_map(|(target, src_path)| {
+            let dir = src_path.parent()?;
+            (dir != pkg.root() && path.starts_with(dir))
+                .then_some((target, dir.components().count()))
+        })
+        .collect();
+    let Some(depth) = ancestors.iter().map(|(_, depth)| *depth).max() else {
+        return Vec::new();
+    };
+    ancestors
+        .into_iter()
+        .filter(|(_, d)| *d == depth)
+        .map(|(target, _)| target)
+        .collect()
+}
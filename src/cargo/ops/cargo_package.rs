@@ -10,12 +10,13 @@ use crate::core::compiler::{BuildConfig, CompileMode, DefaultExecutor, Executor}
 use crate::core::manifest::Target;
 use crate::core::resolver::CliFeatures;
 use crate::core::{registry::PackageRegistry, resolver::HasDevUnits};
-use crate::core::{Feature, PackageIdSpecQuery, Shell, Verbosity, Workspace};
 use crate::core::{Package, PackageId, PackageSet, Resolve, SourceId};
+use crate::core::{PackageIdSpecQuery, Shell, Verbosity, Workspace};
 use crate::sources::PathSource;
 use crate::util::cache_lock::CacheLockMode;
-use crate::util::context::JobsConfig;
-use crate::util::errors::CargoResult;
+use crate::util::context::{CargoPackageConfig, JobsConfig};
+use crate::util::errors::{AlreadyPrintedError, CargoResult};
+use crate::util::lints::check_package_contents;
 use crate::util::toml::prepare_for_publish;
 use crate::util::{self, human_readable_bytes, restricted_names, FileLock, GlobalContext};
 use crate::{drop_println, ops};
@@ -33,6 +34,8 @@ pub struct PackageOpts<'gctx> {
     pub list: bool,
     pub check_metadata: bool,
     pub allow_dirty: bool,
+    /// Don't include `Cargo.lock`, whatever the package and config say.
+    pub exclude_lockfile: bool,
     pub verify: bool,
     pub jobs: Option<JobsConfig>,
     pub keep_going: bool,
@@ -112,7 +115,9 @@ pub fn package_one(
         None
     };
 
-    let ar_files = build_ar_list(ws, pkg, src_files, vcs_info)?;
+    let include_lockfile = include_lockfile(pkg, opts)?;
+    let ar_files = build_ar_list(ws, pkg, src_files, vcs_info, include_lockfile)?;
+    lint_package_contents(ws, pkg, &ar_files)?;
 
     let filecount = ar_files.len();
 
@@ -151,9 +156,8 @@ pub fn package_one(
     }
 
     dst.seek(SeekFrom::Start(0))?;
-    let src_path = dst.path();
     let dst_path = dst.parent().join(&filename);
-    fs::rename(&src_path, &dst_path)
+    dst.rename(&dst_path)
         .with_context(|| "failed to move temporary tarball into final location")?;
 
     let dst_metadata = dst
@@ -176,7 +180,7 @@ pub fn package_one(
 }
 
 pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option<Vec<FileLock>>> {
-    let specs = &opts.to_package.to_package_id_specs(ws)?;
+    let specs = &opts.to_package.to_member_package_id_specs(ws)?;
     // If -p is used, we should check spec is matched with the members (See #13719)
     if let ops::Packages::Packages(_) = opts.to_package {
         for spec in specs.iter() {
@@ -184,7 +188,20 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
             spec.query(member_ids)?;
         }
     }
-    let pkgs = ws.members_with_features(specs, &opts.cli_features)?;
+    let mut pkgs = ws.members_with_features(specs, &opts.cli_features)?;
+    // Members checked out from git are packaged from their own repository.
+    if let ops::Packages::Packages(_) = opts.to_package {
+        for (pkg, _) in &pkgs {
+            if let Some(url) = ws.git_member_url(pkg) {
+                anyhow::bail!(
+                    "`{}` can't be packaged, it's a workspace member checked out from `{url}`",
+                    pkg.name()
+                );
+            }
+        }
+    } else {
+        pkgs.retain(|(pkg, _)| ws.git_member_url(pkg).is_none());
+    }
 
     let mut dsts = Vec::with_capacity(pkgs.len());
 
@@ -204,6 +221,7 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
                 list: opts.list,
                 check_metadata: opts.check_metadata,
                 allow_dirty: opts.allow_dirty,
+                exclude_lockfile: opts.exclude_lockfile,
                 verify: opts.verify,
                 jobs: opts.jobs.clone(),
                 keep_going: opts.keep_going,
@@ -226,12 +244,39 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
     }
 }
 
+/// Decides whether `Cargo.lock` is packaged.
+///
+/// In order of precedence, this is `--exclude-lockfile`, then
+/// `package.publish-lockfile` in the manifest, then
+/// `package.publish-lockfile` in the config, and otherwise whether the package
+/// has a binary or an example.
+fn include_lockfile(pkg: &Package, opts: &PackageOpts<'_>) -> CargoResult<bool> {
+    if opts.exclude_lockfile {
+        return Ok(false);
+    }
+    if let Some(publish_lockfile) = pkg.publish_lockfile() {
+        return Ok(publish_lockfile);
+    }
+    let config = opts.gctx.get::<CargoPackageConfig>("package")?;
+    match config.publish_lockfile {
+        Some(_) if !opts.gctx.cli_unstable().unstable_options => {
+            opts.gctx
+                .shell()
+                .warn("`package.publish-lockfile` requires `-Zunstable-options`, ignoring it")?;
+        }
+        Some(publish_lockfile) => return Ok(publish_lockfile),
+        None => {}
+    }
+    Ok(pkg.include_lockfile())
+}
+
 /// Builds list of files to archive.
 fn build_ar_list(
     ws: &Workspace<'_>,
     pkg: &Package,
     src_files: Vec<PathBuf>,
     vcs_info: Option<VcsInfo>,
+    include_lockfile: bool,
 ) -> CargoResult<Vec<ArchiveFile>> {
     let mut result = HashMap::new();
     let root = pkg.root();
@@ -287,7 +332,7 @@ fn build_ar_list(
         ))?;
     }
 
-    if pkg.include_lockfile() {
+    if include_lockfile {
         let rel_str = "Cargo.lock";
         result
             .entry(UncasedAscii::new(rel_str))
@@ -358,6 +403,45 @@ fn build_ar_list(
     Ok(result)
 }
 
+/// Runs the `cargo::package_contents` lint on the files to archive.
+fn lint_package_contents(
+    ws: &Workspace<'_>,
+    pkg: &Package,
+    ar_files: &[ArchiveFile],
+) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    if !gctx.cli_unstable().cargo_lints {
+        return Ok(());
+    }
+    let pkg_lints = pkg
+        .manifest()
+        .resolved_toml()
+        .lints
+        .as_ref()
+        .and_then(|lints| lints.lints.get("cargo"))
+        .cloned()
+        .unwrap_or_default();
+    let files: Vec<_> = ar_files
+        .iter()
+        .filter_map(|ar_file| match &ar_file.contents {
+            FileContents::OnDisk(disk_path) => {
+                Some((ar_file.rel_path.as_path(), disk_path.as_path()))
+            }
+            FileContents::Generated(_) => None,
+        })
+        .collect();
+    let mut error_count = 0;
+    check_package_contents(pkg, &pkg_lints, &files, &mut error_count, gctx)?;
+    if error_count > 0 {
+        Err(AlreadyPrintedError::new(anyhow::anyhow!(
+            "encountered {error_count} errors(s) while running lints"
+        ))
+        .into())
+    } else {
+        Ok(())
+    }
+}
+
 fn check_for_file_and_add(
     label: &str,
     file_path: &Path,
@@ -908,19 +992,9 @@ fn run_verify(
     let pkg_fingerprint = hash_all(&dst)?;
     let ws = Workspace::ephemeral(new_pkg, gctx, None, true)?;
 
-    let rustc_args = if pkg
-        .manifest()
-        .unstable_features()
-        .require(Feature::public_dependency())
-        .is_ok()
-        || ws.gctx().cli_unstable().public_dependency
-    {
-        // FIXME: Turn this on at some point in the future
-        //Some(vec!["-D exported_private_dependencies".to_string()])
-        Some(vec![])
-    } else {
-        None
-    };
+    // FIXME: Turn this on at some point in the future
+    //let rustc_args = Some(vec!["-D exported_private_dependencies".to_string()]);
+    let rustc_args = None;
 
     let exec: Arc<dyn Executor> = Arc::new(DefaultExecutor);
     ops::compile_with_exec(
@@ -943,6 +1017,7 @@ fn run_verify(
             target_rustc_crate_types: None,
             rustdoc_document_private_items: false,
             honor_rust_version: None,
+            fuzz: ops::FilterRule::none(),
         },
         &exec,
     )?;
@@ -0,0 +1,502 @@
+//! Implementation of `cargo query` subcommand.
+//!
+//! `cargo query` evaluates a small set-based expression language against the
+//! resolved dependency graph of a workspace, for example:
+//!
+//! ```text
+//! deps(foo) & kind(normal) - workspace()
+//! ```
+//!
+//! All binary operators have the same precedence and are left-associative,
+//! parentheses can be used for grouping:
+//!
+//! * `a & b` --- packages in both `a` and `b`.
+//! * `a | b` --- packages in either `a` or `b`.
+//! * `a - b` --- packages in `a` but not in `b`. The operator must be
+//!   surrounded by whitespace since `-` is valid within package names.
+//!
+//! The following functions are available:
+//!
+//! * `all()` --- every package in the resolved graph.
+//! * `workspace()` --- the workspace members.
+//! * `deps(expr)` --- `expr` and everything it transitively depends on.
+//! * `rdeps(expr)` --- `expr` and everything that transitively depends on it.
+//! * `kind(normal|dev|build)` --- packages depended on with the given kind.
+//! * `source(registry|git|path)` --- packages coming from the given kind of
+//!   source.
+//!
+//! Any other word is interpreted as a [`PackageIdSpec`] and matches every
+//! package in the graph it applies to.
+
+use crate::core::dependency::DepKind;
+use crate::core::{PackageId, PackageIdSpec, PackageIdSpecQuery, Resolve, Workspace};
+use crate::drop_println;
+use crate::ops;
+use crate::util::errors::CargoResult;
+use anyhow::{bail, format_err, Context as _, Error};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+pub enum QueryFormat {
+    List,
+    Tree,
+    Json,
+}
+
+impl QueryFormat {
+    /// For clap.
+    pub const POSSIBLE_VALUES: [&'static str; 3] = ["list", "tree", "json"];
+}
+
+impl FromStr for QueryFormat {
+    type Err = Error;
+    fn from_str(s: &str) -> CargoResult<Self> {
+        match s {
+            "list" => Ok(QueryFormat::List),
+            "tree" => Ok(QueryFormat::Tree),
+            "json" => Ok(QueryFormat::Json),
+            f => bail!("unknown query format `{}`", f),
+        }
+    }
+}
+
+impl fmt::Display for QueryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            QueryFormat::List => write!(f, "list"),
+            QueryFormat::Tree => write!(f, "tree"),
+            QueryFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Options for `cargo query`.
+pub struct QueryOptions<'a> {
+    pub expr: &'a str,
+    pub format: QueryFormat,
+}
+
+pub fn query(ws: &Workspace<'_>, opts: &QueryOptions<'_>) -> CargoResult<()> {
+    let expr = parse(opts.expr).with_context(|| format!("invalid query `{}`", opts.expr))?;
+    let (_packages, resolve) = ops::resolve_ws(ws)?;
+    let graph = QueryGraph::new(ws, &resolve);
+    let result = graph.eval(&expr)?;
+
+    let gctx = ws.gctx();
+    match opts.format {
+        QueryFormat::List => {
+            for id in &result {
+                drop_println!(gctx, "{}", id);
+            }
+        }
+        QueryFormat::Tree => {
+            let mut visited = BTreeSet::new();
+            for root in graph.roots(&result) {
+                graph.print_tree(ws, root, &result, &mut visited, 0);
+            }
+            // The packages of a cycle, like one through a dev-dependency,
+            // have no root, so start from the first one not yet printed.
+            for &id in &result {
+                if !visited.contains(&id) {
+                    graph.print_tree(ws, id, &result, &mut visited, 0);
+                }
+            }
+        }
+        QueryFormat::Json => {
+            let packages: Vec<_> = result
+                .iter()
+                .map(|&id| SerializedPackage {
+                    id: id.to_spec().to_string(),
+                    name: id.name().to_string(),
+                    version: id.version().to_string(),
+                    source: (!id.source_id().is_path())
+                        .then(|| id.source_id().as_url().to_string()),
+                    dependencies: graph
+                        .deps_of(id)
+                        .filter(|dep| result.contains(dep))
+                        .map(|dep| dep.to_spec().to_string())
+                        .collect(),
+                })
+                .collect();
+            gctx.shell().print_json(&packages)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct SerializedPackage {
+    id: String,
+    name: String,
+    version: String,
+    source: Option<String>,
+    /// Dependencies that are also part of the query result.
+    dependencies: Vec<String>,
+}
+
+/// A parsed query expression.
+#[derive(Debug, PartialEq)]
+enum Expr {
+    All,
+    Workspace,
+    Spec(PackageIdSpec),
+    Deps(Box<Expr>),
+    Rdeps(Box<Expr>),
+    Kind(DepKind),
+    Source(SourceFilter),
+    Intersect(Box<Expr>, Box<Expr>),
+    Union(Box<Expr>, Box<Expr>),
+    Difference(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum SourceFilter {
+    Registry,
+    Git,
+    Path,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token<'a> {
+    Word(&'a str),
+    LParen,
+    RParen,
+    And,
+    Or,
+    Minus,
+}
+
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start();
+        let Some(c) = rest.chars().next() else {
+            break;
+        };
+        let (token, len) = match c {
+            '(' => (Token::LParen, 1),
+            ')' => (Token::RParen, 1),
+            '&' => (Token::And, 1),
+            '|' => (Token::Or, 1),
+            _ => {
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '&' | '|'))
+                    .unwrap_or(rest.len());
+                let word = &rest[..len];
+                if word == "-" {
+                    (Token::Minus, len)
+                } else {
+                    (Token::Word(word), len)
+                }
+            }
+        };
+        tokens.push(token);
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+fn parse(input: &str) -> CargoResult<Expr> {
+    let tokens = tokenize(input);
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expr()?;
+    if let Some(token) = parser.peek() {
+        bail!("unexpected {} after end of expression", describe(token));
+    }
+    Ok(expr)
+}
+
+fn describe(token: &Token<'_>) -> String {
+    match token {
+        Token::Word(w) => format!("`{w}`"),
+        Token::LParen => "`(`".to_string(),
+        Token::RParen => "`)`".to_string(),
+        Token::And => "`&`".to_string(),
+        Token::Or => "`|`".to_string(),
+        Token::Minus => "`-`".to_string(),
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token<'a>) -> CargoResult<()> {
+        match self.advance() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => bail!(
+                "expected {}, found {}",
+                describe(&expected),
+                describe(&token)
+            ),
+            None => bail!("expected {}, found end of expression", describe(&expected)),
+        }
+    }
+
+    fn expr(&mut self) -> CargoResult<Expr> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::And) => Expr::Intersect,
+                Some(Token::Or) => Expr::Union,
+                Some(Token::Minus) => Expr::Difference,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            let rhs = self.term()?;
+            lhs = op(Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn term(&mut self) -> CargoResult<Expr> {
+        let word = match self.advance() {
+            Some(Token::LParen) => {
+                let expr = self.expr()?;
+                self.expect(Token::RParen)?;
+                return Ok(expr);
+            }
+            Some(Token::Word(word)) => word,
+            Some(token) => bail!("expected an expression, found {}", describe(&token)),
+            None => bail!("expected an expression, found end of expression"),
+        };
+        if self.peek() != Some(&Token::LParen) {
+            let spec = PackageIdSpec::parse(word)
+                .with_context(|| format!("invalid package ID specification: `{word}`"))?;
+            return Ok(Expr::Spec(spec));
+        }
+        self.pos += 1;
+        let expr = match word {
+            "all" => Expr::All,
+            "workspace" => Expr::Workspace,
+            "deps" => Expr::Deps(Box::new(self.expr()?)),
+            "rdeps" => Expr::Rdeps(Box::new(self.expr()?)),
+            "kind" => match self.advance() {
+                Some(Token::Word("normal")) => Expr::Kind(DepKind::Normal),
+                Some(Token::Word("dev")) => Expr::Kind(DepKind::Development),
+                Some(Token::Word("build")) => Expr::Kind(DepKind::Build),
+                _ => bail!("`kind` expects one of `normal`, `dev` or `build`"),
+            },
+            "source" => match self.advance() {
+                Some(Token::Word("registry")) => Expr::Source(SourceFilter::Registry),
+                Some(Token::Word("git")) => Expr::Source(SourceFilter::Git),
+                Some(Token::Word("path")) => Expr::Source(SourceFilter::Path),
+                _ => bail!("`source` expects one of `registry`, `git` or `path`"),
+            },
+            f => bail!(
+                "unknown function `{f}`, \
+                 expected one of `all`, `workspace`, `deps`, `rdeps`, `kind` or `source`"
+            ),
+        };
+        self.expect(Token::RParen)?;
+        Ok(expr)
+    }
+}
+
+/// The resolved dependency graph a query is evaluated against.
+struct QueryGraph {
+    members: BTreeSet<PackageId>,
+    all: BTreeSet<PackageId>,
+    deps: HashMap<PackageId, Vec<(PackageId, Vec<DepKind>)>>,
+    rdeps: HashMap<PackageId, Vec<PackageId>>,
+}
+
+type PackageIds = BTreeSet<PackageId>;
+
+impl QueryGraph {
+    fn new(ws: &Workspace<'_>, resolve: &Resolve) -> QueryGraph {
+        let mut deps = HashMap::new();
+        let mut rdeps: HashMap<PackageId, Vec<PackageId>> = HashMap::new();
+        for id in resolve.iter() {
+            let edges: Vec<_> = resolve
+                .deps(id)
+                .map(|(dep_id, deps)| {
+                    rdeps.entry(dep_id).or_default().push(id);
+                    let mut kinds: Vec<_> = deps.iter().map(|dep| dep.kind()).collect();
+                    kinds.sort();
+                    kinds.dedup();
+                    (dep_id, kinds)
+                })
+                .collect();
+            deps.insert(id, edges);
+        }
+        QueryGraph {
+            members: ws.members().map(|pkg| pkg.package_id()).collect(),
+            all: resolve.iter().collect(),
+            deps,
+            rdeps,
+        }
+    }
+
+    fn deps_of(&self, id: PackageId) -> impl Iterator<Item = PackageId> + '_ {
+        self.deps
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(|(dep_id, _)| *dep_id)
+    }
+
+    fn rdeps_of(&self, id: PackageId) -> impl Iterator<Item = PackageId> + '_ {
+        self.rdeps.get(&id).into_iter().flatten().copied()
+    }
+
+    fn eval(&self, expr: &Expr) -> CargoResult<PackageIds> {
+        let ids = match expr {
+            Expr::All => self.all.clone(),
+            Expr::Workspace => self.members.clone(),
+            Expr::Spec(spec) => {
+                let ids: PackageIds = self
+                    .all
+                    .iter()
+                    .copied()
+                    .filter(|&id| spec.matches(id))
+                    .collect();
+                if ids.is_empty() {
+                    return Err(format_err!(
+                        "package ID specification `{spec}` did not match any packages"
+                    ));
+                }
+                ids
+            }
+            Expr::Deps(inner) => self.closure(self.eval(inner)?, |id| self.deps_of(id)),
+            Expr::Rdeps(inner) => self.closure(self.eval(inner)?, |id| self.rdeps_of(id)),
+            Expr::Kind(kind) => self
+                .deps
+                .values()
+                .flatten()
+                .filter(|(_, kinds)| kinds.contains(kind))
+                .map(|(dep_id, _)| *dep_id)
+                .collect(),
+            Expr::Source(filter) => self
+                .all
+                .iter()
+                .copied()
+                .filter(|id| {
+                    let source_id = id.source_id();
+                    match filter {
+                        SourceFilter::Registry => source_id.is_registry(),
+                        SourceFilter::Git => source_id.is_git(),
+                        SourceFilter::Path => source_id.is_path(),
+                    }
+                })
+                .collect(),
+            Expr::Intersect(lhs, rhs) => {
+                let rhs = self.eval(rhs)?;
+                self.eval(lhs)?.intersection(&rhs).copied().collect()
+            }
+            Expr::Union(lhs, rhs) => {
+                let mut lhs = self.eval(lhs)?;
+                lhs.extend(self.eval(rhs)?);
+                lhs
+            }
+            Expr::Difference(lhs, rhs) => {
+                let rhs = self.eval(rhs)?;
+                self.eval(lhs)?.difference(&rhs).copied().collect()
+            }
+        };
+        Ok(ids)
+    }
+
+    /// Computes the transitive closure of `start` over the given edges.
+    fn closure<'a, I>(&'a self, start: PackageIds, edges: impl Fn(PackageId) -> I) -> PackageIds
+    where
+        I: Iterator<Item = PackageId> + 'a,
+    {
+        let mut stack: Vec<_> = start.iter().copied().collect();
+        let mut seen = start;
+        while let Some(id) = stack.pop() {
+            for next in edges(id) {
+                if seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Packages in `set` that are not depended on by any other package in
+    /// `set`.
+    fn roots<'a>(&'a self, set: &'a PackageIds) -> impl Iterator<Item = PackageId> + 'a {
+        set.iter()
+            .copied()
+            .filter(move |&id| !self.rdeps_of(id).any(|parent| set.contains(&parent)))
+    }
+
+    fn print_tree(
+        &self,
+        ws: &Workspace<'_>,
+        id: PackageId,
+        set: &PackageIds,
+        visited: &mut PackageIds,
+        depth: usize,
+    ) {
+        let indent = "    ".repeat(depth);
+        if !visited.insert(id) {
+            drop_println!(ws.gctx(), "{indent}{id} (*)");
+            return;
+        }
+        drop_println!(ws.gctx(), "{indent}{id}");
+        let mut deps: Vec<_> = self.deps_of(id).filter(|dep| set.contains(dep)).collect();
+        deps.sort();
+        for dep in deps {
+            self.print_tree(ws, dep, set, visited, depth + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(s: &str) -> Box<Expr> {
+        Box::new(Expr::Spec(PackageIdSpec::parse(s).unwrap()))
+    }
+
+    #[test]
+    fn parse_expressions() {
+        assert_eq!(
+            parse("deps(foo) & kind(normal) - workspace()").unwrap(),
+            Expr::Difference(
+                Box::new(Expr::Intersect(
+                    Box::new(Expr::Deps(spec("foo"))),
+                    Box::new(Expr::Kind(DepKind::Normal)),
+                )),
+                Box::new(Expr::Workspace),
+            )
+        );
+        assert_eq!(
+            parse("serde-json | (rdeps(bar@1.0) & source(git))").unwrap(),
+            Expr::Union(
+                spec("serde-json"),
+                Box::new(Expr::Intersect(
+                    Box::new(Expr::Rdeps(spec("bar@1.0"))),
+                    Box::new(Expr::Source(SourceFilter::Git)),
+                )),
+            )
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("deps(foo").is_err());
+        assert!(parse("foo bar").is_err());
+        assert!(parse("kind(weird)").is_err());
+        assert!(parse("nope()").is_err());
+        assert!(parse("foo &").is_err());
+    }
+}
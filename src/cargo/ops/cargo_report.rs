@@ -0,0 +1,239 @@
+//! Implementation of the `cargo report` subcommands.
+//!
+//! Cargo keeps a few kinds of reports about previous builds in the target
+//! directory, each in its own location:
+//!
+//! * future-incompat reports, in `.future-incompat-report.json`, see
+//!   [`OnDiskReports`],
+//! * build summaries recorded with `-Zbuild-reports`, in `reports`, see
+//!   [`OnDiskBuilds`],
+//! * HTML timing reports written with `--timings`, in `cargo-timings`.
+//!
+//! This module displays them, optionally as JSON, and prunes old ones.
+
+use crate::core::compiler::build_reports::{BuildSummary, OnDiskBuilds};
+use crate::core::compiler::future_incompat::{OnDiskReports, REPORT_PREAMBLE};
+use crate::core::Workspace;
+use crate::drop_println;
+use crate::util::CargoResult;
+use anyhow::Context as _;
+use cargo_util::paths;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// The directory in the top-level `target` directory where `--timings`
+/// writes its HTML reports.
+const TIMINGS_DIR: &str = "cargo-timings";
+/// The copy of the most recent timing report.
+const UNSTAMPED_TIMING_FILE: &str = "cargo-timing.html";
+
+pub struct ReportOptions<'a> {
+    /// The report to display, or the most recent one.
+    pub id: Option<u32>,
+    /// Restricts a future-incompat report to a single package.
+    pub package: Option<&'a str>,
+    /// Prints the report as JSON instead of text.
+    pub json: bool,
+}
+
+/// Displays a future-incompat report.
+pub fn report_future_incompat(ws: &Workspace<'_>, opts: &ReportOptions<'_>) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let reports = OnDiskReports::load(ws)?;
+    let id = match opts.id {
+        Some(id) => id,
+        None => reports.last_id()?,
+    };
+    if opts.json {
+        let report = reports.get_report_json(id, opts.package)?;
+        gctx.shell().print_json(&report)?;
+    } else {
+        let report = reports.get_report(id, opts.package)?;
+        drop_println!(gctx, "{}", REPORT_PREAMBLE);
+        drop(gctx.shell().print_ansi_stdout(report.as_bytes()));
+    }
+    Ok(())
+}
+
+/// Displays the recorded build summaries, or a single one if an ID is given.
+pub fn report_builds(ws: &Workspace<'_>, opts: &ReportOptions<'_>) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let builds = OnDiskBuilds::load(ws)?;
+    if let Some(id) = opts.id {
+        let build = builds.get_build(id)?;
+        if opts.json {
+            gctx.shell().print_json(build)?;
+        } else {
+            drop_println!(gctx, "id: {}", build.id);
+            drop_println!(gctx, "finished: {}", build.finished);
+            drop_println!(gctx, "profile: {}", build.profile);
+            drop_println!(gctx, "result: {}", result(build.success));
+            drop_println!(gctx, "duration: {:.2}s", build.duration);
+            drop_println!(gctx, "units: {} fresh, {} dirty", build.fresh, build.dirty);
+            drop_println!(gctx, "warnings: {}", build.warnings);
+            drop_println!(gctx, "errors: {}", build.errors);
+        }
+        return Ok(());
+    }
+
+    if opts.json {
+        #[derive(Serialize)]
+        struct Builds<'a> {
+            builds: &'a [BuildSummary],
+        }
+        return gctx.shell().print_json(&Builds {
+            builds: builds.builds(),
+        });
+    }
+    if builds.builds().is_empty() {
+        gctx.shell()
+            .note("no builds have been recorded, pass `-Zbuild-reports` to record them")?;
+        return Ok(());
+    }
+    drop_println!(
+        gctx,
+        "{:>4}  {:<20}  {:<10}  {:<7}  {:>9}  {:>5}  {:>5}  {:>8}",
+        "ID",
+        "FINISHED",
+        "PROFILE",
+        "RESULT",
+        "DURATION",
+        "FRESH",
+        "DIRTY",
+        "WARNINGS"
+    );
+    for build in builds.builds() {
+        drop_println!(
+            gctx,
+            "{:>4}  {:<20}  {:<10}  {:<7}  {:>9}  {:>5}  {:>5}  {:>8}",
+            build.id,
+            build.finished,
+            build.profile,
+            result(build.success),
+            format!("{:.2}s", build.duration),
+            build.fresh,
+            build.dirty,
+            build.warnings
+        );
+    }
+    Ok(())
+}
+
+fn result(success: bool) -> &'static str {
+    if success {
+        "success"
+    } else {
+        "failure"
+    }
+}
+
+/// A timing report written by `--timings`.
+#[derive(Serialize)]
+struct TimingReport {
+    /// When the build started, in RFC 3339 format.
+    started: String,
+    path: PathBuf,
+}
+
+/// Finds the timestamped timing reports, oldest first.
+fn timing_reports(ws: &Workspace<'_>) -> CargoResult<Vec<TimingReport>> {
+    let dir = ws.target_dir().join(TIMINGS_DIR).into_path_unlocked();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut reports = Vec::new();
+    let entries =
+        std::fs::read_dir(&dir).with_context(|| format!("failed to read `{}`", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        let Some(stamp) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("cargo-timing-"))
+            .and_then(|name| name.strip_suffix(".html"))
+        else {
+            continue;
+        };
+        // `--timings` strips the separators from the RFC 3339 timestamp to
+        // build the file name, e.g. `20240101T120000Z`.
+        // Only ASCII stamps can be sliced by bytes.
+        let started = if stamp.len() == 16 && stamp.is_ascii() {
+            format!(
+                "{}-{}-{}T{}:{}:{}Z",
+                &stamp[..4],
+                &stamp[4..6],
+                &stamp[6..8],
+                &stamp[9..11],
+                &stamp[11..13],
+                &stamp[13..15]
+            )
+        } else {
+            stamp.to_string()
+        };
+        reports.push(TimingReport { started, path });
+    }
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(reports)
+}
+
+/// Lists the timing reports written by `--timings`.
+pub fn report_timings(ws: &Workspace<'_>, json: bool) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let reports = timing_reports(ws)?;
+    if json {
+        #[derive(Serialize)]
+        struct Timings {
+            timings: Vec<TimingReport>,
+        }
+        return gctx.shell().print_json(&Timings { timings: reports });
+    }
+    if reports.is_empty() {
+        gctx.shell()
+            .note("no timing reports found, pass `--timings` to a build to write one")?;
+        return Ok(());
+    }
+    for report in reports {
+        drop_println!(gctx, "{}  {}", report.started, report.path.display());
+    }
+    Ok(())
+}
+
+/// Removes all but the `keep` most recent reports of every kind.
+pub fn prune_reports(ws: &Workspace<'_>, keep: usize) -> CargoResult<()> {
+    let builds = OnDiskBuilds::prune(ws, keep)?;
+    let future_incompat = OnDiskReports::prune(ws, keep)?;
+
+    let timings = timing_reports(ws)?;
+    let stale = timings.len().saturating_sub(keep);
+    for report in &timings[..stale] {
+        paths::remove_file(&report.path)?;
+    }
+    if keep == 0 {
+        let unstamped = ws
+            .target_dir()
+            .join(TIMINGS_DIR)
+            .into_path_unlocked()
+            .join(UNSTAMPED_TIMING_FILE);
+        if unstamped.exists() {
+            paths::remove_file(&unstamped)?;
+        }
+    }
+
+    ws.gctx().shell().status(
+        "Removed",
+        format!(
+            "{builds} build {}, {future_incompat} future-incompat {}, {stale} timing {}",
+            plural(builds, "summary", "summaries"),
+            plural(future_incompat, "report", "reports"),
+            plural(stale, "report", "reports"),
+        ),
+    )
+}
+
+fn plural<'a>(count: usize, one: &'a str, many: &'a str) -> &'a str {
+    if count == 1 {
+        one
+    } else {
+        many
+    }
+}
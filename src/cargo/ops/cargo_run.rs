@@ -3,13 +3,14 @@ use std::iter;
 use std::path::Path;
 
 use crate::core::compiler::UnitOutput;
-use crate::core::{TargetKind, Workspace};
+use crate::core::{Package, TargetKind, Workspace};
 use crate::ops;
 use crate::util::CargoResult;
 
 pub fn run(
     ws: &Workspace<'_>,
     options: &ops::CompileOptions,
+    preset: Option<&str>,
     args: &[OsString],
 ) -> CargoResult<()> {
     let gctx = ws.gctx();
@@ -79,6 +80,13 @@ pub fn run(
     // `cargo run` is only compatible with one `--target` flag at most
     options.build_config.single_requested_kind()?;
 
+    // Look the preset up before compiling so a typo fails fast. If no binary
+    // was found, `ops::compile` reports the error instead.
+    let preset_args = match (preset, bins.first()) {
+        (Some(preset), Some((pkg, _))) => preset_args(pkg, preset)?,
+        _ => Vec::new(),
+    };
+
     let compile = ops::compile(ws, options)?;
     assert_eq!(compile.binaries.len(), 1);
     let UnitOutput {
@@ -98,7 +106,7 @@ pub fn run(
     // directory of the parent process.
     // Overrides the default working directory of the `ProcessBuilder` returned
     // by `compile.target_process` (the package's root directory)
-    process.args(args).cwd(gctx.cwd());
+    process.args(&preset_args).args(args).cwd(gctx.cwd());
 
     if gctx.extra_verbose() {
         process.display_env_vars();
@@ -108,3 +116,33 @@ pub fn run(
 
     process.exec_replace()
 }
+
+/// Returns the arguments of the `[run.args]` preset named `name` in `pkg`.
+fn preset_args(pkg: &Package, name: &str) -> CargoResult<Vec<OsString>> {
+    let presets = pkg
+        .manifest()
+        .resolved_toml()
+        .run
+        .as_ref()
+        .and_then(|run| run.args.as_ref());
+    if let Some(args) = presets.and_then(|presets| presets.get(name)) {
+        return Ok(args.iter().map(OsString::from).collect());
+    }
+    let available = presets
+        .map(|presets| presets.keys().map(String::as_str).collect::<Vec<_>>())
+        .unwrap_or_default();
+    if available.is_empty() {
+        anyhow::bail!(
+            "no argument preset named `{name}` in package `{}`\n\
+             note: the package does not define any presets in `[run.args]`",
+            pkg.name()
+        )
+    } else {
+        anyhow::bail!(
+            "no argument preset named `{name}` in package `{}`\n\
+             available presets: {}",
+            pkg.name(),
+            available.join(", ")
+        )
+    }
+}
@@ -0,0 +1,531 @@
+//! Implementation of `cargo server`, and the dispatching of `cargo build` and
+//! `cargo check` to it with `-Zbuild-server`.
+//!
+//! Most of the time of an incremental build in a large workspace can go into
+//! starting Cargo: loading the configuration, parsing the manifests and
+//! checking what changed. A build server is a long-running Cargo process which
+//! keeps the configuration, the rustc info and the workspaces in memory, and
+//! runs builds on behalf of the `cargo` processes started by the user.
+//!
+//! The server listens on a TCP socket bound to localhost. When it starts, it
+//! writes its address, along with a random token, to a file in
+//! `$CARGO_HOME/build-servers` for the manifest of the workspace root and of
+//! each member, named after a hash of the manifest path. A client finds the
+//! server of its manifest with that file, sends a single [`Request`] with the
+//! token, and copies the [`Reply`] messages with the output of the build to
+//! its stderr until the build finishes.
+//!
+//! The server runs one build at a time, and gives up on a client which
+//! doesn't send its request within a few seconds. As the configuration is only
+//! loaded when the server starts, and as the builds run with the environment
+//! of the server, it only serves clients which run in the same directory, with
+//! the same environment variables and the same global options. Otherwise, it
+//! refuses the request, and the client runs the build itself. The workspaces
+//! are loaded again when one of their manifests, the manifest of one of their
+//! path dependencies or `Cargo.lock` changes, or when a directory matched by a
+//! glob of `workspace.members` changes. When one of the configuration files
+//! changes, the server refuses the request and stops, as it would need to be
+//! started again to load it.
+//!
+//! Only the workspaces are kept between builds: the dependencies are resolved
+//! again, from the index cached on disk, and the unit graph is built again for
+//! every build.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context as _;
+use cargo_util::{paths, Sha256};
+use filetime::FileTime;
+use serde::{Deserialize, Serialize};
+
+use crate::core::shell::Shell;
+use crate::core::Workspace;
+use crate::ops::{self, CompileOptions};
+use crate::util::errors::CargoResult;
+use crate::util::hex::short_hash;
+use crate::GlobalContext;
+
+/// How long the server waits for the request of a client it accepted.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum length of a request, which is a single line of JSON.
+const MAX_REQUEST_LEN: u64 = 1024 * 1024;
+
+/// What the server does with a request, decided by the caller of [`serve`]
+/// from the command-line arguments of the client.
+pub enum Dispatch {
+    /// Runs the build with these options.
+    Compile(CompileOptions),
+    /// Lets the client run the build itself, for the given reason.
+    Refuse(String),
+}
+
+/// Where to find a running server, written for each of its manifests.
+#[derive(Serialize, Deserialize)]
+struct ServerInfo {
+    addr: SocketAddr,
+    token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Request {
+    token: String,
+    /// Stops the server instead of running a build.
+    #[serde(default)]
+    stop: bool,
+    /// The arguments of the client, without the name of the executable.
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    manifest_path: PathBuf,
+    #[serde(default)]
+    cwd: PathBuf,
+    /// The hash of the environment of the client, see [`env_hash`].
+    #[serde(default)]
+    env_hash: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "reason")]
+enum Reply {
+    /// Output of the build, to be printed to stderr.
+    Output { text: String },
+    /// The client has to run the build itself.
+    Refused { message: String },
+    /// The build finished with this exit code.
+    Finished { code: i32 },
+}
+
+/// A workspace kept in memory between builds.
+struct CachedWorkspace<'gctx> {
+    ws: Workspace<'gctx>,
+    /// The files the workspace was loaded from, to know when to load it
+    /// again.
+    files: WatchedFiles,
+}
+
+impl<'gctx> CachedWorkspace<'gctx> {
+    fn load(gctx: &'gctx GlobalContext, manifest_path: &Path) -> CargoResult<Self> {
+        let ws = Workspace::new(manifest_path, gctx)?;
+        let files = WatchedFiles::new(workspace_files(&ws)?);
+        Ok(CachedWorkspace { ws, files })
+    }
+}
+
+/// Files with their modification time, or `None` if they don't exist, to know
+/// when one of them is modified, created or removed.
+struct WatchedFiles(Vec<(PathBuf, Option<FileTime>)>);
+
+impl WatchedFiles {
+    fn new(files: Vec<PathBuf>) -> WatchedFiles {
+        WatchedFiles(
+            files
+                .into_iter()
+                .map(|path| {
+                    let mtime = paths::mtime(&path).ok();
+                    (path, mtime)
+                })
+                .collect(),
+        )
+    }
+
+    fn changed(&self) -> bool {
+        self.0
+            .iter()
+            .any(|(path, mtime)| paths::mtime(path).ok() != *mtime)
+    }
+}
+
+/// Removes the server info files when the server stops.
+struct InfoFiles(Vec<PathBuf>);
+
+impl Drop for InfoFiles {
+    fn drop(&mut self) {
+        for path in &self.0 {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Serves builds for the workspace of `root_manifest` until a client sends a
+/// stop request.
+///
+/// `dispatch` turns the command-line arguments of a client into the options
+/// of the build, or refuses them.
+pub fn serve(
+    gctx: &GlobalContext,
+    root_manifest: &Path,
+    dispatch: &mut dyn FnMut(&mut Workspace<'_>, &[String]) -> CargoResult<Dispatch>,
+) -> CargoResult<()> {
+    let mut workspaces = HashMap::new();
+    let root = CachedWorkspace::load(gctx, root_manifest)?;
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .context("failed to bind the build server to a local port")?;
+    let info = ServerInfo {
+        addr: listener.local_addr()?,
+        token: format!("{:032x}", rand::random::<u128>()),
+    };
+    let mut manifests: Vec<_> = root
+        .ws
+        .members()
+        .map(|pkg| pkg.manifest_path().to_path_buf())
+        .collect();
+    manifests.push(root.ws.root_manifest().to_path_buf());
+    manifests.sort();
+    manifests.dedup();
+    let _info_files = write_info(gctx, &manifests, &info)?;
+    gctx.shell().status(
+        "Serving",
+        format!("builds of `{}` on {}", root.ws.root().display(), info.addr),
+    )?;
+    workspaces.insert(root_manifest.to_path_buf(), root);
+    let env_hash = env_hash();
+    let config_files = WatchedFiles::new(config_files(gctx));
+
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::debug!("failed to accept a build server client: {e}");
+                continue;
+            }
+        };
+        let request = match read_request(&stream) {
+            Ok(request) if request.token == info.token => request,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::debug!("invalid build server request: {e:?}");
+                continue;
+            }
+        };
+        if request.stop {
+            let _ = send(&mut stream, &Reply::Finished { code: 0 });
+            break;
+        }
+        if config_files.changed() {
+            let message = "the configuration changed since the server started".to_string();
+            let _ = send(&mut stream, &Reply::Refused { message });
+            gctx.shell().status(
+                "Stopping",
+                "the build server, the configuration changed since it started",
+            )?;
+            break;
+        }
+        let refusal = if request.cwd != gctx.cwd() {
+            Some(format!("the server runs in `{}`", gctx.cwd().display()))
+        } else if request.env_hash != env_hash {
+            Some("the environment differs from the server's".to_string())
+        } else if !manifests.contains(&request.manifest_path) {
+            Some(format!(
+                "`{}` isn't part of the server's workspace",
+                request.manifest_path.display()
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = refusal {
+            let _ = send(&mut stream, &Reply::Refused { message });
+            continue;
+        }
+        let result = build(gctx, &mut workspaces, &request, &mut stream, dispatch);
+        let reply = match result {
+            Ok(reply) => reply,
+            Err(e) => {
+                // The output goes to the client, but it may be gone.
+                tracing::debug!("build server client failed: {e:?}");
+                continue;
+            }
+        };
+        let _ = send(&mut stream, &reply);
+    }
+    Ok(())
+}
+
+/// Runs the build of a request, copying its output to the client.
+fn build<'gctx>(
+    gctx: &'gctx GlobalContext,
+    workspaces: &mut HashMap<PathBuf, CachedWorkspace<'gctx>>,
+    request: &Request,
+    stream: &mut TcpStream,
+    dispatch: &mut dyn FnMut(&mut Workspace<'_>, &[String]) -> CargoResult<Dispatch>,
+) -> CargoResult<Reply> {
+    let stale = match workspaces.get(&request.manifest_path) {
+        Some(cached) => cached.files.changed(),
+        None => true,
+    };
+    if stale {
+        workspaces.remove(&request.manifest_path);
+        match CachedWorkspace::load(gctx, &request.manifest_path) {
+            Ok(cached) => {
+                workspaces.insert(request.manifest_path.clone(), cached);
+            }
+            Err(e) => {
+                // Let the client report the error, as it would without a server.
+                return Ok(Reply::Refused {
+                    message: format!("failed to load the workspace: {e}"),
+                });
+            }
+        }
+    }
+    let ws = &mut workspaces.get_mut(&request.manifest_path).unwrap().ws;
+
+    let mut shell = Shell::from_write(Box::new(OutputWriter(stream.try_clone()?)));
+    shell.set_verbosity(gctx.shell().verbosity());
+    let server_shell = std::mem::replace(&mut *gctx.shell(), shell);
+    let result = dispatch(ws, &request.args).and_then(|action| match action {
+        Dispatch::Compile(opts) => ops::compile(ws, &opts).map(|_| None),
+        Dispatch::Refuse(message) => Ok(Some(message)),
+    });
+    let code = match result {
+        Ok(Some(message)) => {
+            *gctx.shell() = server_shell;
+            return Ok(Reply::Refused { message });
+        }
+        Ok(None) => 0,
+        Err(e) => {
+            crate::display_error(&e, &mut gctx.shell());
+            101
+        }
+    };
+    *gctx.shell() = server_shell;
+    gctx.shell().status(
+        "Served",
+        format!("`cargo {}` (exit code {code})", request.args.join(" ")),
+    )?;
+    Ok(Reply::Finished { code })
+}
+
+/// Sends a build to the server of `manifest_path`, if one is running.
+///
+/// Returns the exit code of the build, or `None` if there is no server or it
+/// refused the build, in which case the caller runs the build itself.
+pub fn dispatch(
+    gctx: &GlobalContext,
+    manifest_path: &Path,
+    args: Vec<String>,
+) -> CargoResult<Option<i32>> {
+    let path = info_path(gctx, manifest_path);
+    let Some(info) = read_info(&path) else {
+        return Ok(None);
+    };
+    let mut stream = match TcpStream::connect(info.addr) {
+        Ok(stream) => stream,
+        Err(e) => {
+            // The server was killed without removing its files.
+            tracing::debug!("failed to connect to the build server: {e}");
+            let _ = fs::remove_file(&path);
+            return Ok(None);
+        }
+    };
+    let request = Request {
+        token: info.token,
+        stop: false,
+        args,
+        manifest_path: manifest_path.to_path_buf(),
+        cwd: gctx.cwd().to_path_buf(),
+        env_hash: env_hash(),
+    };
+    send(&mut stream, &request)?;
+    for line in BufReader::new(stream).lines() {
+        let reply: Reply = serde_json::from_str(&line?)?;
+        match reply {
+            Reply::Output { text } => {
+                drop(gctx.shell().err().write_all(text.as_bytes()));
+            }
+            Reply::Refused { message } => {
+                gctx.shell().verbose(|shell| {
+                    shell.note(format!("not using the build server: {message}"))
+                })?;
+                return Ok(None);
+            }
+            Reply::Finished { code } => return Ok(Some(code)),
+        }
+    }
+    anyhow::bail!("the build server stopped before the end of the build")
+}
+
+/// Stops the server of `manifest_path`.
+pub fn stop(gctx: &GlobalContext, manifest_path: &Path) -> CargoResult<()> {
+    let path = info_path(gctx, manifest_path);
+    let Some(info) = read_info(&path) else {
+        anyhow::bail!(
+            "no build server is running for `{}`",
+            manifest_path.display()
+        );
+    };
+    let addr = info.addr;
+    let mut stream = TcpStream::connect(addr)
+        .with_context(|| format!("failed to connect to the build server on {addr}"))?;
+    let request = Request {
+        token: info.token,
+        stop: true,
+        args: Vec::new(),
+        manifest_path: manifest_path.to_path_buf(),
+        cwd: PathBuf::new(),
+        env_hash: String::new(),
+    };
+    send(&mut stream, &request)?;
+    // Wait for the server to acknowledge it.
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    gctx.shell()
+        .status("Stopped", format!("build server on {addr}"))
+}
+
+/// Environment variables which shells update on their own, and which don't
+/// affect builds.
+pub(crate) const SHELL_ENV: [&str; 3] = ["_", "OLDPWD", "PWD"];
+
+/// Hashes the environment variables, which have to match between the server
+/// and its clients, as the builds run with the environment of the server, and
+/// as any variable may be read by build scripts, compilers or linkers.
+///
+/// Only the hash is sent, not the variables, which may hold secrets.
+fn env_hash() -> String {
+    let env: BTreeMap<_, _> = std::env::vars_os()
+        .filter(|(key, _)| !SHELL_ENV.iter().any(|shell_key| key == shell_key))
+        .collect();
+    let mut hasher = Sha256::new();
+    for (key, value) in env {
+        hasher
+            .update(key.as_encoded_bytes())
+            .update(b"=")
+            .update(value.as_encoded_bytes())
+            .update(b"\0");
+    }
+    hasher.finish_hex()
+}
+
+/// The configuration files Cargo may load, whether they exist or not, which
+/// the server has to be started again to load.
+fn config_files(gctx: &GlobalContext) -> Vec<PathBuf> {
+    let mut dirs: Vec<_> = gctx
+        .cwd()
+        .ancestors()
+        .map(|dir| dir.join(".cargo"))
+        .collect();
+    dirs.push(gctx.home().as_path_unlocked().to_path_buf());
+    dirs.iter()
+        .flat_map(|dir| [dir.join("config"), dir.join("config.toml")])
+        .collect()
+}
+
+/// The files a workspace is loaded from: the manifests of its members and of
+/// the path dependencies outside of it, its lockfile, and the directories
+/// where the globs of `workspace.members` look for new members.
+fn workspace_files(ws: &Workspace<'_>) -> CargoResult<Vec<PathBuf>> {
+    let mut files: Vec<_> = ws
+        .members()
+        .map(|pkg| pkg.manifest_path().to_path_buf())
+        .collect();
+    files.push(ws.root_manifest().to_path_buf());
+    files.push(
+        ops::lockfile::lock_root(ws)
+            .as_path_unlocked()
+            .join("Cargo.lock"),
+    );
+    files.extend(ws.member_glob_dirs()?);
+
+    // The workspace keeps the path dependencies outside of it once loaded.
+    let mut seen: HashSet<_> = files.iter().cloned().collect();
+    let mut pending: Vec<_> = ws.members().cloned().collect();
+    while let Some(pkg) = pending.pop() {
+        for dep in pkg.dependencies() {
+            let source_id = dep.source_id();
+            if !source_id.is_path() {
+                continue;
+            }
+            let Ok(dir) = source_id.url().to_file_path() else {
+                continue;
+            };
+            let manifest_path = dir.join("Cargo.toml");
+            if !seen.insert(manifest_path.clone()) {
+                continue;
+            }
+            files.push(manifest_path.clone());
+            // A missing or broken manifest fails the build, which reports it.
+            if let Ok(dep_pkg) = ws.load(&manifest_path) {
+                pending.push(dep_pkg);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn info_path(gctx: &GlobalContext, manifest_path: &Path) -> PathBuf {
+    gctx.home()
+        .as_path_unlocked()
+        .join("build-servers")
+        .join(format!("{}.json", short_hash(&manifest_path)))
+}
+
+fn write_info(
+    gctx: &GlobalContext,
+    manifests: &[PathBuf],
+    info: &ServerInfo,
+) -> CargoResult<InfoFiles> {
+    let contents = serde_json::to_string(info)?;
+    let mut files = InfoFiles(Vec::new());
+    for manifest_path in manifests {
+        let path = info_path(gctx, manifest_path);
+        paths::create_dir_all(path.parent().unwrap())?;
+        // Clients must never read a partially written file.
+        let tmp = path.with_extension("json.tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // The token lets anyone who can read it run builds as this user.
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(&tmp)
+            .with_context(|| format!("failed to create `{}`", tmp.display()))?;
+        file.write_all(contents.as_bytes())?;
+        drop(file);
+        fs::rename(&tmp, &path)
+            .with_context(|| format!("failed to create `{}`", path.display()))?;
+        files.0.push(path);
+    }
+    Ok(files)
+}
+
+fn read_info(path: &Path) -> Option<ServerInfo> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Reads the request of a client, giving up after [`REQUEST_TIMEOUT`], so a
+/// client which never sends its request doesn't block the server.
+fn read_request(stream: &TcpStream) -> CargoResult<Request> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(stream.take(MAX_REQUEST_LEN)).read_line(&mut line)?;
+    stream.set_read_timeout(None)?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+fn send<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+/// Sends what the build prints to the client.
+struct OutputWriter(TcpStream);
+
+impl Write for OutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf).into_owned();
+        send(&mut self.0, &Reply::Output { text })?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
@@ -3,18 +3,43 @@ use crate::core::profiles::PanicStrategy;
 use crate::core::shell::Verbosity;
 use crate::core::{TargetKind, Workspace};
 use crate::ops;
+use crate::ops::cargo_server::SHELL_ENV;
+use crate::util::context::CargoTestConfig;
 use crate::util::errors::CargoResult;
+use crate::util::interrupt::{self, InterruptGuard};
 use crate::util::{add_path_args, CliError, CliResult, GlobalContext};
-use anyhow::format_err;
-use cargo_util::{ProcessBuilder, ProcessError};
-use std::ffi::OsString;
+use anyhow::{format_err, Context as _};
+use cargo_util::{paths, ProcessBuilder, ProcessError, Sha256};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
 use std::fmt::Write;
+use std::io::{BufRead, BufReader, Write as _};
 use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 pub struct TestOptions {
     pub compile_opts: ops::CompileOptions,
     pub no_run: bool,
     pub no_fail_fast: bool,
+    pub runners: TestRunners,
+    /// Whether to skip the test binaries which already passed with the same
+    /// inputs, or `None` to follow `test.cache-results`.
+    pub cache_results: Option<bool>,
+}
+
+/// Which runners from `target.<triple>.runners` to execute tests with.
+#[derive(Clone, Debug, Default)]
+pub enum TestRunners {
+    /// Use the runner from `target.<triple>.runner`, if any.
+    #[default]
+    Default,
+    /// Use the runner with the given label (`--runner`).
+    Label(String),
+    /// Run the tests once with each labeled runner (`--all-runners`).
+    All,
 }
 
 /// The kind of test.
@@ -26,12 +51,15 @@ enum TestKind {
     Test,
     Bench,
     Doctest,
+    FuzzSmoke,
 }
 
 /// A unit that failed to run.
 struct UnitTestError {
     unit: Unit,
     kind: TestKind,
+    /// The label of the runner the test failed under, if any.
+    runner: Option<String>,
 }
 
 impl UnitTestError {
@@ -52,8 +80,13 @@ impl UnitTestError {
                 TargetKind::Bench => add("bench"),
                 TargetKind::ExampleLib(_) | TargetKind::ExampleBin => add("example"),
                 TargetKind::CustomBuild => panic!("unexpected CustomBuild kind"),
+                TargetKind::Fuzz => panic!("unexpected Fuzz kind"),
             },
             TestKind::Doctest => args.push_str("--doc"),
+            TestKind::FuzzSmoke => args.push_str("--fuzz-smoke"),
+        }
+        if let Some(runner) = &self.runner {
+            write!(args, " --runner {runner}").unwrap();
         }
         args
     }
@@ -64,21 +97,87 @@ impl UnitTestError {
 /// On error, the returned [`CliError`] will have the appropriate process exit
 /// code that Cargo should use.
 pub fn run_tests(ws: &Workspace<'_>, options: &TestOptions, test_args: &[&str]) -> CliResult {
-    let compilation = compile_tests(ws, options)?;
+    let mut compilation = compile_tests(ws, options)?;
+    let runners = selected_runners(&compilation, &options.runners)?;
 
     if options.no_run {
         if !options.compile_opts.build_config.emit_json() {
-            display_no_run_information(ws, test_args, &compilation, "unittests")?;
+            for runner in &runners {
+                compilation.select_target_runner(runner.as_deref());
+                display_no_run_information(ws, test_args, &compilation, "unittests")?;
+            }
         }
         return Ok(());
     }
-    let mut errors = run_unit_tests(ws, options, test_args, &compilation, TestKind::Test)?;
 
-    let doctest_errors = run_doc_tests(ws, options, test_args, &compilation)?;
-    errors.extend(doctest_errors);
+    let gctx = ws.gctx();
+    let mut errors = Vec::new();
+    for (i, runner) in runners.iter().enumerate() {
+        compilation.select_target_runner(runner.as_deref());
+        if let Some(label) = runner {
+            gctx.shell().status("Runner", label)?;
+        }
+        let first_run = i == 0;
+        errors.extend(run_unit_tests(
+            ws,
+            options,
+            test_args,
+            &compilation,
+            TestKind::Test,
+            first_run,
+        )?);
+        // Doctests only go through the runner with `-Zdoctest-xcompile`, so
+        // there is no point in running them more than once otherwise.
+        if first_run || gctx.cli_unstable().doctest_xcompile {
+            errors.extend(run_doc_tests(
+                ws,
+                options,
+                test_args,
+                &compilation,
+                first_run,
+            )?);
+        }
+        errors.extend(run_fuzz_smoke_tests(ws, options, &compilation, first_run)?);
+    }
     no_fail_fast_err(ws, &options.compile_opts, &errors)
 }
 
+/// Returns the labels of the runners to execute the tests with, in order.
+///
+/// `None` stands for the default runner from `target.<triple>.runner`.
+fn selected_runners(
+    compilation: &Compilation<'_>,
+    runners: &TestRunners,
+) -> CargoResult<Vec<Option<String>>> {
+    let labels = compilation.target_runner_labels();
+    match runners {
+        TestRunners::Default => Ok(vec![None]),
+        TestRunners::Label(label) => {
+            if !labels.contains(label.as_str()) {
+                let mut msg = format!(
+                    "no runner labeled `{label}` is configured in `target.<triple>.runners` \
+                     for the requested targets"
+                );
+                if !labels.is_empty() {
+                    let labels: Vec<_> = labels.iter().map(|l| format!("`{l}`")).collect();
+                    write!(msg, "\navailable runners: {}", labels.join(", ")).unwrap();
+                }
+                anyhow::bail!(msg);
+            }
+            Ok(vec![Some(label.clone())])
+        }
+        TestRunners::All => {
+            if labels.is_empty() {
+                anyhow::bail!(
+                    "`--all-runners` was specified, but no `target.<triple>.runners` \
+                     are configured for the requested targets"
+                );
+            }
+            Ok(labels.into_iter().map(|l| Some(l.to_string())).collect())
+        }
+    }
+}
+
 /// Compiles and runs benchmarks.
 ///
 /// On error, the returned [`CliError`] will have the appropriate process exit
@@ -96,7 +195,7 @@ pub fn run_benches(ws: &Workspace<'_>, options: &TestOptions, args: &[&str]) ->
     let mut args = args.to_vec();
     args.push("--bench");
 
-    let errors = run_unit_tests(ws, options, &args, &compilation, TestKind::Bench)?;
+    let errors = run_unit_tests(ws, options, &args, &compilation, TestKind::Bench, true)?;
     no_fail_fast_err(ws, &options.compile_opts, &errors)
 }
 
@@ -116,9 +215,17 @@ fn run_unit_tests(
     test_args: &[&str],
     compilation: &Compilation<'_>,
     test_kind: TestKind,
+    first_run: bool,
 ) -> Result<Vec<UnitTestError>, CliError> {
     let gctx = ws.gctx();
     let cwd = gctx.cwd();
+    let timeout = test_timeout(gctx)?;
+    let cache_results = matches!(test_kind, TestKind::Test)
+        && match options.cache_results {
+            Some(cache_results) => cache_results,
+            None => cache_results_config(gctx)?,
+        };
+    let results_dir = ws.target_dir().join(".test-results").into_path_unlocked();
     let mut errors = Vec::new();
 
     for UnitOutput {
@@ -127,6 +234,11 @@ fn run_unit_tests(
         script_meta,
     } in compilation.tests.iter()
     {
+        let runner = compilation.target_runner_label(unit.kind);
+        if runner.is_none() && !first_run {
+            // Already run with the default runner of its target.
+            continue;
+        }
         let (exe_display, mut cmd) = cmd_builds(
             gctx,
             cwd,
@@ -142,16 +254,54 @@ fn run_unit_tests(
             cmd.display_env_vars();
         }
 
+        let cached = if cache_results {
+            let record = results_dir.join(hash_test_command(&cmd));
+            // The fingerprint doesn't track the sources of local packages,
+            // but the binary is relinked whenever they change.
+            let fingerprint = compilation.test_fingerprints.get(unit);
+            let binary = format!(
+                "{:016x} {}",
+                fingerprint.copied().unwrap_or_default(),
+                paths::mtime(path)?
+            );
+            if fingerprint.is_some()
+                && paths::read(&record).ok().as_deref() == Some(binary.as_str())
+            {
+                gctx.shell()
+                    .status("Cached", format!("pass {exe_display}"))?;
+                continue;
+            }
+            Some((record, binary))
+        } else {
+            None
+        };
+
         gctx.shell()
             .concise(|shell| shell.status("Running", &exe_display))?;
         gctx.shell()
             .verbose(|shell| shell.status("Running", &cmd))?;
 
-        if let Err(e) = cmd.exec() {
+        let result = match timeout {
+            Some(timeout) => {
+                // The output of libtest is piped, so it must be told to use
+                // colors if Cargo would.
+                let color = unit.target.harness()
+                    && gctx.shell().out_supports_color()
+                    && !test_args.iter().any(|arg| arg.starts_with("--color"));
+                exec_with_timeout(&cmd, timeout, color)
+            }
+            None => cmd.exec(),
+        };
+        if let (Ok(()), Some((record, binary))) = (&result, cached) {
+            paths::create_dir_all(&results_dir)?;
+            paths::write(&record, binary)?;
+        }
+        if let Err(e) = result {
             let code = fail_fast_code(&e);
             let unit_err = UnitTestError {
                 unit: unit.clone(),
                 kind: test_kind,
+                runner: runner.map(str::to_string),
             };
             report_test_error(ws, test_args, &options.compile_opts, &unit_err, e);
             errors.push(unit_err);
@@ -163,6 +313,68 @@ fn run_unit_tests(
     Ok(errors)
 }
 
+/// Runs each fuzz target built by `cargo test --fuzz-smoke` once, to check
+/// it starts and handles an input without crashing.
+///
+/// Returns a `Vec` of fuzz targets that failed when `--no-fail-fast` is used.
+/// If `--no-fail-fast` is *not* used, then this returns an `Err`.
+fn run_fuzz_smoke_tests(
+    ws: &Workspace<'_>,
+    options: &TestOptions,
+    compilation: &Compilation<'_>,
+    first_run: bool,
+) -> Result<Vec<UnitTestError>, CliError> {
+    let gctx = ws.gctx();
+    let cwd = gctx.cwd();
+    let fuzz_args = ["-runs=1"];
+    let mut errors = Vec::new();
+
+    for UnitOutput {
+        unit,
+        path,
+        script_meta,
+    } in compilation
+        .binaries
+        .iter()
+        .filter(|o| o.unit.target.is_fuzz())
+    {
+        let runner = compilation.target_runner_label(unit.kind);
+        if runner.is_none() && !first_run {
+            continue;
+        }
+        let (exe_display, cmd) = cmd_builds(
+            gctx,
+            cwd,
+            unit,
+            path,
+            script_meta,
+            &fuzz_args,
+            compilation,
+            "fuzz",
+        )?;
+
+        gctx.shell()
+            .concise(|shell| shell.status("Running", &exe_display))?;
+        gctx.shell()
+            .verbose(|shell| shell.status("Running", &cmd))?;
+
+        if let Err(e) = cmd.exec() {
+            let code = fail_fast_code(&e);
+            let unit_err = UnitTestError {
+                unit: unit.clone(),
+                kind: TestKind::FuzzSmoke,
+                runner: runner.map(str::to_string),
+            };
+            report_test_error(ws, &fuzz_args, &options.compile_opts, &unit_err, e);
+            errors.push(unit_err);
+            if !options.no_fail_fast {
+                return Err(CliError::code(code));
+            }
+        }
+    }
+    Ok(errors)
+}
+
 /// Runs doc tests.
 ///
 /// Returns a `Vec` of tests that failed when `--no-fail-fast` is used.
@@ -172,6 +384,7 @@ fn run_doc_tests(
     options: &TestOptions,
     test_args: &[&str],
     compilation: &Compilation<'_>,
+    first_run: bool,
 ) -> Result<Vec<UnitTestError>, CliError> {
     let gctx = ws.gctx();
     let mut errors = Vec::new();
@@ -187,6 +400,16 @@ fn run_doc_tests(
             env,
         } = doctest_info;
 
+        // Doctests only go through the runner with `-Zdoctest-xcompile`.
+        let runner = if doctest_xcompile {
+            compilation.target_runner_label(unit.kind)
+        } else {
+            None
+        };
+        if runner.is_none() && !first_run {
+            continue;
+        }
+
         if !doctest_xcompile {
             match unit.kind {
                 CompileKind::Host => {}
@@ -288,6 +511,7 @@ fn run_doc_tests(
             let unit_err = UnitTestError {
                 unit: unit.clone(),
                 kind: TestKind::Doctest,
+                runner: runner.map(str::to_string),
             };
             report_test_error(ws, test_args, &options.compile_opts, &unit_err, e);
             errors.push(unit_err);
@@ -379,6 +603,228 @@ fn cmd_builds(
     Ok((exe_display, cmd))
 }
 
+/// Returns the per-binary timeout from `test.timeout`, if configured.
+///
+/// This is only read when `-Ztest-timeout` is enabled.
+fn test_timeout(gctx: &GlobalContext) -> CargoResult<Option<Duration>> {
+    if !gctx.cli_unstable().test_timeout {
+        return Ok(None);
+    }
+    let config = gctx.get::<CargoTestConfig>("test")?;
+    let Some(timeout) = config.timeout else {
+        return Ok(None);
+    };
+    let duration = timeout
+        .val
+        .to_duration()
+        .with_context(|| format!("invalid `test.timeout` in {}", timeout.definition))?;
+    Ok(Some(duration))
+}
+
+/// Returns whether `test.cache-results` asks to skip the test binaries which
+/// already passed.
+///
+/// This is only read with `-Zunstable-options`.
+fn cache_results_config(gctx: &GlobalContext) -> CargoResult<bool> {
+    let config = gctx.get::<CargoTestConfig>("test")?;
+    let Some(cache_results) = config.cache_results else {
+        return Ok(false);
+    };
+    if !gctx.cli_unstable().unstable_options {
+        gctx.shell()
+            .warn("`test.cache-results` requires `-Zunstable-options`, ignoring it")?;
+        return Ok(false);
+    }
+    Ok(cache_results)
+}
+
+/// Hashes the program, arguments, working directory and environment
+/// variables of a test binary's command, naming the file recording the
+/// binary it last passed with.
+///
+/// The environment is the one the binary inherits from Cargo, with the
+/// variables set by Cargo on top, leaving out the ones shells update on
+/// their own.
+fn hash_test_command(cmd: &ProcessBuilder) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(cmd.get_program().to_string_lossy().as_bytes());
+    for arg in cmd.get_args() {
+        hasher
+            .update(b"\0")
+            .update(arg.to_string_lossy().as_bytes());
+    }
+    if let Some(cwd) = cmd.get_cwd() {
+        hasher
+            .update(b"\0")
+            .update(cwd.to_string_lossy().as_bytes());
+    }
+    let mut env = std::env::vars_os()
+        .filter(|(key, _)| !SHELL_ENV.iter().any(|shell_key| key == shell_key))
+        .collect::<BTreeMap<_, _>>();
+    for (key, value) in cmd.get_envs() {
+        match value {
+            Some(value) => env.insert(key.into(), value.clone()),
+            None => env.remove(OsStr::new(key)),
+        };
+    }
+    for (key, value) in env {
+        hasher
+            .update(b"\0")
+            .update(key.to_string_lossy().as_bytes())
+            .update(b"=")
+            .update(value.to_string_lossy().as_bytes());
+    }
+    hasher.finish_hex()
+}
+
+/// Runs a test binary under a watchdog, killing it if it runs longer than
+/// `timeout`.
+///
+/// The standard output of the test binary is forwarded line by line so that
+/// the libtest output can be inspected to figure out which tests were still
+/// running when the binary got killed. Both the human-readable and the JSON
+/// (`--format json`) output of libtest are understood. With `color`, libtest
+/// is asked to keep its colors, as its output isn't a terminal anymore.
+///
+/// On Unix, the test binary runs in its own process group, so that the
+/// processes it spawned are killed along with it. Ctrl-C doesn't reach that
+/// group, so it's handled here.
+fn exec_with_timeout(cmd: &ProcessBuilder, timeout: Duration, color: bool) -> CargoResult<()> {
+    let mut cmd = cmd.clone();
+    if color {
+        cmd.arg("--color=always");
+    }
+    let cmd = &cmd;
+    let mut command = cmd.build_command();
+    command.stdout(Stdio::piped());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let _interrupt_guard = InterruptGuard::install();
+    let mut child = command
+        .spawn()
+        .with_context(|| ProcessError::could_not_execute(cmd))?;
+
+    let running = Arc::new(Mutex::new(Vec::new()));
+    let stdout = child.stdout.take().unwrap();
+    let reader = {
+        let running = Arc::clone(&running);
+        std::thread::spawn(move || {
+            let mut stdout = BufReader::new(stdout);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                match stdout.read_until(b'\n', &mut line) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+                let mut out = std::io::stdout().lock();
+                let _ = out.write_all(&line);
+                let _ = out.flush();
+                let text = String::from_utf8_lossy(&line);
+                track_running_tests(text.trim_end(), &mut running.lock().unwrap());
+            }
+        })
+    };
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .with_context(|| ProcessError::could_not_execute(cmd))?
+        {
+            break Some(status);
+        }
+        if interrupt::is_interrupted() {
+            kill(&mut child);
+            anyhow::bail!("interrupted while running test binary: {cmd}");
+        }
+        if start.elapsed() >= timeout {
+            kill(&mut child);
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    let Some(status) = status else {
+        // The reader is not joined here, a grandchild process which left the
+        // process group might still hold on to the pipe.
+        let running = running.lock().unwrap();
+        let mut msg = format!(
+            "test binary timed out after {}s and was killed: {cmd}",
+            timeout.as_secs()
+        );
+        if running.is_empty() {
+            msg.push_str("\nthe test harness did not report any test as still running");
+        } else {
+            msg.push_str("\ntests still running at timeout:");
+            for name in running.iter() {
+                write!(msg, "\n    {name}").unwrap();
+            }
+        }
+        return Err(anyhow::Error::msg(msg));
+    };
+
+    let _ = reader.join();
+    if status.success() {
+        Ok(())
+    } else {
+        Err(ProcessError::new(
+            &format!("process didn't exit successfully: {}", cmd),
+            Some(status),
+            None,
+        )
+        .into())
+    }
+}
+
+/// Kills a test binary started by [`exec_with_timeout`], along with the rest
+/// of its process group on Unix, and waits for it.
+fn kill(child: &mut std::process::Child) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// A single event of libtest's `--format json` output.
+#[derive(Deserialize)]
+struct LibtestEvent {
+    #[serde(rename = "type")]
+    ty: String,
+    event: String,
+    name: Option<String>,
+}
+
+/// Updates the list of running tests from a line of libtest output.
+fn track_running_tests(line: &str, running: &mut Vec<String>) {
+    if let Ok(event) = serde_json::from_str::<LibtestEvent>(line) {
+        let (Some(name), "test") = (event.name, event.ty.as_str()) else {
+            return;
+        };
+        if event.event == "started" {
+            if !running.contains(&name) {
+                running.push(name);
+            }
+        } else {
+            running.retain(|n| *n != name);
+        }
+        return;
+    }
+    let Some(rest) = line.strip_prefix("test ") else {
+        return;
+    };
+    if let Some(name) = rest.strip_suffix(" has been running for over 60 seconds") {
+        if !running.iter().any(|n| n == name) {
+            running.push(name.to_string());
+        }
+    } else if let Some((name, _result)) = rest.split_once(" ... ") {
+        running.retain(|n| n != name);
+    }
+}
+
 /// Returns the error code to use when *not* using `--no-fail-fast`.
 ///
 /// Cargo will return the error code from the test process itself. If some
@@ -428,6 +874,7 @@ fn report_test_error(
         TestKind::Test => "test failed",
         TestKind::Bench => "bench failed",
         TestKind::Doctest => "doctest failed",
+        TestKind::FuzzSmoke => "fuzz smoke test failed",
     };
 
     let mut err = format_err!("{}, to rerun pass `{}`", which, unit_err.cli_args(ws, opts));
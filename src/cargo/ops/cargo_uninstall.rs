@@ -1,18 +1,25 @@
 use crate::core::PackageId;
 use crate::core::{PackageIdSpec, PackageIdSpecQuery, SourceId};
 use crate::ops::common_for_install_and_uninstall::*;
+use crate::ops::CleanContext;
 use crate::sources::PathSource;
+use crate::util::cache_lock::CacheLockMode;
 use crate::util::errors::CargoResult;
 use crate::util::Filesystem;
 use crate::util::GlobalContext;
 use anyhow::bail;
+use cargo_util::paths;
 use std::collections::BTreeSet;
 use std::env;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::time::SystemTime;
 
 pub fn uninstall(
     root: Option<&str>,
     specs: Vec<&str>,
     bins: &[String],
+    purge: bool,
     gctx: &GlobalContext,
 ) -> CargoResult<()> {
     if specs.len() > 1 && !bins.is_empty() {
@@ -21,17 +28,17 @@ pub fn uninstall(
 
     let root = resolve_root(root, gctx)?;
     let scheduled_error = if specs.len() == 1 {
-        uninstall_one(&root, specs[0], bins, gctx)?;
+        uninstall_one(&root, specs[0], bins, purge, gctx)?;
         false
     } else if specs.is_empty() {
-        uninstall_cwd(&root, bins, gctx)?;
+        uninstall_cwd(&root, bins, purge, gctx)?;
         false
     } else {
         let mut succeeded = vec![];
         let mut failed = vec![];
         for spec in specs {
             let root = root.clone();
-            match uninstall_one(&root, spec, bins, gctx) {
+            match uninstall_one(&root, spec, bins, purge, gctx) {
                 Ok(()) => succeeded.push(spec),
                 Err(e) => {
                     crate::display_error(&e, &mut gctx.shell());
@@ -68,20 +75,79 @@ pub fn uninstall(
     Ok(())
 }
 
+/// Uninstalls every installed package, after the user confirms the list of
+/// what will be removed.
+pub fn uninstall_all(root: Option<&str>, purge: bool, gctx: &GlobalContext) -> CargoResult<()> {
+    let root = resolve_root(root, gctx)?;
+    let mut tracker = InstallTracker::load(gctx, &root)?;
+    let installed: Vec<_> = tracker
+        .all_installed_bins()
+        .map(|(pkg_id, bins)| (*pkg_id, bins.clone()))
+        .collect();
+    if installed.is_empty() {
+        gctx.shell()
+            .warn("no packages are installed, nothing to uninstall")?;
+        return Ok(());
+    }
+
+    let dst = root.join("bin").into_path_unlocked();
+    let mut listing = String::from("the following will be removed:\n");
+    for (pkg_id, bins) in &installed {
+        listing.push_str(&format!("  {pkg_id}\n"));
+        for bin in bins {
+            listing.push_str(&format!("    {}\n", dst.join(bin).display()));
+        }
+        if purge && pkg_id.source_id().is_registry() {
+            listing.push_str("    and its cached sources\n");
+        }
+    }
+    gctx.shell().note(listing.trim_end())?;
+    if !confirm(gctx, "uninstall all of the above? [y/N] ")? {
+        bail!("uninstall aborted, nothing was removed");
+    }
+
+    for (pkg_id, _) in installed {
+        uninstall_pkgid(&root, &mut tracker, pkg_id, &[], purge, gctx)?;
+    }
+    Ok(())
+}
+
+/// Asks the user a yes/no question on stderr, reading the answer from stdin.
+fn confirm(gctx: &GlobalContext, prompt: &str) -> CargoResult<bool> {
+    {
+        let mut shell = gctx.shell();
+        let err = shell.err();
+        write!(err, "{prompt}")?;
+        err.flush()?;
+    }
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
 pub fn uninstall_one(
     root: &Filesystem,
     spec: &str,
     bins: &[String],
+    purge: bool,
     gctx: &GlobalContext,
 ) -> CargoResult<()> {
-    let tracker = InstallTracker::load(gctx, root)?;
+    let mut tracker = InstallTracker::load(gctx, root)?;
     let all_pkgs = tracker.all_installed_bins().map(|(pkg_id, _set)| *pkg_id);
     let pkgid = PackageIdSpec::query_str(spec, all_pkgs)?;
-    uninstall_pkgid(root, tracker, pkgid, bins, gctx)
+    uninstall_pkgid(root, &mut tracker, pkgid, bins, purge, gctx)
 }
 
-fn uninstall_cwd(root: &Filesystem, bins: &[String], gctx: &GlobalContext) -> CargoResult<()> {
-    let tracker = InstallTracker::load(gctx, root)?;
+fn uninstall_cwd(
+    root: &Filesystem,
+    bins: &[String],
+    purge: bool,
+    gctx: &GlobalContext,
+) -> CargoResult<()> {
+    let mut tracker = InstallTracker::load(gctx, root)?;
     let source_id = SourceId::for_path(gctx.cwd())?;
     let mut src = path_source(source_id, gctx)?;
     let pkg = select_pkg(
@@ -92,14 +158,15 @@ fn uninstall_cwd(root: &Filesystem, bins: &[String], gctx: &GlobalContext) -> Ca
         None,
     )?;
     let pkgid = pkg.package_id();
-    uninstall_pkgid(root, tracker, pkgid, bins, gctx)
+    uninstall_pkgid(root, &mut tracker, pkgid, bins, purge, gctx)
 }
 
 fn uninstall_pkgid(
     root: &Filesystem,
-    mut tracker: InstallTracker,
+    tracker: &mut InstallTracker,
     pkgid: PackageId,
     bins: &[String],
+    purge: bool,
     gctx: &GlobalContext,
 ) -> CargoResult<()> {
     let installed = match tracker.installed_bins(pkgid) {
@@ -143,11 +210,59 @@ fn uninstall_pkgid(
         }
     };
 
+    // The binaries are written at the end of their installation, after
+    // the sources they were built from were last used by it.
+    let mut installed_at = None;
     for bin in to_remove {
         let bin_path = dst.join(&bin);
+        if purge {
+            let mtime = paths::metadata(&bin_path)?.modified()?;
+            installed_at = installed_at.max(Some(mtime));
+        }
         gctx.shell().status("Removing", bin_path.display())?;
         tracker.remove_bin_then_save(pkgid, &bin, &bin_path)?;
     }
 
+    if let Some(installed_at) = installed_at {
+        if tracker.installed_bins(pkgid).is_some() {
+            gctx.shell().warn(format!(
+                "not purging cached sources of `{pkgid}`, it still has installed binaries"
+            ))?;
+        } else {
+            let removed = purge_cached_sources(pkgid, &installed_at, gctx)?;
+            tracker.record_purge_then_save(pkgid, removed)?;
+        }
+    }
+
     Ok(())
 }
+
+/// Removes the downloaded `.crate` file and the extracted sources of a
+/// registry package from `CARGO_HOME`, returning the removed paths.
+///
+/// This goes through the global cache tracker, so the removal is recorded
+/// for `cargo clean gc`. The package is looked up in every registry, since
+/// with source replacement it may have been downloaded from a registry other
+/// than the one in its ID. Sources used since `installed_at` are kept, as
+/// another project may depend on them, and so are the sources of other
+/// packages, including the dependencies built along with it; `cargo clean
+/// gc` takes care of those.
+fn purge_cached_sources(
+    pkgid: PackageId,
+    installed_at: &SystemTime,
+    gctx: &GlobalContext,
+) -> CargoResult<Vec<PathBuf>> {
+    if !pkgid.source_id().is_registry() {
+        return Ok(Vec::new());
+    }
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::MutateExclusive)?;
+    let package_dir = format!("{}-{}", pkgid.name(), pkgid.version());
+    let mut clean_ctx = CleanContext::new(gctx);
+    let removed = gctx.global_cache_tracker()?.clean_registry_package(
+        &mut clean_ctx,
+        &package_dir,
+        installed_at,
+    )?;
+    clean_ctx.display_summary()?;
+    Ok(removed)
+}
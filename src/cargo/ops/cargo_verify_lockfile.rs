@@ -0,0 +1,301 @@
+//! Implementation of `cargo verify-lockfile` subcommand.
+//!
+//! `cargo verify-lockfile` checks `Cargo.lock` without modifying it, so it
+//! can gate CI:
+//!
+//! * it parses and is internally consistent,
+//! * it matches the manifests, i.e. resolving the workspace gives the same
+//!   lock file, and succeeds,
+//! * the checksums of registry packages match the `.crate` files in the
+//!   download cache, when they have been downloaded,
+//! * none of its registry packages are yanked.
+
+use std::collections::HashSet;
+use std::task::Poll;
+
+use anyhow::bail;
+use cargo_util::Sha256;
+use serde::Serialize;
+
+use crate::core::registry::PackageRegistry;
+use crate::core::Registry as _;
+use crate::core::{Dependency, PackageId, Resolve, Workspace};
+use crate::ops;
+use crate::ops::lockfile::lock_root;
+use crate::sources::registry::cached_crate_path;
+use crate::sources::source::QueryKind;
+use crate::sources::SourceConfigMap;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::errors::CargoResult;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyLockfileFormat {
+    Human,
+    Json,
+}
+
+impl clap::ValueEnum for VerifyLockfileFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[VerifyLockfileFormat::Human, VerifyLockfileFormat::Json]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let name = match self {
+            VerifyLockfileFormat::Human => "human",
+            VerifyLockfileFormat::Json => "json",
+        };
+        Some(clap::builder::PossibleValue::new(name))
+    }
+}
+
+/// Options for `cargo verify-lockfile`.
+pub struct VerifyLockfileOptions {
+    pub format: VerifyLockfileFormat,
+}
+
+#[derive(Serialize)]
+struct Report {
+    ok: bool,
+    problems: Vec<Problem>,
+    /// Packages whose checksum could not be checked, which is not a failure.
+    unverified: Vec<Unverified>,
+}
+
+#[derive(Serialize)]
+struct Problem {
+    kind: ProblemKind,
+    package: Option<String>,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum ProblemKind {
+    MissingLockfile,
+    Invalid,
+    ResolveFailed,
+    MissingPackage,
+    ExtraPackage,
+    Outdated,
+    MissingChecksum,
+    ChecksumMismatch,
+    Yanked,
+}
+
+#[derive(Serialize)]
+struct Unverified {
+    package: String,
+    reason: String,
+}
+
+pub fn verify_lockfile(ws: &Workspace<'_>, opts: &VerifyLockfileOptions) -> CargoResult<()> {
+    let mut report = Report {
+        ok: true,
+        problems: Vec::new(),
+        unverified: Vec::new(),
+    };
+    check(ws, &mut report)?;
+    report.ok = report.problems.is_empty();
+
+    let gctx = ws.gctx();
+    match opts.format {
+        VerifyLockfileFormat::Human => {
+            for unverified in &report.unverified {
+                gctx.shell().note(format!(
+                    "could not verify the checksum of `{}`: {}",
+                    unverified.package, unverified.reason
+                ))?;
+            }
+            if report.ok {
+                gctx.shell().status("Verified", "Cargo.lock")?;
+                return Ok(());
+            }
+        }
+        VerifyLockfileFormat::Json => gctx.shell().print_json(&report)?,
+    }
+    if !report.ok {
+        let list = report
+            .problems
+            .iter()
+            .map(|p| format!("  {}", p.message))
+            .collect::<Vec<_>>()
+            .join("\n");
+        bail!("Cargo.lock failed verification:\n{list}");
+    }
+    Ok(())
+}
+
+fn check(ws: &Workspace<'_>, report: &mut Report) -> CargoResult<()> {
+    let lock_path = lock_root(ws).as_path_unlocked().join("Cargo.lock");
+    let locked = match ops::load_pkg_lockfile(ws) {
+        Ok(Some(resolve)) => resolve,
+        Ok(None) => {
+            report.problems.push(Problem {
+                kind: ProblemKind::MissingLockfile,
+                package: None,
+                message: format!("no lock file found at {}", lock_path.display()),
+            });
+            return Ok(());
+        }
+        Err(e) => {
+            report.problems.push(Problem {
+                kind: ProblemKind::Invalid,
+                package: None,
+                message: format!("{e:#}"),
+            });
+            return Ok(());
+        }
+    };
+
+    check_packages(ws, &locked, report)?;
+    check_checksums(ws, &locked, report)?;
+    check_yanked(ws, &locked, report)?;
+    Ok(())
+}
+
+/// Resolves the workspace without writing `Cargo.lock`, and compares the
+/// result with the locked one.
+///
+/// Missing and extra packages are reported individually. Any other
+/// difference, like a changed dependency between locked packages, is
+/// reported as the lock file being outdated.
+fn check_packages(ws: &Workspace<'_>, locked: &Resolve, report: &mut Report) -> CargoResult<()> {
+    let mut resolve = match ops::resolve_ws_with_dry_run(ws, true) {
+        Ok((_packages, resolve)) => resolve,
+        Err(e) => {
+            report.problems.push(Problem {
+                kind: ProblemKind::ResolveFailed,
+                package: None,
+                message: format!("failed to resolve the manifests: {e:#}"),
+            });
+            return Ok(());
+        }
+    };
+    // Content checksums are only computed when writing the lock file.
+    resolve.set_content_checksums(locked.content_checksums().clone());
+    let problems = report.problems.len();
+
+    let mut missing: Vec<_> = resolve.iter().filter(|id| !locked.contains(id)).collect();
+    missing.sort();
+    for id in missing {
+        report.problems.push(Problem {
+            kind: ProblemKind::MissingPackage,
+            package: Some(id.to_spec().to_string()),
+            message: format!("package `{id}` is required by the manifests but missing"),
+        });
+    }
+
+    let mut extra: Vec<_> = locked.iter().filter(|id| !resolve.contains(id)).collect();
+    extra.sort();
+    for id in extra {
+        report.problems.push(Problem {
+            kind: ProblemKind::ExtraPackage,
+            package: Some(id.to_spec().to_string()),
+            message: format!("package `{id}` is locked but no longer required by the manifests"),
+        });
+    }
+
+    if report.problems.len() == problems
+        && ops::resolve_to_string(ws, &resolve)? != ops::resolve_to_string(ws, locked)?
+    {
+        report.problems.push(Problem {
+            kind: ProblemKind::Outdated,
+            package: None,
+            message: "the dependencies of the locked packages don't match the manifests"
+                .to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Checks the checksums of registry packages against the downloaded `.crate`
+/// files.
+fn check_checksums(ws: &Workspace<'_>, locked: &Resolve, report: &mut Report) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::Shared)?;
+    let source_config = SourceConfigMap::new(gctx)?;
+
+    let mut ids: Vec<_> = locked
+        .iter()
+        .filter(|id| id.source_id().is_registry())
+        .collect();
+    ids.sort();
+    for id in ids {
+        let Some(checksum) = locked.checksums().get(&id).cloned().flatten() else {
+            report.problems.push(Problem {
+                kind: ProblemKind::MissingChecksum,
+                package: Some(id.to_spec().to_string()),
+                message: format!("package `{id}` has no checksum"),
+            });
+            continue;
+        };
+
+        let source_id = source_config
+            .load(id.source_id(), &HashSet::new())?
+            .replaced_source_id();
+        if !source_id.is_remote_registry() {
+            report.unverified.push(Unverified {
+                package: id.to_spec().to_string(),
+                reason: format!("`{source_id}` has no download cache"),
+            });
+            continue;
+        }
+        let path = cached_crate_path(gctx, id.with_source_id(source_id));
+        if !path.exists() {
+            report.unverified.push(Unverified {
+                package: id.to_spec().to_string(),
+                reason: "it has not been downloaded".to_string(),
+            });
+            continue;
+        }
+        let actual = Sha256::new().update_path(&path)?.finish_hex();
+        if actual != checksum {
+            report.problems.push(Problem {
+                kind: ProblemKind::ChecksumMismatch,
+                package: Some(id.to_spec().to_string()),
+                message: format!(
+                    "checksum for `{id}` is `{checksum}` but {} has checksum `{actual}`",
+                    path.display()
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_yanked(ws: &Workspace<'_>, locked: &Resolve, report: &mut Report) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+    let mut registry = PackageRegistry::new(gctx)?;
+
+    let mut ids: Vec<PackageId> = locked
+        .iter()
+        .filter(|id| id.source_id().is_registry())
+        .collect();
+    ids.sort();
+    // Locked packages are allowed to be yanked, so the registry reports them.
+    registry.add_to_yanked_whitelist(ids.iter().copied());
+    for id in ids {
+        let query = Dependency::parse(id.name(), None, id.source_id())?;
+        let possibilities = loop {
+            match registry.query_vec(&query, QueryKind::Exact) {
+                Poll::Ready(res) => break res?,
+                Poll::Pending => registry.block_until_ready()?,
+            }
+        };
+        let is_yanked = possibilities
+            .iter()
+            .any(|s| s.is_yanked() && s.as_summary().version() == id.version());
+        if is_yanked {
+            report.problems.push(Problem {
+                kind: ProblemKind::Yanked,
+                package: Some(id.to_spec().to_string()),
+                message: format!(
+                    "package `{id}` is yanked in registry `{}`",
+                    id.source_id().display_registry_name()
+                ),
+            });
+        }
+    }
+    Ok(())
+}
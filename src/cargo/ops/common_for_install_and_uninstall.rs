@@ -5,6 +5,7 @@ use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::task::Poll;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{bail, format_err, Context as _};
 use cargo_util::paths;
@@ -49,6 +50,9 @@ pub struct InstallTracker {
 struct CrateListingV2 {
     /// Map of every installed package.
     installs: BTreeMap<PackageId, InstallInfo>,
+    /// Record of packages uninstalled with `--purge`, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    history: Vec<HistoryEntry>,
     /// Forwards compatibility. Unknown keys from future versions of Cargo
     /// will be stored here and retained when the file is saved.
     #[serde(flatten)]
@@ -90,6 +94,22 @@ struct InstallInfo {
     other: BTreeMap<String, serde_json::Value>,
 }
 
+/// A past removal of a package and its cached sources.
+///
+/// This is only used for the v2 format.
+#[derive(Debug, Deserialize, Serialize)]
+struct HistoryEntry {
+    /// The package that was uninstalled.
+    package: PackageId,
+    /// Cached files and directories that were removed along with it.
+    removed: Vec<PathBuf>,
+    /// When it was removed, in seconds since the Unix epoch.
+    timestamp: u64,
+    /// Forwards compatibility.
+    #[serde(flatten)]
+    other: BTreeMap<String, serde_json::Value>,
+}
+
 /// Tracking information for the set of installed packages.
 #[derive(Default, Deserialize, Serialize)]
 pub struct CrateListingV1 {
@@ -322,6 +342,25 @@ impl InstallTracker {
         self.v2.remove(pkg_id, bins);
     }
 
+    /// Record that `pkg_id` was purged along with the cached files in
+    /// `removed`, then save the tracker.
+    pub fn record_purge_then_save(
+        &mut self,
+        pkg_id: PackageId,
+        removed: Vec<PathBuf>,
+    ) -> CargoResult<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.v2.history.push(HistoryEntry {
+            package: pkg_id,
+            removed,
+            timestamp,
+            other: BTreeMap::new(),
+        });
+        self.save()
+    }
+
     /// Remove a bin after it successfully had been removed in disk and then save the tracker at last.
     pub fn remove_bin_then_save(
         &mut self,
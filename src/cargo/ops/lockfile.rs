@@ -1,10 +1,15 @@
+use std::collections::{HashMap, HashSet};
 use std::io::prelude::*;
+use std::path::PathBuf;
 
-use crate::core::{resolver, Resolve, ResolveVersion, Workspace};
+use crate::core::{resolver, Package, PackageId, Resolve, ResolveVersion, SourceId, Workspace};
+use crate::sources::source::MaybePackage;
+use crate::sources::{PathSource, SourceConfigMap};
 use crate::util::errors::CargoResult;
 use crate::util::Filesystem;
 
 use anyhow::Context as _;
+use cargo_util::{paths, Sha256};
 
 #[tracing::instrument(skip_all)]
 pub fn load_pkg_lockfile(ws: &Workspace<'_>) -> CargoResult<Option<Resolve>> {
@@ -38,6 +43,10 @@ pub fn resolve_to_string(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<S
 /// Returns `true` if the lockfile changed
 #[tracing::instrument(skip_all)]
 pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoResult<bool> {
+    if resolve.version() >= ResolveVersion::V5 {
+        let content_checksums = content_checksums(ws, resolve)?;
+        resolve.set_content_checksums(content_checksums);
+    }
     let (orig, mut out, lock_root) = resolve_to_string_orig(ws, resolve);
 
     // If the lock file contents haven't changed so don't rewrite it. This is
@@ -54,6 +63,16 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoRes
         } else {
             "--frozen"
         };
+        if let Some(id) = orig
+            .as_deref()
+            .and_then(|orig| changed_content_checksum(ws, orig, resolve))
+        {
+            anyhow::bail!(
+                "the contents of `{id}` changed since the lock file {} was generated, \
+                 but {flag} was passed to prevent updating its checksum",
+                lock_root.as_path_unlocked().join("Cargo.lock").display(),
+            );
+        }
         anyhow::bail!(
             "the lock file {} needs to be updated but {} was passed to prevent this\n\
              If you want to try to generate the lock file without accessing the network, \
@@ -99,6 +118,111 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoRes
     Ok(true)
 }
 
+/// Computes the checksums of the contents of git packages and path packages
+/// outside the workspace, recorded in V5 lock files.
+///
+/// A checksum is the SHA256 hash of the relative path and contents of every
+/// file `cargo package` would include. The checksum recorded in the existing
+/// lock file is kept when none of those files were modified after it, so
+/// unchanged packages aren't hashed on every write.
+fn content_checksums(
+    ws: &Workspace<'_>,
+    resolve: &Resolve,
+) -> CargoResult<HashMap<PackageId, String>> {
+    let gctx = ws.gctx();
+    let lock_path = lock_root(ws).as_path_unlocked().join("Cargo.lock");
+    let previous = match paths::mtime(&lock_path) {
+        Ok(mtime) => load_pkg_lockfile(ws)
+            .ok()
+            .flatten()
+            .map(|previous| (mtime, previous.content_checksums().clone())),
+        Err(_) => None,
+    };
+    let members: HashSet<_> = ws.members().map(|pkg| pkg.package_id()).collect();
+    let mut ids_by_source: HashMap<SourceId, Vec<PackageId>> = HashMap::new();
+    for id in resolve.iter() {
+        let source_id = id.source_id();
+        if (source_id.is_git() || source_id.is_path()) && !members.contains(&id) {
+            ids_by_source.entry(source_id).or_default().push(id);
+        }
+    }
+
+    let source_config = SourceConfigMap::new(gctx)?;
+    let mut checksums = HashMap::new();
+    for (source_id, ids) in ids_by_source {
+        let mut source = source_config.load(source_id, &HashSet::new())?;
+        source.block_until_ready()?;
+        for id in ids {
+            let MaybePackage::Ready(pkg) = source.download(id)? else {
+                anyhow::bail!("failed to load `{id}` to compute its checksum");
+            };
+            let checksum = (|| {
+                let source = PathSource::new(pkg.root(), id.source_id(), gctx);
+                let mut files = source.list_files(&pkg)?;
+                if let Some((lock_mtime, previous)) = &previous {
+                    if let Some(checksum) = previous.get(&id) {
+                        let mut unchanged = true;
+                        for file in &files {
+                            if paths::mtime(file)? >= *lock_mtime {
+                                unchanged = false;
+                                break;
+                            }
+                        }
+                        if unchanged {
+                            return Ok(checksum.clone());
+                        }
+                    }
+                }
+                files.sort();
+                content_checksum(&pkg, &files)
+            })()
+            .with_context(|| format!("failed to compute the checksum of `{id}`"))?;
+            checksums.insert(id, checksum);
+        }
+    }
+    Ok(checksums)
+}
+
+fn content_checksum(pkg: &Package, files: &[PathBuf]) -> CargoResult<String> {
+    let mut hasher = Sha256::new();
+    for file in files {
+        // Separators are normalized so the checksum is the same on every
+        // platform.
+        let relative = file.strip_prefix(pkg.root())?;
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        hasher.update(relative.as_bytes());
+        hasher.update(&[0]);
+        let contents = paths::read_bytes(file)?;
+        hasher.update(&(contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    Ok(hasher.finish_hex())
+}
+
+/// Finds a package whose content checksum differs from the one in the
+/// original lock file.
+fn changed_content_checksum(
+    ws: &Workspace<'_>,
+    orig: &str,
+    resolve: &Resolve,
+) -> Option<PackageId> {
+    let previous: resolver::EncodableResolve = toml::from_str(orig).ok()?;
+    let previous = previous.into_resolve(orig, ws).ok()?;
+    let mut changed: Vec<_> = resolve
+        .content_checksums()
+        .iter()
+        .filter(|(id, checksum)| {
+            previous
+                .content_checksums()
+                .get(id)
+                .map_or(false, |previous| previous != *checksum)
+        })
+        .map(|(id, _)| *id)
+        .collect();
+    changed.sort();
+    changed.into_iter().next()
+}
+
 fn resolve_to_string_orig(
     ws: &Workspace<'_>,
     resolve: &Resolve,
@@ -246,7 +370,7 @@ fn emit_package(dep: &toml::Table, out: &mut String) {
     }
 }
 
-fn lock_root(ws: &Workspace<'_>) -> Filesystem {
+pub(super) fn lock_root(ws: &Workspace<'_>) -> Filesystem {
     if ws.root_maybe().is_embedded() {
         ws.target_dir()
     } else {
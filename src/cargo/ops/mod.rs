@@ -2,7 +2,8 @@ use crate::sources::CRATES_IO_DOMAIN;
 
 pub use self::cargo_clean::{clean, CleanContext, CleanOptions};
 pub use self::cargo_compile::{
-    compile, compile_with_exec, compile_ws, create_bcx, print, resolve_all_features, CompileOptions,
+    compile, compile_manifest_list, compile_with_exec, compile_ws, create_bcx, print,
+    read_manifest_list, resolve_all_features, CompileOptions,
 };
 pub use self::cargo_compile::{CompileFilter, FilterRule, LibRule, Packages};
 pub use self::cargo_doc::{doc, DocOptions, OutputFormat};
@@ -10,18 +11,25 @@ pub use self::cargo_fetch::{fetch, FetchOptions};
 pub use self::cargo_generate_lockfile::generate_lockfile;
 pub use self::cargo_generate_lockfile::print_lockfile_changes;
 pub use self::cargo_generate_lockfile::update_lockfile;
+pub use self::cargo_generate_lockfile::upgrade_manifests;
+pub use self::cargo_generate_lockfile::write_manifest_upgrades;
+pub use self::cargo_generate_lockfile::ChangelogFormat;
 pub use self::cargo_generate_lockfile::UpdateOptions;
 pub use self::cargo_install::{install, install_list};
+pub(crate) use self::cargo_new::add_workspace_member;
 pub use self::cargo_new::{init, new, NewOptions, NewProjectKind, VersionControl};
-pub use self::cargo_output_metadata::{output_metadata, ExportInfo, OutputMetadataOptions};
+pub use self::cargo_output_metadata::{
+    output_metadata, output_metadata_ndjson, ExportInfo, OutputMetadataOptions,
+};
 pub use self::cargo_package::{check_yanked, package, package_one, PackageOpts};
 pub use self::cargo_pkgid::pkgid;
 pub use self::cargo_read_manifest::{read_package, read_packages};
 pub use self::cargo_run::run;
-pub use self::cargo_test::{run_benches, run_tests, TestOptions};
-pub use self::cargo_uninstall::uninstall;
+pub use self::cargo_test::{run_benches, run_tests, TestOptions, TestRunners};
+pub use self::cargo_uninstall::{uninstall, uninstall_all};
 pub use self::fix::{fix, fix_exec_rustc, fix_get_proxy_lock_addr, FixOptions};
 pub use self::lockfile::{load_pkg_lockfile, resolve_to_string, write_pkg_lockfile};
+pub use self::package_filter::PackageFilter;
 pub use self::registry::modify_owners;
 pub use self::registry::publish;
 pub use self::registry::registry_login;
@@ -33,35 +41,49 @@ pub use self::registry::PublishOpts;
 pub use self::registry::RegistryCredentialConfig;
 pub use self::registry::RegistryOrIndex;
 pub use self::resolve::{
-    add_overrides, get_resolved_packages, resolve_with_previous, resolve_ws, resolve_ws_with_opts,
-    WorkspaceResolve,
+    add_overrides, get_resolved_packages, resolve_with_previous, resolve_ws,
+    resolve_ws_with_dry_run, resolve_ws_with_opts, WorkspaceResolve,
 };
 pub use self::vendor::{vendor, VendorOptions};
+pub use self::vet::vet_check;
 
 pub mod cargo_add;
 mod cargo_clean;
 pub(crate) mod cargo_compile;
 pub mod cargo_config;
+pub mod cargo_daemon;
+pub mod cargo_dedupe;
 mod cargo_doc;
+pub mod cargo_explain;
+pub mod cargo_features;
 mod cargo_fetch;
 mod cargo_generate_lockfile;
 mod cargo_install;
+pub mod cargo_links;
 mod cargo_new;
 mod cargo_output_metadata;
+pub mod cargo_owner_of;
 mod cargo_package;
 mod cargo_pkgid;
+pub mod cargo_query;
 mod cargo_read_manifest;
 pub mod cargo_remove;
+pub mod cargo_report;
 mod cargo_run;
+pub mod cargo_server;
 mod cargo_test;
 mod cargo_uninstall;
+pub mod cargo_verify_lockfile;
 mod common_for_install_and_uninstall;
 mod fix;
 pub(crate) mod lockfile;
+mod package_filter;
 pub(crate) mod registry;
 pub(crate) mod resolve;
+mod resolve_cache;
 pub mod tree;
 mod vendor;
+mod vet;
 
 /// Returns true if the dependency is either git or path, false otherwise
 /// Error if a git/path dep is transitive, but has no version (registry source).
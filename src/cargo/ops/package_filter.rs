@@ -0,0 +1,129 @@
+//! Selection of the packages shown by `cargo tree` and `cargo metadata` by
+//! their source or license, with `--prune-source`, `--only-source`, and
+//! `--prune-license`.
+
+use anyhow::{bail, Context as _};
+
+use crate::core::{Package, SourceId, SourceKind};
+use crate::util::CargoResult;
+
+/// The kinds of sources which can be given instead of a source URL.
+const SOURCE_KINDS: [&str; 5] = ["path", "git", "registry", "local-registry", "directory"];
+
+/// Which packages to prune from a dependency graph, along with their
+/// dependencies which aren't reachable otherwise.
+#[derive(Default)]
+pub struct PackageFilter {
+    prune_sources: Vec<String>,
+    only_sources: Vec<String>,
+    prune_licenses: Vec<glob::Pattern>,
+}
+
+impl PackageFilter {
+    /// Creates a filter from the values of the command-line flags.
+    ///
+    /// Each source is either a kind of source, like `git`, or the URL of a
+    /// source, like `registry+https://github.com/rust-lang/crates.io-index`.
+    /// Each license is a glob pattern, like `GPL-*`.
+    pub fn new(
+        prune_sources: Vec<String>,
+        only_sources: Vec<String>,
+        prune_licenses: Vec<String>,
+    ) -> CargoResult<PackageFilter> {
+        for source in prune_sources.iter().chain(&only_sources) {
+            if !SOURCE_KINDS.contains(&source.as_str()) && !source.contains('+') {
+                bail!(
+                    "invalid source `{source}`, expected one of {} \
+                     or a source URL like `registry+https://github.com/rust-lang/crates.io-index`",
+                    SOURCE_KINDS.map(|kind| format!("`{kind}`")).join(", ")
+                );
+            }
+        }
+        let prune_licenses = prune_licenses
+            .iter()
+            .map(|pattern| {
+                glob::Pattern::new(pattern)
+                    .with_context(|| format!("invalid license pattern `{pattern}`"))
+            })
+            .collect::<CargoResult<_>>()?;
+        Ok(PackageFilter {
+            prune_sources,
+            only_sources,
+            prune_licenses,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prune_sources.is_empty()
+            && self.only_sources.is_empty()
+            && self.prune_licenses.is_empty()
+    }
+
+    /// Whether `pkg` is pruned.
+    ///
+    /// A package is pruned if its source matches `--prune-source`, doesn't
+    /// match `--only-source`, or if any of the licenses of its `license`
+    /// expression matches `--prune-license`.
+    pub fn prunes(&self, pkg: &Package) -> bool {
+        let source_id = pkg.package_id().source_id();
+        if self
+            .prune_sources
+            .iter()
+            .any(|source| source_matches(source_id, source))
+        {
+            return true;
+        }
+        if !self.only_sources.is_empty()
+            && !self
+                .only_sources
+                .iter()
+                .any(|source| source_matches(source_id, source))
+        {
+            return true;
+        }
+        let Some(license) = &pkg.manifest().metadata().license else {
+            return false;
+        };
+        license_ids(license).any(|id| {
+            self.prune_licenses
+                .iter()
+                .any(|pattern| pattern.matches(id))
+        })
+    }
+}
+
+fn source_matches(source_id: SourceId, source: &str) -> bool {
+    match (source, source_id.kind()) {
+        ("path", SourceKind::Path)
+        | ("git", SourceKind::Git(_))
+        | ("registry", SourceKind::Registry | SourceKind::SparseRegistry)
+        | ("local-registry", SourceKind::LocalRegistry)
+        | ("directory", SourceKind::Directory) => true,
+        (kind, _) if SOURCE_KINDS.contains(&kind) => false,
+        (url, _) => {
+            // Git sources also match regardless of their reference.
+            let source_url = source_id.as_url().to_string();
+            let source_url = source_url.trim_end_matches('/');
+            let url = url.trim_end_matches('/');
+            source_url == url
+                || source_url
+                    .strip_prefix(url)
+                    .is_some_and(|rest| rest.starts_with(['?', '#']))
+        }
+    }
+}
+
+/// The license identifiers of an SPDX expression, without the operators and
+/// the exceptions.
+fn license_ids(expression: &str) -> impl Iterator<Item = &str> {
+    let mut after_with = false;
+    expression
+        .split(|c: char| c.is_whitespace() || c == '/' || c == '(' || c == ')')
+        .filter(|token| !token.is_empty())
+        .filter(move |token| {
+            let skip = after_with || matches!(*token, "AND" | "OR" | "WITH");
+            after_with = *token == "WITH";
+            !skip
+        })
+        .map(|id| id.trim_end_matches('+'))
+}
@@ -25,7 +25,7 @@ use crate::util::auth;
 use crate::util::cache_lock::CacheLockMode;
 use crate::util::context::{GlobalContext, PathAndArgs};
 use crate::util::errors::CargoResult;
-use crate::util::network::http::http_handle;
+use crate::util::network::http::http_handle_for_registry;
 
 pub use self::login::registry_login;
 pub use self::logout::registry_logout;
@@ -164,7 +164,7 @@ fn registry(
     } else {
         None
     };
-    let handle = http_handle(gctx)?;
+    let handle = http_handle_for_registry(gctx, source_ids.original)?;
     Ok((
         Registry::new_handle(api_host, token, handle, cfg.auth_required),
         source_ids,
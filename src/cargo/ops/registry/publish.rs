@@ -59,7 +59,7 @@ pub struct PublishOpts<'gctx> {
 }
 
 pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
-    let specs = opts.to_publish.to_package_id_specs(ws)?;
+    let specs = opts.to_publish.to_member_package_id_specs(ws)?;
     if specs.len() > 1 {
         bail!("the `-p` argument must be specified to select a single package to publish")
     }
@@ -80,6 +80,12 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
     assert_eq!(pkgs.len(), 1);
 
     let (pkg, cli_features) = pkgs.pop().unwrap();
+    if let Some(url) = ws.git_member_url(&pkg) {
+        bail!(
+            "`{}` can't be published, it's a workspace member checked out from `{url}`",
+            pkg.name()
+        );
+    }
 
     let mut publish_registry = match opts.reg_or_index.as_ref() {
         Some(RegistryOrIndex::Registry(registry)) => Some(registry.clone()),
@@ -150,6 +156,7 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
             list: false,
             check_metadata: true,
             allow_dirty: opts.allow_dirty,
+            exclude_lockfile: false,
             to_package: Packages::Default,
             targets: opts.targets.clone(),
             jobs: opts.jobs.clone(),
@@ -376,6 +383,7 @@ fn transmit(
                     artifact.target().map(|target| target.as_str().to_owned())
                 }),
                 lib: dep.artifact().map_or(false, |artifact| artifact.is_lib()),
+                features_profile: dep.features_profile().map(|s| s.to_string()),
             })
         })
         .collect::<CargoResult<Vec<NewCrateDependency>>>()?;
@@ -467,6 +475,17 @@ fn transmit(
                 badges: badges.clone(),
                 links: links.clone(),
                 rust_version,
+                feature_profiles: pkg
+                    .summary()
+                    .feature_profiles()
+                    .iter()
+                    .map(|(profile, features)| {
+                        (
+                            profile.to_string(),
+                            features.iter().map(|f| f.to_string()).collect(),
+                        )
+                    })
+                    .collect(),
             },
             tarball,
         )
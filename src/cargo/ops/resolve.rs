@@ -55,13 +55,14 @@
 //! [source implementations]: crate::sources
 //! [`Downloads`]: crate::core::package::Downloads
 
-use crate::core::compiler::{CompileKind, RustcTargetData};
+use crate::core::compiler::{CompileKind, CompileTarget, RustcTargetData};
 use crate::core::registry::{LockedPatchDependency, PackageRegistry};
 use crate::core::resolver::features::{
     CliFeatures, FeatureOpts, FeatureResolver, ForceAllTargets, RequestedFeatures, ResolvedFeatures,
 };
 use crate::core::resolver::{
-    self, HasDevUnits, Resolve, ResolveOpts, ResolveVersion, VersionOrdering, VersionPreferences,
+    self, HasDevUnits, PlatformFilter, Resolve, ResolveOpts, ResolveVersion, VersionOrdering,
+    VersionPreferences,
 };
 use crate::core::summary::Summary;
 use crate::core::Dependency;
@@ -70,15 +71,22 @@ use crate::core::PackageId;
 use crate::core::PackageIdSpec;
 use crate::core::PackageIdSpecQuery;
 use crate::core::PackageSet;
+use crate::core::Registry as _;
 use crate::core::SourceId;
 use crate::core::Workspace;
 use crate::ops;
+use crate::ops::resolve_cache;
+use crate::sources::source::QueryKind;
+use crate::sources::IndexSummary;
 use crate::sources::PathSource;
 use crate::util::cache_lock::CacheLockMode;
+use crate::util::context::{ResolutionMode, YankedPolicy};
 use crate::util::errors::CargoResult;
 use crate::util::CanonicalUrl;
 use anyhow::Context as _;
-use std::collections::{HashMap, HashSet};
+use semver::{Comparator, Op, Version, VersionReq};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::task::Poll;
 use tracing::{debug, trace};
 
 /// Filter for keep using Package ID from previous lockfile.
@@ -116,8 +124,16 @@ version. This may also occur with an optional dependency that is not enabled.";
 /// This is a simple interface used by commands like `clean`, `fetch`, and
 /// `package`, which don't specify any options or features.
 pub fn resolve_ws<'a>(ws: &Workspace<'a>) -> CargoResult<(PackageSet<'a>, Resolve)> {
+    resolve_ws_with_dry_run(ws, false)
+}
+
+/// Like [`resolve_ws`], but with `dry_run`, the lock file is not written.
+pub fn resolve_ws_with_dry_run<'a>(
+    ws: &Workspace<'a>,
+    dry_run: bool,
+) -> CargoResult<(PackageSet<'a>, Resolve)> {
     let mut registry = PackageRegistry::new(ws.gctx())?;
-    let resolve = resolve_with_registry(ws, &mut registry)?;
+    let resolve = resolve_with_registry(ws, &mut registry, dry_run)?;
     let packages = get_resolved_packages(&resolve, registry)?;
     Ok((packages, resolve))
 }
@@ -159,10 +175,26 @@ pub fn resolve_ws_with_opts<'gctx>(
         (resolve, resolved_with_overrides)
     } else if ws.require_optional_deps() {
         // First, resolve the root_package's *listed* dependencies, as well as
-        // downloading and updating all remotes and such.
-        let resolve = resolve_with_registry(ws, &mut registry)?;
-        // No need to add patches again, `resolve_with_registry` has done it.
-        let add_patches = false;
+        // downloading and updating all remotes and such. With
+        // `-Zresolve-cache`, this is skipped if nothing changed since the
+        // last time.
+        let cached = if resolve_cache::is_enabled(ws) {
+            resolve_cache::load(ws)?
+        } else {
+            None
+        };
+        // No need to add patches again if `resolve_with_registry` has done it.
+        let add_patches = cached.is_some();
+        let resolve = match cached {
+            Some(resolve) => resolve,
+            None => {
+                let resolve = resolve_with_registry(ws, &mut registry, false)?;
+                if resolve_cache::is_enabled(ws) {
+                    resolve_cache::save(ws, &resolve);
+                }
+                resolve
+            }
+        };
 
         // Second, resolve with precisely what we're doing. Filter out
         // transitive dependencies if necessary, specify features, handle
@@ -219,6 +251,10 @@ pub fn resolve_ws_with_opts<'gctx>(
         (resolve, resolved_with_overrides)
     };
 
+    if force_all_targets == ForceAllTargets::No {
+        check_filter_platforms(target_data, requested_targets, &resolved_with_overrides)?;
+    }
+
     let pkg_set = get_resolved_packages(&resolved_with_overrides, registry)?;
 
     let member_ids = ws
@@ -269,6 +305,7 @@ pub fn resolve_ws_with_opts<'gctx>(
 fn resolve_with_registry<'gctx>(
     ws: &Workspace<'gctx>,
     registry: &mut PackageRegistry<'gctx>,
+    dry_run: bool,
 ) -> CargoResult<Resolve> {
     let prev = ops::load_pkg_lockfile(ws)?;
     let mut resolve = resolve_with_previous(
@@ -281,9 +318,16 @@ fn resolve_with_registry<'gctx>(
         &[],
         true,
     )?;
+    if let Some(prev) = &prev {
+        resolve = apply_yanked_policy(ws, registry, prev, resolve)?;
+    }
 
     let print = if !ws.is_ephemeral() && ws.require_optional_deps() {
-        ops::write_pkg_lockfile(ws, &mut resolve)?
+        if dry_run {
+            true
+        } else {
+            ops::write_pkg_lockfile(ws, &mut resolve)?
+        }
     } else {
         // This mostly represents
         // - `cargo install --locked` and the only change is the package is no longer local but
@@ -294,9 +338,171 @@ fn resolve_with_registry<'gctx>(
     if print {
         ops::print_lockfile_changes(ws, prev.as_ref(), &resolve, registry)?;
     }
+    if ws.gctx().cli_unstable().cargo_lints {
+        ws.emit_resolve_lints(&resolve, registry)?;
+    }
     Ok(resolve)
 }
 
+/// Applies the `resolver.yanked` policy to the versions kept from the lock
+/// file which have been yanked since it was generated.
+///
+/// With `auto-bump-compatible`, each of them is replaced with the nearest
+/// SemVer compatible version which isn't yanked, preferring newer versions.
+fn apply_yanked_policy<'gctx>(
+    ws: &Workspace<'gctx>,
+    registry: &mut PackageRegistry<'gctx>,
+    previous: &Resolve,
+    resolve: Resolve,
+) -> CargoResult<Resolve> {
+    let policy = ws.yanked_policy();
+    if policy == YankedPolicy::Allow {
+        return Ok(resolve);
+    }
+    let gctx = ws.gctx();
+
+    let mut yanked = Vec::new();
+    {
+        let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+        let mut locked: Vec<_> = resolve
+            .iter()
+            .filter(|id| id.source_id().is_registry() && previous.contains(id))
+            .collect();
+        locked.sort();
+        for id in locked {
+            let query = Dependency::parse(id.name(), None, id.source_id())?;
+            let possibilities = loop {
+                match registry.query_vec(&query, QueryKind::Exact) {
+                    Poll::Ready(res) => break res?,
+                    Poll::Pending => registry.block_until_ready()?,
+                }
+            };
+            let is_yanked = possibilities
+                .iter()
+                .any(|s| s.is_yanked() && s.as_summary().version() == id.version());
+            if is_yanked {
+                yanked.push((id, nearest_unyanked_version(id, &possibilities)));
+            }
+        }
+    }
+    if yanked.is_empty() {
+        return Ok(resolve);
+    }
+
+    let describe = |id: PackageId| {
+        format!(
+            "package `{id}` in Cargo.lock is yanked in registry `{}`",
+            id.source_id().display_registry_name()
+        )
+    };
+    match policy {
+        YankedPolicy::Allow => Ok(resolve),
+        YankedPolicy::Warn => {
+            for (id, _) in &yanked {
+                gctx.shell().warn(format!(
+                    "{}, consider running `cargo update {}@{}`",
+                    describe(*id),
+                    id.name(),
+                    id.version()
+                ))?;
+            }
+            Ok(resolve)
+        }
+        YankedPolicy::Error => {
+            let list = yanked
+                .iter()
+                .map(|(id, _)| format!("  {}", describe(*id)))
+                .collect::<Vec<_>>()
+                .join("\n");
+            anyhow::bail!(
+                "Cargo.lock uses yanked versions, which `resolver.yanked` is set to reject:\n\
+                 {list}\n\
+                 help: run `cargo update` for those packages, or set `resolver.yanked` to \
+                 `auto-bump-compatible` to replace them automatically"
+            )
+        }
+        YankedPolicy::AutoBumpCompatible => {
+            let mut avoid = HashSet::new();
+            let mut sources = Vec::new();
+            for (id, version) in &yanked {
+                match version {
+                    Some(version) => {
+                        avoid.insert(*id);
+                        sources.push(id.source_id().with_precise_registry_version(
+                            id.name(),
+                            id.version().clone(),
+                            &version.to_string(),
+                        )?);
+                    }
+                    None => gctx.shell().warn(format!(
+                        "{}, but no compatible version is available to replace it",
+                        describe(*id)
+                    ))?,
+                }
+            }
+            if avoid.is_empty() {
+                return Ok(resolve);
+            }
+
+            let mut bump_registry = PackageRegistry::new(gctx)?;
+            bump_registry.add_sources(sources)?;
+            let keep = |id: &PackageId| !avoid.contains(id);
+            let bumped = match resolve_with_previous(
+                &mut bump_registry,
+                ws,
+                &CliFeatures::new_all(true),
+                HasDevUnits::Yes,
+                Some(previous),
+                Some(&keep),
+                &[],
+                true,
+            ) {
+                Ok(bumped) => bumped,
+                Err(e) => {
+                    gctx.shell().warn(format!(
+                        "failed to replace the yanked versions in Cargo.lock, keeping them: {e:#}"
+                    ))?;
+                    return Ok(resolve);
+                }
+            };
+            for (id, version) in &yanked {
+                let Some(version) = version else {
+                    continue;
+                };
+                gctx.shell().note(format!(
+                    "updating `{id}` to v{version}, as it is yanked in registry `{}`",
+                    id.source_id().display_registry_name()
+                ))?;
+            }
+            Ok(bumped)
+        }
+    }
+}
+
+/// Finds the SemVer compatible version of `id` which isn't yanked and is the
+/// nearest to it, preferring newer versions.
+fn nearest_unyanked_version(id: PackageId, possibilities: &[IndexSummary]) -> Option<Version> {
+    let current = id.version();
+    let compatible = VersionReq {
+        comparators: vec![Comparator {
+            op: Op::Caret,
+            major: current.major,
+            minor: Some(current.minor),
+            patch: Some(current.patch),
+            pre: current.pre.clone(),
+        }],
+    };
+    let candidates = possibilities
+        .iter()
+        .filter(|s| !s.is_yanked())
+        .map(|s| s.as_summary().version())
+        .filter(|v| *v != current && compatible.matches(v));
+    let newer = candidates.clone().filter(|v| *v > current).min();
+    newer
+        .or_else(|| candidates.filter(|v| *v < current).max())
+        .cloned()
+}
+
 /// Resolves all dependencies for a package using an optional previous instance
 /// of resolve to guide the resolution process.
 ///
@@ -346,10 +552,18 @@ pub fn resolve_with_previous<'gctx>(
     // While registering patches, we will record preferences for particular versions
     // of various packages.
     let mut version_prefs = VersionPreferences::default();
-    if ws.gctx().cli_unstable().minimal_versions {
-        version_prefs.version_ordering(VersionOrdering::MinimumVersionsFirst)
+    match ws.resolution_mode() {
+        ResolutionMode::Maximum => {}
+        ResolutionMode::Minimal => {
+            version_prefs.version_ordering(VersionOrdering::MinimumVersionsFirst)
+        }
+        ResolutionMode::DirectMinimal => {
+            version_prefs.direct_version_ordering(VersionOrdering::MinimumVersionsFirst)
+        }
     }
-    if ws.resolve_honors_rust_version() {
+    if let Some(rust_version) = ws.max_rust_version() {
+        version_prefs.max_rust_version(Some(rust_version.clone()));
+    } else if ws.resolve_honors_rust_version() {
         let rust_version = if let Some(ver) = ws.rust_version() {
             ver.clone().into_partial()
         } else {
@@ -359,6 +573,10 @@ pub fn resolve_with_previous<'gctx>(
         };
         version_prefs.max_rust_version(Some(rust_version));
     }
+    version_prefs.max_publish_time(ws.max_publish_time());
+    for (name, req) in ws.dependency_pins() {
+        version_prefs.pin(*name, req.clone());
+    }
 
     let avoid_patch_ids = if register_patches {
         register_patch_entries(registry, ws, previous, &mut version_prefs, keep_previous)?
@@ -366,8 +584,11 @@ pub fn resolve_with_previous<'gctx>(
         HashSet::new()
     };
 
-    // Refine `keep` with patches that should avoid locking.
-    let keep = |p: &PackageId| keep_previous(p) && !avoid_patch_ids.contains(p);
+    // Refine `keep` with patches that should avoid locking, and with locked
+    // versions that no longer match `workspace.dependency-pins`.
+    let keep = |p: &PackageId| {
+        keep_previous(p) && !avoid_patch_ids.contains(p) && ws.matches_dependency_pins(*p)
+    };
 
     let dev_deps = ws.require_optional_deps() || has_dev_units == HasDevUnits::Yes;
 
@@ -407,14 +628,35 @@ pub fn resolve_with_previous<'gctx>(
 
     let replace = lock_replacements(ws, previous, &keep);
 
+    // Platforms requested for this resolve take precedence over the ones the
+    // lock file was restricted to.
+    let filter_platforms = if ws.filter_platforms().is_empty() {
+        let filter_platforms = previous.map(Resolve::filter_platforms).unwrap_or_default();
+        if !filter_platforms.is_empty() && !ws.gctx().cli_unstable().unstable_options {
+            ws.gctx().shell().warn(
+                "`filter-platforms` in the `[metadata]` table of `Cargo.lock` \
+                 requires `-Zunstable-options`, ignoring it",
+            )?;
+            Vec::new()
+        } else {
+            filter_platforms
+        }
+    } else {
+        ws.filter_platforms().to_vec()
+    };
+    let platform_filter = platform_filter(ws, &filter_platforms)?;
+
     let mut resolved = resolver::resolve(
         &summaries,
         &replace,
         registry,
         &version_prefs,
+        &platform_filter,
         ResolveVersion::with_rust_version(ws.rust_version()),
         Some(ws.gctx()),
     )?;
+    check_bans(ws, &resolved)?;
+    ops::vet_check(ws, previous, &resolved)?;
 
     let patches = registry.patches().values().flat_map(|v| v.iter());
     resolved.register_used_patches(patches);
@@ -423,15 +665,145 @@ pub fn resolve_with_previous<'gctx>(
         emit_warnings_of_unused_patches(ws, &resolved, registry)?;
     }
 
+    if ws.max_publish_time().is_some() {
+        let without_publish_time: Vec<_> = version_prefs
+            .packages_without_publish_time()
+            .into_iter()
+            .filter(|name| resolved.iter().any(|id| id.name() == *name))
+            .map(|name| format!("`{name}`"))
+            .collect();
+        if !without_publish_time.is_empty() {
+            ws.gctx().shell().warn(format!(
+                "`--before` has no effect on {}, as their registry doesn't record \
+                 when versions were published",
+                without_publish_time.join(", ")
+            ))?;
+        }
+    }
+
+    if register_patches {
+        for name in ws.dependency_pins().keys() {
+            if !resolved
+                .iter()
+                .any(|id| id.name() == *name && id.source_id().is_registry())
+            {
+                ws.gctx().shell().warn(format!(
+                    "dependency pin `{name}` in `workspace.dependency-pins` \
+                     does not match any package in the dependency graph"
+                ))?;
+            }
+        }
+    }
+
     if let Some(previous) = previous {
         resolved.merge_from(previous)?;
     }
+    resolved.set_filter_platforms(&filter_platforms);
     let gctx = ws.gctx();
     let mut deferred = gctx.deferred_global_last_use()?;
     deferred.save_no_error(gctx);
     Ok(resolved)
 }
 
+/// Evaluates the `cfg` values of `platforms` to tell which dependencies apply
+/// to them.
+fn platform_filter(ws: &Workspace<'_>, platforms: &[String]) -> CargoResult<PlatformFilter> {
+    if platforms.is_empty() {
+        return Ok(PlatformFilter::default());
+    }
+    let kinds = platforms
+        .iter()
+        .map(|platform| Ok(CompileKind::Target(CompileTarget::new(platform)?)))
+        .collect::<CargoResult<Vec<_>>>()?;
+    let target_data = RustcTargetData::new(ws, &kinds)?;
+    let platforms = kinds
+        .iter()
+        .map(|kind| {
+            (
+                target_data.short_name(kind).to_owned(),
+                target_data.cfg(*kind).to_vec(),
+            )
+        })
+        .collect();
+    Ok(PlatformFilter::new(platforms))
+}
+
+/// Checks that the requested targets are part of the platforms the resolve is
+/// restricted to, as the dependencies of the other platforms are missing.
+fn check_filter_platforms(
+    target_data: &RustcTargetData<'_>,
+    requested_targets: &[CompileKind],
+    resolve: &Resolve,
+) -> CargoResult<()> {
+    let filter_platforms = resolve.filter_platforms();
+    if filter_platforms.is_empty() {
+        return Ok(());
+    }
+    for kind in requested_targets {
+        let target = target_data.short_name(kind);
+        if !filter_platforms.iter().any(|platform| platform == target) {
+            anyhow::bail!(
+                "`Cargo.lock` only locks the dependencies used on {}, \
+                 but the build is for `{target}`\n\
+                 help: run `cargo generate-lockfile -Zunstable-options --filter-platform {target}` \
+                 with the other platforms, or without `--filter-platform` to lock every platform",
+                filter_platforms
+                    .iter()
+                    .map(|platform| format!("`{platform}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Checks that no package of the dependency graph matches `workspace.bans`.
+///
+/// The error names the path of dependencies from a workspace member which
+/// pulled the banned package in.
+fn check_bans(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<()> {
+    let bans = ws.bans();
+    if bans.is_empty() {
+        return Ok(());
+    }
+    let members: HashSet<_> = ws.members().map(|m| m.package_id()).collect();
+
+    let mut reached_from = HashMap::new();
+    let mut queue: VecDeque<_> = ws.members().map(|m| m.package_id()).collect();
+    while let Some(pkg) = queue.pop_front() {
+        for (dep, _) in resolve.deps(pkg) {
+            if members.contains(&dep) || reached_from.contains_key(&dep) {
+                continue;
+            }
+            reached_from.insert(dep, pkg);
+            if let Some(req) = bans.get(&dep.name()) {
+                if req.matches(dep.version()) {
+                    let mut path = vec![dep];
+                    let mut id = dep;
+                    while let Some(&parent) = reached_from.get(&id) {
+                        path.push(parent);
+                        id = parent;
+                    }
+                    let path = path
+                        .iter()
+                        .rev()
+                        .map(|id| format!("`{id}`"))
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+                    anyhow::bail!(
+                        "package `{dep}` is banned by `{} = \"{req}\"` in `workspace.bans`, \
+                         but is part of the dependency graph:\n  {path}",
+                        dep.name(),
+                    );
+                }
+            }
+            queue.push_back(dep);
+        }
+    }
+    Ok(())
+}
+
 /// Read the `paths` configuration variable to discover all path overrides that
 /// have been configured.
 #[tracing::instrument(skip_all)]
@@ -0,0 +1,193 @@
+//! Reusing the resolve of the whole workspace across invocations, see
+//! `-Zresolve-cache`.
+//!
+//! After resolving the workspace, Cargo records a fingerprint of everything
+//! the resolve depends on in `.resolve_cache.json` of the target directory:
+//!
+//! - the Cargo version,
+//! - the contents of `Cargo.lock`,
+//! - the contents of the manifests of every path package in the resolve,
+//!   the workspace members and the `[patch]` and `[replace]` entries,
+//! - the `patch`, `registries`, `registry`, `resolver` and `source` config
+//!   tables, and the `-Z` flags,
+//! - the resolve options of the [`Workspace`].
+//!
+//! The next invocation recomputes the fingerprint from the recorded list of
+//! manifests. If it matches, the lock file is known to be up to date and is
+//! used as the resolve of the workspace instead of running the resolver.
+//! Resolving the packages and features selected on the command line still
+//! happens, with every version locked.
+
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+use cargo_util::paths;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::core::{Resolve, Workspace};
+use crate::ops;
+use crate::util::context::ConfigValue;
+use crate::util::{CargoResult, StableHasher};
+
+/// Config tables which influence the resolve.
+const CONFIG_TABLES: &[&str] = &["patch", "registries", "registry", "resolver", "source"];
+
+#[derive(Serialize, Deserialize)]
+struct CacheData {
+    fingerprint: u64,
+    manifests: Vec<PathBuf>,
+}
+
+/// Whether the resolve of `ws` may be cached.
+///
+/// The resolve lints of `-Zcargo-lints` need the full resolve, so the cache
+/// is not used with them.
+pub fn is_enabled(ws: &Workspace<'_>) -> bool {
+    let unstable = ws.gctx().cli_unstable();
+    unstable.resolve_cache
+        && !unstable.cargo_lints
+        && !ws.is_ephemeral()
+        && ws.require_optional_deps()
+}
+
+/// Returns the resolve of the workspace from the lock file if the cache is
+/// fresh.
+pub fn load(ws: &Workspace<'_>) -> CargoResult<Option<Resolve>> {
+    let path = cache_path(ws);
+    let Ok(contents) = paths::read(&path) else {
+        return Ok(None);
+    };
+    let data: CacheData = match serde_json::from_str(&contents) {
+        Ok(data) => data,
+        Err(e) => {
+            debug!("failed to parse resolve cache: {e}");
+            return Ok(None);
+        }
+    };
+    match fingerprint(ws, &data.manifests) {
+        Ok(fingerprint) if fingerprint == data.fingerprint => {}
+        Ok(_) => {
+            debug!("resolve cache is stale");
+            return Ok(None);
+        }
+        Err(e) => {
+            debug!("failed to compute resolve cache fingerprint: {e:?}");
+            return Ok(None);
+        }
+    }
+    debug!("reusing cached resolve");
+    ops::load_pkg_lockfile(ws)
+}
+
+/// Records the fingerprint of `resolve`, which must have been written to the
+/// lock file.
+///
+/// Failing to do so only means the next invocation will resolve again, so
+/// errors are not reported.
+pub fn save(ws: &Workspace<'_>, resolve: &Resolve) {
+    let result = (|| -> CargoResult<()> {
+        let manifests = manifests(ws, resolve);
+        let data = CacheData {
+            fingerprint: fingerprint(ws, &manifests)?,
+            manifests,
+        };
+        let path = cache_path(ws);
+        paths::create_dir_all(path.parent().unwrap())?;
+        paths::write(&path, serde_json::to_string(&data)?)
+    })();
+    if let Err(e) = result {
+        debug!("failed to save resolve cache: {e:?}");
+    }
+}
+
+fn cache_path(ws: &Workspace<'_>) -> PathBuf {
+    ws.target_dir()
+        .as_path_unlocked()
+        .join(".resolve_cache.json")
+}
+
+/// The manifests the resolve of `ws` was computed from.
+fn manifests(ws: &Workspace<'_>, resolve: &Resolve) -> Vec<PathBuf> {
+    let mut manifests = vec![ws.root_manifest().to_owned()];
+    manifests.extend(ws.members().map(|pkg| pkg.manifest_path().to_owned()));
+    let mut source_ids: Vec<_> = resolve.iter().map(|id| id.source_id()).collect();
+    if let Ok(patches) = ws.root_patch() {
+        source_ids.extend(patches.values().flatten().map(|dep| dep.source_id()));
+    }
+    source_ids.extend(ws.root_replace().iter().map(|(_, dep)| dep.source_id()));
+    manifests.extend(
+        source_ids
+            .into_iter()
+            .filter(|id| id.is_path())
+            .filter_map(|id| id.url().to_file_path().ok())
+            .map(|path| path.join("Cargo.toml")),
+    );
+    manifests.sort();
+    manifests.dedup();
+    manifests
+}
+
+fn fingerprint(ws: &Workspace<'_>, manifests: &[PathBuf]) -> CargoResult<u64> {
+    let gctx = ws.gctx();
+    let mut hasher = StableHasher::new();
+    crate::version().to_string().hash(&mut hasher);
+    paths::read_bytes(
+        &super::lockfile::lock_root(ws)
+            .as_path_unlocked()
+            .join("Cargo.lock"),
+    )?
+    .hash(&mut hasher);
+    for manifest in manifests {
+        manifest.hash(&mut hasher);
+        paths::read_bytes(manifest)?.hash(&mut hasher);
+    }
+
+    let values = gctx.values()?;
+    for table in CONFIG_TABLES {
+        if let Some(value) = values.get(*table) {
+            table.hash(&mut hasher);
+            hash_config_value(value, &mut hasher);
+        }
+    }
+    format!("{:?}", gctx.cli_unstable()).hash(&mut hasher);
+
+    format!("{:?}", ws.resolution_mode()).hash(&mut hasher);
+    ws.resolve_honors_rust_version().hash(&mut hasher);
+    format!("{:?}", ws.max_rust_version()).hash(&mut hasher);
+    format!("{:?}", ws.max_publish_time()).hash(&mut hasher);
+    ws.filter_platforms().hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// Hashes a config value, with tables in a stable order.
+///
+/// The definitions are included as relative paths are resolved against them.
+fn hash_config_value(value: &ConfigValue, hasher: &mut StableHasher) {
+    match value {
+        ConfigValue::Integer(i, def) => {
+            (0, i, def.to_string()).hash(hasher);
+        }
+        ConfigValue::String(s, def) => {
+            (1, s, def.to_string()).hash(hasher);
+        }
+        ConfigValue::List(list, def) => {
+            (2, def.to_string()).hash(hasher);
+            for (s, def) in list {
+                (s, def.to_string()).hash(hasher);
+            }
+        }
+        ConfigValue::Table(table, def) => {
+            (3, def.to_string()).hash(hasher);
+            let mut entries: Vec<_> = table.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (key, value) in entries {
+                key.hash(hasher);
+                hash_config_value(value, hasher);
+            }
+        }
+        ConfigValue::Boolean(b, def) => {
+            (4, b, def.to_string()).hash(hasher);
+        }
+    }
+}
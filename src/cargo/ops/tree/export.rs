@@ -0,0 +1,219 @@
+//! Implementation of `cargo tree --format dot` and `--format json`, which
+//! print the whole dependency graph for other tools instead of a tree.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::graph::{EdgeKind, Graph, Node, EDGE_KINDS};
+use crate::core::compiler::CompileKind;
+use crate::core::dependency::DepKind;
+use crate::core::PackageIdSpec;
+use crate::drop_println;
+use crate::util::{CargoResult, GlobalContext};
+
+/// The output format of `cargo tree --format dot|json`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+impl clap::ValueEnum for GraphFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[GraphFormat::Dot, GraphFormat::Json]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let name = match self {
+            GraphFormat::Dot => "dot",
+            GraphFormat::Json => "json",
+        };
+        Some(clap::builder::PossibleValue::new(name))
+    }
+}
+
+fn edge_kind_name(kind: EdgeKind) -> &'static str {
+    match kind {
+        EdgeKind::Dep(DepKind::Normal) => "normal",
+        EdgeKind::Dep(DepKind::Build) => "build",
+        EdgeKind::Dep(DepKind::Development) => "dev",
+        EdgeKind::Feature => "feature",
+    }
+}
+
+/// The part of the graph reachable from the roots, with nodes numbered in
+/// the order they were visited.
+struct Reachable {
+    /// Original node indexes.
+    nodes: Vec<usize>,
+    /// Edges as `(from, to, kind)`, using the new numbering.
+    edges: Vec<(usize, usize, EdgeKind)>,
+}
+
+fn reachable(graph: &Graph<'_>, roots: &[usize], pkgs_to_prune: &[PackageIdSpec]) -> Reachable {
+    let nodes = graph.reachable(roots, pkgs_to_prune);
+    let ids: HashMap<usize, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(id, index)| (*index, id))
+        .collect();
+    let mut edges = Vec::new();
+    for (from, index) in nodes.iter().enumerate() {
+        for kind in EDGE_KINDS {
+            for to in graph.connected_nodes(*index, &kind) {
+                // Pruned packages aren't in the graph.
+                if let Some(to) = ids.get(&to) {
+                    edges.push((from, *to, kind));
+                }
+            }
+        }
+    }
+    Reachable { nodes, edges }
+}
+
+/// Prints the graph reachable from `roots` in the given format.
+pub fn print(
+    gctx: &GlobalContext,
+    graph: &Graph<'_>,
+    roots: &[usize],
+    pkgs_to_prune: &[PackageIdSpec],
+    format: GraphFormat,
+) -> CargoResult<()> {
+    let reachable = reachable(graph, roots, pkgs_to_prune);
+    let ids: HashMap<usize, usize> = reachable
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(id, index)| (*index, id))
+        .collect();
+
+    match format {
+        GraphFormat::Dot => {
+            drop_println!(gctx, "digraph {{");
+            for (id, index) in reachable.nodes.iter().enumerate() {
+                let (label, shape) = match graph.node(*index) {
+                    Node::Package {
+                        package_id,
+                        features,
+                        ..
+                    } => {
+                        let mut label = format!("{} v{}", package_id.name(), package_id.version());
+                        if !features.is_empty() {
+                            label.push_str(&format!("\n[{}]", itertools::join(features, ", ")));
+                        }
+                        (label, "box")
+                    }
+                    Node::Feature { node_index, name } => {
+                        let package_id = graph.package_id_for_index(*node_index);
+                        (
+                            format!("{} feature \"{name}\"", package_id.name()),
+                            "ellipse",
+                        )
+                    }
+                };
+                drop_println!(
+                    gctx,
+                    "    {id} [label=\"{}\", shape={shape}];",
+                    dot_escape(&label)
+                );
+            }
+            for (from, to, kind) in &reachable.edges {
+                let attrs = match kind {
+                    EdgeKind::Dep(DepKind::Normal) => "",
+                    EdgeKind::Dep(DepKind::Build) => " [label=\"build\", color=blue]",
+                    EdgeKind::Dep(DepKind::Development) => " [label=\"dev\", style=dashed]",
+                    EdgeKind::Feature => " [style=dotted]",
+                };
+                drop_println!(gctx, "    {from} -> {to}{attrs};");
+            }
+            drop_println!(gctx, "}}");
+        }
+        GraphFormat::Json => {
+            #[derive(Serialize)]
+            struct JsonGraph {
+                roots: Vec<usize>,
+                nodes: Vec<JsonNode>,
+                edges: Vec<JsonEdge>,
+            }
+            #[derive(Serialize)]
+            #[serde(tag = "kind", rename_all = "kebab-case")]
+            enum JsonNode {
+                Package {
+                    id: usize,
+                    package: String,
+                    name: String,
+                    version: String,
+                    features: Vec<String>,
+                    target: Option<String>,
+                },
+                Feature {
+                    id: usize,
+                    /// The package this feature is for.
+                    package: String,
+                    name: String,
+                },
+            }
+            #[derive(Serialize)]
+            struct JsonEdge {
+                from: usize,
+                to: usize,
+                kind: &'static str,
+            }
+
+            let nodes = reachable
+                .nodes
+                .iter()
+                .enumerate()
+                .map(|(id, index)| match graph.node(*index) {
+                    Node::Package {
+                        package_id,
+                        features,
+                        kind,
+                    } => JsonNode::Package {
+                        id,
+                        package: package_id.to_spec().to_string(),
+                        name: package_id.name().to_string(),
+                        version: package_id.version().to_string(),
+                        features: features.iter().map(|f| f.to_string()).collect(),
+                        target: match kind {
+                            CompileKind::Host => None,
+                            CompileKind::Target(target) => Some(target.short_name().to_string()),
+                        },
+                    },
+                    Node::Feature { node_index, name } => JsonNode::Feature {
+                        id,
+                        package: graph
+                            .package_id_for_index(*node_index)
+                            .to_spec()
+                            .to_string(),
+                        name: name.to_string(),
+                    },
+                })
+                .collect();
+            let edges = reachable
+                .edges
+                .iter()
+                .map(|(from, to, kind)| JsonEdge {
+                    from: *from,
+                    to: *to,
+                    kind: edge_kind_name(*kind),
+                })
+                .collect();
+            let roots = roots.iter().filter_map(|r| ids.get(r).copied()).collect();
+            gctx.shell().print_json(&JsonGraph {
+                roots,
+                nodes,
+                edges,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Escapes a label for a quoted DOT string.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -8,7 +8,7 @@ use crate::core::resolver::Resolve;
 use crate::core::{FeatureMap, FeatureValue, Package, PackageId, PackageIdSpec, Workspace};
 use crate::util::interning::InternedString;
 use crate::util::CargoResult;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub enum Node {
@@ -33,6 +33,14 @@ pub enum EdgeKind {
     Feature,
 }
 
+/// Edge kinds in the order they are visited.
+pub const EDGE_KINDS: [EdgeKind; 4] = [
+    EdgeKind::Dep(DepKind::Normal),
+    EdgeKind::Dep(DepKind::Build),
+    EdgeKind::Dep(DepKind::Development),
+    EdgeKind::Feature,
+];
+
 /// Set of outgoing edges for a single node.
 ///
 /// Edges are separated by the edge kind (`DepKind` or `Feature`). This is
@@ -144,11 +152,28 @@ impl<'a> Graph<'a> {
         result.into_iter().map(|(_node, i)| i).collect()
     }
 
+    /// Returns the indexes of the nodes of the given feature of the given
+    /// packages, for the packages where it is enabled.
+    pub fn feature_indexes(&self, package_indexes: &[usize], name: &str) -> Vec<usize> {
+        let name = InternedString::new(name);
+        package_indexes
+            .iter()
+            .filter_map(|node_index| {
+                self.index
+                    .get(&Node::Feature {
+                        node_index: *node_index,
+                        name,
+                    })
+                    .copied()
+            })
+            .collect()
+    }
+
     pub fn package_for_id(&self, id: PackageId) -> &Package {
         self.package_map[&id]
     }
 
-    fn package_id_for_index(&self, index: usize) -> PackageId {
+    pub fn package_id_for_index(&self, index: usize) -> PackageId {
         match self.nodes[index] {
             Node::Package { package_id, .. } => package_id,
             Node::Feature { .. } => panic!("unexpected feature node"),
@@ -201,6 +226,50 @@ impl<'a> Graph<'a> {
         new_graph
     }
 
+    /// Returns the nodes reachable from the given nodes, including the roots
+    /// themselves, in breadth-first order. Packages matching `pkgs_to_prune`
+    /// are not visited.
+    pub fn reachable(&self, roots: &[usize], pkgs_to_prune: &[PackageIdSpec]) -> Vec<usize> {
+        let mut visited = vec![false; self.nodes.len()];
+        let mut nodes = Vec::new();
+        for &root in roots {
+            if !std::mem::replace(&mut visited[root], true) {
+                nodes.push(root);
+            }
+        }
+        let mut next = 0;
+        while next < nodes.len() {
+            for kind in EDGE_KINDS {
+                for to in self.connected_nodes(nodes[next], &kind) {
+                    if visited[to] {
+                        continue;
+                    }
+                    if let Node::Package { package_id, .. } = self.nodes[to] {
+                        if pkgs_to_prune.iter().any(|spec| spec.matches(package_id)) {
+                            continue;
+                        }
+                    }
+                    visited[to] = true;
+                    nodes.push(to);
+                }
+            }
+            next += 1;
+        }
+        nodes
+    }
+
+    /// Returns the packages reachable from the given nodes, including the
+    /// roots themselves.
+    pub fn reachable_package_ids(&self, roots: &[usize]) -> BTreeSet<PackageId> {
+        self.reachable(roots, &[])
+            .into_iter()
+            .filter_map(|index| match self.nodes[index] {
+                Node::Package { package_id, .. } => Some(package_id),
+                Node::Feature { .. } => None,
+            })
+            .collect()
+    }
+
     /// Inverts the direction of all edges.
     pub fn invert(&mut self) {
         let mut new_edges = vec![Edges::new(); self.edges.len()];
@@ -421,7 +490,12 @@ fn add_pkg(
                         EdgeKind::Dep(dep.kind()),
                     );
                 }
-                for feature in dep.features().iter() {
+                let profile_features = dep
+                    .features_profile()
+                    .and_then(|profile| dep_pkg.summary().feature_profiles().get(&profile))
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                for feature in dep.features().iter().chain(profile_features) {
                     add_feature(
                         graph,
                         *feature,
@@ -430,7 +504,10 @@ fn add_pkg(
                         EdgeKind::Dep(dep.kind()),
                     );
                 }
-                if !dep.uses_default_features() && dep.features().is_empty() {
+                if !dep.uses_default_features()
+                    && dep.features().is_empty()
+                    && profile_features.is_empty()
+                {
                     // No features, use a direct connection.
                     graph.edges[from_index].add_edge(EdgeKind::Dep(dep.kind()), dep_index);
                 }
@@ -0,0 +1,209 @@
+//! Implementation of `cargo tree --emit licenses`, an inventory of the
+//! licenses of the packages in the tree.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::core::Package;
+use crate::drop_println;
+use crate::util::{CargoResult, GlobalContext};
+
+/// The output format of `cargo tree --emit licenses`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LicensesFormat {
+    Human,
+    Json,
+    Csv,
+}
+
+impl clap::ValueEnum for LicensesFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            LicensesFormat::Human,
+            LicensesFormat::Json,
+            LicensesFormat::Csv,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let name = match self {
+            LicensesFormat::Human => "human",
+            LicensesFormat::Json => "json",
+            LicensesFormat::Csv => "csv",
+        };
+        Some(clap::builder::PossibleValue::new(name))
+    }
+}
+
+/// The license a package declares in its manifest.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum License {
+    /// A `license` SPDX expression.
+    Expression(String),
+    /// Only a `license-file`, which can't be identified.
+    Unknown,
+    /// Neither `license` nor `license-file`.
+    Missing,
+}
+
+impl License {
+    fn of(pkg: &Package) -> License {
+        let metadata = pkg.manifest().metadata();
+        match (&metadata.license, &metadata.license_file) {
+            (Some(license), _) => License::Expression(normalize_expression(license)),
+            (None, Some(_)) => License::Unknown,
+            (None, None) => License::Missing,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            License::Expression(_) => "expression",
+            License::Unknown => "unknown",
+            License::Missing => "missing",
+        }
+    }
+
+    fn expression(&self) -> Option<&str> {
+        match self {
+            License::Expression(expr) => Some(expr),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for License {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            License::Expression(expr) => f.write_str(expr),
+            License::Unknown => f.write_str("unknown (license file only)"),
+            License::Missing => f.write_str("missing"),
+        }
+    }
+}
+
+/// Normalizes the legacy `/` separator, which crates.io treats as `OR`, and
+/// whitespace so equivalent expressions are grouped together.
+fn normalize_expression(license: &str) -> String {
+    license
+        .split('/')
+        .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+/// Prints the licenses of `packages`, grouped by license.
+///
+/// Packages with an unknown or missing license are also reported as a
+/// warning.
+pub fn print(
+    gctx: &GlobalContext,
+    packages: &[&Package],
+    format: LicensesFormat,
+) -> CargoResult<()> {
+    let mut groups: BTreeMap<License, Vec<&Package>> = BTreeMap::new();
+    for pkg in packages {
+        groups.entry(License::of(pkg)).or_default().push(pkg);
+    }
+    for pkgs in groups.values_mut() {
+        pkgs.sort_by_key(|pkg| pkg.package_id());
+    }
+
+    match format {
+        LicensesFormat::Human => {
+            for (license, pkgs) in &groups {
+                drop_println!(gctx, "{license} ({})", pkgs.len());
+                for pkg in pkgs {
+                    drop_println!(gctx, "  {}", pkg.package_id());
+                }
+            }
+        }
+        LicensesFormat::Json => {
+            #[derive(Serialize)]
+            struct Licenses<'a> {
+                licenses: Vec<Group<'a>>,
+            }
+            #[derive(Serialize)]
+            struct Group<'a> {
+                kind: &'static str,
+                license: Option<&'a str>,
+                packages: Vec<LicensedPackage<'a>>,
+            }
+            #[derive(Serialize)]
+            struct LicensedPackage<'a> {
+                id: String,
+                name: &'a str,
+                version: String,
+                license_file: Option<&'a str>,
+            }
+            let licenses = groups
+                .iter()
+                .map(|(license, pkgs)| Group {
+                    kind: license.kind(),
+                    license: license.expression(),
+                    packages: pkgs
+                        .iter()
+                        .map(|pkg| LicensedPackage {
+                            id: pkg.package_id().to_spec().to_string(),
+                            name: pkg.name().as_str(),
+                            version: pkg.version().to_string(),
+                            license_file: pkg.manifest().metadata().license_file.as_deref(),
+                        })
+                        .collect(),
+                })
+                .collect();
+            gctx.shell().print_json(&Licenses { licenses })?;
+        }
+        LicensesFormat::Csv => {
+            drop_println!(gctx, "name,version,id,kind,license,license_file");
+            for (license, pkgs) in &groups {
+                for pkg in pkgs {
+                    let fields = [
+                        pkg.name().as_str(),
+                        &pkg.version().to_string(),
+                        &pkg.package_id().to_spec().to_string(),
+                        license.kind(),
+                        license.expression().unwrap_or_default(),
+                        pkg.manifest()
+                            .metadata()
+                            .license_file
+                            .as_deref()
+                            .unwrap_or_default(),
+                    ];
+                    let row = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>();
+                    drop_println!(gctx, "{}", row.join(","));
+                }
+            }
+        }
+    }
+
+    for license in [License::Unknown, License::Missing] {
+        let Some(pkgs) = groups.get(&license) else {
+            continue;
+        };
+        let what = match license {
+            License::Unknown => "only a `license-file`, which can't be identified",
+            _ => "no `license` or `license-file`",
+        };
+        gctx.shell().warn(format!(
+            "{} package(s) have {what}: {}",
+            pkgs.len(),
+            pkgs.iter()
+                .map(|pkg| format!("`{}`", pkg.package_id()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))?;
+    }
+    Ok(())
+}
+
+/// Quotes a CSV field if needed, per RFC 4180.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
@@ -5,7 +5,7 @@ use crate::core::compiler::{CompileKind, RustcTargetData};
 use crate::core::dependency::DepKind;
 use crate::core::resolver::{features::CliFeatures, ForceAllTargets, HasDevUnits};
 use crate::core::{Package, PackageId, PackageIdSpec, PackageIdSpecQuery, Workspace};
-use crate::ops::{self, Packages};
+use crate::ops::{self, PackageFilter, Packages};
 use crate::util::{CargoResult, GlobalContext};
 use crate::{drop_print, drop_println};
 use anyhow::Context as _;
@@ -13,10 +13,13 @@ use graph::Graph;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
+mod export;
 mod format;
 mod graph;
+mod licenses;
+mod sizes;
 
-pub use {graph::EdgeKind, graph::Node};
+pub use {export::GraphFormat, graph::EdgeKind, graph::Node, licenses::LicensesFormat};
 
 pub struct TreeOptions {
     pub cli_features: CliFeatures,
@@ -29,6 +32,8 @@ pub struct TreeOptions {
     pub invert: Vec<String>,
     /// The packages to prune from the display of the dependency tree.
     pub pkgs_to_prune: Vec<String>,
+    /// Also prunes the packages from some sources or with some licenses.
+    pub package_filter: PackageFilter,
     /// The style of prefix for each line.
     pub prefix: Prefix,
     /// If `true`, duplicates will be repeated.
@@ -47,6 +52,18 @@ pub struct TreeOptions {
     pub max_display_depth: u32,
     /// Excludes proc-macro dependencies.
     pub no_proc_macro: bool,
+    /// If set, prints the licenses of the packages in the tree in the given
+    /// format instead of the tree.
+    pub emit_licenses: Option<LicensesFormat>,
+    /// If set, prints the whole graph in the given format instead of the
+    /// tree, ignoring `format`.
+    pub graph_format: Option<GraphFormat>,
+    /// If set, a `PACKAGE/FEATURE` to explain, showing every path which
+    /// enables that feature. Implies `invert` and `graph_features`.
+    pub why_feature: Option<String>,
+    /// Shows the download and unpacked size of each package, and the totals
+    /// of its subtree.
+    pub sizes: bool,
 }
 
 #[derive(PartialEq)]
@@ -159,16 +176,38 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
         opts,
     )?;
 
-    let root_specs = if opts.invert.is_empty() {
-        specs
+    let root_indexes = if let Some(why_feature) = &opts.why_feature {
+        let (pkg, feature) = why_feature.rsplit_once('/').with_context(|| {
+            format!("`--why-feature` expects `PACKAGE/FEATURE`, found `{why_feature}`")
+        })?;
+        let spec = PackageIdSpec::parse(pkg)?;
+        let ids = ws_resolve.targeted_resolve.specs_to_ids(&[spec])?;
+        let indexes = graph.feature_indexes(&graph.indexes_from_ids(&ids), feature);
+        if indexes.is_empty() {
+            anyhow::bail!("feature `{feature}` of package `{pkg}` is not enabled");
+        }
+        indexes
     } else {
-        opts.invert
-            .iter()
-            .map(|p| PackageIdSpec::parse(p))
-            .collect::<Result<Vec<PackageIdSpec>, _>>()?
+        let root_specs = if opts.invert.is_empty() {
+            specs
+        } else {
+            opts.invert
+                .iter()
+                .map(|p| PackageIdSpec::parse(p))
+                .collect::<Result<Vec<PackageIdSpec>, _>>()?
+        };
+        let root_ids = ws_resolve.targeted_resolve.specs_to_ids(&root_specs)?;
+        graph.indexes_from_ids(&root_ids)
     };
-    let root_ids = ws_resolve.targeted_resolve.specs_to_ids(&root_specs)?;
-    let root_indexes = graph.indexes_from_ids(&root_ids);
+
+    if let Some(format) = opts.emit_licenses {
+        let packages: Vec<_> = graph
+            .reachable_package_ids(&root_indexes)
+            .into_iter()
+            .map(|id| graph.package_for_id(id))
+            .collect();
+        return licenses::print(ws.gctx(), &packages, format);
+    }
 
     let root_indexes = if opts.duplicates {
         // `-d -p foo` will only show duplicates within foo's subtree
@@ -178,12 +217,12 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
         root_indexes
     };
 
-    if !opts.invert.is_empty() || opts.duplicates {
+    if !opts.invert.is_empty() || opts.duplicates || opts.why_feature.is_some() {
         graph.invert();
     }
 
     // Packages to prune.
-    let pkgs_to_prune = opts
+    let mut pkgs_to_prune = opts
         .pkgs_to_prune
         .iter()
         .map(|p| PackageIdSpec::parse(p).map_err(Into::into))
@@ -193,6 +232,17 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
             r.and_then(|spec| spec.query(ws_resolve.targeted_resolve.iter()).and(Ok(spec)))
         })
         .collect::<CargoResult<Vec<PackageIdSpec>>>()?;
+    pkgs_to_prune.extend(
+        ws_resolve
+            .pkg_set
+            .packages()
+            .filter(|pkg| opts.package_filter.prunes(pkg))
+            .map(|pkg| pkg.package_id().to_spec()),
+    );
+
+    if let Some(format) = opts.graph_format {
+        return export::print(ws.gctx(), &graph, &root_indexes, &pkgs_to_prune, format);
+    }
 
     if root_indexes.len() == 0 {
         ws.gctx().shell().warn(
@@ -201,7 +251,17 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
         try to use option `--target all` first, and then narrow your search scope accordingly.",
         )?;
     } else {
-        print(ws.gctx(), opts, root_indexes, &pkgs_to_prune, &graph)?;
+        let sizes = opts
+            .sizes
+            .then(|| sizes::Sizes::new(ws.gctx(), &graph, &root_indexes, &pkgs_to_prune));
+        print(
+            ws.gctx(),
+            opts,
+            root_indexes,
+            &pkgs_to_prune,
+            &graph,
+            sizes.as_ref(),
+        )?;
     }
     Ok(())
 }
@@ -213,6 +273,7 @@ fn print(
     roots: Vec<usize>,
     pkgs_to_prune: &[PackageIdSpec],
     graph: &Graph<'_>,
+    sizes: Option<&sizes::Sizes>,
 ) -> CargoResult<()> {
     let format = Pattern::new(&opts.format)
         .with_context(|| format!("tree format `{}` not valid", opts.format))?;
@@ -245,6 +306,7 @@ fn print(
             root_index,
             &format,
             symbols,
+            sizes,
             pkgs_to_prune,
             opts.prefix,
             opts.no_dedupe,
@@ -265,6 +327,7 @@ fn print_node<'a>(
     node_index: usize,
     format: &Pattern,
     symbols: &Symbols,
+    sizes: Option<&sizes::Sizes>,
     pkgs_to_prune: &[PackageIdSpec],
     prefix: Prefix,
     no_dedupe: bool,
@@ -305,7 +368,14 @@ fn print_node<'a>(
     } else {
         " (*)"
     };
-    drop_println!(gctx, "{}{}", format.display(graph, node_index), star);
+    let size = sizes.map_or_else(String::new, |sizes| sizes.display(graph, node_index));
+    drop_println!(
+        gctx,
+        "{}{}{}",
+        format.display(graph, node_index),
+        size,
+        star
+    );
 
     if !new || in_cycle {
         return;
@@ -324,6 +394,7 @@ fn print_node<'a>(
             node_index,
             format,
             symbols,
+            sizes,
             pkgs_to_prune,
             prefix,
             no_dedupe,
@@ -344,6 +415,7 @@ fn print_dependencies<'a>(
     node_index: usize,
     format: &Pattern,
     symbols: &Symbols,
+    sizes: Option<&sizes::Sizes>,
     pkgs_to_prune: &[PackageIdSpec],
     prefix: Prefix,
     no_dedupe: bool,
@@ -402,6 +474,7 @@ fn print_dependencies<'a>(
             *dependency,
             format,
             symbols,
+            sizes,
             pkgs_to_prune,
             prefix,
             no_dedupe,
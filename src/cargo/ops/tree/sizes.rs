@@ -0,0 +1,155 @@
+//! Implementation of `cargo tree --sizes`, which shows how much each
+//! package and its dependencies weigh on disk.
+//!
+//! The sizes come from the local cache of registry packages: the download
+//! size is the size of the `.crate` file, and the unpacked size is the size of
+//! its extracted source. Packages from other sources don't have sizes.
+
+use std::collections::{HashMap, HashSet};
+
+use super::graph::{Graph, Node, EDGE_KINDS};
+use crate::core::{Package, PackageId, PackageIdSpec};
+use crate::util::{human_readable_bytes, GlobalContext};
+
+/// The sizes of a package, when known.
+#[derive(Clone, Copy, Default)]
+struct PackageSize {
+    download: Option<u64>,
+    unpacked: Option<u64>,
+}
+
+/// The sizes of the packages in the graph, and the totals of their subtrees.
+pub struct Sizes {
+    packages: HashMap<PackageId, PackageSize>,
+    /// Total `(download, unpacked)` size of the distinct packages reachable
+    /// from each package node, including itself. Nodes without dependencies
+    /// have no entry.
+    subtrees: HashMap<usize, (u64, u64)>,
+}
+
+impl Sizes {
+    /// Collects the sizes of the packages reachable from `roots`.
+    pub fn new(
+        gctx: &GlobalContext,
+        graph: &Graph<'_>,
+        roots: &[usize],
+        pkgs_to_prune: &[PackageIdSpec],
+    ) -> Sizes {
+        let nodes = graph.reachable(roots, pkgs_to_prune);
+        let positions: HashMap<usize, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(position, index)| (*index, position))
+            .collect();
+        let mut packages = HashMap::new();
+        // The distinct packages reachable from each node, including itself.
+        let mut reachable: Vec<HashSet<PackageId>> = Vec::with_capacity(nodes.len());
+        for index in &nodes {
+            let mut ids = HashSet::new();
+            if let Node::Package { package_id, .. } = graph.node(*index) {
+                packages
+                    .entry(*package_id)
+                    .or_insert_with(|| package_size(gctx, graph.package_for_id(*package_id)));
+                ids.insert(*package_id);
+            }
+            reachable.push(ids);
+        }
+        // Dependencies are mostly visited after their dependents, so walking
+        // the nodes backwards settles in one pass unless the graph has cycles.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for position in (0..nodes.len()).rev() {
+                for kind in EDGE_KINDS {
+                    for to in graph.connected_nodes(nodes[position], &kind) {
+                        // Pruned packages aren't counted.
+                        let Some(&to) = positions.get(&to) else {
+                            continue;
+                        };
+                        if to == position {
+                            continue;
+                        }
+                        let deps = std::mem::take(&mut reachable[to]);
+                        let len = reachable[position].len();
+                        reachable[position].extend(&deps);
+                        changed |= reachable[position].len() != len;
+                        reachable[to] = deps;
+                    }
+                }
+            }
+        }
+        let mut subtrees = HashMap::new();
+        for (index, ids) in nodes.iter().zip(&reachable) {
+            if !matches!(graph.node(*index), Node::Package { .. }) || ids.len() < 2 {
+                continue;
+            }
+            let mut total = (0, 0);
+            for id in ids {
+                let size = packages.get(id).copied().unwrap_or_default();
+                total.0 += size.download.unwrap_or(0);
+                total.1 += size.unpacked.unwrap_or(0);
+            }
+            subtrees.insert(*index, total);
+        }
+        Sizes { packages, subtrees }
+    }
+
+    /// Returns the text appended to the line of the given node, or an empty
+    /// string if there is nothing to show.
+    pub fn display(&self, graph: &Graph<'_>, index: usize) -> String {
+        let Node::Package { package_id, .. } = graph.node(index) else {
+            return String::new();
+        };
+        let mut parts = Vec::new();
+        let size = self.packages.get(package_id).copied().unwrap_or_default();
+        if let Some(download) = size.download {
+            parts.push(format!("download: {}", bytes(download)));
+        }
+        if let Some(unpacked) = size.unpacked {
+            parts.push(format!("unpacked: {}", bytes(unpacked)));
+        }
+        if let Some((download, unpacked)) = self.subtrees.get(&index) {
+            parts.push(format!(
+                "subtree: {} download, {} unpacked",
+                bytes(*download),
+                bytes(*unpacked)
+            ));
+        }
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", parts.join("; "))
+        }
+    }
+}
+
+fn bytes(n: u64) -> String {
+    let (size, unit) = human_readable_bytes(n);
+    format!("{size:.1}{unit}")
+}
+
+/// Reads the sizes of a registry package from the local cache.
+fn package_size(gctx: &GlobalContext, pkg: &Package) -> PackageSize {
+    if !pkg.package_id().source_id().is_registry() {
+        return PackageSize::default();
+    }
+    let root = pkg.root();
+    let unpacked = cargo_util::du(root, &[])
+        .map_err(|e| tracing::debug!("failed to get size of `{}`: {e:?}", root.display()))
+        .ok();
+    // The `.crate` file is in the cache directory of the same name as the
+    // directory it was unpacked in.
+    let download = root
+        .strip_prefix(gctx.registry_source_path().as_path_unlocked())
+        .ok()
+        .and_then(|rel| rel.parent())
+        .map(|dir| {
+            gctx.registry_cache_path()
+                .as_path_unlocked()
+                .join(dir)
+                .join(pkg.package_id().tarball_name())
+        })
+        .and_then(|path| path.metadata().ok())
+        .map(|meta| meta.len());
+    PackageSize { download, unpacked }
+}
@@ -1,5 +1,6 @@
+use crate::core::compiler::UnitInterner;
 use crate::core::shell::Verbosity;
-use crate::core::{GitReference, Package, Workspace};
+use crate::core::{GitReference, Package, PackageId, Workspace};
 use crate::ops;
 use crate::sources::path::PathSource;
 use crate::sources::CRATES_IO_REGISTRY;
@@ -20,6 +21,9 @@ pub struct VendorOptions<'a> {
     pub versioned_dirs: bool,
     pub destination: &'a Path,
     pub extra: Vec<PathBuf>,
+    /// Only vendor the packages needed to build with these options, and
+    /// replace the others with stubs.
+    pub only_used: Option<ops::CompileOptions>,
 }
 
 pub fn vendor(ws: &Workspace<'_>, opts: &VendorOptions<'_>) -> CargoResult<()> {
@@ -137,6 +141,20 @@ fn sync(
         }
     }
 
+    // With `--only-used`, only the packages which are part of the unit graph
+    // of one of the workspaces are vendored. The others are replaced by stubs
+    // containing just enough for the resolver to still use the lockfile.
+    let used = match &opts.only_used {
+        Some(compile_opts) => {
+            let mut used = HashSet::new();
+            for ws in workspaces {
+                used.extend(used_packages(ws, compile_opts)?);
+            }
+            Some(used)
+        }
+        None => None,
+    };
+
     let mut checksums = HashMap::new();
     let mut ids = BTreeMap::new();
 
@@ -206,22 +224,30 @@ fn sync(
         let dst = canonical_destination.join(&dst_name);
         to_remove.remove(&dst);
         let cksum = dst.join(".cargo-checksum.json");
-        if dir_has_version_suffix && cksum.exists() {
+        // A previous `--only-used` run may have left a stub behind, so always
+        // re-copy in that mode.
+        if dir_has_version_suffix && cksum.exists() && used.is_none() {
             // Always re-copy directory without version suffix in case the version changed
             continue;
         }
 
+        let stub = used.as_ref().map_or(false, |used| !used.contains(id));
         gctx.shell().status(
-            "Vendoring",
+            if stub { "Stubbing" } else { "Vendoring" },
             &format!("{} ({}) to {}", id, src.to_string_lossy(), dst.display()),
         )?;
 
         let _ = fs::remove_dir_all(&dst);
-        let pathsource = PathSource::new(src, id.source_id(), gctx);
-        let paths = pathsource.list_files(pkg)?;
         let mut map = BTreeMap::new();
-        cp_sources(pkg, src, &paths, &dst, &mut map, &mut tmp_buf)
-            .with_context(|| format!("failed to copy over vendored sources for: {}", id))?;
+        if stub {
+            stub_sources(pkg, src, &dst, &mut map, &mut tmp_buf)
+                .with_context(|| format!("failed to write stub sources for: {}", id))?;
+        } else {
+            let pathsource = PathSource::new(src, id.source_id(), gctx);
+            let paths = pathsource.list_files(pkg)?;
+            cp_sources(pkg, src, &paths, &dst, &mut map, &mut tmp_buf)
+                .with_context(|| format!("failed to copy over vendored sources for: {}", id))?;
+        }
 
         // Finally, emit the metadata about this package
         let json = serde_json::json!({
@@ -309,6 +335,57 @@ fn sync(
     Ok(VendorConfig { source: config })
 }
 
+/// Finds the packages which are part of the unit graph for the given compile
+/// options, i.e. the ones which are actually needed to build.
+fn used_packages(
+    ws: &Workspace<'_>,
+    compile_opts: &ops::CompileOptions,
+) -> CargoResult<HashSet<PackageId>> {
+    let interner = UnitInterner::new();
+    let bcx = ops::create_bcx(ws, compile_opts, &interner)?;
+    Ok(bcx
+        .unit_graph
+        .keys()
+        .map(|unit| unit.pkg.package_id())
+        .collect())
+}
+
+/// Writes a stub of a package which is never built: its manifest, so the
+/// resolver can still load it, and an empty file for each of its targets.
+fn stub_sources(
+    pkg: &Package,
+    src: &Path,
+    dst: &Path,
+    cksums: &mut BTreeMap<String, String>,
+    tmp_buf: &mut [u8],
+) -> CargoResult<()> {
+    cp_sources(
+        pkg,
+        src,
+        &[pkg.manifest_path().to_owned()],
+        dst,
+        cksums,
+        tmp_buf,
+    )?;
+    for target in pkg.targets() {
+        let Some(path) = target.src_path().path() else {
+            continue;
+        };
+        let Ok(relative) = path.strip_prefix(src) else {
+            continue;
+        };
+        let dst = relative
+            .iter()
+            .fold(dst.to_owned(), |acc, component| acc.join(&component));
+        paths::create_dir_all(dst.parent().unwrap())?;
+        let mut dst_opts = OpenOptions::new();
+        dst_opts.write(true).create(true).truncate(true);
+        let cksum = copy_and_checksum(&dst, &mut dst_opts, &mut &b""[..], "stub", tmp_buf)?;
+        cksums.insert(relative.to_str().unwrap().replace("\\", "/"), cksum);
+    }
+    Ok(())
+}
+
 fn cp_sources(
     pkg: &Package,
     src: &Path,
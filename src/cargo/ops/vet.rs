@@ -0,0 +1,266 @@
+//! Enforcement of dependency audits with `-Zvet`.
+//!
+//! The `vet.audits` config lists files or URLs in the format of the
+//! `audits.toml` and `config.toml` files of [cargo-vet]. After resolution,
+//! every registry package version which wasn't already in the lock file must
+//! be covered by one of them, either by:
+//!
+//! * a full audit of that version,
+//! * a chain of delta audits starting from a fully audited version,
+//! * an exemption for that version.
+//!
+//! A version matching a `violation` entry is never vetted. The audits
+//! imported from other projects in `imports.lock` can be listed as well. The
+//! criteria of the audits aren't taken into account.
+//!
+//! [cargo-vet]: https://mozilla.github.io/cargo-vet/
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Context as _};
+use cargo_util::paths;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+
+use crate::core::{Resolve, Workspace};
+use crate::util::context::CargoVetConfig;
+use crate::util::errors::CargoResult;
+use crate::util::GlobalContext;
+
+/// The parts of an audits file used to decide whether a version is vetted.
+#[derive(Deserialize)]
+struct AuditsFile {
+    #[serde(default)]
+    audits: HashMap<String, AuditsTableValue>,
+    #[serde(default)]
+    exemptions: HashMap<String, Vec<ExemptionEntry>>,
+}
+
+/// A value of the `audits` table.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AuditsTableValue {
+    /// The audits of a package, in `audits.toml`.
+    Package(Vec<AuditEntry>),
+    /// The audits imported from another project, in `imports.lock`.
+    Imported {
+        #[serde(default)]
+        audits: HashMap<String, Vec<AuditEntry>>,
+    },
+}
+
+#[derive(Deserialize)]
+struct AuditEntry {
+    /// Set for a full audit of a version.
+    version: Option<String>,
+    /// Set for an audit of the changes between two versions, as `A -> B`.
+    delta: Option<String>,
+    /// Set for the versions which must not be used.
+    violation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ExemptionEntry {
+    version: String,
+}
+
+/// The versions of each package which are vetted, from all audits files.
+#[derive(Default)]
+struct Audits {
+    /// Versions which are fully audited or exempted.
+    vetted: HashMap<String, HashSet<Version>>,
+    /// Delta audits, from a version to another.
+    deltas: HashMap<String, Vec<(Version, Version)>>,
+    /// Requirements matching the versions which must not be used, with the
+    /// audits they come from.
+    violations: HashMap<String, Vec<(VersionReq, String)>>,
+}
+
+impl Audits {
+    fn add(&mut self, file: AuditsFile, source: &str) -> CargoResult<()> {
+        let parse = |name: &str, version: &str| {
+            version.trim().parse::<Version>().with_context(|| {
+                format!("invalid version `{version}` for `{name}` in audits `{source}`")
+            })
+        };
+        let mut packages = Vec::new();
+        for (key, value) in file.audits {
+            match value {
+                AuditsTableValue::Package(entries) => packages.push((key, entries)),
+                AuditsTableValue::Imported { audits } => packages.extend(audits),
+            }
+        }
+        for (name, entries) in packages {
+            for entry in entries {
+                if let Some(version) = &entry.version {
+                    let version = parse(&name, version)?;
+                    self.vetted.entry(name.clone()).or_default().insert(version);
+                }
+                if let Some(delta) = &entry.delta {
+                    let Some((from, to)) = delta.split_once("->") else {
+                        bail!(
+                            "invalid delta `{delta}` for `{name}` in audits `{source}`, \
+                             expected `<from> -> <to>`"
+                        );
+                    };
+                    let delta = (parse(&name, from)?, parse(&name, to)?);
+                    self.deltas.entry(name.clone()).or_default().push(delta);
+                }
+                if let Some(violation) = &entry.violation {
+                    let req = violation.trim().parse::<VersionReq>().with_context(|| {
+                        format!("invalid violation `{violation}` for `{name}` in audits `{source}`")
+                    })?;
+                    let violation = (req, source.to_string());
+                    self.violations
+                        .entry(name.clone())
+                        .or_default()
+                        .push(violation);
+                }
+            }
+        }
+        for (name, entries) in file.exemptions {
+            for entry in entries {
+                let version = parse(&name, &entry.version)?;
+                self.vetted.entry(name.clone()).or_default().insert(version);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the audits marking `version` of `name` as a violation, if any.
+    fn violation(&self, name: &str, version: &Version) -> Option<&str> {
+        self.violations
+            .get(name)?
+            .iter()
+            .find(|(req, _)| req.matches(version))
+            .map(|(_, source)| source.as_str())
+    }
+
+    /// Whether `version` of `name` is vetted, following delta audits from the
+    /// fully vetted versions.
+    fn covers(&self, name: &str, version: &Version) -> bool {
+        if self.violation(name, version).is_some() {
+            return false;
+        }
+        let Some(vetted) = self.vetted.get(name) else {
+            return false;
+        };
+        let mut reached: HashSet<&Version> = vetted.iter().collect();
+        let deltas = self.deltas.get(name).map(Vec::as_slice).unwrap_or_default();
+        loop {
+            if reached.contains(version) {
+                return true;
+            }
+            let new: Vec<_> = deltas
+                .iter()
+                .filter(|(from, to)| reached.contains(from) && !reached.contains(to))
+                .map(|(_, to)| to)
+                .collect();
+            if new.is_empty() {
+                return false;
+            }
+            reached.extend(new);
+        }
+    }
+}
+
+/// Checks that every registry package version added to the lock file by this
+/// resolution is covered by the audits of `vet.audits`.
+///
+/// Does nothing without `-Zvet` or when `vet.audits` isn't set. The error
+/// lists the Package ID Spec of each unvetted package, one per line.
+pub fn vet_check(
+    ws: &Workspace<'_>,
+    previous: Option<&Resolve>,
+    resolve: &Resolve,
+) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    if !gctx.cli_unstable().vet {
+        return Ok(());
+    }
+    let Some(config) = gctx.get::<Option<CargoVetConfig>>("vet")? else {
+        return Ok(());
+    };
+    if config.audits.is_empty() {
+        return Ok(());
+    }
+
+    let mut unvetted: Vec<_> = resolve
+        .iter()
+        .filter(|id| id.source_id().is_registry())
+        .filter(|id| previous.map_or(true, |previous| !previous.contains(id)))
+        .collect();
+    if unvetted.is_empty() {
+        return Ok(());
+    }
+
+    let mut audits = Audits::default();
+    for path in &config.audits {
+        let source = path.raw_value();
+        let contents = if source.starts_with("https://") || source.starts_with("http://") {
+            download(gctx, source)?
+        } else {
+            paths::read(&path.resolve_path(gctx))?
+        };
+        let file: AuditsFile = toml::from_str(&contents)
+            .with_context(|| format!("failed to parse audits `{source}`"))?;
+        audits.add(file, source)?;
+    }
+
+    unvetted.sort();
+    let violations = unvetted
+        .iter()
+        .filter_map(|id| {
+            let source = audits.violation(&id.name(), id.version())?;
+            Some(format!("  {} (in `{source}`)", id.to_spec()))
+        })
+        .collect::<Vec<_>>();
+    if !violations.is_empty() {
+        bail!(
+            "{} package version(s) are marked as a violation in `vet.audits`:\n{}",
+            violations.len(),
+            violations.join("\n")
+        );
+    }
+
+    unvetted.retain(|id| !audits.covers(&id.name(), id.version()));
+    if unvetted.is_empty() {
+        return Ok(());
+    }
+    let specs = unvetted
+        .iter()
+        .map(|id| format!("  {}", id.to_spec()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    bail!(
+        "{} package version(s) are not covered by an audit or exemption in `vet.audits`:\n{specs}",
+        unvetted.len()
+    )
+}
+
+/// Fetches an audits file from a URL.
+fn download(gctx: &GlobalContext, url: &str) -> CargoResult<String> {
+    if gctx.offline() {
+        bail!("can't download audits `{url}`: you are in the offline mode (--offline)");
+    }
+    let mut handle = gctx.http()?.borrow_mut();
+    handle.get(true)?;
+    handle.url(url)?;
+    handle.follow_location(true)?;
+    let mut body = Vec::new();
+    {
+        let mut transfer = handle.transfer();
+        transfer.write_function(|buf| {
+            body.extend_from_slice(buf);
+            Ok(buf.len())
+        })?;
+        transfer
+            .perform()
+            .with_context(|| format!("failed to download audits `{url}`"))?;
+    }
+    let code = handle.response_code()?;
+    if code != 200 {
+        bail!("failed to download audits `{url}`: got status code {code}");
+    }
+    String::from_utf8(body).with_context(|| format!("audits `{url}` are not valid UTF-8"))
+}
@@ -3,15 +3,18 @@
 //! Apparently, the most important type in this module is [`GitSource`].
 //! [`utils`] provides libgit2 utilities like fetch and checkout, whereas
 //! [`oxide`] is the counterpart for gitoxide integration. [`known_hosts`]
-//! is the mitigation of [CVE-2022-46176].
+//! is the mitigation of [CVE-2022-46176]. [`sparse`] decides what the sparse
+//! checkouts of `-Zgit=sparse-checkout` are made of.
 //!
 //! [CVE-2022-46176]: https://blog.rust-lang.org/2023/01/10/cve-2022-46176.html
 
 pub use self::source::GitSource;
+pub(crate) use self::utils::looks_like_commit_hash;
 pub use self::utils::{fetch, resolve_ref, GitCheckout, GitDatabase, GitRemote};
 mod known_hosts;
 mod oxide;
 mod source;
+mod sparse;
 mod utils;
 
 /// For `-Zgitoxide` integration.
@@ -4,7 +4,8 @@ use crate::core::global_cache_tracker;
 use crate::core::GitReference;
 use crate::core::SourceId;
 use crate::core::{Dependency, Package, PackageId};
-use crate::sources::git::utils::GitRemote;
+use crate::sources::git::sparse::SparseTree;
+use crate::sources::git::utils::{GitDatabase, GitRemote};
 use crate::sources::source::MaybePackage;
 use crate::sources::source::QueryKind;
 use crate::sources::source::Source;
@@ -17,7 +18,9 @@ use crate::util::interning::InternedString;
 use crate::util::GlobalContext;
 use anyhow::Context as _;
 use cargo_util::paths::exclude_from_backups_and_indexing;
+use std::collections::HashSet;
 use std::fmt::{self, Debug, Formatter};
+use std::path::PathBuf;
 use std::task::Poll;
 use tracing::trace;
 use url::Url;
@@ -55,6 +58,13 @@ use url::Url;
 /// For more on the directory format `<pkg>-<hash>[-shallow]`, see [`ident`]
 /// and [`ident_shallow`].
 ///
+/// ## Sparse checkouts
+///
+/// With `-Zgit=sparse-checkout`, the checkout of a revision starts empty, in
+/// a `<short-id>-sparse` directory. The packages are then materialized as they
+/// are queried, along with their path dependencies, see
+/// [`GitSource::materialize`].
+///
 /// ## Locked to a revision
 ///
 /// Once a `GitSource` is fetched, it will resolve to a specific commit revision.
@@ -82,7 +92,8 @@ pub struct GitSource<'gctx> {
     /// A short string that uniquely identifies the version of the checkout.
     ///
     /// This is typically a 7-character string of the OID hash, automatically
-    /// increasing in size if it is ambiguous.
+    /// increasing in size if it is ambiguous, with a `-sparse` suffix for
+    /// sparse checkouts.
     ///
     /// This is set to `Some` after the git repo has been checked out
     /// (automatically handled via [`GitSource::block_until_ready`]).
@@ -90,6 +101,13 @@ pub struct GitSource<'gctx> {
     /// The identifier of this source for Cargo's Git cache directory.
     /// See [`ident`] for more.
     ident: InternedString,
+    /// The state of the sparse checkout, with `-Zgit=sparse-checkout`.
+    ///
+    /// This is set to `Some` along with [`GitSource::path_source`].
+    sparse: Option<SparseCheckout>,
+    /// Where to check out the repository instead of Cargo's Git cache
+    /// directory, see [`GitSource::set_checkouts_path`].
+    checkouts_path: Option<PathBuf>,
     gctx: &'gctx GlobalContext,
     /// Disables status messages.
     quiet: bool,
@@ -121,6 +139,8 @@ impl<'gctx> GitSource<'gctx> {
             path_source: None,
             short_id: None,
             ident: ident.into(),
+            sparse: None,
+            checkouts_path: None,
             gctx,
             quiet: false,
         };
@@ -128,6 +148,13 @@ impl<'gctx> GitSource<'gctx> {
         Ok(source)
     }
 
+    /// Checks out the repository in `path` rather than in the `checkouts`
+    /// directory of Cargo's Git cache, still under the [`ident`] of the
+    /// source and the short id of the revision.
+    pub fn set_checkouts_path(&mut self, path: PathBuf) {
+        self.checkouts_path = Some(path);
+    }
+
     /// Gets the remote repository URL.
     pub fn url(&self) -> &Url {
         self.remote.url()
@@ -141,9 +168,53 @@ impl<'gctx> GitSource<'gctx> {
             self.invalidate_cache();
             self.block_until_ready()?;
         }
+        // All the packages are needed.
+        self.materialize(None)?;
         self.path_source.as_mut().unwrap().read_packages()
     }
 
+    /// Checks out the package `name`, or all of them if `None`, with their
+    /// path dependencies, when this is a sparse checkout which doesn't have
+    /// them yet. The packages are then discovered again.
+    fn materialize(&mut self, name: Option<InternedString>) -> CargoResult<()> {
+        let Some(sparse) = self.sparse.as_mut() else {
+            return Ok(());
+        };
+        let paths = match name {
+            Some(name) => {
+                if sparse.all || !sparse.names.insert(name) {
+                    return Ok(());
+                }
+                sparse.tree.paths(|pkg| pkg == name.as_str())
+            }
+            None => {
+                if sparse.all {
+                    return Ok(());
+                }
+                sparse.all = true;
+                sparse.tree.paths(|_| true)
+            }
+        };
+        if paths.is_empty() {
+            return Ok(());
+        }
+
+        let _lock = self
+            .gctx
+            .acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+        sparse
+            .db
+            .sparse_copy_to(sparse.rev, &sparse.path, &paths, self.gctx)?;
+
+        let source_id = self
+            .source_id
+            .with_git_precise(Some(sparse.rev.to_string()));
+        let mut path_source = PathSource::new_recursive(&sparse.path, source_id, self.gctx);
+        path_source.update()?;
+        self.path_source = Some(path_source);
+        Ok(())
+    }
+
     fn mark_used(&self, size: Option<u64>) -> CargoResult<()> {
         self.gctx
             .deferred_global_last_use()?
@@ -156,6 +227,22 @@ impl<'gctx> GitSource<'gctx> {
     }
 }
 
+/// The state of a sparse checkout of a [`GitSource`].
+struct SparseCheckout {
+    /// The database the checkout is from.
+    db: GitDatabase,
+    /// The tree of the revision of the checkout.
+    tree: SparseTree,
+    /// The revision of the checkout.
+    rev: git2::Oid,
+    /// The path of the checkout.
+    path: PathBuf,
+    /// The names of the packages queried so far.
+    names: HashSet<InternedString>,
+    /// Whether all the packages were asked for.
+    all: bool,
+}
+
 /// Indicates a [Git revision] that might be locked or deferred to be resolved.
 ///
 /// [Git revision]: https://git-scm.com/docs/revisions
@@ -251,6 +338,11 @@ impl<'gctx> Source for GitSource<'gctx> {
         kind: QueryKind,
         f: &mut dyn FnMut(IndexSummary),
     ) -> Poll<CargoResult<()>> {
+        if self.path_source.is_some() {
+            if let Err(e) = self.materialize(Some(dep.package_name())) {
+                return Poll::Ready(Err(e));
+            }
+        }
         if let Some(src) = self.path_source.as_mut() {
             src.query(dep, kind, f)
         } else {
@@ -345,17 +437,36 @@ impl<'gctx> Source for GitSource<'gctx> {
         // path length limit on Windows. See
         // <https://github.com/servo/servo/pull/14397>.
         let short_id = db.to_short_id(actual_rev)?;
+        let sparse = self
+            .gctx
+            .cli_unstable()
+            .git
+            .map_or(false, |features| features.sparse_checkout);
+        let short_id = if sparse {
+            format!("{}-sparse", short_id.as_str())
+        } else {
+            short_id.as_str().to_string()
+        };
 
         // Check out `actual_rev` from the database to a scoped location on the
         // filesystem. This will use hard links and such to ideally make the
         // checkout operation here pretty fast.
-        let checkout_path = self
-            .gctx
-            .git_checkouts_path()
-            .join(&self.ident)
-            .join(short_id.as_str());
-        let checkout_path = checkout_path.into_path_unlocked();
-        db.copy_to(actual_rev, &checkout_path, self.gctx)?;
+        let checkout_path = match &self.checkouts_path {
+            Some(path) => path.join(self.ident.as_str()).join(&short_id),
+            None => self
+                .gctx
+                .git_checkouts_path()
+                .join(&self.ident)
+                .join(&short_id)
+                .into_path_unlocked(),
+        };
+        if sparse {
+            // Only what was materialized by previous builds, the rest is
+            // checked out when queried.
+            db.sparse_copy_to(actual_rev, &checkout_path, &[], self.gctx)?;
+        } else {
+            db.copy_to(actual_rev, &checkout_path, self.gctx)?;
+        }
 
         let source_id = self
             .source_id
@@ -366,6 +477,16 @@ impl<'gctx> Source for GitSource<'gctx> {
         self.short_id = Some(short_id.as_str().into());
         self.locked_rev = Revision::Locked(actual_rev);
         self.path_source.as_mut().unwrap().update()?;
+        if sparse {
+            self.sparse = Some(SparseCheckout {
+                tree: db.sparse_tree(actual_rev, self.gctx)?,
+                db,
+                rev: actual_rev,
+                path: checkout_path.clone(),
+                names: HashSet::new(),
+                all: false,
+            });
+        }
 
         // Hopefully this shouldn't incur too much of a performance hit since
         // most of this should already be in cache since it was just
@@ -382,6 +503,7 @@ impl<'gctx> Source for GitSource<'gctx> {
             self.remote
         );
         self.mark_used(None)?;
+        self.materialize(Some(id.name()))?;
         self.path_source
             .as_mut()
             .expect("BUG: `update()` must be called before `get()`")
@@ -0,0 +1,269 @@
+//! Sparse checkouts of git dependencies, for `-Zgit=sparse-checkout`.
+//!
+//! Rather than the whole tree of a revision, a sparse checkout only
+//! materializes the packages which are asked for, along with what they need
+//! from the rest of the repository, see [`SparseTree::paths`]. This keeps the
+//! checkouts of dependencies living in large repositories small.
+//!
+//! When fetching with the git CLI, the database of a sparse checkout is
+//! fetched with `--filter=blob:none`, and only the blobs of the paths which
+//! get checked out are fetched afterwards.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::util::errors::CargoResult;
+
+/// The files and manifests of the tree of a revision, used to know which
+/// paths of it to check out.
+pub struct SparseTree {
+    /// The paths of the files of the tree.
+    files: Vec<String>,
+    /// The paths of the submodules of the tree.
+    submodules: Vec<String>,
+    /// The manifests of the tree, by the path of their directory, which is
+    /// empty for the root of the repository.
+    manifests: BTreeMap<String, toml::Table>,
+}
+
+impl SparseTree {
+    /// Reads the tree of the revision `rev` of `repo`, calling `fetch_blobs`
+    /// with the blobs of its manifests before reading them.
+    pub fn new(
+        repo: &git2::Repository,
+        rev: git2::Oid,
+        fetch_blobs: impl FnOnce(&[git2::Oid]) -> CargoResult<()>,
+    ) -> CargoResult<SparseTree> {
+        let tree = repo.find_object(rev, None)?.peel_to_tree()?;
+        let mut files = Vec::new();
+        let mut submodules = Vec::new();
+        let mut manifest_blobs = Vec::new();
+        tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+            let (Some(name), Some(kind)) = (entry.name(), entry.kind()) else {
+                return git2::TreeWalkResult::Ok;
+            };
+            if kind == git2::ObjectType::Blob && name == "Cargo.toml" {
+                let dir = root.trim_end_matches('/').to_string();
+                manifest_blobs.push((dir, entry.id()));
+            }
+            match kind {
+                git2::ObjectType::Blob => files.push(format!("{root}{name}")),
+                git2::ObjectType::Commit => submodules.push(format!("{root}{name}")),
+                _ => {}
+            }
+            git2::TreeWalkResult::Ok
+        })?;
+
+        let ids = manifest_blobs.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+        fetch_blobs(&ids)?;
+        let mut manifests = BTreeMap::new();
+        for (dir, id) in manifest_blobs {
+            let blob = repo.find_blob(id)?;
+            // Malformed manifests are skipped, like when reading the packages
+            // of the checkout.
+            let manifest = std::str::from_utf8(blob.content())
+                .ok()
+                .and_then(|contents| contents.parse::<toml::Table>().ok());
+            if let Some(manifest) = manifest {
+                manifests.insert(dir, manifest);
+            }
+        }
+        Ok(SparseTree {
+            files,
+            submodules,
+            manifests,
+        })
+    }
+
+    /// The paths to check out for the packages whose name matches `filter`.
+    ///
+    /// These are the files of the directories of the packages, without the
+    /// ones of the packages nested in them, and the same for their path
+    /// dependencies, transitively, along with the paths their manifests refer
+    /// to, like `build = "../build.rs"`. The manifests of the directories
+    /// above them, which may be their workspace root, and `.gitmodules` come
+    /// along, as well as the files which don't belong to any package, as
+    /// they may be shared by the packages, like with `include_str!`.
+    ///
+    /// Submodules are only checked out in the directories of the packages,
+    /// or when their manifests refer to them.
+    pub fn paths(&self, filter: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut packages = BTreeSet::new();
+        let mut stack = self
+            .manifests
+            .iter()
+            .filter(|(_, manifest)| package_name(manifest).is_some_and(&filter))
+            .map(|(dir, _)| dir.clone())
+            .collect::<Vec<_>>();
+        while let Some(dir) = stack.pop() {
+            if !packages.contains(&dir) {
+                stack.extend(self.path_dependencies(&dir));
+                packages.insert(dir);
+            }
+        }
+
+        let referenced = packages
+            .iter()
+            .flat_map(|dir| self.referenced_paths(dir))
+            .collect::<Vec<_>>();
+        let is_referenced = |path: &str| {
+            referenced.iter().any(|referenced| {
+                path == referenced || ancestors(path).any(|dir| dir == referenced)
+            })
+        };
+
+        let mut paths = BTreeSet::new();
+        for file in &self.files {
+            let owner = self.owner(file);
+            let shared = owner.map_or(true, |dir| package_name(&self.manifests[dir]).is_none());
+            if owner.is_some_and(|dir| packages.contains(dir))
+                || shared
+                || is_referenced(file)
+                || file == ".gitmodules"
+            {
+                paths.insert(file.clone());
+            }
+        }
+        for submodule in &self.submodules {
+            if self
+                .owner(submodule)
+                .is_some_and(|dir| packages.contains(dir))
+                || is_referenced(submodule)
+            {
+                paths.insert(submodule.clone());
+            }
+        }
+        for dir in &packages {
+            for ancestor in ancestors(dir) {
+                if self.manifests.contains_key(ancestor) {
+                    paths.insert(in_dir(ancestor, "Cargo.toml"));
+                }
+            }
+        }
+        paths.into_iter().collect()
+    }
+
+    /// The directory of the closest manifest above `path`.
+    fn owner<'a>(&self, path: &'a str) -> Option<&'a str> {
+        ancestors(path).find(|dir| self.manifests.contains_key(*dir))
+    }
+
+    /// The paths which the manifest of the package in `dir` refers to, which
+    /// may be outside of its directory.
+    fn referenced_paths(&self, dir: &str) -> Vec<String> {
+        let manifest = &self.manifests[dir];
+        let mut paths = Vec::new();
+        if let Some(package) = manifest.get("package") {
+            for key in ["build", "readme", "license-file", "license_file"] {
+                paths.extend(package.get(key).and_then(|path| path.as_str()));
+            }
+        }
+        paths.extend(
+            manifest
+                .get("lib")
+                .and_then(|lib| lib.get("path")?.as_str()),
+        );
+        for key in ["bin", "example", "test", "bench"] {
+            let targets = manifest.get(key).and_then(|targets| targets.as_array());
+            paths.extend(
+                targets
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|target| target.get("path")?.as_str()),
+            );
+        }
+        paths
+            .into_iter()
+            .filter_map(|path| join(dir, path))
+            .collect()
+    }
+
+    /// The directories of the path dependencies of the package in `dir`,
+    /// leaving out its dev-dependencies.
+    fn path_dependencies(&self, dir: &str) -> Vec<String> {
+        let manifest = &self.manifests[dir];
+        let targets = manifest
+            .get("target")
+            .and_then(|targets| targets.as_table())
+            .into_iter()
+            .flat_map(|targets| targets.values().filter_map(|target| target.as_table()));
+        let mut tables = Vec::new();
+        for table in std::iter::once(manifest).chain(targets) {
+            for key in ["dependencies", "build-dependencies", "build_dependencies"] {
+                tables.extend(table.get(key).and_then(|deps| deps.as_table()));
+            }
+        }
+
+        let mut dirs = Vec::new();
+        for (name, dep) in tables.into_iter().flatten() {
+            let path = match dep.get("path").and_then(|path| path.as_str()) {
+                Some(path) => join(dir, path),
+                None if dep.get("workspace").and_then(|w| w.as_bool()) == Some(true) => {
+                    self.workspace_dependency_path(dir, name)
+                }
+                None => None,
+            };
+            dirs.extend(path.filter(|path| self.manifests.contains_key(path)));
+        }
+        dirs
+    }
+
+    /// The directory of the path dependency `name` of the workspace of the
+    /// package in `dir`, which is the closest one at or above it.
+    fn workspace_dependency_path(&self, dir: &str, name: &str) -> Option<String> {
+        let (root, workspace) = std::iter::once(dir)
+            .chain(ancestors(dir))
+            .find_map(|dir| Some((dir, self.manifests.get(dir)?.get("workspace")?)))?;
+        let path = workspace.get("dependencies")?.get(name)?.get("path")?;
+        join(root, path.as_str()?)
+    }
+}
+
+/// The name of the package of `manifest`, if it has one.
+fn package_name(manifest: &toml::Table) -> Option<&str> {
+    manifest.get("package")?.get("name")?.as_str()
+}
+
+/// The directories above `path` in the tree, from the closest one.
+fn ancestors(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(path), |&path| {
+        (!path.is_empty()).then(|| path.rfind('/').map_or("", |i| &path[..i]))
+    })
+    .skip(1)
+}
+
+/// The path of `name` in the directory `dir` of the tree.
+fn in_dir(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{dir}/{name}")
+    }
+}
+
+/// Joins the relative `path` to the directory `dir` of the tree, returning
+/// `None` if it leaves the tree.
+fn join(dir: &str, path: &str) -> Option<String> {
+    let mut components = dir
+        .split('/')
+        .filter(|component| !component.is_empty())
+        .collect::<Vec<_>>();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop()?;
+            }
+            component => components.push(component),
+        }
+    }
+    Some(components.join("/"))
+}
+
+#[test]
+fn joins_paths_in_tree() {
+    assert_eq!(join("crates/foo", "../bar").as_deref(), Some("crates/bar"));
+    assert_eq!(join("", "./crates/bar/").as_deref(), Some("crates/bar"));
+    assert_eq!(join("crates", "../.."), None);
+    assert_eq!(ancestors("a/b/c").collect::<Vec<_>>(), ["a/b", "a", ""]);
+    assert_eq!(ancestors("").count(), 0);
+}
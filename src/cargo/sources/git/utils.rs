@@ -5,9 +5,10 @@ use crate::core::{GitReference, Verbosity};
 use crate::sources::git::fetch::RemoteKind;
 use crate::sources::git::oxide;
 use crate::sources::git::oxide::cargo_config_to_gitoxide_overrides;
+use crate::sources::git::sparse::SparseTree;
 use crate::util::errors::CargoResult;
 use crate::util::{
-    human_readable_bytes, network, GlobalContext, IntoUrl, MetricsCounter, Progress,
+    auth, human_readable_bytes, network, GlobalContext, IntoUrl, MetricsCounter, Progress,
 };
 use anyhow::{anyhow, Context as _};
 use cargo_util::{paths, ProcessBuilder};
@@ -16,6 +17,7 @@ use git2::{ErrorClass, ObjectType, Oid};
 use serde::ser;
 use serde::Serialize;
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fmt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -29,6 +31,10 @@ use url::Url;
 /// checkout is ready to go. See [`GitCheckout::reset`] for why we need this.
 const CHECKOUT_READY_LOCK: &str = ".cargo-ok";
 
+/// A file listing the paths materialized in a sparse checkout, one per line.
+/// See [`GitCheckout::materialize`].
+const SPARSE_PATHS: &str = ".cargo-sparse";
+
 fn serialize_str<T, S>(t: &T, s: S) -> Result<S::Ok, S::Error>
 where
     T: fmt::Display,
@@ -180,12 +186,64 @@ impl GitDatabase {
             .filter(|co| co.is_fresh())
         {
             Some(co) => co,
-            None => GitCheckout::clone_into(dest, self, rev, gctx)?,
+            None => {
+                // A sparse checkout may have left the database without the
+                // blobs of the revision.
+                let tree = self.repo.find_object(rev, None)?.peel_to_tree()?;
+                let mut ids = Vec::new();
+                tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+                    if entry.kind() == Some(ObjectType::Blob) {
+                        ids.push(entry.id());
+                    }
+                    git2::TreeWalkResult::Ok
+                })?;
+                fetch_missing_blobs(&self.repo, &ids, gctx)?;
+                GitCheckout::clone_into(dest, self, rev, false, gctx)?
+            }
+        };
+        checkout.update_submodules(gctx, |_| true)?;
+        Ok(checkout)
+    }
+
+    /// Like [`GitDatabase::copy_to`], but only checks out the `paths` of the
+    /// revision, on top of the ones already materialized at `dest`ination.
+    pub fn sparse_copy_to(
+        &self,
+        rev: git2::Oid,
+        dest: &Path,
+        paths: &[String],
+        gctx: &GlobalContext,
+    ) -> CargoResult<GitCheckout<'_>> {
+        let checkout = match git2::Repository::open(dest)
+            .ok()
+            .map(|repo| GitCheckout::new(self, rev, repo))
+            .filter(|co| co.is_fresh())
+        {
+            Some(co) => co,
+            None => GitCheckout::clone_into(dest, self, rev, true, gctx)?,
         };
-        checkout.update_submodules(gctx)?;
+        checkout.materialize(paths, gctx)?;
+        let materialized = paths::read(&dest.join(SPARSE_PATHS))?;
+        let materialized = materialized.lines().collect::<HashSet<_>>();
+        checkout.update_submodules(gctx, |path| {
+            path.to_str()
+                .is_some_and(|path| materialized.contains(path))
+        })?;
         Ok(checkout)
     }
 
+    /// Reads the tree of a `revision`, to know which of its paths a sparse
+    /// checkout needs.
+    pub fn sparse_tree(
+        &self,
+        revision: git2::Oid,
+        gctx: &GlobalContext,
+    ) -> CargoResult<SparseTree> {
+        SparseTree::new(&self.repo, revision, |ids| {
+            fetch_missing_blobs(&self.repo, ids, gctx)
+        })
+    }
+
     /// Get a short OID for a `revision`, usually 7 chars or more if ambiguous.
     pub fn to_short_id(&self, revision: git2::Oid) -> CargoResult<GitShortID> {
         let obj = self.repo.find_object(revision, None)?;
@@ -274,10 +332,14 @@ impl<'a> GitCheckout<'a> {
 
     /// Clone a repo for a `revision` into a local path from a `datatabase`.
     /// This is a filesystem-to-filesystem clone.
+    ///
+    /// A `sparse` checkout starts without any file, see
+    /// [`GitCheckout::materialize`].
     fn clone_into(
         into: &Path,
         database: &'a GitDatabase,
         revision: git2::Oid,
+        sparse: bool,
         gctx: &GlobalContext,
     ) -> CargoResult<GitCheckout<'a>> {
         let dirname = into.parent().unwrap();
@@ -322,13 +384,29 @@ impl<'a> GitCheckout<'a> {
                     r.path().join("shallow"),
                 )?;
             }
+            // The blobs of a sparse checkout are fetched into the database
+            // as they get checked out, so it needs to see its later objects.
+            if sparse {
+                let objects = database.repo.path().join("objects");
+                let objects = objects
+                    .to_str()
+                    .ok_or_else(|| anyhow::format_err!("non-utf8 path {}", objects.display()))?;
+                paths::write(
+                    r.path().join("objects/info/alternates"),
+                    format!("{objects}\n"),
+                )?;
+            }
             repo = Some(r);
             Ok(())
         })?;
         let repo = repo.unwrap();
 
         let checkout = GitCheckout::new(database, revision, repo);
-        checkout.reset(gctx)?;
+        if sparse {
+            checkout.init_sparse()?;
+        } else {
+            checkout.reset(gctx)?;
+        }
         Ok(checkout)
     }
 
@@ -373,14 +451,111 @@ impl<'a> GitCheckout<'a> {
         Ok(())
     }
 
+    /// Like [`GitCheckout::reset`], but for a sparse checkout, only moving
+    /// the `HEAD` to the revision of this checkout, without checking out any
+    /// file.
+    fn init_sparse(&self) -> CargoResult<()> {
+        let ok_file = self.path.join(CHECKOUT_READY_LOCK);
+        let _ = paths::remove_file(&ok_file);
+        info!(
+            "init sparse {} at {}",
+            self.repo.path().display(),
+            self.revision
+        );
+
+        if let Ok(mut git_config) = self.repo.config() {
+            git_config.set_bool("core.autocrlf", false)?;
+        }
+
+        self.repo.set_head_detached(self.revision)?;
+        paths::write(self.path.join(SPARSE_PATHS), "")?;
+        paths::create(ok_file)?;
+        Ok(())
+    }
+
+    /// Checks out the `paths` of the revision of this sparse checkout which
+    /// aren't materialized yet, and adds them to [`SPARSE_PATHS`].
+    ///
+    /// The paths are only recorded once checked out, so that an interrupted
+    /// checkout gets done again.
+    fn materialize(&self, paths: &[String], gctx: &GlobalContext) -> CargoResult<()> {
+        let sparse_paths = self.path.join(SPARSE_PATHS);
+        let materialized = paths::read(&sparse_paths)?;
+        let materialized = materialized.lines().collect::<HashSet<_>>();
+        let missing = paths
+            .iter()
+            .filter(|path| !materialized.contains(path.as_str()))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        info!(
+            "materialize {} paths in {}",
+            missing.len(),
+            self.repo.path().display()
+        );
+
+        let tree = self.repo.find_object(self.revision, None)?.peel_to_tree()?;
+        let ids = missing
+            .iter()
+            .filter_map(|path| tree.get_path(Path::new(path.as_str())).ok())
+            .filter(|entry| entry.kind() == Some(ObjectType::Blob))
+            .map(|entry| entry.id())
+            .collect::<Vec<_>>();
+        fetch_missing_blobs(&self.database.repo, &ids, gctx)?;
+
+        let mut pb = Progress::new("Checkout", gctx);
+        let mut opts = git2::build::CheckoutBuilder::new();
+        opts.force()
+            .recreate_missing(true)
+            .disable_pathspec_match(true)
+            .progress(|_, cur, max| {
+                drop(pb.tick(cur, max, ""));
+            });
+        for path in &missing {
+            opts.path(path.as_str());
+        }
+        self.repo.checkout_tree(tree.as_object(), Some(&mut opts))?;
+
+        let mut contents = String::new();
+        for path in missing {
+            contents.push_str(path);
+            contents.push('\n');
+        }
+        paths::append(&sparse_paths, contents.as_bytes())?;
+        Ok(())
+    }
+
     /// Like `git submodule update --recursive` but for this git checkout.
     ///
     /// This function respects `submodule.<name>.update = none`[^1] git config.
     /// Submodules set to `none` won't be fetched.
     ///
+    /// Only the submodules whose path matches `filter` are updated, and
+    /// all the submodules nested in them.
+    ///
     /// [^1]: <https://git-scm.com/docs/git-submodule#Documentation/git-submodule.txt-none>
-    fn update_submodules(&self, gctx: &GlobalContext) -> CargoResult<()> {
-        return update_submodules(&self.repo, gctx, self.remote_url().as_str());
+    fn update_submodules(
+        &self,
+        gctx: &GlobalContext,
+        filter: impl Fn(&Path) -> bool,
+    ) -> CargoResult<()> {
+        debug!("update submodules for: {:?}", self.path);
+        let parent_remote_url = self.remote_url().as_str();
+        for mut child in self.repo.submodules()? {
+            if !filter(child.path()) {
+                continue;
+            }
+            update_submodule(&self.repo, &mut child, gctx, parent_remote_url).with_context(
+                || {
+                    format!(
+                        "failed to update submodule `{}`",
+                        child.name().unwrap_or("")
+                    )
+                },
+            )?;
+        }
+        return Ok(());
 
         /// Recursive helper for [`GitCheckout::update_submodules`].
         fn update_submodules(
@@ -570,7 +745,10 @@ where
     cred_helper.config(cfg);
 
     let mut ssh_username_requested = false;
+    let mut tried_provider = false;
+    let mut provider_attempt = None;
     let mut cred_helper_bad = None;
+    let mut tried_default = false;
     let mut ssh_agent_attempts = Vec::new();
     let mut any_attempts = false;
     let mut tried_sshkey = false;
@@ -628,10 +806,29 @@ where
 
         // Sometimes libgit2 will ask for a username/password in plaintext. This
         // is where Cargo would have an interactive prompt if we supported it,
-        // but we currently don't! Right now the only way we support fetching a
-        // plaintext password is through the `credential.helper` support, so
-        // fetch that here.
-        //
+        // but we currently don't! Right now the only ways we support fetching
+        // a plaintext password are the credential provider configured for the
+        // host in `net.git-credentials`, tried first, and the
+        // `credential.helper` support.
+        if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) && !tried_provider {
+            tried_provider = true;
+            match auth::git_credential(gctx, url, username) {
+                Ok(None) => {}
+                Ok(Some(auth::GitCredential {
+                    provider,
+                    credentials: Some((user, password)),
+                })) => {
+                    provider_attempt = Some(Ok((provider, true)));
+                    return git2::Cred::userpass_plaintext(&user, password.as_deref().expose());
+                }
+                Ok(Some(auth::GitCredential {
+                    provider,
+                    credentials: None,
+                })) => provider_attempt = Some(Ok((provider, false))),
+                Err(e) => provider_attempt = Some(Err(e)),
+            }
+        }
+
         // If ssh-agent authentication fails, libgit2 will keep calling this
         // callback asking for other authentication methods to try. Check
         // cred_helper_bad to make sure we only try the git credential helper
@@ -646,6 +843,7 @@ where
         // I'm... not sure what the DEFAULT kind of authentication is, but seems
         // easy to support?
         if allowed.contains(git2::CredentialType::DEFAULT) {
+            tried_default = true;
             return git2::Cred::default();
         }
 
@@ -743,6 +941,23 @@ where
                  no usernames succeeded: {}",
                 names
             ));
+            msg.push_str("\n  ");
+            msg.push_str(&ssh_agent_hint(gctx));
+        }
+        match &provider_attempt {
+            None => {}
+            Some(Ok((provider, true))) => msg.push_str(&format!(
+                "\n* attempted to find username/password via credential \
+                 provider `{provider}`, but maybe the found credentials were incorrect"
+            )),
+            Some(Ok((provider, false))) => msg.push_str(&format!(
+                "\n* attempted to find username/password via credential \
+                 provider `{provider}`, but it had no credentials for this repository"
+            )),
+            Some(Err(e)) => msg.push_str(&format!(
+                "\n* attempted to find username/password via \
+                 `net.git-credentials`, but failed: {e:#}"
+            )),
         }
         if let Some(failed_cred_helper) = cred_helper_bad {
             if failed_cred_helper {
@@ -758,6 +973,12 @@ where
                 );
             }
         }
+        if tried_default {
+            msg.push_str(
+                "\n* attempted the default credentials of the system, \
+                 such as Kerberos, but they were not accepted",
+            );
+        }
         msg.push_str("\n\n");
         msg.push_str("if the git CLI succeeds then `net.git-fetch-with-cli` may help here\n");
         msg.push_str("https://doc.rust-lang.org/cargo/reference/config.html#netgit-fetch-with-cli");
@@ -797,6 +1018,25 @@ where
     Err(err)
 }
 
+/// Explains why the keys of ssh-agent may not have been offered.
+fn ssh_agent_hint(gctx: &GlobalContext) -> String {
+    // On Windows, the agent is reached through a named pipe instead.
+    if cfg!(windows) {
+        return "the keys of ssh-agent can be listed with `ssh-add -l`".to_string();
+    }
+    match gctx.get_env_os("SSH_AUTH_SOCK") {
+        None => "`SSH_AUTH_SOCK` is not set, so no ssh-agent could be reached".to_string(),
+        Some(sock) if !Path::new(&sock).exists() => format!(
+            "`SSH_AUTH_SOCK` is set to `{}`, which doesn't exist, \
+             so no ssh-agent could be reached",
+            Path::new(&sock).display()
+        ),
+        Some(_) => {
+            "ssh-agent was reached, the keys it offered can be listed with `ssh-add -l`".to_string()
+        }
+    }
+}
+
 /// `git reset --hard` to the given `obj` for the `repo`.
 ///
 /// The `obj` is a commit-ish to which the head should be moved.
@@ -831,7 +1071,7 @@ pub fn with_fetch_options(
     let config_known_hosts = ssh_config.and_then(|ssh| ssh.known_hosts.as_ref());
     let diagnostic_home_config = gctx.diagnostic_home_config();
     network::retry::with_retry(gctx, || {
-        with_authentication(gctx, url, git_config, |f| {
+        let result = with_authentication(gctx, url, git_config, |f| {
             let port = Url::parse(url).ok().and_then(|url| url.port());
             let mut last_update = Instant::now();
             let mut rcb = git2::RemoteCallbacks::new();
@@ -879,9 +1119,11 @@ pub fn with_fetch_options(
                     let (rate, unit) = human_readable_bytes(counter.rate() as u64);
                     format!(", {:.2}{}/s", rate, unit)
                 };
+                // Returning `false` aborts the transfer.
                 progress
                     .tick(stats.indexed_objects(), stats.total_objects(), &msg)
                     .is_ok()
+                    && !gctx.cancellation_token().is_cancelled()
             });
 
             // Create a local anonymous remote in the repository to fetch the
@@ -889,8 +1131,11 @@ pub fn with_fetch_options(
             let mut opts = git2::FetchOptions::new();
             opts.remote_callbacks(rcb);
             cb(opts)
-        })?;
-        Ok(())
+        });
+        // An aborted transfer fails with an error from git, report the
+        // cancellation instead.
+        gctx.cancellation_token().check()?;
+        result
     })
 }
 
@@ -989,7 +1234,16 @@ pub fn fetch(
     }
 
     if let Some(true) = gctx.net_config()?.git_fetch_with_cli {
-        return fetch_with_cli(repo, remote_url, &refspecs, tags, gctx);
+        // Sparse checkouts fetch the blobs they need later on into the
+        // database, see `fetch_missing_blobs`. Submodules are checked out
+        // whole, their repository not being bare.
+        let blobless = matches!(remote_kind, RemoteKind::GitDependency)
+            && repo.is_bare()
+            && gctx
+                .cli_unstable()
+                .git
+                .map_or(false, |features| features.sparse_checkout);
+        return fetch_with_cli(repo, remote_url, &refspecs, tags, blobless, gctx);
     }
 
     if gctx.cli_unstable().gitoxide.map_or(false, |git| git.fetch) {
@@ -1164,11 +1418,15 @@ fn has_shallow_lock_file(err: &crate::sources::git::fetch::Error) -> bool {
 /// speed and portability of using `libgit2`.
 ///
 /// [1]: https://doc.rust-lang.org/nightly/cargo/reference/config.html#netgit-fetch-with-cli
+///
+/// A `blobless` fetch leaves out the blobs of the trees it fetches, turning
+/// the repository into a partial clone of `url`, see [`fetch_missing_blobs`].
 fn fetch_with_cli(
     repo: &mut git2::Repository,
     url: &str,
     refspecs: &[String],
     tags: bool,
+    blobless: bool,
     gctx: &GlobalContext,
 ) -> CargoResult<()> {
     let mut cmd = ProcessBuilder::new("git");
@@ -1176,6 +1434,14 @@ fn fetch_with_cli(
     if tags {
         cmd.arg("--tags");
     }
+    if blobless {
+        let mut config = repo.config()?;
+        config.set_str("remote.origin.url", url)?;
+        config.set_bool("remote.origin.promisor", true)?;
+        config.set_str("remote.origin.partialclonefilter", "blob:none")?;
+        config.set_str("extensions.partialclone", "origin")?;
+        cmd.arg("--filter=blob:none");
+    }
     match gctx.shell().verbosity() {
         Verbosity::Normal => {}
         Verbosity::Verbose => {
@@ -1207,6 +1473,65 @@ fn fetch_with_cli(
     Ok(())
 }
 
+/// Fetches the blobs of `ids` which `repo` lacks, when it is a partial clone
+/// made by a `blobless` [`fetch_with_cli`]. Otherwise it has all of them.
+fn fetch_missing_blobs(
+    repo: &git2::Repository,
+    ids: &[git2::Oid],
+    gctx: &GlobalContext,
+) -> CargoResult<()> {
+    if repo
+        .config()?
+        .get_string("extensions.partialclone")
+        .is_err()
+    {
+        return Ok(());
+    }
+    let odb = repo.odb()?;
+    let missing = ids
+        .iter()
+        .filter(|id| !odb.exists(**id))
+        .map(|id| format!("{id}\n"))
+        .collect::<String>();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    if gctx.frozen() {
+        anyhow::bail!(
+            "attempting to update a git repository, but --frozen \
+             was specified"
+        )
+    }
+    if !gctx.network_allowed() {
+        anyhow::bail!("can't update a git repository in the offline mode")
+    }
+
+    // Like the fetches git itself does for the missing objects of a partial
+    // clone, asking for the blobs without negotiating what we have.
+    let mut cmd = ProcessBuilder::new("git");
+    cmd.arg("-c")
+        .arg("fetch.negotiationAlgorithm=noop")
+        .arg("fetch")
+        .arg("--quiet")
+        .arg("--no-tags")
+        .arg("--no-write-fetch-head")
+        .arg("--recurse-submodules=no")
+        .arg("--filter=blob:none")
+        .arg("--stdin")
+        .arg("origin")
+        .env_remove("GIT_DIR")
+        .env_remove("GIT_WORK_TREE")
+        .env_remove("GIT_INDEX_FILE")
+        .env_remove("GIT_OBJECT_DIRECTORY")
+        .env_remove("GIT_ALTERNATE_OBJECT_DIRECTORIES")
+        .cwd(repo.path())
+        .stdin(missing);
+    gctx.shell()
+        .verbose(|s| s.status("Running", &cmd.to_string()))?;
+    cmd.exec()?;
+    Ok(())
+}
+
 /// Attempts to `git gc` a repository.
 ///
 /// Cargo has a bunch of long-lived git repositories in its global cache and
@@ -1503,7 +1828,7 @@ fn is_github(url: &Url) -> bool {
 }
 
 /// Whether a `rev` looks like a commit hash (ASCII hex digits).
-fn looks_like_commit_hash(rev: &str) -> bool {
+pub(crate) fn looks_like_commit_hash(rev: &str) -> bool {
     rev.len() >= 7 && rev.chars().all(|ch| ch.is_ascii_hexdigit())
 }
 
@@ -8,7 +8,7 @@ use crate::sources::registry::{LoadResponse, RegistryConfig, RegistryData};
 use crate::util::cache_lock::CacheLockMode;
 use crate::util::errors::{CargoResult, HttpNotSuccessful};
 use crate::util::interning::InternedString;
-use crate::util::network::http::http_handle;
+use crate::util::network::http::http_handle_for_registry;
 use crate::util::network::retry::{Retry, RetryResult};
 use crate::util::network::sleep::SleepTracker;
 use crate::util::{auth, Filesystem, GlobalContext, IntoUrl, Progress, ProgressStyle};
@@ -132,6 +132,9 @@ struct Downloads<'gctx> {
     /// Number of times the caller has requested blocking. This is used for
     /// an estimate of progress.
     blocking_calls: usize,
+    /// Whether the caller is polling the pending downloads, which counts as
+    /// a single blocking call until they are all done.
+    polling: bool,
 }
 
 /// Represents a single index file download, including its progress and retry.
@@ -225,6 +228,7 @@ impl<'gctx> HttpRegistry<'gctx> {
                 ))),
                 downloads_finished: 0,
                 blocking_calls: 0,
+                polling: false,
             },
             fresh: HashSet::new(),
             requested_update: false,
@@ -456,6 +460,23 @@ impl<'gctx> HttpRegistry<'gctx> {
         }
         Ok(())
     }
+
+    /// Does the work that is ready for the transfers without blocking, and
+    /// returns whether all of them are done.
+    fn perform(&mut self) -> CargoResult<bool> {
+        self.gctx.cancellation_token().check()?;
+        self.handle_completed_downloads()?;
+        self.add_sleepers()?;
+
+        let remaining_in_multi = tls::set(&self.downloads, || {
+            self.multi
+                .perform()
+                .with_context(|| "failed to perform http requests")
+        })?;
+        trace!(target: "network", "{} transfers remaining", remaining_in_multi);
+
+        Ok(remaining_in_multi + self.downloads.sleeping.len() as u32 == 0)
+    }
 }
 
 impl<'gctx> RegistryData for HttpRegistry<'gctx> {
@@ -626,7 +647,7 @@ impl<'gctx> RegistryData for HttpRegistry<'gctx> {
         // Looks like we're going to have to do a network request.
         self.start_fetch()?;
 
-        let mut handle = http_handle(self.gctx)?;
+        let mut handle = http_handle_for_registry(self.gctx, self.source_id)?;
         let full_url = self.full_url(path);
         debug!(target: "network", "fetch {}", full_url);
         handle.get(true)?;
@@ -796,17 +817,7 @@ impl<'gctx> RegistryData for HttpRegistry<'gctx> {
         self.downloads.blocking_calls += 1;
 
         loop {
-            self.handle_completed_downloads()?;
-            self.add_sleepers()?;
-
-            let remaining_in_multi = tls::set(&self.downloads, || {
-                self.multi
-                    .perform()
-                    .with_context(|| "failed to perform http requests")
-            })?;
-            trace!(target: "network", "{} transfers remaining", remaining_in_multi);
-
-            if remaining_in_multi + self.downloads.sleeping.len() as u32 == 0 {
+            if self.perform()? {
                 return Ok(());
             }
 
@@ -827,6 +838,20 @@ impl<'gctx> RegistryData for HttpRegistry<'gctx> {
             }
         }
     }
+
+    fn poll_ready(&mut self) -> Poll<CargoResult<()>> {
+        if !self.downloads.polling {
+            self.downloads.blocking_calls += 1;
+            self.downloads.polling = true;
+        }
+        match self.perform() {
+            Ok(false) => Poll::Pending,
+            result => {
+                self.downloads.polling = false;
+                Poll::Ready(result.map(drop))
+            }
+        }
+    }
 }
 
 impl<'gctx> Downloads<'gctx> {
@@ -37,6 +37,8 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::str;
 use std::task::{ready, Poll};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 use tracing::{debug, info};
 
 mod cache;
@@ -47,6 +49,10 @@ use self::cache::SummariesCache;
 /// cargo understands. See [`IndexPackage::v`] for the detail.
 const INDEX_V_MAX: u32 = 2;
 
+/// The schema version of the `v` field in the index adding feature profiles,
+/// which is understood regardless of [`INDEX_V_MAX`]. See [`IndexPackage::v`].
+const INDEX_V_FEATURE_PROFILES: u32 = 4;
+
 /// Manager for handling the on-disk index.
 ///
 /// Different kinds of registries store the index differently:
@@ -233,6 +239,23 @@ pub struct IndexPackage<'a> {
     /// Added in 2023 (see <https://github.com/rust-lang/crates.io/pull/6267>),
     /// can be `None` if published before then or if not set in the manifest.
     rust_version: Option<RustVersion>,
+    /// Deprecation status set by the registry, with a free-form reason.
+    ///
+    /// An empty string marks the package as deprecated without giving a
+    /// reason. Older versions of cargo ignore this field, and the package
+    /// is still a candidate for resolution.
+    #[serde(borrow)]
+    deprecated: Option<Cow<'a, str>>,
+    /// When the version was published, as an RFC 3339 timestamp.
+    ///
+    /// Used by `--before` to only consider versions published before a given
+    /// time. Can be `None` if the registry doesn't record it, in which case
+    /// the version is always considered.
+    #[serde(borrow)]
+    pubtime: Option<Cow<'a, str>>,
+    /// Named sets of features which dependents can use instead of the default
+    /// features, i.e., the `[features.profiles]` table.
+    feature_profiles: Option<BTreeMap<InternedString, Vec<InternedString>>>,
     /// The schema version for this entry.
     ///
     /// If this is None, it defaults to version `1`. Entries with unknown
@@ -243,6 +266,9 @@ pub struct IndexPackage<'a> {
     /// Version `3` schema adds `artifact`, `bindep_targes`, and `lib` for
     /// artifact dependencies support.
     ///
+    /// Version `4` schema adds `feature_profiles`, and `features_profile` for
+    /// dependencies.
+    ///
     /// This provides a method to safely introduce changes to index entries
     /// and allow older versions of cargo to ignore newer entries it doesn't
     /// understand. This is honored as of 1.51, so unfortunately older
@@ -282,7 +308,7 @@ struct RegistryDependency<'a> {
     registry: Option<Cow<'a, str>>,
     /// The original name if the dependency is renamed.
     package: Option<InternedString>,
-    /// Whether or not this is a public dependency. Unstable. See [RFC 1977].
+    /// Whether or not this is a public dependency. See [RFC 1977].
     ///
     /// [RFC 1977]: https://rust-lang.github.io/rfcs/1977-public-private-dependencies.html
     public: Option<bool>,
@@ -290,6 +316,8 @@ struct RegistryDependency<'a> {
     bindep_target: Option<Cow<'a, str>>,
     #[serde(default)]
     lib: bool,
+    /// The name of the feature profile used instead of the default features.
+    features_profile: Option<InternedString>,
 }
 
 impl<'gctx> RegistryIndex<'gctx> {
@@ -722,6 +750,9 @@ impl IndexSummary {
             yanked,
             links,
             rust_version,
+            deprecated,
+            pubtime,
+            feature_profiles,
             v,
         } = serde_json::from_slice(line)?;
         let v = v.unwrap_or(1);
@@ -738,6 +769,16 @@ impl IndexSummary {
         }
         let mut summary = Summary::new(pkgid, deps, &features, links, rust_version)?;
         summary.set_checksum(cksum);
+        if let Some(feature_profiles) = feature_profiles {
+            summary.set_feature_profiles(feature_profiles);
+        }
+        if let Some(deprecated) = deprecated {
+            summary.set_deprecated(deprecated.into_owned());
+        }
+        // An invalid timestamp is ignored rather than rejecting the entry.
+        if let Some(pubtime) = pubtime.and_then(|t| OffsetDateTime::parse(&t, &Rfc3339).ok()) {
+            summary.set_pubtime(pubtime);
+        }
 
         let v_max = if bindeps {
             INDEX_V_MAX + 1
@@ -745,7 +786,7 @@ impl IndexSummary {
             INDEX_V_MAX
         };
 
-        if v_max < v {
+        if v_max < v && v != INDEX_V_FEATURE_PROFILES {
             Ok(IndexSummary::Unsupported(summary, v))
         } else if yanked.unwrap_or(false) {
             Ok(IndexSummary::Yanked(summary))
@@ -772,6 +813,7 @@ impl<'a> RegistryDependency<'a> {
             artifact,
             bindep_target,
             lib,
+            features_profile,
         } = self;
 
         let id = if let Some(registry) = &registry {
@@ -819,6 +861,7 @@ impl<'a> RegistryDependency<'a> {
         dep.set_optional(optional)
             .set_default_features(default_features)
             .set_features(features)
+            .set_features_profile(features_profile)
             .set_platform(platform)
             .set_kind(kind)
             .set_public(public);
@@ -424,6 +424,13 @@ pub trait RegistryData {
 
     /// Block until all outstanding Poll::Pending requests are Poll::Ready.
     fn block_until_ready(&mut self) -> CargoResult<()>;
+
+    /// Makes progress on the outstanding requests without blocking.
+    ///
+    /// See [`Source::poll_ready`].
+    fn poll_ready(&mut self) -> Poll<CargoResult<()>> {
+        Poll::Ready(self.block_until_ready())
+    }
 }
 
 /// The status of [`RegistryData::download`] which indicates if a `.crate`
@@ -467,7 +474,47 @@ fn short_name(id: SourceId, is_shallow: bool) -> String {
     name
 }
 
+/// Like [`short_name`], for a remote registry whose index may be fetched
+/// shallowly.
+fn remote_short_name(id: SourceId, gctx: &GlobalContext) -> String {
+    short_name(
+        id,
+        gctx.cli_unstable()
+            .git
+            .map_or(false, |features| features.shallow_index)
+            && !id.is_sparse(),
+    )
+}
+
+/// Path to the `.crate` file of `pkg` in the download cache of its remote
+/// registry, whether or not it has been downloaded yet.
+pub fn cached_crate_path(gctx: &GlobalContext, pkg: PackageId) -> PathBuf {
+    assert!(pkg.source_id().is_remote_registry());
+    gctx.registry_cache_path()
+        .join(remote_short_name(pkg.source_id(), gctx))
+        .join(pkg.tarball_name())
+        .into_path_unlocked()
+}
+
 impl<'gctx> RegistrySource<'gctx> {
+    /// Marks `<cargo_home>/registry` as excluded from indexing and backups.
+    fn exclude_registry_base(&self) {
+        // Before starting to work on the registry, make sure that
+        // `<cargo_home>/registry` is marked as excluded from indexing and
+        // backups. Older versions of Cargo didn't do this, so we do it here
+        // regardless of whether `<cargo_home>` exists.
+        //
+        // This does not use `create_dir_all_excluded_from_backups_atomic` for
+        // the same reason: we want to exclude it even if the directory already
+        // exists.
+        //
+        // IO errors in creating and marking it are ignored, e.g. in case we're on a
+        // read-only filesystem.
+        let registry_base = self.gctx.registry_base_path();
+        let _ = registry_base.create_dir();
+        exclude_from_backups_and_indexing(&registry_base.into_path_unlocked());
+    }
+
     /// Creates a [`Source`] of a "remote" registry.
     /// It could be either an HTTP-based [`http_remote::HttpRegistry`] or
     /// a Git-based [`remote::RemoteRegistry`].
@@ -479,13 +526,7 @@ impl<'gctx> RegistrySource<'gctx> {
         gctx: &'gctx GlobalContext,
     ) -> CargoResult<RegistrySource<'gctx>> {
         assert!(source_id.is_remote_registry());
-        let name = short_name(
-            source_id,
-            gctx.cli_unstable()
-                .git
-                .map_or(false, |features| features.shallow_index)
-                && !source_id.is_sparse(),
-        );
+        let name = remote_short_name(source_id, gctx);
         let ops = if source_id.is_sparse() {
             Box::new(http_remote::HttpRegistry::new(source_id, gctx, &name)?) as Box<_>
         } else {
@@ -943,23 +984,14 @@ impl<'gctx> Source for RegistrySource<'gctx> {
     }
 
     fn block_until_ready(&mut self) -> CargoResult<()> {
-        // Before starting to work on the registry, make sure that
-        // `<cargo_home>/registry` is marked as excluded from indexing and
-        // backups. Older versions of Cargo didn't do this, so we do it here
-        // regardless of whether `<cargo_home>` exists.
-        //
-        // This does not use `create_dir_all_excluded_from_backups_atomic` for
-        // the same reason: we want to exclude it even if the directory already
-        // exists.
-        //
-        // IO errors in creating and marking it are ignored, e.g. in case we're on a
-        // read-only filesystem.
-        let registry_base = self.gctx.registry_base_path();
-        let _ = registry_base.create_dir();
-        exclude_from_backups_and_indexing(&registry_base.into_path_unlocked());
-
+        self.exclude_registry_base();
         self.ops.block_until_ready()
     }
+
+    fn poll_ready(&mut self) -> Poll<CargoResult<()>> {
+        self.exclude_registry_base();
+        self.ops.poll_ready()
+    }
 }
 
 impl RegistryConfig {
@@ -43,6 +43,18 @@ impl<'gctx> ReplacedSource<'gctx> {
     fn is_builtin_replacement(&self) -> bool {
         self.replace_with.is_crates_io() && self.to_replace.is_crates_io()
     }
+
+    /// Adds the replaced source to an error from updating the inner source.
+    fn update_error(&self, e: anyhow::Error) -> anyhow::Error {
+        if self.is_builtin_replacement() {
+            e
+        } else {
+            e.context(format!(
+                "failed to update replaced source {}",
+                self.to_replace
+            ))
+        }
+    }
 }
 
 impl<'gctx> Source for ReplacedSource<'gctx> {
@@ -168,15 +180,12 @@ impl<'gctx> Source for ReplacedSource<'gctx> {
     }
 
     fn block_until_ready(&mut self) -> CargoResult<()> {
-        self.inner.block_until_ready().map_err(|e| {
-            if self.is_builtin_replacement() {
-                e
-            } else {
-                e.context(format!(
-                    "failed to update replaced source {}",
-                    self.to_replace
-                ))
-            }
-        })
+        let result = self.inner.block_until_ready();
+        result.map_err(|e| self.update_error(e))
+    }
+
+    fn poll_ready(&mut self) -> Poll<CargoResult<()>> {
+        let poll = self.inner.poll_ready();
+        poll.map_err(|e| self.update_error(e))
     }
 }
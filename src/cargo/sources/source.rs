@@ -167,6 +167,19 @@ pub trait Source {
     /// If no queries previously returned `Poll::Pending`, and [`Source::invalidate_cache`]
     /// was not called, this function should be a no-op.
     fn block_until_ready(&mut self) -> CargoResult<()>;
+
+    /// Makes progress on the outstanding [`Poll::Pending`] requests without
+    /// blocking, returning [`Poll::Ready`] once they are all ready.
+    ///
+    /// This is the non-blocking counterpart of [`Source::block_until_ready`],
+    /// for callers driving the source from their own event loop, like the
+    /// resolver. They must call it again later when it returns
+    /// [`Poll::Pending`]. Only sparse registries make progress without
+    /// blocking: the other sources default to [`Source::block_until_ready`],
+    /// including git-based registries, whose index is fetched by git.
+    fn poll_ready(&mut self) -> Poll<CargoResult<()>> {
+        Poll::Ready(self.block_until_ready())
+    }
 }
 
 /// Defines how a dependency query will be performed for a [`Source`].
@@ -275,6 +288,10 @@ impl<'a, T: Source + ?Sized + 'a> Source for Box<T> {
     fn block_until_ready(&mut self) -> CargoResult<()> {
         (**self).block_until_ready()
     }
+
+    fn poll_ready(&mut self) -> Poll<CargoResult<()>> {
+        (**self).poll_ready()
+    }
 }
 
 /// A blanket implementation forwards all methods to [`Source`].
@@ -347,6 +364,10 @@ impl<'a, T: Source + ?Sized + 'a> Source for &'a mut T {
     fn block_until_ready(&mut self) -> CargoResult<()> {
         (**self).block_until_ready()
     }
+
+    fn poll_ready(&mut self) -> Poll<CargoResult<()>> {
+        (**self).poll_ready()
+    }
 }
 
 /// A [`HashMap`] of [`SourceId`] to `Box<Source>`.
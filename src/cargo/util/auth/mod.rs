@@ -22,7 +22,7 @@ use crate::util::credential::adaptor::BasicProcessCredential;
 use crate::util::credential::paseto::PasetoCredential;
 
 use super::{
-    context::{CredentialCacheValue, OptValue, PathAndArgs},
+    context::{ConfigRelativePath, CredentialCacheValue, OptValue, PathAndArgs},
     credential::process::CredentialProcessCredential,
     credential::token::TokenCredential,
 };
@@ -38,6 +38,10 @@ pub struct RegistryConfig {
     pub credential_provider: Option<PathAndArgs>,
     pub secret_key: OptValue<Secret<String>>,
     pub secret_key_subject: Option<String>,
+    pub proxy: Option<String>,
+    pub cainfo: Option<ConfigRelativePath>,
+    pub client_cert: Option<ConfigRelativePath>,
+    pub client_key: Option<ConfigRelativePath>,
     #[serde(rename = "protocol")]
     _protocol: Option<String>,
 }
@@ -68,6 +72,10 @@ impl RegistryConfigExtended {
             credential_provider: self.credential_provider,
             secret_key: self.secret_key,
             secret_key_subject: self.secret_key_subject,
+            proxy: None,
+            cainfo: None,
+            client_cert: None,
+            client_key: None,
             _protocol: None,
         }
     }
@@ -332,7 +340,25 @@ fn registry_credential_config_raw_uncached(
 
     if let Some(name) = &name {
         tracing::debug!("found alternative registry name `{name}` for {sid}");
-        gctx.get::<Option<RegistryConfig>>(&format!("registries.{name}"))
+        let mut cfg = gctx.get::<Option<RegistryConfig>>(&format!("registries.{name}"))?;
+        if let Some(cfg) = &mut cfg {
+            if !gctx.cli_unstable().unstable_options {
+                let unstable = [
+                    ("proxy", cfg.proxy.take().is_some()),
+                    ("cainfo", cfg.cainfo.take().is_some()),
+                    ("client-cert", cfg.client_cert.take().is_some()),
+                    ("client-key", cfg.client_key.take().is_some()),
+                ];
+                for (key, set) in unstable {
+                    if set {
+                        gctx.shell().warn(format!(
+                            "`registries.{name}.{key}` requires `-Zunstable-options`, ignoring it"
+                        ))?;
+                    }
+                }
+            }
+        }
+        Ok(cfg)
     } else {
         tracing::debug!("no registry name found for {sid}");
         Ok(None)
@@ -512,6 +538,28 @@ fn credential_action(
         headers,
     };
     let providers = credential_provider(gctx, sid, require_cred_provider_config, true)?;
+    perform_credential_action(
+        gctx,
+        providers,
+        &registry,
+        &action,
+        args,
+        &sid.display_registry_name(),
+    )
+}
+
+/// Performs `action` with each of the `providers` in turn, until one of them
+/// handles `registry`.
+///
+/// `display_name` names what the credentials are for in the status messages.
+fn perform_credential_action(
+    gctx: &GlobalContext,
+    providers: Vec<Vec<String>>,
+    registry: &RegistryInfo<'_>,
+    action: &Action<'_>,
+    args: &[&str],
+    display_name: &str,
+) -> CargoResult<CredentialResponse> {
     let mut any_not_found = false;
     for provider in providers {
         let args: Vec<&str> = provider
@@ -543,14 +591,10 @@ fn credential_action(
         gctx.shell().verbose(|c| {
             c.status(
                 "Credential",
-                format!(
-                    "{} {action} {}",
-                    args.join(" "),
-                    sid.display_registry_name()
-                ),
+                format!("{} {action} {display_name}", args.join(" ")),
             )
         })?;
-        match provider.perform(&registry, &action, &args[1..]) {
+        match provider.perform(registry, action, &args[1..]) {
             Ok(response) => return Ok(response),
             Err(cargo_credential::Error::UrlNotSupported) => {}
             Err(cargo_credential::Error::NotFound) => any_not_found = true,
@@ -665,6 +709,94 @@ fn auth_token_optional(
     Ok(Some(token))
 }
 
+/// The outcome of the credential provider configured in
+/// `net.git-credentials` for the host of a git repository.
+pub struct GitCredential {
+    /// The command of the credential provider.
+    pub provider: String,
+    /// The username and password to fetch the repository with, or `None` if
+    /// the provider had no credentials for it.
+    pub credentials: Option<(String, Secret<String>)>,
+}
+
+/// Asks the credential provider configured for the host of the git
+/// repository at `url` for credentials to fetch it over HTTP(S).
+///
+/// `username` is the one from `url`, which is used unless the host config
+/// sets one. Returns `None` if no provider is configured, which requires
+/// `-Zgit-credentials`.
+pub fn git_credential(
+    gctx: &GlobalContext,
+    url: &str,
+    username: Option<&str>,
+) -> CargoResult<Option<GitCredential>> {
+    if !gctx.cli_unstable().git_credentials {
+        return Ok(None);
+    }
+    let Ok(parsed) = Url::parse(url) else {
+        return Ok(None);
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Ok(None);
+    }
+    let Some(host) = parsed.host_str() else {
+        return Ok(None);
+    };
+    let Some(cfg) = gctx
+        .net_config()?
+        .git_credentials
+        .as_ref()
+        .and_then(|hosts| hosts.get(host))
+    else {
+        return Ok(None);
+    };
+    let Some(provider) = cfg.credential_provider.clone() else {
+        return Ok(None);
+    };
+    let provider = resolve_credential_alias(gctx, provider);
+    let display = provider.join(" ");
+    let registry = RegistryInfo {
+        index_url: url,
+        name: None,
+        headers: Vec::new(),
+    };
+    let response = perform_credential_action(
+        gctx,
+        vec![provider],
+        &registry,
+        &Action::Get(Operation::Read),
+        &[],
+        host,
+    );
+    let response = match response {
+        Err(e)
+            if matches!(
+                e.downcast_ref::<cargo_credential::Error>(),
+                Some(cargo_credential::Error::NotFound)
+            ) =>
+        {
+            return Ok(Some(GitCredential {
+                provider: display,
+                credentials: None,
+            }));
+        }
+        response => response?,
+    };
+    let CredentialResponse::Get { token, .. } = response else {
+        bail!("credential provider produced unexpected response for `get` request: {response:?}")
+    };
+    let username = cfg
+        .username
+        .as_deref()
+        .or(username)
+        .unwrap_or("git")
+        .to_string();
+    Ok(Some(GitCredential {
+        provider: display,
+        credentials: Some((username, token)),
+    }))
+}
+
 /// Log out from the given registry.
 pub fn logout(gctx: &GlobalContext, sid: &SourceId) -> CargoResult<()> {
     let credential_response = credential_action(gctx, sid, Action::Logout, vec![], &[], false);
@@ -0,0 +1,86 @@
+//! Cancellation of long-running operations, for tools embedding Cargo as a
+//! library.
+//!
+//! A [`CancellationToken`] is shared by a [`GlobalContext`] and its embedder,
+//! which can cancel it from another thread, for example when the user closes a
+//! window or a request times out. The network-facing loops, such as fetching
+//! the index, downloading packages and fetching git repositories, as well as
+//! the resolver, check it regularly and fail with a [`Cancelled`] error once
+//! it is cancelled.
+//!
+//! Embedders which don't want to block a thread can instead drive sources
+//! with [`Source::poll_ready`], which doesn't wait for the network with sparse
+//! registries. Fetching the index of a git-based registry still blocks.
+//!
+//! [`GlobalContext`]: crate::GlobalContext
+//! [`Source::poll_ready`]: crate::sources::source::Source::poll_ready
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::util::errors::CargoResult;
+
+/// A handle to cancel the operations of a [`GlobalContext`].
+///
+/// Clones share the same state: cancelling one of them cancels all of them.
+///
+/// [`GlobalContext`]: crate::GlobalContext
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Requests the operations using this token to stop as soon as possible.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Returns a [`Cancelled`] error if the token is cancelled.
+    pub fn check(&self) -> CargoResult<()> {
+        if self.is_cancelled() {
+            Err(Cancelled.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The error of an operation stopped by [`CancellationToken::cancel`].
+///
+/// It can be found with `error.downcast_ref::<Cancelled>()`.
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("operation was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(token.check().is_ok());
+        clone.cancel();
+        assert!(token.is_cancelled());
+        let err = token.check().unwrap_err();
+        assert!(err.downcast_ref::<Cancelled>().is_some());
+    }
+}
@@ -1,8 +1,10 @@
 use crate::core::compiler::{BuildConfig, MessageFormat, TimingOutput};
 use crate::core::resolver::CliFeatures;
-use crate::core::{Edition, Workspace};
+use crate::core::{Edition, JsonProgress, Workspace};
 use crate::ops::registry::RegistryOrIndex;
-use crate::ops::{CompileFilter, CompileOptions, NewOptions, Packages, VersionControl};
+use crate::ops::{
+    CompileFilter, CompileOptions, FilterRule, NewOptions, PackageFilter, Packages, VersionControl,
+};
 use crate::util::important_paths::find_root_manifest_for_wd;
 use crate::util::interning::InternedString;
 use crate::util::is_rustup;
@@ -21,6 +23,9 @@ use clap::builder::UnknownArgumentValueParser;
 use std::ffi::{OsStr, OsString};
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 
 pub use crate::core::compiler::CompileMode;
 pub use crate::{CliError, CliResult, GlobalContext};
@@ -295,11 +300,73 @@ pub trait CommandExt: Sized {
 
     fn arg_build_plan(self) -> Self {
         self._arg(
-            flag("build-plan", "Output the build plan in JSON (unstable)")
+            flag("build-plan", "Output the build plan in JSON")
                 .help_heading(heading::COMPILATION_OPTIONS),
         )
     }
 
+    fn arg_emit_ninja(self) -> Self {
+        self._arg(
+            opt(
+                "emit-ninja",
+                "Write a Ninja build file to FILE instead of compiling (unstable)",
+            )
+            .value_name("FILE")
+            .conflicts_with("build-plan")
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
+    }
+
+    fn arg_explain_rebuild(self) -> Self {
+        self._arg(
+            flag(
+                "explain-rebuild",
+                "Explain why each dirty unit is rebuilt (unstable)",
+            )
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
+    }
+
+    fn arg_show_dependency_warnings(self) -> Self {
+        self._arg(
+            multi_opt(
+                "show-dependency-warnings",
+                "SPEC",
+                "Show the warnings of the given dependencies at the end of the build (unstable)",
+            )
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
+    }
+
+    fn arg_checkpoint_interval(self) -> Self {
+        self._arg(
+            opt(
+                "checkpoint-interval",
+                "Save the unit durations and fingerprint index every N seconds (unstable)",
+            )
+            .value_name("N")
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
+    }
+
+    fn arg_package_filter(self) -> Self {
+        self._arg(multi_opt(
+            "prune-source",
+            "SOURCE",
+            "Prune the packages from the given source (unstable)",
+        ))
+        ._arg(multi_opt(
+            "only-source",
+            "SOURCE",
+            "Prune the packages which aren't from the given sources (unstable)",
+        ))
+        ._arg(multi_opt(
+            "prune-license",
+            "LICENSE",
+            "Prune the packages whose license matches the pattern (unstable)",
+        ))
+    }
+
     fn arg_unit_graph(self) -> Self {
         self._arg(
             flag("unit-graph", "Output build graph in JSON (unstable)")
@@ -351,6 +418,16 @@ pub trait CommandExt: Sized {
         self._arg(flag("dry-run", dry_run).short('n'))
     }
 
+    fn arg_before(self) -> Self {
+        self._arg(
+            opt(
+                "before",
+                "Only consider versions published before DATE (unstable)",
+            )
+            .value_name("DATE"),
+        )
+    }
+
     fn arg_ignore_rust_version(self) -> Self {
         self.arg_ignore_rust_version_with_help("Ignore `rust-version` specification in packages")
     }
@@ -536,6 +613,29 @@ pub trait ArgMatchesExt {
         self.maybe_flag("keep-going")
     }
 
+    /// Parses `--before`, either a date like `2024-06-01`, standing for
+    /// midnight UTC, or an RFC 3339 timestamp.
+    fn before(&self) -> CargoResult<Option<OffsetDateTime>> {
+        let Some(arg) = self._value_of("before") else {
+            return Ok(None);
+        };
+        let timestamp = if arg.len() == "YYYY-MM-DD".len() {
+            format!("{arg}T00:00:00Z")
+        } else {
+            arg.to_string()
+        };
+        let time = OffsetDateTime::parse(&timestamp, &Rfc3339).map_err(|_| {
+            clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                format!(
+                    "invalid value `{arg}` for `--before`: \
+                     expected a date like `2024-06-01` or an RFC 3339 timestamp"
+                ),
+            )
+        })?;
+        Ok(Some(time))
+    }
+
     fn honor_rust_version(&self) -> Option<bool> {
         self.flag("ignore-rust-version").then_some(false)
     }
@@ -668,6 +768,14 @@ Run `{cmd}` to see possible targets."
                         }
                         message_format = Some(MessageFormat::Short);
                     }
+                    "sarif" => {
+                        gctx.cli_unstable()
+                            .fail_if_stable_opt("--message-format sarif", None)?;
+                        if message_format.is_some() {
+                            bail!(two_kinds_of_msg_format_err);
+                        }
+                        message_format = Some(MessageFormat::Sarif);
+                    }
                     "json-render-diagnostics" => {
                         if message_format.is_none() {
                             message_format = Some(default_json);
@@ -697,6 +805,17 @@ Run `{cmd}` to see possible targets."
                             _ => bail!(two_kinds_of_msg_format_err),
                         }
                     }
+                    "json-progress" => {
+                        gctx.cli_unstable()
+                            .fail_if_stable_opt("--message-format json-progress", None)?;
+                        if message_format.is_none() {
+                            message_format = Some(default_json);
+                        }
+                        if !matches!(message_format, Some(MessageFormat::Json { .. })) {
+                            bail!(two_kinds_of_msg_format_err);
+                        }
+                        gctx.shell().set_json_progress(JsonProgress::Stdout);
+                    }
                     s => bail!("invalid message format specifier: `{}`", s),
                 }
             }
@@ -712,7 +831,14 @@ Run `{cmd}` to see possible targets."
         build_config.message_format = message_format.unwrap_or(MessageFormat::Human);
         build_config.requested_profile = self.get_profile_name(gctx, "dev", profile_checking)?;
         build_config.build_plan = self.flag("build-plan");
+        build_config.emit_ninja = self.value_of_path("emit-ninja", gctx);
         build_config.unit_graph = self.flag("unit-graph");
+        build_config.explain_rebuild = self.flag("explain-rebuild");
+        build_config.show_dependency_warnings = self._values_of("show-dependency-warnings");
+        build_config.checkpoint_interval = match self.value_of_u32("checkpoint-interval")? {
+            Some(0) => anyhow::bail!("checkpoint-interval may not be 0"),
+            secs => secs.map(|secs| Duration::from_secs(secs.into())),
+        };
         build_config.future_incompat_report = self.flag("future-incompat-report");
 
         if self._contains("timings") {
@@ -740,14 +866,28 @@ Run `{cmd}` to see possible targets."
             }
         }
 
-        if build_config.build_plan {
+        if build_config.emit_ninja.is_some() {
             gctx.cli_unstable()
-                .fail_if_stable_opt("--build-plan", 5579)?;
-        };
+                .fail_if_stable_opt("--emit-ninja", None)?;
+            // The Ninja file is written from the build plan.
+            build_config.build_plan = true;
+        }
         if build_config.unit_graph {
             gctx.cli_unstable()
                 .fail_if_stable_opt("--unit-graph", 8002)?;
         }
+        if build_config.explain_rebuild {
+            gctx.cli_unstable()
+                .fail_if_stable_opt("--explain-rebuild", None)?;
+        }
+        if build_config.checkpoint_interval.is_some() {
+            gctx.cli_unstable()
+                .fail_if_stable_opt("--checkpoint-interval", None)?;
+        }
+        if !build_config.show_dependency_warnings.is_empty() {
+            gctx.cli_unstable()
+                .fail_if_stable_opt("--show-dependency-warnings", None)?;
+        }
 
         let opts = CompileOptions {
             build_config,
@@ -770,6 +910,7 @@ Run `{cmd}` to see possible targets."
             target_rustc_crate_types: None,
             rustdoc_document_private_items: false,
             honor_rust_version: self.honor_rust_version(),
+            fuzz: FilterRule::none(),
         };
 
         if let Some(ws) = workspace {
@@ -859,6 +1000,22 @@ Run `{cmd}` to see possible targets."
         }
     }
 
+    fn package_filter(&self, gctx: &GlobalContext) -> CargoResult<PackageFilter> {
+        let prune_sources = self._values_of("prune-source");
+        let only_sources = self._values_of("only-source");
+        let prune_licenses = self._values_of("prune-license");
+        for (flag, values) in [
+            ("--prune-source", &prune_sources),
+            ("--only-source", &only_sources),
+            ("--prune-license", &prune_licenses),
+        ] {
+            if !values.is_empty() {
+                gctx.cli_unstable().fail_if_stable_opt(flag, None)?;
+            }
+        }
+        PackageFilter::new(prune_sources, only_sources, prune_licenses)
+    }
+
     fn check_optional_opts(
         &self,
         workspace: &Workspace<'_>,
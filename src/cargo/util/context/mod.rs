@@ -53,7 +53,7 @@ use crate::util::cache_lock::{CacheLock, CacheLockMode, CacheLocker};
 use std::borrow::Cow;
 use std::cell::{RefCell, RefMut};
 use std::collections::hash_map::Entry::{Occupied, Vacant};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::ffi::{OsStr, OsString};
 use std::fmt;
@@ -64,21 +64,26 @@ use std::mem;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Once;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use self::ConfigValue as CV;
 use crate::core::compiler::rustdoc::RustdocExternMap;
+use crate::core::compiler::FingerprintMode;
 use crate::core::global_cache_tracker::{DeferredGlobalLastUse, GlobalCacheTracker};
-use crate::core::shell::Verbosity;
-use crate::core::{features, CliUnstable, Shell, SourceId, Workspace, WorkspaceRootConfig};
+use crate::core::shell::{JsonProgress, Verbosity};
+use crate::core::{
+    features, find_workspace_root, CliUnstable, Shell, SourceId, Workspace, WorkspaceRootConfig,
+};
 use crate::ops::RegistryCredentialConfig;
 use crate::sources::CRATES_IO_INDEX;
 use crate::sources::CRATES_IO_REGISTRY;
 use crate::util::errors::CargoResult;
+use crate::util::important_paths::find_root_manifest_for_wd;
 use crate::util::network::http::configure_http_handle;
 use crate::util::network::http::http_handle;
+use crate::util::network::simulate::SimulatedNetwork;
 use crate::util::try_canonicalize;
-use crate::util::{internal, CanonicalUrl};
+use crate::util::{internal, CancellationToken, CanonicalUrl};
 use crate::util::{Filesystem, IntoUrl, IntoUrlWithBase, Rustc};
 use anyhow::{anyhow, bail, format_err, Context as _};
 use cargo_credential::Secret;
@@ -222,12 +227,16 @@ pub struct GlobalContext {
     /// Cached configuration parsed by Cargo
     http_config: LazyCell<CargoHttpConfig>,
     future_incompat_config: LazyCell<CargoFutureIncompatConfig>,
+    origin_lints_config: LazyCell<CargoOriginLintsConfig>,
     net_config: LazyCell<CargoNetConfig>,
     build_config: LazyCell<CargoBuildConfig>,
     target_cfgs: LazyCell<Vec<(String, TargetCfgConfig)>>,
     doc_extern_map: LazyCell<RustdocExternMap>,
     progress_config: ProgressConfig,
     env_config: LazyCell<EnvConfig>,
+    /// Root of the workspace containing the current directory, used for the
+    /// `{workspace-root}` placeholder.
+    placeholder_workspace_root: LazyCell<PathBuf>,
     /// This should be false if:
     /// - this is an artifact of the rustc distribution process for "stable" or for "beta"
     /// - this is an `#[test]` that does not opt in with `enable_nightly_features`
@@ -251,6 +260,10 @@ pub struct GlobalContext {
     /// A cache of modifications to make to [`GlobalContext::global_cache_tracker`],
     /// saved to disk in a batch to improve performance.
     deferred_global_last_use: LazyCell<RefCell<DeferredGlobalLastUse>>,
+    /// Token checked by long-running operations, to stop them when cancelled.
+    cancellation_token: CancellationToken,
+    /// Failures injected into network operations, for testing.
+    simulated_network: Option<SimulatedNetwork>,
 }
 
 impl GlobalContext {
@@ -316,16 +329,20 @@ impl GlobalContext {
             package_cache_lock: CacheLocker::new(),
             http_config: LazyCell::new(),
             future_incompat_config: LazyCell::new(),
+            origin_lints_config: LazyCell::new(),
             net_config: LazyCell::new(),
             build_config: LazyCell::new(),
             target_cfgs: LazyCell::new(),
             doc_extern_map: LazyCell::new(),
             progress_config: ProgressConfig::default(),
             env_config: LazyCell::new(),
+            placeholder_workspace_root: LazyCell::new(),
             nightly_features_allowed: matches!(&*features::channel(), "nightly" | "dev"),
             ws_roots: RefCell::new(HashMap::new()),
             global_cache_tracker: LazyCell::new(),
             deferred_global_last_use: LazyCell::new(),
+            cancellation_token: CancellationToken::new(),
+            simulated_network: None,
         }
     }
 
@@ -889,6 +906,42 @@ impl GlobalContext {
         }
     }
 
+    /// Expands the `{workspace-root}` and `{target-dir}` placeholders in a
+    /// config value. Values are returned as-is without `-Zconfig-placeholders`.
+    ///
+    /// Like the config files, the workspace is discovered from the current
+    /// directory.
+    pub fn expand_placeholders<'a>(&self, value: &'a str) -> CargoResult<Cow<'a, str>> {
+        if !self.cli_unstable().config_placeholders || !value.contains('{') {
+            return Ok(Cow::Borrowed(value));
+        }
+        let mut value = value.to_string();
+        if value.contains("{workspace-root}") {
+            let root = self.placeholder_workspace_root()?;
+            value = value.replace("{workspace-root}", placeholder_path(root)?);
+        }
+        if value.contains("{target-dir}") {
+            let target_dir = match self.target_dir()? {
+                Some(dir) => dir.into_path_unlocked(),
+                None => self.placeholder_workspace_root()?.join("target"),
+            };
+            value = value.replace("{target-dir}", placeholder_path(&target_dir)?);
+        }
+        Ok(Cow::Owned(value))
+    }
+
+    fn placeholder_workspace_root(&self) -> CargoResult<&Path> {
+        let root = self
+            .placeholder_workspace_root
+            .try_borrow_with(|| -> CargoResult<_> {
+                let manifest = find_root_manifest_for_wd(&self.cwd)?;
+                let root = find_workspace_root(&manifest, self)?.unwrap_or(manifest);
+                Ok(root.parent().unwrap().to_path_buf())
+            })
+            .context("failed to find the workspace for the `{workspace-root}` placeholder")?;
+        Ok(root.as_path())
+    }
+
     /// Get a list of strings.
     ///
     /// DO NOT USE outside of the config module. `pub` will be removed in the
@@ -1093,6 +1146,18 @@ impl GlobalContext {
 
         self.load_unstable_flags_from_config()?;
 
+        if let ProgressWhen::Json = self.progress_config.when {
+            if self.cli_unstable().unstable_options {
+                self.shell().set_json_progress(JsonProgress::Stderr);
+            } else {
+                self.shell().warn(
+                    "`term.progress = \"json\"` requires `-Zunstable-options`, \
+                     falling back to `auto`",
+                )?;
+                self.progress_config.when = ProgressWhen::Auto;
+            }
+        }
+
         Ok(())
     }
 
@@ -1811,6 +1876,18 @@ impl GlobalContext {
             .try_borrow_with(|| self.get::<CargoFutureIncompatConfig>("future-incompat-report"))
     }
 
+    /// The `[lints.cargo-origin]` table, which is only read with
+    /// `-Zcargo-origin-lints`.
+    pub fn origin_lints_config(&self) -> CargoResult<&CargoOriginLintsConfig> {
+        self.origin_lints_config.try_borrow_with(|| {
+            if self.cli_unstable().cargo_origin_lints {
+                self.get::<CargoOriginLintsConfig>("lints.cargo-origin")
+            } else {
+                Ok(CargoOriginLintsConfig::default())
+            }
+        })
+    }
+
     pub fn net_config(&self) -> CargoResult<&CargoNetConfig> {
         self.net_config
             .try_borrow_with(|| self.get::<CargoNetConfig>("net"))
@@ -1915,6 +1992,29 @@ impl GlobalContext {
         self.creation_time
     }
 
+    /// The token checked by long-running operations, such as network
+    /// transfers and dependency resolution, to stop when it is cancelled.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    /// Replaces the token checked by long-running operations, for example to
+    /// share one with other parts of an application embedding Cargo.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation_token = token;
+    }
+
+    /// The failures injected into network operations, if any.
+    pub fn simulated_network(&self) -> Option<&SimulatedNetwork> {
+        self.simulated_network.as_ref()
+    }
+
+    /// Injects deterministic failures into network operations, to test how
+    /// retries and fallbacks behave without a flaky network.
+    pub fn set_simulated_network(&mut self, network: Option<SimulatedNetwork>) {
+        self.simulated_network = network;
+    }
+
     /// Retrieves a config variable.
     ///
     /// This supports most serde `Deserialize` types. Examples:
@@ -2498,6 +2598,47 @@ pub enum CargoFutureIncompatFrequencyConfig {
     Never,
 }
 
+/// Configuration for `[lints.cargo-origin]`, the handling of warnings per
+/// origin of the package being compiled.
+///
+/// ```toml
+/// [lints.cargo-origin]
+/// workspace = "deny-warnings"
+/// path-deps = "warn"
+/// git = "allow"
+/// registry = "allow"
+///
+/// [lints.cargo-origin.package]
+/// my-legacy-crate = "allow"
+/// ```
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct CargoOriginLintsConfig {
+    /// Members of the workspace.
+    pub workspace: Option<OriginLintPolicy>,
+    /// Path dependencies which aren't members of the workspace.
+    pub path_deps: Option<OriginLintPolicy>,
+    /// Git dependencies.
+    pub git: Option<OriginLintPolicy>,
+    /// Dependencies from a registry.
+    pub registry: Option<OriginLintPolicy>,
+    /// Policies of specific packages, by package ID spec, which take
+    /// precedence over their origin.
+    pub package: Option<BTreeMap<String, OriginLintPolicy>>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum OriginLintPolicy {
+    /// Fail the build on warnings, with `-D warnings`.
+    DenyWarnings,
+    /// Show warnings, but never fail the build on lints, with
+    /// `--cap-lints=warn`.
+    Warn,
+    /// Silence all lints, with `--cap-lints=allow`.
+    Allow,
+}
+
 impl CargoFutureIncompatConfig {
     pub fn should_display_message(&self) -> bool {
         use CargoFutureIncompatFrequencyConfig::*;
@@ -2555,6 +2696,8 @@ pub struct CargoNetConfig {
     pub offline: Option<bool>,
     pub git_fetch_with_cli: Option<bool>,
     pub ssh: Option<CargoSshConfig>,
+    /// Unstable `-Zgit-credentials`, by host name.
+    pub git_credentials: Option<HashMap<String, GitCredentialConfig>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -2563,6 +2706,14 @@ pub struct CargoSshConfig {
     pub known_hosts: Option<Vec<Value<String>>>,
 }
 
+/// `[net.git-credentials.<host>]` tables.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GitCredentialConfig {
+    pub username: Option<String>,
+    pub credential_provider: Option<PathAndArgs>,
+}
+
 /// Configuration for `jobs` in `build` section. There are two
 /// ways to configure: An integer or a simple string expression.
 ///
@@ -2610,6 +2761,63 @@ pub struct CargoBuildConfig {
     pub rustc: Option<ConfigRelativePath>,
     pub rustdoc: Option<ConfigRelativePath>,
     pub out_dir: Option<ConfigRelativePath>,
+    pub replay_warnings: Option<bool>,
+    pub fingerprint: Option<FingerprintMode>,
+    pub max_memory: Option<String>,
+    pub link_jobs: Option<u32>,
+    pub artifact_cache: Option<bool>,
+    pub cache: Option<CargoBuildCacheConfig>,
+    pub executor: Option<PathAndArgs>,
+    pub sandbox: Option<BuildSandboxConfig>,
+}
+
+/// The `[build.cache]` table.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CargoBuildCacheConfig {
+    pub remote: Option<String>,
+    pub read_only: Option<bool>,
+    pub token: Option<Secret<String>>,
+    pub max_age: Option<String>,
+}
+
+/// Configuration for `build.sandbox`, enabled for all packages or only for
+/// some of them:
+///
+/// ```toml
+/// [build]
+/// sandbox = true
+/// ```
+///
+/// ```toml
+/// [build]
+/// sandbox = ["openssl-sys", "serde_derive"]
+/// ```
+#[derive(Debug, Clone)]
+pub enum BuildSandboxConfig {
+    Enabled(bool),
+    Packages(Vec<String>),
+}
+
+impl<'de> Deserialize<'de> for BuildSandboxConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error as _;
+        UntaggedEnumVisitor::new()
+            .bool(|enabled| Ok(BuildSandboxConfig::Enabled(enabled)))
+            // Like `CARGO_BUILD_SANDBOX=true`.
+            .string(|string| match string {
+                "true" => Ok(BuildSandboxConfig::Enabled(true)),
+                "false" => Ok(BuildSandboxConfig::Enabled(false)),
+                _ => Err(serde_untagged::de::Error::custom(
+                    "expected a boolean or a list of package names",
+                )),
+            })
+            .seq(|packages| packages.deserialize().map(BuildSandboxConfig::Packages))
+            .deserialize(deserializer)
+    }
 }
 
 /// Configuration for `build.target`.
@@ -2672,10 +2880,99 @@ impl BuildTargetConfig {
     }
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CargoPackageConfig {
+    /// Whether `Cargo.lock` is packaged when the manifest doesn't say.
+    pub publish_lockfile: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CargoTestConfig {
+    pub timeout: Option<Value<TestTimeoutConfig>>,
+    pub cache_results: Option<bool>,
+}
+
+/// Configuration for `timeout` in `test` section. There are two
+/// ways to configure: An integer number of seconds or a time span string.
+///
+/// ```toml
+/// [test]
+/// timeout = 120
+/// ```
+///
+/// ```toml
+/// [test]
+/// timeout = "2 minutes" # Short forms like "120s" are also accepted.
+/// ```
+#[derive(Debug, Clone)]
+pub enum TestTimeoutConfig {
+    Integer(u64),
+    String(String),
+}
+
+impl<'de> Deserialize<'de> for TestTimeoutConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        UntaggedEnumVisitor::new()
+            .u64(|int| Ok(TestTimeoutConfig::Integer(int)))
+            .string(|string| Ok(TestTimeoutConfig::String(string.to_owned())))
+            .deserialize(deserializer)
+    }
+}
+
+impl TestTimeoutConfig {
+    /// Converts the configured value into a [`Duration`].
+    pub fn to_duration(&self) -> CargoResult<Duration> {
+        let duration = match self {
+            TestTimeoutConfig::Integer(secs) => Duration::from_secs(*secs),
+            TestTimeoutConfig::String(s) => parse_short_time_span(s)
+                .or_else(|| crate::core::gc::parse_time_span(s).ok())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "expected a value of the form \"Ns/Nmin/Nh\" or \
+                         \"N seconds/minutes/hours/days/weeks/months\", got: {s:?}"
+                    )
+                })?,
+        };
+        if duration.is_zero() {
+            bail!("test timeout must be greater than zero");
+        }
+        Ok(duration)
+    }
+}
+
+/// Parses the short forms of a time span only accepted by `test.timeout`,
+/// like `120s`, `2min` or `1h`.
+fn parse_short_time_span(span: &str) -> Option<Duration> {
+    let (count, factor) = if let Some(count) = span.strip_suffix("min") {
+        (count, 60)
+    } else if let Some(count) = span.strip_suffix('h') {
+        (count, 60 * 60)
+    } else {
+        (span.strip_suffix('s')?, 1)
+    };
+    let count: u64 = count.trim_end().parse().ok()?;
+    Some(Duration::from_secs(count * factor))
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct CargoResolverConfig {
     pub something_like_precedence: Option<CargoResolverPrecedence>,
+    pub incompatible_rust_versions: Option<IncompatibleRustVersions>,
+}
+
+/// The `[vet]` table, with `-Zvet`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CargoVetConfig {
+    /// Paths or URLs of audits in the format of cargo-vet.
+    #[serde(default)]
+    pub audits: Vec<ConfigRelativePath>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -2685,6 +2982,64 @@ pub enum CargoResolverPrecedence {
     SomethingLikeRustVersion,
 }
 
+/// How `resolver.incompatible-rust-versions` treats dependency versions whose
+/// `rust-version` is newer than the workspace's.
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum IncompatibleRustVersions {
+    /// Select them like any other version.
+    Allow,
+    /// Prefer compatible versions, falling back to incompatible ones.
+    Fallback,
+}
+
+/// Which versions of dependencies the resolver prefers, set with
+/// `resolver.resolution` or `cargo update --resolution`.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResolutionMode {
+    /// Prefer the highest compatible version of every dependency.
+    #[default]
+    Maximum,
+    /// Prefer the lowest compatible version of every dependency.
+    Minimal,
+    /// Prefer the lowest compatible version of the direct dependencies of
+    /// workspace members, and the highest one of everything else.
+    DirectMinimal,
+}
+
+/// How locked versions which have been yanked since are treated, set with
+/// `resolver.yanked`.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum YankedPolicy {
+    /// Keep using them silently.
+    #[default]
+    Allow,
+    /// Keep using them with a warning.
+    Warn,
+    /// Fail the resolution.
+    Error,
+    /// Switch to the nearest compatible version which isn't yanked.
+    AutoBumpCompatible,
+}
+
+impl FromStr for ResolutionMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> CargoResult<ResolutionMode> {
+        match s {
+            "maximum" => Ok(ResolutionMode::Maximum),
+            "minimal" => Ok(ResolutionMode::Minimal),
+            "direct-minimal" => Ok(ResolutionMode::DirectMinimal),
+            _ => anyhow::bail!(
+                "invalid resolution mode `{s}`, \
+                 expected one of `maximum`, `minimal`, `direct-minimal`"
+            ),
+        }
+    }
+}
+
 #[derive(Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct TermConfig {
@@ -2712,6 +3067,7 @@ pub enum ProgressWhen {
     Auto,
     Never,
     Always,
+    Json,
 }
 
 fn progress_or_string<'de, D>(deserializer: D) -> Result<Option<ProgressConfig>, D::Error>
@@ -2724,7 +3080,7 @@ where
         type Value = Option<ProgressConfig>;
 
         fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-            formatter.write_str("a string (\"auto\" or \"never\") or a table")
+            formatter.write_str("a string (\"auto\", \"never\" or \"json\") or a table")
         }
 
         fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
@@ -2740,8 +3096,12 @@ where
                     when: ProgressWhen::Never,
                     width: None,
                 })),
+                "json" => Ok(Some(ProgressConfig {
+                    when: ProgressWhen::Json,
+                    width: None,
+                })),
                 "always" => Err(E::custom("\"always\" progress requires a `width` key")),
-                _ => Err(E::unknown_variant(s, &["auto", "never"])),
+                _ => Err(E::unknown_variant(s, &["auto", "never", "json"])),
             }
         }
 
@@ -2825,27 +3185,36 @@ impl EnvConfigValue {
         }
     }
 
-    pub fn resolve<'a>(&'a self, gctx: &GlobalContext) -> Cow<'a, OsStr> {
-        match self.inner.val {
-            EnvConfigValueInner::Simple(ref s) => Cow::Borrowed(OsStr::new(s.as_str())),
+    pub fn resolve<'a>(&'a self, gctx: &GlobalContext) -> CargoResult<Cow<'a, OsStr>> {
+        let value = match self.inner.val {
+            EnvConfigValueInner::Simple(ref s) => gctx.expand_placeholders(s)?,
             EnvConfigValueInner::WithOptions {
                 ref value,
                 relative,
                 ..
             } => {
+                let value = gctx.expand_placeholders(value)?;
                 if relative {
-                    let p = self.inner.definition.root(gctx).join(&value);
-                    Cow::Owned(p.into_os_string())
-                } else {
-                    Cow::Borrowed(OsStr::new(value.as_str()))
+                    let p = self.inner.definition.root(gctx).join(&*value);
+                    return Ok(Cow::Owned(p.into_os_string()));
                 }
+                value
             }
-        }
+        };
+        Ok(match value {
+            Cow::Borrowed(s) => Cow::Borrowed(OsStr::new(s)),
+            Cow::Owned(s) => Cow::Owned(s.into()),
+        })
     }
 }
 
 pub type EnvConfig = HashMap<String, EnvConfigValue>;
 
+fn placeholder_path(path: &Path) -> CargoResult<&str> {
+    path.to_str()
+        .ok_or_else(|| anyhow!("path `{}` is not valid UTF-8", path.display()))
+}
+
 fn parse_document(toml: &str, _file: &Path, _gctx: &GlobalContext) -> CargoResult<toml::Table> {
     // At the moment, no compatibility checks are needed.
     toml.parse().map_err(Into::into)
@@ -1,4 +1,5 @@
 use super::{GlobalContext, UnmergedStringList, Value};
+use crate::util::CargoResult;
 use serde::{de::Error, Deserialize};
 use std::path::PathBuf;
 
@@ -82,6 +83,20 @@ impl<'de> serde::Deserialize<'de> for PathAndArgs {
 }
 
 impl PathAndArgs {
+    /// Resolves the program with [`ConfigRelativePath::resolve_program`], after
+    /// expanding the config placeholders in the program and its arguments.
+    pub fn resolve(&self, gctx: &GlobalContext) -> CargoResult<(PathBuf, Vec<String>)> {
+        let path = self.path.value();
+        let program = gctx.expand_placeholders(&path.val)?;
+        let program = gctx.string_to_path(&program, &path.definition);
+        let args = self
+            .args
+            .iter()
+            .map(|arg| Ok(gctx.expand_placeholders(arg)?.into_owned()))
+            .collect::<CargoResult<_>>()?;
+        Ok((program, args))
+    }
+
     /// Construct a PathAndArgs from a string. The string will be split on ascii whitespace,
     /// with the first item being treated as a `ConfigRelativePath` to the executable, and subsequent
     /// items as arguments.
@@ -120,7 +120,7 @@ fn load_config_table(gctx: &GlobalContext, prefix: &str) -> CargoResult<TargetCo
     // Links do not support environment variables.
     let target_key = ConfigKey::from_str(prefix);
     let links_overrides = match gctx.get_table(&target_key)? {
-        Some(links) => parse_links_overrides(&target_key, links.val)?,
+        Some(links) => parse_links_overrides(gctx, &target_key, links.val)?,
         None => BTreeMap::new(),
     };
     Ok(TargetConfig {
@@ -133,6 +133,7 @@ fn load_config_table(gctx: &GlobalContext, prefix: &str) -> CargoResult<TargetCo
 }
 
 fn parse_links_overrides(
+    gctx: &GlobalContext,
     target_key: &ConfigKey,
     links: HashMap<String, CV>,
 ) -> CargoResult<BTreeMap<String, BuildOutput>> {
@@ -142,7 +143,7 @@ fn parse_links_overrides(
         // Skip these keys, it shares the namespace with `TargetConfig`.
         match lib_name.as_str() {
             // `ar` is a historical thing.
-            "ar" | "linker" | "runner" | "rustflags" | "rustdocflags" => continue,
+            "ar" | "linker" | "runner" | "runners" | "rustflags" | "rustdocflags" => continue,
             _ => {}
         }
         let mut output = BuildOutput::default();
@@ -154,6 +155,17 @@ fn parse_links_overrides(
         }
         pairs.sort_by_key(|p| p.0);
         for (key, value) in pairs {
+            if let (Some(scope), CV::Table(..)) = (target_scope(key), value) {
+                if !gctx.cli_unstable().unstable_options {
+                    gctx.shell().warn(format!(
+                        "`{target_key}.{lib_name}.{key}` requires `-Zunstable-options`, ignoring it"
+                    ))?;
+                    continue;
+                }
+                let table_key = format!("{target_key}.{lib_name}.{key}");
+                parse_scoped_override(&mut output, scope, &table_key, value)?;
+                continue;
+            }
             match key.as_str() {
                 "rustc-flags" => {
                     let flags = value.string(key)?;
@@ -226,6 +238,59 @@ fn parse_links_overrides(
     Ok(links_overrides)
 }
 
+/// The kind of targets the scoped table `key` of a build script override
+/// applies to, like `[target.<triple>.<links>.bins]`.
+fn target_scope(key: &str) -> Option<LinkArgTarget> {
+    match key {
+        "bins" => Some(LinkArgTarget::Bin),
+        "tests" => Some(LinkArgTarget::Test),
+        "benches" => Some(LinkArgTarget::Bench),
+        "examples" => Some(LinkArgTarget::Example),
+        _ => None,
+    }
+}
+
+/// Parses a scoped table of a build script override, whose flags and
+/// environment variables only apply to the targets of `scope`.
+fn parse_scoped_override(
+    output: &mut BuildOutput,
+    scope: LinkArgTarget,
+    table_key: &str,
+    value: &CV,
+) -> CargoResult<()> {
+    let mut pairs: Vec<_> = value.table(table_key)?.0.iter().collect();
+    pairs.sort_by_key(|p| p.0);
+    for (key, value) in pairs {
+        match key.as_str() {
+            "rustc-cfg" => {
+                let list = value.list(key)?;
+                output
+                    .target_cfgs
+                    .extend(list.iter().map(|v| (scope.clone(), v.0.clone())));
+            }
+            // The expected cfgs are harmless to declare for all the targets.
+            "rustc-check-cfg" => {
+                let list = value.list(key)?;
+                output.check_cfgs.extend(list.iter().map(|v| v.0.clone()));
+            }
+            "rustc-env" => {
+                for (name, val) in value.table(key)?.0 {
+                    let val = val.string(name)?.0;
+                    output
+                        .target_env
+                        .push((scope.clone(), (name.clone(), val.to_string())));
+                }
+            }
+            "rustc-link-arg" => {
+                let args = extra_link_args(scope.clone(), key, value)?;
+                output.linker_args.extend(args);
+            }
+            _ => anyhow::bail!("`{key}` is not supported in `{table_key}`"),
+        }
+    }
+    Ok(())
+}
+
 fn extra_link_args<'a>(
     link_type: LinkArgTarget,
     key: &str,
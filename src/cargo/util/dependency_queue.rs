@@ -97,51 +97,45 @@ impl<N: Hash + Eq + Clone, E: Eq + Hash + Clone, V> DependencyQueue<N, E, V> {
 
     /// All nodes have been added, calculate some internal metadata and prepare
     /// for `dequeue`.
+    ///
+    /// The priority of a node is the length of the critical path starting at
+    /// it: its own cost plus the highest priority of the nodes depending on
+    /// it. Nodes on the longest chains of the graph are dequeued first.
     pub fn queue_finished(&mut self) {
         let mut out = HashMap::new();
         for key in self.dep_map.keys() {
-            depth(key, &self.reverse_dep_map, &mut out);
+            critical_path(key, &self.reverse_dep_map, &self.cost, &mut out);
         }
         self.priority = out
             .into_iter()
-            .map(|(n, set)| {
-                let total_cost =
-                    self.cost[&n] + set.iter().map(|key| self.cost[key]).sum::<usize>();
-                (n, total_cost)
-            })
+            .map(|(n, priority)| (n, priority.expect("cycle in DependencyQueue")))
             .collect();
 
-        /// Creates a flattened reverse dependency list. For a given key, finds the
-        /// set of nodes which depend on it, including transitively. This is different
-        /// from self.reverse_dep_map because self.reverse_dep_map only maps one level
-        /// of reverse dependencies.
-        fn depth<'a, N: Hash + Eq + Clone, E: Hash + Eq + Clone>(
+        /// Computes the length of the critical path starting at `key`. While a
+        /// node is being visited, its entry in `results` is `None`, to detect
+        /// cycles.
+        fn critical_path<N: Hash + Eq + Clone, E: Hash + Eq + Clone>(
             key: &N,
             map: &HashMap<N, HashMap<E, HashSet<N>>>,
-            results: &'a mut HashMap<N, HashSet<N>>,
-        ) -> &'a HashSet<N> {
-            if results.contains_key(key) {
-                let depth = &results[key];
-                assert!(!depth.is_empty(), "cycle in DependencyQueue");
-                return depth;
+            cost: &HashMap<N, usize>,
+            results: &mut HashMap<N, Option<usize>>,
+        ) -> usize {
+            if let Some(priority) = results.get(key) {
+                return priority.expect("cycle in DependencyQueue");
             }
-            results.insert(key.clone(), HashSet::new());
-
-            let mut set = HashSet::new();
-            set.insert(key.clone());
+            results.insert(key.clone(), None);
 
-            for dep in map
+            let longest_dependent = map
                 .get(key)
                 .into_iter()
                 .flat_map(|it| it.values())
                 .flatten()
-            {
-                set.extend(depth(dep, map, results).iter().cloned())
-            }
-
-            let slot = results.get_mut(key).unwrap();
-            *slot = set;
-            &*slot
+                .map(|dep| critical_path(dep, map, cost, results))
+                .max()
+                .unwrap_or(0);
+            let priority = cost[key] + longest_dependent;
+            results.insert(key.clone(), Some(priority));
+            priority
         }
     }
 
@@ -170,6 +164,11 @@ impl<N: Hash + Eq + Clone, E: Eq + Hash + Clone, V> DependencyQueue<N, E, V> {
         self.dep_map.len()
     }
 
+    /// Returns the remaining packages to be built.
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.dep_map.keys()
+    }
+
     /// Indicate that something has finished.
     ///
     /// Calling this function indicates that the `node` has produced `edge`. All
@@ -212,19 +211,19 @@ mod test {
         q.queue(5, (), vec![(4, ()), (3, ())], 1);
         q.queue_finished();
 
-        assert_eq!(q.dequeue(), Some((1, (), 5)));
-        assert_eq!(q.dequeue(), Some((3, (), 4)));
+        assert_eq!(q.dequeue(), Some((1, (), 4)));
+        assert_eq!(q.dequeue(), Some((3, (), 3)));
         assert_eq!(q.dequeue(), None);
         q.finish(&3, &());
         assert_eq!(q.dequeue(), None);
         q.finish(&1, &());
-        assert_eq!(q.dequeue(), Some((2, (), 4)));
+        assert_eq!(q.dequeue(), Some((2, (), 3)));
         assert_eq!(q.dequeue(), None);
         q.finish(&2, &());
-        assert_eq!(q.dequeue(), Some((4, (), 3)));
+        assert_eq!(q.dequeue(), Some((4, (), 2)));
         assert_eq!(q.dequeue(), None);
         q.finish(&4, &());
-        assert_eq!(q.dequeue(), Some((5, (), 2)));
+        assert_eq!(q.dequeue(), Some((5, (), 1)));
     }
 
     #[test]
@@ -237,18 +236,36 @@ mod test {
         q.queue(4, (), vec![(2, ()), (3, ())], 1);
         q.queue_finished();
 
-        assert_eq!(q.dequeue(), Some((3, (), 9)));
-        assert_eq!(q.dequeue(), Some((1, (), 4)));
+        assert_eq!(q.dequeue(), Some((3, (), 5)));
+        assert_eq!(q.dequeue(), Some((1, (), 3)));
         assert_eq!(q.dequeue(), None);
         q.finish(&3, &());
         assert_eq!(q.dequeue(), None);
         q.finish(&1, &());
-        assert_eq!(q.dequeue(), Some((2, (), 3)));
+        assert_eq!(q.dequeue(), Some((2, (), 2)));
         assert_eq!(q.dequeue(), None);
         q.finish(&2, &());
-        assert_eq!(q.dequeue(), Some((4, (), 2)));
+        assert_eq!(q.dequeue(), Some((4, (), 1)));
         assert_eq!(q.dequeue(), None);
         q.finish(&4, &());
         assert_eq!(q.dequeue(), None);
     }
+
+    #[test]
+    fn long_chain_before_many_dependents() {
+        let mut q = DependencyQueue::new();
+
+        // 1 is needed by many cheap nodes, while 5 starts a longer chain.
+        q.queue(1, (), vec![], 1);
+        q.queue(2, (), vec![(1, ())], 1);
+        q.queue(3, (), vec![(1, ())], 1);
+        q.queue(4, (), vec![(1, ())], 1);
+        q.queue(5, (), vec![], 1);
+        q.queue(6, (), vec![(5, ())], 2);
+        q.queue_finished();
+
+        assert_eq!(q.dequeue(), Some((5, (), 3)));
+        assert_eq!(q.dequeue(), Some((1, (), 2)));
+        assert_eq!(q.dequeue(), None);
+    }
 }
@@ -56,6 +56,20 @@ impl FileLock {
         self.path.parent().unwrap()
     }
 
+    /// Renames the locked file to `new_path`, which this lock then points to.
+    pub fn rename<P: AsRef<Path>>(&mut self, new_path: P) -> CargoResult<()> {
+        let new_path = new_path.as_ref();
+        std::fs::rename(&self.path, new_path).with_context(|| {
+            format!(
+                "failed to rename `{}` to `{}`",
+                self.path.display(),
+                new_path.display()
+            )
+        })?;
+        self.path = new_path.to_path_buf();
+        Ok(())
+    }
+
     /// Removes all sibling files to this locked file.
     ///
     /// This can be useful if a directory is locked with a sentinel file but it
@@ -0,0 +1,101 @@
+//! Graceful handling of Ctrl-C during builds, with `-Zgraceful-interrupt`.
+//!
+//! Without it, Ctrl-C terminates Cargo right away, along with the compiler
+//! processes it runs (see [`crate::util::job`]). While an [`InterruptGuard`]
+//! is alive, the first Ctrl-C only records that the build was interrupted.
+//! The job queue then stops starting new units and waits for the running
+//! ones. Ctrl-C in a terminal also reaches them, being sent to the whole
+//! process group, so most of them fail: their outputs are discarded. The
+//! state of the build is then saved before returning. A second Ctrl-C
+//! terminates Cargo as usual.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by the handler on the first Ctrl-C.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Handles Ctrl-C until it is dropped.
+pub struct InterruptGuard {
+    _private: (),
+}
+
+impl InterruptGuard {
+    /// Installs the Ctrl-C handler.
+    pub fn install() -> InterruptGuard {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        imp::install();
+        InterruptGuard { _private: () }
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        imp::uninstall();
+        INTERRUPTED.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Whether Ctrl-C was pressed since the handler was installed.
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::sync::atomic::Ordering;
+
+    use super::INTERRUPTED;
+
+    extern "C" fn handler(_signal: libc::c_int) {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            // Only async-signal-safe functions may be called here.
+            unsafe {
+                libc::signal(libc::SIGINT, libc::SIG_DFL);
+                libc::raise(libc::SIGINT);
+            }
+        }
+    }
+
+    pub fn install() {
+        unsafe {
+            libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+        }
+    }
+
+    pub fn uninstall() {
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::sync::atomic::Ordering;
+
+    use windows_sys::Win32::Foundation::{BOOL, FALSE, TRUE};
+    use windows_sys::Win32::System::Console::{SetConsoleCtrlHandler, CTRL_C_EVENT};
+
+    use super::INTERRUPTED;
+
+    unsafe extern "system" fn handler(ctrl_type: u32) -> BOOL {
+        // Returning `FALSE` lets the default handler terminate Cargo.
+        if ctrl_type == CTRL_C_EVENT && !INTERRUPTED.swap(true, Ordering::SeqCst) {
+            TRUE
+        } else {
+            FALSE
+        }
+    }
+
+    pub fn install() {
+        unsafe {
+            SetConsoleCtrlHandler(Some(handler), TRUE);
+        }
+    }
+
+    pub fn uninstall() {
+        unsafe {
+            SetConsoleCtrlHandler(Some(handler), FALSE);
+        }
+    }
+}
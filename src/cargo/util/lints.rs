@@ -1,19 +1,37 @@
 use crate::core::dependency::DepKind;
+use crate::core::registry::PackageRegistry;
 use crate::core::FeatureValue::Dep;
-use crate::core::{Edition, Feature, FeatureValue, Features, Manifest, Package};
+use crate::core::Registry as _;
+use crate::core::{
+    Dependency, Edition, Feature, FeatureValue, Features, Manifest, Package, PackageId, Resolve,
+};
+use crate::sources::source::QueryKind;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::human_readable_bytes;
 use crate::util::interning::InternedString;
 use crate::{CargoResult, GlobalContext};
 use annotate_snippets::{Level, Snippet};
-use cargo_util_schemas::manifest::{TomlLintLevel, TomlToolLints};
+use cargo_util_schemas::manifest::{TomlLint, TomlLintLevel, TomlToolLints};
 use pathdiff::diff_paths;
-use std::collections::HashSet;
+use semver::{Comparator, Op, VersionReq};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fmt::Display;
 use std::ops::Range;
 use std::path::Path;
+use std::task::Poll;
+use time::OffsetDateTime;
 use toml_edit::ImDocument;
 
 const LINT_GROUPS: &[LintGroup] = &[TEST_DUMMY_UNSTABLE];
-const LINTS: &[Lint] = &[IM_A_TEAPOT, IMPLICIT_FEATURES, UNUSED_OPTIONAL_DEPENDENCY];
+const LINTS: &[Lint] = &[
+    DEPRECATED_DEPENDENCY,
+    IM_A_TEAPOT,
+    IMPLICIT_FEATURES,
+    MULTIPLE_VERSIONS,
+    PACKAGE_CONTENTS,
+    STALE_DEPENDENCIES,
+    UNUSED_OPTIONAL_DEPENDENCY,
+];
 
 pub fn analyze_cargo_lints_table(
     pkg: &Package,
@@ -779,3 +797,489 @@ pub fn unused_dependencies(
     }
     Ok(())
 }
+
+const DEPRECATED_DEPENDENCY: Lint = Lint {
+    name: "deprecated-dependency",
+    desc: "dependency is deprecated by its registry",
+    groups: &[],
+    default_level: LintLevel::Warn,
+    edition_lint_opts: None,
+    feature_gate: None,
+};
+
+/// Checks the direct dependencies of the workspace members in `resolve` for
+/// packages that their registry has marked as deprecated.
+///
+/// Each deprecated package is reported once for the whole workspace, at the
+/// dependency of the first member depending on it whose lint level isn't
+/// `allow`.
+pub fn check_deprecated_dependencies(
+    members: &[(&Package, TomlToolLints)],
+    resolve: &Resolve,
+    error_count: &mut usize,
+    gctx: &GlobalContext,
+) -> CargoResult<()> {
+    let mut reported = HashSet::new();
+    for (pkg, pkg_lints) in members {
+        let manifest = pkg.manifest();
+        let (lint_level, reason) = DEPRECATED_DEPENDENCY.level(
+            pkg_lints,
+            manifest.edition(),
+            manifest.unstable_features(),
+        );
+        if lint_level == LintLevel::Allow {
+            continue;
+        }
+
+        let mut emitted_source = None;
+        for (dep_id, deps) in resolve.deps(pkg.package_id()) {
+            let Some(deprecation) = resolve.summary(dep_id).deprecated() else {
+                continue;
+            };
+            if !reported.insert(dep_id) {
+                continue;
+            }
+            for dep in deps {
+                if lint_level == LintLevel::Forbid || lint_level == LintLevel::Deny {
+                    *error_count += 1;
+                }
+                let mut toml_path = vec![dep.kind().kind_table(), dep.name_in_toml().as_str()];
+                let platform = dep.platform().map(|p| p.to_string());
+                if let Some(platform) = platform.as_ref() {
+                    toml_path.insert(0, platform);
+                    toml_path.insert(0, "target");
+                }
+                let level = lint_level.to_diagnostic_level();
+                let manifest_path = rel_cwd_manifest_path(pkg.manifest_path(), gctx);
+                let title = format!("{}: `{dep_id}`", DEPRECATED_DEPENDENCY.desc);
+                let mut message = level.title(&title);
+                if let Some(span) = get_span(manifest.document(), &toml_path, false) {
+                    message = message.snippet(
+                        Snippet::source(manifest.contents())
+                            .origin(&manifest_path)
+                            .annotation(level.span(span))
+                            .fold(true),
+                    );
+                }
+                if emitted_source.is_none() {
+                    emitted_source = Some(format!(
+                        "`cargo::{}` is set to `{lint_level}` {reason}",
+                        DEPRECATED_DEPENDENCY.name
+                    ));
+                    message = message.footer(Level::Note.title(emitted_source.as_ref().unwrap()));
+                }
+                let deprecation_note = format!("`{}` is deprecated: {deprecation}", dep_id.name());
+                if !deprecation.is_empty() {
+                    message = message.footer(Level::Note.title(&deprecation_note));
+                }
+                gctx.shell().print_message(message)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+const MULTIPLE_VERSIONS: Lint = Lint {
+    name: "multiple_versions",
+    desc: "multiple semver-incompatible versions of a package",
+    groups: &[],
+    default_level: LintLevel::Allow,
+    edition_lint_opts: None,
+    feature_gate: None,
+};
+
+/// Checks the packages the workspace members depend on, directly or not, for
+/// packages present in several semver-incompatible versions.
+///
+/// Each duplicated package is reported once for the whole workspace, by the
+/// first member reaching it whose lint level isn't `allow`. Packages listed
+/// in the `allow` array of the lint configuration are skipped, and the
+/// entries naming no package of the dependency graph are warned about.
+pub fn check_multiple_versions(
+    members: &[(&Package, TomlToolLints)],
+    resolve: &Resolve,
+    error_count: &mut usize,
+    gctx: &GlobalContext,
+) -> CargoResult<()> {
+    let mut reported = HashSet::new();
+    let mut unknown_allowed = BTreeSet::new();
+    for (pkg, pkg_lints) in members {
+        let allowed: HashSet<&str> = match pkg_lints.get(MULTIPLE_VERSIONS.name) {
+            Some(TomlLint::Config(config)) => config
+                .config
+                .get("allow")
+                .and_then(|allow| allow.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|name| name.as_str())
+                .collect(),
+            _ => HashSet::new(),
+        };
+        unknown_allowed.extend(
+            allowed
+                .iter()
+                .filter(|&&name| !resolve.iter().any(|id| id.name() == name))
+                .map(|name| name.to_string()),
+        );
+
+        let manifest = pkg.manifest();
+        let (lint_level, reason) =
+            MULTIPLE_VERSIONS.level(pkg_lints, manifest.edition(), manifest.unstable_features());
+        if lint_level == LintLevel::Allow {
+            continue;
+        }
+
+        // Walk the dependency graph, remembering through which package each
+        // dependency was first reached to describe the path to it.
+        let root = pkg.package_id();
+        let mut reached_from = HashMap::new();
+        let mut queue = VecDeque::from([root]);
+        let mut versions: BTreeMap<InternedString, BTreeSet<PackageId>> = BTreeMap::new();
+        while let Some(id) = queue.pop_front() {
+            for (dep_id, _) in resolve.deps(id) {
+                if dep_id == root || reached_from.contains_key(&dep_id) {
+                    continue;
+                }
+                reached_from.insert(dep_id, id);
+                versions.entry(dep_id.name()).or_default().insert(dep_id);
+                queue.push_back(dep_id);
+            }
+        }
+        let path = |mut id: PackageId| {
+            let mut path = vec![id];
+            while let Some(&parent) = reached_from.get(&id) {
+                path.push(parent);
+                id = parent;
+            }
+            path.iter()
+                .rev()
+                .map(|id| format!("`{id}`"))
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        };
+
+        let mut emitted_source = None;
+        for (name, ids) in versions {
+            if allowed.contains(name.as_str()) || reported.contains(&name) {
+                continue;
+            }
+            let compat_versions: BTreeSet<_> = ids
+                .iter()
+                .map(|id| {
+                    let v = id.version();
+                    match (v.major, v.minor) {
+                        (0, 0) => (0, 0, v.patch),
+                        (0, minor) => (0, minor, 0),
+                        (major, _) => (major, 0, 0),
+                    }
+                })
+                .collect();
+            if compat_versions.len() < 2 {
+                continue;
+            }
+            reported.insert(name);
+            if lint_level == LintLevel::Forbid || lint_level == LintLevel::Deny {
+                *error_count += 1;
+            }
+            let level = lint_level.to_diagnostic_level();
+            let title = format!("{}: `{name}`", MULTIPLE_VERSIONS.desc);
+            let paths: Vec<_> = ids.iter().map(|&id| path(id)).collect();
+            let mut message = level.title(&title);
+            if emitted_source.is_none() {
+                emitted_source = Some(format!(
+                    "`cargo::{}` is set to `{lint_level}` {reason}",
+                    MULTIPLE_VERSIONS.name
+                ));
+                message = message.footer(Level::Note.title(emitted_source.as_ref().unwrap()));
+            }
+            for path in &paths {
+                message = message.footer(Level::Note.title(path));
+            }
+            gctx.shell().print_message(message)?;
+        }
+    }
+    for name in unknown_allowed {
+        gctx.shell().warn(format!(
+            "`{name}` in the `allow` list of `cargo::{}` is not a dependency of the workspace",
+            MULTIPLE_VERSIONS.name
+        ))?;
+    }
+    Ok(())
+}
+
+const PACKAGE_CONTENTS: Lint = Lint {
+    name: "package_contents",
+    desc: "suspicious file in package",
+    groups: &[],
+    default_level: LintLevel::Warn,
+    edition_lint_opts: None,
+    feature_gate: None,
+};
+
+/// Default `max-file-size` of the `package_contents` lint, in bytes.
+const DEFAULT_MAX_PACKAGE_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Extensions of files which are usually produced by a build rather than
+/// written by hand.
+const BUILD_OUTPUT_EXTENSIONS: &[&str] = &[
+    "a", "dll", "dylib", "exe", "lib", "o", "obj", "pdb", "rlib", "rmeta", "so",
+];
+
+/// Files Cargo writes at the root of a target directory.
+const BUILD_OUTPUT_FILE_NAMES: &[&str] = &["CACHEDIR.TAG", ".rustc_info.json"];
+
+/// Checks the files about to be packaged for symlinks pointing outside of
+/// the package root, files larger than `max-file-size` and files which look
+/// like build outputs.
+///
+/// `files` are the paths of the files in the package along with their paths
+/// on disk. Paths listed in the `allow` array of the lint configuration are
+/// skipped.
+pub fn check_package_contents(
+    pkg: &Package,
+    pkg_lints: &TomlToolLints,
+    files: &[(&Path, &Path)],
+    error_count: &mut usize,
+    gctx: &GlobalContext,
+) -> CargoResult<()> {
+    let manifest = pkg.manifest();
+    let (lint_level, reason) =
+        PACKAGE_CONTENTS.level(pkg_lints, manifest.edition(), manifest.unstable_features());
+    if lint_level == LintLevel::Allow {
+        return Ok(());
+    }
+    let config = match pkg_lints.get(PACKAGE_CONTENTS.name) {
+        Some(TomlLint::Config(config)) => Some(&config.config),
+        _ => None,
+    };
+    let allowed: HashSet<&str> = config
+        .and_then(|config| config.get("allow"))
+        .and_then(|allow| allow.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|path| path.as_str())
+        .collect();
+    let max_file_size = match config.and_then(|config| config.get("max-file-size")) {
+        Some(value) => value
+            .as_integer()
+            .and_then(|size| u64::try_from(size).ok())
+            .ok_or_else(|| {
+                anyhow::format_err!(
+                    "`max-file-size` of `cargo::{}` must be a number of bytes, found `{value}`",
+                    PACKAGE_CONTENTS.name
+                )
+            })?,
+        None => DEFAULT_MAX_PACKAGE_FILE_SIZE,
+    };
+    let root = pkg.root();
+    let canonical_root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+
+    let mut emitted_source = None;
+    for (rel_path, disk_path) in files {
+        let rel_str = rel_path.display().to_string();
+        if allowed.contains(rel_str.as_str()) {
+            continue;
+        }
+        let mut problems = Vec::new();
+        // Files outside of the package root, like a `license-file` in the
+        // workspace root, were explicitly asked for.
+        if disk_path.starts_with(root) {
+            if let Ok(target) = std::fs::canonicalize(disk_path) {
+                if !target.starts_with(&canonical_root) {
+                    problems.push(format!(
+                        "`{rel_str}` is a symlink to `{}`, outside of the package root",
+                        target.display()
+                    ));
+                }
+            }
+        }
+        if let Ok(metadata) = std::fs::metadata(disk_path) {
+            if metadata.len() > max_file_size {
+                let size = human_readable_bytes(metadata.len());
+                let max = human_readable_bytes(max_file_size);
+                problems.push(format!(
+                    "`{rel_str}` is {:.1}{}, larger than the `max-file-size` of {:.1}{}",
+                    size.0, size.1, max.0, max.1
+                ));
+            }
+        }
+        let is_build_output = rel_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| BUILD_OUTPUT_EXTENSIONS.contains(&ext))
+            || rel_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| BUILD_OUTPUT_FILE_NAMES.contains(&name));
+        if is_build_output {
+            problems.push(format!("`{rel_str}` looks like a build output"));
+        }
+
+        for problem in problems {
+            if lint_level == LintLevel::Forbid || lint_level == LintLevel::Deny {
+                *error_count += 1;
+            }
+            let level = lint_level.to_diagnostic_level();
+            let title = format!("{}: {problem}", PACKAGE_CONTENTS.desc);
+            let mut message = level.title(&title);
+            if emitted_source.is_none() {
+                emitted_source = Some(format!(
+                    "`cargo::{}` is set to `{lint_level}` {reason}",
+                    PACKAGE_CONTENTS.name
+                ));
+                message = message.footer(Level::Note.title(emitted_source.as_ref().unwrap()));
+            }
+            message = message.footer(Level::Help.title(
+                "exclude it with `package.exclude`, or add it to the `allow` list of the lint",
+            ));
+            gctx.shell().print_message(message)?;
+        }
+    }
+    Ok(())
+}
+
+const STALE_DEPENDENCIES: Lint = Lint {
+    name: "stale_dependencies",
+    desc: "locked version is old and a newer compatible version is available",
+    groups: &[],
+    default_level: LintLevel::Allow,
+    edition_lint_opts: None,
+    feature_gate: None,
+};
+
+/// Default `max-age-days` of the `stale_dependencies` lint.
+const DEFAULT_MAX_DEPENDENCY_AGE_DAYS: i64 = 365;
+
+/// Checks the registry packages `pkg` depends on, directly or not, for
+/// versions published more than `max-age-days` ago while a newer SemVer
+/// compatible version is available in their registry.
+///
+/// Only versions whose publish date is recorded in the index are checked.
+/// Packages listed in the `allow` array of the lint configuration are
+/// skipped.
+pub fn check_stale_dependencies(
+    pkg: &Package,
+    pkg_lints: &TomlToolLints,
+    resolve: &Resolve,
+    registry: &mut PackageRegistry<'_>,
+    error_count: &mut usize,
+    gctx: &GlobalContext,
+) -> CargoResult<()> {
+    let manifest = pkg.manifest();
+    let (lint_level, reason) =
+        STALE_DEPENDENCIES.level(pkg_lints, manifest.edition(), manifest.unstable_features());
+    if lint_level == LintLevel::Allow {
+        return Ok(());
+    }
+    let config = match pkg_lints.get(STALE_DEPENDENCIES.name) {
+        Some(TomlLint::Config(config)) => Some(&config.config),
+        _ => None,
+    };
+    let allowed: HashSet<&str> = config
+        .and_then(|config| config.get("allow"))
+        .and_then(|allow| allow.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|name| name.as_str())
+        .collect();
+    let max_age_days = match config.and_then(|config| config.get("max-age-days")) {
+        Some(value) => value
+            .as_integer()
+            .filter(|days| *days >= 0)
+            .ok_or_else(|| {
+                anyhow::format_err!(
+                    "`max-age-days` of `cargo::{}` must be a number of days, found `{value}`",
+                    STALE_DEPENDENCIES.name
+                )
+            })?,
+        None => DEFAULT_MAX_DEPENDENCY_AGE_DAYS,
+    };
+    let now = OffsetDateTime::now_utc();
+
+    let root = pkg.package_id();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([root]);
+    while let Some(id) = queue.pop_front() {
+        for (dep_id, _) in resolve.deps(id) {
+            if dep_id != root && seen.insert(dep_id) {
+                queue.push_back(dep_id);
+            }
+        }
+    }
+    let mut stale = Vec::new();
+    for id in seen.into_iter().collect::<BTreeSet<_>>() {
+        if !id.source_id().is_registry() || allowed.contains(id.name().as_str()) {
+            continue;
+        }
+        let Some(pubtime) = resolve.summary(id).pubtime() else {
+            continue;
+        };
+        let age_days = (now - pubtime).whole_days();
+        if age_days <= max_age_days {
+            continue;
+        }
+        stale.push((id, age_days));
+    }
+    if stale.is_empty() {
+        return Ok(());
+    }
+
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+    let mut emitted_source = None;
+    for (id, age_days) in stale {
+        let current = id.version();
+        let compatible = VersionReq {
+            comparators: vec![Comparator {
+                op: Op::Caret,
+                major: current.major,
+                minor: Some(current.minor),
+                patch: Some(current.patch),
+                pre: current.pre.clone(),
+            }],
+        };
+        let query = Dependency::parse(id.name(), None, id.source_id())?;
+        let possibilities = loop {
+            match registry.query_vec(&query, QueryKind::Exact) {
+                Poll::Ready(res) => break res?,
+                Poll::Pending => registry.block_until_ready()?,
+            }
+        };
+        let Some(newest) = possibilities
+            .iter()
+            .filter(|s| !s.is_yanked())
+            .map(|s| s.as_summary().version())
+            .filter(|v| *v > current && compatible.matches(v))
+            .max()
+        else {
+            continue;
+        };
+
+        if lint_level == LintLevel::Forbid || lint_level == LintLevel::Deny {
+            *error_count += 1;
+        }
+        let level = lint_level.to_diagnostic_level();
+        let title = format!("{}: `{id}`", STALE_DEPENDENCIES.desc);
+        let mut message = level.title(&title);
+        if emitted_source.is_none() {
+            emitted_source = Some(format!(
+                "`cargo::{}` is set to `{lint_level}` {reason}",
+                STALE_DEPENDENCIES.name
+            ));
+            message = message.footer(Level::Note.title(emitted_source.as_ref().unwrap()));
+        }
+        let age_note = format!(
+            "`{} v{current}` was published {age_days} days ago, \
+             which is more than the `max-age-days` of {max_age_days}",
+            id.name()
+        );
+        message = message.footer(Level::Note.title(&age_note));
+        let help = format!(
+            "`{} v{newest}` is available, run `cargo update {}@{current}` to update it",
+            id.name(),
+            id.name()
+        );
+        message = message.footer(Level::Help.title(&help));
+        gctx.shell().print_message(message)?;
+    }
+    Ok(())
+}
@@ -5,7 +5,8 @@ use serde::ser;
 use serde::Serialize;
 use serde_json::{json, value::RawValue};
 
-use crate::core::compiler::CompileMode;
+use crate::core::compiler::fingerprint::RebuildDetails;
+use crate::core::compiler::{BuildScriptDiagnostic, CompileMode};
 use crate::core::Target;
 
 pub trait Message: ser::Serialize {
@@ -87,6 +88,39 @@ impl<'a> Message for BuildScript<'a> {
     }
 }
 
+/// The warnings and stderr of a build script, which are replayed from its
+/// last run when it is fresh.
+#[derive(Serialize)]
+pub struct BuildScriptOutput<'a> {
+    pub package_id: PackageIdSpec,
+    pub fresh: bool,
+    pub warnings: &'a [String],
+    pub diagnostics: &'a [BuildScriptDiagnostic],
+    pub stderr: &'a str,
+}
+
+impl<'a> Message for BuildScriptOutput<'a> {
+    fn reason(&self) -> &str {
+        "build-script-output"
+    }
+}
+
+/// Why a dirty unit is rebuilt, printed with `--explain-rebuild`.
+#[derive(Serialize)]
+pub struct RebuildReason<'a> {
+    pub package_id: PackageIdSpec,
+    pub target: &'a Target,
+    pub mode: CompileMode,
+    #[serde(flatten)]
+    pub details: RebuildDetails,
+}
+
+impl<'a> Message for RebuildReason<'a> {
+    fn reason(&self) -> &str {
+        "rebuild-reason"
+    }
+}
+
 #[derive(Serialize)]
 pub struct TimingInfo<'a> {
     pub package_id: PackageIdSpec,
@@ -113,3 +147,67 @@ impl Message for BuildFinished {
         "build-finished"
     }
 }
+
+#[derive(Serialize)]
+pub struct UnitStarted<'a> {
+    pub package_id: PackageIdSpec,
+    pub target: &'a Target,
+    pub mode: CompileMode,
+    pub fresh: bool,
+}
+
+impl<'a> Message for UnitStarted<'a> {
+    fn reason(&self) -> &str {
+        "unit-started"
+    }
+}
+
+#[derive(Serialize)]
+pub struct UnitFinished<'a> {
+    pub package_id: PackageIdSpec,
+    pub target: &'a Target,
+    pub mode: CompileMode,
+    pub success: bool,
+}
+
+impl<'a> Message for UnitFinished<'a> {
+    fn reason(&self) -> &str {
+        "unit-finished"
+    }
+}
+
+#[derive(Serialize)]
+pub struct BuildProgress {
+    pub finished: usize,
+    pub total: usize,
+    pub percentage: usize,
+}
+
+impl Message for BuildProgress {
+    fn reason(&self) -> &str {
+        "build-progress"
+    }
+}
+
+#[derive(Serialize)]
+pub struct DownloadStarted {
+    pub package_id: PackageIdSpec,
+}
+
+impl Message for DownloadStarted {
+    fn reason(&self) -> &str {
+        "download-started"
+    }
+}
+
+#[derive(Serialize)]
+pub struct DownloadFinished {
+    pub package_id: PackageIdSpec,
+    pub bytes: usize,
+}
+
+impl Message for DownloadFinished {
+    fn reason(&self) -> &str {
+        "download-finished"
+    }
+}
@@ -0,0 +1,127 @@
+//! Memory usage of the system and of the processes spawned by Cargo, used to
+//! limit the parallelism of builds with `build.max-memory`.
+//!
+//! The memory of processes is only measured on Linux. The available memory is
+//! known on Linux and Windows. Elsewhere, these functions return `None`.
+
+/// A thread which spawns processes, such as a job of the build running rustc.
+#[derive(Clone, Copy, Debug)]
+pub struct Thread(imp::Thread);
+
+impl Thread {
+    /// Identifies the calling thread.
+    pub fn current() -> Option<Thread> {
+        imp::current_thread().map(Thread)
+    }
+
+    /// Returns the memory currently used by the processes spawned by this
+    /// thread, and by their own descendants, in bytes.
+    ///
+    /// This includes processes spawned by the compiler, like the linker.
+    pub fn spawned_memory(&self) -> Option<u64> {
+        imp::spawned_memory(self.0)
+    }
+}
+
+/// Returns the memory available for new processes without swapping, in
+/// bytes.
+pub fn available() -> Option<u64> {
+    imp::available()
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::fs;
+
+    pub type Thread = libc::pid_t;
+
+    pub fn current_thread() -> Option<Thread> {
+        Some(unsafe { libc::syscall(libc::SYS_gettid) } as Thread)
+    }
+
+    pub fn spawned_memory(thread: Thread) -> Option<u64> {
+        // Requires a kernel built with `CONFIG_PROC_CHILDREN`, as most are.
+        let children = fs::read_to_string(format!("/proc/self/task/{thread}/children")).ok()?;
+        Some(pids(&children).map(tree_memory).sum())
+    }
+
+    pub fn available() -> Option<u64> {
+        let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+        let line = meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix("MemAvailable:"))?;
+        let kib: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+        Some(kib * 1024)
+    }
+
+    /// Resident memory of a process and of all its descendants.
+    fn tree_memory(pid: libc::pid_t) -> u64 {
+        let mut total = resident_memory(pid).unwrap_or(0);
+        // Children are listed per thread of the process which spawned them.
+        let Ok(tasks) = fs::read_dir(format!("/proc/{pid}/task")) else {
+            return total;
+        };
+        for task in tasks.flatten() {
+            if let Ok(children) = fs::read_to_string(task.path().join("children")) {
+                total += pids(&children).map(tree_memory).sum::<u64>();
+            }
+        }
+        total
+    }
+
+    fn resident_memory(pid: libc::pid_t) -> Option<u64> {
+        let statm = fs::read_to_string(format!("/proc/{pid}/statm")).ok()?;
+        let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        Some(pages * u64::try_from(page_size).ok()?)
+    }
+
+    fn pids(list: &str) -> impl Iterator<Item = libc::pid_t> + '_ {
+        list.split_whitespace().filter_map(|pid| pid.parse().ok())
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::mem;
+
+    use windows_sys::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    pub type Thread = ();
+
+    pub fn current_thread() -> Option<Thread> {
+        None
+    }
+
+    pub fn spawned_memory(_thread: Thread) -> Option<u64> {
+        None
+    }
+
+    pub fn available() -> Option<u64> {
+        unsafe {
+            let mut status: MEMORYSTATUSEX = mem::zeroed();
+            status.dwLength = mem::size_of::<MEMORYSTATUSEX>() as u32;
+            if GlobalMemoryStatusEx(&mut status) == 0 {
+                return None;
+            }
+            Some(status.ullAvailPhys)
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod imp {
+    pub type Thread = ();
+
+    pub fn current_thread() -> Option<Thread> {
+        None
+    }
+
+    pub fn spawned_memory(_thread: Thread) -> Option<u64> {
+        None
+    }
+
+    pub fn available() -> Option<u64> {
+        None
+    }
+}
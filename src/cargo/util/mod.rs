@@ -1,6 +1,7 @@
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+pub use self::cancel::{CancellationToken, Cancelled};
 pub use self::canonical_url::CanonicalUrl;
 pub use self::context::{homedir, ConfigValue, GlobalContext};
 pub(crate) use self::counter::MetricsCounter;
@@ -30,6 +31,7 @@ pub use self::workspace::{
 
 pub mod auth;
 pub mod cache_lock;
+pub mod cancel;
 mod canonical_url;
 pub mod command_prelude;
 pub mod context;
@@ -47,6 +49,7 @@ pub mod hex;
 mod hostname;
 pub mod important_paths;
 pub mod interning;
+pub mod interrupt;
 pub mod into_url;
 mod into_url_with_base;
 mod io;
@@ -54,6 +57,7 @@ pub mod job;
 pub mod lints;
 mod lockserver;
 pub mod machine_message;
+pub mod memory;
 pub mod network;
 mod progress;
 mod queue;
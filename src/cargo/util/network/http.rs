@@ -11,6 +11,8 @@ use curl::easy::SslVersion;
 use tracing::debug;
 use tracing::trace;
 
+use crate::core::SourceId;
+use crate::util::auth;
 use crate::util::context::SslVersionConfig;
 use crate::util::context::SslVersionConfigRange;
 use crate::version;
@@ -24,6 +26,16 @@ pub fn http_handle(gctx: &GlobalContext) -> CargoResult<Easy> {
     Ok(handle)
 }
 
+/// Creates a new HTTP handle for talking to the registry `sid`.
+///
+/// This is the same as [`http_handle`], with the proxy and TLS settings from
+/// `[registries.<name>]` layered on top of the global `[http]` configuration.
+pub fn http_handle_for_registry(gctx: &GlobalContext, sid: SourceId) -> CargoResult<Easy> {
+    let mut handle = http_handle(gctx)?;
+    configure_registry_http_handle(gctx, sid, &mut handle)?;
+    Ok(handle)
+}
+
 pub fn http_handle_and_timeout(gctx: &GlobalContext) -> CargoResult<(Easy, HttpTimeout)> {
     if gctx.frozen() {
         bail!(
@@ -188,6 +200,43 @@ pub fn configure_http_handle(gctx: &GlobalContext, handle: &mut Easy) -> CargoRe
     HttpTimeout::new(gctx)
 }
 
+/// Applies the `[registries.<name>]` proxy and TLS settings of `sid` to a
+/// handle that was already configured with [`configure_http_handle`].
+///
+/// crates.io, and registries without a name, always use `[http]` alone.
+pub fn configure_registry_http_handle(
+    gctx: &GlobalContext,
+    sid: SourceId,
+    handle: &mut Easy,
+) -> CargoResult<()> {
+    if !sid.is_remote_registry() || sid.is_crates_io() {
+        return Ok(());
+    }
+    let Some(cfg) = auth::registry_credential_config_raw(gctx, &sid)? else {
+        return Ok(());
+    };
+    if let Some(proxy) = &cfg.proxy {
+        handle.proxy(proxy)?;
+    }
+    if let Some(cainfo) = &cfg.cainfo {
+        handle.cainfo(&cainfo.resolve_path(gctx))?;
+    }
+    match (&cfg.client_cert, &cfg.client_key) {
+        (Some(cert), key) => {
+            handle.ssl_cert(&cert.resolve_path(gctx))?;
+            if let Some(key) = key {
+                handle.ssl_key(&key.resolve_path(gctx))?;
+            }
+        }
+        (None, Some(_)) => bail!(
+            "`client-key` is set for registry `{}`, but `client-cert` is not",
+            sid.display_registry_name()
+        ),
+        (None, None) => {}
+    }
+    Ok(())
+}
+
 #[must_use]
 pub struct HttpTimeout {
     pub dur: Duration,
@@ -10,6 +10,7 @@ use std::task::Poll;
 pub mod http;
 pub mod proxy;
 pub mod retry;
+pub mod simulate;
 pub mod sleep;
 
 /// LOCALHOST constants for both IPv4 and IPv6.
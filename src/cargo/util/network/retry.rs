@@ -101,8 +101,20 @@ impl<'a> Retry<'a> {
     /// Calls the given callback, and returns a [`RetryResult`] which
     /// indicates whether or not this needs to be called again at some point
     /// in the future to retry the operation if it failed.
+    ///
+    /// With a [`SimulatedNetwork`], the attempt may fail without calling it.
+    ///
+    /// [`SimulatedNetwork`]: super::simulate::SimulatedNetwork
     pub fn r#try<T>(&mut self, f: impl FnOnce() -> CargoResult<T>) -> RetryResult<T> {
-        match f() {
+        let simulated = self
+            .gctx
+            .simulated_network()
+            .and_then(|network| network.attempt());
+        let result = match simulated {
+            Some(e) => Err(e),
+            None => f(),
+        };
+        match result {
             Err(ref e) if maybe_spurious(e) && self.retries < self.max_retries => {
                 let err_msg = e
                     .downcast_ref::<HttpNotSuccessful>()
@@ -0,0 +1,113 @@
+//! Deterministic network failures, to test retries and fallbacks end-to-end.
+//!
+//! A [`SimulatedNetwork`] is set on the [`GlobalContext`] by the tools
+//! embedding Cargo, or with `cargo --simulate-network <SPEC>` when the
+//! [`ENABLE_ENV`] environment variable is set. Every network operation retried
+//! with [`Retry`] counts as an attempt, like fetching a git repository or
+//! downloading a crate or an index file. Once `fail-after` attempts ran, the
+//! next ones fail with a spurious error instead of accessing the network, at
+//! most `times` of them if set, which is then retried like any other network
+//! failure.
+//!
+//! The specification is a comma-separated list of `key=value`:
+//!
+//! - `fail-after=N`: the number of attempts running normally.
+//! - `times=M`: the number of attempts failing after them, all by default.
+//!
+//! [`GlobalContext`]: crate::GlobalContext
+//! [`Retry`]: super::retry::Retry
+
+use std::cell::Cell;
+use std::str::FromStr;
+
+use anyhow::{bail, Context as _};
+
+use crate::CargoResult;
+
+/// The environment variable enabling `cargo --simulate-network`.
+pub const ENABLE_ENV: &str = "__CARGO_TEST_ENABLE_SIMULATE_NETWORK";
+
+/// Injects failures into the network operations of Cargo.
+#[derive(Debug)]
+pub struct SimulatedNetwork {
+    /// The number of attempts running normally.
+    fail_after: u64,
+    /// The number of attempts failing after them, or `None` for all of them.
+    times: Option<u64>,
+    /// The number of attempts so far.
+    attempts: Cell<u64>,
+}
+
+impl SimulatedNetwork {
+    pub fn new(fail_after: u64, times: Option<u64>) -> SimulatedNetwork {
+        SimulatedNetwork {
+            fail_after,
+            times,
+            attempts: Cell::new(0),
+        }
+    }
+
+    /// Counts a network attempt, and returns the error it must fail with
+    /// instead of running, if any.
+    pub fn attempt(&self) -> Option<anyhow::Error> {
+        let attempt = self.attempts.get();
+        self.attempts.set(attempt + 1);
+        let failed = attempt.checked_sub(self.fail_after)?;
+        if self.times.is_some_and(|times| failed >= times) {
+            return None;
+        }
+        let mut err = curl::Error::new(curl_sys::CURLE_COULDNT_CONNECT);
+        err.set_extra(format!("simulated network failure {}", failed + 1));
+        Some(err.into())
+    }
+}
+
+impl FromStr for SimulatedNetwork {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> CargoResult<SimulatedNetwork> {
+        let mut fail_after = None;
+        let mut times = None;
+        for pair in spec.split(',') {
+            let Some((key, value)) = pair.split_once('=') else {
+                bail!("expected `key=value` in the network simulation, found `{pair}`");
+            };
+            let value = value.parse::<u64>().with_context(|| {
+                format!("invalid value `{value}` for `{key}` in the network simulation")
+            })?;
+            match key {
+                "fail-after" => fail_after = Some(value),
+                "times" => times = Some(value),
+                _ => bail!(
+                    "unknown key `{key}` in the network simulation, \
+                     expected `fail-after` or `times`"
+                ),
+            }
+        }
+        let Some(fail_after) = fail_after else {
+            bail!("the network simulation requires `fail-after=N`");
+        };
+        Ok(SimulatedNetwork::new(fail_after, times))
+    }
+}
+
+#[test]
+fn fails_after_attempts() {
+    let network: SimulatedNetwork = "fail-after=1,times=2".parse().unwrap();
+    let failed = (0..5)
+        .map(|_| network.attempt().is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(failed, [false, true, true, false, false]);
+
+    let network: SimulatedNetwork = "fail-after=0".parse().unwrap();
+    assert!((0..5).all(|_| network.attempt().is_some()));
+}
+
+#[test]
+fn parses_spec() {
+    assert!("times=1".parse::<SimulatedNetwork>().is_err());
+    assert!("fail-after=x".parse::<SimulatedNetwork>().is_err());
+    assert!("fail-after=1,retries=2"
+        .parse::<SimulatedNetwork>()
+        .is_err());
+}
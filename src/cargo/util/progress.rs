@@ -100,9 +100,13 @@ impl<'gctx> Progress<'gctx> {
             Err(_) => false,
         };
         let progress_config = gctx.progress_config();
+        // Progress is reported through JSON events instead of the bar.
+        if gctx.shell().json_progress() {
+            return Progress { state: None };
+        }
         match progress_config.when {
             ProgressWhen::Always => return Progress::new_priv(name, style, gctx),
-            ProgressWhen::Never => return Progress { state: None },
+            ProgressWhen::Never | ProgressWhen::Json => return Progress { state: None },
             ProgressWhen::Auto => {}
         }
         if gctx.shell().verbosity() == Verbosity::Quiet || dumb || is_ci() {
@@ -78,7 +78,15 @@ fn expand_manifest_(
 ) -> CargoResult<toml::Table> {
     let mut manifest: toml::Table = toml::from_str(&manifest)?;
 
-    for key in ["workspace", "lib", "bin", "example", "test", "bench"] {
+    for key in [
+        "workspace",
+        "lib",
+        "bin",
+        "example",
+        "test",
+        "bench",
+        "fuzz",
+    ] {
         if manifest.contains_key(key) {
             anyhow::bail!("`{key}` is not allowed in embedded manifests")
         }
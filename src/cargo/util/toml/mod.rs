@@ -252,6 +252,9 @@ fn to_workspace_root_config(
         &resolved_toml.exclude,
         &Some(inheritable),
         &resolved_toml.metadata,
+        &resolved_toml.build,
+        &resolved_toml.dependency_pins,
+        &resolved_toml.bans,
     );
     ws_root_config
 }
@@ -268,9 +271,24 @@ fn resolve_toml(
     errors: &mut Vec<String>,
 ) -> CargoResult<manifest::TomlManifest> {
     if let Some(workspace) = &original_toml.workspace {
-        if workspace.resolver.as_deref() == Some("3") {
+        if matches!(workspace.resolver.as_deref(), Some("3" | "msrv")) {
             features.require(Feature::edition2024())?;
         }
+        if workspace.build.is_some() {
+            features
+                .require(Feature::per_package_target())
+                .with_context(|| "the `[workspace.build]` manifest table is unstable")?;
+        }
+        if workspace.dependency_pins.is_some() {
+            features.require(Feature::dependency_pins())?;
+        }
+        if workspace.bans.is_some() {
+            features.require(Feature::bans())?;
+        }
+        let mut members = workspace.members.iter().chain(&workspace.default_members);
+        if members.any(|m| m.iter().any(|m| m.starts_with("git+"))) {
+            features.require(Feature::git_members())?;
+        }
     }
 
     let mut resolved_toml = manifest::TomlManifest {
@@ -283,6 +301,7 @@ fn resolve_toml(
         example: None,
         test: None,
         bench: None,
+        fuzz: None,
         dependencies: None,
         dev_dependencies: None,
         dev_dependencies2: None,
@@ -295,6 +314,9 @@ fn resolve_toml(
         workspace: original_toml.workspace.clone(),
         badges: None,
         lints: None,
+        run: None,
+        build_directives: None,
+        system_dependencies: None,
         _unused_keys: Default::default(),
     };
 
@@ -360,10 +382,16 @@ fn resolve_toml(
             warnings,
             errors,
         )?);
+        resolved_toml.fuzz = original_toml
+            .fuzz
+            .as_ref()
+            .map(|fuzz| {
+                targets::resolve_fuzz_targets(fuzz, package_root, edition, warnings, errors)
+            })
+            .transpose()?;
 
         let activated_opt_deps = resolved_toml
-            .features
-            .as_ref()
+            .features()
             .map(|map| {
                 map.values()
                     .flatten()
@@ -376,9 +404,7 @@ fn resolve_toml(
             .unwrap_or_default();
 
         resolved_toml.dependencies = resolve_dependencies(
-            gctx,
             edition,
-            &features,
             original_toml.dependencies.as_ref(),
             &activated_opt_deps,
             None,
@@ -396,9 +422,7 @@ fn resolve_toml(
             warnings,
         )?;
         resolved_toml.dev_dependencies = resolve_dependencies(
-            gctx,
             edition,
-            &features,
             original_toml.dev_dependencies(),
             &activated_opt_deps,
             Some(DepKind::Development),
@@ -416,9 +440,7 @@ fn resolve_toml(
             warnings,
         )?;
         resolved_toml.build_dependencies = resolve_dependencies(
-            gctx,
             edition,
-            &features,
             original_toml.build_dependencies(),
             &activated_opt_deps,
             Some(DepKind::Build),
@@ -429,9 +451,7 @@ fn resolve_toml(
         let mut resolved_target = BTreeMap::new();
         for (name, platform) in original_toml.target.iter().flatten() {
             let resolved_dependencies = resolve_dependencies(
-                gctx,
                 edition,
-                &features,
                 platform.dependencies.as_ref(),
                 &activated_opt_deps,
                 None,
@@ -449,9 +469,7 @@ fn resolve_toml(
                 warnings,
             )?;
             let resolved_dev_dependencies = resolve_dependencies(
-                gctx,
                 edition,
-                &features,
                 platform.dev_dependencies(),
                 &activated_opt_deps,
                 Some(DepKind::Development),
@@ -469,9 +487,7 @@ fn resolve_toml(
                 warnings,
             )?;
             let resolved_build_dependencies = resolve_dependencies(
-                gctx,
                 edition,
-                &features,
                 platform.build_dependencies(),
                 &activated_opt_deps,
                 Some(DepKind::Build),
@@ -503,6 +519,9 @@ fn resolve_toml(
         });
 
         resolved_toml.badges = original_toml.badges.clone();
+        resolved_toml.run = original_toml.run.clone();
+        resolved_toml.build_directives = original_toml.build_directives.clone();
+        resolved_toml.system_dependencies = original_toml.system_dependencies.clone();
     } else {
         for field in original_toml.requires_package() {
             bail!("this virtual manifest specifies a `{field}` section, which is not allowed");
@@ -550,6 +569,8 @@ fn resolve_package_toml<'a>(
         default_target: original_package.default_target.clone(),
         forced_target: original_package.forced_target.clone(),
         links: original_package.links.clone(),
+        links_metadata: original_package.links_metadata.clone(),
+        links_consume: original_package.links_consume.clone(),
         exclude: original_package
             .exclude
             .clone()
@@ -568,6 +589,14 @@ fn resolve_package_toml<'a>(
             .map(|value| field_inherit_with(value, "publish", || inherit()?.publish()))
             .transpose()?
             .map(manifest::InheritableField::Value),
+        publish_lockfile: original_package
+            .publish_lockfile
+            .clone()
+            .map(|value| {
+                field_inherit_with(value, "publish-lockfile", || inherit()?.publish_lockfile())
+            })
+            .transpose()?
+            .map(manifest::InheritableField::Value),
         workspace: original_package.workspace.clone(),
         im_a_teapot: original_package.im_a_teapot.clone(),
         autobins: Some(false),
@@ -647,7 +676,7 @@ fn resolve_package_toml<'a>(
         _invalid_cargo_features: Default::default(),
     };
 
-    if resolved_package.resolver.as_deref() == Some("3") {
+    if matches!(resolved_package.resolver.as_deref(), Some("3" | "msrv")) {
         features.require(Feature::edition2024())?;
     }
 
@@ -685,9 +714,7 @@ fn default_readme_from_package_root(package_root: &Path) -> Option<String> {
 
 #[tracing::instrument(skip_all)]
 fn resolve_dependencies<'a>(
-    gctx: &GlobalContext,
     edition: Edition,
-    features: &Features,
     orig_deps: Option<&BTreeMap<manifest::PackageName, manifest::InheritableDependency>>,
     activated_opt_deps: &HashSet<&str>,
     kind: Option<DepKind>,
@@ -719,36 +746,11 @@ fn resolve_dependencies<'a>(
                 edition,
                 warnings,
             )?;
-            if d.public.is_some() {
-                let public_feature = features.require(Feature::public_dependency());
-                let with_public_feature = public_feature.is_ok();
-                let with_z_public = gctx.cli_unstable().public_dependency;
-                if !with_public_feature && (!with_z_public && !gctx.nightly_features_allowed) {
-                    public_feature?;
-                }
-                if matches!(kind, None) {
-                    if !with_public_feature && !with_z_public {
-                        d.public = None;
-                        warnings.push(format!(
-                            "ignoring `public` on dependency {name_in_toml}, pass `-Zpublic-dependency` to enable support for it"
-                        ))
-                    }
-                } else {
-                    let kind_name = match kind {
-                        Some(k) => k.kind_table(),
-                        None => "dependencies",
-                    };
-                    let hint = format!(
-                        "'public' specifier can only be used on regular dependencies, not {kind_name}",
-                    );
-                    if with_public_feature || with_z_public {
-                        bail!(hint)
-                    } else {
-                        // If public feature isn't enabled in nightly, we instead warn that.
-                        warnings.push(hint);
-                        d.public = None;
-                    }
-                }
+            if let (Some(_), Some(kind)) = (d.public, kind) {
+                bail!(
+                    "'public' specifier can only be used on regular dependencies, not {}",
+                    kind.kind_table()
+                );
             }
         }
 
@@ -864,6 +866,7 @@ impl InheritableFields {
         ("keywords",      keywords      -> Vec<String>),
         ("license",       license       -> String),
         ("publish",       publish       -> manifest::VecStringOrBool),
+        ("publish-lockfile", publish_lockfile -> bool),
         ("repository",    repository    -> String),
         ("rust-version",  rust_version  -> RustVersion),
         ("version",       version       -> semver::Version),
@@ -1196,6 +1199,49 @@ fn to_real_manifest(
         features.require(Feature::metabuild())?;
     }
 
+    if resolved_toml.run.is_some() {
+        features.require(Feature::run_args())?;
+    }
+
+    if resolved_toml.fuzz.is_some() {
+        features.require(Feature::fuzz_targets())?;
+    }
+
+    if resolved_toml.build_directives.is_some() {
+        features.require(Feature::build_directives())?;
+        if let Some(StringOrBool::String(_)) = &resolved_package.build {
+            bail!("`[build-directives]` can't be used along with a build script");
+        }
+    }
+
+    if let Some(system_deps) = &resolved_toml.system_dependencies {
+        features.require(Feature::system_dependencies())?;
+        for (name, dep) in system_deps {
+            if let Some(version) = dep.version() {
+                semver::VersionReq::parse(version).with_context(|| {
+                    format!(
+                        "failed to parse the version requirement `{version}` \
+                         of the system dependency `{name}`"
+                    )
+                })?;
+            }
+        }
+    }
+
+    if resolved_package.links_metadata.is_some() || resolved_package.links_consume.is_some() {
+        features.require(Feature::links_metadata())?;
+    }
+    // Packages published before `publish-lockfile` was removed in 1.37 may
+    // still set it. It only matters when packaging, so it's only checked for
+    // local packages.
+    if resolved_package.publish_lockfile.is_some() && source_id.is_path() {
+        features.require(Feature::lockfile_policy())?;
+    }
+
+    if resolved_package.links_metadata.is_some() && resolved_package.links.is_none() {
+        bail!("`package.links-metadata` requires `package.links` to be set");
+    }
+
     let resolve_behavior = match (
         resolved_package.resolver.as_ref(),
         resolved_toml
@@ -1435,12 +1481,14 @@ fn to_real_manifest(
             .unwrap_or_else(|| semver::Version::new(0, 0, 0)),
         source_id,
     );
-    let summary = Summary::new(
+    if deps.iter().any(|dep| dep.features_profile().is_some()) {
+        features.require(Feature::feature_profiles())?;
+    }
+    let mut summary = Summary::new(
         pkgid,
         deps,
         &resolved_toml
-            .features
-            .as_ref()
+            .features()
             .unwrap_or(&Default::default())
             .iter()
             .map(|(k, v)| {
@@ -1460,6 +1508,45 @@ fn to_real_manifest(
                 .to_string(),
         )
     }
+    if let Some(conflicts) = resolved_toml.feature_conflicts() {
+        features.require(Feature::feature_conflicts())?;
+        for (feature, others) in conflicts {
+            for name in std::iter::once(feature.as_str()).chain(others.iter().map(String::as_str)) {
+                if !summary.features().contains_key(name) {
+                    bail!(
+                        "feature `{name}` in `[features.conflicts]` is not defined in `[features]`"
+                    );
+                }
+            }
+            if others.iter().any(|other| other == feature.as_str()) {
+                bail!("feature `{feature}` cannot conflict with itself in `[features.conflicts]`");
+            }
+        }
+    }
+    if let Some(profiles) = resolved_toml.feature_profiles() {
+        features.require(Feature::feature_profiles())?;
+        for (profile, profile_features) in profiles {
+            for name in profile_features {
+                if !summary.features().contains_key(name.as_str()) {
+                    bail!(
+                        "feature `{name}` of profile `{profile}` in `[features.profiles]` \
+                         is not defined in `[features]`"
+                    );
+                }
+            }
+        }
+        summary.set_feature_profiles(
+            profiles
+                .iter()
+                .map(|(profile, profile_features)| {
+                    (
+                        InternedString::new(profile),
+                        profile_features.iter().map(InternedString::from).collect(),
+                    )
+                })
+                .collect(),
+        );
+    }
 
     if let Some(run) = &resolved_package.default_run {
         if !targets
@@ -1476,9 +1563,15 @@ fn to_real_manifest(
     let default_kind = resolved_package
         .default_target
         .as_ref()
-        .map(|t| CompileTarget::new(&*t))
-        .transpose()?
-        .map(CompileKind::Target);
+        .map(|t| -> CargoResult<_> {
+            // `host` opts out of `workspace.build.targets`.
+            if t == "host" {
+                Ok(CompileKind::Host)
+            } else {
+                Ok(CompileKind::Target(CompileTarget::new(&*t)?))
+            }
+        })
+        .transpose()?;
     let forced_kind = resolved_package
         .forced_target
         .as_ref()
@@ -1881,10 +1974,22 @@ fn detailed_dep_to_dependency<P: ResolveToPath + Clone>(
         None => (name_in_toml, None),
     };
 
+    // A feature profile replaces the default features.
+    if orig.features_profile.is_some() && orig.default_features() == Some(true) {
+        bail!(
+            "dependency ({name_in_toml}) cannot set both `features-profile` and \
+             `default-features = true`"
+        );
+    }
+
     let version = orig.version.as_deref();
     let mut dep = Dependency::parse(pkg_name, version, new_source_id)?;
     dep.set_features(orig.features.iter().flatten())
-        .set_default_features(orig.default_features().unwrap_or(true))
+        .set_default_features(
+            orig.default_features()
+                .unwrap_or(orig.features_profile.is_none()),
+        )
+        .set_features_profile(orig.features_profile.as_deref())
         .set_optional(orig.optional.unwrap_or(false))
         .set_platform(manifest_ctx.platform.clone());
     if let Some(registry) = &orig.registry {
@@ -2132,12 +2237,26 @@ pub fn validate_profile(
     }
 
     // `inherits` validation
-    if matches!(root.inherits.as_deref(), Some("debug")) {
-        bail!(
-            "profile.{}.inherits=\"debug\" should be profile.{}.inherits=\"dev\"",
-            name,
-            name
-        );
+    if let Some(inherits) = &root.inherits {
+        if let manifest::TomlProfileInherits::Many(names) = inherits {
+            match (
+                features.require(Feature::profile_stack()),
+                cli_unstable.profile_stack,
+            ) {
+                (Err(e), false) => return Err(e),
+                _ => {}
+            }
+            if names.is_empty() {
+                bail!("`inherits` of profile `{}` must not be empty", name);
+            }
+        }
+        if inherits.names().iter().any(|parent| parent == "debug") {
+            bail!(
+                "profile.{}.inherits=\"debug\" should be profile.{}.inherits=\"dev\"",
+                name,
+                name
+            );
+        }
     }
 
     match name {
@@ -2219,6 +2338,25 @@ fn validate_profile_layer(
             _ => {}
         }
     }
+    if profile.strip_keep_symbols.is_some() || profile.compress_debuginfo.is_some() {
+        match (
+            features.require(Feature::profile_binary_size()),
+            cli_unstable.profile_binary_size,
+        ) {
+            (Err(e), false) => return Err(e),
+            _ => {}
+        }
+    }
+    if let Some(compress_debuginfo) = &profile.compress_debuginfo {
+        if !["none", "zlib", "zstd"].contains(&compress_debuginfo.as_str()) {
+            bail!(
+                "`profile.{}.compress-debuginfo` setting of `{}` is not a valid setting, \
+                 must be `none`, `zlib` or `zstd`",
+                name,
+                compress_debuginfo,
+            );
+        }
+    }
     Ok(())
 }
 
@@ -2605,6 +2743,7 @@ fn prepare_toml_for_publish(
     let example = prepare_targets_for_publish(me.example.as_ref(), included, "example", ws.gctx())?;
     let test = prepare_targets_for_publish(me.test.as_ref(), included, "test", ws.gctx())?;
     let bench = prepare_targets_for_publish(me.bench.as_ref(), included, "benchmark", ws.gctx())?;
+    let fuzz = prepare_targets_for_publish(me.fuzz.as_ref(), included, "fuzz target", ws.gctx())?;
 
     let all = |_d: &manifest::TomlDependency| true;
     let mut manifest = manifest::TomlManifest {
@@ -2616,6 +2755,7 @@ fn prepare_toml_for_publish(
         example,
         test,
         bench,
+        fuzz,
         dependencies: map_deps(gctx, me.dependencies.as_ref(), all)?,
         dev_dependencies: map_deps(
             gctx,
@@ -2657,6 +2797,9 @@ fn prepare_toml_for_publish(
         badges: me.badges.clone(),
         cargo_features: me.cargo_features.clone(),
         lints: me.lints.clone(),
+        run: me.run.clone(),
+        build_directives: me.build_directives.clone(),
+        system_dependencies: me.system_dependencies.clone(),
         _unused_keys: Default::default(),
     };
     strip_features(&mut manifest);
@@ -2685,7 +2828,7 @@ fn prepare_toml_for_publish(
                 insert_dep_name(&mut dep_name_set, v.build_dependencies());
             });
         }
-        let features = manifest.features.as_mut();
+        let features = manifest.features.as_mut().map(|f| &mut f.features);
 
         let Some(features) = features else {
             return;
@@ -6,6 +6,7 @@
 //!  * `examples/*.rs` are examples
 //!  * `tests/*.rs` are integration tests
 //!  * `benches/*.rs` are benchmarks
+//!  * `fuzz/*.rs` are fuzz targets, only when listed in `[[fuzz]]`
 //!
 //! It is a bit tricky because we need match explicit information from `Cargo.toml`
 //! with implicit info in directory layout.
@@ -17,7 +18,7 @@ use std::path::{Path, PathBuf};
 use anyhow::Context as _;
 use cargo_util_schemas::manifest::{
     PathValue, StringOrBool, StringOrVec, TomlBenchTarget, TomlBinTarget, TomlExampleTarget,
-    TomlLibTarget, TomlManifest, TomlTarget, TomlTestTarget,
+    TomlFuzzTarget, TomlLibTarget, TomlManifest, TomlTarget, TomlTestTarget,
 };
 
 use crate::core::compiler::rustdoc::RustdocScrapeExamples;
@@ -30,6 +31,7 @@ use crate::util::toml::deprecated_underscore;
 const DEFAULT_TEST_DIR_NAME: &'static str = "tests";
 const DEFAULT_BENCH_DIR_NAME: &'static str = "benches";
 const DEFAULT_EXAMPLE_DIR_NAME: &'static str = "examples";
+const DEFAULT_FUZZ_DIR_NAME: &'static str = "fuzz";
 
 #[tracing::instrument(skip_all)]
 pub(super) fn to_targets(
@@ -83,6 +85,12 @@ pub(super) fn to_targets(
         edition,
     )?);
 
+    targets.extend(to_fuzz_targets(
+        resolved_toml.fuzz.as_deref().unwrap_or_default(),
+        package_root,
+        edition,
+    )?);
+
     // processing the custom build script
     if let Some(custom_build) = package.resolved_build().expect("should be resolved") {
         if metabuild.is_some() {
@@ -548,6 +556,58 @@ fn to_bench_targets(
     Ok(result)
 }
 
+/// Fuzz targets are never auto-discovered, as `fuzz/` is commonly the
+/// separate package of `cargo fuzz`. Only their path is inferred.
+#[tracing::instrument(skip_all)]
+pub fn resolve_fuzz_targets(
+    toml_fuzz: &Vec<TomlFuzzTarget>,
+    package_root: &Path,
+    edition: Edition,
+    warnings: &mut Vec<String>,
+    errors: &mut Vec<String>,
+) -> CargoResult<Vec<TomlFuzzTarget>> {
+    let mut inferred = || infer_from_directory(&package_root, Path::new(DEFAULT_FUZZ_DIR_NAME));
+
+    let targets = resolve_targets(
+        "fuzz",
+        "fuzz",
+        Some(toml_fuzz),
+        &mut inferred,
+        package_root,
+        edition,
+        Some(false),
+        warnings,
+        errors,
+        "autofuzz",
+    )?;
+
+    Ok(targets)
+}
+
+#[tracing::instrument(skip_all)]
+fn to_fuzz_targets(
+    targets: &[TomlFuzzTarget],
+    package_root: &Path,
+    edition: Edition,
+) -> CargoResult<Vec<Target>> {
+    validate_unique_names(&targets, "fuzz")?;
+
+    let mut result = Vec::new();
+    for toml in targets {
+        let path = package_root.join(&toml.path.as_ref().expect("previously resolved").0);
+        let mut target = Target::fuzz_target(
+            name_or_panic(&toml),
+            path,
+            toml.required_features.clone(),
+            edition,
+        );
+        configure(&toml, &mut target)?;
+        result.push(target);
+    }
+
+    Ok(result)
+}
+
 fn is_resolved(toml_targets: Option<&Vec<TomlTarget>>, autodiscover: Option<bool>) -> bool {
     if autodiscover != Some(false) {
         return false;
@@ -887,6 +947,7 @@ fn configure(toml: &TomlTarget, target: &mut Target) -> CargoResult<()> {
 /// bench -> benches/*.rs, benches/*/main.rs
 /// example -> examples/*.rs, examples/*/main.rs
 /// bin -> src/bin/*.rs, src/bin/*/main.rs
+/// fuzz -> fuzz/*.rs, fuzz/*/main.rs
 ///
 /// Note that the logic need to sync with [`infer_from_directory`] if changes.
 fn target_path_not_found_error_message(
@@ -903,10 +964,16 @@ fn target_path_not_found_error_message(
                 target_path.push("src");
                 target_path.push("bins");
             }
+            // The layout of `cargo fuzz`
+            ("fuzz", true) => {
+                target_path.push(DEFAULT_FUZZ_DIR_NAME);
+                target_path.push("fuzz_targets");
+            }
             // default inferred paths
             ("test", false) => target_path.push(DEFAULT_TEST_DIR_NAME),
             ("bench", false) => target_path.push(DEFAULT_BENCH_DIR_NAME),
             ("example", false) => target_path.push(DEFAULT_EXAMPLE_DIR_NAME),
+            ("fuzz", false) => target_path.push(DEFAULT_FUZZ_DIR_NAME),
             ("bin", false) => {
                 target_path.push("src");
                 target_path.push("bin");
@@ -6,7 +6,7 @@ use std::str;
 
 use anyhow::Context as _;
 
-use super::dependency::Dependency;
+use super::dependency::{Dependency, RegistrySource, Source};
 use crate::core::dependency::DepKind;
 use crate::core::FeatureValue;
 use crate::util::interning::InternedString;
@@ -191,6 +191,38 @@ impl Manifest {
         sections
     }
 
+    /// Get mutable references to all tables in the manifest that might
+    /// contain dependencies, including `[workspace.dependencies]`.
+    pub fn get_dependency_tables_mut(
+        &mut self,
+    ) -> impl Iterator<Item = &mut dyn toml_edit::TableLike> + '_ {
+        let is_dep_table = |key: &str| DepTable::KINDS.iter().any(|t| t.kind.kind_table() == key);
+        self.data.as_table_mut().iter_mut().flat_map(
+            move |(key, item)| -> Vec<&mut dyn toml_edit::TableLike> {
+                match key.get() {
+                    key if is_dep_table(key) => item.as_table_like_mut().into_iter().collect(),
+                    "workspace" => item
+                        .as_table_like_mut()
+                        .into_iter()
+                        .flat_map(toml_edit::TableLike::iter_mut)
+                        .filter(|(key, _)| key.get() == "dependencies")
+                        .filter_map(|(_, item)| item.as_table_like_mut())
+                        .collect(),
+                    "target" => item
+                        .as_table_like_mut()
+                        .into_iter()
+                        .flat_map(toml_edit::TableLike::iter_mut)
+                        .filter_map(|(_, target_table)| target_table.as_table_like_mut())
+                        .flat_map(toml_edit::TableLike::iter_mut)
+                        .filter(|(key, _)| is_dep_table(key.get()))
+                        .filter_map(|(_, item)| item.as_table_like_mut())
+                        .collect(),
+                    _ => Vec::new(),
+                }
+            },
+        )
+    }
+
     pub fn get_legacy_sections(&self) -> Vec<String> {
         let mut result = Vec::new();
 
@@ -218,6 +250,44 @@ impl Manifest {
         }
         result
     }
+
+    /// Add `member` to `workspace.members`, creating the array if needed.
+    ///
+    /// Returns `false` if `member` is already matched by one of the members.
+    /// If the existing members are sorted, they are kept sorted.
+    pub fn add_workspace_member(&mut self, member: &str) -> CargoResult<bool> {
+        let workspace = self
+            .data
+            .get_mut("workspace")
+            .and_then(|w| w.as_table_like_mut())
+            .ok_or_else(|| non_existent_table_err("workspace"))?;
+        crate::ops::add_workspace_member(workspace, member)
+    }
+
+    /// Remove `member` from `workspace.members`.
+    ///
+    /// Returns `false` if `member` isn't listed. Glob patterns are only
+    /// removed when given verbatim.
+    pub fn remove_workspace_member(&mut self, member: &str) -> CargoResult<bool> {
+        let workspace = self
+            .data
+            .get_mut("workspace")
+            .and_then(|w| w.as_table_like_mut())
+            .ok_or_else(|| non_existent_table_err("workspace"))?;
+        let Some(members) = workspace
+            .get_mut("members")
+            .and_then(|members| members.as_array_mut())
+        else {
+            return Ok(false);
+        };
+        match members.iter().position(|m| m.as_str() == Some(member)) {
+            Some(idx) => {
+                remove_array_index(members, idx);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
 }
 
 impl str::FromStr for Manifest {
@@ -275,6 +345,9 @@ impl LocalManifest {
     }
 
     /// Write changes back to the file.
+    ///
+    /// This requires the manifest to be the one of a package, see
+    /// [`LocalManifest::write_any`] to also write virtual manifests.
     pub fn write(&self) -> CargoResult<()> {
         if !self.manifest.data.contains_key("package")
             && !self.manifest.data.contains_key("project")
@@ -293,12 +366,49 @@ impl LocalManifest {
             }
         }
 
+        self.write_any()
+    }
+
+    /// Write changes back to the file, whether it is the manifest of a
+    /// package or a virtual manifest.
+    pub fn write_any(&self) -> CargoResult<()> {
         let s = self.manifest.data.to_string();
         let new_contents_bytes = s.as_bytes();
 
         cargo_util::paths::write_atomic(&self.path, new_contents_bytes)
     }
 
+    /// Lookup the dependency `name` in the table at `table_path`.
+    pub fn get_dependency(&self, table_path: &[String], name: &str) -> CargoResult<Dependency> {
+        let crate_root = self.path.parent().expect("manifest path is absolute");
+        let table = self.get_table(table_path)?;
+        match table.get(name).filter(|i| !i.is_none()) {
+            Some(item) => Dependency::from_toml(crate_root, name, item),
+            None => Err(self.dependency_not_found(table_path, name)),
+        }
+    }
+
+    /// Set the version requirement of the dependency `name` in the table at
+    /// `table_path`, keeping its source, features and formatting.
+    pub fn set_dependency_version(
+        &mut self,
+        table_path: &[String],
+        name: &str,
+        version: &str,
+    ) -> CargoResult<()> {
+        let dep = self.get_dependency(table_path, name)?;
+        let source: Source = match dep.source() {
+            Some(Source::Registry(_)) | None => RegistrySource::new(version).into(),
+            Some(Source::Path(path)) => path.clone().set_version(version).into(),
+            Some(Source::Git(git)) => git.clone().set_version(version).into(),
+            Some(Source::Workspace(_)) => anyhow::bail!(
+                "dependency `{name}` is inherited from the workspace, \
+                 set its version in `workspace.dependencies` instead"
+            ),
+        };
+        self.insert_into_table(table_path, &dep.set_source(source))
+    }
+
     /// Lookup a dependency.
     pub fn get_dependency_versions<'s>(
         &'s self,
@@ -378,25 +488,26 @@ impl LocalManifest {
                 }
             }
             None => {
-                // Search in other tables.
-                let sections = self.get_sections();
-                let found_table_path = sections.iter().find_map(|(t, i)| {
-                    let table_path: Vec<String> =
-                        t.to_table().iter().map(|s| s.to_string()).collect();
-                    i.get(name).is_some().then(|| table_path.join("."))
-                });
-
-                return Err(non_existent_dependency_err(
-                    name,
-                    table_path.join("."),
-                    found_table_path,
-                ));
+                return Err(self.dependency_not_found(table_path, name));
             }
         }
 
         Ok(())
     }
 
+    /// Error for `name` missing from `table_path`, mentioning the table it is
+    /// in instead, if any.
+    fn dependency_not_found(&self, table_path: &[String], name: &str) -> anyhow::Error {
+        // Search in other tables.
+        let sections = self.get_sections();
+        let found_table_path = sections.iter().find_map(|(t, i)| {
+            let table_path: Vec<String> = t.to_table().iter().map(|s| s.to_string()).collect();
+            i.get(name).is_some().then(|| table_path.join("."))
+        });
+
+        non_existent_dependency_err(name, table_path.join("."), found_table_path)
+    }
+
     /// Remove references to `dep_key` if its no longer present.
     pub fn gc_dep(&mut self, dep_key: &str) {
         let explicit_dep_activation = self.is_explicit_dep_activation(dep_key);
@@ -601,3 +712,118 @@ fn remove_array_index(array: &mut toml_edit::Array, index: usize) {
         array.set_trailing(merged_lines);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_manifest(contents: &str) -> LocalManifest {
+        let path = cargo_util::paths::normalize_path(
+            &std::env::current_dir()
+                .unwrap()
+                .join(Path::new("/Cargo.toml")),
+        );
+        LocalManifest {
+            path,
+            manifest: contents.parse().unwrap(),
+        }
+    }
+
+    fn table_path(path: &[&str]) -> Vec<String> {
+        path.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn set_dependency_version_keeps_entry() {
+        let mut manifest = local_manifest(
+            r#"[package]
+name = "foo"
+
+[dependencies]
+bar = "1.0" # a comment
+baz = { version = "0.1", features = ["f"] }
+qux = { path = "qux", version = "0.2" }
+"#,
+        );
+        let deps = table_path(&["dependencies"]);
+        manifest
+            .set_dependency_version(&deps, "bar", "2.0")
+            .unwrap();
+        manifest
+            .set_dependency_version(&deps, "baz", "0.2")
+            .unwrap();
+        manifest
+            .set_dependency_version(&deps, "qux", "0.3")
+            .unwrap();
+        assert_eq!(
+            manifest.to_string(),
+            r#"[package]
+name = "foo"
+
+[dependencies]
+bar = "2.0" # a comment
+baz = { version = "0.2", features = ["f"] }
+qux = { path = "qux", version = "0.3" }
+"#
+        );
+    }
+
+    #[test]
+    fn set_dependency_version_errors() {
+        let mut manifest = local_manifest(
+            r#"[package]
+name = "foo"
+
+[dependencies]
+bar.workspace = true
+
+[dev-dependencies]
+baz = "1.0"
+"#,
+        );
+        let deps = table_path(&["dependencies"]);
+        assert_eq!(
+            manifest
+                .set_dependency_version(&deps, "bar", "2.0")
+                .unwrap_err()
+                .to_string(),
+            "dependency `bar` is inherited from the workspace, \
+             set its version in `workspace.dependencies` instead"
+        );
+        assert_eq!(
+            manifest
+                .set_dependency_version(&deps, "baz", "2.0")
+                .unwrap_err()
+                .to_string(),
+            "the dependency `baz` could not be found in `dependencies`; \
+             it is present in `dev-dependencies`"
+        );
+    }
+
+    #[test]
+    fn workspace_members() {
+        let mut manifest = local_manifest(
+            r#"[workspace]
+members = ["a", "crates/*"]
+"#,
+        );
+        assert!(manifest.add_workspace_member("b").unwrap());
+        assert!(!manifest.add_workspace_member("crates/c").unwrap());
+        assert!(manifest.remove_workspace_member("a").unwrap());
+        assert!(!manifest.remove_workspace_member("crates/c").unwrap());
+        let members: Vec<_> = manifest.data["workspace"]["members"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m.as_str().unwrap())
+            .collect();
+        assert_eq!(members, ["b", "crates/*"]);
+
+        let mut manifest = local_manifest("[workspace]\n");
+        assert!(manifest.add_workspace_member("a").unwrap());
+        assert_eq!(manifest.to_string(), "[workspace]\nmembers = [\"a\"]\n");
+
+        let mut manifest = local_manifest("[package]\nname = \"foo\"\n");
+        assert!(manifest.add_workspace_member("a").is_err());
+    }
+}
@@ -8,9 +8,26 @@
 //! In most cases, the entrypoint for editing is
 //! [`LocalManifest`](crate::util::toml_mut::manifest::LocalManifest),
 //! which contains editing functionality for a given manifest's dependencies.
+//!
+//! This is the editing that `cargo add`, `cargo remove` and
+//! `cargo update --breaking` do, through:
+//!
+//! - [`LocalManifest::get_dependency`](manifest::LocalManifest::get_dependency),
+//!   [`LocalManifest::insert_into_table`](manifest::LocalManifest::insert_into_table)
+//!   and [`LocalManifest::remove_from_table`](manifest::LocalManifest::remove_from_table)
+//!   to look up, add and remove dependencies. The table path is the one of a
+//!   [`DepTable`](manifest::DepTable), or `["workspace", "dependencies"]`.
+//! - [`LocalManifest::set_dependency_version`](manifest::LocalManifest::set_dependency_version)
+//!   to change a version requirement without touching the rest of the entry.
+//! - [`Manifest::add_workspace_member`](manifest::Manifest::add_workspace_member)
+//!   and [`Manifest::remove_workspace_member`](manifest::Manifest::remove_workspace_member)
+//!   to manage `workspace.members`.
+//! - [`LocalManifest::write_any`](manifest::LocalManifest::write_any) to write
+//!   back a package or virtual manifest.
 
 pub mod dependency;
 pub mod manifest;
+pub mod upgrade;
 
 // Based on Iterator::is_sorted from nightly std; remove in favor of that when stabilized.
 pub fn is_sorted(mut it: impl Iterator<Item = impl PartialOrd>) -> bool {
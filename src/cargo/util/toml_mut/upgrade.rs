@@ -0,0 +1,88 @@
+//! Rewriting of version requirements, used by `cargo update --breaking`.
+
+/// Upgrades the version requirement `req` so that it matches `version`,
+/// keeping the precision and operators of the original requirement.
+///
+/// Returns `None` if the requirement is unchanged or can't be upgraded, e.g.
+/// because it uses `<` or `>` comparators.
+pub fn upgrade_requirement(req: &str, version: &semver::Version) -> Option<String> {
+    let raw_req = semver::VersionReq::parse(req).ok()?;
+    if raw_req.comparators.is_empty() {
+        // Empty matches everything, no-change.
+        return None;
+    }
+    let comparators = raw_req
+        .comparators
+        .into_iter()
+        .map(|c| set_comparator(c, version))
+        .collect::<Option<Vec<_>>>()?;
+    let new_req = semver::VersionReq { comparators };
+    let mut new_req_text = new_req.to_string();
+    if new_req_text.starts_with('^') && !req.trim_start().starts_with('^') {
+        new_req_text.remove(0);
+    }
+    debug_assert!(
+        new_req.matches(version),
+        "new requirement {new_req_text} does not match {version}"
+    );
+    if new_req_text == req {
+        None
+    } else {
+        Some(new_req_text)
+    }
+}
+
+fn set_comparator(
+    mut comparator: semver::Comparator,
+    version: &semver::Version,
+) -> Option<semver::Comparator> {
+    match comparator.op {
+        semver::Op::Caret | semver::Op::Tilde | semver::Op::Exact | semver::Op::Wildcard => {}
+        _ => return None,
+    }
+    comparator.major = version.major;
+    if comparator.minor.is_some() {
+        comparator.minor = Some(version.minor);
+    }
+    if comparator.patch.is_some() {
+        comparator.patch = Some(version.patch);
+    }
+    if comparator.op != semver::Op::Wildcard {
+        comparator.pre = version.pre.clone();
+    }
+    Some(comparator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::upgrade_requirement;
+
+    #[track_caller]
+    fn assert_upgrade(req: &str, version: &str, expected: Option<&str>) {
+        let version = semver::Version::parse(version).unwrap();
+        assert_eq!(upgrade_requirement(req, &version).as_deref(), expected);
+    }
+
+    #[test]
+    fn keeps_precision() {
+        assert_upgrade("1", "2.3.4", Some("2"));
+        assert_upgrade("1.0", "2.3.4", Some("2.3"));
+        assert_upgrade("1.0.0", "2.3.4", Some("2.3.4"));
+        assert_upgrade("0.1", "0.2.0", Some("0.2"));
+    }
+
+    #[test]
+    fn keeps_operators() {
+        assert_upgrade("^1.0", "2.3.4", Some("^2.3"));
+        assert_upgrade("~1.0", "2.3.4", Some("~2.3"));
+        assert_upgrade("=1.0.0", "2.3.4", Some("=2.3.4"));
+        assert_upgrade("1.*", "2.3.4", Some("2.*"));
+    }
+
+    #[test]
+    fn unchanged_or_unsupported() {
+        assert_upgrade("2", "2.3.4", None);
+        assert_upgrade("*", "2.3.4", None);
+        assert_upgrade(">=1.0, <2.0", "2.3.4", None);
+    }
+}
@@ -1497,6 +1497,50 @@ checksum = "458c1addb23fde7dfbca0410afdbcc0086f96197281ec304d9e0e10def3cb899""#,
     );
 }
 
+#[cargo_test]
+fn sparse_client_key_without_cert() {
+    let _registry = registry::RegistryBuilder::new()
+        .http_index()
+        .alternative()
+        .build();
+    Package::new("foo", "0.1.0").alternative(true).publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.5.0"
+                edition = "2015"
+
+                [dependencies]
+                foo = { registry = 'alternative', version = '0.1.0'}
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["registries.<name>.client-key"])
+        .arg("--config")
+        .arg("registries.alternative.client-key='client.key'")
+        .with_status(101)
+        .with_stderr_contains(
+            "  `client-key` is set for registry `alternative`, but `client-cert` is not",
+        )
+        .run();
+
+    p.cargo("generate-lockfile")
+        .arg("--config")
+        .arg("registries.alternative.client-key='client.key'")
+        .with_stderr_contains(
+            "[WARNING] `registries.alternative.client-key` requires `-Zunstable-options`, ignoring it",
+        )
+        .with_stderr_contains("[LOCKING] 2 packages to latest compatible versions")
+        .run();
+}
+
 #[cargo_test]
 fn publish_with_transitive_dep() {
     let _alt1 = RegistryBuilder::new()
@@ -0,0 +1,560 @@
+//! Tests for the `build.artifact-cache` config.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use cargo_test_support::install::cargo_home;
+use cargo_test_support::paths::CargoPathExt;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{paths, project, Project};
+
+const CONFIG: &str = r#"
+    [build]
+    artifact-cache = true
+"#;
+
+fn manifest(name: &str) -> String {
+    format!(
+        r#"
+            [package]
+            name = "{name}"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            bar = "0.1"
+        "#
+    )
+}
+
+/// The number of entries of libraries in the cache.
+fn entries() -> usize {
+    entries_in("artifact-cache")
+}
+
+/// The number of entries of build script runs in the cache.
+fn script_entries() -> usize {
+    entries_in("artifact-cache/build-scripts")
+}
+
+fn entries_in(dir: &str) -> usize {
+    cargo_home()
+        .join(dir)
+        .read_dir()
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().file_name().len() == 64)
+        .count()
+}
+
+#[cargo_test]
+fn gated() {
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file("Cargo.toml", &manifest("foo"))
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+
+    p.cargo("check")
+        .with_stderr_contains(
+            "[WARNING] `build.artifact-cache` requires `-Zunstable-options`, ignoring it",
+        )
+        .run();
+    assert!(!cargo_home().join("artifact-cache").exists());
+}
+
+#[cargo_test]
+fn shared_between_workspaces() {
+    Package::new("baz", "0.1.0")
+        .file("src/lib.rs", "pub fn baz() {}")
+        .publish();
+    Package::new("bar", "0.1.0")
+        .dep("baz", "0.1")
+        .file("src/lib.rs", "pub fn bar() { baz::baz() }")
+        .publish();
+    let p1 = project()
+        .at("one")
+        .file("Cargo.toml", &manifest("one"))
+        .file("src/lib.rs", "pub fn one() { bar::bar() }")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+    let p2 = project()
+        .at("two")
+        .file("Cargo.toml", &manifest("two"))
+        .file("src/lib.rs", "pub fn two() { bar::bar() }")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+
+    p1.cargo("build -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name baz [..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+    assert_eq!(entries(), 2);
+
+    // The second workspace links the outputs of the first instead of
+    // compiling the dependencies again.
+    p2.cargo("build -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_contains("[COMPILING] bar v0.1.0")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name bar [..]")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name baz [..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name two [..]")
+        .run();
+    p2.cargo("build -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_contains("[FRESH] bar v0.1.0")
+        .with_stderr_contains("[FRESH] two v0.1.0 ([CWD])")
+        .run();
+
+    // Other flags make other entries.
+    p2.cargo("build -v -Zunstable-options --release")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+    assert_eq!(entries(), 4);
+}
+
+/// Publishes `bar`, whose build script writes `gen.rs` in its `OUT_DIR` and
+/// prints `extra` as well.
+fn publish_bar_with_build_script(extra: &str) {
+    Package::new("bar", "0.1.0")
+        .file(
+            "build.rs",
+            &format!(
+                r#"
+                    fn main() {{
+                        let out_dir = std::env::var("OUT_DIR").unwrap();
+                        let gen = std::path::Path::new(&out_dir).join("gen.rs");
+                        std::fs::write(gen, "pub fn bar() {{}}").unwrap();
+                        println!("cargo::rerun-if-env-changed=BAR_LEVEL");
+                        {extra}
+                    }}
+                "#
+            ),
+        )
+        .file(
+            "src/lib.rs",
+            r#"include!(concat!(env!("OUT_DIR"), "/gen.rs"));"#,
+        )
+        .publish();
+}
+
+/// Whether the `OUT_DIR` of the build script of `bar` holds `gen.rs`.
+fn generated(p: &Project) -> bool {
+    p.target_debug_dir()
+        .join("build")
+        .read_dir()
+        .unwrap()
+        .any(|entry| entry.unwrap().path().join("out/gen.rs").is_file())
+}
+
+#[cargo_test]
+fn build_script_shared_between_workspaces() {
+    publish_bar_with_build_script("");
+    let p1 = project()
+        .at("one")
+        .file("Cargo.toml", &manifest("one"))
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+    let p2 = project()
+        .at("two")
+        .file("Cargo.toml", &manifest("two"))
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+
+    p1.cargo("check -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_contains(
+            "[RUNNING] `[ROOT]/one/target/debug/build/bar-[..]/build-script-build`",
+        )
+        .run();
+    assert_eq!(script_entries(), 1);
+
+    // The second workspace restores the `OUT_DIR` of the first.
+    p2.cargo("check -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_does_not_contain("[RUNNING] `[..]/build-script-build`")
+        .run();
+    assert!(generated(&p2));
+
+    // Unless it declared reading a variable which changed.
+    p2.cargo("clean").run();
+    p2.cargo("check -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .env("BAR_LEVEL", "1")
+        .with_stderr_contains(
+            "[RUNNING] `[ROOT]/two/target/debug/build/bar-[..]/build-script-build`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn build_script_external_inputs() {
+    publish_bar_with_build_script(
+        r#"println!("cargo::rerun-if-changed={}", std::env::var("BAR_INPUT").unwrap());"#,
+    );
+    let input = paths::root().join("input.h");
+    std::fs::write(&input, "1").unwrap();
+    let p1 = project()
+        .at("one")
+        .file("Cargo.toml", &manifest("one"))
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+    let p2 = project()
+        .at("two")
+        .file("Cargo.toml", &manifest("two"))
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+
+    p1.cargo("check -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .env("BAR_INPUT", &input)
+        .with_stderr_contains("[RUNNING] `[..]/build-script-build`")
+        .run();
+
+    // The file it declared reading outside of the package changed.
+    std::fs::write(&input, "2").unwrap();
+    p2.cargo("check -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .env("BAR_INPUT", &input)
+        .with_stderr_contains(
+            "[RUNNING] `[ROOT]/two/target/debug/build/bar-[..]/build-script-build`",
+        )
+        .run();
+
+    // Once it ran with the new contents, it's restored.
+    p2.cargo("clean").run();
+    p2.cargo("check -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .env("BAR_INPUT", &input)
+        .with_stderr_does_not_contain("[RUNNING] `[..]/build-script-build`")
+        .run();
+    assert!(generated(&p2));
+}
+
+#[cargo_test]
+fn generated_code_in_key() {
+    Package::new("bar", "0.1.0")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    let out_dir = std::env::var("OUT_DIR").unwrap();
+                    let gen = std::path::Path::new(&out_dir).join("gen.rs");
+                    let level = std::env::var("BAR_LEVEL").unwrap_or_default();
+                    std::fs::write(gen, format!("pub const LEVEL: &str = {level:?};")).unwrap();
+                    println!("cargo::rerun-if-env-changed=BAR_LEVEL");
+                }
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"include!(concat!(env!("OUT_DIR"), "/gen.rs"));"#,
+        )
+        .publish();
+    let p1 = project()
+        .at("one")
+        .file("Cargo.toml", &manifest("one"))
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+    let p2 = project()
+        .at("two")
+        .file("Cargo.toml", &manifest("two"))
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+
+    p1.cargo("check -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .env("BAR_LEVEL", "1")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+
+    // The same invocation of `rustc` compiles other generated code.
+    p2.cargo("check -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .env("BAR_LEVEL", "2")
+        .with_stderr_contains("[RUNNING] `[..]/build-script-build`")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+    assert_eq!(entries(), 2);
+}
+
+#[cargo_test]
+fn removes_unused_entries() {
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file("Cargo.toml", &manifest("foo"))
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .run();
+    let cache = cargo_home().join("artifact-cache");
+    let unused = cache.join("0".repeat(64));
+    unused.mkdir_p();
+    let old = filetime::FileTime::from_unix_time(0, 0);
+    filetime::set_file_mtime(&unused, old).unwrap();
+    assert_eq!(entries(), 2);
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .run();
+    assert!(!unused.exists());
+    assert_eq!(entries(), 1);
+    assert!(cache.join(".last-gc").exists());
+
+    // It only looks for unused entries once a day.
+    unused.mkdir_p();
+    filetime::set_file_mtime(&unused, old).unwrap();
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .run();
+    assert!(unused.exists());
+}
+
+#[cargo_test]
+fn removes_unused_build_script_entries() {
+    publish_bar_with_build_script("");
+    let p = project()
+        .file("Cargo.toml", &manifest("foo"))
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .run();
+    let unused = cargo_home()
+        .join("artifact-cache/build-scripts")
+        .join("0".repeat(64));
+    unused.mkdir_p();
+    let old = filetime::FileTime::from_unix_time(0, 0);
+    filetime::set_file_mtime(&unused, old).unwrap();
+    cargo_home()
+        .join("artifact-cache/build-scripts/.last-gc")
+        .rm_rf();
+    assert_eq!(script_entries(), 2);
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .run();
+    assert!(!unused.exists());
+    assert_eq!(script_entries(), 1);
+}
+
+#[cargo_test]
+fn nondeterministic_build_script() {
+    publish_bar_with_build_script(r#"println!("cargo::nondeterministic=true");"#);
+    let p = project()
+        .file("Cargo.toml", &manifest("foo"))
+        .file("src/lib.rs", "")
+        .file(".cargo/config.toml", CONFIG)
+        .build();
+
+    p.cargo("check -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_contains("[RUNNING] `[..]/build-script-build`")
+        .run();
+    assert!(!cargo_home().join("artifact-cache/build-scripts").exists());
+
+    p.cargo("clean").run();
+    p.cargo("check -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_contains("[RUNNING] `[..]/build-script-build`")
+        .run();
+}
+
+/// Serves `GET` and `PUT` of files kept in memory, like a remote cache.
+fn remote_cache() -> (String, Arc<Mutex<HashMap<String, Vec<u8>>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/cache", listener.local_addr().unwrap());
+    let files = Arc::new(Mutex::new(HashMap::new()));
+    let served = files.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let files = served.clone();
+            thread::spawn(move || {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut request = String::new();
+                    if reader.read_line(&mut request).unwrap_or(0) == 0 {
+                        return;
+                    }
+                    let mut parts = request.split_whitespace();
+                    let method = parts.next().unwrap().to_string();
+                    let path = parts.next().unwrap().to_string();
+                    let mut len = 0;
+                    let mut authorized = false;
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap();
+                        let header = header.trim();
+                        if header.is_empty() {
+                            break;
+                        }
+                        let (name, value) = header.split_once(": ").unwrap_or((header, ""));
+                        match name.to_lowercase().as_str() {
+                            "content-length" => len = value.parse().unwrap(),
+                            "authorization" => authorized = value == "Bearer secret",
+                            _ => {}
+                        }
+                    }
+                    let (status, body) = match method.as_str() {
+                        "GET" => match files.lock().unwrap().get(&path) {
+                            Some(body) => ("200 OK", body.clone()),
+                            None => ("404 Not Found", Vec::new()),
+                        },
+                        "PUT" => {
+                            let mut body = vec![0; len];
+                            reader.read_exact(&mut body).unwrap();
+                            if authorized {
+                                files.lock().unwrap().insert(path, body);
+                                ("200 OK", Vec::new())
+                            } else {
+                                ("403 Forbidden", Vec::new())
+                            }
+                        }
+                        _ => ("405 Method Not Allowed", Vec::new()),
+                    };
+                    write!(
+                        stream,
+                        "HTTP/1.1 {status}\r\nContent-Length: {}\r\n\r\n",
+                        body.len()
+                    )
+                    .unwrap();
+                    stream.write_all(&body).unwrap();
+                }
+            });
+        }
+    });
+    (url, files)
+}
+
+#[cargo_test]
+fn remote() {
+    Package::new("bar", "0.1.0")
+        .file("src/lib.rs", "pub fn bar() {}")
+        .publish();
+    let (url, files) = remote_cache();
+    let p = project()
+        .file("Cargo.toml", &manifest("foo"))
+        .file("src/lib.rs", "pub fn foo() { bar::bar() }")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [build.cache]
+                    remote = "{url}"
+                    token = "Bearer secret"
+                "#
+            ),
+        )
+        .build();
+
+    p.cargo("build -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+    let mut names = files.lock().unwrap().keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names.len(), 2);
+    assert!(names[0].ends_with(".sha256"));
+    assert!(names[1].ends_with(".tar.gz"));
+
+    // Another machine downloads the entry instead of compiling `bar`.
+    cargo_home().join("artifact-cache").rm_rf();
+    p.cargo("clean").run();
+    p.cargo("build -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name bar [..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name foo [..]")
+        .run();
+
+    // A corrupted entry is compiled again.
+    let archive = names[1].clone();
+    files.lock().unwrap().get_mut(&archive).unwrap()[0] ^= 1;
+    cargo_home().join("artifact-cache").rm_rf();
+    p.cargo("clean").run();
+    p.cargo("build -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .with_stderr_contains(
+            "[WARNING] failed to download `bar` from the remote artifact cache: \
+             the checksum of `[..].tar.gz` is `[..]`, but `[..].sha256` says it should be `[..]`",
+        )
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+}
+
+#[cargo_test]
+fn remote_read_only() {
+    Package::new("bar", "0.1.0").publish();
+    let (url, files) = remote_cache();
+    let p = project()
+        .file("Cargo.toml", &manifest("foo"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [build.cache]
+                    remote = "{url}"
+                    read-only = true
+                "#
+            ),
+        )
+        .build();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .run();
+    assert!(files.lock().unwrap().is_empty());
+    assert_eq!(entries(), 1);
+}
+
+#[cargo_test]
+fn remote_unreachable() {
+    Package::new("baz", "0.1.0").publish();
+    Package::new("bar", "0.1.0").dep("baz", "0.1").publish();
+    // Nothing listens on the port once the listener is dropped.
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let p = project()
+        .file("Cargo.toml", &manifest("foo"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [build.cache]
+                    remote = "http://{addr}/cache"
+                "#
+            ),
+        )
+        .build();
+
+    let output = p
+        .cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["artifact-cache"])
+        .exec_with_output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let warning = "the remote artifact cache is no longer used for this build";
+    assert_eq!(stderr.matches(warning).count(), 1, "{stderr}");
+    // The local cache is still used.
+    assert_eq!(entries(), 2);
+}
@@ -4,8 +4,8 @@
 use cargo_test_support::compare;
 use cargo_test_support::registry::{Package, RegistryBuilder};
 use cargo_test_support::{
-    basic_bin_manifest, basic_manifest, cross_compile, project, publish, registry, rustc_host,
-    Project,
+    basic_bin_manifest, basic_lib_manifest, basic_manifest, cross_compile, project, publish,
+    registry, rustc_host, Project,
 };
 
 #[cargo_test]
@@ -3231,3 +3231,99 @@ fn build_only_specified_artifact_library() {
         &build_script_output_string(&staticlib, "foo"),
     );
 }
+
+#[cargo_test]
+fn check_skip_artifacts() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.0"
+                edition = "2015"
+                authors = []
+                resolver = "2"
+
+                [dependencies]
+                bar = { path = "bar/", artifact = "bin" }
+                qux = { path = "qux/", artifact = "bin", optional = true }
+
+                [build-dependencies]
+                baz = { path = "baz/", artifact = "bin" }
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                pub fn foo() -> &'static str {
+                    env!("CARGO_BIN_DIR_BAR");
+                    assert!(option_env!("CARGO_BIN_DIR_QUX").is_none());
+                    env!("CARGO_BIN_FILE_BAR")
+                }
+            "#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    let baz = std::env::var("CARGO_BIN_FILE_BAZ").unwrap();
+                    assert!(std::path::Path::new(&baz).is_file());
+                }
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.5.0"
+                edition = "2015"
+
+                [dependencies]
+                pm = { path = "../pm" }
+
+                [build-dependencies]
+                helper = { path = "../helper" }
+            "#,
+        )
+        .file("bar/build.rs", "fn main() {}")
+        .file("bar/src/main.rs", "extern crate pm; fn main() {}")
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.5.0"
+                edition = "2015"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file("pm/src/lib.rs", "")
+        .file("helper/Cargo.toml", &basic_lib_manifest("helper"))
+        .file("helper/src/lib.rs", "")
+        .file("baz/Cargo.toml", &basic_bin_manifest("baz"))
+        .file("baz/src/main.rs", "fn main() {}")
+        .file("qux/Cargo.toml", &basic_bin_manifest("qux"))
+        .file("qux/src/main.rs", "fn main() {}")
+        .build();
+
+    // The build scripts and proc-macros of the skipped artifacts aren't
+    // built either.
+    p.cargo("check -Z bindeps -Z check-skip-artifacts")
+        .masquerade_as_nightly_cargo(&["bindeps", "check-skip-artifacts"])
+        .with_stderr_does_not_contain("[COMPILING] bar [..]")
+        .with_stderr_does_not_contain("[COMPILING] pm [..]")
+        .with_stderr_does_not_contain("[COMPILING] helper [..]")
+        .with_stderr_contains("[COMPILING] baz v0.5.0 ([CWD]/baz)")
+        .with_stderr_contains("[CHECKING] foo v0.0.0 ([CWD])")
+        .run();
+
+    // Without the flag, the artifact is built as usual.
+    p.cargo("check -Z bindeps")
+        .masquerade_as_nightly_cargo(&["bindeps"])
+        .with_stderr_contains("[COMPILING] bar v0.5.0 ([CWD]/bar)")
+        .run();
+}
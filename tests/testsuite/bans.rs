@@ -0,0 +1,120 @@
+//! Tests for `[workspace.bans]`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+
+fn banned_project(bans: &str) -> cargo_test_support::Project {
+    Package::new("sys", "0.9.92").publish();
+    Package::new("wrapper", "1.0.0").dep("sys", "0.9").publish();
+
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    cargo-features = ["bans"]
+
+                    [workspace]
+                    members = ["app"]
+
+                    [workspace.bans]
+                    {bans}
+                "#
+            ),
+        )
+        .file(
+            "app/Cargo.toml",
+            r#"
+                [package]
+                name = "app"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                wrapper = "1.0"
+            "#,
+        )
+        .file("app/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn requires_nightly() {
+    let p = banned_project(r#"sys = "*""#);
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr_contains("  feature `bans` is required")
+        .run();
+}
+
+#[cargo_test]
+fn banned_transitive_dependency() {
+    let p = banned_project(r#"sys = "*""#);
+
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo(&["bans"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[ERROR] package `sys v0.9.92` is banned by `sys = \"*\"` in `workspace.bans`, but is part of the dependency graph:
+  `app v0.1.0 ([CWD]/app)` -> `wrapper v1.0.0` -> `sys v0.9.92`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn banned_version_range() {
+    let p = banned_project(r#"sys = "<0.9.90""#);
+
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo(&["bans"])
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["bans"]
+
+            [workspace]
+            members = ["app"]
+
+            [workspace.bans]
+            sys = ">=0.9.90, <0.10"
+        "#,
+    );
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["bans"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] package `sys v0.9.92` is banned by `sys = \">=0.9.90, <0.10\"` \
+             in `workspace.bans`, but is part of the dependency graph:",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn members_are_not_banned() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["bans"]
+
+                [workspace]
+                members = ["app"]
+
+                [workspace.bans]
+                app = "*"
+            "#,
+        )
+        .file("app/Cargo.toml", &basic_manifest("app", "0.1.0"))
+        .file("app/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["bans"])
+        .run();
+}
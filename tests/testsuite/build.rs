@@ -1415,6 +1415,57 @@ failed to select a version for `bad` which could resolve this conflict",
         .run();
 }
 
+#[cargo_test]
+fn incompatible_dependencies_verbose_explanation() {
+    Package::new("bad", "1.0.0").publish();
+    Package::new("bad", "1.0.1").publish();
+    Package::new("bad", "2.0.0").publish();
+    Package::new("bad", "2.0.1").publish();
+    Package::new("bar", "0.1.0").dep("bad", "=1.0.0").publish();
+    Package::new("baz", "0.1.0").dep("bad", ">=2.0.1").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = "0.1.0"
+                baz = "0.1.0"
+                bad = ">=1.0.1, <=2.0.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main(){}")
+        .build();
+
+    // The conflicts with the selected packages are already in the error.
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_does_not_contain("explanation:")
+        .run();
+
+    p.cargo("build -v")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+failed to select a version for `bad` which could resolve this conflict
+
+explanation:
+`foo v0.0.1 ([..])` requires `bad = \">=1.0.1, <=2.0.0\"`
+├── `bad v2.0.0` conflicts with `bad v2.0.1`, which is already selected
+│   └── `baz v0.1.0` requires `bad = \">=2.0.1\"`
+│       └── `foo v0.0.1 ([..])` requires `baz = \"^0.1.0\"`
+└── `bad v1.0.1` conflicts with `bad v1.0.0`, which is already selected
+    └── `bar v0.1.0` requires `bad = \"=1.0.0\"`
+        └── `foo v0.0.1 ([..])` requires `bar = \"^0.1.0\"`",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn compile_path_dep_then_change_version() {
     let p = project()
@@ -4810,6 +4861,70 @@ fn build_virtual_manifest_broken_glob() {
         .run();
 }
 
+#[cargo_test]
+fn build_virtual_manifest_glob_with_version() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["bar", "baz"]
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "1.0.0"))
+        .file("bar/src/lib.rs", "pub fn bar() { break_the_build(); }")
+        .file("baz/Cargo.toml", &basic_manifest("baz", "2.0.0"))
+        .file("baz/src/lib.rs", "pub fn baz() {}")
+        .build();
+
+    p.cargo("build -p 'ba*@2'")
+        .with_stderr_does_not_contain("[..]bar[..]")
+        .with_stderr(
+            "\
+[LOCKING] 2 packages to latest compatible versions
+[COMPILING] baz v2.0.0 ([..])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn build_glob_dependencies() {
+    Package::new("tokio-util", "0.1.0").publish();
+    Package::new("tokio-macros", "0.1.0").publish();
+    Package::new("serde", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                tokio-util = "0.1"
+                tokio-macros = "0.1"
+                serde = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build -p 'tokio-*'")
+        .with_stderr_contains("[COMPILING] tokio-util v0.1.0")
+        .with_stderr_contains("[COMPILING] tokio-macros v0.1.0")
+        .with_stderr_does_not_contain("[COMPILING] serde [..]")
+        .with_stderr_does_not_contain("[COMPILING] foo [..]")
+        .run();
+
+    p.cargo("build -p 'tokio-*@2'")
+        .with_status(101)
+        .with_stderr("[ERROR] package pattern `tokio-*@2` did not match any packages")
+        .run();
+}
+
 #[cargo_test]
 fn build_all_virtual_manifest_implicit_examples() {
     let p = project()
@@ -6764,3 +6879,362 @@ fn renamed_uplifted_artifact_remains_unmodified_after_rebuild() {
     let not_the_same = !same_file::is_same_file(bin, renamed_bin).unwrap();
     assert!(not_the_same, "renamed uplifted artifact must be unmodified");
 }
+
+#[cargo_test]
+fn manifest_list_gated() {
+    let p = project()
+        .file("foo/Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("foo/src/lib.rs", "")
+        .file("list.txt", "foo/Cargo.toml")
+        .build();
+
+    p.cargo("build --manifest-list list.txt")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--manifest-list` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn manifest_list() {
+    let p = project()
+        .file("foo/Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("foo/src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/main.rs", "fn main() {}")
+        .file(
+            "list.txt",
+            "\
+# projects to build
+foo/Cargo.toml
+
+bar/Cargo.toml
+",
+        )
+        .build();
+
+    p.cargo("build -Zunstable-options --manifest-list list.txt")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr(
+            "\
+[BUILDING] [1/2] [CWD]/foo/Cargo.toml
+[COMPILING] foo v0.1.0 ([CWD]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+[BUILDING] [2/2] [CWD]/bar/Cargo.toml
+[COMPILING] bar v0.1.0 ([CWD]/bar)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+[SUMMARY] 2 of 2 projects built
+",
+        )
+        .run();
+
+    let exe_name = format!("bar{}", env::consts::EXE_SUFFIX);
+    assert!(p.root().join("bar/target/debug").join(&exe_name).is_file());
+}
+
+#[cargo_test]
+fn manifest_list_keeps_going() {
+    let p = project()
+        .file("foo/Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("foo/src/lib.rs", "invalid rust code")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .file("list.txt", "foo/Cargo.toml\nbar/Cargo.toml\n")
+        .build();
+
+    p.cargo("build -Zunstable-options --manifest-list list.txt")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] could not compile `foo` (lib) due to 1 previous error")
+        .with_stderr_contains("[COMPILING] bar v0.1.0 ([CWD]/bar)")
+        .with_stderr_contains(
+            "[SUMMARY] 1 of 2 projects built, failed to build `[CWD]/foo/Cargo.toml` (see error(s) above)",
+        )
+        .with_stderr_contains("[ERROR] 1 of 2 projects failed to build")
+        .run();
+}
+
+#[cargo_test]
+fn manifest_list_missing_manifest() {
+    let p = project()
+        .file("foo/Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("foo/src/lib.rs", "")
+        .file("list.txt", "foo/Cargo.toml\nbar/Cargo.toml\n")
+        .build();
+
+    p.cargo("build -Zunstable-options --manifest-list list.txt")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] manifest path `[CWD]/bar/Cargo.toml` in manifest list `[CWD]/list.txt` does not exist
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn manifest_list_rejects_project_config() {
+    let p = project()
+        .file("foo/Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("foo/src/lib.rs", "")
+        .file("foo/.cargo/config.toml", "[build]\njobs = 1\n")
+        .file("list.txt", "foo/Cargo.toml\n")
+        .build();
+
+    p.cargo("build -Zunstable-options --manifest-list list.txt")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the configuration file `[CWD]/foo/.cargo/config.toml` applies to `[CWD]/foo/Cargo.toml`, \
+but projects in a manifest list are built with the configuration of `[CWD]`
+Build this project separately, or move its configuration.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn manifest_list_rejects_package() {
+    let p = project()
+        .file("foo/Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("foo/src/lib.rs", "")
+        .file("list.txt", "foo/Cargo.toml\n")
+        .build();
+
+    p.cargo("build -Zunstable-options --manifest-list list.txt -p foo")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] `--package` and `--exclude` can't be used with `--manifest-list`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn unit_durations_recorded_for_scheduling() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "pub fn bar() {}")
+        .build();
+
+    // The durations of the rebuilt units are the costs of the next build.
+    p.cargo("build").run();
+    let durations = p.read_file("target/.unit-durations.json");
+    assert!(durations.contains("bar v0.1.0"), "{durations}");
+    assert!(durations.contains("foo v0.1.0"), "{durations}");
+
+    p.change_file("bar/src/lib.rs", "pub fn bar() { }");
+    p.cargo("build")
+        .with_stderr(
+            "\
+[COMPILING] bar v0.1.0 ([CWD]/bar)
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn checkpoint_interval() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [build-dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        // `bar` is built before the build script, which waits for its
+        // duration to be saved while the build is still running.
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                        .join("target/.unit-durations.json");
+                    for _ in 0..300 {
+                        let durations = std::fs::read_to_string(&path).unwrap_or_default();
+                        if durations.contains("bar v0.1.0") {
+                            return;
+                        }
+                        std::thread::sleep(std::time::Duration::from_millis(100));
+                    }
+                    panic!("the duration of bar was not saved");
+                }
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build --checkpoint-interval 1")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--checkpoint-interval` flag is unstable[..]")
+        .run();
+
+    p.cargo("build -Zunstable-options --checkpoint-interval 0")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr("[ERROR] checkpoint-interval may not be 0")
+        .run();
+
+    p.cargo("build -Zunstable-options --checkpoint-interval 1")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr(
+            "\
+[COMPILING] bar v0.1.0 ([CWD]/bar)
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+    let durations = p.read_file("target/.unit-durations.json");
+    assert!(durations.contains("foo v0.0.1"), "{durations}");
+}
+
+#[cargo_test]
+fn max_memory() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+                baz = { path = "baz" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); baz::baz(); }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "pub fn bar() {}")
+        .file("baz/Cargo.toml", &basic_manifest("baz", "0.1.0"))
+        .file("baz/src/lib.rs", "pub fn baz() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                max-memory = "1B"
+            "#,
+        )
+        .build();
+
+    p.cargo("check")
+        .with_stderr_contains(
+            "[WARNING] `build.max-memory` requires `-Zunstable-options`, ignoring it",
+        )
+        .run();
+
+    // A limit smaller than any job still runs them, one at a time.
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_unordered(
+            "\
+[COMPILING] bar v0.1.0 ([CWD]/bar)
+[COMPILING] baz v0.1.0 ([CWD]/baz)
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [build]
+            max-memory = "lots"
+        "#,
+    );
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse config `build.max-memory`
+
+Caused by:
+  invalid size `lots`, expected a number with an optional B, kB, MB, GB, kiB, MiB, or GiB suffix
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn link_jobs() {
+    let p = project()
+        .file("src/lib.rs", "pub fn foo() {}")
+        .file("src/bin/a.rs", "fn main() { foo::foo(); }")
+        .file("src/bin/b.rs", "fn main() { foo::foo(); }")
+        .file("src/bin/c.rs", "fn main() { foo::foo(); }")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                link-jobs = 1
+            "#,
+        )
+        .build();
+
+    p.cargo("check")
+        .with_stderr_contains(
+            "[WARNING] `build.link-jobs` requires `-Zunstable-options`, ignoring it",
+        )
+        .run();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .run();
+    assert!(p.bin("a").is_file());
+    assert!(p.bin("b").is_file());
+    assert!(p.bin("c").is_file());
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [build]
+            link-jobs = 0
+        "#,
+    );
+    p.cargo("check")
+        .with_stderr_contains(
+            "[WARNING] `build.link-jobs` requires `-Zunstable-options`, ignoring it",
+        )
+        .run();
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("error: link-jobs may not be 0")
+        .run();
+}
@@ -0,0 +1,191 @@
+//! Tests for the `[build-directives]` table.
+
+use cargo_test_support::project;
+
+fn manifest(directives: &str) -> String {
+    format!(
+        r#"
+            cargo-features = ["build-directives"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [build-directives]
+            {directives}
+        "#
+    )
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [build-directives]
+                rustc-cfg = ["foo"]
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-directives"])
+        .with_status(101)
+        .with_stderr_contains("  feature `build-directives` is required")
+        .run();
+}
+
+#[cargo_test]
+fn cfg_and_env() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &manifest(
+                r#"
+                    rustc-cfg = ["fast"]
+                    rustc-check-cfg = ["cfg(fast)"]
+                    rustc-env = { GREETING = "hello" }
+                "#,
+            ),
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                #[cfg(fast)]
+                const SPEED: &str = "fast";
+                #[cfg(not(fast))]
+                const SPEED: &str = "slow";
+
+                fn main() {
+                    println!("{} {}", env!("GREETING"), SPEED);
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("run")
+        .masquerade_as_nightly_cargo(&["build-directives"])
+        .with_stdout("hello fast\n")
+        .with_stderr_does_not_contain("[WARNING] [..]")
+        .run();
+
+    // Changing the directives rebuilds the package.
+    p.change_file(
+        "Cargo.toml",
+        &manifest(
+            r#"
+                rustc-check-cfg = ["cfg(fast)"]
+                rustc-env = { GREETING = "bye" }
+            "#,
+        ),
+    );
+    p.cargo("run")
+        .masquerade_as_nightly_cargo(&["build-directives"])
+        .with_stdout("bye slow\n")
+        .run();
+}
+
+#[cargo_test]
+fn rerun_if_changed_and_env_changed() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &manifest(
+                r#"
+                    rerun-if-changed = ["data/*.txt"]
+                    rerun-if-env-changed = ["FOO_LEVEL"]
+                "#,
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file("data/a.txt", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-directives"])
+        .with_stderr_contains("[CHECKING] foo v0.1.0 ([CWD])")
+        .run();
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-directives"])
+        .with_stderr_does_not_contain("[CHECKING] foo [..]")
+        .run();
+
+    p.change_file("data/b.txt", "");
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-directives"])
+        .with_stderr_contains("[CHECKING] foo v0.1.0 ([CWD])")
+        .run();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-directives"])
+        .env("FOO_LEVEL", "1")
+        .with_stderr_contains("[CHECKING] foo v0.1.0 ([CWD])")
+        .run();
+}
+
+#[cargo_test]
+fn link_search_of_dependency() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "extern crate bar;")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                cargo-features = ["build-directives"]
+
+                [package]
+                name = "bar"
+                version = "0.1.0"
+                edition = "2015"
+
+                [build-directives]
+                rustc-link-search = ["native=libs"]
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -v")
+        .masquerade_as_nightly_cargo(&["build-directives"])
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name bar [..]-L native=[ROOT]/foo/bar/libs[..]`",
+        )
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo [..]-L native=[ROOT]/foo/bar/libs[..]`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn not_with_build_script() {
+    let p = project()
+        .file("Cargo.toml", &manifest(r#"rustc-cfg = ["foo"]"#))
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-directives"])
+        .with_status(101)
+        .with_stderr_contains("  `[build-directives]` can't be used along with a build script")
+        .run();
+}
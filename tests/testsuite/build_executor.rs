@@ -0,0 +1,152 @@
+//! Tests for the `build.executor` config.
+
+use std::path::PathBuf;
+
+use cargo_test_support::{basic_manifest, project};
+
+/// Builds an executor which runs the `rustc` invocation of the request
+/// locally, printing the name of its target.
+fn executor() -> PathBuf {
+    let p = project()
+        .at("executor")
+        .file("Cargo.toml", &basic_manifest("executor", "1.0.0"))
+        .file(
+            "src/main.rs",
+            r#"
+                use std::process::Command;
+
+                fn unquote(s: &str) -> String {
+                    let s = s.trim_end_matches(',');
+                    s[1..s.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\")
+                }
+
+                fn main() {
+                    let path = std::env::args().last().unwrap();
+                    let request = std::fs::read_to_string(path).unwrap();
+                    let mut program = None;
+                    let mut target = None;
+                    let mut args = Vec::new();
+                    let mut in_args = false;
+                    for line in request.lines().map(str::trim) {
+                        if in_args {
+                            if line.starts_with(']') {
+                                in_args = false;
+                            } else {
+                                args.push(unquote(line));
+                            }
+                        } else if let Some(value) = line.strip_prefix("\"program\": ") {
+                            program = Some(unquote(value));
+                        } else if let Some(value) = line.strip_prefix("\"target\": ") {
+                            target = Some(unquote(value));
+                        } else if line == "\"args\": [" {
+                            in_args = true;
+                        }
+                    }
+                    eprintln!("EXECUTOR: {}", target.unwrap());
+                    if std::env::var_os("EXECUTOR_SKIP").is_some() {
+                        return;
+                    }
+                    let status = Command::new(program.unwrap()).args(&args).status().unwrap();
+                    std::process::exit(status.code().unwrap_or(1));
+                }
+            "#,
+        )
+        .build();
+    p.cargo("build").run();
+    p.bin("executor")
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/main.rs", "fn main() {}").build();
+
+    p.cargo("check")
+        .env("CARGO_BUILD_EXECUTOR", "missing-executor")
+        .with_stderr_contains(
+            "[WARNING] `build.executor` requires `-Zunstable-options`, ignoring it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn runs_rustc_invocations() {
+    let executor = executor();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar() }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", r#"pub fn bar() { println!("bar"); }"#)
+        .build();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build.executor"])
+        .env("CARGO_BUILD_EXECUTOR", &executor)
+        .with_stderr_contains("EXECUTOR: bar")
+        .with_stderr_contains("EXECUTOR: foo")
+        .run();
+    p.process(&p.bin("foo")).with_stdout("bar\n").run();
+
+    let request = p
+        .glob("target/debug/.fingerprint/foo-*/executor-request.json")
+        .next()
+        .unwrap()
+        .unwrap();
+    let request = std::fs::read_to_string(request).unwrap();
+    assert!(request.contains(r#""version": 1,"#));
+    assert!(request.contains(r#""target": "foo","#));
+    assert!(request.contains("libbar-"));
+
+    // Fresh units aren't sent to the executor.
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build.executor"])
+        .env("CARGO_BUILD_EXECUTOR", &executor)
+        .with_stderr_does_not_contain("EXECUTOR: [..]")
+        .run();
+}
+
+#[cargo_test]
+fn forwards_diagnostics() {
+    let executor = executor();
+    let p = project()
+        .file("src/lib.rs", "pub fn foo() -> u32 { \"\" }")
+        .build();
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build.executor"])
+        .env("CARGO_BUILD_EXECUTOR", &executor)
+        .with_status(101)
+        .with_stderr_contains("EXECUTOR: foo")
+        .with_stderr_contains("[ERROR] mismatched types")
+        .with_stderr_contains("[ERROR] could not compile `foo` (lib) due to 1 previous error")
+        .run();
+}
+
+#[cargo_test]
+fn missing_outputs() {
+    let executor = executor();
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build.executor"])
+        .env("CARGO_BUILD_EXECUTOR", &executor)
+        .env("EXECUTOR_SKIP", "1")
+        .with_status(101)
+        .with_stderr_contains("[ERROR] could not compile `foo` (lib)")
+        .with_stderr_contains(
+            "  the executor `[..]executor[EXE]` exited successfully but did not produce:",
+        )
+        .with_stderr_contains("    [ROOT]/foo/target/debug/deps/libfoo-[..].rmeta")
+        .with_stderr_contains("    [ROOT]/foo/target/debug/deps/foo-[..].d")
+        .run();
+}
@@ -0,0 +1,78 @@
+//! Tests for `-Zbuild-meta`, detecting changes of the build inputs.
+
+use cargo_test_support::project;
+
+#[cargo_test]
+fn not_recorded_without_flag() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check").run();
+    assert!(!p.build_dir().join(".build-meta").exists());
+
+    p.cargo("check -Zbuild-meta")
+        .masquerade_as_nightly_cargo(&["build-meta"])
+        .run();
+    assert!(p.build_dir().join(".build-meta").exists());
+}
+
+#[cargo_test]
+fn warns_about_changed_env() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check -Zbuild-meta")
+        .masquerade_as_nightly_cargo(&["build-meta"])
+        .with_stderr_does_not_contain("[WARNING] build inputs changed[..]")
+        .run();
+
+    p.cargo("check -Zbuild-meta")
+        .masquerade_as_nightly_cargo(&["build-meta"])
+        .env("RUSTFLAGS", "-Cdebug-assertions=off")
+        .with_stderr(
+            "\
+[CHECKING] foo v0.0.1 ([CWD])
+[WARNING] build inputs changed since the last build, which may explain why 1 of 1 units were rebuilt:
+  environment variable `RUSTFLAGS` was set
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    // Changes which don't cause rebuilds aren't reported.
+    p.cargo("check -Zbuild-meta")
+        .masquerade_as_nightly_cargo(&["build-meta"])
+        .env("RUSTFLAGS", "-Cdebug-assertions=off")
+        .env("CARGO_BUILD_JOBS", "1")
+        .with_stderr(
+            "\
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn warns_about_changed_config() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check -Zbuild-meta")
+        .masquerade_as_nightly_cargo(&["build-meta"])
+        .run();
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [profile.dev]
+            debug-assertions = false
+        "#,
+    );
+
+    p.cargo("check -Zbuild-meta")
+        .masquerade_as_nightly_cargo(&["build-meta"])
+        .with_stderr_contains(
+            "\
+[WARNING] build inputs changed since the last build, which may explain why 1 of 1 units were rebuilt:
+  config `profile` was set
+",
+        )
+        .run();
+}
@@ -1,4 +1,4 @@
-//! Tests for --build-plan feature.
+//! Tests for the `--build-plan` flag.
 
 use cargo_test_support::registry::Package;
 use cargo_test_support::{basic_bin_manifest, basic_manifest, main_file, project};
@@ -10,11 +10,11 @@ fn cargo_build_plan_simple() {
         .file("src/foo.rs", &main_file(r#""i am foo""#, &[]))
         .build();
 
-    p.cargo("build --build-plan -Zunstable-options")
-        .masquerade_as_nightly_cargo(&["build-plan"])
+    p.cargo("build --build-plan")
         .with_json(
             r#"
             {
+                "version": 1,
                 "inputs": [
                     "[..]/foo/Cargo.toml"
                 ],
@@ -23,6 +23,7 @@ fn cargo_build_plan_simple() {
                         "args": "{...}",
                         "cwd": "[..]/cit/[..]/foo",
                         "deps": [],
+                        "inputs": ["[..]/foo/src/foo.rs"],
                         "env": "{...}",
                         "kind": null,
                         "links": "{...}",
@@ -69,11 +70,11 @@ fn cargo_build_plan_single_dep() {
         .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
         .file("bar/src/lib.rs", "pub fn bar() {}")
         .build();
-    p.cargo("build --build-plan -Zunstable-options")
-        .masquerade_as_nightly_cargo(&["build-plan"])
+    p.cargo("build --build-plan")
         .with_json(
             r#"
             {
+                "version": 1,
                 "inputs": [
                     "[..]/foo/Cargo.toml",
                     "[..]/foo/bar/Cargo.toml"
@@ -83,6 +84,7 @@ fn cargo_build_plan_single_dep() {
                         "args": "{...}",
                         "cwd": "[..]/cit/[..]/foo",
                         "deps": [],
+                        "inputs": ["[..]/foo/bar/src/lib.rs"],
                         "env": "{...}",
                         "kind": null,
                         "links": "{...}",
@@ -100,6 +102,7 @@ fn cargo_build_plan_single_dep() {
                         "args": "{...}",
                         "cwd": "[..]/cit/[..]/foo",
                         "deps": [0],
+                        "inputs": ["[..]/foo/src/lib.rs"],
                         "env": "{...}",
                         "kind": null,
                         "links": "{...}",
@@ -138,11 +141,11 @@ fn cargo_build_plan_build_script() {
         .file("build.rs", r#"fn main() {}"#)
         .build();
 
-    p.cargo("build --build-plan -Zunstable-options")
-        .masquerade_as_nightly_cargo(&["build-plan"])
+    p.cargo("build --build-plan")
         .with_json(
             r#"
             {
+                "version": 1,
                 "inputs": [
                     "[..]/foo/Cargo.toml"
                 ],
@@ -151,6 +154,7 @@ fn cargo_build_plan_build_script() {
                         "args": "{...}",
                         "cwd": "[..]/cit/[..]/foo",
                         "deps": [],
+                        "inputs": ["[..]/foo/build.rs"],
                         "env": "{...}",
                         "kind": null,
                         "links": "{...}",
@@ -165,6 +169,7 @@ fn cargo_build_plan_build_script() {
                         "args": "{...}",
                         "cwd": "[..]/cit/[..]/foo",
                         "deps": [0],
+                        "inputs": ["[..]/foo"],
                         "env": "{...}",
                         "kind": null,
                         "links": "{...}",
@@ -179,6 +184,94 @@ fn cargo_build_plan_build_script() {
                         "args": "{...}",
                         "cwd": "[..]/cit/[..]/foo",
                         "deps": [1],
+                        "inputs": ["[..]/foo/src/main.rs"],
+                        "env": "{...}",
+                        "kind": null,
+                        "links": "{...}",
+                        "outputs": "{...}",
+                        "package_name": "foo",
+                        "package_version": "0.5.0",
+                        "program": "rustc",
+                        "target_kind": ["bin"],
+                        "compile_mode": "build"
+                    }
+                ]
+            }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn cargo_build_plan_inputs_of_previous_build() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                edition = "2015"
+                build = "build.rs"
+            "#,
+        )
+        .file("src/main.rs", "mod a; fn main() { a::a() }")
+        .file("src/a.rs", "pub fn a() {}")
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo::rerun-if-changed=data.txt"); }"#,
+        )
+        .file("data.txt", "")
+        .build();
+
+    p.cargo("build").run();
+    p.cargo("build --build-plan")
+        .with_json(
+            r#"
+            {
+                "version": 1,
+                "inputs": [
+                    "[..]/foo/Cargo.toml"
+                ],
+                "invocations": [
+                    {
+                        "args": "{...}",
+                        "cwd": "[..]/cit/[..]/foo",
+                        "deps": [],
+                        "inputs": ["[..]/foo/build.rs"],
+                        "previous_build_inputs": ["[..]/foo/build.rs"],
+                        "env": "{...}",
+                        "kind": null,
+                        "links": "{...}",
+                        "outputs": "{...}",
+                        "package_name": "foo",
+                        "package_version": "0.5.0",
+                        "program": "rustc",
+                        "target_kind": ["custom-build"],
+                        "compile_mode": "build"
+                    },
+                    {
+                        "args": "{...}",
+                        "cwd": "[..]/cit/[..]/foo",
+                        "deps": [0],
+                        "inputs": ["[..]/foo"],
+                        "previous_build_inputs": ["[..]/foo/data.txt"],
+                        "env": "{...}",
+                        "kind": null,
+                        "links": "{...}",
+                        "outputs": [],
+                        "package_name": "foo",
+                        "package_version": "0.5.0",
+                        "program": "[..]/build-script-build",
+                        "target_kind": ["custom-build"],
+                        "compile_mode": "run-custom-build"
+                    },
+                    {
+                        "args": "{...}",
+                        "cwd": "[..]/cit/[..]/foo",
+                        "deps": [1],
+                        "inputs": ["[..]/foo/src/main.rs"],
+                        "previous_build_inputs": ["[..]/foo/src/main.rs", "[..]/foo/src/a.rs"],
                         "env": "{...}",
                         "kind": null,
                         "links": "{...}",
@@ -216,7 +309,112 @@ fn build_plan_with_dev_dep() {
         .file("src/lib.rs", "")
         .build();
 
-    p.cargo("build --build-plan -Zunstable-options")
-        .masquerade_as_nightly_cargo(&["build-plan"])
+    p.cargo("build --build-plan").run();
+}
+
+#[cargo_test]
+fn emit_ninja_gated() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/foo.rs", &main_file(r#""i am foo""#, &[]))
+        .build();
+
+    p.cargo("build --emit-ninja build.ninja")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--emit-ninja` flag is unstable[..]")
         .run();
 }
+
+#[cargo_test]
+fn emit_ninja() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                edition = "2015"
+                build = "build.rs"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "extern crate bar; fn main() { bar::bar(); }")
+        .file("build.rs", "fn main() {}")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "pub fn bar() {}")
+        .build();
+
+    p.cargo("build -Zunstable-options --emit-ninja build.ninja")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("")
+        .run();
+    assert!(!p.bin("foo").is_file());
+
+    let ninja = p.read_file("build.ninja");
+    let statements: Vec<_> = ninja.split("\n\n").collect();
+    assert!(
+        statements[0].contains(&format!("#   {}", p.root().join("Cargo.toml").display())),
+        "{ninja}"
+    );
+    assert!(
+        statements[1].contains("ninja_required_version = 1.10"),
+        "{ninja}"
+    );
+    assert!(statements[2].starts_with("rule cargo\n"), "{ninja}");
+
+    // One statement per invocation.
+    let statements = &statements[3..];
+    assert_eq!(statements.len(), 4, "{ninja}");
+    let find = |description: &str, target: &str| {
+        *statements
+            .iter()
+            .find(|statement| {
+                statement.contains(&format!("  description = {description}\n"))
+                    && statement.contains(target)
+            })
+            .unwrap_or_else(|| panic!("no statement for `{description}`:\n{ninja}"))
+    };
+
+    let bar = find("Compiling bar v0.0.1", "");
+    assert!(bar.starts_with("build "), "{bar}");
+    assert!(bar.contains("/bar/src/lib.rs\n"), "{bar}");
+    assert!(bar.contains(" 'rustc' "), "{bar}");
+    assert!(bar.contains("'--crate-name' 'bar'"), "{bar}");
+    assert!(bar.contains("  depfile = "), "{bar}");
+    assert!(bar.contains("  deps = gcc"), "{bar}");
+
+    let build_script = find("Compiling foo v0.5.0", "/build.rs");
+    assert!(
+        build_script.contains("'--crate-name' 'build_script_build'"),
+        "{build_script}"
+    );
+
+    let run_build_script = find("Running build script of foo v0.5.0", "");
+    assert!(
+        run_build_script.contains(&format!("/output: cargo {} | ", p.root().display())),
+        "{run_build_script}"
+    );
+    assert!(
+        run_build_script.contains("mkdir -p '"),
+        "{run_build_script}"
+    );
+    assert!(run_build_script.contains(" > '"), "{run_build_script}");
+    assert!(!run_build_script.contains("depfile"), "{run_build_script}");
+
+    let bin = find("Compiling foo v0.5.0", "/src/main.rs");
+    assert!(bin.contains("/output"), "{bin}");
+    assert!(bin.contains("/libbar-"), "{bin}");
+    assert!(bin.contains(" && ln -f '"), "{bin}");
+    // The directives of the build script are passed to rustc.
+    assert!(bin.contains("set -- && while IFS= read -r line;"), "{bin}");
+    assert!(
+        bin.contains(r#"cargo::rustc-cfg=*) set -- "$$@" --cfg "$${line#*=}";;"#),
+        "{bin}"
+    );
+    assert!(bin.contains(r#" "$$@" && ln -f '"#), "{bin}");
+    assert!(!bar.contains("read -r line"), "{bar}");
+}
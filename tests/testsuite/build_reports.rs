@@ -0,0 +1,175 @@
+//! Tests for `-Zbuild-reports` and the `cargo report` subcommands browsing
+//! previous builds.
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn report_builds_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("report builds")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo report builds` command is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+
+    p.cargo("report future-incompat --json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--json` flag is unstable[..]")
+        .run();
+}
+
+#[cargo_test]
+fn no_builds_recorded() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check").run();
+    p.cargo("report builds -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("")
+        .with_stderr("[NOTE] no builds have been recorded, pass `-Zbuild-reports` to record them")
+        .run();
+    assert!(!p.root().join("target/reports").exists());
+}
+
+#[cargo_test]
+fn records_builds() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/main.rs", "fn main() { let x = 1; }")
+        .build();
+
+    p.cargo("check -Zbuild-reports")
+        .masquerade_as_nightly_cargo(&["build-reports"])
+        .run();
+    p.cargo("check -Zbuild-reports")
+        .masquerade_as_nightly_cargo(&["build-reports"])
+        .run();
+    p.change_file("src/main.rs", "fn main() { let x = }");
+    p.cargo("check -Zbuild-reports")
+        .masquerade_as_nightly_cargo(&["build-reports"])
+        .with_status(101)
+        .run();
+
+    p.cargo("report builds -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "  ID  FINISHED              PROFILE     RESULT    DURATION  FRESH  DIRTY  WARNINGS
+   1  [..]  dev         success  [..]      0      1         1
+   2  [..]  dev         success  [..]      1      0         1
+   3  [..]  dev         failure  [..]      0      1         0
+",
+        )
+        .run();
+
+    p.cargo("report builds -Zunstable-options --id 3 --json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+                {
+                  "id": 3,
+                  "finished": "{...}",
+                  "profile": "dev",
+                  "success": false,
+                  "duration": "{...}",
+                  "fresh": 0,
+                  "dirty": 1,
+                  "warnings": 0,
+                  "errors": 1
+                }
+            "#,
+        )
+        .run();
+
+    p.cargo("report builds -Zunstable-options --id 4")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] could not find build with ID 4
+Available IDs are: 1, 2, 3
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn prune_reports() {
+    let p = project().file("src/lib.rs", "").build();
+
+    for _ in 0..3 {
+        p.cargo("check -Zbuild-reports")
+            .masquerade_as_nightly_cargo(&["build-reports"])
+            .run();
+    }
+
+    p.cargo("report prune -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr("[REMOVED] 2 build summaries, 0 future-incompat reports, 0 timing reports")
+        .run();
+    p.cargo("report builds -Zunstable-options --json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+                {
+                  "builds": [
+                    {
+                      "id": 3,
+                      "finished": "{...}",
+                      "profile": "dev",
+                      "success": true,
+                      "duration": "{...}",
+                      "fresh": 1,
+                      "dirty": 0,
+                      "warnings": 0,
+                      "errors": 0
+                    }
+                  ]
+                }
+            "#,
+        )
+        .run();
+
+    p.cargo("report prune -Zunstable-options --keep 0")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr("[REMOVED] 1 build summary, 0 future-incompat reports, 0 timing reports")
+        .run();
+    p.cargo("report builds -Zunstable-options --json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(r#"{"builds": []}"#)
+        .run();
+}
+
+#[cargo_test]
+fn report_timings() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("report timings -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr("[NOTE] no timing reports found, pass `--timings` to a build to write one")
+        .run();
+
+    p.cargo("check --timings").run();
+    p.cargo("report timings -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("[..]Z  [CWD]/target/cargo-timings/cargo-timing-[..]Z.html")
+        .run();
+
+    p.cargo("report prune -Zunstable-options --keep 0")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr("[REMOVED] 0 build summaries, 0 future-incompat reports, 1 timing report")
+        .run();
+    assert!(!p
+        .root()
+        .join("target/cargo-timings/cargo-timing.html")
+        .exists());
+    p.cargo("report timings -Zunstable-options --json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(r#"{"timings": []}"#)
+        .run();
+}
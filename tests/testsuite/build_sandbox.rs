@@ -0,0 +1,137 @@
+//! Tests for the `build.sandbox` config.
+
+use cargo_test_support::{paths, project};
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("check")
+        .env("CARGO_BUILD_SANDBOX", "true")
+        .with_stderr_contains(
+            "[WARNING] `build.sandbox` requires `-Zunstable-options`, ignoring it",
+        )
+        .run();
+}
+
+#[cfg(target_os = "linux")]
+#[cargo_test(requires_bwrap)]
+fn build_script_only_writes_out_dir() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+                use std::path::Path;
+
+                fn main() {
+                    let out_dir = std::env::var("OUT_DIR").unwrap();
+                    std::fs::write(Path::new(&out_dir).join("out.txt"), "").unwrap();
+                    let wrote = std::fs::write("escaped.txt", "").is_ok();
+                    let read = std::fs::read(std::env::var("SECRET_PATH").unwrap()).is_ok();
+                    let addr = std::env::var("LISTENER_ADDR").unwrap();
+                    let connected = std::net::TcpStream::connect(addr).is_ok();
+                    println!("cargo:warning=wrote outside OUT_DIR: {wrote}, read secret: {read}, connected: {connected}");
+                }
+            "#,
+        )
+        .build();
+    let secret = paths::home().join(".ssh/id_ed25519");
+    std::fs::create_dir_all(secret.parent().unwrap()).unwrap();
+    std::fs::write(&secret, "secret").unwrap();
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build.sandbox"])
+        .env("CARGO_BUILD_SANDBOX", "true")
+        .env("SECRET_PATH", &secret)
+        .env("LISTENER_ADDR", listener.local_addr().unwrap().to_string())
+        .with_stderr_contains(
+            "[WARNING] foo@0.0.1: wrote outside OUT_DIR: false, read secret: false, connected: false",
+        )
+        .run();
+    assert!(!p.root().join("escaped.txt").exists());
+
+    // Without the sandbox, the same script can do all of them.
+    p.cargo("clean").run();
+    p.cargo("check")
+        .env("SECRET_PATH", &secret)
+        .env("LISTENER_ADDR", listener.local_addr().unwrap().to_string())
+        .with_stderr_contains(
+            "[WARNING] foo@0.0.1: wrote outside OUT_DIR: true, read secret: true, connected: true",
+        )
+        .run();
+}
+
+#[cfg(target_os = "linux")]
+#[cargo_test(requires_bwrap)]
+fn proc_macro_of_listed_package() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                pm = { path = "pm" }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                #[macro_use]
+                extern crate pm;
+
+                probe!();
+
+                fn main() {
+                    println!("wrote: {}", WROTE);
+                }
+            "#,
+        )
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+                edition = "2015"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file(
+            "pm/src/lib.rs",
+            r#"
+                extern crate proc_macro;
+                use proc_macro::TokenStream;
+
+                #[proc_macro]
+                pub fn probe(_input: TokenStream) -> TokenStream {
+                    let wrote = std::fs::write("escaped.txt", "").is_ok();
+                    format!("const WROTE: bool = {};", wrote).parse().unwrap()
+                }
+            "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                sandbox = ["pm"]
+            "#,
+        )
+        .build();
+
+    p.cargo("run -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build.sandbox"])
+        .with_stdout("wrote: false\n")
+        .run();
+    assert!(!p.root().join("escaped.txt").exists());
+}
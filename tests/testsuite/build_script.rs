@@ -1346,6 +1346,189 @@ fn links_passes_env_vars() {
     p.cargo("build -v").run();
 }
 
+#[cargo_test]
+fn links_metadata_typed() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["links-metadata"]
+
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                edition = "2015"
+                build = "build.rs"
+
+                [package.links-consume]
+                foo = ["include", "static"]
+
+                [dependencies.a]
+                path = "a"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r##"
+                use std::env;
+                fn main() {
+                    assert_eq!(
+                        env::var("CARGO_LINKS_METADATA").unwrap(),
+                        r#"{"foo":{"include":"/usr/include/foo","static":true}}"#
+                    );
+                    assert_eq!(env::var("DEP_FOO_STATIC").unwrap(), "true");
+                }
+            "##,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                cargo-features = ["links-metadata"]
+
+                [package]
+                name = "a"
+                version = "0.5.0"
+                edition = "2015"
+                links = "foo"
+                build = "build.rs"
+
+                [package.links-metadata]
+                include = "path"
+                static = "bool"
+                version = "integer"
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "a/build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::metadata=include=/usr/include/foo");
+                    println!("cargo::metadata=static=true");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo(&["links-metadata"])
+        .run();
+}
+
+#[cargo_test]
+fn links_metadata_invalid_value() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["links-metadata"]
+
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                edition = "2015"
+                links = "foo"
+                build = "build.rs"
+
+                [package.links-metadata]
+                static = "bool"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::metadata=static=yes");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo(&["links-metadata"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[COMPILING] foo v0.5.0 ([CWD])
+[ERROR] the build script of `foo v0.5.0 ([CWD])` emitted an invalid value `yes` \
+for the `cargo::metadata` key `static`, expected bool
+",
+        )
+        .run();
+
+    p.change_file(
+        "build.rs",
+        r#"
+            fn main() {
+                println!("cargo::metadata=shared=true");
+            }
+        "#,
+    );
+    p.cargo("build")
+        .masquerade_as_nightly_cargo(&["links-metadata"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[COMPILING] foo v0.5.0 ([CWD])
+[ERROR] the build script of `foo v0.5.0 ([CWD])` emitted the `cargo::metadata` key `shared`, \
+which is not declared in `package.links-metadata`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn links_metadata_undeclared_consumed_key() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["links-metadata"]
+
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                edition = "2015"
+                build = "build.rs"
+
+                [package.links-consume]
+                foo = ["lib-dir"]
+
+                [dependencies.a]
+                path = "a"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.5.0"
+                edition = "2015"
+                links = "foo"
+                build = "build.rs"
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file("a/build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo(&["links-metadata"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] package `foo v0.5.0 ([CWD])` consumes the `links` metadata key `lib-dir` of `foo`, \
+but `a v0.5.0 ([CWD]/a)` does not declare it in `package.links-metadata`
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn only_rerun_build_script() {
     let p = project()
@@ -5716,3 +5899,230 @@ hello",
         )
         .run();
 }
+
+#[cargo_test]
+fn fresh_build_script_output_replayed() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::warning=careful");
+                    eprintln!("some stderr");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("check -vv")
+        .with_stderr_contains("[foo 0.0.1] some stderr")
+        .with_stderr_contains("[WARNING] foo@0.0.1: careful")
+        .run();
+
+    // The build script is fresh, what it printed is replayed.
+    p.cargo("check -vv")
+        .with_stderr_contains("[FRESH] foo v0.0.1 ([CWD])")
+        .with_stderr_contains("[foo 0.0.1] some stderr")
+        .with_stderr_contains("[WARNING] foo@0.0.1: careful")
+        .run();
+
+    p.cargo("check --message-format json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json_contains_unordered(
+            r#"
+            {
+              "reason": "build-script-output",
+              "package_id": "path+file:///[..]/foo#0.0.1",
+              "fresh": true,
+              "warnings": ["careful"],
+              "diagnostics": [],
+              "stderr": "some stderr\n"
+            }
+            "#,
+        )
+        .run();
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [build]
+            replay-warnings = false
+        "#,
+    );
+    p.cargo("check -vv -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_does_not_contain("[..]some stderr")
+        .with_stderr_does_not_contain("[..]careful")
+        .run();
+}
+
+#[cargo_test]
+#[cfg(target_os = "linux")]
+fn missing_native_symbols_attributed_to_links() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar-sys = { path = "bar-sys" }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                extern crate bar_sys;
+
+                extern "C" {
+                    fn bar_init();
+                    fn other_init();
+                }
+
+                fn main() {
+                    unsafe {
+                        bar_init();
+                        other_init();
+                    }
+                }
+            "#,
+        )
+        .file(
+            "bar-sys/Cargo.toml",
+            r#"
+                [package]
+                name = "bar-sys"
+                version = "0.0.1"
+                edition = "2015"
+                links = "bar"
+            "#,
+        )
+        .file("bar-sys/src/lib.rs", "")
+        .file("bar-sys/build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("[ERROR] linking with `[..]` failed: [..]")
+        .with_stderr_contains(
+            "\
+[NOTE] the linker couldn't find 2 symbols needed by `foo` (bin \"foo\"):
+  `bar_init`, which may be provided by `bar-sys v0.0.1 ([CWD]/bar-sys)` (`links = \"bar\"`)
+  `other_init`, which isn't provided by any package with a `links` key",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn structured_diagnostics() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r##"
+                fn main() {
+                    println!(r#"cargo::diagnostic={{"level":"warning","message":"libfoo not found","file":"build/foo.c","line":3,"column":5,"help":"install libfoo"}}"#);
+                    println!(r#"cargo::diagnostic={{"level":"note","message":"using the bundled libfoo"}}"#);
+                }
+            "##,
+        )
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["cargo::diagnostic"])
+        .with_stderr_contains(
+            "\
+[WARNING] foo@0.0.1: libfoo not found
+  --> build/foo.c:3:5
+   = help: install libfoo
+[NOTE] foo@0.0.1: using the bundled libfoo
+",
+        )
+        .run();
+
+    p.cargo("check --message-format json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo::diagnostic"])
+        .with_json_contains_unordered(
+            r#"
+            {
+              "reason": "build-script-output",
+              "package_id": "path+file:///[..]/foo#0.0.1",
+              "fresh": true,
+              "warnings": [],
+              "diagnostics": [
+                {
+                  "level": "warning",
+                  "message": "libfoo not found",
+                  "file": "build/foo.c",
+                  "line": 3,
+                  "column": 5,
+                  "help": "install libfoo"
+                },
+                {
+                  "level": "note",
+                  "message": "using the bundled libfoo"
+                }
+              ],
+              "stderr": ""
+            }
+            "#,
+        )
+        .run();
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr_contains(
+            "The `cargo::diagnostic` instruction is unstable and requires the nightly channel of Cargo.",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn structured_diagnostics_errors() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r##"
+                fn main() {
+                    println!("cargo::warning=careful");
+                    println!(r#"cargo::diagnostic={{"level":"error","message":"unsupported target"}}"#);
+                    println!("some raw output");
+                    if std::env::var("EXIT_FAILURE").is_ok() {
+                        std::process::exit(1);
+                    }
+                }
+            "##,
+        )
+        .build();
+
+    // The build script fails even though it exits successfully.
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["cargo::diagnostic"])
+        .with_status(101)
+        .with_stderr_contains("[WARNING] foo@0.0.1: careful")
+        .with_stderr_contains("[ERROR] foo@0.0.1: unsupported target")
+        .with_stderr_contains(
+            "\
+[ERROR] failed to run custom build command for `foo v0.0.1 ([ROOT]/foo)`
+
+Caused by:
+  the build script reported 1 error
+",
+        )
+        .run();
+
+    // The reported errors replace the raw output of a failing build script.
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["cargo::diagnostic"])
+        .env("EXIT_FAILURE", "1")
+        .with_status(101)
+        .with_stderr_contains("[ERROR] foo@0.0.1: unsupported target")
+        .with_stderr_contains("  the build script reported 1 error")
+        .with_stderr_does_not_contain("[..]some raw output[..]")
+        .run();
+}
@@ -0,0 +1,192 @@
+//! Tests for `-Zbuild-server` and the `cargo server` command.
+
+use std::net::TcpStream;
+use std::process::{Child, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use cargo_test_support::install::cargo_home;
+use cargo_test_support::{basic_manifest, execs, project, Project};
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("server")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo server` command is unstable, pass `-Z build-server` to enable it
+",
+        )
+        .run();
+}
+
+/// Starts a build server for `p`, and waits for it to write where it listens.
+fn start_server(p: &Project) -> Child {
+    let mut server = p
+        .cargo("-Zbuild-server -v server")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .build_command();
+    server.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let server = server.spawn().unwrap();
+
+    let servers = cargo_home().join("build-servers");
+    let start = Instant::now();
+    while !servers.exists() || servers.read_dir().unwrap().next().is_none() {
+        assert!(
+            start.elapsed() < Duration::from_secs(60),
+            "server didn't start"
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+    server
+}
+
+#[cargo_test]
+fn build_with_server() {
+    let p = project().file("src/lib.rs", "").build();
+    let server = start_server(&p);
+    let servers = cargo_home().join("build-servers");
+
+    p.cargo("-Zbuild-server -v build")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    // The global options must be the same as the server's.
+    p.cargo("-Zbuild-server -v check --offline")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .with_stderr_contains(
+            "[NOTE] not using the build server: the global options differ from the server's",
+        )
+        .with_stderr_contains("[CHECKING] foo v0.0.1 ([CWD])")
+        .run();
+
+    // So must the environment, which the builds run with.
+    p.cargo("-Zbuild-server -v check")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .env("CC", "other-cc")
+        .with_stderr_contains(
+            "[NOTE] not using the build server: the environment differs from the server's",
+        )
+        .run();
+
+    p.cargo("-Zbuild-server server --stop")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .with_stderr("     Stopped build server on 127.0.0.1:[..]")
+        .run();
+
+    let output = server.wait_with_output().unwrap();
+    execs()
+        .with_stderr(
+            "\
+     Serving builds of `[ROOT]/foo` on 127.0.0.1:[..]
+      Served `cargo -Zbuild-server -v build` (exit code 0)
+",
+        )
+        .run_output(&output);
+    assert!(servers.read_dir().unwrap().next().is_none());
+
+    p.cargo("-Zbuild-server server --stop")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .with_status(101)
+        .with_stderr("[ERROR] no build server is running for `[ROOT]/foo/Cargo.toml`")
+        .run();
+}
+
+#[cargo_test]
+fn server_stops_when_config_changes() {
+    let p = project().file("src/lib.rs", "").build();
+    let server = start_server(&p);
+
+    p.change_file(".cargo/config.toml", "[build]\nincremental = false\n");
+    p.cargo("-Zbuild-server -v build")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .with_stderr(
+            "\
+[NOTE] not using the build server: the configuration changed since the server started
+[COMPILING] foo v0.0.1 ([CWD])
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    let output = server.wait_with_output().unwrap();
+    execs()
+        .with_stderr(
+            "\
+     Serving builds of `[ROOT]/foo` on 127.0.0.1:[..]
+    Stopping the build server, the configuration changed since it started
+",
+        )
+        .run_output(&output);
+    let servers = cargo_home().join("build-servers");
+    assert!(servers.read_dir().unwrap().next().is_none());
+}
+
+#[cargo_test]
+fn server_gives_up_on_idle_clients() {
+    let p = project().file("src/lib.rs", "").build();
+    let server = start_server(&p);
+
+    // A client which never sends its request doesn't block the others.
+    let servers = cargo_home().join("build-servers");
+    let info = servers.read_dir().unwrap().next().unwrap().unwrap().path();
+    let info: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(info).unwrap()).unwrap();
+    let _idle = TcpStream::connect(info["addr"].as_str().unwrap()).unwrap();
+
+    p.cargo("-Zbuild-server -v build")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .with_stderr_does_not_contain("[NOTE] not using the build server[..]")
+        .with_stderr_contains("[COMPILING] foo v0.0.1 ([CWD])")
+        .run();
+
+    p.cargo("-Zbuild-server server --stop")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .run();
+    server.wait_with_output().unwrap();
+}
+
+#[cargo_test]
+fn server_reloads_workspace_with_new_glob_member() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["crates/*"]
+            "#,
+        )
+        .file("crates/a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("crates/a/src/lib.rs", "")
+        .build();
+    let server = start_server(&p);
+
+    p.cargo("-Zbuild-server -v build")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .with_stderr_does_not_contain("[NOTE] not using the build server[..]")
+        .with_stderr_contains("[COMPILING] a v0.1.0 ([..])")
+        .run();
+
+    p.change_file("crates/b/Cargo.toml", &basic_manifest("b", "0.1.0"));
+    p.change_file("crates/b/src/lib.rs", "");
+    p.cargo("-Zbuild-server -v build")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .with_stderr_does_not_contain("[NOTE] not using the build server[..]")
+        .with_stderr_contains("[COMPILING] b v0.1.0 ([..])")
+        .run();
+
+    p.cargo("-Zbuild-server server --stop")
+        .masquerade_as_nightly_cargo(&["build-server"])
+        .run();
+    server.wait_with_output().unwrap();
+}
@@ -496,3 +496,53 @@ fn wacky_hashless_fingerprint() {
         .with_stderr_does_not_contain("[..]unused[..]")
         .run();
 }
+
+#[cargo_test]
+fn replay_warnings_disabled() {
+    let p = project()
+        .file("src/lib.rs", "fn unused_func() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                replay-warnings = false
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_contains("[WARNING] `foo` (lib) generated 1 warning")
+        .run();
+
+    // Fresh, the cached warning isn't shown.
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr(
+            "\
+[FINISHED] `dev` profile [..]
+",
+        )
+        .run();
+
+    // The config is ignored without `-Zunstable-options`.
+    p.cargo("check")
+        .with_stderr_contains(
+            "[WARNING] `build.replay-warnings` requires `-Zunstable-options`, ignoring it",
+        )
+        .with_stderr_contains("[WARNING] `foo` (lib) generated 1 warning")
+        .with_stderr_does_not_contain("[..](cached)[..]")
+        .run();
+}
+
+#[cargo_test]
+fn replayed_warnings_marked_cached() {
+    let p = project().file("src/lib.rs", "fn unused_func() {}").build();
+
+    p.cargo("check").run();
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_contains("[WARNING] `foo` (lib) generated 1 warning (cached)")
+        .run();
+}
@@ -16,7 +16,6 @@ fn case() {
     snapbox::cmd::Command::cargo_ui()
         .arg("add")
         .args(["foo", "-p", "bar", "--public"])
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .current_dir(cwd)
         .assert()
         .success()
@@ -70,6 +70,7 @@ mod no_args;
 mod no_default_features;
 mod no_optional;
 mod no_public;
+mod no_workspace_table;
 mod offline_empty_cache;
 mod optional;
 mod overwrite_default_features;
@@ -136,3 +137,5 @@ mod vers;
 mod workspace_name;
 mod workspace_path;
 mod workspace_path_dev;
+mod workspace_table;
+mod workspace_table_incompatible;
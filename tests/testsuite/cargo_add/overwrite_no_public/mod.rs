@@ -17,7 +17,6 @@ fn case() {
         .arg("add")
         .arg_line("my-package --no-public")
         .current_dir(cwd)
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .assert()
         .success()
         .stdout_matches(str![""])
@@ -242,3 +242,50 @@ fn env_applied_to_target_info_discovery_rustc() {
         .with_stderr_contains("MAIN ENV_TEST:from-env")
         .run();
 }
+
+#[cargo_test]
+fn env_placeholders() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo"]
+                resolver = "2"
+            "#,
+        )
+        .file("foo/Cargo.toml", &basic_bin_manifest("foo"))
+        .file(
+            "foo/src/main.rs",
+            r#"
+        use std::env;
+        fn main() {
+            println!( "compile-time:{}", env!("ENV_TEST_ROOT") );
+            println!( "run-time:{}", env::var("ENV_TEST_ROOT").unwrap());
+            println!( "out:{}", env::var("ENV_TEST_OUT").unwrap());
+        }
+        "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [env]
+                ENV_TEST_ROOT = "{workspace-root}"
+                ENV_TEST_OUT = { value = "{target-dir}/generated", relative = true }
+            "#,
+        )
+        .build();
+
+    p.cargo("run")
+        .with_stdout_contains("compile-time:{workspace-root}")
+        .with_stdout_contains("out:[ROOT]/foo/{target-dir}/generated")
+        .run();
+
+    p.cargo("run -Zconfig-placeholders")
+        .cwd(p.root().join("foo"))
+        .masquerade_as_nightly_cargo(&["config-placeholders"])
+        .with_stdout_contains("compile-time:[ROOT]/foo")
+        .with_stdout_contains("run-time:[ROOT]/foo")
+        .with_stdout_contains("out:[ROOT]/foo/target/generated")
+        .run();
+}
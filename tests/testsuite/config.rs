@@ -518,7 +518,7 @@ lto = false
         cargo_toml::TomlProfile {
             lto: Some(cargo_toml::StringOrBool::Bool(false)),
             dir_name: Some(String::from("without-lto")),
-            inherits: Some(String::from("dev")),
+            inherits: Some(cargo_toml::TomlProfileInherits::One(String::from("dev"))),
             ..Default::default()
         }
     );
@@ -1612,7 +1612,7 @@ fn all_profile_options() {
         overflow_checks: Some(true),
         incremental: Some(true),
         dir_name: Some(String::from("dir_name")),
-        inherits: Some(String::from("debug")),
+        inherits: Some(cargo_toml::TomlProfileInherits::One(String::from("debug"))),
         strip: Some(cargo_toml::StringOrBool::String("symbols".to_string())),
         package: None,
         build_override: None,
@@ -364,6 +364,119 @@ fn workspace_with_multiple_targets() {
     }
 }
 
+#[cargo_test]
+fn workspace_build_targets() {
+    if cross_compile::disabled() {
+        return;
+    }
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    cargo-features = ["per-package-target"]
+
+                    [workspace]
+                    members = ["native", "cross"]
+
+                    [workspace.build]
+                    targets = ["{}"]
+                "#,
+                cross_compile::alternate(),
+            ),
+        )
+        .file(
+            "native/Cargo.toml",
+            r#"
+                cargo-features = ["per-package-target"]
+
+                [package]
+                name = "native"
+                version = "0.0.0"
+                edition = "2015"
+                authors = []
+                build = "build.rs"
+                default-target = "host"
+            "#,
+        )
+        .file(
+            "native/build.rs",
+            &format!(
+                r#"
+                    fn main() {{
+                        assert_eq!(std::env::var("TARGET").unwrap(), "{}");
+                    }}
+                "#,
+                cross_compile::native()
+            ),
+        )
+        .file("native/src/main.rs", "fn main() {}")
+        .file(
+            "cross/Cargo.toml",
+            r#"
+                [package]
+                name = "cross"
+                version = "0.0.0"
+                edition = "2015"
+                authors = []
+                build = "build.rs"
+            "#,
+        )
+        .file(
+            "cross/build.rs",
+            &format!(
+                r#"
+                    fn main() {{
+                        assert_eq!(std::env::var("TARGET").unwrap(), "{}");
+                    }}
+                "#,
+                cross_compile::alternate()
+            ),
+        )
+        .file("cross/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo(&["per-package-target"])
+        .run();
+    assert!(p.bin("native").is_file());
+    assert!(p.target_bin(cross_compile::alternate(), "cross").is_file());
+    assert!(!p.bin("cross").is_file());
+
+    // An explicit `--target` takes precedence over the workspace targets.
+    p.cargo("build -v --target")
+        .arg(cross_compile::native())
+        .masquerade_as_nightly_cargo(&["per-package-target"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] failed to run custom build command for `cross v0.0.0[..]`")
+        .run();
+}
+
+#[cargo_test]
+fn workspace_build_targets_requires_nightly() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo"]
+
+                [workspace.build]
+                targets = ["host"]
+            "#,
+        )
+        .file("foo/Cargo.toml", &basic_manifest("foo", "0.0.0"))
+        .file("foo/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["per-package-target"])
+        .with_status(101)
+        .with_stderr_contains("[..]the `[workspace.build]` manifest table is unstable")
+        .run();
+}
+
 #[cargo_test]
 fn linker() {
     if cross_compile::disabled() {
@@ -0,0 +1,88 @@
+//! Tests for the `cargo daemon` command.
+
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("daemon")
+        .masquerade_as_nightly_cargo(&["cargo-daemon"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo daemon` command is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn requests() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("daemon -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-daemon"])
+        .with_stdin(
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "resolve"}
+{"jsonrpc": "2.0", "id": 2, "method": "metadata", "params": {"no_deps": true}}
+{"jsonrpc": "2.0", "method": "didChange"}
+{"jsonrpc": "2.0", "id": 3, "method": "build"}
+{"jsonrpc": "2.0", "id": 4, "method": "check", "params": {"package": "foo"}}
+not json
+{"jsonrpc": "2.0", "id": 5, "method": "shutdown"}
+{"jsonrpc": "2.0", "id": 6, "method": "resolve"}
+"#,
+        )
+        .with_json(
+            r#"
+            {
+              "jsonrpc": "2.0",
+              "id": 1,
+              "result": [
+                {
+                  "id": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+                  "dependencies": []
+                },
+                {
+                  "id": "path+file:[..]foo#0.1.0",
+                  "dependencies": [
+                    "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0"
+                  ]
+                }
+              ]
+            }
+
+            {"jsonrpc": "2.0", "id": 2, "result": "{...}"}
+
+            {
+              "jsonrpc": "2.0",
+              "id": 3,
+              "error": {"code": -32601, "message": "unknown method `build`"}
+            }
+
+            {"jsonrpc": "2.0", "id": 4, "result": null}
+
+            {"jsonrpc": "2.0", "id": null, "error": {"code": -32700, "message": "[..]"}}
+
+            {"jsonrpc": "2.0", "id": 5, "result": null}
+            "#,
+        )
+        .with_stderr_contains("[CHECKING] foo v0.1.0 ([CWD])")
+        .run();
+}
@@ -1,6 +1,6 @@
 //! Tests for ctrl-C handling.
 
-use cargo_test_support::{project, slow_cpu_multiplier};
+use cargo_test_support::{basic_manifest, project, slow_cpu_multiplier};
 use std::fs;
 use std::io::{self, Read};
 use std::net::TcpListener;
@@ -86,6 +86,87 @@ fn ctrl_c_kills_everyone() {
     );
 }
 
+#[cfg(unix)]
+#[cargo_test]
+fn graceful_interrupt() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [build-dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                use std::io::Read;
+                use std::net::TcpStream;
+
+                fn main() {
+                    if let Ok(addr) = std::env::var("BLOCK_ON") {
+                        let mut socket = TcpStream::connect(addr).unwrap();
+                        let _ = socket.read(&mut [0; 10]);
+                        panic!("that read should never return");
+                    }
+                }
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    let mut cargo = p
+        .cargo("check -Zgraceful-interrupt")
+        .masquerade_as_nightly_cargo(&["graceful-interrupt"])
+        .env("BLOCK_ON", addr.to_string())
+        .build_command();
+    cargo
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .env("__CARGO_TEST_SETSID_PLEASE_DONT_USE_ELSEWHERE", "1");
+    let mut child = cargo.spawn().unwrap();
+
+    // The build script runs once `bar` is built, and is interrupted along
+    // with Cargo, like in a terminal.
+    let _sock = listener.accept().unwrap().0;
+    ctrl_c(&mut child);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("note: build interrupted, not starting any new job"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("error: build was interrupted"), "{stderr}");
+    assert!(
+        !stderr.contains("failed to run custom build command"),
+        "{stderr}"
+    );
+
+    // `bar` was recorded as up to date, and the build script runs again.
+    p.cargo("check -Zgraceful-interrupt")
+        .masquerade_as_nightly_cargo(&["graceful-interrupt"])
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
 #[cfg(unix)]
 pub fn ctrl_c(child: &mut Child) {
     let r = unsafe { libc::kill(-(child.id() as i32), libc::SIGINT) };
@@ -0,0 +1,123 @@
+//! Tests for the `cargo dedupe` command.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, Project};
+
+/// `dep` accepts `bar 2.0.0` but is locked to `bar 1.0.0`, while `foo` itself
+/// depends on `bar 2.0.0`.
+fn make_project() -> Project {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("dep", "1.0.0").dep("bar", ">=1, <3").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    Package::new("bar", "2.0.0").publish();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            bar = "2.0"
+            dep = "1.0"
+        "#,
+    );
+    // Keeps `bar 1.0.0` locked for `dep`.
+    p.cargo("fetch").run();
+    let lockfile = p.read_lockfile();
+    assert!(lockfile.contains("\"bar 1.0.0\""));
+    assert!(lockfile.contains("\"bar 2.0.0\""));
+    p
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("dedupe")
+        .masquerade_as_nightly_cargo(&["cargo-dedupe"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo dedupe` command is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn dry_run() {
+    let p = make_project();
+    let lockfile = p.read_lockfile();
+
+    p.cargo("dedupe -Zunstable-options --dry-run")
+        .masquerade_as_nightly_cargo(&["cargo-dedupe"])
+        .with_stdout("cargo update -p bar@1.0.0 --precise 2.0.0")
+        .with_stderr_contains("[WARNING] not updating lockfile due to dry run")
+        .run();
+    assert_eq!(p.read_lockfile(), lockfile);
+}
+
+#[cargo_test]
+fn collapses_versions() {
+    let p = make_project();
+
+    p.cargo("dedupe -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-dedupe"])
+        .run();
+    let lockfile = p.read_lockfile();
+    assert_eq!(lockfile.matches("name = \"bar\"").count(), 1);
+    assert!(lockfile.contains("name = \"bar\"\nversion = \"2.0.0\""));
+
+    p.cargo("dedupe -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-dedupe"])
+        .with_stderr_contains("[NOTE] no duplicate versions can be collapsed into one")
+        .run();
+}
+
+#[cargo_test]
+fn incompatible_requirements() {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("bar", "2.0.0").publish();
+    Package::new("dep", "1.0.0").dep("bar", "1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "2.0"
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    let lockfile = p.read_lockfile();
+
+    p.cargo("dedupe -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-dedupe"])
+        .with_stderr_contains("[NOTE] no duplicate versions can be collapsed into one")
+        .run();
+    assert_eq!(p.read_lockfile(), lockfile);
+}
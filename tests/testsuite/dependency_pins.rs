@@ -0,0 +1,216 @@
+//! Tests for `[workspace.dependency-pins]`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+
+fn publish_sys() {
+    Package::new("sys", "0.9.92").publish();
+    Package::new("sys", "0.9.93").publish();
+    Package::new("wrapper", "1.0.0").dep("sys", "0.9").publish();
+}
+
+fn pinned_project(pin: &str) -> cargo_test_support::Project {
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    cargo-features = ["dependency-pins"]
+
+                    [workspace]
+                    members = ["app"]
+
+                    [workspace.dependency-pins]
+                    sys = "{pin}"
+                "#
+            ),
+        )
+        .file(
+            "app/Cargo.toml",
+            r#"
+                [package]
+                name = "app"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                wrapper = "1.0"
+            "#,
+        )
+        .file("app/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn requires_nightly() {
+    publish_sys();
+    let p = pinned_project("=0.9.92");
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr_contains("  feature `dependency-pins` is required")
+        .run();
+}
+
+#[cargo_test]
+fn pins_transitive_dependency() {
+    publish_sys();
+    let p = pinned_project("=0.9.92");
+
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo(&["dependency-pins"])
+        .run();
+    let lock = p.read_lockfile();
+    assert!(lock.contains("name = \"sys\"\nversion = \"0.9.92\""));
+}
+
+#[cargo_test]
+fn updates_locked_version_not_matching_pin() {
+    publish_sys();
+    let p = pinned_project(">=0.9");
+
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo(&["dependency-pins"])
+        .run();
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"sys\"\nversion = \"0.9.93\""));
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["dependency-pins"]
+
+            [workspace]
+            members = ["app"]
+
+            [workspace.dependency-pins]
+            sys = "=0.9.92"
+        "#,
+    );
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["dependency-pins"])
+        .with_stderr_contains("[DOWNGRADING] sys v0.9.93 -> v0.9.92")
+        .run();
+}
+
+#[cargo_test]
+fn no_matching_version() {
+    publish_sys();
+    let p = pinned_project("=0.8.0");
+
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo(&["dependency-pins"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] failed to select a version for the requirement `sys = \"^0.9\"`",
+        )
+        .with_stderr_contains(
+            "only versions matching `=0.8.0` are considered, \
+             as `sys` is pinned in `workspace.dependency-pins`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn path_dependency_not_pinned() {
+    publish_sys();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["dependency-pins"]
+
+                [workspace]
+                members = ["app"]
+
+                [workspace.dependency-pins]
+                sys = "=0.9.92"
+            "#,
+        )
+        .file(
+            "app/Cargo.toml",
+            r#"
+                [package]
+                name = "app"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                sys = { path = "../sys" }
+            "#,
+        )
+        .file("app/src/lib.rs", "")
+        .file("sys/Cargo.toml", &basic_manifest("sys", "1.0.0"))
+        .file("sys/src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo(&["dependency-pins"])
+        .with_stderr_contains(
+            "[WARNING] dependency pin `sys` in `workspace.dependency-pins` \
+             does not match any package in the dependency graph",
+        )
+        .run();
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"sys\"\nversion = \"1.0.0\""));
+}
+
+#[cargo_test]
+fn unused_pin() {
+    publish_sys();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["dependency-pins"]
+
+                [workspace]
+                members = ["app"]
+
+                [workspace.dependency-pins]
+                sys = "=0.9.92"
+            "#,
+        )
+        .file("app/Cargo.toml", &basic_manifest("app", "0.1.0"))
+        .file("app/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["dependency-pins"])
+        .with_stderr_contains(
+            "[WARNING] dependency pin `sys` in `workspace.dependency-pins` \
+             does not match any package in the dependency graph",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_requirement() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["dependency-pins"]
+
+                [workspace]
+                members = ["app"]
+
+                [workspace.dependency-pins]
+                sys = "not a version"
+            "#,
+        )
+        .file("app/Cargo.toml", &basic_manifest("app", "0.1.0"))
+        .file("app/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["dependency-pins"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] failed to parse the version requirement `not a version` for `sys` \
+             in `workspace.dependency-pins`",
+        )
+        .run();
+}
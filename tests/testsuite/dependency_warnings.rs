@@ -0,0 +1,95 @@
+//! Tests for `--show-dependency-warnings`.
+
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+fn make_project() -> cargo_test_support::Project {
+    Package::new("bar", "1.0.0")
+        .file("src/lib.rs", "pub fn f() { let x = 1; }")
+        .publish();
+    Package::new("baz", "1.0.0")
+        .file("src/lib.rs", "pub fn h() { let z = 1; }")
+        .publish();
+
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = make_project();
+
+    p.cargo("check --show-dependency-warnings bar")
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--show-dependency-warnings` flag is unstable[..]")
+        .run();
+}
+
+#[cargo_test]
+fn summary_of_selected_dependencies() {
+    let p = make_project();
+
+    p.cargo("check")
+        .with_stderr_does_not_contain("[..]unused variable[..]")
+        .run();
+
+    // Selecting a dependency rebuilds it without silencing its warnings.
+    p.cargo("check -Zunstable-options --show-dependency-warnings bar")
+        .masquerade_as_nightly_cargo(&["show-dependency-warnings"])
+        .with_stderr_contains(
+            "[NOTE] warnings of the dependencies selected with `--show-dependency-warnings`:",
+        )
+        .with_stderr_contains("[WARNING] unused variable: `x`")
+        .with_stderr_contains("[WARNING] `bar v1.0.0[..]` generated 1 warning")
+        .with_stderr_does_not_contain("[..]unused variable: `z`[..]")
+        .with_stderr_does_not_contain("[WARNING] `bar` (lib) generated [..]")
+        .run();
+
+    // The warnings are replayed when the dependency is fresh.
+    p.cargo("check -Zunstable-options --show-dependency-warnings bar")
+        .masquerade_as_nightly_cargo(&["show-dependency-warnings"])
+        .with_stderr_contains("[WARNING] unused variable: `x`")
+        .with_stderr_contains("[WARNING] `bar v1.0.0[..]` generated 1 warning")
+        .run();
+}
+
+#[cargo_test]
+fn invalid_spec() {
+    let p = make_project();
+
+    p.cargo("check -Zunstable-options --show-dependency-warnings bar@x")
+        .masquerade_as_nightly_cargo(&["show-dependency-warnings"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] invalid package ID spec `bar@x` in `--show-dependency-warnings`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn spec_matching_no_package() {
+    let p = make_project();
+
+    p.cargo("check -Zunstable-options --show-dependency-warnings bat")
+        .masquerade_as_nightly_cargo(&["show-dependency-warnings"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] failed to select the dependencies in `--show-dependency-warnings`",
+        )
+        .with_stderr_contains("[..]package ID specification `bat` did not match any packages[..]")
+        .run();
+}
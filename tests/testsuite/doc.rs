@@ -2779,3 +2779,58 @@ Caused by:
         .with_status(101)
         .run();
 }
+
+#[cargo_test]
+fn features_table() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [features]
+                default = ["std"]
+                # Not documentation.
+
+                ## Uses the standard library.
+                std = []
+                ## Makes it <fast>.
+                ##
+                ## Needs `std`.
+                fast = ["std"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("doc --features-table")
+        .masquerade_as_nightly_cargo(&["features-table"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--features-table` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+
+    p.cargo("doc -Zunstable-options --features-table")
+        .masquerade_as_nightly_cargo(&["features-table"])
+        .run();
+    let html = p.read_file("target/doc/foo/features.html");
+    assert!(html.contains(
+        "<tr id=\"feature-default\"><td><code>default</code></td>\
+         <td><a href=\"#feature-std\"><code>std</code></a></td><td></td></tr>"
+    ));
+    assert!(html.contains(
+        "<tr id=\"feature-fast\"><td><code>fast</code></td>\
+         <td><a href=\"#feature-std\"><code>std</code></a></td>\
+         <td><p>Makes it &lt;fast&gt;.</p><p>Needs `std`.</p></td></tr>"
+    ));
+    assert!(html.contains(
+        "<tr id=\"feature-std\"><td><code>std</code> (default)</td><td></td>\
+         <td><p>Uses the standard library.</p></td></tr>"
+    ));
+}
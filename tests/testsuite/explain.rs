@@ -0,0 +1,160 @@
+//! Tests for the `cargo explain` command.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("explain foo")
+        .masquerade_as_nightly_cargo(&["cargo-explain"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo explain` command is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn requirements() {
+    for version in ["1.0.0", "1.1.0", "1.2.0", "2.0.0"] {
+        Package::new("a", version)
+            .feature("default", &["std"])
+            .feature("std", &[])
+            .publish();
+    }
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["bar"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "~1.1.0"
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("explain -Zunstable-options a")
+        .masquerade_as_nightly_cargo(&["cargo-explain"])
+        .with_stdout(
+            "\
+a v1.1.0
+  locked: no, this version was newly selected
+  required by:
+    bar v0.1.0 ([ROOT]/foo/bar) requires `~1.1.0`, features: default [tightest]
+    foo v0.1.0 ([ROOT]/foo) requires `^1.0`, features: default
+  newer versions:
+    v2.0.0: excluded by the requirement of bar v0.1.0 ([ROOT]/foo/bar), foo v0.1.0 ([ROOT]/foo)
+    v1.2.0: excluded by the requirement of bar v0.1.0 ([ROOT]/foo/bar)
+  features:
+    default enables std
+    std
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn locked_and_yanked() {
+    Package::new("a", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = { version = "1.0", default-features = false }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    Package::new("a", "1.1.0").publish();
+    Package::new("a", "1.2.0").yanked(true).publish();
+
+    p.cargo("explain -Zunstable-options a@1.0.0 --format json")
+        .masquerade_as_nightly_cargo(&["cargo-explain"])
+        .with_json(
+            r#"
+{
+  "id": "registry+https://github.com/rust-lang/crates.io-index#a@1.0.0",
+  "name": "a",
+  "version": "1.0.0",
+  "locked": true,
+  "dependents": [
+    {
+      "id": "path+[..]#0.1.0",
+      "requirement": "^1.0",
+      "kind": "normal",
+      "optional": false,
+      "default_features": false,
+      "features": [],
+      "tightest": true
+    }
+  ],
+  "newer_versions": [
+    {
+      "version": "1.2.0",
+      "reason": { "kind": "yanked" }
+    },
+    {
+      "version": "1.1.0",
+      "reason": { "kind": "locked" }
+    }
+  ],
+  "features": []
+}
+"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn workspace_member() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("explain -Zunstable-options foo")
+        .masquerade_as_nightly_cargo(&["cargo-explain"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([ROOT]/foo)
+  locked: no, this version was newly selected
+  required by: nothing, it is a workspace member
+",
+        )
+        .run();
+}
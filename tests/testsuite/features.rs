@@ -1,7 +1,9 @@
 //! Tests for `[features]` table.
 
+use std::fs;
+
 use cargo_test_support::paths::CargoPathExt;
-use cargo_test_support::registry::{Dependency, Package};
+use cargo_test_support::registry::{registry_path, Dependency, Package, RegistryBuilder};
 use cargo_test_support::{basic_manifest, project};
 
 #[cargo_test]
@@ -2204,3 +2206,398 @@ fn invalid_feature_name_slash_error() {
         )
         .run();
 }
+
+#[cargo_test]
+fn feature_conflicts_requires_nightly() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [features]
+                tls-native = []
+                tls-rustls = []
+
+                [features.conflicts]
+                tls-native = ["tls-rustls"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr_contains("  feature `feature-conflicts` is required")
+        .run();
+}
+
+#[cargo_test]
+fn feature_conflicts_undefined_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["feature-conflicts"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [features]
+                tls-native = []
+
+                [features.conflicts]
+                tls-native = ["tls-rustls"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["feature-conflicts"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  feature `tls-rustls` in `[features.conflicts]` is not defined in `[features]`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn feature_conflicts_across_workspace() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+                resolver = "2"
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                net = { path = "../net", features = ["native"] }
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                net = { path = "../net", default-features = false }
+
+                [features]
+                rustls = ["net/tls-rustls"]
+                default = ["rustls"]
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file(
+            "net/Cargo.toml",
+            r#"
+                cargo-features = ["feature-conflicts"]
+
+                [package]
+                name = "net"
+                version = "0.1.0"
+                edition = "2015"
+
+                [features]
+                native = ["tls-native"]
+                tls-native = []
+                tls-rustls = []
+
+                [features.conflicts]
+                tls-native = ["tls-rustls"]
+            "#,
+        )
+        .file("net/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -p a")
+        .masquerade_as_nightly_cargo(&["feature-conflicts"])
+        .with_stderr(
+            "\
+[LOCKING] 3 packages to latest compatible versions
+[CHECKING] net v0.1.0 ([CWD]/net)
+[CHECKING] a v0.1.0 ([CWD]/a)
+[FINISHED] `dev` profile [..]
+",
+        )
+        .run();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["feature-conflicts"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] features `tls-native` and `tls-rustls` of package `net v0.1.0 ([CWD]/net)` are mutually exclusive, but both are enabled
+
+`tls-native` is enabled by:
+  a v0.1.0 ([CWD]/a)
+
+`tls-rustls` is enabled by:
+  b v0.1.0 ([CWD]/b)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn feature_profiles_requires_nightly() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [features]
+                small = []
+
+                [features.profiles]
+                minimal = ["small"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr_contains("  feature `feature-profiles` is required")
+        .run();
+}
+
+#[cargo_test]
+fn feature_profiles_undefined_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["feature-profiles"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [features]
+                small = []
+
+                [features.profiles]
+                minimal = ["tiny"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["feature-profiles"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  feature `tiny` of profile `minimal` in `[features.profiles]` is not defined in `[features]`
+",
+        )
+        .run();
+}
+
+fn feature_profiles_project(dep_features: &str) -> cargo_test_support::Project {
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    cargo-features = ["feature-profiles"]
+
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+                    edition = "2015"
+
+                    [dependencies]
+                    dep = {{ path = "dep", {dep_features} }}
+                "#
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "dep/Cargo.toml",
+            r#"
+                cargo-features = ["feature-profiles"]
+
+                [package]
+                name = "dep"
+                version = "0.1.0"
+                edition = "2015"
+
+                [features]
+                default = ["full"]
+                full = ["small"]
+                small = []
+
+                [features.profiles]
+                minimal = ["small"]
+            "#,
+        )
+        .file(
+            "dep/src/lib.rs",
+            r#"
+                #[cfg(feature = "full")]
+                compile_error!("default features should not be enabled");
+                #[cfg(not(feature = "small"))]
+                compile_error!("the `minimal` profile should be enabled");
+            "#,
+        )
+        .build()
+}
+
+#[cargo_test]
+fn feature_profiles_replace_default_features() {
+    let p = feature_profiles_project(r#"features-profile = "minimal""#);
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["feature-profiles"])
+        .with_stderr(
+            "\
+[LOCKING] 2 packages to latest compatible versions
+[CHECKING] dep v0.1.0 ([CWD]/dep)
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] `dev` profile [..]
+",
+        )
+        .run();
+
+    p.cargo("tree -e features")
+        .masquerade_as_nightly_cargo(&["feature-profiles"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([CWD])
+└── dep feature \"small\"
+    └── dep v0.1.0 ([CWD]/dep)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn feature_profiles_with_default_features() {
+    let p = feature_profiles_project(r#"features-profile = "minimal", default-features = true"#);
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["feature-profiles"])
+        .with_status(101)
+        .with_stderr_contains(
+            "  dependency (dep) cannot set both `features-profile` and `default-features = true`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn feature_profiles_missing_profile() {
+    let p = feature_profiles_project(r#"features-profile = "huge""#);
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["feature-profiles"])
+        .with_status(101)
+        .with_stderr_contains(
+            "the package `foo` depends on `dep`, with features profile: `huge` but `dep` \
+             does not have this profile in `[features.profiles]`.",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn feature_profiles_from_registry() {
+    let registry = RegistryBuilder::new().http_api().http_index().build();
+
+    let dep = project()
+        .at("dep")
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["feature-profiles"]
+
+                [package]
+                name = "dep"
+                version = "0.1.0"
+                edition = "2015"
+                license = "MIT"
+                description = "dep"
+
+                [features]
+                default = ["full"]
+                full = ["small"]
+                small = []
+
+                [features.profiles]
+                minimal = ["small"]
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                #[cfg(feature = "full")]
+                compile_error!("default features should not be enabled");
+            "#,
+        )
+        .build();
+    dep.cargo("publish --no-verify")
+        .masquerade_as_nightly_cargo(&["feature-profiles"])
+        .replace_crates_io(registry.index_url())
+        .run();
+
+    // Like `features2`, the entry uses a newer schema version, so older
+    // versions of cargo ignore it.
+    let line = fs::read_to_string(registry_path().join("3/d/dep")).unwrap();
+    assert!(line.contains(r#""v":4"#), "{line}");
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["feature-profiles"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                dep = { version = "0.1.0", features-profile = "minimal" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["feature-profiles"])
+        .replace_crates_io(registry.index_url())
+        .with_stderr_contains("[CHECKING] dep v0.1.0")
+        .run();
+}
@@ -1303,7 +1303,7 @@ fn resolver_bad_setting() {
 error: failed to parse manifest at `[..]/foo/Cargo.toml`
 
 Caused by:
-  `resolver` setting `foo` is not valid, valid options are \"1\" or \"2\"
+  `resolver` setting `foo` is not valid, valid options are \"1\", \"2\", \"3\" or \"msrv\"
 ",
         )
         .run();
@@ -0,0 +1,192 @@
+//! Tests for the `cargo features diff` command.
+
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+fn make_project() -> cargo_test_support::Project {
+    Package::new("common", "1.0.0")
+        .feature("f1", &[])
+        .feature("f2", &[])
+        .feature("f3", &[])
+        .publish();
+
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+                resolver = "2"
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                common = { version = "1.0", features = ["f1"] }
+
+                [features]
+                extra = ["common/f3"]
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                common = { version = "1.0", features = ["f2"] }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("features diff --before=-p --after=--workspace")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo features diff` command is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn workspace_unification() {
+    let p = make_project();
+
+    p.cargo("features diff -Zunstable-options")
+        .arg("--before=-p a")
+        .arg("--after=--workspace")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+common v1.0.0
+  + f2
+      enabled by the dependency declaration of `b v0.1.0 ([..])`
+",
+        )
+        .run();
+
+    p.cargo("features diff -Zunstable-options")
+        .arg("--before=--workspace")
+        .arg("--after=-p a")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+common v1.0.0
+  - f2
+      enabled by the dependency declaration of `b v0.1.0 ([..])`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn no_difference() {
+    let p = make_project();
+
+    p.cargo("features diff -Zunstable-options")
+        .arg("--before=-p a")
+        .arg("--after=-p a --no-default-features")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("")
+        .with_stderr_contains("[NOTE] both invocations enable the same features")
+        .run();
+}
+
+#[cargo_test]
+fn json_format() {
+    let p = make_project();
+
+    p.cargo("features diff -Zunstable-options --format json")
+        .arg("--before=-p a")
+        .arg("--after=-p a -F extra")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+                {
+                  "packages": [
+                    {
+                      "id": "path+file://[..]/a#0.1.0",
+                      "for_host": false,
+                      "added": [
+                        {
+                          "feature": "extra",
+                          "reasons": [{ "kind": "command-line" }]
+                        }
+                      ],
+                      "removed": []
+                    },
+                    {
+                      "id": "registry+https://github.com/rust-lang/crates.io-index#common@1.0.0",
+                      "for_host": false,
+                      "added": [
+                        {
+                          "feature": "f3",
+                          "reasons": [
+                            {
+                              "kind": "dependent-feature",
+                              "package": "a v0.1.0 ([..])",
+                              "feature": "extra"
+                            }
+                          ]
+                        }
+                      ],
+                      "removed": []
+                    }
+                  ]
+                }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_flags() {
+    let p = make_project();
+
+    p.cargo("features diff -Zunstable-options")
+        .arg("--before=-p a")
+        .arg("--after=--release")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] invalid flags `--release` for `--after`")
+        .run();
+}
+
+#[cargo_test]
+fn quoted_flags() {
+    let p = make_project();
+
+    p.cargo("features diff -Zunstable-options")
+        .arg("--before=-p a")
+        .arg(r#"--after=-p a --features "extra common/f2""#)
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_contains("  + f2")
+        .with_stdout_contains("  + f3")
+        .run();
+
+    p.cargo("features diff -Zunstable-options")
+        .arg("--before=-p a")
+        .arg(r#"--after=-p a --features "extra"#)
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains(r#"[ERROR] invalid flags `-p a --features "extra` for `--after`"#)
+        .with_stderr_contains("  unterminated double quote")
+        .run();
+}
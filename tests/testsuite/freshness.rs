@@ -2939,3 +2939,240 @@ fn use_mtime_cache_in_cargo_home() {
         )
         .run_expect_error();
 }
+
+#[cargo_test]
+fn fingerprint_index() {
+    let p = project().file("src/main.rs", "fn main() {}").build();
+
+    p.cargo("build -Zfingerprint-index")
+        .masquerade_as_nightly_cargo(&["fingerprint-index"])
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    let index = p.root().join("target/debug/.fingerprint/index");
+    let contents = fs::read_to_string(&index).unwrap();
+    let line = contents
+        .lines()
+        .find(|line| line.contains("bin-foo "))
+        .expect("index should record the bin fingerprint");
+    let (key, hash) = line.rsplit_once(' ').unwrap();
+    assert_eq!(hash.len(), 16);
+
+    // The hash comes from the index, not from the individual hash file.
+    let hash_file = p.root().join("target/debug/.fingerprint").join(key);
+    assert_eq!(fs::read_to_string(&hash_file).unwrap(), hash);
+    fs::remove_file(&hash_file).unwrap();
+    p.cargo("build -Zfingerprint-index -v")
+        .masquerade_as_nightly_cargo(&["fingerprint-index"])
+        .with_stderr(
+            "\
+[FRESH] foo v0.0.1 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    // A changed fingerprint still rebuilds.
+    p.cargo("build -Zfingerprint-index -v")
+        .masquerade_as_nightly_cargo(&["fingerprint-index"])
+        .env("RUSTFLAGS", "-C debug-assertions")
+        .with_stderr(
+            "\
+[DIRTY] foo v0.0.1 ([CWD]): the rustflags changed
+[COMPILING] foo v0.0.1 ([CWD])
+[RUNNING] `rustc [..]
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+    assert!(fs::read_to_string(&hash_file).is_ok());
+
+    // Building without the index drops it, as it would go stale.
+    p.cargo("build").with_stderr("[FINISHED] [..]").run();
+    assert!(!index.exists());
+}
+
+#[cargo_test]
+fn fingerprint_index_clean_package() {
+    let p = project().file("src/main.rs", "fn main() {}").build();
+
+    p.cargo("build -Zfingerprint-index")
+        .masquerade_as_nightly_cargo(&["fingerprint-index"])
+        .run();
+    let index = p.root().join("target/debug/.fingerprint/index");
+    assert!(index.exists());
+
+    p.cargo("clean -p foo").run();
+    assert!(!index.exists());
+
+    p.cargo("build -Zfingerprint-index")
+        .masquerade_as_nightly_cargo(&["fingerprint-index"])
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn fingerprint_hash_ignores_mtime_changes() {
+    let p = project()
+        .file("src/lib.rs", "pub fn foo() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                fingerprint = "hash"
+            "#,
+        )
+        .build();
+    p.root().move_into_the_past();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    // Touching the file, like a fresh checkout would, doesn't rebuild.
+    let lib = p.root().join("src/lib.rs");
+    filetime::set_file_mtime(&lib, FileTime::now()).unwrap();
+    p.cargo("build -Zunstable-options -v")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr(
+            "\
+[FRESH] foo v0.0.1 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    // Without hashes, the touched file is newer than the last build.
+    p.cargo("build -v")
+        .with_stderr(
+            "\
+[WARNING] `build.fingerprint` requires `-Zunstable-options`, ignoring it
+[DIRTY] foo v0.0.1 ([CWD]): the file `src/lib.rs` has changed ([..])
+[COMPILING] foo v0.0.1 ([CWD])
+[RUNNING] `rustc [..]
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn fingerprint_hash_detects_changed_contents() {
+    let p = project()
+        .file("src/lib.rs", "pub fn foo() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                fingerprint = "hash"
+            "#,
+        )
+        .build();
+    p.root().move_into_the_past();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .run();
+
+    // The edit is detected even though the mtime is set back to before the
+    // last build.
+    let lib = p.root().join("src/lib.rs");
+    let mtime = FileTime::from_last_modification_time(&fs::metadata(&lib).unwrap());
+    p.change_file("src/lib.rs", "pub fn foo() {} pub fn bar() {}");
+    filetime::set_file_mtime(&lib, mtime).unwrap();
+    p.cargo("build -Zunstable-options -v")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr(
+            "\
+[DIRTY] foo v0.0.1 ([CWD]): the file `src/lib.rs` has changed (its contents differ from last build)
+[COMPILING] foo v0.0.1 ([CWD])
+[RUNNING] `rustc [..]
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    // The config is ignored without `-Zunstable-options`.
+    p.cargo("build")
+        .with_stderr(
+            "\
+[WARNING] `build.fingerprint` requires `-Zunstable-options`, ignoring it
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn explain_rebuild() {
+    let p = project()
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::rerun-if-env-changed=FOO");
+                }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build --explain-rebuild")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--explain-rebuild` flag is unstable[..]")
+        .run();
+
+    // Units built for the first time are not explained.
+    p.cargo("build -Zunstable-options --explain-rebuild")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_does_not_contain("[DIRTY] [..]")
+        .run();
+
+    p.cargo("build -Zunstable-options --explain-rebuild")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .env("FOO", "1")
+        .with_stderr_contains(
+            "\
+[DIRTY] foo v0.0.1 ([CWD]): the env variable FOO changed
+[NOTE] was unset, now `1`
+",
+        )
+        .with_stderr_contains("[DIRTY] foo v0.0.1 ([CWD]): [..]dependency[..]")
+        .run();
+
+    p.cargo("build -Zunstable-options --explain-rebuild --message-format json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .env("FOO", "2")
+        .with_json_contains_unordered(
+            r#"
+            {
+              "reason": "rebuild-reason",
+              "package_id": "path+file:///[..]/foo#0.0.1",
+              "target": "{...}",
+              "mode": "run-custom-build",
+              "component": "env",
+              "name": "FOO",
+              "old": "1",
+              "new": "2"
+            }
+            "#,
+        )
+        .run();
+}
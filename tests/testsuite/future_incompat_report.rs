@@ -315,6 +315,47 @@ Available IDs are: 1
         .run();
 }
 
+#[cargo_test(
+    nightly,
+    reason = "-Zfuture-incompat-test requires nightly (permanently)"
+)]
+fn pruned_reports() {
+    let p = simple_project();
+
+    p.cargo("check")
+        .env("RUSTFLAGS", "-Zfuture-incompat-test")
+        .masquerade_as_nightly_cargo(&["future-incompat-test"])
+        .run();
+
+    p.cargo("report prune -Zunstable-options --keep 0")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr("[REMOVED] 0 build summaries, 1 future-incompat report, 0 timing reports")
+        .run();
+    p.cargo("report prune -Zunstable-options --keep 0")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr("[REMOVED] 0 build summaries, 0 future-incompat reports, 0 timing reports")
+        .run();
+    p.cargo("report future-incompatibilities")
+        .with_status(101)
+        .with_stderr("error: no reports are currently available")
+        .run();
+
+    // The next report doesn't reuse the ID of a pruned one.
+    p.cargo("check")
+        .env("RUSTFLAGS", "-Zfuture-incompat-test")
+        .masquerade_as_nightly_cargo(&["future-incompat-test"])
+        .run();
+    p.cargo("report future-incompatibilities --id 1")
+        .with_status(101)
+        .with_stderr(
+            "\
+error: could not find report with ID 1
+Available IDs are: 2
+",
+        )
+        .run();
+}
+
 #[cargo_test(
     nightly,
     reason = "-Zfuture-incompat-test requires nightly (permanently)"
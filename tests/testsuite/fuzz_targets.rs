@@ -0,0 +1,161 @@
+//! Tests for `[[fuzz]]` targets, `cargo fuzz-build` and `cargo test --fuzz-smoke`.
+
+use cargo_test_support::{project, rustc_host, Project};
+
+const MANIFEST: &str = r#"
+    cargo-features = ["fuzz-targets"]
+
+    [package]
+    name = "foo"
+    version = "0.1.0"
+    edition = "2021"
+
+    [[fuzz]]
+    name = "parse"
+"#;
+
+/// A fuzz target which links without libFuzzer: the instrumentation symbols
+/// are stubbed when built with `--cfg fuzzing`.
+const FUZZ_TARGET: &str = r#"
+    #[cfg(fuzzing)]
+    mod stubs {
+        #[no_mangle]
+        pub extern "C" fn __sanitizer_cov_8bit_counters_init(_: *const u8, _: *const u8) {}
+        #[no_mangle]
+        pub extern "C" fn __sanitizer_cov_pcs_init(_: *const usize, _: *const usize) {}
+    }
+
+    fn main() {
+        let arg = std::env::args().nth(1).unwrap_or_default();
+        println!("fuzzing with `{arg}`");
+        foo::parse(&arg);
+    }
+"#;
+
+fn make_project() -> Project {
+    project()
+        .file("Cargo.toml", MANIFEST)
+        .file("src/lib.rs", "pub fn parse(_: &str) {}")
+        .file("fuzz/parse.rs", FUZZ_TARGET)
+        .build()
+}
+
+#[cargo_test]
+fn requires_nightly() {
+    let p = make_project();
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr_contains("  feature `fuzz-targets` is required")
+        .run();
+}
+
+#[cargo_test]
+fn fuzz_build_requires_unstable_options() {
+    let p = make_project();
+
+    p.cargo("fuzz-build")
+        .masquerade_as_nightly_cargo(&["fuzz-targets"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `cargo fuzz-build` command is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn not_built_by_default() {
+    let p = make_project();
+
+    p.cargo("build --all-targets")
+        .masquerade_as_nightly_cargo(&["fuzz-targets"])
+        .with_stderr_does_not_contain("[..]--crate-name parse[..]")
+        .run();
+    assert!(!p.bin("parse").exists());
+}
+
+#[cargo_test]
+fn fuzz_build() {
+    let p = make_project();
+
+    p.cargo("fuzz-build -Zunstable-options -v")
+        .masquerade_as_nightly_cargo(&["fuzz-targets"])
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo [..]-Cpasses=sancov-module[..]--cfg fuzzing[..]`",
+        )
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name parse [..]-Cpasses=sancov-module[..]--cfg fuzzing[..]`",
+        )
+        .with_stderr_contains("[FINISHED] `fuzz` profile [optimized] target(s) in [..]")
+        .run();
+
+    let bin = p
+        .root()
+        .join("target")
+        .join(rustc_host())
+        .join("fuzz")
+        .join(format!("parse{}", std::env::consts::EXE_SUFFIX));
+    assert!(bin.is_file());
+}
+
+#[cargo_test]
+fn fuzz_build_unknown_target() {
+    let p = make_project();
+
+    p.cargo("fuzz-build -Zunstable-options --fuzz pars")
+        .masquerade_as_nightly_cargo(&["fuzz-targets"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] no fuzz target named `pars`
+
+<tab>Did you mean `parse`?",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn missing_path() {
+    let p = project()
+        .file("Cargo.toml", MANIFEST)
+        .file("src/lib.rs", "pub fn parse(_: &str) {}")
+        .file("fuzz/fuzz_targets/parse.rs", FUZZ_TARGET)
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["fuzz-targets"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+  can't find `parse` fuzz at default paths, but found a file at `fuzz/fuzz_targets/parse.rs`.
+  Perhaps rename the file to `fuzz/parse.rs` for target auto-discovery, or specify fuzz.path if you want to use a non-default path.",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn test_fuzz_smoke() {
+    let p = make_project();
+
+    p.cargo("test -Zunstable-options --fuzz-smoke")
+        .masquerade_as_nightly_cargo(&["fuzz-targets"])
+        .with_stderr_contains("[RUNNING] fuzz fuzz/parse.rs (target/debug/parse[EXE])")
+        .with_stdout_contains("fuzzing with `-runs=1`")
+        .run();
+}
+
+#[cargo_test]
+fn test_fuzz_smoke_failure() {
+    let p = project()
+        .file("Cargo.toml", MANIFEST)
+        .file("src/lib.rs", "")
+        .file("fuzz/parse.rs", "fn main() { std::process::exit(3) }")
+        .build();
+
+    p.cargo("test -Zunstable-options --fuzz-smoke")
+        .masquerade_as_nightly_cargo(&["fuzz-targets"])
+        .with_status(3)
+        .with_stderr_contains("[ERROR] fuzz smoke test failed, to rerun pass `--fuzz-smoke`")
+        .run();
+}
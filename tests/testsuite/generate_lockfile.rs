@@ -1,7 +1,7 @@
 //! Tests for the `cargo generate-lockfile` command.
 
 use cargo_test_support::registry::{Package, RegistryBuilder};
-use cargo_test_support::{basic_manifest, paths, project, ProjectBuilder};
+use cargo_test_support::{basic_manifest, paths, project, rustc_host, ProjectBuilder};
 use std::fs;
 
 #[cargo_test]
@@ -273,3 +273,157 @@ fn generate_lockfile_holds_lock_and_offline() {
         )
         .run();
 }
+
+fn filter_platform_project() -> cargo_test_support::Project {
+    Package::new("normal-dep", "0.0.1").publish();
+    Package::new("host-dep", "0.0.1").publish();
+    Package::new("alt-dep", "0.0.1").publish();
+    Package::new("cfg-dep", "0.0.1").publish();
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+                    edition = "2015"
+
+                    [dependencies]
+                    normal-dep = "0.0.1"
+
+                    [target.{}.dependencies]
+                    host-dep = "0.0.1"
+
+                    [target.wasm32-unknown-unknown.dependencies]
+                    alt-dep = "0.0.1"
+
+                    [target.'cfg(foobar)'.dependencies]
+                    cfg-dep = "0.0.1"
+                "#,
+                rustc_host()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn filter_platform_requires_unstable_options() {
+    let p = filter_platform_project();
+
+    p.cargo("generate-lockfile --filter-platform wasm32-unknown-unknown")
+        .masquerade_as_nightly_cargo(&["filter-platform"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--filter-platform` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn filter_platform() {
+    let p = filter_platform_project();
+
+    p.cargo("generate-lockfile -Zunstable-options --filter-platform wasm32-unknown-unknown")
+        .masquerade_as_nightly_cargo(&["filter-platform"])
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest compatible versions
+",
+        )
+        .run();
+    let lock = p.read_lockfile();
+    assert!(lock.contains("name = \"normal-dep\""));
+    assert!(lock.contains("name = \"alt-dep\""));
+    assert!(!lock.contains("name = \"host-dep\""));
+    assert!(!lock.contains("name = \"cfg-dep\""));
+    assert!(lock.contains("[metadata]\nfilter-platforms = \"wasm32-unknown-unknown\"\n"));
+
+    // Both platforms.
+    p.cargo("generate-lockfile -Zunstable-options --filter-platform wasm32-unknown-unknown")
+        .arg("--filter-platform")
+        .arg(rustc_host())
+        .masquerade_as_nightly_cargo(&["filter-platform"])
+        .run();
+    let lock = p.read_lockfile();
+    assert!(lock.contains("name = \"alt-dep\""));
+    assert!(lock.contains("name = \"host-dep\""));
+    assert!(!lock.contains("name = \"cfg-dep\""));
+
+    // Without the flag, every platform is locked again.
+    p.cargo("generate-lockfile").run();
+    let lock = p.read_lockfile();
+    assert!(lock.contains("name = \"cfg-dep\""));
+    assert!(!lock.contains("filter-platforms"));
+}
+
+#[cargo_test]
+fn filter_platform_is_kept_by_later_resolves() {
+    let p = filter_platform_project();
+
+    p.cargo("generate-lockfile -Zunstable-options --filter-platform wasm32-unknown-unknown")
+        .masquerade_as_nightly_cargo(&["filter-platform"])
+        .run();
+    let lock = p.read_lockfile();
+
+    p.cargo("update -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["filter-platform"])
+        .run();
+    assert_eq!(p.read_lockfile(), lock);
+
+    p.cargo("tree --locked -Zunstable-options --target wasm32-unknown-unknown")
+        .masquerade_as_nightly_cargo(&["filter-platform"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([ROOT]/foo)
+├── alt-dep v0.0.1
+└── normal-dep v0.0.1
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn filter_platform_is_ignored_without_unstable_options() {
+    let p = filter_platform_project();
+
+    p.cargo("generate-lockfile -Zunstable-options --filter-platform wasm32-unknown-unknown")
+        .masquerade_as_nightly_cargo(&["filter-platform"])
+        .run();
+
+    p.cargo("update")
+        .with_stderr_contains(
+            "[WARNING] `filter-platforms` in the `[metadata]` table of `Cargo.lock` \
+             requires `-Zunstable-options`, ignoring it",
+        )
+        .run();
+    let lock = p.read_lockfile();
+    assert!(lock.contains("name = \"host-dep\""));
+    assert!(lock.contains("name = \"cfg-dep\""));
+    assert!(!lock.contains("filter-platforms"));
+}
+
+#[cargo_test]
+fn filter_platform_rejects_other_targets() {
+    let p = filter_platform_project();
+
+    p.cargo("generate-lockfile -Zunstable-options --filter-platform wasm32-unknown-unknown")
+        .masquerade_as_nightly_cargo(&["filter-platform"])
+        .run();
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["filter-platform"])
+        .with_status(101)
+        .with_stderr(&format!(
+            "\
+[ERROR] `Cargo.lock` only locks the dependencies used on `wasm32-unknown-unknown`, but the build is for `{host}`
+help: run `cargo generate-lockfile -Zunstable-options --filter-platform {host}` with the other platforms, or without `--filter-platform` to lock every platform
+",
+            host = rustc_host()
+        ))
+        .run();
+}
@@ -430,3 +430,71 @@ Caused by:
 
     t.join().ok().unwrap();
 }
+
+// Tests that HTTP auth is offered from the credential provider of the host,
+// before `credential.helper`.
+#[cargo_test]
+fn http_auth_offered_from_credential_provider() {
+    if cargo_uses_gitoxide() {
+        // Credential providers are only used by the libgit2 fetch.
+        return;
+    }
+    let (addr, t, connections) = setup_failed_auth_test();
+    let provider = project()
+        .at("provider")
+        .file("Cargo.toml", &basic_manifest("provider", "0.1.0"))
+        .file("src/main.rs", r#"fn main() { println!("bar"); }"#)
+        .build();
+    provider.cargo("build").run();
+    let provider = provider.bin("provider");
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+                    edition = "2015"
+
+                    [dependencies.bar]
+                    git = "http://127.0.0.1:{}/foo/bar"
+                "#,
+                addr.port()
+            ),
+        )
+        .file("src/main.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [net]
+                    retry = 0
+
+                    [net.git-credentials."127.0.0.1"]
+                    username = "foo"
+                    credential-provider = ["cargo:token-from-stdout", '{}']
+                "#,
+                provider.display()
+            ),
+        )
+        .build();
+
+    p.cargo("check -Zgit-credentials")
+        .masquerade_as_nightly_cargo(&["git-credentials"])
+        .with_status(101)
+        .with_stderr_contains(
+            "  * attempted to find username/password via credential provider \
+             `cargo:token-from-stdout [..]provider[EXE]`, but maybe the found credentials were incorrect",
+        )
+        .with_stderr_contains(
+            "  * attempted to find username/password via `credential.helper`, \
+             but maybe the found credentials were incorrect",
+        )
+        .run();
+
+    // The first attempt was with the credentials of the provider.
+    assert!(connections.load(SeqCst) >= 2);
+    t.join().ok().unwrap();
+}
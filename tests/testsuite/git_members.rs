@@ -0,0 +1,112 @@
+//! Tests for workspace members checked out from `git+` URLs.
+
+use cargo_test_support::{basic_lib_manifest, git, project};
+
+fn manifest(member: &str) -> String {
+    format!(
+        r#"
+            cargo-features = ["git-members"]
+
+            [workspace]
+            members = ["app", "{member}"]
+        "#
+    )
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["git+https://example.com/lib?rev=0000000"]
+            "#,
+        )
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["git-members"])
+        .with_status(101)
+        .with_stderr_contains("  feature `git-members` is required")
+        .run();
+}
+
+#[cargo_test]
+fn build_and_test_git_member() {
+    let lib = git::new("lib", |project| {
+        project.file("Cargo.toml", &basic_lib_manifest("lib")).file(
+            "src/lib.rs",
+            r#"
+                pub fn answer() -> u32 { 42 }
+
+                #[test]
+                fn it_works() { assert_eq!(answer(), 42); }
+            "#,
+        )
+    });
+    let repo = git2::Repository::open(&lib.root()).unwrap();
+    let rev = repo.head().unwrap().target().unwrap();
+    let url = format!("git+{}?rev={rev}", lib.url());
+
+    let p = project()
+        .file("Cargo.toml", &manifest(&url))
+        .file("app/Cargo.toml", &basic_lib_manifest("app"))
+        .file("app/src/lib.rs", "")
+        .build();
+
+    p.cargo("build --workspace")
+        .masquerade_as_nightly_cargo(&["git-members"])
+        .with_stderr_contains("[UPDATING] git repository `[..]`")
+        .with_stderr_contains("[COMPILING] lib v0.5.0 ([CWD]/target/git-members/lib-[..])")
+        .with_stderr_contains("[COMPILING] app v0.5.0 ([CWD]/app)")
+        .run();
+
+    p.cargo("test -p lib")
+        .masquerade_as_nightly_cargo(&["git-members"])
+        .with_stdout_contains("test it_works ... ok")
+        .run();
+
+    // It's published from its own repository.
+    p.cargo("package --workspace --no-verify")
+        .masquerade_as_nightly_cargo(&["git-members"])
+        .with_stderr_contains("[PACKAGING] app v0.5.0 ([CWD]/app)")
+        .with_stderr_does_not_contain("[PACKAGING] lib [..]")
+        .run();
+    p.cargo("publish -p lib --dry-run")
+        .masquerade_as_nightly_cargo(&["git-members"])
+        .with_status(101)
+        .with_stderr_contains(&format!(
+            "[ERROR] `lib` can't be published, it's a workspace member checked out from `{url}`"
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn unpinned() {
+    let lib = git::new("lib", |project| {
+        project
+            .file("Cargo.toml", &basic_lib_manifest("lib"))
+            .file("src/lib.rs", "")
+    });
+    let p = project()
+        .file("Cargo.toml", &manifest(&format!("git+{}", lib.url())))
+        .file("app/Cargo.toml", &basic_lib_manifest("app"))
+        .file("app/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["git-members"])
+        .with_status(101)
+        .with_stderr(&format!(
+            "\
+[ERROR] failed to check out workspace member `git+{}`
+referenced by workspace at `[CWD]/Cargo.toml`
+
+Caused by:
+  the revision of the member must be pinned with a `rev` parameter
+",
+            lib.url()
+        ))
+        .run();
+}
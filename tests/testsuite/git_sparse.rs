@@ -0,0 +1,267 @@
+//! Tests for `-Zgit=sparse-checkout`.
+
+use std::path::{Path, PathBuf};
+
+use cargo_test_support::{basic_manifest, git, paths, project, t, Project};
+
+fn monorepo() -> Project {
+    git::new("monorepo", |project| {
+        project
+            .file(
+                "Cargo.toml",
+                r#"
+                    [workspace]
+                    members = ["crates/*"]
+
+                    [workspace.dependencies]
+                    bar = { path = "crates/bar" }
+                "#,
+            )
+            .file("README.md", "")
+            .file(
+                "crates/foo/Cargo.toml",
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+                    edition = "2015"
+
+                    [dependencies]
+                    bar.workspace = true
+
+                    [dev-dependencies]
+                    baz = { path = "../baz" }
+                "#,
+            )
+            .file(
+                "crates/foo/src/lib.rs",
+                "extern crate bar; pub use bar::bar;",
+            )
+            .file("crates/bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+            .file("crates/bar/src/lib.rs", "pub fn bar() {}")
+            .file("crates/baz/Cargo.toml", &basic_manifest("baz", "0.1.0"))
+            .file("crates/baz/src/lib.rs", "")
+            .file("crates/qux/Cargo.toml", &basic_manifest("qux", "0.1.0"))
+            .file("crates/qux/src/lib.rs", "pub fn qux() {}")
+    })
+}
+
+fn manifest(repo: &Project, deps: &[&str]) -> String {
+    let deps = deps
+        .iter()
+        .map(|dep| format!("{dep} = {{ git = '{}' }}\n", repo.url()))
+        .collect::<String>();
+    format!(
+        r#"
+            [package]
+            name = "app"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            {deps}
+        "#
+    )
+}
+
+/// The checkouts of the git dependencies.
+fn checkouts() -> Vec<PathBuf> {
+    let checkouts = t!(glob::glob(
+        paths::home()
+            .join(".cargo/git/checkouts/monorepo-*/*")
+            .to_str()
+            .unwrap()
+    ));
+    checkouts.map(|path| path.unwrap()).collect()
+}
+
+#[cargo_test]
+fn checks_out_packages_in_use() {
+    let repo = monorepo();
+    let p = project()
+        .file("Cargo.toml", &manifest(&repo, &["foo"]))
+        .file("src/main.rs", "extern crate foo; fn main() { foo::bar() }")
+        .build();
+
+    p.cargo("build -Zgit=sparse-checkout")
+        .masquerade_as_nightly_cargo(&["git"])
+        .run();
+
+    let dirs = checkouts();
+    assert_eq!(dirs.len(), 1);
+    let checkout = &dirs[0];
+    assert!(checkout.to_str().unwrap().ends_with("-sparse"));
+    assert!(checkout.join("Cargo.toml").is_file());
+    assert!(checkout.join("crates/foo/src/lib.rs").is_file());
+    assert!(checkout.join("crates/bar/src/lib.rs").is_file());
+    assert!(!checkout.join("crates/baz").exists());
+    assert!(!checkout.join("crates/qux").exists());
+    // Files which don't belong to any package come along.
+    assert!(checkout.join("README.md").is_file());
+
+    // Depending on another package of the repository checks it out as well.
+    p.change_file("Cargo.toml", &manifest(&repo, &["foo", "qux"]));
+    p.change_file(
+        "src/main.rs",
+        "extern crate foo; extern crate qux; fn main() { foo::bar(); qux::qux() }",
+    );
+    p.cargo("build -Zgit=sparse-checkout")
+        .masquerade_as_nightly_cargo(&["git"])
+        .run();
+
+    assert_eq!(checkouts(), dirs);
+    assert!(checkout.join("crates/qux/src/lib.rs").is_file());
+    assert!(checkout.join("crates/foo/src/lib.rs").is_file());
+    assert!(!checkout.join("crates/baz").exists());
+}
+
+#[cargo_test]
+fn complete_checkout_without_flag() {
+    let repo = monorepo();
+    let p = project()
+        .file("Cargo.toml", &manifest(&repo, &["foo"]))
+        .file("src/main.rs", "extern crate foo; fn main() { foo::bar() }")
+        .build();
+
+    p.cargo("build").run();
+
+    let dirs = checkouts();
+    assert_eq!(dirs.len(), 1);
+    assert!(!dirs[0].to_str().unwrap().ends_with("-sparse"));
+    assert!(dirs[0].join("crates/qux/src/lib.rs").is_file());
+    assert!(dirs[0].join("README.md").is_file());
+}
+
+#[cargo_test]
+fn checks_out_paths_referred_to() {
+    let repo = git::new("monorepo", |project| {
+        project
+            .file(
+                "Cargo.toml",
+                r#"
+                    [workspace]
+                    members = ["crates/*"]
+                "#,
+            )
+            .file("shared/greeting.txt", "hello")
+            .file(
+                "crates/foo/Cargo.toml",
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+                    edition = "2015"
+                    build = "../qux/build.rs"
+
+                    [lib]
+                    path = "../qux/foo.rs"
+                "#,
+            )
+            .file("crates/qux/Cargo.toml", &basic_manifest("qux", "0.1.0"))
+            .file("crates/qux/src/lib.rs", "")
+            .file("crates/qux/build.rs", "fn main() {}")
+            .file(
+                "crates/qux/foo.rs",
+                r#"pub fn greeting() -> &'static str { include_str!("../../shared/greeting.txt") }"#,
+            )
+    });
+    let p = project()
+        .file("Cargo.toml", &manifest(&repo, &["foo"]))
+        .file(
+            "src/main.rs",
+            "extern crate foo; fn main() { foo::greeting(); }",
+        )
+        .build();
+
+    p.cargo("build -Zgit=sparse-checkout")
+        .masquerade_as_nightly_cargo(&["git"])
+        .run();
+
+    let dirs = checkouts();
+    let checkout = &dirs[0];
+    assert!(checkout.join("shared/greeting.txt").is_file());
+    assert!(checkout.join("crates/qux/build.rs").is_file());
+    assert!(checkout.join("crates/qux/foo.rs").is_file());
+    assert!(!checkout.join("crates/qux/src").exists());
+}
+
+#[cargo_test]
+fn updates_submodules_of_packages_in_use() {
+    let repo = monorepo();
+    let sub = git::new("sub", |project| project.file("data.txt", "data"));
+    let git_repo = git2::Repository::open(&repo.root()).unwrap();
+    let url = sub.url().to_string();
+    git::add_submodule(&git_repo, &url, Path::new("crates/foo/vendor"));
+    git::add_submodule(&git_repo, &url, Path::new("crates/qux/vendor"));
+    git::commit(&git_repo);
+
+    let p = project()
+        .file("Cargo.toml", &manifest(&repo, &["foo"]))
+        .file("src/main.rs", "extern crate foo; fn main() { foo::bar() }")
+        .build();
+
+    p.cargo("build -Zgit=sparse-checkout")
+        .masquerade_as_nightly_cargo(&["git"])
+        .run();
+
+    let dirs = checkouts();
+    let checkout = &dirs[0];
+    assert!(checkout.join("crates/foo/vendor/data.txt").is_file());
+    assert!(!checkout.join("crates/qux").exists());
+}
+
+#[cargo_test(requires_git)]
+fn fetches_blobs_of_checked_out_paths() {
+    let repo = monorepo();
+    let git_repo = git2::Repository::open(&repo.root()).unwrap();
+    let mut config = git_repo.config().unwrap();
+    config.set_bool("uploadpack.allowFilter", true).unwrap();
+    config
+        .set_bool("uploadpack.allowAnySHA1InWant", true)
+        .unwrap();
+    let p = project()
+        .file("Cargo.toml", &manifest(&repo, &["foo"]))
+        .file("src/main.rs", "extern crate foo; fn main() { foo::bar() }")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [net]
+                git-fetch-with-cli = true
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Zgit=sparse-checkout")
+        .masquerade_as_nightly_cargo(&["git"])
+        .run();
+
+    let blob = |path: &str| {
+        let head = git_repo.head().unwrap().peel_to_tree().unwrap();
+        head.get_path(Path::new(path)).unwrap().id()
+    };
+    let db = t!(glob::glob(
+        paths::home()
+            .join(".cargo/git/db/monorepo-*")
+            .to_str()
+            .unwrap()
+    ))
+    .next()
+    .unwrap()
+    .unwrap();
+    let db = git2::Repository::open(&db).unwrap();
+    let odb = db.odb().unwrap();
+    assert!(odb.exists(blob("crates/bar/src/lib.rs")));
+    assert!(!odb.exists(blob("crates/qux/src/lib.rs")));
+
+    p.change_file("Cargo.toml", &manifest(&repo, &["foo", "qux"]));
+    p.cargo("build -Zgit=sparse-checkout")
+        .masquerade_as_nightly_cargo(&["git"])
+        .run();
+
+    assert!(odb.exists(blob("crates/qux/src/lib.rs")));
+    assert!(checkouts()[0].join("crates/qux/src/lib.rs").is_file());
+
+    // Checking out the whole revision fetches the rest.
+    p.cargo("build").run();
+    assert!(odb.exists(blob("crates/baz/src/lib.rs")));
+}
@@ -1089,6 +1089,109 @@ foo v0.0.1:
         .run();
 }
 
+#[cargo_test]
+fn list_git_shows_full_commit() {
+    let p = git::repo(&paths::root().join("foo"))
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    let repo = git2::Repository::open(p.root()).unwrap();
+    let head = repo.head().unwrap().target().unwrap();
+
+    cargo_process("install --git")
+        .arg(p.url().to_string())
+        .run();
+    cargo_process("install --list")
+        .with_stdout(format!(
+            "\
+foo v0.1.0 ({}#{head}):
+    foo[EXE]
+",
+            p.url()
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn git_ref_requires_unstable_options() {
+    let p = git::repo(&paths::root().join("foo"))
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    cargo_process("install --ref refs/pull/1/head --git")
+        .arg(p.url().to_string())
+        .masquerade_as_nightly_cargo(&["git-ref"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--ref` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn git_ref_pull_request() {
+    let p = git::repo(&paths::root().join("foo"))
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    // A commit only reachable from a pull request ref, as on GitHub.
+    let repo = git2::Repository::open(p.root()).unwrap();
+    let pull = repo.head().unwrap().target().unwrap();
+    repo.reference("refs/pull/1/head", pull, false, "open pull request")
+        .unwrap();
+    p.change_file("Cargo.toml", &basic_manifest("foo", "0.2.0"));
+    git::add(&repo);
+    git::commit(&repo);
+
+    cargo_process("install -Zunstable-options --ref refs/pull/1/head --git")
+        .arg(p.url().to_string())
+        .masquerade_as_nightly_cargo(&["git-ref"])
+        .with_stderr_contains(
+            "[INSTALLED] package `foo v0.1.0 ([..]?rev=refs/pull/1/head#[..])` (executable `foo[EXE]`)",
+        )
+        .run();
+    cargo_process("install --list")
+        .with_stdout(format!(
+            "\
+foo v0.1.0 ({}?rev=refs/pull/1/head#{pull}):
+    foo[EXE]
+",
+            p.url()
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn git_ref_branch_and_invalid() {
+    let p = git::repo(&paths::root().join("foo"))
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    cargo_process("install -Zunstable-options --ref main --git")
+        .arg(p.url().to_string())
+        .masquerade_as_nightly_cargo(&["git-ref"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] invalid git reference `main`, expected a full reference name like `refs/pull/123/head`
+    Use `--branch`, `--tag` or `--rev` to install from a branch, tag or commit.
+",
+        )
+        .run();
+
+    cargo_process("install -Zunstable-options --ref refs/heads/master --git")
+        .arg(p.url().to_string())
+        .masquerade_as_nightly_cargo(&["git-ref"])
+        .with_stderr_contains(
+            "[INSTALLED] package `foo v0.1.0 ([..]?branch=master#[..])` (executable `foo[EXE]`)",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn list_error() {
     pkg("foo", "0.0.1");
@@ -1726,6 +1829,173 @@ error: some packages failed to uninstall
     assert_has_not_installed_exe(cargo_home(), "bar");
 }
 
+/// Paths of the downloaded `.crate` and extracted sources of `name-version`.
+fn cached_sources(name_version: &str) -> Vec<PathBuf> {
+    let registry = cargo_home().join("registry");
+    let mut found = Vec::new();
+    for (dir, file) in [
+        ("cache", format!("{name_version}.crate")),
+        ("src", name_version.to_string()),
+    ] {
+        for entry in fs::read_dir(registry.join(dir)).unwrap() {
+            let path = entry.unwrap().path().join(&file);
+            if path.exists() {
+                found.push(path);
+            }
+        }
+    }
+    found
+}
+
+#[cargo_test]
+fn uninstall_purge() {
+    pkg("foo", "0.0.1");
+    pkg("bar", "0.0.1");
+
+    cargo_process("install foo bar").run();
+    assert_eq!(cached_sources("foo-0.0.1").len(), 2);
+
+    cargo_process("uninstall foo --purge")
+        .with_status(101)
+        .with_stderr("[ERROR] the `--purge` flag is unstable, [..]")
+        .run();
+
+    cargo_process("uninstall foo --purge -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["uninstall --purge"])
+        .with_stderr(
+            "\
+[REMOVING] [CWD]/home/.cargo/bin/foo[EXE]
+[REMOVED] [..] files, [..] total
+",
+        )
+        .run();
+    assert_has_not_installed_exe(cargo_home(), "foo");
+    assert!(cached_sources("foo-0.0.1").is_empty());
+    assert_eq!(cached_sources("bar-0.0.1").len(), 2);
+
+    let listing: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(cargo_home().join(".crates2.json")).unwrap())
+            .unwrap();
+    let history = listing["history"].as_array().unwrap();
+    assert_eq!(history.len(), 1);
+    assert!(history[0]["package"]
+        .as_str()
+        .unwrap()
+        .starts_with("foo 0.0.1 "));
+    assert_eq!(history[0]["removed"].as_array().unwrap().len(), 2);
+}
+
+#[cargo_test]
+fn uninstall_purge_keeps_sources_of_remaining_bins() {
+    Package::new("foo", "0.0.1")
+        .file("src/bin/a.rs", "fn main() {}")
+        .file("src/bin/b.rs", "fn main() {}")
+        .publish();
+
+    cargo_process("install foo").run();
+    cargo_process("uninstall foo --bin a --purge -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["uninstall --purge"])
+        .with_stderr(
+            "\
+[REMOVING] [CWD]/home/.cargo/bin/a[EXE]
+[WARNING] not purging cached sources of `foo v0.0.1`, it still has installed binaries
+",
+        )
+        .run();
+    assert_has_installed_exe(cargo_home(), "b");
+    assert_eq!(cached_sources("foo-0.0.1").len(), 2);
+}
+
+#[cargo_test]
+fn uninstall_purge_keeps_sources_used_since_install() {
+    pkg("foo", "0.0.1");
+
+    cargo_process("install foo").run();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                foo = "0.0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let later = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 3600;
+    p.cargo("check")
+        .env("__CARGO_TEST_LAST_USE_NOW", later.to_string())
+        .run();
+
+    cargo_process("uninstall foo --purge -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["uninstall --purge"])
+        .with_stderr(
+            "\
+[REMOVING] [CWD]/home/.cargo/bin/foo[EXE]
+[REMOVED] 0 files
+",
+        )
+        .run();
+    assert_eq!(cached_sources("foo-0.0.1").len(), 2);
+}
+
+#[cargo_test]
+fn uninstall_all() {
+    pkg("foo", "0.0.1");
+    pkg("bar", "0.0.1");
+
+    cargo_process("install foo bar").run();
+
+    cargo_process("uninstall --all -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["uninstall --all"])
+        .with_stdin("n\n")
+        .with_status(101)
+        .with_stderr(
+            "\
+[NOTE] the following will be removed:
+  bar v0.0.1
+    [CWD]/home/.cargo/bin/bar[EXE]
+  foo v0.0.1
+    [CWD]/home/.cargo/bin/foo[EXE]
+uninstall all of the above? [y/N] [ERROR] uninstall aborted, nothing was removed
+",
+        )
+        .run();
+    assert_has_installed_exe(cargo_home(), "foo");
+    assert_has_installed_exe(cargo_home(), "bar");
+
+    cargo_process("uninstall --all -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["uninstall --all"])
+        .with_stdin("y\n")
+        .with_stderr(
+            "\
+[NOTE] the following will be removed:
+  bar v0.0.1
+    [CWD]/home/.cargo/bin/bar[EXE]
+  foo v0.0.1
+    [CWD]/home/.cargo/bin/foo[EXE]
+uninstall all of the above? [y/N] [REMOVING] [CWD]/home/.cargo/bin/bar[EXE]
+[REMOVING] [CWD]/home/.cargo/bin/foo[EXE]
+",
+        )
+        .run();
+    assert_has_not_installed_exe(cargo_home(), "foo");
+    assert_has_not_installed_exe(cargo_home(), "bar");
+
+    cargo_process("uninstall --all -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["uninstall --all"])
+        .with_stderr("[WARNING] no packages are installed, nothing to uninstall")
+        .run();
+}
+
 #[cargo_test]
 fn custom_target_dir_for_git_source() {
     let p = git::repo(&paths::root().join("foo"))
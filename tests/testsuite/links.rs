@@ -0,0 +1,156 @@
+//! Tests for `cargo links` and the scoped tables of build script overrides.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, rustc_host, Project};
+
+/// A package linking `foo`, whose build script is overridden by `config`.
+fn overridden_project(config: &str) -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+                links = "foo"
+            "#,
+        )
+        .file("build.rs", r#"fn main() { panic!("not overridden"); }"#)
+        .file(
+            "src/lib.rs",
+            r#"
+                #[cfg(prebuilt)]
+                compile_error!("the `tests` override applied to the library");
+            "#,
+        )
+        .file(
+            "tests/it.rs",
+            r#"
+                #[cfg(prebuilt)]
+                #[test]
+                fn scoped() {
+                    assert_eq!(env!("FOO_DATA"), "/opt/foo");
+                }
+            "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            &format!("[target.{}.foo]\n{config}", rustc_host()),
+        )
+        .build()
+}
+
+#[cargo_test]
+fn scoped_override() {
+    let p = overridden_project(
+        r#"
+            rustc-check-cfg = ["cfg(prebuilt)"]
+
+            [target.'HOST'.foo.tests]
+            rustc-cfg = ["prebuilt"]
+            rustc-env = { FOO_DATA = "/opt/foo" }
+        "#
+        .replace("HOST", &rustc_host())
+        .as_str(),
+    );
+
+    p.cargo("test -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["scoped build script overrides"])
+        .with_stdout_contains("test scoped ... ok")
+        .run();
+}
+
+#[cargo_test]
+fn scoped_override_gated() {
+    let p = overridden_project(
+        r#"
+            [target.'HOST'.foo.tests]
+            rustc-cfg = ["prebuilt"]
+        "#
+        .replace("HOST", &rustc_host())
+        .as_str(),
+    );
+
+    p.cargo("check")
+        .with_stderr_contains(&format!(
+            "[WARNING] `target.{}.foo.tests` requires `-Zunstable-options`, ignoring it",
+            rustc_host()
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn scoped_override_unsupported_key() {
+    let p = overridden_project(
+        r#"
+            [target.'HOST'.foo.bins]
+            rustc-link-lib = ["foo"]
+        "#
+        .replace("HOST", &rustc_host())
+        .as_str(),
+    );
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["scoped build script overrides"])
+        .with_status(101)
+        .with_stderr_contains(&format!(
+            "[..]`rustc-link-lib` is not supported in `target.{}.foo.bins`",
+            rustc_host()
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn cargo_links_gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("links")
+        .masquerade_as_nightly_cargo(&["cargo-links"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo links` command is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn cargo_links_lists_owners() {
+    Package::new("libz-sys", "1.1.0")
+        .links("z")
+        .file("build.rs", "fn main() {}")
+        .publish();
+    Package::new("regex", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+                links = "foo"
+                build = "build.rs"
+
+                [dependencies]
+                libz-sys = "1.1"
+                regex = "1.0"
+            "#,
+        )
+        .file("build.rs", "fn main() {}")
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("links -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-links"])
+        .with_stdout(
+            "\
+foo path+[ROOTURL]/foo#0.1.0
+z registry+https://github.com/rust-lang/crates.io-index#libz-sys@1.1.0
+",
+        )
+        .run();
+}
@@ -0,0 +1,167 @@
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+#[cargo_test]
+fn default() {
+    Package::new("bar", "0.1.0")
+        .deprecated("use `baz` instead")
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+bar = "0.1.0"
+"#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr(
+            "\
+[UPDATING] [..]
+[LOCKING] 2 packages to latest compatible versions
+warning: dependency is deprecated by its registry: `bar v0.1.0`
+ --> Cargo.toml:8:1
+  |
+8 | bar = \"0.1.0\"
+  | ---
+  |
+  = note: `cargo::deprecated-dependency` is set to `warn` by default
+  = note: `bar` is deprecated: use `baz` instead
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v0.1.0 ([..])
+[CHECKING] bar v0.1.0
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn deny() {
+    Package::new("bar", "0.1.0").deprecated("").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[dev-dependencies]
+bar = "0.1.0"
+
+[lints.cargo]
+deprecated-dependency = "deny"
+"#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] [..]
+[LOCKING] 2 packages to latest compatible versions
+error: dependency is deprecated by its registry: `bar v0.1.0`
+ --> Cargo.toml:8:1
+  |
+8 | bar = \"0.1.0\"
+  | ^^^
+  |
+  = note: `cargo::deprecated-dependency` is set to `deny` in `[lints]`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn not_deprecated() {
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+bar = "0.1.0"
+"#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr(
+            "\
+[UPDATING] [..]
+[LOCKING] 2 packages to latest compatible versions
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v0.1.0 ([..])
+[CHECKING] bar v0.1.0
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn reported_once_per_workspace() {
+    Package::new("bar", "0.1.0").deprecated("").publish();
+    let member = |name| {
+        format!(
+            r#"
+[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+bar = "0.1.0"
+
+[lints]
+workspace = true
+"#
+        )
+    };
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[workspace]
+members = ["foo", "baz"]
+
+[workspace.lints.cargo]
+deprecated-dependency = "deny"
+"#,
+        )
+        .file("foo/Cargo.toml", &member("foo"))
+        .file("foo/src/lib.rs", "")
+        .file("baz/Cargo.toml", &member("baz"))
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] dependency is deprecated by its registry: `bar v0.1.0`")
+        .with_stderr_contains("[ERROR] encountered 1 errors(s) while running lints")
+        .run();
+}
@@ -1,9 +1,13 @@
 use cargo_test_support::project;
 use cargo_test_support::registry::Package;
 
+mod deprecated_dependency;
 mod error;
 mod implicit_features;
 mod inherited;
+mod multiple_versions;
+mod package_contents;
+mod stale_dependencies;
 mod unknown_lints;
 mod unused_optional_dependencies;
 mod warning;
@@ -0,0 +1,217 @@
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+fn publish_duplicates() {
+    Package::new("sys", "0.1.0").publish();
+    Package::new("sys", "0.2.0").publish();
+    Package::new("a", "1.0.0").dep("sys", "0.1").publish();
+    Package::new("b", "1.0.0").dep("sys", "0.2").publish();
+}
+
+#[cargo_test]
+fn allowed_by_default() {
+    publish_duplicates();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+a = "1.0"
+b = "1.0"
+"#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_does_not_contain("[..]multiple semver-incompatible versions[..]")
+        .run();
+}
+
+#[cargo_test]
+fn deny() {
+    publish_duplicates();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+a = "1.0"
+b = "1.0"
+
+[lints.cargo]
+multiple_versions = "deny"
+"#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] multiple semver-incompatible versions of a package: `sys`")
+        .with_stderr_contains("  = note: `cargo::multiple_versions` is set to `deny` in `[lints]`")
+        .with_stderr_contains("  = note: `foo v0.1.0 ([CWD])` -> `a v1.0.0` -> `sys v0.1.0`")
+        .with_stderr_contains("  = note: `foo v0.1.0 ([CWD])` -> `b v1.0.0` -> `sys v0.2.0`")
+        .with_stderr_contains("[ERROR] encountered 1 errors(s) while running lints")
+        .run();
+}
+
+#[cargo_test]
+fn allow_list() {
+    publish_duplicates();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+a = "1.0"
+b = "1.0"
+
+[lints.cargo]
+multiple_versions = { level = "deny", allow = ["sys"] }
+"#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_does_not_contain("[..]multiple semver-incompatible versions[..]")
+        .run();
+}
+
+#[cargo_test]
+fn workspace_inherited() {
+    publish_duplicates();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[workspace]
+members = ["foo"]
+
+[workspace.lints.cargo]
+multiple_versions = "warn"
+"#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+a = "1.0"
+b = "1.0"
+
+[lints]
+workspace = true
+"#,
+        )
+        .file("foo/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_contains("[WARNING] multiple semver-incompatible versions of a package: `sys`")
+        .with_stderr_contains("  = note: `foo v0.1.0 ([CWD]/foo)` -> `a v1.0.0` -> `sys v0.1.0`")
+        .run();
+}
+
+#[cargo_test]
+fn reported_once_per_workspace() {
+    publish_duplicates();
+    let member = |name| {
+        format!(
+            r#"
+[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+a = "1.0"
+b = "1.0"
+
+[lints]
+workspace = true
+"#
+        )
+    };
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[workspace]
+members = ["foo", "bar"]
+
+[workspace.lints.cargo]
+multiple_versions = "deny"
+"#,
+        )
+        .file("foo/Cargo.toml", &member("foo"))
+        .file("foo/src/lib.rs", "")
+        .file("bar/Cargo.toml", &member("bar"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] multiple semver-incompatible versions of a package: `sys`")
+        .with_stderr_contains("[ERROR] encountered 1 errors(s) while running lints")
+        .run();
+}
+
+#[cargo_test]
+fn unknown_allow_entry() {
+    publish_duplicates();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+a = "1.0"
+b = "1.0"
+
+[lints.cargo]
+multiple_versions = { level = "deny", allow = ["sys", "sys-typo"] }
+"#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_contains(
+            "[WARNING] `sys-typo` in the `allow` list of `cargo::multiple_versions` \
+             is not a dependency of the workspace",
+        )
+        .with_stderr_does_not_contain("[..]`sys` in the `allow` list[..]")
+        .with_stderr_does_not_contain("[..]multiple semver-incompatible versions[..]")
+        .run();
+}
@@ -0,0 +1,113 @@
+use cargo_test_support::{paths, project, symlink_supported};
+
+#[cargo_test]
+fn warn_on_build_output() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+"#,
+        )
+        .file("src/lib.rs", "")
+        .file("prebuilt/libfoo.rlib", "")
+        .build();
+
+    p.cargo("package --list -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_contains(
+            "[WARNING] suspicious file in package: `prebuilt/libfoo.rlib` looks like a build output",
+        )
+        .with_stderr_contains("  = note: `cargo::package_contents` is set to `warn` by default")
+        .with_stdout_contains("prebuilt/libfoo.rlib")
+        .run();
+}
+
+#[cargo_test]
+fn deny_large_file() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[lints.cargo]
+package_contents = { level = "deny", max-file-size = 10, allow = ["Cargo.toml.orig"] }
+"#,
+        )
+        .file("src/lib.rs", "")
+        .file("data.txt", "twenty bytes of data")
+        .build();
+
+    p.cargo("package --no-verify -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] suspicious file in package: \
+             `data.txt` is 20.0B, larger than the `max-file-size` of 10.0B",
+        )
+        .with_stderr_contains("  = note: `cargo::package_contents` is set to `deny` in `[lints]`")
+        .with_stderr_contains("[ERROR] encountered 1 errors(s) while running lints")
+        .with_stderr_does_not_contain("[PACKAGING] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn allow_list() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[lints.cargo]
+package_contents = { level = "deny", allow = ["prebuilt/libfoo.rlib"] }
+"#,
+        )
+        .file("src/lib.rs", "")
+        .file("prebuilt/libfoo.rlib", "")
+        .build();
+
+    p.cargo("package --list -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_does_not_contain("[..]suspicious file in package[..]")
+        .run();
+}
+
+#[cargo_test]
+fn symlink_outside_of_package() {
+    if !symlink_supported() {
+        return;
+    }
+    std::fs::write(paths::root().join("outside.txt"), "").unwrap();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+"#,
+        )
+        .file("src/lib.rs", "")
+        .symlink("../outside.txt", "outside.txt")
+        .build();
+
+    p.cargo("package --list -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_contains(
+            "[WARNING] suspicious file in package: \
+             `outside.txt` is a symlink to `[..]outside.txt`, outside of the package root",
+        )
+        .run();
+}
@@ -0,0 +1,158 @@
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, Project};
+
+/// Locks `bar v1.0.0`, published long ago, before publishing `bar v1.0.1`.
+fn locked_old_version(lints: &str) -> Project {
+    Package::new("bar", "1.0.0")
+        .pubtime("2020-01-01T00:00:00Z")
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+bar = "1.0"
+
+{lints}
+"#
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    p
+}
+
+#[cargo_test]
+fn allowed_by_default() {
+    let p = locked_old_version("");
+    Package::new("bar", "1.0.1").publish();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_does_not_contain("[..]newer compatible version[..]")
+        .run();
+}
+
+#[cargo_test]
+fn warn() {
+    let p = locked_old_version(
+        r#"
+[lints.cargo]
+stale_dependencies = "warn"
+"#,
+    );
+    Package::new("bar", "1.0.1").publish();
+    Package::new("bar", "1.0.2").publish();
+    Package::new("bar", "2.0.0").publish();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_contains(
+            "[WARNING] locked version is old and a newer compatible version is available: `bar v1.0.0`",
+        )
+        .with_stderr_contains("  = note: `cargo::stale_dependencies` is set to `warn` in `[lints]`")
+        .with_stderr_contains(
+            "  = note: `bar v1.0.0` was published [..] days ago, \
+             which is more than the `max-age-days` of 365",
+        )
+        .with_stderr_contains(
+            "  = help: `bar v1.0.2` is available, run `cargo update bar@1.0.0` to update it",
+        )
+        .with_stderr_contains("[CHECKING] bar v1.0.0")
+        .run();
+}
+
+#[cargo_test]
+fn deny() {
+    let p = locked_old_version(
+        r#"
+[lints.cargo]
+stale_dependencies = "deny"
+"#,
+    );
+    Package::new("bar", "1.0.1").publish();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] locked version is old and a newer compatible version is available: `bar v1.0.0`",
+        )
+        .with_stderr_contains("[ERROR] encountered 1 errors(s) while running lints")
+        .run();
+}
+
+#[cargo_test]
+fn not_older_than_max_age() {
+    let p = locked_old_version(
+        r#"
+[lints.cargo]
+stale_dependencies = { level = "deny", max-age-days = 100000 }
+"#,
+    );
+    Package::new("bar", "1.0.1").publish();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_does_not_contain("[..]newer compatible version[..]")
+        .run();
+}
+
+#[cargo_test]
+fn no_newer_compatible_version() {
+    let p = locked_old_version(
+        r#"
+[lints.cargo]
+stale_dependencies = "deny"
+"#,
+    );
+    Package::new("bar", "2.0.0").publish();
+    Package::new("bar", "1.0.1").yanked(true).publish();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_does_not_contain("[..]newer compatible version[..]")
+        .run();
+}
+
+#[cargo_test]
+fn allow_list() {
+    let p = locked_old_version(
+        r#"
+[lints.cargo]
+stale_dependencies = { level = "deny", allow = ["bar"] }
+"#,
+    );
+    Package::new("bar", "1.0.1").publish();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_does_not_contain("[..]newer compatible version[..]")
+        .run();
+}
+
+#[cargo_test]
+fn invalid_max_age() {
+    let p = locked_old_version(
+        r#"
+[lints.cargo]
+stale_dependencies = { level = "warn", max-age-days = "1 year" }
+"#,
+    );
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] `max-age-days` of `cargo::stale_dependencies` must be a number of days, \
+             found `\"1 year\"`",
+        )
+        .run();
+}
@@ -1324,3 +1324,149 @@ dependencies = [
         );
     }
 }
+
+#[cargo_test]
+fn v5_path_content_checksum() {
+    let _bar = project()
+        .at("bar")
+        .file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "../bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("Cargo.lock", "version = 5")
+        .build();
+
+    p.cargo("fetch -Znext-lockfile-bump")
+        .masquerade_as_nightly_cargo(&["-Znext-lockfile-bump"])
+        .run();
+
+    let lock = p.read_lockfile();
+    assert_match_exact(
+        r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 5
+
+[[package]]
+name = "bar"
+version = "0.1.0"
+checksum = "[..]"
+
+[[package]]
+name = "foo"
+version = "0.0.1"
+dependencies = [
+ "bar",
+]
+"#,
+        &lock,
+    );
+
+    // Unchanged contents keep the lock file up to date.
+    p.cargo("fetch -Znext-lockfile-bump --locked")
+        .masquerade_as_nightly_cargo(&["-Znext-lockfile-bump"])
+        .run();
+
+    // Files that weren't modified since the lock file was written aren't
+    // hashed again.
+    let lib = p.root().join("../bar/src/lib.rs");
+    let mtime = filetime::FileTime::from_unix_time(0, 0);
+    p.change_file("../bar/src/lib.rs", "pub fn g() {}");
+    filetime::set_file_times(&lib, mtime, mtime).unwrap();
+    p.cargo("fetch -Znext-lockfile-bump --locked")
+        .masquerade_as_nightly_cargo(&["-Znext-lockfile-bump"])
+        .run();
+
+    p.change_file("../bar/src/lib.rs", "pub fn f() {}");
+    p.cargo("fetch -Znext-lockfile-bump --locked")
+        .masquerade_as_nightly_cargo(&["-Znext-lockfile-bump"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the contents of `bar v0.1.0 ([ROOT]/bar)` changed since the lock file [ROOT]/foo/Cargo.lock \
+was generated, but --locked was passed to prevent updating its checksum
+",
+        )
+        .run();
+
+    p.cargo("fetch -Znext-lockfile-bump")
+        .masquerade_as_nightly_cargo(&["-Znext-lockfile-bump"])
+        .run();
+    assert_ne!(lock, p.read_lockfile());
+}
+
+#[cargo_test]
+fn v5_git_content_checksum() {
+    let (git_project, repo) = git::new_repo("dep1", |project| {
+        project
+            .file("Cargo.toml", &basic_lib_manifest("dep1"))
+            .file("src/lib.rs", "")
+    });
+    let head_id = repo.head().unwrap().target().unwrap();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+                    edition = "2015"
+
+                    [dependencies]
+                    dep1 = {{ git = '{}', branch = 'master' }}
+                "#,
+                git_project.url(),
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file("Cargo.lock", "version = 5")
+        .build();
+
+    p.cargo("fetch -Znext-lockfile-bump")
+        .masquerade_as_nightly_cargo(&["-Znext-lockfile-bump"])
+        .run();
+
+    let lock = p.read_lockfile();
+    assert_match_exact(
+        &format!(
+            r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 5
+
+[[package]]
+name = "dep1"
+version = "0.5.0"
+source = "git+{}?branch=master#{}"
+checksum = "[..]"
+
+[[package]]
+name = "foo"
+version = "0.0.1"
+dependencies = [
+ "dep1",
+]
+"#,
+            git_project.url(),
+            head_id,
+        ),
+        &lock,
+    );
+
+    p.cargo("fetch -Znext-lockfile-bump --locked")
+        .masquerade_as_nightly_cargo(&["-Znext-lockfile-bump"])
+        .run();
+}
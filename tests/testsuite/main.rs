@@ -7,16 +7,24 @@ extern crate cargo_test_macro;
 
 mod advanced_env;
 mod alt_registry;
+mod artifact_cache;
 mod artifact_dep;
 mod bad_config;
 mod bad_manifest_path;
+mod bans;
 mod bench;
 mod binary_name;
 mod build;
+mod build_directives;
+mod build_executor;
+mod build_meta;
 mod build_plan;
+mod build_reports;
+mod build_sandbox;
 mod build_script;
 mod build_script_env;
 mod build_script_extra_link_arg;
+mod build_server;
 mod cache_lock;
 mod cache_messages;
 mod cargo;
@@ -77,8 +85,12 @@ mod credential_process;
 mod cross_compile;
 mod cross_publish;
 mod custom_target;
+mod daemon;
 mod death;
+mod dedupe;
 mod dep_info;
+mod dependency_pins;
+mod dependency_warnings;
 mod diagnostics;
 mod direct_minimal_versions;
 mod directory;
@@ -86,19 +98,24 @@ mod doc;
 mod docscrape;
 mod edition;
 mod error;
+mod explain;
 mod features;
 mod features2;
+mod features_diff;
 mod features_namespaced;
 mod fetch;
 mod fix;
 mod fix_n_times;
 mod freshness;
 mod future_incompat_report;
+mod fuzz_targets;
 mod generate_lockfile;
 mod git;
 mod git_auth;
 mod git_gc;
+mod git_members;
 mod git_shallow;
+mod git_sparse;
 mod glob_targets;
 mod global_cache_tracker;
 mod help;
@@ -107,6 +124,7 @@ mod inheritable_workspace_fields;
 mod install;
 mod install_upgrade;
 mod jobserver;
+mod links;
 mod lints;
 mod lints_table;
 mod list_availables;
@@ -129,8 +147,10 @@ mod new;
 mod offline;
 mod old_cargos;
 mod open_namespaces;
+mod origin_lints;
 mod out_dir;
 mod owner;
+mod owner_of;
 mod package;
 mod package_features;
 mod patch;
@@ -139,6 +159,7 @@ mod paths;
 mod pkgid;
 mod precise_pre_release;
 mod proc_macro;
+mod profile_binary_size;
 mod profile_config;
 mod profile_custom;
 mod profile_overrides;
@@ -149,12 +170,14 @@ mod progress;
 mod pub_priv;
 mod publish;
 mod publish_lockfile;
+mod query;
 mod read_manifest;
 mod registry;
 mod registry_auth;
 mod rename_deps;
 mod replace;
 mod required_features;
+mod resolve_cache;
 mod run;
 mod rust_version;
 mod rustc;
@@ -170,6 +193,7 @@ mod shell_quoting;
 mod source_replacement;
 mod ssh;
 mod standard_lib;
+mod system_dependencies;
 mod test;
 mod timings;
 mod tool_paths;
@@ -178,12 +202,15 @@ mod tree_graph_features;
 mod unit_graph;
 mod update;
 mod vendor;
+mod verify_lockfile;
 mod verify_project;
 mod version;
+mod vet;
 mod warn_on_failure;
 mod weak_dep_features;
 mod workspaces;
 mod yank;
+mod yanked_policy;
 
 #[cargo_test]
 fn aaa_trigger_cross_compile_disabled_check() {
@@ -1,5 +1,6 @@
 //! Tests for --message-format flag.
 
+use cargo_test_support::registry::Package;
 use cargo_test_support::{basic_lib_manifest, basic_manifest, project};
 
 #[cargo_test]
@@ -131,3 +132,271 @@ fn cargo_renders_doctests() {
         .with_stdout_contains("[..]src/lib.rs - bar (line 1)[..]")
         .run();
 }
+
+#[cargo_test]
+fn json_progress_requires_nightly() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check --message-format json-progress")
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--message-format json-progress` flag is unstable, [..]")
+        .run();
+}
+
+#[cargo_test]
+fn json_progress() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zunstable-options --message-format json-progress")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+{
+    "reason": "unit-started",
+    "package_id": "path+file:///[..]/foo#0.1.0",
+    "target": "{...}",
+    "mode": "check",
+    "fresh": false
+}
+
+{
+    "reason": "compiler-artifact",
+    "package_id": "path+file:///[..]/foo#0.1.0",
+    "manifest_path": "[..]",
+    "target": "{...}",
+    "profile": "{...}",
+    "features": [],
+    "filenames": "{...}",
+    "executable": null,
+    "fresh": false
+}
+
+{
+    "reason": "unit-finished",
+    "package_id": "path+file:///[..]/foo#0.1.0",
+    "target": "{...}",
+    "mode": "check",
+    "success": true
+}
+
+{"reason": "build-progress", "finished": 1, "total": 1, "percentage": 100}
+
+{"reason": "build-finished", "success": true}
+"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn json_progress_from_config() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [term]
+                progress = "json"
+            "#,
+        )
+        .build();
+
+    p.cargo("check")
+        .with_stdout("")
+        .with_stderr_contains(
+            "[WARNING] `term.progress = \"json\"` requires `-Zunstable-options`, \
+             falling back to `auto`",
+        )
+        .run();
+
+    // The events go to stderr, as the setting applies to every command.
+    p.cargo("clean").run();
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("")
+        .with_stderr_contains(
+            r#"{"reason":"unit-started","package_id":"registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0","target":{[..]},"mode":"check","fresh":false}"#,
+        )
+        .with_stderr_contains(
+            r#"{"reason":"unit-finished","package_id":"registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0","target":{[..]},"mode":"check","success":true}"#,
+        )
+        .with_stderr_contains(
+            r#"{"reason":"unit-started","package_id":"path+file:///[..]/foo#0.1.0","target":{[..]},"mode":"check","fresh":false}"#,
+        )
+        .with_stderr_contains(r#"{"reason":"build-progress","finished":1,"total":2,"percentage":50}"#)
+        .with_stderr_contains(r#"{"reason":"build-progress","finished":2,"total":2,"percentage":100}"#)
+        .run();
+
+    p.cargo("check -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("")
+        .with_stderr_contains(
+            r#"{"reason":"unit-started","package_id":"path+file:///[..]/foo#0.1.0","target":{[..]},"mode":"check","fresh":true}"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn json_progress_downloads() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [term]
+                progress = "json"
+            "#,
+        )
+        .build();
+
+    p.cargo("fetch -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("")
+        .with_stderr_contains(
+            r#"{"reason":"download-started","package_id":"registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0"}"#,
+        )
+        .with_stderr_contains(
+            r#"{"reason":"download-finished","package_id":"registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0","bytes":[..]}"#,
+        )
+        .run();
+
+    // The output of commands printing JSON isn't mixed with the events.
+    Package::new("baz", "1.0.0").publish();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            baz = "1.0"
+        "#,
+    );
+    p.cargo("metadata -Zunstable-options --format-version 1")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_does_not_contain("[..]download-started[..]")
+        .with_stderr_contains("[..]\"reason\":\"download-started\"[..]")
+        .run();
+}
+
+#[cargo_test]
+fn sarif_requires_nightly() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check --message-format sarif")
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--message-format sarif` flag is unstable, [..]")
+        .run();
+}
+
+#[cargo_test]
+fn sarif() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "pub fn f() { let x = 1; }")
+        .build();
+
+    p.cargo("check -Zunstable-options --message-format sarif")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_contains("[WARNING] unused variable: `x`")
+        .with_json(
+            r#"
+{
+    "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+    "version": "2.1.0",
+    "runs": [
+        {
+            "tool": {
+                "driver": {
+                    "name": "cargo",
+                    "informationUri": "https://doc.rust-lang.org/cargo/",
+                    "rules": [{ "id": "unused_variables" }]
+                }
+            },
+            "originalUriBaseIds": {
+                "%SRCROOT%": { "uri": "file:///[..]/foo/" }
+            },
+            "results": [
+                {
+                    "level": "warning",
+                    "ruleId": "unused_variables",
+                    "message": { "text": "unused variable: `x`" },
+                    "locations": [
+                        {
+                            "physicalLocation": {
+                                "artifactLocation": {
+                                    "uri": "src/lib.rs",
+                                    "uriBaseId": "%SRCROOT%"
+                                },
+                                "region": {
+                                    "startLine": 1,
+                                    "startColumn": 18,
+                                    "endLine": 1,
+                                    "endColumn": 19
+                                }
+                            }
+                        }
+                    ]
+                }
+            ]
+        }
+    ]
+}
+"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn sarif_error_rule() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "pub fn f() -> u8 { \"\" }")
+        .build();
+
+    p.cargo("check -Zunstable-options --message-format sarif")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stdout_contains("[..]\"id\":\"E0308\"[..]")
+        .with_stdout_contains(
+            "[..]\"helpUri\":\"https://doc.rust-lang.org/error_codes/E0308.html\"[..]",
+        )
+        .with_stdout_contains("[..]\"level\":\"error\"[..]")
+        .run();
+}
@@ -469,11 +469,12 @@ fn metabuild_metadata() {
 fn metabuild_build_plan() {
     let p = basic_project();
 
-    p.cargo("build --build-plan -Zunstable-options")
-        .masquerade_as_nightly_cargo(&["metabuild", "build-plan"])
+    p.cargo("build --build-plan")
+        .masquerade_as_nightly_cargo(&["metabuild"])
         .with_json(
             r#"
             {
+                "version": 1,
                 "invocations": [
                     {
                         "package_name": "mb",
@@ -482,6 +483,7 @@ fn metabuild_build_plan() {
                         "compile_mode": "build",
                         "kind": null,
                         "deps": [],
+                        "inputs": ["[..]/src/lib.rs"],
                         "outputs": [
                             "[..]/target/debug/deps/libmb-[..].rlib",
                             "[..]/target/debug/deps/libmb-[..].rmeta"
@@ -499,6 +501,7 @@ fn metabuild_build_plan() {
                         "compile_mode": "build",
                         "kind": null,
                         "deps": [],
+                        "inputs": ["[..]/src/lib.rs"],
                         "outputs": [
                             "[..]/target/debug/deps/libmb_other-[..].rlib",
                             "[..]/target/debug/deps/libmb_other-[..].rmeta"
@@ -516,6 +519,7 @@ fn metabuild_build_plan() {
                         "compile_mode": "build",
                         "kind": null,
                         "deps": [0, 1],
+                        "inputs": ["[..]"],
                         "outputs": "{...}",
                         "links": "{...}",
                         "program": "rustc",
@@ -530,6 +534,7 @@ fn metabuild_build_plan() {
                         "compile_mode": "run-custom-build",
                         "kind": null,
                         "deps": [2],
+                        "inputs": ["[..]/foo"],
                         "outputs": [],
                         "links": {},
                         "program": "[..]/foo/target/debug/build/foo-[..]/metabuild-foo",
@@ -544,6 +549,7 @@ fn metabuild_build_plan() {
                         "compile_mode": "build",
                         "kind": null,
                         "deps": [3],
+                        "inputs": ["[..]/foo/src/lib.rs"],
                         "outputs": [
                             "[..]/foo/target/debug/deps/libfoo-[..].rlib",
                             "[..]/foo/target/debug/deps/libfoo-[..].rmeta"
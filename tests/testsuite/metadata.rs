@@ -4652,3 +4652,549 @@ local-time = 1979-05-27
         )
         .run();
 }
+
+#[cargo_test]
+fn unit_features() {
+    Package::new("common", "1.0.0")
+        .feature("a", &[])
+        .feature("b", &[])
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2021"
+
+            [dependencies]
+            common = { version = "1.0", features = ["a"] }
+
+            [build-dependencies]
+            common = { version = "1.0", features = ["b"] }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("metadata -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+            {
+              "packages": "{...}",
+              "workspace_members": "{...}",
+              "workspace_default_members": "{...}",
+              "target_directory": "{...}",
+              "version": 1,
+              "workspace_root": "{...}",
+              "metadata": null,
+              "resolve": {
+                "nodes": "{...}",
+                "root": "[..]foo#0.1.0",
+                "units": [
+                  {
+                    "pkg": "registry+https://github.com/rust-lang/crates.io-index#common@1.0.0",
+                    "kind": "normal",
+                    "target": null,
+                    "features": ["a"]
+                  },
+                  {
+                    "pkg": "registry+https://github.com/rust-lang/crates.io-index#common@1.0.0",
+                    "kind": "host",
+                    "target": null,
+                    "features": ["b"]
+                  },
+                  {
+                    "pkg": "path+file:[..]foo#0.1.0",
+                    "kind": "normal",
+                    "target": null,
+                    "features": []
+                  }
+                ]
+              }
+            }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn ndjson_format() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("metadata --format ndjson")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `--format` flag is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+
+    p.cargo("metadata --format ndjson -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+            {
+              "reason": "package",
+              "name": "bar",
+              "version": "1.0.0",
+              "id": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+              "license": null,
+              "license_file": null,
+              "description": null,
+              "source": "registry+https://github.com/rust-lang/crates.io-index",
+              "dependencies": [],
+              "targets": "{...}",
+              "features": {},
+              "manifest_path": "[..]Cargo.toml",
+              "metadata": null,
+              "publish": null,
+              "authors": [],
+              "categories": [],
+              "keywords": [],
+              "readme": null,
+              "repository": null,
+              "homepage": null,
+              "documentation": null,
+              "edition": "2015",
+              "links": null,
+              "default_run": null,
+              "rust_version": null,
+              "lints": {},
+              "profiles": "{...}"
+            }
+
+            {
+              "reason": "package",
+              "name": "foo",
+              "version": "0.1.0",
+              "id": "path+file:[..]foo#0.1.0",
+              "license": null,
+              "license_file": null,
+              "description": null,
+              "source": null,
+              "dependencies": "{...}",
+              "targets": "{...}",
+              "features": {},
+              "manifest_path": "[..]Cargo.toml",
+              "metadata": null,
+              "publish": null,
+              "authors": [],
+              "categories": [],
+              "keywords": [],
+              "readme": null,
+              "repository": null,
+              "homepage": null,
+              "documentation": null,
+              "edition": "2015",
+              "links": null,
+              "default_run": null,
+              "rust_version": null,
+              "lints": {},
+              "profiles": "{...}"
+            }
+
+            {
+              "reason": "resolve-node",
+              "id": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+              "dependencies": [],
+              "deps": [],
+              "features": []
+            }
+
+            {
+              "reason": "resolve-node",
+              "id": "path+file:[..]foo#0.1.0",
+              "dependencies": [
+                "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0"
+              ],
+              "deps": "{...}",
+              "features": []
+            }
+
+            {
+              "reason": "unit",
+              "pkg": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+              "kind": "normal",
+              "target": null,
+              "features": []
+            }
+
+            {
+              "reason": "unit",
+              "pkg": "path+file:[..]foo#0.1.0",
+              "kind": "normal",
+              "target": null,
+              "features": []
+            }
+
+            {
+              "reason": "metadata",
+              "workspace_members": ["path+file:[..]foo#0.1.0"],
+              "workspace_default_members": ["path+file:[..]foo#0.1.0"],
+              "resolve": {
+                "root": "path+file:[..]foo#0.1.0"
+              },
+              "target_directory": "[..]foo/target",
+              "version": 1,
+              "workspace_root": "[..]/foo",
+              "metadata": null
+            }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn filter_spec() {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("baz", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["a", "b"]
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            bar = "1.0"
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            baz = "1.0"
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("metadata --filter-spec a")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `--filter-spec` flag is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+
+    p.cargo("metadata --format-version 1 --filter-spec a -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+            {
+              "packages": [
+                {
+                  "name": "a",
+                  "version": "0.1.0",
+                  "id": "path+file:[..]a#0.1.0",
+                  "license": null,
+                  "license_file": null,
+                  "description": null,
+                  "source": null,
+                  "dependencies": "{...}",
+                  "targets": "{...}",
+                  "features": {},
+                  "manifest_path": "[..]a/Cargo.toml",
+                  "metadata": null,
+                  "publish": null,
+                  "authors": [],
+                  "categories": [],
+                  "keywords": [],
+                  "readme": null,
+                  "repository": null,
+                  "homepage": null,
+                  "documentation": null,
+                  "edition": "2015",
+                  "links": null,
+                  "default_run": null,
+                  "rust_version": null,
+                  "lints": {},
+                  "profiles": "{...}"
+                },
+                {
+                  "name": "bar",
+                  "version": "1.0.0",
+                  "id": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+                  "license": null,
+                  "license_file": null,
+                  "description": null,
+                  "source": "registry+https://github.com/rust-lang/crates.io-index",
+                  "dependencies": [],
+                  "targets": "{...}",
+                  "features": {},
+                  "manifest_path": "[..]Cargo.toml",
+                  "metadata": null,
+                  "publish": null,
+                  "authors": [],
+                  "categories": [],
+                  "keywords": [],
+                  "readme": null,
+                  "repository": null,
+                  "homepage": null,
+                  "documentation": null,
+                  "edition": "2015",
+                  "links": null,
+                  "default_run": null,
+                  "rust_version": null,
+                  "lints": {},
+                  "profiles": "{...}"
+                }
+              ],
+              "workspace_members": "{...}",
+              "workspace_default_members": "{...}",
+              "resolve": {
+                "nodes": [
+                  {
+                    "id": "path+file:[..]a#0.1.0",
+                    "dependencies": [
+                      "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0"
+                    ],
+                    "deps": "{...}",
+                    "features": []
+                  },
+                  {
+                    "id": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+                    "dependencies": [],
+                    "deps": [],
+                    "features": []
+                  }
+                ],
+                "root": null,
+                "units": "{...}"
+              },
+              "target_directory": "[..]foo/target",
+              "version": 1,
+              "workspace_root": "[..]/foo",
+              "metadata": null
+            }
+            "#,
+        )
+        .run();
+
+    p.cargo("metadata --format-version 1 --filter-spec c -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] package ID specification `c` did not match any packages[..]")
+        .run();
+}
+
+#[cargo_test]
+fn package_filter() {
+    Package::new("bar", "1.0.0")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "1.0.0"
+            license = "MIT"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            bar = "1.0"
+            baz = { path = "baz" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("baz/Cargo.toml", &basic_lib_manifest("baz"))
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    p.cargo("metadata --format-version 1 --prune-license MIT")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `--prune-license` flag is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+
+    p.cargo("metadata --format-version 1 --prune-license MIT -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_contains("[..]\"name\":\"baz\"[..]")
+        .with_stdout_does_not_contain("[..]\"name\":\"bar\"[..]")
+        .run();
+
+    p.cargo("metadata --format-version 1 --only-source registry -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_contains("[..]\"name\":\"bar\"[..]")
+        .with_stdout_does_not_contain("[..]\"name\":\"baz\"[..]")
+        .run();
+}
+
+#[cargo_test]
+fn lints_and_profiles() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["foo"]
+
+            [workspace.lints.rust]
+            unsafe_code = "forbid"
+
+            [profile.dev.package.foo]
+            opt-level = 1
+
+            [profile.dev.build-override]
+            opt-level = 3
+
+            [profile.release]
+            lto = true
+            "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [lints]
+            workspace = true
+            "#,
+        )
+        .file("foo/src/lib.rs", "")
+        .build();
+
+    // The settings are only reported with `-Zunstable-options`.
+    p.cargo("metadata --format-version 1 --no-deps")
+        .with_stdout_does_not_contain("[..]\"profiles\"[..]")
+        .run();
+    p.cargo("metadata --format-version 1 --no-deps --profile release")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `--profile` flag is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+
+    p.cargo("metadata --format-version 1 --no-deps -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+            {
+              "packages": [
+                {
+                  "name": "foo",
+                  "version": "0.1.0",
+                  "id": "path+file:[..]foo#0.1.0",
+                  "license": null,
+                  "license_file": null,
+                  "description": null,
+                  "source": null,
+                  "dependencies": [],
+                  "targets": "{...}",
+                  "features": {},
+                  "manifest_path": "[..]foo/Cargo.toml",
+                  "metadata": null,
+                  "publish": null,
+                  "authors": [],
+                  "categories": [],
+                  "keywords": [],
+                  "readme": null,
+                  "repository": null,
+                  "homepage": null,
+                  "documentation": null,
+                  "edition": "2015",
+                  "links": null,
+                  "default_run": null,
+                  "rust_version": null,
+                  "lints": {
+                    "rust": {
+                      "unsafe_code": "forbid"
+                    }
+                  },
+                  "profiles": {
+                    "normal": {
+                      "codegen_backend": null,
+                      "codegen_units": null,
+                      "debug_assertions": true,
+                      "debuginfo": 2,
+                      "incremental": false,
+                      "lto": "false",
+                      "name": "dev",
+                      "opt_level": "1",
+                      "overflow_checks": true,
+                      "panic": "unwind",
+                      "rpath": false,
+                      "split_debuginfo": "{...}",
+                      "strip": "{...}"
+                    },
+                    "host": {
+                      "codegen_backend": null,
+                      "codegen_units": null,
+                      "debug_assertions": true,
+                      "debuginfo": "{...}",
+                      "incremental": false,
+                      "lto": "false",
+                      "name": "dev",
+                      "opt_level": "3",
+                      "overflow_checks": true,
+                      "panic": "unwind",
+                      "rpath": false,
+                      "split_debuginfo": "{...}",
+                      "strip": "{...}"
+                    }
+                  }
+                }
+              ],
+              "workspace_members": "{...}",
+              "workspace_default_members": "{...}",
+              "resolve": null,
+              "target_directory": "[..]foo/target",
+              "version": 1,
+              "workspace_root": "[..]/foo",
+              "metadata": null
+            }
+            "#,
+        )
+        .run();
+
+    p.cargo("metadata --format-version 1 --no-deps --profile release -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_contains(r#"[..]"normal":{"name":"release","opt_level":"3","lto":"true",[..]"#)
+        .run();
+}
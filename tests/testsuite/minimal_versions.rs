@@ -43,3 +43,133 @@ fn minimal_version_cli() {
 
     assert!(!lock.contains("1.1.0"));
 }
+
+#[cargo_test]
+fn minimal_version_config() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                authors = []
+                version = "0.0.1"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                resolution = "minimal"
+            "#,
+        )
+        .build();
+
+    p.cargo("generate-lockfile")
+        .with_stderr(
+            "\
+[UPDATING] [..]
+[LOCKING] 2 packages to earliest compatible versions
+[ADDING] dep v1.0.0 (latest: v1.1.0)
+",
+        )
+        .run();
+
+    let lock = p.read_lockfile();
+
+    assert!(!lock.contains("1.1.0"));
+}
+
+#[cargo_test]
+fn update_resolution() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                authors = []
+                version = "0.0.1"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("update --resolution minimal")
+        .with_stderr(
+            "\
+[UPDATING] [..]
+[LOCKING] 1 package to earliest compatible version
+[DOWNGRADING] dep v1.1.0 -> v1.0.0 (latest: v1.1.0)
+",
+        )
+        .run();
+
+    p.cargo("update --resolution maximum")
+        .with_stderr(
+            "\
+[UPDATING] [..]
+[LOCKING] 1 package to latest compatible version
+[UPDATING] dep v1.0.0 -> v1.1.0
+",
+        )
+        .run();
+
+    p.cargo("update --resolution lowest")
+        .with_status(1)
+        .with_stderr_contains("[ERROR] invalid value 'lowest' for '--resolution <MODE>'")
+        .run();
+}
+
+#[cargo_test]
+fn minimal_version_pre_1_0() {
+    Package::new("dep", "0.1.0").publish();
+    Package::new("dep", "0.1.1").publish();
+    Package::new("dep", "0.2.0").publish();
+    Package::new("bounded", "0.3.0").publish();
+    Package::new("bounded", "0.3.1").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                authors = []
+                version = "0.0.1"
+
+                [dependencies]
+                dep = ">=0.1"
+                bounded = "0.3"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("update --resolution minimal")
+        .with_stderr(
+            "\
+[UPDATING] [..]
+[LOCKING] 3 packages to earliest compatible versions
+[ADDING] bounded v0.3.0 (latest: v0.3.1)
+[ADDING] dep v0.1.0 (latest: v0.2.0)
+[WARNING] `dep v0.1.0` is the minimal version of `dep` matching `>=0.1` (required by `foo v0.0.1 ([CWD])`), but the requirement also accepts later, incompatible pre-1.0 versions
+",
+        )
+        .run();
+}
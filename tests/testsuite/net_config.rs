@@ -1,6 +1,7 @@
 //! Tests for network configuration.
 
 use cargo_test_support::project;
+use cargo_test_support::registry::Package;
 
 #[cargo_test]
 fn net_retry_loads_from_config() {
@@ -72,3 +73,70 @@ fn net_retry_git_outputs_warning() {
         .with_stderr_contains("[WARNING] spurious network error (1 tries remaining): [..]")
         .run();
 }
+
+fn registry_dep_project() -> cargo_test_support::Project {
+    Package::new("bar", "0.1.0").publish();
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build()
+}
+
+#[cargo_test]
+fn simulate_network_requires_env() {
+    let p = registry_dep_project();
+
+    p.cargo("fetch --simulate-network fail-after=0")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `--simulate-network` flag is only meant for testing, \
+             set `__CARGO_TEST_ENABLE_SIMULATE_NETWORK=1` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn simulate_network_retries_then_works() {
+    let p = registry_dep_project();
+
+    p.cargo("fetch --simulate-network fail-after=0,times=2")
+        .env("__CARGO_TEST_ENABLE_SIMULATE_NETWORK", "1")
+        .env("__CARGO_TEST_FIXED_RETRY_SLEEP_MS", "1")
+        .with_stderr_contains(
+            "[WARNING] spurious network error (3 tries remaining): \
+             [..]simulated network failure 1[..]",
+        )
+        .with_stderr_contains(
+            "[WARNING] spurious network error (2 tries remaining): \
+             [..]simulated network failure 2[..]",
+        )
+        .with_stderr_contains("[DOWNLOADED] bar v0.1.0 (registry `dummy-registry`)")
+        .run();
+}
+
+#[cargo_test]
+fn simulate_network_fails_download() {
+    let p = registry_dep_project();
+
+    // The index is fetched by the first attempt, the download of `bar` fails.
+    p.cargo("fetch --simulate-network fail-after=1")
+        .env("__CARGO_TEST_ENABLE_SIMULATE_NETWORK", "1")
+        .env("__CARGO_TEST_FIXED_RETRY_SLEEP_MS", "1")
+        .env("CARGO_NET_RETRY", "1")
+        .with_status(101)
+        .with_stderr_contains("[WARNING] spurious network error (1 tries remaining): [..]")
+        .with_stderr_contains("[..]failed to download from `[..]`")
+        .with_stderr_contains("[..]simulated network failure 2[..]")
+        .run();
+}
@@ -0,0 +1,156 @@
+//! Tests for `[lints.cargo-origin]`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project, Project};
+
+fn make_project(config: &str) -> Project {
+    Package::new("bar", "1.0.0")
+        .file("src/lib.rs", "pub fn f() { let x = 1; }")
+        .publish();
+
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                local = { path = "local" }
+            "#,
+        )
+        .file("src/lib.rs", "pub fn foo() { let y = 1; }")
+        .file("local/Cargo.toml", &basic_manifest("local", "0.1.0"))
+        .file("local/src/lib.rs", "pub fn local() { let z = 1; }")
+        .file(".cargo/config.toml", config)
+        .build()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = make_project(
+        r#"
+            [lints.cargo-origin]
+            workspace = "deny-warnings"
+        "#,
+    );
+
+    p.cargo("check")
+        .with_stderr_contains("[WARNING] unused variable: `y`")
+        .run();
+}
+
+#[cargo_test]
+fn deny_warnings_for_workspace() {
+    let p = make_project(
+        r#"
+            [lints.cargo-origin]
+            workspace = "deny-warnings"
+        "#,
+    );
+
+    p.cargo("check")
+        .with_stderr_contains("[WARNING] unused variable: `y`")
+        .run();
+
+    // Enabling the policy rebuilds the member.
+    p.cargo("check -Zcargo-origin-lints")
+        .masquerade_as_nightly_cargo(&["cargo-origin-lints"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] unused variable: `y`")
+        .with_stderr_contains("[WARNING] unused variable: `z`")
+        .with_stderr_does_not_contain("[..]unused variable: `x`[..]")
+        .run();
+}
+
+#[cargo_test]
+fn warn_for_registry() {
+    let p = make_project(
+        r#"
+            [lints.cargo-origin]
+            registry = "warn"
+        "#,
+    );
+
+    p.cargo("check -Zcargo-origin-lints")
+        .masquerade_as_nightly_cargo(&["cargo-origin-lints"])
+        .with_stderr_contains("[WARNING] unused variable: `x`")
+        .with_stderr_contains("[WARNING] unused variable: `y`")
+        .run();
+}
+
+#[cargo_test]
+fn allow_for_path_deps() {
+    let p = make_project(
+        r#"
+            [lints.cargo-origin]
+            path-deps = "allow"
+        "#,
+    );
+
+    p.cargo("check -Zcargo-origin-lints")
+        .masquerade_as_nightly_cargo(&["cargo-origin-lints"])
+        .with_stderr_contains("[WARNING] unused variable: `y`")
+        .with_stderr_does_not_contain("[..]unused variable: `z`[..]")
+        .run();
+}
+
+#[cargo_test]
+fn invalid_policy() {
+    let p = make_project(
+        r#"
+            [lints.cargo-origin]
+            registry = "deny"
+        "#,
+    );
+
+    p.cargo("check -Zcargo-origin-lints")
+        .masquerade_as_nightly_cargo(&["cargo-origin-lints"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] error in [..]config.toml: could not load config key `lints.cargo-origin.registry`")
+        .run();
+}
+
+#[cargo_test]
+fn package_overrides_origin() {
+    let p = make_project(
+        r#"
+            [lints.cargo-origin]
+            workspace = "deny-warnings"
+            path-deps = "deny-warnings"
+
+            [lints.cargo-origin.package]
+            local = "allow"
+            "bar@1.0.0" = "warn"
+        "#,
+    );
+
+    p.cargo("check -Zcargo-origin-lints")
+        .masquerade_as_nightly_cargo(&["cargo-origin-lints"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] unused variable: `y`")
+        .with_stderr_contains("[WARNING] unused variable: `x`")
+        .with_stderr_does_not_contain("[..]unused variable: `z`[..]")
+        .run();
+}
+
+#[cargo_test]
+fn invalid_package_spec() {
+    let p = make_project(
+        r#"
+            [lints.cargo-origin.package]
+            "bar@not-a-version" = "allow"
+        "#,
+    );
+
+    p.cargo("check -Zcargo-origin-lints")
+        .masquerade_as_nightly_cargo(&["cargo-origin-lints"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] invalid package ID spec `bar@not-a-version` in `lints.cargo-origin.package`",
+        )
+        .run();
+}
@@ -0,0 +1,97 @@
+//! Tests for the `cargo owner-of` command.
+
+use cargo_test_support::{basic_manifest, project, Project};
+
+fn make_project() -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+            "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/build.rs", "fn main() {}")
+        .file("a/src/lib.rs", "mod util;")
+        .file("a/src/util/mod.rs", "")
+        .file("a/src/main.rs", "fn main() {}")
+        .file("a/src/bin/other.rs", "fn main() {}")
+        .file("a/tests/it.rs", "")
+        .file("a/README.md", "")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = make_project();
+    p.cargo("owner-of a/src/lib.rs")
+        .masquerade_as_nightly_cargo(&["cargo-owner-of"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo owner-of` command is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn source_files() {
+    let p = make_project();
+    p.cargo("owner-of -Zunstable-options a/src/lib.rs")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("path+file://[..]/foo/a#0.1.0 lib a")
+        .run();
+    p.cargo("owner-of -Zunstable-options a/src/bin/other.rs")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("path+file://[..]/foo/a#0.1.0 bin other")
+        .run();
+    p.cargo("owner-of -Zunstable-options a/tests/it.rs")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("path+file://[..]/foo/a#0.1.0 integration-test it")
+        .run();
+    p.cargo("owner-of -Zunstable-options a/build.rs")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("path+file://[..]/foo/a#0.1.0 build-script build-script-build")
+        .run();
+    p.cargo("owner-of -Zunstable-options src/lib.rs")
+        .cwd("b")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("path+file://[..]/foo/b#0.1.0 lib b")
+        .run();
+}
+
+#[cargo_test]
+fn module_files() {
+    let p = make_project();
+    p.cargo("owner-of -Zunstable-options a/src/util/mod.rs")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_unordered(
+            "\
+path+file://[..]/foo/a#0.1.0 lib a
+path+file://[..]/foo/a#0.1.0 bin a
+",
+        )
+        .run();
+    p.cargo("owner-of -Zunstable-options a/README.md")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout("path+file://[..]/foo/a#0.1.0")
+        .run();
+}
+
+#[cargo_test]
+fn not_in_workspace() {
+    let p = make_project();
+    p.cargo("owner-of -Zunstable-options Cargo.toml")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] `[ROOT]/foo/Cargo.toml` is not owned by any member of the workspace at `[ROOT]/foo`
+",
+        )
+        .run();
+}
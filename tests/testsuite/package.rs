@@ -1349,7 +1349,7 @@ fn package_two_kinds_of_deps() {
     p.cargo("package --no-verify").run();
 }
 
-#[cargo_test(nightly, reason = "exported_private_dependencies lint is unstable")]
+#[cargo_test]
 fn package_public_dep() {
     Package::new("bar", "1.0.0").publish();
     Package::new("baz", "1.0.0").publish();
@@ -1391,34 +1391,6 @@ readme = false
 name = "foo"
 path = "src/main.rs"
 
-[dependencies.bar]
-version = "1.0.0"
-
-[target.{host}.dependencies.baz]
-version = "1.0.0"
-"#,
-        cargo::core::manifest::MANIFEST_PREAMBLE,
-        host = rustc_host()
-    );
-    verify(&p, "package", rewritten_toml);
-
-    let rewritten_toml = format!(
-        r#"{}
-[package]
-edition = "2015"
-name = "foo"
-version = "0.0.1"
-build = false
-autobins = false
-autoexamples = false
-autotests = false
-autobenches = false
-readme = false
-
-[[bin]]
-name = "foo"
-path = "src/main.rs"
-
 [dependencies.bar]
 version = "1.0.0"
 public = true
@@ -1430,9 +1402,11 @@ public = true
         cargo::core::manifest::MANIFEST_PREAMBLE,
         host = rustc_host()
     );
-    verify(&p, "package -Zpublic-dependency", rewritten_toml);
+    verify(&p, "package", &rewritten_toml);
+    // The stabilized flag is still accepted, and changes nothing.
+    verify(&p, "package -Zpublic-dependency", &rewritten_toml);
 
-    fn verify(p: &cargo_test_support::Project, cmd: &str, rewritten_toml: String) {
+    fn verify(p: &cargo_test_support::Project, cmd: &str, rewritten_toml: &str) {
         p.cargo(cmd)
             .masquerade_as_nightly_cargo(&["public-dependency"])
             .run();
@@ -1441,7 +1415,7 @@ public = true
             f,
             "foo-0.0.1.crate",
             &["Cargo.toml", "Cargo.toml.orig", "Cargo.lock", "src/main.rs"],
-            &[("Cargo.toml", &rewritten_toml)],
+            &[("Cargo.toml", rewritten_toml)],
         );
     }
 }
@@ -2601,6 +2575,51 @@ fn reproducible_output() {
     }
 }
 
+#[cargo_test]
+fn output_hash() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+            "#,
+        )
+        .file("src/main.rs", r#"fn main() { println!("hello"); }"#)
+        .build();
+
+    p.cargo("package --no-verify --output-hash")
+        .masquerade_as_nightly_cargo(&["output-hash"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--output-hash` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+
+    p.cargo("package --no-verify --output-hash -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["output-hash"])
+        .with_stdout("[..]  [CWD]/target/package/foo-0.0.1.crate")
+        .run();
+    let crate_path = p.root().join("target/package/foo-0.0.1.crate");
+    let first = fs::read(&crate_path).unwrap();
+    assert!(
+        first[4..8].iter().all(|b| *b == 0),
+        "gzip mtime must be zero"
+    );
+
+    // Rewriting a file bumps its mtime, which must not affect the archive.
+    p.change_file("src/main.rs", r#"fn main() { println!("hello"); }"#);
+    p.cargo("package --no-verify").run();
+    let second = fs::read(&crate_path).unwrap();
+    assert_eq!(first, second);
+}
+
 #[cargo_test]
 fn package_with_resolver_and_metadata() {
     let p = project()
@@ -3002,3 +3002,114 @@ foo v0.0.0 ([ROOT]/foo)
 
     assert_eq!(p.read_file("Cargo.lock"), p.read_file("Cargo.lock.orig"));
 }
+
+#[cargo_test]
+fn patch_sets_from_cli() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                authors = []
+
+                [dependencies]
+                bar = "0.1.0"
+            "#,
+        )
+        .file(
+            "../.cargo/config.toml",
+            r#"
+                [patch-sets.local-bar.crates-io]
+                bar = { path = 'foo/bar' }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.1"))
+        .file("bar/src/lib.rs", r#""#)
+        .build();
+
+    p.cargo("check --enable-patch-set local-bar")
+        .masquerade_as_nightly_cargo(&["patch-sets"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--enable-patch-set` flag is unstable, pass `-Z patch-sets` to enable it
+",
+        )
+        .run();
+
+    p.cargo("check -Zpatch-sets --enable-patch-set missing")
+        .masquerade_as_nightly_cargo(&["patch-sets"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] patch set `missing` is not defined in `[patch-sets]`")
+        .run();
+
+    // Patch sets are only used when enabled.
+    p.cargo("check -Zpatch-sets")
+        .masquerade_as_nightly_cargo(&["patch-sets"])
+        .with_stderr_contains("[CHECKING] bar v0.1.0")
+        .run();
+
+    p.cargo("check -Zpatch-sets --enable-patch-set local-bar")
+        .masquerade_as_nightly_cargo(&["patch-sets"])
+        .with_stderr_contains("[CHECKING] bar v0.1.1 ([..])")
+        .run();
+}
+
+#[cargo_test]
+fn patch_sets_workspace_opt_in() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                authors = []
+
+                [dependencies]
+                bar = "0.1.0"
+            "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                enable-patch-sets = ["local-bar"]
+            "#,
+        )
+        .file(
+            "../.cargo/config.toml",
+            r#"
+                [patch.crates-io]
+                bar = { path = 'foo/bar' }
+
+                [patch-sets.local-bar.crates-io]
+                bar = { path = 'foo/bar2' }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.1"))
+        .file("bar/src/lib.rs", r#""#)
+        .file("bar2/Cargo.toml", &basic_manifest("bar", "0.1.2"))
+        .file("bar2/src/lib.rs", r#""#)
+        .build();
+
+    // Without `-Zpatch-sets`, only `[patch]` is used.
+    p.cargo("check")
+        .with_stderr_contains("[CHECKING] bar v0.1.1 ([..])")
+        .run();
+
+    // The patch set takes precedence over `[patch]`.
+    p.cargo("check -Zpatch-sets")
+        .masquerade_as_nightly_cargo(&["patch-sets"])
+        .with_stderr_contains("[CHECKING] bar v0.1.2 ([..])")
+        .run();
+}
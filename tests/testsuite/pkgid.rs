@@ -283,8 +283,16 @@ Please re-run this command with one of the following specifications:
 ",
         )
         .run();
-    // TODO, what should the `-p` value be here?
-    //p.cargo("update -p")
+    // A `?rev=` qualifier picks one of them, even as an abbreviated hash.
+    let rev1_spec = format!(
+        "git+{}?rev={}#0.5.0",
+        xyz_project.url(),
+        &rev1.to_string()[..8]
+    );
+    p.cargo(&format!("pkgid {rev1_spec}"))
+        .with_stdout(&format!("git+file://[..]/xyz?rev={rev1}#0.5.0"))
+        .run();
+    p.cargo(&format!("update -p {rev1_spec}")).run();
 }
 
 // Keep Package ID format in sync among
@@ -292,6 +300,53 @@ Please re-run this command with one of the following specifications:
 // * Package ID specifications
 // * machine-readable message via `--message-format=json`
 // * `cargo metadata` output
+#[cargo_test]
+fn json_output() {
+    Package::new("crates-io", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [dependencies]
+                crates-io = "0.1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("pkgid --json crates-io")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--json` flag is unstable, [..]
+See [..]
+",
+        )
+        .run();
+
+    p.cargo("pkgid -Zunstable-options --json crates-io")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+{
+  "name": "crates-io",
+  "version": "0.1.0",
+  "url": "https://github.com/rust-lang/crates.io-index",
+  "source_kind": "registry",
+  "git_ref": null
+}
+"#,
+        )
+        .run();
+}
+
 #[cargo_test]
 fn pkgid_json_message_metadata_consistency() {
     let p = project()
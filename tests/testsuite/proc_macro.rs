@@ -508,3 +508,91 @@ fn proc_macro_built_once() {
         )
         .run();
 }
+
+#[cargo_test]
+fn proc_macro_inputs() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [dependencies]
+                pm = { path = "pm" }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"fn main() { println!("{}", pm::data!()); }"#,
+        )
+        .file("data.txt", "hello")
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+                edition = "2018"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file(
+            "pm/src/lib.rs",
+            r#"
+                extern crate proc_macro;
+                use proc_macro::TokenStream;
+                use std::io::Write;
+                use std::path::Path;
+
+                #[proc_macro]
+                pub fn data(_: TokenStream) -> TokenStream {
+                    let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+                    let path = Path::new(&dir).join("data.txt");
+                    if let Some(inputs) = std::env::var_os("CARGO_PROC_MACRO_INPUTS") {
+                        let mut file = std::fs::OpenOptions::new()
+                            .append(true)
+                            .create(true)
+                            .open(inputs)
+                            .unwrap();
+                        writeln!(file, "cargo::rerun-if-changed={}", path.display()).unwrap();
+                        writeln!(file, "cargo::rerun-if-env-changed=DATA_SUFFIX").unwrap();
+                    }
+                    let data = std::fs::read_to_string(&path).unwrap();
+                    let suffix = std::env::var("DATA_SUFFIX").unwrap_or_default();
+                    format!("{:?}", format!("{}{}", data, suffix)).parse().unwrap()
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("run -Zproc-macro-inputs")
+        .masquerade_as_nightly_cargo(&["proc-macro-inputs"])
+        .with_stdout("hello\n")
+        .run();
+
+    p.change_file("data.txt", "bye");
+    p.cargo("run -Zproc-macro-inputs")
+        .masquerade_as_nightly_cargo(&["proc-macro-inputs"])
+        .with_stderr_contains("[COMPILING] foo v0.1.0 ([CWD])")
+        .with_stdout("bye\n")
+        .run();
+
+    p.cargo("run -Zproc-macro-inputs")
+        .masquerade_as_nightly_cargo(&["proc-macro-inputs"])
+        .env("DATA_SUFFIX", "!")
+        .with_stderr_contains("[COMPILING] foo v0.1.0 ([CWD])")
+        .with_stdout("bye!\n")
+        .run();
+
+    p.cargo("run -Zproc-macro-inputs")
+        .masquerade_as_nightly_cargo(&["proc-macro-inputs"])
+        .env("DATA_SUFFIX", "!")
+        .with_stderr_does_not_contain("[COMPILING] foo [..]")
+        .with_stdout("bye!\n")
+        .run();
+}
@@ -0,0 +1,208 @@
+//! Tests for `-Zprofile-binary-size`.
+
+use cargo_test_support::project;
+use cargo_test_support::tools;
+
+#[cargo_test]
+fn gated_manifest() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.release]
+                strip-keep-symbols = ["plugin_init"]
+           "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-binary-size"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  feature `profile-binary-size` is required",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn gated_config_toml() {
+    let p = project()
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [profile.release]
+                compress-debuginfo = "zstd"
+           "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-binary-size"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] config profile `release` is not valid (defined in `[CWD]/.cargo/config.toml`)
+
+Caused by:
+  feature `profile-binary-size` is required",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_compress_debuginfo() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-binary-size"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.release]
+                compress-debuginfo = "gzip"
+           "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-binary-size"])
+        .with_status(101)
+        .with_stderr_contains(
+            "  `profile.release.compress-debuginfo` setting of `gzip` is not a valid setting, \
+             must be `none`, `zlib` or `zstd`",
+        )
+        .run();
+}
+
+#[cargo_test(nightly, reason = "-Zdebuginfo-compression is unstable")]
+fn compress_debuginfo() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-binary-size"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.dev]
+                compress-debuginfo = "zlib"
+           "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo(&["profile-binary-size"])
+        .with_stderr_contains("[RUNNING] `rustc [..]-Zdebuginfo-compression=zlib[..]`")
+        .run();
+}
+
+#[cargo_test]
+fn strip_keep_symbols() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-binary-size"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.release]
+                strip = "symbols"
+                strip-keep-symbols = ["plugin_init", "plugin_version"]
+           "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build --release -v")
+        .env("STRIP", tools::echo())
+        .masquerade_as_nightly_cargo(&["profile-binary-size"])
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo --edition=2015 src/lib.rs [..]-C strip=symbols[..]`",
+        )
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo --edition=2015 src/main.rs [..]-C strip=debuginfo[..]`",
+        )
+        .with_stderr_contains(
+            "[RUNNING] `[..] --strip-all --keep-symbol=plugin_init --keep-symbol=plugin_version \
+             [CWD]/target/release/deps/foo-[..][EXE]`",
+        )
+        .with_stderr_does_not_contain("[RUNNING] `[..] --strip-all [..]libfoo[..]`")
+        .run();
+
+    // Changing the kept symbols rebuilds the binary.
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["profile-binary-size"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+
+            [profile.release]
+            strip = "symbols"
+            strip-keep-symbols = ["plugin_init"]
+        "#,
+    );
+    p.cargo("build --release -v")
+        .env("STRIP", tools::echo())
+        .masquerade_as_nightly_cargo(&["profile-binary-size"])
+        .with_stderr_contains(
+            "[RUNNING] `[..] --strip-all --keep-symbol=plugin_init \
+             [CWD]/target/release/deps/foo-[..][EXE]`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn strip_keep_symbols_without_strip() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-binary-size"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.release]
+                strip-keep-symbols = ["plugin_init"]
+           "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build --release -v")
+        .env("STRIP", tools::echo())
+        .masquerade_as_nightly_cargo(&["profile-binary-size"])
+        .with_stderr_does_not_contain("[..]--strip-all[..]")
+        .run();
+}
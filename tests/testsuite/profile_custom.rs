@@ -755,3 +755,117 @@ fn legacy_rustc() {
         )
         .run();
 }
+
+#[cargo_test]
+fn profile_stack_requires_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.ci]
+                inherits = ["release", "thin"]
+
+                [profile.thin]
+                lto = "thin"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build --profile ci")
+        .with_status(101)
+        .with_stderr_contains("[..]feature `profile-stack` is required[..]")
+        .run();
+}
+
+#[cargo_test]
+fn profile_stack() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-stack"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.ci]
+                inherits = ["release", "small", "fast-link"]
+                debug-assertions = true
+
+                [profile.small]
+                opt-level = "s"
+                codegen-units = 4
+
+                [profile.fast-link]
+                codegen-units = 16
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    // Fragments are merged in order over the parent, and the profile itself
+    // comes last.
+    p.cargo("build --profile ci -v")
+        .masquerade_as_nightly_cargo(&["profile-stack"])
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo [..]-C opt-level=s[..]-C codegen-units=16[..]-C debug-assertions=on[..]",
+        )
+        .with_stderr_contains("[FINISHED] `ci` profile [optimized] target(s) in [..]")
+        .run();
+
+    // A fragment isn't a profile on its own.
+    p.cargo("build --profile small")
+        .masquerade_as_nightly_cargo(&["profile-stack"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] profile `small` is missing an `inherits` directive \
+(`inherits` is required for all profiles except `dev` or `release`)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn profile_stack_fragment_with_inherits() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-stack"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.ci]
+                inherits = ["release", "thin"]
+
+                [profile.thin]
+                inherits = "release"
+                lto = "thin"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build --profile ci")
+        .masquerade_as_nightly_cargo(&["profile-stack"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] profile `ci` stacks `thin`, but that profile has an `inherits` directive \
+(only the first profile in `inherits` may inherit from other profiles)
+",
+        )
+        .run();
+}
@@ -160,3 +160,81 @@ fn never_progress() {
         .with_stderr_does_not_contain("[BUILDING] [..] [..]/4: [..]")
         .run();
 }
+
+#[cargo_test]
+fn ui_compact_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check --ui compact")
+        .masquerade_as_nightly_cargo(&["ui"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--ui` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn ui_unknown_mode() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check -Zunstable-options --ui fancy")
+        .masquerade_as_nightly_cargo(&["ui"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] argument for --ui must be human or compact, but found `fancy`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn ui_compact_only_prints_diagnostics() {
+    Package::new("dep1", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                dep1 = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "fn unused() {}")
+        .build();
+
+    p.cargo("check -Zunstable-options --ui compact")
+        .masquerade_as_nightly_cargo(&["ui"])
+        .with_stderr_contains("[WARNING] function `unused` is never used")
+        .with_stderr_does_not_contain("[UPDATING] [..]")
+        .with_stderr_does_not_contain("[CHECKING] [..]")
+        .with_stderr_does_not_contain("[FINISHED] [..]")
+        .run();
+
+    // Durations of the rebuilt units are recorded for the next estimate.
+    let durations = p.read_file("target/.unit-durations.json");
+    assert!(durations.contains("dep1 v1.0.0"), "{durations}");
+    assert!(durations.contains("foo v0.1.0"), "{durations}");
+
+    // The reasons why units are rebuilt are still printed.
+    p.change_file("src/lib.rs", "pub fn used() {}");
+    p.cargo("check -v -Zunstable-options --ui compact")
+        .masquerade_as_nightly_cargo(&["ui"])
+        .with_stderr_contains(
+            "[DIRTY] foo v0.1.0 ([CWD]): the file `src/lib.rs` has changed ([..])",
+        )
+        .with_stderr_does_not_contain("[CHECKING] [..]")
+        .with_stderr_does_not_contain("[FRESH] [..]")
+        .run();
+
+    p.cargo("check")
+        .with_stderr_contains("[FINISHED] [..]")
+        .run();
+}
@@ -8,13 +8,14 @@ fn exported_priv_warning() {
     Package::new("priv_dep", "0.1.0")
         .file("src/lib.rs", "pub struct FromPriv;")
         .publish();
+    Package::new("pub_dep", "0.1.0")
+        .file("src/lib.rs", "pub struct FromPub;")
+        .publish();
 
     let p = project()
         .file(
             "Cargo.toml",
             r#"
-                cargo-features = ["public-dependency"]
-
                 [package]
                 name = "foo"
                 version = "0.0.1"
@@ -22,6 +23,7 @@ fn exported_priv_warning() {
 
                 [dependencies]
                 priv_dep = "0.1.0"
+                pub_dep = { version = "0.1.0", public = true }
             "#,
         )
         .file(
@@ -34,7 +36,6 @@ fn exported_priv_warning() {
         .build();
 
     p.cargo("check --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_stderr_contains(
             "\
 src/lib.rs:3:13: warning: type `[..]FromPriv` from private dependency 'priv_dep' in public interface
@@ -53,8 +54,6 @@ fn exported_pub_dep() {
         .file(
             "Cargo.toml",
             r#"
-                cargo-features = ["public-dependency"]
-
                 [package]
                 name = "foo"
                 version = "0.0.1"
@@ -74,7 +73,6 @@ fn exported_pub_dep() {
         .build();
 
     p.cargo("check --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_stderr(
             "\
 [UPDATING] `[..]` index
@@ -90,34 +88,37 @@ fn exported_pub_dep() {
 }
 
 #[cargo_test]
-pub fn requires_nightly_cargo() {
+fn cargo_feature_stabilized() {
     let p = project()
         .file(
             "Cargo.toml",
             r#"
                 cargo-features = ["public-dependency"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
             "#,
         )
         .file("src/lib.rs", "")
         .build();
 
     p.cargo("check --message-format=short")
-        .with_status(101)
         .with_stderr(
             "\
-error: failed to parse manifest at `[..]`
-
-Caused by:
-  the cargo feature `public-dependency` requires a nightly version of Cargo, but this is the `stable` channel
-  See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
-  See https://doc.rust-lang.org/[..]cargo/reference/unstable.html#public-dependency for more information about using this feature.
-"
+[WARNING] the cargo feature `public-dependency` has been stabilized in the 1.80 \
+release and is no longer necessary to be listed in the manifest
+  See https://doc.rust-lang.org/[..]cargo/reference/specifying-dependencies.html#public-dependencies for more information about using this feature.
+[CHECKING] foo v0.0.1 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+",
         )
         .run()
 }
 
 #[cargo_test]
-fn requires_feature() {
+fn public_on_stable() {
     Package::new("pub_dep", "0.1.0")
         .file("src/lib.rs", "")
         .publish();
@@ -139,10 +140,8 @@ fn requires_feature() {
         .build();
 
     p.cargo("check --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_stderr(
             "\
-[WARNING] ignoring `public` on dependency pub_dep, pass `-Zpublic-dependency` to enable support for it
 [UPDATING] `[..]` index
 [LOCKING] 2 packages to latest compatible versions
 [DOWNLOADING] crates ...
@@ -177,7 +176,7 @@ fn pub_dev_dependency() {
             "#,
         )
         .file(
-            "src/lib.rs",
+            "tests/mod.rs",
             "
             extern crate pub_dep;
             pub fn use_pub(_: pub_dep::FromPub) {}
@@ -186,7 +185,6 @@ fn pub_dev_dependency() {
         .build();
 
     p.cargo("check --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_status(101)
         .with_stderr(
             "\
@@ -228,13 +226,13 @@ fn pub_dev_dependency_without_feature() {
         .build();
 
     p.cargo("check --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
+        .with_status(101)
         .with_stderr(
             "\
-[WARNING] 'public' specifier can only be used on regular dependencies, not dev-dependencies
-[UPDATING] `[..]` index
-[LOCKING] 2 packages to latest compatible versions
-[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+error: failed to parse manifest at `[..]`
+
+Caused by:
+  'public' specifier can only be used on regular dependencies, not dev-dependencies
 ",
         )
         .run()
@@ -256,8 +254,6 @@ fn workspace_pub_disallowed() {
         .file(
             "Cargo.toml",
             r#"
-                cargo-features = ["public-dependency"]
-
                 [package]
                 name = "foo"
                 version = "0.0.1"
@@ -286,7 +282,6 @@ fn workspace_pub_disallowed() {
         .build();
 
     p.cargo("check")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_status(101)
         .with_stderr(
             "\
@@ -309,8 +304,6 @@ fn allow_priv_in_tests() {
         .file(
             "Cargo.toml",
             r#"
-                cargo-features = ["public-dependency"]
-
                 [package]
                 name = "foo"
                 version = "0.0.1"
@@ -330,7 +323,6 @@ fn allow_priv_in_tests() {
         .build();
 
     p.cargo("check --tests --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_stderr(
             "\
 [UPDATING] `[..]` index
@@ -355,8 +347,6 @@ fn allow_priv_in_benchs() {
         .file(
             "Cargo.toml",
             r#"
-                cargo-features = ["public-dependency"]
-
                 [package]
                 name = "foo"
                 version = "0.0.1"
@@ -376,7 +366,6 @@ fn allow_priv_in_benchs() {
         .build();
 
     p.cargo("check --benches --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_stderr(
             "\
 [UPDATING] `[..]` index
@@ -401,8 +390,6 @@ fn allow_priv_in_bins() {
         .file(
             "Cargo.toml",
             r#"
-                cargo-features = ["public-dependency"]
-
                 [package]
                 name = "foo"
                 version = "0.0.1"
@@ -423,7 +410,6 @@ fn allow_priv_in_bins() {
         .build();
 
     p.cargo("check --bins --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_stderr(
             "\
 [UPDATING] `[..]` index
@@ -448,8 +434,6 @@ fn allow_priv_in_examples() {
         .file(
             "Cargo.toml",
             r#"
-                cargo-features = ["public-dependency"]
-
                 [package]
                 name = "foo"
                 version = "0.0.1"
@@ -470,7 +454,6 @@ fn allow_priv_in_examples() {
         .build();
 
     p.cargo("check --examples --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_stderr(
             "\
 [UPDATING] `[..]` index
@@ -495,8 +478,6 @@ fn allow_priv_in_custom_build() {
         .file(
             "Cargo.toml",
             r#"
-                cargo-features = ["public-dependency"]
-
                 [package]
                 name = "foo"
                 version = "0.0.1"
@@ -518,7 +499,6 @@ fn allow_priv_in_custom_build() {
         .build();
 
     p.cargo("check --all-targets --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_stderr(
             "\
 [UPDATING] `[..]` index
@@ -539,7 +519,6 @@ fn publish_package_with_public_dependency() {
         .file("src/lib.rs", "pub struct FromPub;")
         .publish();
     Package::new("bar", "0.1.0")
-        .cargo_feature("public-dependency")
         .add_dep(Dependency::new("pub_bar", "0.1.0").public(true))
         .file(
             "src/lib.rs",
@@ -554,7 +533,6 @@ fn publish_package_with_public_dependency() {
         .file(
             "Cargo.toml",
             r#"
-            cargo-features = ["public-dependency"]
             [package]
             name = "foo"
             version = "0.0.1"
@@ -573,7 +551,6 @@ fn publish_package_with_public_dependency() {
         .build();
 
     p.cargo("check --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
         .with_stderr(
             "\
 [UPDATING] `[..]` index
@@ -630,8 +607,12 @@ fn verify_mix_cargo_feature_z() {
         )
         .build();
 
+    // The stabilized feature is still accepted in both places.
     p.cargo("check -Zpublic-dependency --message-format=short")
         .masquerade_as_nightly_cargo(&["public-dependency"])
+        .with_stderr_contains(
+            "[WARNING] flag `-Z public-dependency` has been stabilized in the 1.80 release[..]",
+        )
         .with_stderr_contains(
             "\
 src/lib.rs:5:13: warning: type `FromDep` from private dependency 'dep' in public interface
@@ -642,7 +623,7 @@ src/lib.rs:6:13: warning: type `FromPriv` from private dependency 'priv_dep' in
 }
 
 #[cargo_test(nightly, reason = "exported_private_dependencies lint is unstable")]
-fn verify_z_public_dependency() {
+fn verify_public_dependency() {
     Package::new("dep", "0.1.0")
         .file("src/lib.rs", "pub struct FromDep;")
         .publish();
@@ -680,8 +661,7 @@ fn verify_z_public_dependency() {
         )
         .build();
 
-    p.cargo("check -Zpublic-dependency --message-format=short")
-        .masquerade_as_nightly_cargo(&["public-dependency"])
+    p.cargo("check --message-format=short")
         .with_stderr_contains(
             "\
 src/lib.rs:5:13: warning: type `FromDep` from private dependency 'dep' in public interface
@@ -690,3 +670,59 @@ src/lib.rs:6:13: warning: type `FromPriv` from private dependency 'priv_dep' in
         )
         .run();
 }
+
+#[cargo_test]
+fn conflicting_public_dependencies() {
+    Package::new("c", "1.0.0").publish();
+    Package::new("c", "2.0.0").publish();
+    Package::new("a", "1.0.0")
+        .add_dep(Dependency::new("c", "1.0").public(true))
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                a = { version = "1.0", public = true }
+                c = { version = "2.0", public = true }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .with_stderr_contains(
+            "\
+[WARNING] package `foo v0.0.1 ([CWD])` publicly depends on two different packages named `c`:
+  `foo v0.0.1 ([CWD])` -> `c v2.0.0`
+  `foo v0.0.1 ([CWD])` -> `a v1.0.0` -> `c v1.0.0`
+",
+        )
+        .with_stderr_contains("[FINISHED] [..]")
+        .run();
+
+    // A private dependency on another version is fine.
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+
+            [dependencies]
+            a = { version = "1.0", public = true }
+            c = "2.0"
+        "#,
+    );
+    p.cargo("check")
+        .with_stderr_does_not_contain("[WARNING] [..]publicly depends[..]")
+        .with_stderr_contains("[FINISHED] [..]")
+        .run();
+}
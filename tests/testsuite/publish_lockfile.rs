@@ -607,3 +607,205 @@ See [..]
         &[],
     );
 }
+
+#[cargo_test]
+fn publish_lockfile_field() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                license = "MIT"
+                description = "foo"
+                publish-lockfile = true
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("package -l")
+        .masquerade_as_nightly_cargo(&["lockfile-policy"])
+        .with_status(101)
+        .with_stderr_contains("  feature `lockfile-policy` is required")
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["lockfile-policy"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+            license = "MIT"
+            description = "foo"
+            publish-lockfile = true
+        "#,
+    );
+    p.cargo("package -l")
+        .masquerade_as_nightly_cargo(&["lockfile-policy"])
+        .with_stdout(
+            "\
+Cargo.lock
+Cargo.toml
+Cargo.toml.orig
+src/lib.rs
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn publish_lockfile_field_in_dependency() {
+    // Crates published before the removal of the old `publish-lockfile`
+    // feature may still carry the key.
+    Package::new("bar", "0.0.1")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.0.1"
+                edition = "2015"
+                publish-lockfile = true
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = "0.0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .with_stderr_contains("[CHECKING] bar v0.0.1")
+        .with_stderr_contains("[FINISHED] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn publish_lockfile_inherited() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["bar"]
+
+                [workspace.package]
+                publish-lockfile = false
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                cargo-features = ["lockfile-policy"]
+
+                [package]
+                name = "bar"
+                version = "0.0.1"
+                edition = "2015"
+                license = "MIT"
+                description = "bar"
+                publish-lockfile.workspace = true
+            "#,
+        )
+        .file("bar/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("package -l -p bar")
+        .masquerade_as_nightly_cargo(&["lockfile-policy"])
+        .with_stdout(
+            "\
+Cargo.toml
+Cargo.toml.orig
+src/main.rs
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn publish_lockfile_config() {
+    let p = project()
+        .file("Cargo.toml", &pl_manifest("foo", "0.0.1", ""))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [package]
+                publish-lockfile = true
+            "#,
+        )
+        .build();
+
+    p.cargo("package -l")
+        .with_stderr(
+            "[WARNING] `package.publish-lockfile` requires `-Zunstable-options`, ignoring it",
+        )
+        .with_stdout(
+            "\
+Cargo.toml
+Cargo.toml.orig
+src/lib.rs
+",
+        )
+        .run();
+
+    p.cargo("package -l -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["lockfile-policy"])
+        .with_stdout(
+            "\
+Cargo.lock
+Cargo.toml
+Cargo.toml.orig
+src/lib.rs
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn exclude_lockfile() {
+    let p = project()
+        .file("Cargo.toml", &pl_manifest("foo", "0.0.1", ""))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("package -l --exclude-lockfile")
+        .masquerade_as_nightly_cargo(&["lockfile-policy"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--exclude-lockfile` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+
+    p.cargo("package --exclude-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["lockfile-policy"])
+        .run();
+    let f = File::open(&p.root().join("target/package/foo-0.0.1.crate")).unwrap();
+    validate_crate_contents(
+        f,
+        "foo-0.0.1.crate",
+        &["Cargo.toml", "Cargo.toml.orig", "src/main.rs"],
+        &[],
+    );
+}
@@ -0,0 +1,223 @@
+//! Tests for the `cargo query` command.
+
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+fn make_project() -> cargo_test_support::Project {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").dep("a", "1.0").publish();
+    Package::new("devdep", "1.0.0").publish();
+    Package::new("builddep", "1.0.0").publish();
+
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["bar"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                b = "1.0"
+                bar = { path = "bar" }
+
+                [dev-dependencies]
+                devdep = "1.0"
+
+                [build-dependencies]
+                builddep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("query all()")
+        .masquerade_as_nightly_cargo(&["cargo-query"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo query` command is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn deps_kind_minus_workspace() {
+    let p = make_project();
+
+    p.cargo("query -Zunstable-options")
+        .arg("deps(foo) & kind(normal) - workspace()")
+        .masquerade_as_nightly_cargo(&["cargo-query"])
+        .with_stdout(
+            "\
+a v1.0.0
+b v1.0.0
+",
+        )
+        .run();
+
+    p.cargo("query -Zunstable-options")
+        .arg("kind(dev) | kind(build)")
+        .masquerade_as_nightly_cargo(&["cargo-query"])
+        .with_stdout(
+            "\
+builddep v1.0.0
+devdep v1.0.0
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn rdeps_and_source() {
+    let p = make_project();
+
+    p.cargo("query -Zunstable-options")
+        .arg("rdeps(a) & source(path)")
+        .masquerade_as_nightly_cargo(&["cargo-query"])
+        .with_stdout(
+            "\
+bar v0.1.0 ([CWD]/bar)
+foo v0.1.0 ([CWD])
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn tree_and_json() {
+    let p = make_project();
+
+    p.cargo("query -Zunstable-options --format tree")
+        .arg("deps(b)")
+        .masquerade_as_nightly_cargo(&["cargo-query"])
+        .with_stdout(
+            "\
+b v1.0.0
+    a v1.0.0
+",
+        )
+        .run();
+
+    p.cargo("query -Zunstable-options --format json")
+        .arg("deps(b)")
+        .masquerade_as_nightly_cargo(&["cargo-query"])
+        .with_json(
+            r#"
+[
+  {
+    "id": "registry+https://github.com/rust-lang/crates.io-index#a@1.0.0",
+    "name": "a",
+    "version": "1.0.0",
+    "source": "registry+https://github.com/rust-lang/crates.io-index",
+    "dependencies": []
+  },
+  {
+    "id": "registry+https://github.com/rust-lang/crates.io-index#b@1.0.0",
+    "name": "b",
+    "version": "1.0.0",
+    "source": "registry+https://github.com/rust-lang/crates.io-index",
+    "dependencies": [
+      "registry+https://github.com/rust-lang/crates.io-index#a@1.0.0"
+    ]
+  }
+]
+"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn tree_with_cycle() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dev-dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                foo = { path = ".." }
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("query -Zunstable-options --format tree")
+        .arg("all()")
+        .masquerade_as_nightly_cargo(&["cargo-query"])
+        .with_stdout(
+            "\
+bar v0.1.0 ([CWD]/bar)
+    foo v0.1.0 ([CWD])
+        bar v0.1.0 ([CWD]/bar) (*)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_query() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("query -Zunstable-options")
+        .arg("deps(foo")
+        .masquerade_as_nightly_cargo(&["cargo-query"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] invalid query `deps(foo`
+
+Caused by:
+  expected `)`, found end of expression
+",
+        )
+        .run();
+
+    p.cargo("query -Zunstable-options")
+        .arg("nothere")
+        .masquerade_as_nightly_cargo(&["cargo-query"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] package ID specification `nothere` did not match any packages",
+        )
+        .run();
+}
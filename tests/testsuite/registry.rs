@@ -1,6 +1,12 @@
 //! Tests for normal registry dependencies.
 
-use cargo::core::SourceId;
+use crate::config::GlobalContextBuilder;
+use cargo::core::registry::PackageRegistry;
+use cargo::core::{Registry, SourceId, Workspace};
+use cargo::ops;
+use cargo::sources::source::QueryKind;
+use cargo::util::cache_lock::CacheLockMode;
+use cargo::util::{CancellationToken, Cancelled};
 use cargo_test_support::cargo_process;
 use cargo_test_support::paths::{self, CargoPathExt};
 use cargo_test_support::registry::{
@@ -14,6 +20,9 @@ use std::fs::{self, File};
 use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::task::Poll;
+use std::thread;
+use std::time::Duration;
 
 fn setup_http() -> TestRegistry {
     RegistryBuilder::new().http_index().build()
@@ -3833,3 +3842,68 @@ fn builtin_source_replacement() {
         )
         .run();
 }
+
+#[cargo_test]
+fn cancelled_http() {
+    let _server = setup_http();
+    Package::new("bar", "0.0.1").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = "0.0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    let mut gctx = GlobalContextBuilder::new().cwd(p.root()).build();
+    let token = CancellationToken::new();
+    gctx.set_cancellation_token(token.clone());
+    token.cancel();
+
+    let ws = Workspace::new(&p.root().join("Cargo.toml"), &gctx).unwrap();
+    let err = ops::resolve_ws(&ws).unwrap_err();
+    assert!(err.chain().any(|e| e.is::<Cancelled>()), "{err:?}");
+    assert!(!p.root().join("Cargo.lock").exists());
+}
+
+#[cargo_test]
+fn poll_ready_http() {
+    let _server = setup_http();
+    Package::new("bar", "0.0.1").publish();
+    Package::new("bar", "0.0.2").publish();
+
+    let gctx = GlobalContextBuilder::new().build();
+    let _lock = gctx
+        .acquire_package_cache_lock(CacheLockMode::DownloadExclusive)
+        .unwrap();
+    let mut registry = PackageRegistry::new(&gctx).unwrap();
+    let source_id = SourceId::crates_io(&gctx).unwrap();
+    let dep = cargo::core::Dependency::parse("bar", Some("0.0.*"), source_id).unwrap();
+
+    let summaries = loop {
+        match registry.query_vec(&dep, QueryKind::Exact) {
+            Poll::Ready(summaries) => break summaries.unwrap(),
+            Poll::Pending => loop {
+                match registry.poll_ready() {
+                    Poll::Ready(result) => break result.unwrap(),
+                    // The caller keeps control while the index is downloaded.
+                    Poll::Pending => thread::sleep(Duration::from_millis(1)),
+                }
+            },
+        }
+    };
+    let mut versions: Vec<_> = summaries
+        .iter()
+        .map(|s| s.as_summary().version().to_string())
+        .collect();
+    versions.sort();
+    assert_eq!(versions, ["0.0.1", "0.0.2"]);
+}
@@ -0,0 +1,187 @@
+//! Tests for `-Zresolve-cache`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+
+const HIT: &str = "[..]reusing cached resolve[..]";
+const STALE: &str = "[..]resolve cache is stale[..]";
+
+fn make_project() -> cargo_test_support::Project {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("baz", "1.0.0").publish();
+
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                local = { path = "local" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("local/Cargo.toml", &basic_manifest("local", "0.1.0"))
+        .file("local/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn not_used_without_flag() {
+    let p = make_project();
+
+    p.cargo("check").run();
+    p.cargo("check")
+        .env("CARGO_LOG", "cargo::ops::resolve_cache=debug")
+        .with_stderr_does_not_contain(HIT)
+        .run();
+    assert!(!p.root().join("target/.resolve_cache.json").exists());
+}
+
+#[cargo_test]
+fn reuses_resolve() {
+    let p = make_project();
+
+    p.cargo("check -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .run();
+    assert!(p.root().join("target/.resolve_cache.json").is_file());
+
+    p.cargo("check -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .env("CARGO_LOG", "cargo::ops::resolve_cache=debug")
+        .with_stderr_contains(HIT)
+        .run();
+
+    p.cargo("metadata -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .env("CARGO_LOG", "cargo::ops::resolve_cache=debug")
+        .with_stderr_contains(HIT)
+        .run();
+}
+
+#[cargo_test]
+fn manifest_change_resolves_again() {
+    let p = make_project();
+
+    p.cargo("check -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            bar = "1.0"
+            baz = "1.0"
+            local = { path = "local" }
+        "#,
+    );
+    p.cargo("check -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .env("CARGO_LOG", "cargo::ops::resolve_cache=debug")
+        .with_stderr_contains(STALE)
+        .with_stderr_does_not_contain(HIT)
+        .run();
+    assert!(p.read_lockfile().contains("name = \"baz\""));
+}
+
+#[cargo_test]
+fn path_dependency_change_resolves_again() {
+    let p = make_project();
+
+    p.cargo("check -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .run();
+
+    p.change_file(
+        "local/Cargo.toml",
+        r#"
+            [package]
+            name = "local"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            baz = "1.0"
+        "#,
+    );
+    p.cargo("check -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .env("CARGO_LOG", "cargo::ops::resolve_cache=debug")
+        .with_stderr_contains(STALE)
+        .with_stderr_does_not_contain(HIT)
+        .run();
+    assert!(p.read_lockfile().contains("name = \"baz\""));
+}
+
+#[cargo_test]
+fn lockfile_change_resolves_again() {
+    let p = make_project();
+
+    p.cargo("check -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .run();
+
+    Package::new("bar", "1.0.1").publish();
+    p.cargo("update bar").run();
+    p.cargo("check -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .env("CARGO_LOG", "cargo::ops::resolve_cache=debug")
+        .with_stderr_contains(STALE)
+        .with_stderr_does_not_contain(HIT)
+        .run();
+}
+
+#[cargo_test]
+fn validates_required_features() {
+    Package::new("bar", "1.0.0").feature("std", &[]).publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+
+                [[bin]]
+                name = "foo"
+                required-features = ["bar/std", "bar/nope", "missing/std"]
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("check -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .run();
+
+    p.cargo("check -Zresolve-cache")
+        .masquerade_as_nightly_cargo(&["resolve-cache"])
+        .env("CARGO_LOG", "cargo::ops::resolve_cache=debug")
+        .with_stderr_contains(HIT)
+        .with_stderr_contains(
+            "[WARNING] invalid feature `bar/nope` in required-features of target `foo`: \
+             feature `nope` does not exist in package `bar v1.0.0`",
+        )
+        .with_stderr_contains(
+            "[WARNING] invalid feature `missing/std` in required-features of target `foo`: \
+             dependency `missing` does not exist",
+        )
+        .with_stderr_does_not_contain("[..]`bar/std`[..]")
+        .run();
+}
@@ -411,6 +411,152 @@ Caused by:
         .run();
 }
 
+#[cargo_test]
+fn run_preset() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["run-args"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [run.args]
+                serve = ["--port", "8080"]
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                fn main() {
+                    let args: Vec<_> = std::env::args().skip(1).collect();
+                    println!("{}", args.join(" "));
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("run -Zunstable-options --preset serve -- --verbose")
+        .masquerade_as_nightly_cargo(&["run-args"])
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+[RUNNING] `target/debug/foo[EXE] --port 8080 --verbose`",
+        )
+        .with_stdout("--port 8080 --verbose")
+        .run();
+
+    p.cargo("run -Zunstable-options --preset serv")
+        .masquerade_as_nightly_cargo(&["run-args"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] no argument preset named `serv` in package `foo`
+available presets: serve
+",
+        )
+        .run();
+
+    p.cargo("run --preset serve")
+        .masquerade_as_nightly_cargo(&["run-args"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--preset` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn run_no_build() {
+    let p = project()
+        .file("src/main.rs", r#"fn main() { println!("hello"); }"#)
+        .build();
+
+    p.cargo("run --no-build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--no-build` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+
+    p.cargo("run -Zunstable-options --no-build")
+        .masquerade_as_nightly_cargo(&["no-build"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[DIRTY] foo v0.0.1 ([CWD]): fresh build
+[ERROR] the bin target `foo` of `foo v0.0.1 ([CWD])` is not up to date, and `--no-build` doesn't build it
+",
+        )
+        .run();
+
+    p.cargo("build").run();
+    p.cargo("run -Zunstable-options --no-build")
+        .masquerade_as_nightly_cargo(&["no-build"])
+        .with_stderr(
+            "\
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [..]
+[RUNNING] `target/debug/foo[EXE]`",
+        )
+        .with_stdout("hello")
+        .run();
+
+    // A stale artifact isn't run, nor rebuilt.
+    p.change_file("src/main.rs", r#"fn main() { println!("bye"); }"#);
+    p.cargo("run -Zunstable-options --no-build")
+        .masquerade_as_nightly_cargo(&["no-build"])
+        .with_status(101)
+        .with_stderr_contains("[DIRTY] foo v0.0.1 ([CWD]): the file `src/main.rs` has changed ([..])")
+        .with_stderr_contains(
+            "[ERROR] the bin target `foo` of `foo v0.0.1 ([CWD])` is not up to date, and `--no-build` doesn't build it",
+        )
+        .with_stderr_does_not_contain("[COMPILING] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn run_preset_requires_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [run.args]
+                serve = ["--port", "8080"]
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("run")
+        .masquerade_as_nightly_cargo(&["run-args"])
+        .with_status(101)
+        .with_stderr(
+            r#"[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  feature `run-args` is required
+
+  The package requires the Cargo feature called `run-args`, but that feature is not stabilized in this version of Cargo ([..]).
+  Consider adding `cargo-features = ["run-args"]` to the top of Cargo.toml (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#run-args for more information about the status of this feature.
+"#,
+        )
+        .run();
+}
+
 #[cargo_test]
 fn run_example() {
     let p = project()
@@ -248,6 +248,7 @@ foo v0.0.1 ([CWD])
 [UPDATING] `dummy-registry` index
 [LOCKING] 3 packages to latest Rust 1.60.0 compatible versions
 [ADDING] newer-and-older v1.5.0 (latest: v1.6.0)
+[WARNING] no version of `only-newer` compatible with Rust 1.60.0 satisfies the requirements, falling back to `only-newer v1.6.0` which requires Rust 1.65.0
 ",
         )
         .run();
@@ -262,6 +263,93 @@ foo v0.0.1 ([CWD])
         .run();
 }
 
+#[cargo_test]
+fn resolve_incompatible_rust_versions_config() {
+    Package::new("only-newer", "1.6.0")
+        .rust_version("1.65.0")
+        .file("src/lib.rs", "fn other_stuff() {}")
+        .publish();
+    Package::new("newer-and-older", "1.5.0")
+        .rust_version("1.55.0")
+        .file("src/lib.rs", "fn other_stuff() {}")
+        .publish();
+    Package::new("newer-and-older", "1.6.0")
+        .rust_version("1.65.0")
+        .file("src/lib.rs", "fn other_stuff() {}")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+            authors = []
+            rust-version = "1.60.0"
+
+            [dependencies]
+            only-newer = "1.0.0"
+            newer-and-older = "1.0.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main(){}")
+        .build();
+
+    p.cargo("generate-lockfile")
+        .env("CARGO_RESOLVER_INCOMPATIBLE_RUST_VERSIONS", "allow")
+        .arg("-Zmsrv-policy")
+        .masquerade_as_nightly_cargo(&["msrv-policy"])
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest compatible versions
+",
+        )
+        .run();
+
+    p.cargo("generate-lockfile")
+        .env("CARGO_RESOLVER_INCOMPATIBLE_RUST_VERSIONS", "fallback")
+        .arg("-Zmsrv-policy")
+        .masquerade_as_nightly_cargo(&["msrv-policy"])
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest Rust 1.60.0 compatible versions
+[ADDING] newer-and-older v1.5.0 (latest: v1.6.0)
+[WARNING] no version of `only-newer` compatible with Rust 1.60.0 satisfies the requirements, falling back to `only-newer v1.6.0` which requires Rust 1.65.0
+",
+        )
+        .run();
+    p.cargo("tree")
+        .with_stdout(
+            "\
+foo v0.0.1 ([CWD])
+├── newer-and-older v1.5.0
+└── only-newer v1.6.0
+",
+        )
+        .run();
+
+    // `incompatible-rust-versions` takes precedence over `something-like-precedence`
+    p.cargo("generate-lockfile")
+        .env("CARGO_RESOLVER_INCOMPATIBLE_RUST_VERSIONS", "allow")
+        .env(
+            "CARGO_RESOLVER_SOMETHING_LIKE_PRECEDENCE",
+            "something-like-rust-version",
+        )
+        .arg("-Zmsrv-policy")
+        .masquerade_as_nightly_cargo(&["msrv-policy"])
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[LOCKING] 3 packages to latest compatible versions
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn resolve_with_rustc() {
     Package::new("only-newer", "1.6.0")
@@ -332,6 +420,7 @@ foo v0.0.1 ([CWD])
 [UPDATING] `dummy-registry` index
 [LOCKING] 3 packages to latest Rust [..] compatible versions
 [ADDING] newer-and-older v1.5.0 (latest: v1.6.0)
+[WARNING] no version of `only-newer` compatible with Rust [..] satisfies the requirements, falling back to `only-newer v1.6.0` which requires Rust 1.2345
 ",
         )
         .run();
@@ -414,6 +503,7 @@ foo v0.0.1 ([CWD])
             "\
 [UPDATING] `dummy-registry` index
 [LOCKING] 3 packages to latest Rust 1.60.0 compatible versions
+[WARNING] no version of `has-rust-version` compatible with Rust 1.60.0 satisfies the requirements, falling back to `has-rust-version v1.6.0` which requires Rust 1.65.0
 ",
         )
         .run();
@@ -521,6 +611,7 @@ higher v0.0.1 ([CWD])
 [UPDATING] `dummy-registry` index
 [LOCKING] 4 packages to latest Rust 1.50.0 compatible versions
 [ADDING] newer-and-older v1.5.0 (latest: v1.6.0)
+[WARNING] no version of `only-newer` compatible with Rust 1.50.0 satisfies the requirements, falling back to `only-newer v1.6.0` which requires Rust 1.65.0
 ",
         )
         .run();
@@ -658,6 +749,7 @@ fn resolve_edition2024() {
 [UPDATING] `dummy-registry` index
 [LOCKING] 3 packages to latest Rust 1.60.0 compatible versions
 [ADDING] newer-and-older v1.5.0 (latest: v1.6.0)
+[WARNING] no version of `only-newer` compatible with Rust 1.60.0 satisfies the requirements, falling back to `only-newer v1.6.0` which requires Rust 1.65.0
 ",
         )
         .run();
@@ -770,6 +862,7 @@ fn resolve_v3() {
 [UPDATING] `dummy-registry` index
 [LOCKING] 3 packages to latest Rust 1.60.0 compatible versions
 [ADDING] newer-and-older v1.5.0 (latest: v1.6.0)
+[WARNING] no version of `only-newer` compatible with Rust 1.60.0 satisfies the requirements, falling back to `only-newer v1.6.0` which requires Rust 1.65.0
 ",
         )
         .run();
@@ -851,6 +944,51 @@ Caused by:
         .run();
 }
 
+#[cargo_test(nightly, reason = "edition2024 in rustc is unstable")]
+fn resolve_msrv_resolver_name() {
+    Package::new("bar", "1.5.0")
+        .rust_version("1.55.0")
+        .file("src/lib.rs", "fn other_stuff() {}")
+        .publish();
+    Package::new("bar", "1.6.0")
+        .rust_version("1.65.0")
+        .file("src/lib.rs", "fn other_stuff() {}")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["edition2024"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+            authors = []
+            rust-version = "1.60.0"
+            resolver = "msrv"
+
+            [dependencies]
+            bar = "1.0.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main(){}")
+        .build();
+
+    p.cargo("generate-lockfile")
+        .arg("-Zmsrv-policy")
+        .masquerade_as_nightly_cargo(&["edition2024", "msrv-policy"])
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest Rust 1.60.0 compatible versions
+[ADDING] bar v1.5.0 (latest: v1.6.0)
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn generate_lockfile_ignore_rust_version_is_unstable() {
     Package::new("bar", "1.5.0")
@@ -1098,6 +1236,7 @@ foo v0.0.1 ([CWD])
 [UPDATING] `dummy-registry` index
 [LOCKING] 3 packages to latest Rust 1.60.0 compatible versions
 [ADDING] newer-and-older v1.5.0 (latest: v1.6.0)
+[WARNING] no version of `only-newer` compatible with Rust 1.60.0 satisfies the requirements, falling back to `only-newer v1.6.0` which requires Rust 1.65.0
 [DOWNLOADING] crates ...
 [DOWNLOADED] [..]
 [CHECKING] [..]
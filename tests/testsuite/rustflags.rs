@@ -1672,3 +1672,24 @@ fn host_config_rustflags_with_target() {
         .arg("host.rustflags=[\"--cfg=foo\"]")
         .run();
 }
+
+#[cargo_test]
+fn build_rustflags_placeholders() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                rustflags = ["--remap-path-prefix={workspace-root}=/ws", "-Ldependency={target-dir}/extra"]
+            "#,
+        )
+        .build();
+
+    p.cargo("check -v -Zconfig-placeholders")
+        .masquerade_as_nightly_cargo(&["config-placeholders"])
+        .with_stderr_contains(
+            "[RUNNING] `rustc [..] --remap-path-prefix=[ROOT]/foo=/ws -Ldependency=[ROOT]/foo/target/extra[..]`",
+        )
+        .run();
+}
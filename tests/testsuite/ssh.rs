@@ -506,6 +506,7 @@ Caused by:
     } else {
         format!(
             "{shared_stderr} attempted ssh-agent authentication, but no usernames succeeded: `git`
+    `SSH_AUTH_SOCK` is set to `[ROOT]/home/ssh_auth_sock`, which doesn't exist, so no ssh-agent could be reached
 
   if the git CLI succeeds then `net.git-fetch-with-cli` may help here
   https://doc.rust-lang.org/cargo/reference/config.html#netgit-fetch-with-cli
@@ -556,6 +557,7 @@ Caused by:
     } else {
         format!(
             "{shared_stderr} attempted ssh-agent authentication, but no usernames succeeded: `git`
+    `SSH_AUTH_SOCK` is set to `[ROOT]/home/ssh_auth_sock`, which doesn't exist, so no ssh-agent could be reached
 
   if the git CLI succeeds then `net.git-fetch-with-cli` may help here
   https://doc.rust-lang.org/cargo/reference/config.html#netgit-fetch-with-cli
@@ -0,0 +1,305 @@
+//! Tests for the `[system-dependencies]` table.
+
+use cargo_test_support::{cross_compile, project};
+
+/// A fake `pkg-config`, only knowing `zlib` at `$ZLIB_VERSION`, or 1.2.13.
+#[cfg(unix)]
+fn with_pkg_config(p: cargo_test_support::ProjectBuilder) -> cargo_test_support::ProjectBuilder {
+    p.executable(
+        "pkg-config",
+        r#"#!/bin/sh
+            [ "$2" = zlib ] || exit 1
+            case "$1" in
+                --modversion) echo "${ZLIB_VERSION:-1.2.13}" ;;
+                --libs) echo "-L/opt/zlib/lib -lz" ;;
+                --cflags) echo "-I/opt/zlib/include" ;;
+            esac
+        "#,
+    )
+}
+
+#[cfg(unix)]
+fn pkg_config(p: &cargo_test_support::Project) -> std::path::PathBuf {
+    p.root().join("pkg-config")
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [system-dependencies]
+                zlib = "1.2"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["system-dependencies"])
+        .with_status(101)
+        .with_stderr_contains("  feature `system-dependencies` is required")
+        .run();
+}
+
+#[cargo_test]
+fn invalid_version_requirement() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["system-dependencies"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [system-dependencies]
+                zlib = "one"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["system-dependencies"])
+        .with_status(101)
+        .with_stderr_contains(
+            "  failed to parse the version requirement `one` of the system dependency `zlib`",
+        )
+        .run();
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn env_for_build_script() {
+    let p = with_pkg_config(project())
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["system-dependencies"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [system-dependencies]
+                libz = { version = "1.2", pkg-config = "zlib" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    for suffix in ["VERSION", "LIBS", "CFLAGS"] {
+                        let var = format!("CARGO_SYSTEM_DEP_LIBZ_{suffix}");
+                        println!("cargo::warning={var}={}", std::env::var(&var).unwrap());
+                    }
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["system-dependencies"])
+        .env("PKG_CONFIG", pkg_config(&p))
+        .with_stderr_contains("[WARNING] foo@0.1.0: CARGO_SYSTEM_DEP_LIBZ_VERSION=1.2.13")
+        .with_stderr_contains("[WARNING] foo@0.1.0: CARGO_SYSTEM_DEP_LIBZ_LIBS=-L/opt/zlib/lib -lz")
+        .with_stderr_contains(
+            "[WARNING] foo@0.1.0: CARGO_SYSTEM_DEP_LIBZ_CFLAGS=-I/opt/zlib/include",
+        )
+        .run();
+
+    // A new version of the library reruns the build script.
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["system-dependencies"])
+        .env("PKG_CONFIG", pkg_config(&p))
+        .env("ZLIB_VERSION", "1.3.1")
+        .with_stderr_contains("[WARNING] foo@0.1.0: CARGO_SYSTEM_DEP_LIBZ_VERSION=1.3.1")
+        .run();
+    p.cargo("check -v")
+        .masquerade_as_nightly_cargo(&["system-dependencies"])
+        .env("PKG_CONFIG", pkg_config(&p))
+        .env("ZLIB_VERSION", "1.3.1")
+        .with_stderr_contains("[FRESH] foo v0.1.0 ([CWD])")
+        .run();
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn reports_all_missing() {
+    let p = with_pkg_config(project())
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["system-dependencies"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+
+                [system-dependencies]
+                zlib = ">=1.3"
+                openssl = "1.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() {}")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                cargo-features = ["system-dependencies"]
+
+                [package]
+                name = "bar"
+                version = "0.1.0"
+                edition = "2015"
+
+                [system-dependencies]
+                sqlite = { pkg-config = "sqlite3" }
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["system-dependencies"])
+        .env("PKG_CONFIG", pkg_config(&p))
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] failed to find the system dependencies of the packages to build:",
+        )
+        .with_stderr_contains(
+            "  `sqlite`, required by `bar v0.1.0 ([CWD]/bar)`: not found by `pkg-config` as `sqlite3`",
+        )
+        .with_stderr_contains(
+            "  `openssl 1.1`, required by `foo v0.1.0 ([CWD])`: not found by `pkg-config` as `openssl`",
+        )
+        .with_stderr_contains(
+            "  `zlib >=1.3`, required by `foo v0.1.0 ([CWD])`: found version `1.2.13` with `pkg-config`",
+        )
+        .with_stderr_does_not_contain("[COMPILING] [..]")
+        .run();
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn probes_each_library_once() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["system-dependencies"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+
+                [system-dependencies]
+                zlib = "1.2"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                cargo-features = ["system-dependencies"]
+
+                [package]
+                name = "bar"
+                version = "0.1.0"
+                edition = "2015"
+
+                [system-dependencies]
+                libz = { version = "1", pkg-config = "zlib" }
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .executable(
+            "pkg-config",
+            r#"#!/bin/sh
+                echo "$1 $2" >> "$(dirname "$0")/probes.log"
+                echo 1.2.13
+            "#,
+        )
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["system-dependencies"])
+        .env("PKG_CONFIG", p.root().join("pkg-config"))
+        .run();
+
+    let probes = p.read_file("probes.log");
+    assert_eq!(probes.lines().count(), 3, "{probes}");
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn probes_for_the_target() {
+    if cross_compile::disabled() {
+        return;
+    }
+    let target = cross_compile::alternate();
+    let p = with_pkg_config(project())
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["system-dependencies"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [system-dependencies]
+                zlib = "1.2"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    let version = std::env::var("CARGO_SYSTEM_DEP_ZLIB_VERSION").unwrap();
+                    println!("cargo::warning=zlib {version}");
+                }
+            "#,
+        )
+        .build();
+
+    // The libraries of the host aren't used for another platform.
+    p.cargo(&format!("check --target {target}"))
+        .masquerade_as_nightly_cargo(&["system-dependencies"])
+        .with_status(101)
+        .with_stderr_contains(&format!(
+            "  `zlib 1.2`, required by `foo v0.1.0 ([CWD])` for `{target}`: \
+             `pkg-config` isn't configured for cross-compiling to `{target}`, \
+             set `PKG_CONFIG_SYSROOT_DIR_{target}` or `PKG_CONFIG_ALLOW_CROSS=1`"
+        ))
+        .run();
+
+    p.cargo(&format!("check --target {target}"))
+        .masquerade_as_nightly_cargo(&["system-dependencies"])
+        .env(format!("PKG_CONFIG_{target}"), pkg_config(&p))
+        .env(format!("PKG_CONFIG_SYSROOT_DIR_{target}"), "/sysroot")
+        .env("ZLIB_VERSION", "1.2.8")
+        .with_stderr_contains("[WARNING] foo@0.1.0: zlib 1.2.8")
+        .run();
+}
@@ -5115,3 +5115,250 @@ fn cargo_test_set_out_dir_env_var() {
     p.cargo("test --package foo --test case -- tests::test_add --exact --nocapture")
         .run();
 }
+
+#[cargo_test]
+fn test_timeout_kills_hung_binary() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [[test]]
+                name = "hang"
+                harness = false
+            "#,
+        )
+        .file(
+            "tests/hang.rs",
+            r#"
+                fn main() {
+                    loop {
+                        std::thread::sleep(std::time::Duration::from_secs(1));
+                    }
+                }
+            "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [test]
+                timeout = "1s"
+            "#,
+        )
+        .build();
+
+    p.cargo("test -Ztest-timeout")
+        .masquerade_as_nightly_cargo(&["test-timeout"])
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [..]
+[RUNNING] tests/hang.rs (target/debug/deps/hang-[..][EXE])
+[ERROR] test failed, to rerun pass `--test hang`
+
+Caused by:
+  test binary timed out after 1s and was killed: `[..]hang-[..]`
+  the test harness did not report any test as still running
+",
+        )
+        .with_status(101)
+        .run();
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn test_timeout_kills_child_processes() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [[test]]
+                name = "hang"
+                harness = false
+            "#,
+        )
+        .file(
+            "tests/hang.rs",
+            r#"
+                use std::time::Duration;
+                use std::{env, fs, process, thread};
+
+                fn main() {
+                    if env::var_os("GRANDCHILD").is_none() {
+                        process::Command::new(env::current_exe().unwrap())
+                            .env("GRANDCHILD", "1")
+                            .spawn()
+                            .unwrap();
+                        loop {
+                            thread::sleep(Duration::from_secs(1));
+                        }
+                    }
+                    for i in 0.. {
+                        fs::write(env::var("TICKS").unwrap(), i.to_string()).unwrap();
+                        thread::sleep(Duration::from_millis(50));
+                    }
+                }
+            "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [test]
+                timeout = "1s"
+            "#,
+        )
+        .build();
+
+    let ticks = p.root().join("ticks");
+    p.cargo("test -Ztest-timeout")
+        .masquerade_as_nightly_cargo(&["test-timeout"])
+        .env("TICKS", &ticks)
+        .with_stderr_contains("  test binary timed out after 1s and was killed: `[..]hang-[..]`")
+        .with_status(101)
+        .run();
+
+    // The process spawned by the test binary was killed too.
+    let before = std::fs::read_to_string(&ticks).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    assert_eq!(std::fs::read_to_string(&ticks).unwrap(), before);
+}
+
+#[cargo_test]
+fn test_timeout_invalid_value() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [test]
+                timeout = "soon"
+            "#,
+        )
+        .build();
+
+    p.cargo("test -Ztest-timeout")
+        .masquerade_as_nightly_cargo(&["test-timeout"])
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [..]
+[ERROR] invalid `test.timeout` in [..]config.toml
+
+Caused by:
+  expected a value of the form \"Ns/Nmin/Nh\" or \"N seconds/minutes/hours/days/weeks/months\", got: \"soon\"
+",
+        )
+        .with_status(101)
+        .run();
+}
+
+#[cargo_test]
+fn cache_results_gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --cache-results")
+        .masquerade_as_nightly_cargo(&["cache-results"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--cache-results` flag is unstable[..]")
+        .run();
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [test]
+            cache-results = true
+        "#,
+    );
+    p.cargo("test")
+        .with_stderr_contains(
+            "[WARNING] `test.cache-results` requires `-Zunstable-options`, ignoring it",
+        )
+        .with_stderr_contains("[RUNNING] unittests src/lib.rs [..]")
+        .run();
+}
+
+#[cargo_test]
+fn cache_results_skips_unchanged_binaries() {
+    let p = project()
+        .file("src/lib.rs", "#[test] fn lib_test() {}")
+        .file("tests/other.rs", "#[test] fn other_test() {}")
+        .build();
+
+    p.cargo("test -Zunstable-options --cache-results")
+        .masquerade_as_nightly_cargo(&["cache-results"])
+        .with_stderr_contains("[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[..][EXE])")
+        .with_stderr_contains("[RUNNING] tests/other.rs (target/debug/deps/other-[..][EXE])")
+        .run();
+
+    p.cargo("test -Zunstable-options --cache-results")
+        .masquerade_as_nightly_cargo(&["cache-results"])
+        .with_stderr_contains(
+            "[..]Cached pass unittests src/lib.rs (target/debug/deps/foo-[..][EXE])",
+        )
+        .with_stderr_contains("[..]Cached pass tests/other.rs (target/debug/deps/other-[..][EXE])")
+        .with_stderr_does_not_contain("[RUNNING] [..]")
+        .run();
+
+    // Different arguments are a different run.
+    p.cargo("test -Zunstable-options --cache-results -- lib_test")
+        .masquerade_as_nightly_cargo(&["cache-results"])
+        .with_stderr_contains("[RUNNING] unittests src/lib.rs [..]")
+        .run();
+
+    // So is a different environment, which the binaries inherit.
+    p.cargo("test -Zunstable-options --cache-results")
+        .env("__CARGO_TEST_CACHE_VAR", "1")
+        .masquerade_as_nightly_cargo(&["cache-results"])
+        .with_stderr_contains("[RUNNING] unittests src/lib.rs [..]")
+        .with_stderr_contains("[RUNNING] tests/other.rs [..]")
+        .run();
+
+    // Only the rebuilt binary runs again.
+    p.change_file(
+        "tests/other.rs",
+        "#[test] fn other_test() { assert!(true) }",
+    );
+    p.cargo("test -Zunstable-options --cache-results")
+        .masquerade_as_nightly_cargo(&["cache-results"])
+        .with_stderr_contains("[..]Cached pass unittests src/lib.rs [..]")
+        .with_stderr_contains("[RUNNING] tests/other.rs [..]")
+        .run();
+
+    p.cargo("test -Zunstable-options --no-cache")
+        .masquerade_as_nightly_cargo(&["cache-results"])
+        .with_stderr_contains("[RUNNING] unittests src/lib.rs [..]")
+        .with_stderr_contains("[RUNNING] tests/other.rs [..]")
+        .run();
+}
+
+#[cargo_test]
+fn cache_results_reruns_failures() {
+    let p = project()
+        .file("src/lib.rs", "#[test] fn fails() { panic!() }")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [test]
+                cache-results = true
+            "#,
+        )
+        .build();
+
+    for _ in 0..2 {
+        p.cargo("test -Zunstable-options")
+            .masquerade_as_nightly_cargo(&["cache-results"])
+            .with_status(101)
+            .with_stderr_contains("[RUNNING] unittests src/lib.rs [..]")
+            .with_stderr_does_not_contain("[..]Cached[..]")
+            .run();
+    }
+}
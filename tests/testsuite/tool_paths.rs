@@ -1,6 +1,6 @@
 //! Tests for configuration values that point to programs.
 
-use cargo_test_support::{basic_lib_manifest, project, rustc_host, rustc_host_env};
+use cargo_test_support::{basic_lib_manifest, cross_compile, project, rustc_host, rustc_host_env};
 
 #[cargo_test]
 fn pathless_tools() {
@@ -347,6 +347,128 @@ second match `cfg(not(target_os = \"none\"))` located in [..]/foo/.cargo/config.
         .run();
 }
 
+#[cargo_test]
+fn custom_labeled_runners() {
+    let target = rustc_host();
+
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("tests/test.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [target.{target}]
+                    runner = "nonexistent-default"
+
+                    [target.{target}.runners]
+                    b = "nonexistent-b -x"
+                    a = ["nonexistent-a"]
+                "#
+            ),
+        )
+        .build();
+
+    p.cargo("test --test test --runner a")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--runner` flag is unstable, [..]
+See [..]
+",
+        )
+        .run();
+
+    p.cargo("test --test test --verbose -Zunstable-options --runner b")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[RUNNING] `nonexistent-b -x [..]/target/debug/deps/test-[..][EXE]`")
+        .run();
+
+    p.cargo("test --test test -Zunstable-options --runner c")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] no runner labeled `c` is configured in `target.<triple>.runners` for the requested targets
+available runners: `a`, `b`
+",
+        )
+        .run();
+
+    p.cargo("test --test test --verbose -Zunstable-options --all-runners --no-fail-fast")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[..]Runner a
+[RUNNING] `nonexistent-a [..]/target/debug/deps/test-[..][EXE]`
+",
+        )
+        .with_stderr_contains(
+            "\
+[..]Runner b
+[RUNNING] `nonexistent-b -x [..]/target/debug/deps/test-[..][EXE]`
+",
+        )
+        .with_stderr_contains(
+            "\
+[ERROR] 2 targets failed:
+    `--test test --runner a`
+    `--test test --runner b`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn custom_labeled_runners_multiple_targets() {
+    if cross_compile::disabled() {
+        return;
+    }
+    let target = rustc_host();
+    let alternate = cross_compile::alternate();
+
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("tests/test.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [target.{target}.runners]
+                    a = "nonexistent-a"
+                    b = "nonexistent-b"
+
+                    [target.{alternate}.runners]
+                    a = "nonexistent-alt-a"
+                "#
+            ),
+        )
+        .build();
+
+    // The alternate target has no runner `b`, so its tests only run under `a`.
+    p.cargo("test --test test --verbose -Zunstable-options --all-runners --no-fail-fast")
+        .arg("--target")
+        .arg(&target)
+        .arg("--target")
+        .arg(alternate)
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains(&format!(
+            "[RUNNING] `nonexistent-alt-a [..]/target/{alternate}/debug/deps/test-[..][EXE]`"
+        ))
+        .with_stderr_contains(
+            "\
+[ERROR] 3 targets failed:
+    `--test test --runner a`
+    `--test test --runner a`
+    `--test test --runner b`
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn custom_runner_env() {
     let p = project().file("src/main.rs", "fn main() {}").build();
@@ -486,3 +608,45 @@ fn cfg_ignored_fields() {
         )
         .run();
 }
+
+#[cargo_test]
+fn custom_runner_placeholders() {
+    let target = rustc_host();
+
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [target.{}]
+                    runner = "nonexistent-runner --root={{workspace-root}} --out={{target-dir}}"
+                "#,
+                target
+            ),
+        )
+        .build();
+
+    p.cargo("run")
+        .with_status(101)
+        .with_stderr_contains(
+            "[RUNNING] `nonexistent-runner --root={workspace-root} --out={target-dir} target/debug/foo[EXE]`",
+        )
+        .run();
+
+    p.cargo("run -Zconfig-placeholders")
+        .masquerade_as_nightly_cargo(&["config-placeholders"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[RUNNING] `nonexistent-runner --root=[ROOT]/foo --out=[ROOT]/foo/target target/debug/foo[EXE]`",
+        )
+        .run();
+
+    p.cargo("run -Zconfig-placeholders --target-dir custom")
+        .masquerade_as_nightly_cargo(&["config-placeholders"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[RUNNING] `nonexistent-runner --root=[ROOT]/foo --out=[ROOT]/foo/custom custom/debug/foo[EXE]`",
+        )
+        .run();
+}
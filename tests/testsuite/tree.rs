@@ -2199,3 +2199,428 @@ foo v1.0.0 ([ROOT]/foo)
         )
         .run();
 }
+
+fn make_licenses_proj() -> Project {
+    Package::new("bar", "1.0.0")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "1.0.0"
+            license = "MIT/Apache-2.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .publish();
+
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            license = "MIT OR Apache-2.0"
+
+            [dependencies]
+            bar = "1.0"
+            custom = { path = "custom" }
+            none = { path = "none" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "custom/Cargo.toml",
+            r#"
+            [package]
+            name = "custom"
+            version = "0.1.0"
+            license-file = "LICENSE, custom.txt"
+            "#,
+        )
+        .file("custom/src/lib.rs", "")
+        .file("none/Cargo.toml", &basic_manifest("none", "0.1.0"))
+        .file("none/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn emit_licenses_gated() {
+    let p = make_licenses_proj();
+
+    p.cargo("tree --emit licenses")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--emit` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn emit_licenses() {
+    let p = make_licenses_proj();
+
+    p.cargo("tree -Zunstable-options --emit licenses")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+MIT OR Apache-2.0 (2)
+  bar v1.0.0
+  foo v0.1.0 ([ROOT]/foo)
+unknown (license file only) (1)
+  custom v0.1.0 ([ROOT]/foo/custom)
+missing (1)
+  none v0.1.0 ([ROOT]/foo/none)
+",
+        )
+        .with_stderr_contains(
+            "[WARNING] 1 package(s) have only a `license-file`, which can't be identified: \
+             `custom v0.1.0 ([ROOT]/foo/custom)`",
+        )
+        .with_stderr_contains(
+            "[WARNING] 1 package(s) have no `license` or `license-file`: \
+             `none v0.1.0 ([ROOT]/foo/none)`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn emit_licenses_csv() {
+    let p = make_licenses_proj();
+
+    p.cargo("tree -Zunstable-options --emit licenses --emit-format csv -e normal -p custom")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+name,version,id,kind,license,license_file
+custom,0.1.0,path+[ROOTURL]/foo/custom#0.1.0,unknown,,\"LICENSE, custom.txt\"
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn emit_licenses_json() {
+    let p = make_licenses_proj();
+
+    p.cargo("tree -Zunstable-options --emit licenses --emit-format json -p bar")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+{
+  "licenses": [
+    {
+      "kind": "expression",
+      "license": "MIT OR Apache-2.0",
+      "packages": [
+        {
+          "id": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+          "name": "bar",
+          "version": "1.0.0",
+          "license_file": null
+        }
+      ]
+    }
+  ]
+}
+
+#[cargo_test]
+fn package_filter() {
+    let p = make_licenses_proj();
+
+    p.cargo("tree --prune-source path")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--prune-source` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+
+    p.cargo("tree -Zunstable-options --prune-source path")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([ROOT]/foo)
+└── bar v1.0.0
+",
+        )
+        .run();
+
+    p.cargo("tree -Zunstable-options --only-source path")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([ROOT]/foo)
+├── custom v0.1.0 ([ROOT]/foo/custom)
+└── none v0.1.0 ([ROOT]/foo/none)
+",
+        )
+        .run();
+
+    p.cargo("tree -Zunstable-options --only-source registry+https://github.com/rust-lang/crates.io-index")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([ROOT]/foo)
+└── bar v1.0.0
+",
+        )
+        .run();
+
+    // The root is never pruned, and `MIT/Apache-2.0` contains `Apache-2.0`.
+    p.cargo("tree -Zunstable-options --prune-license Apache-*")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([ROOT]/foo)
+├── custom v0.1.0 ([ROOT]/foo/custom)
+└── none v0.1.0 ([ROOT]/foo/none)
+",
+        )
+        .run();
+
+    p.cargo("tree -Zunstable-options --only-source svn")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] invalid source `svn`, expected one of `path`, `git`, `registry`, `local-registry`, \
+`directory` or a source URL like `registry+https://github.com/rust-lang/crates.io-index`
+",
+        )
+        .run();
+}
+"#,
+        )
+        .run();
+}
+
+fn make_graph_proj() -> Project {
+    Package::new("bar", "1.0.0").feature("f1", &[]).publish();
+    Package::new("baz", "1.0.0").publish();
+    Package::new("qux", "1.0.0").publish();
+
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { version = "1.0", features = ["f1"] }
+
+            [build-dependencies]
+            baz = "1.0"
+
+            [dev-dependencies]
+            qux = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn graph_format_gated() {
+    let p = make_graph_proj();
+
+    // Without `-Zunstable-options`, it is a format string.
+    p.cargo("tree --format dot")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+dot
+└── dot
+[build-dependencies]
+└── dot
+[dev-dependencies]
+└── dot
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn graph_format_dot() {
+    let p = make_graph_proj();
+
+    p.cargo("tree -Zunstable-options --format dot")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+digraph {
+    0 [label=\"foo v0.1.0\", shape=box];
+    1 [label=\"bar v1.0.0\\n[f1]\", shape=box];
+    2 [label=\"baz v1.0.0\", shape=box];
+    3 [label=\"qux v1.0.0\", shape=box];
+    0 -> 1;
+    0 -> 2 [label=\"build\", color=blue];
+    0 -> 3 [label=\"dev\", style=dashed];
+}
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn graph_format_json() {
+    let p = make_graph_proj();
+
+    p.cargo("tree -Zunstable-options --format json -e normal,build")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+{
+  "roots": [0],
+  "nodes": [
+    {
+      "kind": "package",
+      "id": 0,
+      "package": "path+[ROOTURL]/foo#0.1.0",
+      "name": "foo",
+      "version": "0.1.0",
+      "features": [],
+      "target": null
+    },
+    {
+      "kind": "package",
+      "id": 1,
+      "package": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+      "name": "bar",
+      "version": "1.0.0",
+      "features": ["f1"],
+      "target": null
+    },
+    {
+      "kind": "package",
+      "id": 2,
+      "package": "registry+https://github.com/rust-lang/crates.io-index#baz@1.0.0",
+      "name": "baz",
+      "version": "1.0.0",
+      "features": [],
+      "target": null
+    }
+  ],
+  "edges": [
+    { "from": 0, "to": 1, "kind": "normal" },
+    { "from": 0, "to": 2, "kind": "build" }
+  ]
+}
+"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn graph_format_json_features() {
+    let p = make_graph_proj();
+
+    p.cargo("tree -Zunstable-options --format json -e normal,features")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+{
+  "roots": [0],
+  "nodes": [
+    {
+      "kind": "package",
+      "id": 0,
+      "package": "path+[ROOTURL]/foo#0.1.0",
+      "name": "foo",
+      "version": "0.1.0",
+      "features": [],
+      "target": null
+    },
+    {
+      "kind": "feature",
+      "id": 1,
+      "package": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+      "name": "default"
+    },
+    {
+      "kind": "feature",
+      "id": 2,
+      "package": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+      "name": "f1"
+    },
+    {
+      "kind": "package",
+      "id": 3,
+      "package": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+      "name": "bar",
+      "version": "1.0.0",
+      "features": ["f1"],
+      "target": null
+    }
+  ],
+  "edges": [
+    { "from": 0, "to": 1, "kind": "normal" },
+    { "from": 0, "to": 2, "kind": "normal" },
+    { "from": 1, "to": 3, "kind": "feature" },
+    { "from": 2, "to": 3, "kind": "feature" }
+  ]
+}
+"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn sizes() {
+    Package::new("baz", "1.0.0").publish();
+    Package::new("bar", "1.0.0").dep("baz", "1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree --sizes")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--sizes` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+
+    p.cargo("tree -Zunstable-options --sizes")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([..]/foo) [subtree: [..]B download, [..]B unpacked]
+└── bar v1.0.0 [download: [..]B; unpacked: [..]B; subtree: [..]B download, [..]B unpacked]
+    └── baz v1.0.0 [download: [..]B; unpacked: [..]B]
+",
+        )
+        .run();
+
+    // Pruned packages don't count in the totals.
+    p.cargo("tree -Zunstable-options --sizes --prune baz")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+foo v0.1.0 ([..]/foo) [subtree: [..]B download, [..]B unpacked]
+└── bar v1.0.0 [download: [..]B; unpacked: [..]B]
+",
+        )
+        .run();
+}
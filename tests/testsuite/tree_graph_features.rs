@@ -360,3 +360,73 @@ foo v0.1.0 ([..]/foo)
         )
         .run();
 }
+
+#[cargo_test]
+fn why_feature() {
+    Package::new("notopt", "1.0.0")
+        .feature("cat", &[])
+        .feature("animal", &["cat"])
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            notopt = "1.0"
+
+            [features]
+            f1 = ["notopt/animal"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree --why-feature notopt/cat")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--why-feature` flag is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+
+    p.cargo("tree -Zunstable-options --features f1 --why-feature notopt/cat")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+notopt feature \"cat\"
+└── notopt feature \"animal\"
+    └── foo feature \"f1\" (command-line)
+",
+        )
+        .run();
+
+    p.cargo("tree -Zunstable-options --why-feature notopt/default")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout(
+            "\
+notopt feature \"default\"
+└── foo v0.1.0 ([..]/foo)
+    └── foo feature \"default\" (command-line)
+",
+        )
+        .run();
+
+    p.cargo("tree -Zunstable-options --why-feature notopt/cat")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] feature `cat` of package `notopt` is not enabled")
+        .run();
+
+    p.cargo("tree -Zunstable-options --why-feature notopt")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] `--why-feature` expects `PACKAGE/FEATURE`, found `notopt`")
+        .run();
+}
@@ -1659,3 +1659,460 @@ fn update_with_missing_feature() {
         )
         .run();
 }
+
+#[cargo_test]
+fn update_breaking_unstable() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("update --breaking")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--breaking` flag is unstable[..]")
+        .run();
+}
+
+#[cargo_test]
+fn update_breaking() {
+    Package::new("compatible", "1.0.0").publish();
+    Package::new("incompatible", "1.0.0").publish();
+    Package::new("ws", "1.0.0").publish();
+    Package::new("pinned", "1.0.0").publish();
+    Package::new("tilde", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo", "bar"]
+
+                [workspace.dependencies]
+                ws = "1.0"
+            "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                authors = []
+
+                [dependencies]
+                compatible = "1.0"
+                incompatible = "1.0"
+                ws.workspace = true
+                renamed = { package = "pinned", version = "1.0" }
+            "#,
+        )
+        .file("foo/src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.0.1"
+                edition = "2015"
+                authors = []
+
+                [dev-dependencies]
+                incompatible = "^1"
+                tilde = "~1.0"
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+
+    Package::new("compatible", "1.0.1").publish();
+    Package::new("incompatible", "2.1.0").publish();
+    Package::new("ws", "2.0.0").publish();
+    Package::new("ws", "3.0.0-alpha.1").publish();
+    Package::new("pinned", "2.0.0").publish();
+    Package::new("tilde", "2.0.0").publish();
+
+    p.cargo("update -Zunstable-options --breaking")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_stderr_contains("[UPGRADING] incompatible ^1.0 -> ^2.1")
+        .with_stderr_contains("[UPGRADING] incompatible ^1 -> ^2")
+        .with_stderr_contains("[UPGRADING] ws ^1.0 -> ^2.0")
+        .with_stderr_contains("[UPDATING] incompatible v1.0.0 -> v2.1.0")
+        .with_stderr_contains("[UPDATING] ws v1.0.0 -> v2.0.0")
+        .with_stderr_does_not_contain("[..]compatible v1.0.0 -> v1.0.1")
+        .with_stderr_does_not_contain("[..]pinned[..]")
+        .with_stderr_does_not_contain("[..]tilde[..]")
+        .run();
+
+    let root = p.read_file("Cargo.toml");
+    assert!(root.contains(r#"ws = "2.0""#), "{root}");
+    let foo = p.read_file("foo/Cargo.toml");
+    assert!(foo.contains(r#"compatible = "1.0""#), "{foo}");
+    assert!(foo.contains(r#"incompatible = "2.1""#), "{foo}");
+    assert!(foo.contains("ws.workspace = true"), "{foo}");
+    assert!(
+        foo.contains(r#"renamed = { package = "pinned", version = "1.0" }"#),
+        "{foo}"
+    );
+    let bar = p.read_file("bar/Cargo.toml");
+    assert!(bar.contains(r#"incompatible = "^2""#), "{bar}");
+    assert!(bar.contains(r#"tilde = "~1.0""#), "{bar}");
+
+    let lock = p.read_lockfile();
+    assert!(lock.contains("name = \"incompatible\"\nversion = \"2.1.0\""));
+    assert!(lock.contains("name = \"compatible\"\nversion = \"1.0.0\""));
+}
+
+#[cargo_test]
+fn update_breaking_spec_dry_run() {
+    Package::new("incompatible", "1.0.0").publish();
+    Package::new("other", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                authors = []
+
+                [dependencies]
+                incompatible = "1.0"
+                other = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    let old_lockfile = p.read_lockfile();
+    let old_manifest = p.read_file("Cargo.toml");
+
+    Package::new("incompatible", "2.0.0").publish();
+    Package::new("other", "2.0.0").publish();
+
+    p.cargo("update -Zunstable-options --breaking incompatible --dry-run")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_stderr_contains("[UPGRADING] incompatible ^1.0 -> ^2.0")
+        .with_stderr_contains("[UPDATING] incompatible v1.0.0 -> v2.0.0")
+        .with_stderr_contains("[WARNING] aborting update due to dry run")
+        .with_stderr_does_not_contain("[..]other[..]")
+        .run();
+    assert_eq!(p.read_lockfile(), old_lockfile);
+    assert_eq!(p.read_file("Cargo.toml"), old_manifest);
+
+    p.cargo("update -Zunstable-options --breaking incompatible")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .run();
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains(r#"incompatible = "2.0""#), "{manifest}");
+    assert!(manifest.contains(r#"other = "1.0""#), "{manifest}");
+}
+
+#[cargo_test]
+fn update_breaking_versioned_spec() {
+    Package::new("incompatible", "1.0.0").publish();
+    Package::new("other", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                authors = []
+
+                [dependencies]
+                incompatible = "1.0"
+                other = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+
+    Package::new("incompatible", "2.0.0").publish();
+    Package::new("other", "2.0.0").publish();
+
+    p.cargo("update -Zunstable-options --breaking incompatible@2.0.0")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] package ID specification `incompatible@2.0.0` did not match \
+             any dependency of the workspace members",
+        )
+        .run();
+
+    p.cargo("update -Zunstable-options --breaking incompatible@1.0.0")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_stderr_contains("[UPGRADING] incompatible ^1.0 -> ^2.0")
+        .with_stderr_does_not_contain("[..]other[..]")
+        .run();
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains(r#"incompatible = "2.0""#), "{manifest}");
+    assert!(manifest.contains(r#"other = "1.0""#), "{manifest}");
+}
+
+#[cargo_test]
+fn update_before() {
+    Package::new("log", "0.1.0")
+        .pubtime("2024-01-01T00:00:00Z")
+        .publish();
+    Package::new("log", "0.1.1")
+        .pubtime("2024-05-31T10:00:00Z")
+        .publish();
+    Package::new("log", "0.1.2")
+        .pubtime("2024-06-15T00:00:00Z")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                authors = []
+
+                [dependencies]
+                log = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile --before 2024-06-01")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--before` flag is unstable[..]")
+        .run();
+
+    p.cargo("generate-lockfile -Zunstable-options --before yesterday")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(1)
+        .with_stderr_contains(
+            "[ERROR] invalid value `yesterday` for `--before`: \
+             expected a date like `2024-06-01` or an RFC 3339 timestamp",
+        )
+        .run();
+
+    p.cargo("generate-lockfile -Zunstable-options --before 2024-06-01")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .run();
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"log\"\nversion = \"0.1.1\""));
+
+    p.cargo("update -Zunstable-options --before 2024-05-31T10:00:00Z")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_contains("[DOWNGRADING] log v0.1.1 -> v0.1.0")
+        .run();
+
+    p.cargo("update")
+        .with_stderr_contains("[UPDATING] log v0.1.0 -> v0.1.2")
+        .run();
+}
+
+#[cargo_test]
+fn update_before_without_pubtime() {
+    Package::new("log", "0.1.0")
+        .pubtime("2024-01-01T00:00:00Z")
+        .publish();
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                authors = []
+
+                [dependencies]
+                bar = "0.1"
+                log = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Zunstable-options --before 2024-06-01")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_contains(
+            "[WARNING] `--before` has no effect on `bar`, \
+             as their registry doesn't record when versions were published",
+        )
+        .with_stderr_does_not_contain("[..]`log`[..]")
+        .run();
+}
+
+#[cargo_test]
+fn update_rust_version() {
+    Package::new("log", "0.1.0").rust_version("1.60").publish();
+    Package::new("log", "0.1.1").rust_version("1.70").publish();
+    Package::new("log", "0.1.2").rust_version("1.80").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                authors = []
+
+                [dependencies]
+                log = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"log\"\nversion = \"0.1.2\""));
+
+    p.cargo("update --rust-version 1.70")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--rust-version` flag is unstable[..]")
+        .run();
+
+    p.cargo("update -Zunstable-options --rust-version latest")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] invalid value `latest` for `--rust-version`")
+        .run();
+
+    p.cargo("update -Zunstable-options --rust-version 1.70")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest Rust 1.70 compatible version
+[DOWNGRADING] log v0.1.2 -> v0.1.1 (latest: v0.1.2)
+[NOTE] capped `log v0.1.1` for Rust 1.70, v0.1.2 requires Rust 1.80
+",
+        )
+        .run();
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"log\"\nversion = \"0.1.1\""));
+
+    // Nothing newer is compatible, the update is only reported as capped.
+    p.cargo("update -Zunstable-options --rust-version 1.70")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[LOCKING] 0 packages to latest Rust 1.70 compatible versions
+[NOTE] capped `log v0.1.1` for Rust 1.70, v0.1.2 requires Rust 1.80
+[NOTE] pass `--verbose` to see 1 unchanged dependencies behind latest
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn update_changelog() {
+    fn publish(name: &str, version: &str, repository: &str) {
+        Package::new(name, version)
+            .file(
+                "Cargo.toml",
+                &format!(
+                    r#"
+                        [package]
+                        name = "{name}"
+                        version = "{version}"
+                        edition = "2015"
+                        repository = "{repository}"
+                    "#
+                ),
+            )
+            .file("src/lib.rs", "")
+            .publish();
+    }
+    publish("log", "0.1.0", "https://github.com/rust-lang/log");
+    publish("other", "0.1.0", "https://example.com/other");
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+                authors = []
+
+                [dependencies]
+                log = "0.1"
+                other = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    publish(
+        "log",
+        "0.1.1",
+        "https://github.com/rust-lang/log/tree/master/log",
+    );
+    publish("other", "0.1.1", "https://example.com/other");
+
+    p.cargo("update --changelog")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--changelog` flag is unstable[..]")
+        .run();
+
+    p.cargo("update --dry-run -Zunstable-options --changelog")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_contains(
+            "   Changelog log v0.1.0 -> v0.1.1: \
+             https://github.com/rust-lang/log/compare/v0.1.0...v0.1.1",
+        )
+        .with_stderr_contains("   Changelog other v0.1.0 -> v0.1.1: https://example.com/other")
+        .run();
+
+    p.cargo("update -Zunstable-options --changelog json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_json(
+            r#"
+            {
+                "updates": [
+                    {
+                        "name": "log",
+                        "from": "0.1.0",
+                        "to": "0.1.1",
+                        "repository": "https://github.com/rust-lang/log/tree/master/log",
+                        "changelog": "https://github.com/rust-lang/log/compare/v0.1.0...v0.1.1"
+                    },
+                    {
+                        "name": "other",
+                        "from": "0.1.0",
+                        "to": "0.1.1",
+                        "repository": "https://example.com/other",
+                        "changelog": null
+                    }
+                ]
+            }
+            "#,
+        )
+        .run();
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"log\"\nversion = \"0.1.1\""));
+}
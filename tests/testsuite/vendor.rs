@@ -1156,3 +1156,79 @@ fn vendor_crate_with_ws_inherit() {
         .with_stderr_contains("[..]foo/vendor/bar/src/lib.rs[..]")
         .run();
 }
+
+#[cargo_test]
+fn only_used() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                used = "1.0"
+                optional = { version = "1.0", optional = true }
+
+                [target.'cfg(target_os = "nonexistent")'.dependencies]
+                platform = "1.0"
+
+                [dev-dependencies]
+                dev = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    Package::new("used", "1.0.0")
+        .file("src/lib.rs", "pub fn used() {}")
+        .publish();
+    Package::new("optional", "1.0.0")
+        .file("src/lib.rs", "pub fn optional() {}")
+        .publish();
+    Package::new("platform", "1.0.0")
+        .file("src/lib.rs", "pub fn platform() {}")
+        .publish();
+    Package::new("dev", "1.0.0")
+        .file("src/lib.rs", "pub fn dev() {}")
+        .publish();
+
+    p.cargo("vendor --respect-source-config --only-used")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the `--only-used` flag is unstable[..]")
+        .run();
+
+    p.cargo("vendor --respect-source-config --features optional")
+        .with_status(101)
+        .with_stderr("[ERROR] `--features` requires `--only-used`")
+        .run();
+
+    p.cargo("vendor --respect-source-config -Zunstable-options --only-used --features optional --all-targets")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .run();
+    assert_eq!(p.read_file("vendor/used/src/lib.rs"), "pub fn used() {}");
+    assert_eq!(
+        p.read_file("vendor/optional/src/lib.rs"),
+        "pub fn optional() {}"
+    );
+    assert_eq!(p.read_file("vendor/dev/src/lib.rs"), "pub fn dev() {}");
+    assert_eq!(p.read_file("vendor/platform/src/lib.rs"), "");
+
+    p.cargo("vendor --respect-source-config -Zunstable-options --only-used")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_contains("[..]Stubbing dev v1.0.0 ([..])[..]")
+        .with_stderr_contains("[..]Stubbing optional v1.0.0 ([..])[..]")
+        .with_stderr_contains("[..]Stubbing platform v1.0.0 ([..])[..]")
+        .with_stderr_contains("[..]Vendoring used v1.0.0 ([..])[..]")
+        .run();
+    assert_eq!(p.read_file("vendor/used/src/lib.rs"), "pub fn used() {}");
+    assert_eq!(p.read_file("vendor/optional/src/lib.rs"), "");
+    assert_eq!(p.read_file("vendor/dev/src/lib.rs"), "");
+    assert_eq!(p.read_file("vendor/platform/src/lib.rs"), "");
+    assert!(p.root().join("vendor/platform/Cargo.toml").exists());
+
+    add_vendor_config(&p);
+    p.cargo("check").run();
+}
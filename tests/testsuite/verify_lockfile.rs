@@ -0,0 +1,272 @@
+//! Tests for the `cargo verify-lockfile` command.
+
+use std::fs;
+
+use cargo_test_support::paths::{self, CargoPathExt};
+use cargo_test_support::registry::{registry_path, Package};
+use cargo_test_support::{project, Project};
+
+fn make_project() -> Project {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("baz", "1.0.0").publish();
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("verify-lockfile")
+        .masquerade_as_nightly_cargo(&["cargo-verify-lockfile"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo verify-lockfile` command is unstable, pass `-Z unstable-options` to enable it
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn verified() {
+    let p = make_project();
+    p.cargo("fetch").run();
+    let lockfile = p.read_lockfile();
+
+    p.cargo("verify-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-verify-lockfile"])
+        .with_stderr_contains("[..]Verified Cargo.lock")
+        .with_stderr_does_not_contain("[NOTE][..]")
+        .run();
+    assert_eq!(p.read_lockfile(), lockfile);
+}
+
+#[cargo_test]
+fn not_downloaded_is_unverified() {
+    let p = make_project();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("verify-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-verify-lockfile"])
+        .with_stderr_contains(
+            "[NOTE] could not verify the checksum of \
+             `registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0`: \
+             it has not been downloaded",
+        )
+        .with_stderr_contains("[..]Verified Cargo.lock")
+        .run();
+}
+
+#[cargo_test]
+fn missing_lockfile() {
+    let p = make_project();
+
+    p.cargo("verify-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-verify-lockfile"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] Cargo.lock failed verification:
+  no lock file found at [ROOT]/foo/Cargo.lock
+",
+        )
+        .run();
+    assert!(!p.root().join("Cargo.lock").exists());
+}
+
+#[cargo_test]
+fn invalid_lockfile() {
+    let p = make_project();
+    p.change_file("Cargo.lock", "version = \"not a number\"");
+
+    p.cargo("verify-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-verify-lockfile"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] Cargo.lock failed verification:")
+        .with_stderr_contains("  failed to parse lock file at: [ROOT]/foo/Cargo.lock[..]")
+        .run();
+}
+
+#[cargo_test]
+fn out_of_date() {
+    let p = make_project();
+    p.cargo("generate-lockfile").run();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            bar = "1.0"
+            qux = "1.0"
+        "#,
+    );
+    Package::new("qux", "1.0.0").publish();
+    let lockfile = p.read_lockfile();
+
+    p.cargo("verify-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-verify-lockfile"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] Cargo.lock failed verification:
+  package `qux v1.0.0` is required by the manifests but missing
+  package `baz v1.0.0` is locked but no longer required by the manifests
+",
+        )
+        .run();
+    assert_eq!(p.read_lockfile(), lockfile);
+}
+
+#[cargo_test]
+fn outdated_dependencies() {
+    let p = make_project();
+    p.cargo("generate-lockfile").run();
+    // Same packages, but `foo` no longer depends on `baz` in the lock file.
+    let lockfile = p.read_lockfile().replace(" \"baz\",\n]", "]");
+    p.change_file("Cargo.lock", &lockfile);
+
+    p.cargo("verify-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-verify-lockfile"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] Cargo.lock failed verification:
+  the dependencies of the locked packages don't match the manifests
+",
+        )
+        .run();
+    assert_eq!(p.read_lockfile(), lockfile);
+}
+
+#[cargo_test]
+fn resolve_failure_json() {
+    let p = make_project();
+    p.cargo("generate-lockfile").run();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2015"
+
+            [dependencies]
+            bar = "1.0"
+            baz = "1.0"
+            missing = "1.0"
+        "#,
+    );
+
+    p.cargo("verify-lockfile -Zunstable-options --format json")
+        .masquerade_as_nightly_cargo(&["cargo-verify-lockfile"])
+        .with_status(101)
+        .with_json(
+            r#"
+{
+  "ok": false,
+  "problems": [
+    {
+      "kind": "resolve-failed",
+      "package": null,
+      "message": "{...}"
+    }
+  ],
+  "unverified": "{...}"
+}
+"#,
+        )
+        .with_stderr_contains(
+            "\
+[ERROR] Cargo.lock failed verification:
+  failed to resolve the manifests: no matching package named `missing` found[..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn checksum_mismatch() {
+    let p = make_project();
+    p.cargo("fetch").run();
+    let cache = paths::home().join(".cargo/registry/cache");
+    let dir = fs::read_dir(&cache)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    fs::write(dir.join("bar-1.0.0.crate"), "not a crate").unwrap();
+
+    p.cargo("verify-lockfile -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["cargo-verify-lockfile"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] Cargo.lock failed verification:
+  checksum for `bar v1.0.0` is `[..]` but [..]bar-1.0.0.crate has checksum `[..]`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn yanked_json() {
+    let p = make_project();
+    p.cargo("generate-lockfile").run();
+    registry_path().join("3").rm_rf();
+    Package::new("bar", "1.0.0").yanked(true).publish();
+    Package::new("baz", "1.0.0").publish();
+
+    p.cargo("verify-lockfile -Zunstable-options --format json")
+        .masquerade_as_nightly_cargo(&["cargo-verify-lockfile"])
+        .with_status(101)
+        .with_json(
+            r#"
+{
+  "ok": false,
+  "problems": [
+    {
+      "kind": "yanked",
+      "package": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+      "message": "package `bar v1.0.0` is yanked in registry `crates-io`"
+    }
+  ],
+  "unverified": [
+    {
+      "package": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+      "reason": "it has not been downloaded"
+    },
+    {
+      "package": "registry+https://github.com/rust-lang/crates.io-index#baz@1.0.0",
+      "reason": "it has not been downloaded"
+    }
+  ]
+}
+"#,
+        )
+        .with_stderr_contains(
+            "\
+[ERROR] Cargo.lock failed verification:
+  package `bar v1.0.0` is yanked in registry `crates-io`
+",
+        )
+        .run();
+}
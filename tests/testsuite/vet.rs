@@ -0,0 +1,201 @@
+//! Tests for the `vet.audits` config of `-Zvet`.
+
+use cargo_test_support::paths::CargoPathExt;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, Project};
+
+fn make_project(audits: &str) -> Project {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("bar", "1.0.1").publish();
+    Package::new("baz", "0.2.0").publish();
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "0.2"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [vet]
+                audits = ["supply-chain/audits.toml"]
+            "#,
+        )
+        .file("supply-chain/audits.toml", audits)
+        .build()
+}
+
+#[cargo_test]
+fn ignored_without_flag() {
+    let p = make_project("");
+
+    p.cargo("generate-lockfile").run();
+}
+
+#[cargo_test]
+fn unvetted_versions() {
+    let p = make_project(
+        r#"
+            [[audits.bar]]
+            who = "Alice"
+            criteria = "safe-to-deploy"
+            version = "1.0.0"
+        "#,
+    );
+
+    p.cargo("generate-lockfile -Zvet")
+        .masquerade_as_nightly_cargo(&["vet"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[ERROR] 2 package version(s) are not covered by an audit or exemption in `vet.audits`:
+  registry+[..]#bar@1.0.1
+  registry+[..]#baz@0.2.0
+",
+        )
+        .run();
+    assert!(!p.root().join("Cargo.lock").exists());
+}
+
+#[cargo_test]
+fn delta_audits_and_exemptions() {
+    let p = make_project(
+        r#"
+            [[audits.bar]]
+            criteria = "safe-to-deploy"
+            version = "1.0.0"
+
+            [[audits.bar]]
+            criteria = "safe-to-deploy"
+            delta = "1.0.0 -> 1.0.1"
+
+            [[exemptions.baz]]
+            version = "0.2.0"
+            criteria = "safe-to-run"
+        "#,
+    );
+
+    p.cargo("generate-lockfile -Zvet")
+        .masquerade_as_nightly_cargo(&["vet"])
+        .run();
+}
+
+#[cargo_test]
+fn only_new_versions_are_checked() {
+    let p = make_project("");
+    p.cargo("generate-lockfile").run();
+
+    // The versions already in the lock file are trusted.
+    p.cargo("check -Zvet")
+        .masquerade_as_nightly_cargo(&["vet"])
+        .run();
+
+    Package::new("bar", "1.0.2").publish();
+    p.cargo("update bar -Zvet")
+        .masquerade_as_nightly_cargo(&["vet"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[ERROR] 1 package version(s) are not covered by an audit or exemption in `vet.audits`:
+  registry+[..]#bar@1.0.2
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn violations() {
+    let p = make_project(
+        r#"
+            [[audits.bar]]
+            criteria = "safe-to-deploy"
+            version = "1.0.1"
+
+            [[audits.bar]]
+            criteria = "safe-to-deploy"
+            violation = ">=1.0.1"
+
+            [[exemptions.baz]]
+            version = "0.2.0"
+            criteria = "safe-to-run"
+        "#,
+    );
+
+    p.cargo("generate-lockfile -Zvet")
+        .masquerade_as_nightly_cargo(&["vet"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[ERROR] 1 package version(s) are marked as a violation in `vet.audits`:
+  registry+[..]#bar@1.0.1 (in `supply-chain/audits.toml`)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn imported_audits() {
+    let p = make_project(
+        r#"
+            [[exemptions.baz]]
+            version = "0.2.0"
+            criteria = "safe-to-run"
+        "#,
+    );
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [vet]
+            audits = ["supply-chain/audits.toml", "supply-chain/imports.lock"]
+        "#,
+    );
+    p.change_file(
+        "supply-chain/imports.lock",
+        r#"
+            [[audits.other.audits.bar]]
+            who = "Bob"
+            criteria = "safe-to-deploy"
+            version = "1.0.1"
+        "#,
+    );
+
+    p.cargo("generate-lockfile -Zvet")
+        .masquerade_as_nightly_cargo(&["vet"])
+        .run();
+}
+
+#[cargo_test]
+fn offline_url() {
+    let p = make_project("");
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [vet]
+            audits = ["https://example.com/audits.toml"]
+        "#,
+    );
+    p.cargo("generate-lockfile").run();
+    p.root().join("Cargo.lock").rm_rf();
+
+    p.cargo("generate-lockfile -Zvet --offline")
+        .masquerade_as_nightly_cargo(&["vet"])
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] can't download audits `https://example.com/audits.toml`: you are in the offline mode (--offline)
+",
+        )
+        .run();
+}
@@ -0,0 +1,150 @@
+//! Tests for the `resolver.yanked` config of `-Zyanked-policy`.
+
+use cargo_test_support::paths::CargoPathExt;
+use cargo_test_support::registry::{registry_path, Package};
+use cargo_test_support::{project, Project};
+
+/// Locks `bar v1.0.1`, then yanks it while `bar v1.0.0` and `bar v1.0.2`
+/// stay available.
+fn make_project(policy: &str) -> Project {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("bar", "1.0.1").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [resolver]
+                    yanked = "{policy}"
+                "#
+            ),
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    registry_path().join("3").rm_rf();
+    Package::new("bar", "1.0.0").publish();
+    Package::new("bar", "1.0.1").yanked(true).publish();
+    Package::new("bar", "1.0.2").publish();
+    p
+}
+
+#[cargo_test]
+fn ignored_without_flag() {
+    let p = make_project("error");
+
+    p.cargo("check")
+        .with_stderr_does_not_contain("[..]yanked[..]")
+        .run();
+    assert!(p.read_lockfile().contains("version = \"1.0.1\""));
+}
+
+#[cargo_test]
+fn allow() {
+    let p = make_project("allow");
+
+    p.cargo("check -Zyanked-policy")
+        .masquerade_as_nightly_cargo(&["yanked-policy"])
+        .with_stderr_does_not_contain("[..]yanked[..]")
+        .run();
+    assert!(p.read_lockfile().contains("version = \"1.0.1\""));
+}
+
+#[cargo_test]
+fn warn() {
+    let p = make_project("warn");
+
+    p.cargo("check -Zyanked-policy")
+        .masquerade_as_nightly_cargo(&["yanked-policy"])
+        .with_stderr_contains(
+            "[WARNING] package `bar v1.0.1` in Cargo.lock is yanked in registry \
+             `crates-io`, consider running `cargo update bar@1.0.1`",
+        )
+        .run();
+    assert!(p.read_lockfile().contains("version = \"1.0.1\""));
+}
+
+#[cargo_test]
+fn error() {
+    let p = make_project("error");
+
+    p.cargo("check -Zyanked-policy")
+        .masquerade_as_nightly_cargo(&["yanked-policy"])
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] Cargo.lock uses yanked versions, which `resolver.yanked` is set to reject:
+  package `bar v1.0.1` in Cargo.lock is yanked in registry `crates-io`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn auto_bump_compatible() {
+    let p = make_project("auto-bump-compatible");
+
+    p.cargo("check -Zyanked-policy")
+        .masquerade_as_nightly_cargo(&["yanked-policy"])
+        .with_stderr_contains(
+            "[NOTE] updating `bar v1.0.1` to v1.0.2, as it is yanked in registry `crates-io`",
+        )
+        .run();
+    let lock = p.read_lockfile();
+    assert!(lock.contains("version = \"1.0.2\""));
+    assert!(!lock.contains("version = \"1.0.1\""));
+}
+
+#[cargo_test]
+fn auto_bump_compatible_without_candidate() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [resolver]
+                yanked = "auto-bump-compatible"
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    registry_path().join("3").rm_rf();
+    Package::new("bar", "1.0.0").yanked(true).publish();
+    Package::new("bar", "2.0.0").publish();
+
+    p.cargo("check -Zyanked-policy")
+        .masquerade_as_nightly_cargo(&["yanked-policy"])
+        .with_stderr_contains(
+            "[WARNING] package `bar v1.0.0` in Cargo.lock is yanked in registry \
+             `crates-io`, but no compatible version is available to replace it",
+        )
+        .run();
+    assert!(p.read_lockfile().contains("version = \"1.0.0\""));
+}